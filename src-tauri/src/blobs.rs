@@ -0,0 +1,238 @@
+use crate::{db, relations};
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Largest file accepted as an attachment.
+const MAX_BLOB_BYTES: u64 = 100 * 1024 * 1024;
+/// Unreferenced blobs younger than this survive garbage collection, so a
+/// file stored just before its reference is saved isn't lost.
+const GC_GRACE_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// What an attachment can belong to, by table name.
+const OWNERS: &[&str] = &[relations::THREADS, relations::BRAIN_DUMPS, relations::KANBAN_ITEMS];
+
+/// A file attached to a thread, brain dump or kanban card.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub hash: String,
+    pub name: String,
+    pub mime: String,
+    pub size: u64,
+    pub path: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcReport {
+    pub removed: u32,
+    pub bytes_freed: u64,
+    /// Blobs whose file had gone missing; their rows are dropped too.
+    pub missing: u32,
+}
+
+/// `~/.openclaw/chat/blobs`; each file sits at `<first two hex chars>/<sha256>`.
+pub fn blobs_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".openclaw")
+        .join("chat")
+        .join("blobs")
+}
+
+pub fn blob_path(hash: &str) -> Result<PathBuf> {
+    if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(anyhow!("Invalid blob hash: {:?}", hash));
+    }
+    Ok(blobs_dir().join(&hash[..2]).join(hash))
+}
+
+/// Copy `source` into the store, unless the same content is already there,
+/// and return its hash.
+pub fn store_file(conn: &Connection, source: &Path) -> Result<String> {
+    let size = std::fs::metadata(source)
+        .with_context(|| format!("Failed to read {}", source.display()))?
+        .len();
+    if size > MAX_BLOB_BYTES {
+        return Err(anyhow!("{} is larger than {} MB", source.display(), MAX_BLOB_BYTES / 1024 / 1024));
+    }
+    let content = std::fs::read(source).with_context(|| format!("Failed to read {}", source.display()))?;
+    store(conn, &content, mime_type(source))
+}
+
+/// Store `content` and return its hash. Identical content is kept once.
+pub fn store(conn: &Connection, content: &[u8], mime: &str) -> Result<String> {
+    let hash = format!("{:x}", Sha256::digest(content));
+    let path = blob_path(&hash)?;
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &path)?;
+    }
+    conn.execute(
+        "INSERT OR IGNORE INTO blobs (hash, size, mime, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![hash, content.len() as i64, mime, chrono::Utc::now().timestamp_millis()],
+    )?;
+    Ok(hash)
+}
+
+/// Attach the stored blob `hash` to an owner under `name`. Attaching the
+/// same content twice keeps one reference with the newer name.
+pub fn add_ref(conn: &Connection, hash: &str, owner_type: &str, owner_id: &str, name: &str) -> Result<()> {
+    check_owner(owner_type)?;
+    let owner: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM {} WHERE id = ?1", db::quote_ident(owner_type)),
+        params![owner_id],
+        |row| row.get(0),
+    )?;
+    if owner == 0 {
+        return Err(anyhow!("No {} row with id {}", owner_type, owner_id));
+    }
+    let known: i64 = conn.query_row("SELECT COUNT(*) FROM blobs WHERE hash = ?1", params![hash], |row| row.get(0))?;
+    if known == 0 {
+        return Err(anyhow!("Blob {} not found", hash));
+    }
+    conn.execute(
+        "INSERT INTO blob_refs (hash, owner_type, owner_id, name, created_at) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(owner_type, owner_id, hash) DO UPDATE SET name = excluded.name",
+        params![hash, owner_type, owner_id, name, chrono::Utc::now().timestamp_millis()],
+    )?;
+    Ok(())
+}
+
+/// Detach a blob from an owner. The file stays until garbage collection
+/// finds nothing else using it.
+pub fn remove_ref(conn: &Connection, hash: &str, owner_type: &str, owner_id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM blob_refs WHERE hash = ?1 AND owner_type = ?2 AND owner_id = ?3",
+        params![hash, owner_type, owner_id],
+    )?;
+    Ok(())
+}
+
+pub fn list(conn: &Connection, owner_type: &str, owner_id: &str) -> Result<Vec<Attachment>> {
+    check_owner(owner_type)?;
+    let mut stmt = conn.prepare(
+        "SELECT r.hash, r.name, b.mime, b.size, r.created_at
+         FROM blob_refs r JOIN blobs b ON b.hash = r.hash
+         WHERE r.owner_type = ?1 AND r.owner_id = ?2
+         ORDER BY r.created_at",
+    )?;
+    let rows = stmt.query_map(params![owner_type, owner_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, i64>(4)?,
+        ))
+    })?;
+    let mut attachments = Vec::new();
+    for row in rows {
+        let (hash, name, mime, size, created_at) = row?;
+        attachments.push(Attachment {
+            path: blob_path(&hash)?.to_string_lossy().to_string(),
+            hash,
+            name,
+            mime,
+            size: size as u64,
+            created_at,
+        });
+    }
+    Ok(attachments)
+}
+
+/// Delete blobs nothing refers to any more, and files in the store the
+/// database doesn't know about, once they're older than `GC_GRACE_MS`.
+pub fn gc(conn: &Connection) -> Result<GcReport> {
+    let cutoff = chrono::Utc::now().timestamp_millis() - GC_GRACE_MS;
+    let mut report = GcReport::default();
+    let mut stmt = conn.prepare(
+        "SELECT hash, size FROM blobs
+         WHERE created_at < ?1 AND hash NOT IN (SELECT hash FROM blob_refs)",
+    )?;
+    let unused = stmt
+        .query_map(params![cutoff], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    for (hash, size) in unused {
+        let path = blob_path(&hash)?;
+        if path.exists() {
+            std::fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+            report.removed += 1;
+            report.bytes_freed += size as u64;
+        } else {
+            report.missing += 1;
+        }
+        conn.execute("DELETE FROM blobs WHERE hash = ?1", params![hash])?;
+    }
+
+    // Leftovers from a store whose row was never written, or was restored away
+    let Ok(shards) = std::fs::read_dir(blobs_dir()) else {
+        return Ok(report);
+    };
+    for file in shards.flatten().filter_map(|shard| std::fs::read_dir(shard.path()).ok()).flatten().flatten() {
+        let path = file.path();
+        let Some(hash) = path.file_name().and_then(|n| n.to_str()).map(str::to_string) else {
+            continue;
+        };
+        let known: i64 = conn.query_row("SELECT COUNT(*) FROM blobs WHERE hash = ?1", params![hash], |row| row.get(0))?;
+        let Ok(meta) = file.metadata() else {
+            continue;
+        };
+        let old = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .is_some_and(|d| (d.as_millis() as i64) < cutoff);
+        if known == 0 && old {
+            std::fs::remove_file(&path)?;
+            report.removed += 1;
+            report.bytes_freed += meta.len();
+        }
+    }
+    Ok(report)
+}
+
+fn check_owner(owner_type: &str) -> Result<()> {
+    if OWNERS.contains(&owner_type) {
+        Ok(())
+    } else {
+        Err(anyhow!("Attachments can't belong to {:?}", owner_type))
+    }
+}
+
+/// Best guess from the extension; the UI only needs it to pick a preview.
+fn mime_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "json" => "application/json",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "zip" => "application/zip",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{db, openclaw, settings};
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Local, TimeZone, Timelike, Utc};
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+
+/// Setting: "reminders" to mirror due cards into Apple Reminders, "ics" to
+/// write them to a calendar file, "" for neither.
+pub const EXPORT_SETTING: &str = "calendar_export";
+/// Setting: where the .ics file goes; `~` is expanded.
+pub const ICS_PATH_SETTING: &str = "calendar_ics_path";
+/// Setting: the Reminders list cards go into; created when missing.
+pub const REMINDERS_LIST_SETTING: &str = "calendar_reminders_list";
+
+pub const DEFAULT_REMINDERS_LIST: &str = "OpenClaw";
+
+/// How long a card's calendar event lasts.
+const EVENT_MINUTES: u32 = 30;
+
+/// Mirror cards with a due date according to `calendar_export`. Cheap when
+/// nothing changed: the .ics file is only rewritten when its content
+/// differs, and only changed cards are sent to Reminders.
+pub async fn export_job() -> Result<String> {
+    let mode = db::call(|conn| settings::get_text(conn, EXPORT_SETTING)).await?;
+    match mode.as_deref() {
+        Some("ics") => tauri::async_runtime::spawn_blocking(export_ics).await?,
+        Some("reminders") => export_reminders().await,
+        _ => Ok("Calendar export is off".to_string()),
+    }
+}
+
+// .ics feed
+
+/// `~/.openclaw/chat/due-cards.ics` unless `calendar_ics_path` says
+/// otherwise.
+fn ics_path(conn: &Connection) -> Result<PathBuf> {
+    Ok(match settings::get_text(conn, ICS_PATH_SETTING)? {
+        Some(path) => openclaw::expand_home(path.trim()),
+        None => dirs::home_dir()
+            .unwrap_or_default()
+            .join(".openclaw")
+            .join("chat")
+            .join("due-cards.ics"),
+    })
+}
+
+fn export_ics() -> Result<String> {
+    let conn = db::background()?;
+    let path = ics_path(&conn)?;
+    let cards: Vec<_> = db::list_due_kanban_items(&conn)?
+        .into_iter()
+        .filter(|c| c.completed_at.is_none())
+        .collect();
+    drop(conn);
+    let content = ics_calendar(&cards);
+    if std::fs::read_to_string(&path).is_ok_and(|old| old == content) {
+        return Ok("Unchanged".to_string());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Calendar apps polling the file never see half of it
+    let tmp = path.with_extension("ics.tmp");
+    std::fs::write(&tmp, &content)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(format!("Wrote {} cards to {}", cards.len(), path.display()))
+}
+
+/// One VEVENT per card, starting at its due time. Stamps come from the
+/// card's `updated_at` so the file only changes when a card does.
+fn ics_calendar(cards: &[db::KanbanItem]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//OpenClaw Chat//Due cards//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:OpenClaw due cards".to_string(),
+    ];
+    for card in cards {
+        let Some(due) = card.due_at else { continue };
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@openclaw-chat", card.id));
+        lines.push(format!("DTSTAMP:{}", ics_time(card.updated_at)));
+        lines.push(format!("DTSTART:{}", ics_time(due)));
+        lines.push(format!("DURATION:PT{}M", EVENT_MINUTES));
+        lines.push(format!("SUMMARY:{}", ics_escape(&card.title)));
+        if let Some(description) = card.description.as_deref().filter(|d| !d.trim().is_empty()) {
+            lines.push(format!("DESCRIPTION:{}", ics_escape(description)));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|l| ics_fold(l)).collect::<Vec<_>>().join("")
+}
+
+fn ics_time(ms: i64) -> String {
+    Utc.timestamp_millis_opt(ms)
+        .single()
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+fn ics_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Lines end in CRLF and are folded at 75 bytes, never inside a character.
+fn ics_fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+// Apple Reminders
+
+/// Create or update a reminder; prints its id. Dates are passed as local
+/// components because AppleScript date strings depend on the locale.
+const UPSERT_SCRIPT: &str = r#"on run argv
+    set {listName, reminderId, reminderName, reminderBody, y, m, d, t, isDone} to argv
+    set dueDate to current date
+    set day of dueDate to 1
+    set year of dueDate to (y as integer)
+    set month of dueDate to (m as integer)
+    set day of dueDate to (d as integer)
+    set time of dueDate to (t as integer)
+    tell application "Reminders"
+        if not (exists list listName) then make new list with properties {name:listName}
+        set theList to list listName
+        set r to missing value
+        if reminderId is not "" then
+            try
+                set r to first reminder of theList whose id is reminderId
+            end try
+        end if
+        if r is missing value then set r to make new reminder at end of theList
+        set name of r to reminderName
+        set body of r to reminderBody
+        set due date of r to dueDate
+        set completed of r to (isDone is "true")
+        return id of r
+    end tell
+end run"#;
+
+const DELETE_SCRIPT: &str = r#"on run argv
+    set {listName, reminderId} to argv
+    tell application "Reminders"
+        try
+            delete (first reminder of list listName whose id is reminderId)
+        end try
+    end tell
+end run"#;
+
+/// Sync Reminders with the due cards: new and changed cards are written,
+/// done ones ticked off, and reminders of cards that lost their due date
+/// or were deleted are removed. One way only; edits made in Reminders are
+/// overwritten the next time the card changes.
+async fn export_reminders() -> Result<String> {
+    if !cfg!(target_os = "macos") {
+        return Err(anyhow!("Apple Reminders is only available on macOS"));
+    }
+    let (list, cards, mut links) = db::call(|conn| {
+        let list = settings::get_text(conn, REMINDERS_LIST_SETTING)?
+            .unwrap_or_else(|| DEFAULT_REMINDERS_LIST.to_string());
+        Ok((list, db::list_due_kanban_items(conn)?, reminder_links(conn)?))
+    })
+    .await?;
+    let (mut written, mut removed, mut failed) = (0, 0, 0);
+    for card in &cards {
+        let link = links.remove(&card.id);
+        if link.as_ref().is_some_and(|(_, updated_at)| *updated_at == card.updated_at) {
+            continue;
+        }
+        let reminder_id = link.map(|(id, _)| id).unwrap_or_default();
+        match upsert_reminder(&list, &reminder_id, card).await {
+            Ok(id) => {
+                let (item_id, updated_at) = (card.id.clone(), card.updated_at);
+                db::call(move |conn| {
+                    conn.execute(
+                        "INSERT OR REPLACE INTO reminder_links (item_id, reminder_id, item_updated_at, synced_at)
+                         VALUES (?1, ?2, ?3, ?4)",
+                        params![item_id, id, updated_at, Utc::now().timestamp_millis()],
+                    )?;
+                    Ok(())
+                })
+                .await?;
+                written += 1;
+            }
+            Err(e) => {
+                eprintln!("[calendar] Couldn't write a reminder for card {}: {}", card.id, e);
+                failed += 1;
+            }
+        }
+    }
+    // What's left are cards that are gone or no longer have a due date
+    for (item_id, (reminder_id, _)) in links {
+        match osascript(DELETE_SCRIPT, &[&list, &reminder_id]).await {
+            Ok(_) => {
+                db::call(move |conn| {
+                    conn.execute("DELETE FROM reminder_links WHERE item_id = ?1", params![item_id])?;
+                    Ok(())
+                })
+                .await?;
+                removed += 1;
+            }
+            Err(e) => {
+                eprintln!("[calendar] Couldn't remove the reminder for card {}: {}", item_id, e);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 && written == 0 && removed == 0 {
+        return Err(anyhow!("Couldn't update any of {} reminders", failed));
+    }
+    Ok(format!("{} reminders written, {} removed, {} failed", written, removed, failed))
+}
+
+/// Reminder id and the card's `updated_at` when it was written, by card id.
+fn reminder_links(conn: &Connection) -> Result<HashMap<String, (String, i64)>> {
+    let mut stmt = conn.prepare("SELECT item_id, reminder_id, item_updated_at FROM reminder_links")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+async fn upsert_reminder(list: &str, reminder_id: &str, card: &db::KanbanItem) -> Result<String> {
+    let due = card
+        .due_at
+        .and_then(|ms| Local.timestamp_millis_opt(ms).single())
+        .ok_or_else(|| anyhow!("Card has no due date"))?;
+    let id = osascript(
+        UPSERT_SCRIPT,
+        &[
+            list,
+            reminder_id,
+            &card.title,
+            card.description.as_deref().unwrap_or_default(),
+            &due.year().to_string(),
+            &due.month().to_string(),
+            &due.day().to_string(),
+            &due.num_seconds_from_midnight().to_string(),
+            if card.completed_at.is_some() { "true" } else { "false" },
+        ],
+    )
+    .await?;
+    if id.is_empty() {
+        return Err(anyhow!("Reminders didn't return an id"));
+    }
+    Ok(id)
+}
+
+/// Run an AppleScript with `args` as its `argv`. The script goes over
+/// stdin, so card text is never parsed as AppleScript.
+async fn osascript(script: &str, args: &[&str]) -> Result<String> {
+    let mut child = tokio::process::Command::new("/usr/bin/osascript")
+        .arg("-")
+        .args(args)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Couldn't run osascript: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(script.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // -1743: the user hasn't allowed the app to control Reminders
+        if stderr.contains("-1743") {
+            return Err(anyhow!(
+                "Allow OpenClaw Chat to control Reminders in System Settings > Privacy & Security > Automation"
+            ));
+        }
+        return Err(anyhow!("{}", stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
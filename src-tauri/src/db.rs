@@ -1,7 +1,11 @@
-use anyhow::Result;
+use crate::relations;
+use anyhow::{anyhow, Result};
+use rusqlite::types::Value;
 use rusqlite::{Connection, params};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::Duration;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Project {
@@ -10,6 +14,13 @@ pub struct Project {
     pub description: Option<String>,
     pub color: Option<String>,
     pub agent_id: String,
+    pub status: String, // 'active' | 'paused' | 'done' | ...
+    pub obsidian_source: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub priority: i32, // same 0–4 scale as kanban items
+    pub due_at: Option<i64>,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -24,6 +35,24 @@ pub struct Thread {
     pub created_at: i64,
     pub updated_at: i64,
     pub last_message_at: Option<i64>,
+    /// "local" or "remote"; `None` follows the global remote mode.
+    pub execution_target: Option<String>,
+    /// Default model for messages in this thread; `None` uses the agent's.
+    pub model: Option<String>,
+    /// No OS notifications for replies in this thread.
+    #[serde(default)]
+    pub muted: bool,
+}
+
+impl Thread {
+    /// Whether this thread runs on the remote box, given the global remote mode.
+    pub fn runs_remote(&self, remote_default: bool) -> bool {
+        match self.execution_target.as_deref() {
+            Some("remote") => true,
+            Some("local") => false,
+            _ => remote_default,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -36,8 +65,24 @@ pub struct BrainDump {
     pub created_at: i64,
     pub updated_at: i64,
     pub followed_up_at: Option<i64>,
+    /// Hours between follow-ups; `None` follows up once.
+    #[serde(default)]
+    pub follow_up_interval_hours: Option<i64>,
+    /// Unanswered follow-ups before the dump stops being proactive; `None`
+    /// uses `DEFAULT_MAX_FOLLOW_UPS`.
+    #[serde(default)]
+    pub max_follow_ups: Option<i32>,
+    /// Follow-ups sent since the last reply.
+    #[serde(default)]
+    pub follow_up_attempts: i32,
+    /// Thread the follow-ups go to, created with the first one.
+    #[serde(default)]
+    pub follow_up_thread_id: Option<String>,
 }
 
+/// Unanswered follow-ups a dump gets when it sets no limit of its own.
+pub const DEFAULT_MAX_FOLLOW_UPS: i32 = 3;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct KanbanItem {
     pub id: String,
@@ -49,8 +94,18 @@ pub struct KanbanItem {
     pub column: String, // 'backlog' | 'this_week' | 'in_progress' | 'done'
     pub position: i32,
     pub status: String, // 'active' | 'archived'
+    pub recurrence: Option<String>, // 'daily' | 'weekly' | 'monthly'
+    pub completed_at: Option<i64>,
+    pub parent_id: Option<String>,
+    pub due_at: Option<i64>,
+    pub agent_id: Option<String>,
+    pub thread_id: Option<String>,
+    pub priority: i32, // 0 none · 1 low · 2 medium · 3 high · 4 urgent
     pub created_at: i64,
     pub updated_at: i64,
+    /// Ids of cards this one waits on that aren't done yet (computed, not stored).
+    #[serde(default)]
+    pub blocked_by: Vec<String>,
 }
 
 pub fn db_path() -> PathBuf {
@@ -65,10 +120,285 @@ pub fn open_db() -> Result<Connection> {
     }
     let conn = Connection::open(&path)?;
     conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+    // Wait out another connection's write or checkpoint instead of failing with SQLITE_BUSY
+    conn.busy_timeout(BUSY_TIMEOUT)?;
     Ok(conn)
 }
 
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Connections kept open for commands and background jobs. WAL lets the
+/// readers run side by side; writers queue on the busy timeout.
+const POOL_SIZE: usize = 4;
+
+/// Handle to a small pool of connections to the app database. Cheap to clone.
+/// Async code goes through `call`, which runs the work on the blocking pool
+/// so a slow query or a wait for a free connection never stalls the runtime;
+/// sync code (watcher threads, setup) checks a connection out with `get`.
+#[derive(Clone)]
+pub struct Db {
+    pool: Arc<Pool>,
+}
+
+struct Pool {
+    idle: Mutex<Vec<Connection>>,
+    returned: Condvar,
+}
+
+/// A connection checked out of the pool; goes back when dropped.
+pub struct PooledConn {
+    conn: Option<Connection>,
+    pool: Arc<Pool>,
+}
+
+impl std::ops::Deref for PooledConn {
+    type Target = Connection;
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection is only taken on drop")
+    }
+}
+
+impl Drop for PooledConn {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.idle.lock().unwrap_or_else(|e| e.into_inner()).push(conn);
+            self.pool.returned.notify_one();
+        }
+    }
+}
+
+impl Db {
+    /// Open the pool. Call after `init_db` so nothing sees the schema
+    /// mid-migration.
+    pub fn open() -> Result<Self> {
+        let conns = (0..POOL_SIZE).map(|_| open_db()).collect::<Result<Vec<_>>>()?;
+        Ok(Db {
+            pool: Arc::new(Pool {
+                idle: Mutex::new(conns),
+                returned: Condvar::new(),
+            }),
+        })
+    }
+
+    /// Check a connection out, waiting for one to come back if all are in
+    /// use. Blocks, so don't call it on the async runtime; hold the
+    /// connection briefly and never across an `.await`.
+    pub fn get(&self) -> Result<PooledConn> {
+        let mut idle = self.pool.idle.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if let Some(conn) = idle.pop() {
+                return Ok(PooledConn { conn: Some(conn), pool: self.pool.clone() });
+            }
+            let (guard, timeout) = self
+                .pool
+                .returned
+                .wait_timeout(idle, BUSY_TIMEOUT)
+                .unwrap_or_else(|e| e.into_inner());
+            if timeout.timed_out() && guard.is_empty() {
+                return Err(anyhow!("Timed out waiting for a database connection"));
+            }
+            idle = guard;
+        }
+    }
+
+    /// Run `f` with a pooled connection on the blocking thread pool.
+    pub async fn call<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&Connection) -> Result<T, E> + Send + 'static,
+        T: Send + 'static,
+        E: From<anyhow::Error> + Send + 'static,
+    {
+        let db = self.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let conn = db.get().map_err(E::from)?;
+            f(&conn)
+        })
+        .await
+        .map_err(|e| E::from(anyhow!("Database task failed: {}", e)))?
+    }
+}
+
+static DB: OnceLock<Db> = OnceLock::new();
+
+/// Open the app-wide pool; the returned handle goes into the app state.
+pub fn open_pool() -> Result<Db> {
+    let db = Db::open()?;
+    DB.set(db.clone()).map_err(|_| anyhow!("Database pool already open"))?;
+    Ok(db)
+}
+
+/// The app-wide pool, for code without the app state at hand.
+pub fn pool() -> Result<&'static Db> {
+    DB.get().ok_or_else(|| anyhow!("Database pool isn't open yet"))
+}
+
+/// A pooled connection for sync background work (watcher threads,
+/// `spawn_blocking`). Blocks, so async code uses `call` instead.
+pub fn background() -> Result<PooledConn> {
+    pool()?.get()
+}
+
+/// `Db::call` on the app-wide pool, for async jobs without the app state at
+/// hand.
+pub async fn call<T, F>(f: F) -> Result<T>
+where
+    F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    pool()?.call(f).await
+}
+
+/// A schema change, applied once and in order. Each runs in its own
+/// transaction and is recorded in `schema_version`.
+struct Migration {
+    version: u32,
+    name: &'static str,
+    up: fn(&Connection) -> Result<()>,
+}
+
+/// Append new migrations here; never edit or reorder one that has shipped.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "baseline",
+        up: baseline,
+    },
+    Migration {
+        version: 2,
+        name: "search_index",
+        up: search_index,
+    },
+    Migration {
+        version: 3,
+        name: "audit_log",
+        up: audit_log,
+    },
+    Migration {
+        version: 4,
+        name: "maintenance_runs",
+        up: maintenance_runs,
+    },
+    Migration {
+        version: 5,
+        name: "cascade_marks",
+        up: cascade_marks,
+    },
+    Migration {
+        version: 6,
+        name: "blobs",
+        up: blobs,
+    },
+    Migration {
+        version: 7,
+        name: "sync",
+        up: sync,
+    },
+    Migration {
+        version: 8,
+        name: "github",
+        up: github,
+    },
+    Migration {
+        version: 9,
+        name: "webhooks",
+        up: webhooks,
+    },
+    Migration {
+        version: 10,
+        name: "reminder_links",
+        up: reminder_links,
+    },
+    Migration {
+        version: 11,
+        name: "overdue_notices",
+        up: overdue_notices,
+    },
+    Migration {
+        version: 12,
+        name: "missing_sessions",
+        up: missing_sessions,
+    },
+    Migration {
+        version: 13,
+        name: "dismissed_obsidian_tasks",
+        up: dismissed_obsidian_tasks,
+    },
+    Migration {
+        version: 14,
+        name: "deferred_deliver_at",
+        up: deferred_deliver_at,
+    },
+];
+
+/// Check the database file and bring its schema up to date.
 pub fn init_db(conn: &Connection) -> Result<()> {
+    check_integrity(conn)?;
+    migrate(conn)
+}
+
+/// Fail early on a damaged database rather than writing more into it.
+fn check_integrity(conn: &Connection) -> Result<()> {
+    let problems = integrity_problems(conn, false)?;
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "Database {} failed its integrity check: {}",
+        db_path().display(),
+        problems.join("; ")
+    ))
+}
+
+/// What `PRAGMA quick_check` (or the slower, more thorough
+/// `integrity_check` with `full`) finds wrong; empty when all is well.
+pub fn integrity_problems(conn: &Connection, full: bool) -> Result<Vec<String>> {
+    let pragma = if full { "integrity_check" } else { "quick_check" };
+    let mut stmt = conn.prepare(&format!("PRAGMA {}", pragma))?;
+    let problems = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(problems.into_iter().filter(|p| p != "ok").collect())
+}
+
+/// Highest migration applied, 0 for a new database.
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))?)
+}
+
+/// Bring `conn` up to the latest schema. Refuses databases from a newer app.
+pub fn migrate(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        );",
+    )?;
+    let current = schema_version(conn)?;
+    let latest = MIGRATIONS.last().map_or(0, |m| m.version);
+    if current > latest {
+        return Err(anyhow!(
+            "Database schema version {} is newer than this app supports ({}); update the app",
+            current,
+            latest
+        ));
+    }
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn.unchecked_transaction()?;
+        (migration.up)(&tx)
+            .map_err(|e| anyhow!("Migration {} ({}) failed: {}", migration.version, migration.name, e))?;
+        tx.execute(
+            "INSERT INTO schema_version (version, name, applied_at) VALUES (?1, ?2, ?3)",
+            params![migration.version, migration.name, chrono::Utc::now().timestamp_millis()],
+        )?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// The schema as it stood before versioned migrations. Every step checks
+/// for itself, so databases from any earlier release end up the same.
+fn baseline(conn: &Connection) -> Result<()> {
     conn.execute_batch(
         "
         CREATE TABLE IF NOT EXISTS projects (
@@ -110,7 +440,7 @@ pub fn init_db(conn: &Connection) -> Result<()> {
             source_id TEXT,
             title TEXT NOT NULL,
             description TEXT,
-            column TEXT NOT NULL DEFAULT 'backlog',
+            \"column\" TEXT NOT NULL DEFAULT 'backlog',
             position INTEGER NOT NULL DEFAULT 0,
             status TEXT NOT NULL DEFAULT 'active',
             created_at INTEGER NOT NULL,
@@ -122,17 +452,12 @@ pub fn init_db(conn: &Connection) -> Result<()> {
         CREATE INDEX IF NOT EXISTS idx_brain_dumps_status ON brain_dumps(status);
         CREATE INDEX IF NOT EXISTS idx_brain_dumps_proactive ON brain_dumps(proactive);
         CREATE INDEX IF NOT EXISTS idx_kanban_project ON kanban_items(project_id);
-        CREATE INDEX IF NOT EXISTS idx_kanban_column ON kanban_items(column);
+        CREATE INDEX IF NOT EXISTS idx_kanban_column ON kanban_items(\"column\");
         ",
     )?;
 
     // Migration: add title_updated_at column
-    let has_col: bool = conn
-        .prepare("SELECT sql FROM sqlite_master WHERE type='table' AND name='threads'")?
-        .query_row([], |row| row.get::<_, String>(0))
-        .map(|sql| sql.contains("title_updated_at"))
-        .unwrap_or(false);
-    if !has_col {
+    if !has_column(conn, "threads", "title_updated_at")? {
         conn.execute_batch("ALTER TABLE threads ADD COLUMN title_updated_at INTEGER")?;
     }
 
@@ -145,12 +470,7 @@ pub fn init_db(conn: &Connection) -> Result<()> {
     )?;
 
     // Migration: add obsidian_source column to projects
-    let has_obsidian: bool = conn
-        .prepare("SELECT sql FROM sqlite_master WHERE type='table' AND name='projects'")?
-        .query_row([], |row| row.get::<_, String>(0))
-        .map(|sql| sql.contains("obsidian_source"))
-        .unwrap_or(false);
-    if !has_obsidian {
+    if !has_column(conn, "projects", "obsidian_source")? {
         conn.execute_batch("ALTER TABLE projects ADD COLUMN obsidian_source TEXT")?;
         conn.execute_batch(
             "CREATE UNIQUE INDEX IF NOT EXISTS idx_projects_obsidian_source
@@ -158,114 +478,992 @@ pub fn init_db(conn: &Connection) -> Result<()> {
         )?;
     }
 
+    // Migration: recurrence rule + completion timestamp on kanban items
+    if !has_column(conn, "kanban_items", "recurrence")? {
+        conn.execute_batch(
+            "ALTER TABLE kanban_items ADD COLUMN recurrence TEXT;
+             ALTER TABLE kanban_items ADD COLUMN completed_at INTEGER;",
+        )?;
+    }
+
+    // Migration: kanban activity log (column transitions), seeded from current
+    // placement. A card's last update is the nearest known time it reached its
+    // column; its creation time would count all its time on the board there.
+    if !has_table(conn, "kanban_activity")? {
+        conn.execute_batch(
+            "CREATE TABLE kanban_activity (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                item_id TEXT NOT NULL REFERENCES kanban_items(id) ON DELETE CASCADE,
+                from_column TEXT,
+                to_column TEXT NOT NULL,
+                at INTEGER NOT NULL
+            );
+            CREATE INDEX idx_kanban_activity_item ON kanban_activity(item_id, at);
+            INSERT INTO kanban_activity (item_id, from_column, to_column, at)
+                SELECT id, NULL, \"column\", updated_at FROM kanban_items;",
+        )?;
+    }
+
+    // Migration: subtask parent on kanban items
+    if !has_column(conn, "kanban_items", "parent_id")? {
+        conn.execute_batch(
+            "ALTER TABLE kanban_items ADD COLUMN parent_id TEXT REFERENCES kanban_items(id) ON DELETE SET NULL;
+             CREATE INDEX IF NOT EXISTS idx_kanban_parent ON kanban_items(parent_id);",
+        )?;
+    }
+
+    // Migration: due date on kanban items
+    if !has_column(conn, "kanban_items", "due_at")? {
+        conn.execute_batch("ALTER TABLE kanban_items ADD COLUMN due_at INTEGER")?;
+    }
+
+    // Migration: when a card was last nudged about its due date
+    if !has_column(conn, "kanban_items", "nudged_at")? {
+        conn.execute_batch("ALTER TABLE kanban_items ADD COLUMN nudged_at INTEGER")?;
+    }
+
+    // Migration: agent assignment + linked work thread on kanban items
+    if !has_column(conn, "kanban_items", "agent_id")? {
+        conn.execute_batch(
+            "ALTER TABLE kanban_items ADD COLUMN agent_id TEXT;
+             ALTER TABLE kanban_items ADD COLUMN thread_id TEXT REFERENCES threads(id) ON DELETE SET NULL;",
+        )?;
+    }
+
+    // Migration: priority on kanban items
+    if !has_column(conn, "kanban_items", "priority")? {
+        conn.execute_batch("ALTER TABLE kanban_items ADD COLUMN priority INTEGER NOT NULL DEFAULT 0")?;
+    }
+
+    // Migration: blocked-by relations between kanban items
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS kanban_dependencies (
+            item_id TEXT NOT NULL REFERENCES kanban_items(id) ON DELETE CASCADE,
+            blocked_by_id TEXT NOT NULL REFERENCES kanban_items(id) ON DELETE CASCADE,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (item_id, blocked_by_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_kanban_deps_blocker ON kanban_dependencies(blocked_by_id);",
+    )?;
+
+    // Migration: project status + last-seen Obsidian note mtime (for write-back conflicts)
+    if !has_column(conn, "projects", "status")? {
+        conn.execute_batch(
+            "ALTER TABLE projects ADD COLUMN status TEXT NOT NULL DEFAULT 'active';
+             ALTER TABLE projects ADD COLUMN obsidian_mtime INTEGER;",
+        )?;
+    }
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS entity_links (
+            source_type TEXT NOT NULL,
+            source_id TEXT NOT NULL,
+            target_type TEXT NOT NULL,
+            target_id TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (source_type, source_id, target_type, target_id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_entity_links_target ON entity_links(target_type, target_id);",
+    )?;
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sync_conflicts (
+            id TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            field TEXT NOT NULL,
+            local_value TEXT,
+            vault_value TEXT,
+            obsidian_source TEXT,
+            created_at INTEGER NOT NULL,
+            resolved_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_sync_conflicts_project ON sync_conflicts(project_id);",
+    )?;
+
+    // Migration: note values as of the last sync, the base for conflict detection
+    if !has_column(conn, "projects", "synced_name")? {
+        conn.execute_batch(
+            "ALTER TABLE projects ADD COLUMN synced_name TEXT;
+             ALTER TABLE projects ADD COLUMN synced_description TEXT;",
+        )?;
+    }
+
+    if !has_table(conn, "ssh_events")? {
+        conn.execute_batch(
+            "CREATE TABLE ssh_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                host TEXT NOT NULL,
+                detail TEXT,
+                duration_ms INTEGER,
+                ok INTEGER NOT NULL DEFAULT 1,
+                at INTEGER NOT NULL
+            );
+            CREATE INDEX idx_ssh_events_at ON ssh_events(at);",
+        )?;
+    }
+
+    // Migration: per-thread local/remote override
+    if !has_column(conn, "threads", "execution_target")? {
+        conn.execute_batch("ALTER TABLE threads ADD COLUMN execution_target TEXT;")?;
+    }
+
+    // Migration: per-thread model default
+    if !has_column(conn, "threads", "model")? {
+        conn.execute_batch("ALTER TABLE threads ADD COLUMN model TEXT;")?;
+    }
+
+    // Migration: per-dump follow-up cadence
+    if !has_column(conn, "brain_dumps", "follow_up_interval_hours")? {
+        conn.execute_batch(
+            "ALTER TABLE brain_dumps ADD COLUMN follow_up_interval_hours INTEGER;
+             ALTER TABLE brain_dumps ADD COLUMN max_follow_ups INTEGER;
+             ALTER TABLE brain_dumps ADD COLUMN follow_up_attempts INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE brain_dumps ADD COLUMN follow_up_thread_id TEXT;",
+        )?;
+    }
+
+    // Migration: per-thread notification mute
+    if !has_column(conn, "threads", "muted")? {
+        conn.execute_batch("ALTER TABLE threads ADD COLUMN muted INTEGER NOT NULL DEFAULT 0;")?;
+    }
+    // Migration: when the stale-thread pass last suggested picking a thread back up
+    if !has_column(conn, "threads", "resurfaced_at")? {
+        conn.execute_batch("ALTER TABLE threads ADD COLUMN resurfaced_at INTEGER;")?;
+    }
+
+    // Messages sent while the remote host was unreachable, delivered on reconnect
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS remote_outbox (
+            id TEXT PRIMARY KEY,
+            thread_id TEXT NOT NULL,
+            agent_id TEXT NOT NULL,
+            session_id TEXT NOT NULL,
+            message TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );",
+    )?;
+    // Migration: delivery attempts on queued remote messages
+    if !has_column(conn, "remote_outbox", "attempts")? {
+        conn.execute_batch(
+            "ALTER TABLE remote_outbox ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE remote_outbox ADD COLUMN last_error TEXT;",
+        )?;
+    }
+    // Migration: model chosen for a queued remote message
+    if !has_column(conn, "remote_outbox", "model")? {
+        conn.execute_batch("ALTER TABLE remote_outbox ADD COLUMN model TEXT;")?;
+    }
+
+    // Generated thread titles keyed by a hash of model + prompt
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS title_cache (
+            key TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );",
+    )?;
+
+    // Structured log of openclaw runs, written when the agent_log setting allows
+    if !has_table(conn, "agent_invocations")? {
+        conn.execute_batch(
+            "CREATE TABLE agent_invocations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                agent_id TEXT NOT NULL,
+                session_id TEXT,
+                model TEXT,
+                remote INTEGER NOT NULL DEFAULT 0,
+                command TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                exit_code INTEGER,
+                timed_out INTEGER NOT NULL DEFAULT 0,
+                ok INTEGER NOT NULL,
+                response TEXT,
+                error TEXT,
+                at INTEGER NOT NULL
+            );
+            CREATE INDEX idx_agent_invocations_at ON agent_invocations(at);",
+        )?;
+    }
+
+    // Per-agent CLI flags, environment and working directory for local runs
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS agent_overrides (
+            agent_id TEXT PRIMARY KEY,
+            flags TEXT,
+            env TEXT,
+            working_dir TEXT,
+            updated_at INTEGER NOT NULL
+        );",
+    )?;
+
+    // Migration: project metadata mirrored from note frontmatter
+    if !has_column(conn, "projects", "tags")? {
+        conn.execute_batch(
+            "ALTER TABLE projects ADD COLUMN tags TEXT;
+             ALTER TABLE projects ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE projects ADD COLUMN due_at INTEGER;",
+        )?;
+    }
+
+    // User overrides of the built-in agent prompts, keyed by purpose
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS prompt_templates (
+            purpose TEXT PRIMARY KEY,
+            template TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        );",
+    )?;
+
+    // One row per proactive follow-up pass
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS proactive_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            started_at INTEGER NOT NULL,
+            finished_at INTEGER NOT NULL,
+            manual INTEGER NOT NULL DEFAULT 0,
+            digest INTEGER NOT NULL DEFAULT 0,
+            processed INTEGER NOT NULL DEFAULT 0,
+            sent INTEGER NOT NULL DEFAULT 0,
+            stopped INTEGER NOT NULL DEFAULT 0,
+            failed INTEGER NOT NULL DEFAULT 0,
+            failures TEXT,
+            error TEXT
+        );",
+    )?;
+
+    // Whether each follow-up got a reply, for judging whether they help
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS follow_up_outcomes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            brain_dump_id TEXT NOT NULL,
+            thread_id TEXT NOT NULL,
+            attempt INTEGER NOT NULL,
+            digest INTEGER NOT NULL DEFAULT 0,
+            sent_at INTEGER NOT NULL,
+            start_index INTEGER NOT NULL,
+            end_index INTEGER NOT NULL,
+            outcome TEXT NOT NULL DEFAULT 'pending',
+            replied_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_follow_up_outcomes_dump ON follow_up_outcomes(brain_dump_id);
+        CREATE INDEX IF NOT EXISTS idx_follow_up_outcomes_thread ON follow_up_outcomes(thread_id, sent_at);",
+    )?;
+
+    // User-facing events held back while do-not-disturb is on
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS deferred_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            name TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );",
+    )?;
+
+    // Background jobs: per-job switches and schedules, plus a history of runs
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS jobs (
+            id TEXT PRIMARY KEY,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            schedule TEXT,
+            last_run_at INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS job_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            job_id TEXT NOT NULL,
+            manual INTEGER NOT NULL DEFAULT 0,
+            started_at INTEGER NOT NULL,
+            finished_at INTEGER,
+            ok INTEGER,
+            detail TEXT,
+            error TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_job_runs_started ON job_runs(job_id, started_at);",
+    )?;
+
+    // Morning briefings composed by the briefing job
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS briefings (
+            id TEXT PRIMARY KEY,
+            date TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );",
+    )?;
+
+    // Weekly per-project retrospectives, one per ISO week
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS retrospectives (
+            id TEXT PRIMARY KEY,
+            week TEXT NOT NULL UNIQUE,
+            week_start TEXT NOT NULL,
+            narrative TEXT,
+            projects TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );",
+    )?;
+
     Ok(())
 }
 
-// Projects CRUD
+/// Full-text index over thread names, brain dumps and kanban cards, kept
+/// current by triggers. `kind` and `ref_id` point back at the row.
+fn search_index(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE VIRTUAL TABLE search_index USING fts5(
+            kind UNINDEXED,
+            ref_id UNINDEXED,
+            project_id UNINDEXED,
+            title,
+            body,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
 
-pub fn create_project(conn: &Connection, project: &Project) -> Result<()> {
-    conn.execute(
-        "INSERT INTO projects (id, name, description, color, agent_id, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-        params![
-            project.id,
-            project.name,
-            project.description,
-            project.color,
-            project.agent_id,
-            project.created_at,
-            project.updated_at,
-        ],
+        CREATE TRIGGER search_thread_insert AFTER INSERT ON threads BEGIN
+            INSERT INTO search_index (kind, ref_id, project_id, title, body)
+            VALUES ('thread', new.id, new.project_id, new.name, '');
+        END;
+        CREATE TRIGGER search_thread_update AFTER UPDATE OF name, project_id ON threads BEGIN
+            UPDATE search_index SET title = new.name, project_id = new.project_id
+            WHERE kind = 'thread' AND ref_id = old.id;
+        END;
+        CREATE TRIGGER search_thread_delete AFTER DELETE ON threads BEGIN
+            DELETE FROM search_index WHERE kind = 'thread' AND ref_id = old.id;
+        END;
+
+        CREATE TRIGGER search_dump_insert AFTER INSERT ON brain_dumps BEGIN
+            INSERT INTO search_index (kind, ref_id, project_id, title, body)
+            VALUES ('brain_dump', new.id, new.project_id, '', new.content);
+        END;
+        CREATE TRIGGER search_dump_update AFTER UPDATE OF content, project_id ON brain_dumps BEGIN
+            UPDATE search_index SET body = new.content, project_id = new.project_id
+            WHERE kind = 'brain_dump' AND ref_id = old.id;
+        END;
+        CREATE TRIGGER search_dump_delete AFTER DELETE ON brain_dumps BEGIN
+            DELETE FROM search_index WHERE kind = 'brain_dump' AND ref_id = old.id;
+        END;
+
+        CREATE TRIGGER search_card_insert AFTER INSERT ON kanban_items BEGIN
+            INSERT INTO search_index (kind, ref_id, project_id, title, body)
+            VALUES ('kanban', new.id, new.project_id, new.title, COALESCE(new.description, ''));
+        END;
+        CREATE TRIGGER search_card_update AFTER UPDATE OF title, description, project_id ON kanban_items BEGIN
+            UPDATE search_index
+            SET title = new.title, body = COALESCE(new.description, ''), project_id = new.project_id
+            WHERE kind = 'kanban' AND ref_id = old.id;
+        END;
+        CREATE TRIGGER search_card_delete AFTER DELETE ON kanban_items BEGIN
+            DELETE FROM search_index WHERE kind = 'kanban' AND ref_id = old.id;
+        END;
+
+        INSERT INTO search_index (kind, ref_id, project_id, title, body)
+        SELECT 'thread', id, project_id, name, '' FROM threads;
+        INSERT INTO search_index (kind, ref_id, project_id, title, body)
+        SELECT 'brain_dump', id, project_id, '', content FROM brain_dumps;
+        INSERT INTO search_index (kind, ref_id, project_id, title, body)
+        SELECT 'kanban', id, project_id, title, COALESCE(description, '') FROM kanban_items;
+        ",
     )?;
     Ok(())
 }
 
-pub fn list_projects(conn: &Connection) -> Result<Vec<Project>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, name, description, color, agent_id, created_at, updated_at
-         FROM projects ORDER BY updated_at DESC",
+/// Tables whose changes are logged: (table, key column, columns whose
+/// changes alone aren't worth an entry).
+const AUDITED: &[(&str, &str, &[&str])] = &[
+    ("projects", "id", &["updated_at"]),
+    ("threads", "id", &["updated_at", "last_message_at"]),
+    ("brain_dumps", "id", &["updated_at"]),
+    ("kanban_items", "id", &["updated_at", "nudged_at", "overdue_notified_at"]),
+    ("settings", "key", &[]),
+];
+
+fn audit_log(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity_type TEXT NOT NULL,
+            entity_id TEXT NOT NULL,
+            op TEXT NOT NULL,
+            before TEXT,
+            after TEXT,
+            created_at INTEGER NOT NULL
+        );
+        CREATE INDEX idx_audit_entity ON audit_log(entity_type, entity_id);
+        CREATE INDEX idx_audit_created ON audit_log(created_at);",
     )?;
-    let rows = stmt.query_map([], |row| {
-        Ok(Project {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            description: row.get(2)?,
-            color: row.get(3)?,
-            agent_id: row.get(4)?,
-            created_at: row.get(5)?,
-            updated_at: row.get(6)?,
-        })
-    })?;
-    let mut projects = Vec::new();
-    for p in rows {
-        projects.push(p?);
+    create_audit_triggers(conn)
+}
+
+/// (Re)create the triggers that fill `audit_log` with JSON snapshots of each
+/// row before and after a change. Snapshots list the columns a table has
+/// now, so a migration that adds a column to an audited table calls this
+/// again.
+fn create_audit_triggers(conn: &Connection) -> Result<()> {
+    const NOW_MS: &str = "CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)";
+    for (table, key, ignored) in AUDITED {
+        let columns = table_columns(conn, table)?;
+        let snapshot = |row: &str| {
+            let fields: Vec<String> = columns
+                .iter()
+                .map(|c| format!("'{}', {}.{}", c, row, quote_ident(c)))
+                .collect();
+            format!("json_object({})", fields.join(", "))
+        };
+        let changed: Vec<String> = columns
+            .iter()
+            .filter(|c| !ignored.contains(&c.as_str()))
+            .map(|c| format!("old.{0} IS NOT new.{0}", quote_ident(c)))
+            .collect();
+        conn.execute_batch(&format!(
+            "DROP TRIGGER IF EXISTS audit_{table}_insert;
+             DROP TRIGGER IF EXISTS audit_{table}_update;
+             DROP TRIGGER IF EXISTS audit_{table}_delete;
+             CREATE TRIGGER audit_{table}_insert AFTER INSERT ON {table} BEGIN
+                INSERT INTO audit_log (entity_type, entity_id, op, before, after, created_at)
+                VALUES ('{table}', new.{key}, 'create', NULL, {after}, {now});
+             END;
+             CREATE TRIGGER audit_{table}_update AFTER UPDATE ON {table} WHEN {changed} BEGIN
+                INSERT INTO audit_log (entity_type, entity_id, op, before, after, created_at)
+                VALUES ('{table}', new.{key}, 'update', {before}, {after}, {now});
+             END;
+             CREATE TRIGGER audit_{table}_delete AFTER DELETE ON {table} BEGIN
+                INSERT INTO audit_log (entity_type, entity_id, op, before, after, created_at)
+                VALUES ('{table}', old.{key}, 'delete', {before}, NULL, {now});
+             END;",
+            before = snapshot("old"),
+            after = snapshot("new"),
+            changed = changed.join(" OR "),
+            now = NOW_MS,
+        ))?;
     }
-    Ok(projects)
+    Ok(())
 }
 
-pub fn update_project(conn: &Connection, id: &str, name: &str, description: Option<&str>, color: Option<&str>) -> Result<()> {
-    let now = chrono::Utc::now().timestamp_millis();
-    conn.execute(
-        "UPDATE projects SET name=?1, description=?2, color=?3, updated_at=?4 WHERE id=?5",
-        params![name, description, color, now, id],
+fn maintenance_runs(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE maintenance_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            ran_at INTEGER NOT NULL,
+            report TEXT NOT NULL
+        );",
     )?;
     Ok(())
 }
 
-pub fn delete_project(conn: &Connection, id: &str) -> Result<()> {
-    conn.execute("DELETE FROM projects WHERE id=?1", params![id])?;
+/// Rows `relations::archived` changed, so restoring the parent undoes just
+/// those. Also clears references earlier releases left pointing at rows
+/// that were deleted, which hid threads whose project was gone.
+fn cascade_marks(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE cascade_marks (
+            parent_type TEXT NOT NULL,
+            parent_id TEXT NOT NULL,
+            child_type TEXT NOT NULL,
+            child_id TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (parent_type, parent_id, child_type, child_id)
+        );
+        CREATE INDEX idx_cascade_marks_child ON cascade_marks(child_type, child_id);
+
+        UPDATE threads SET project_id = NULL
+         WHERE project_id IS NOT NULL AND project_id NOT IN (SELECT id FROM projects);
+        UPDATE brain_dumps SET project_id = NULL
+         WHERE project_id IS NOT NULL AND project_id NOT IN (SELECT id FROM projects);
+        UPDATE kanban_items SET project_id = NULL
+         WHERE project_id IS NOT NULL AND project_id NOT IN (SELECT id FROM projects);
+        UPDATE kanban_items SET thread_id = NULL
+         WHERE thread_id IS NOT NULL AND thread_id NOT IN (SELECT id FROM threads);",
+    )?;
     Ok(())
 }
 
-pub fn get_project(conn: &Connection, id: &str) -> Result<Option<Project>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, name, description, color, agent_id, created_at, updated_at
-         FROM projects WHERE id=?1",
+/// Files in the content-addressed store and what uses each one.
+fn blobs(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE blobs (
+            hash TEXT PRIMARY KEY,
+            size INTEGER NOT NULL,
+            mime TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        CREATE TABLE blob_refs (
+            hash TEXT NOT NULL,
+            owner_type TEXT NOT NULL,
+            owner_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (owner_type, owner_id, hash)
+        );
+        CREATE INDEX idx_blob_refs_hash ON blob_refs(hash);",
     )?;
-    let mut rows = stmt.query_map(params![id], |row| {
-        Ok(Project {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            description: row.get(2)?,
-            color: row.get(3)?,
-            agent_id: row.get(4)?,
-            created_at: row.get(5)?,
-            updated_at: row.get(6)?,
-        })
-    })?;
-    Ok(rows.next().transpose()?)
+    Ok(())
 }
 
-// Threads CRUD
+/// Bookkeeping for `sync`: other devices and how far this one has merged
+/// them, hashes of the session files last pushed, and deletions so they
+/// reach the other devices. A row that comes back clears its tombstone.
+fn sync(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE sync_peers (
+            device_id TEXT PRIMARY KEY,
+            exported_at INTEGER NOT NULL,
+            merged_at INTEGER NOT NULL
+        );
+        CREATE TABLE sync_files (
+            path TEXT PRIMARY KEY,
+            size INTEGER NOT NULL,
+            modified_at INTEGER NOT NULL,
+            hash TEXT NOT NULL,
+            pushed_hash TEXT
+        );
+        CREATE TABLE sync_tombstones (
+            table_name TEXT NOT NULL,
+            row_id TEXT NOT NULL,
+            deleted_at INTEGER NOT NULL,
+            PRIMARY KEY (table_name, row_id)
+        );",
+    )?;
+    const NOW_MS: &str = "CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)";
+    for table in ["projects", "threads", "brain_dumps", "kanban_items"] {
+        conn.execute_batch(&format!(
+            "CREATE TRIGGER sync_{table}_delete AFTER DELETE ON {table} BEGIN
+                INSERT OR REPLACE INTO sync_tombstones (table_name, row_id, deleted_at)
+                VALUES ('{table}', old.id, {now});
+             END;
+             CREATE TRIGGER sync_{table}_insert AFTER INSERT ON {table} BEGIN
+                DELETE FROM sync_tombstones WHERE table_name = '{table}' AND row_id = new.id;
+             END;",
+            now = NOW_MS,
+        ))?;
+    }
+    Ok(())
+}
 
-pub fn create_thread(conn: &Connection, thread: &Thread) -> Result<()> {
-    conn.execute(
-        "INSERT INTO threads (id, project_id, name, session_id, agent_id, created_at, updated_at, last_message_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-        params![
-            thread.id,
-            thread.project_id,
-            thread.name,
-            thread.session_id,
-            thread.agent_id,
-            thread.created_at,
-            thread.updated_at,
-            thread.last_message_at,
-        ],
+/// The repository each project files issues in, and the issues filed from
+/// cards and brain dumps.
+fn github(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE github_repos (
+            project_id TEXT PRIMARY KEY,
+            repo TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        CREATE TABLE github_issues (
+            owner_type TEXT NOT NULL,
+            owner_id TEXT NOT NULL,
+            repo TEXT NOT NULL,
+            number INTEGER NOT NULL,
+            url TEXT NOT NULL,
+            state TEXT NOT NULL,
+            closed_at INTEGER,
+            created_at INTEGER NOT NULL,
+            checked_at INTEGER NOT NULL,
+            PRIMARY KEY (owner_type, owner_id)
+        );
+        CREATE INDEX idx_github_issues_state ON github_issues(state);",
     )?;
     Ok(())
 }
 
-pub fn list_threads(conn: &Connection, project_id: Option<&str>) -> Result<Vec<Thread>> {
-    let (query, param): (String, Option<String>) = match project_id {
-        Some(pid) => (
-            "SELECT id, project_id, name, session_id, agent_id, created_at, updated_at, last_message_at
-             FROM threads WHERE project_id=?1 ORDER BY last_message_at DESC, updated_at DESC".to_string(),
+/// `events` is a JSON array of event names; empty means every event.
+fn webhooks(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE webhooks (
+            id TEXT PRIMARY KEY,
+            url TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            events TEXT NOT NULL DEFAULT '[]',
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            last_sent_at INTEGER,
+            last_status INTEGER,
+            last_error TEXT
+        );",
+    )?;
+    Ok(())
+}
+
+/// Apple Reminders items made for cards. Kept after a card is deleted so the
+/// next export can remove its reminder.
+fn reminder_links(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE reminder_links (
+            item_id TEXT PRIMARY KEY,
+            reminder_id TEXT NOT NULL,
+            item_updated_at INTEGER NOT NULL,
+            synced_at INTEGER NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+/// When a card was last notified about being overdue; cleared with a new
+/// due date like `nudged_at`.
+fn overdue_notices(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE kanban_items ADD COLUMN overdue_notified_at INTEGER;")?;
+    // Cards already overdue don't all notify at once on the first run
+    conn.execute(
+        "UPDATE kanban_items SET overdue_notified_at = ?1 WHERE due_at < ?1",
+        params![chrono::Utc::now().timestamp_millis()],
+    )?;
+    create_audit_triggers(conn)
+}
+
+/// Local threads whose session file maintenance found missing, so a thread
+/// is only deleted once it's still missing on the next run. Kept out of
+/// `threads` since it's about this machine's files.
+fn missing_sessions(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE missing_sessions (
+            thread_id TEXT PRIMARY KEY,
+            missing_since INTEGER NOT NULL
+        );",
+    )?;
+    Ok(())
+}
+
+/// Vault tasks whose card was deleted in the app, so task sync doesn't bring
+/// the card back. Keyed on note and task text, since lines move.
+fn dismissed_obsidian_tasks(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE dismissed_obsidian_tasks (
+            note TEXT NOT NULL,
+            title TEXT NOT NULL,
+            dismissed_at INTEGER NOT NULL,
+            PRIMARY KEY (note, title)
+        );",
+    )?;
+    Ok(())
+}
+
+/// Held events can wait for a time as well as for do-not-disturb to end, so
+/// a snoozed notification survives a restart.
+fn deferred_deliver_at(conn: &Connection) -> Result<()> {
+    conn.execute_batch("ALTER TABLE deferred_events ADD COLUMN deliver_at INTEGER;")?;
+    Ok(())
+}
+
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", quote_ident(table)))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    Ok(names.collect::<std::result::Result<Vec<_>, _>>()?)
+}
+
+fn has_table(conn: &Connection, table: &str) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name=?1",
+        params![table],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in names {
+        if name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// Dynamic updates
+
+/// Quote an SQL identifier so reserved words like `column` are safe to use.
+pub fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Builds a single-row `UPDATE table SET ... WHERE id=?` with quoted column
+/// names and typed bind values (integers stay integers, None becomes NULL).
+pub struct UpdateBuilder {
+    table: &'static str,
+    assignments: Vec<String>,
+    values: Vec<Value>,
+}
+
+impl UpdateBuilder {
+    pub fn new(table: &'static str) -> Self {
+        Self {
+            table,
+            assignments: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    fn placeholder(&mut self, value: Value) -> String {
+        self.values.push(value);
+        format!("?{}", self.values.len())
+    }
+
+    /// `column = value`
+    pub fn set(&mut self, column: &str, value: impl Into<Value>) -> &mut Self {
+        let ph = self.placeholder(value.into());
+        self.assignments.push(format!("{}={}", quote_ident(column), ph));
+        self
+    }
+
+    /// `column = value`, only when a value was provided.
+    pub fn set_opt<T: Into<Value>>(&mut self, column: &str, value: Option<T>) -> &mut Self {
+        if let Some(v) = value {
+            self.set(column, v);
+        }
+        self
+    }
+
+    /// `column = COALESCE(column, value)` — fill in once, never overwrite.
+    pub fn set_if_null(&mut self, column: &str, value: impl Into<Value>) -> &mut Self {
+        let ph = self.placeholder(value.into());
+        let col = quote_ident(column);
+        self.assignments.push(format!("{}=COALESCE({}, {})", col, col, ph));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assignments.is_empty()
+    }
+
+    /// Run the update against the row with the given id; returns rows changed.
+    pub fn execute(mut self, conn: &Connection, id: &str) -> Result<usize> {
+        if self.assignments.is_empty() {
+            return Ok(0);
+        }
+        let id_ph = self.placeholder(Value::Text(id.to_string()));
+        let sql = format!(
+            "UPDATE {} SET {} WHERE id={}",
+            quote_ident(self.table),
+            self.assignments.join(", "),
+            id_ph
+        );
+        let changed = conn.execute(&sql, rusqlite::params_from_iter(self.values.iter()))?;
+        Ok(changed)
+    }
+}
+
+// Projects CRUD
+
+pub fn create_project(conn: &Connection, project: &Project) -> Result<()> {
+    conn.execute(
+        "INSERT INTO projects (id, name, description, color, agent_id, status, obsidian_source, tags, priority, due_at, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            project.id,
+            project.name,
+            project.description,
+            project.color,
+            project.agent_id,
+            project.status,
+            project.obsidian_source,
+            tags_to_sql(&project.tags),
+            project.priority,
+            project.due_at,
+            project.created_at,
+            project.updated_at,
+        ],
+    )?;
+    Ok(())
+}
+
+const PROJECT_COLUMNS: &str = "id, name, description, color, agent_id, status, obsidian_source, tags, priority, due_at, created_at, updated_at";
+
+/// Tags are stored as a JSON array; NULL when there are none.
+fn tags_to_sql(tags: &[String]) -> Option<String> {
+    if tags.is_empty() {
+        None
+    } else {
+        serde_json::to_string(tags).ok()
+    }
+}
+
+fn row_to_project(row: &rusqlite::Row) -> rusqlite::Result<Project> {
+    let tags: Option<String> = row.get(7)?;
+    Ok(Project {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        color: row.get(3)?,
+        agent_id: row.get(4)?,
+        status: row.get(5)?,
+        obsidian_source: row.get(6)?,
+        tags: tags
+            .and_then(|t| serde_json::from_str(&t).ok())
+            .unwrap_or_default(),
+        priority: row.get(8)?,
+        due_at: row.get(9)?,
+        created_at: row.get(10)?,
+        updated_at: row.get(11)?,
+    })
+}
+
+pub fn list_projects(conn: &Connection, include_archived: bool) -> Result<Vec<Project>> {
+    let filter = if include_archived { "" } else { "WHERE status != 'archived'" };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM projects {} ORDER BY updated_at DESC",
+        PROJECT_COLUMNS, filter
+    ))?;
+    let rows = stmt.query_map([], row_to_project)?;
+    let mut projects = Vec::new();
+    for p in rows {
+        projects.push(p?);
+    }
+    Ok(projects)
+}
+
+pub fn update_project(conn: &Connection, id: &str, name: &str, description: Option<&str>, color: Option<&str>) -> Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut update = UpdateBuilder::new("projects");
+    update
+        .set("name", name.to_string())
+        .set("description", description.map(str::to_string))
+        .set("color", color.map(str::to_string))
+        .set("updated_at", now);
+    update.execute(conn, id)?;
+    Ok(())
+}
+
+pub fn set_project_agent(conn: &Connection, id: &str, agent_id: &str) -> Result<()> {
+    let mut update = UpdateBuilder::new("projects");
+    update
+        .set("agent_id", agent_id.to_string())
+        .set("updated_at", chrono::Utc::now().timestamp_millis());
+    update.execute(conn, id)?;
+    Ok(())
+}
+
+pub fn get_project(conn: &Connection, id: &str) -> Result<Option<Project>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM projects WHERE id=?1", PROJECT_COLUMNS))?;
+    let mut rows = stmt.query_map(params![id], row_to_project)?;
+    Ok(rows.next().transpose()?)
+}
+
+pub fn get_project_id_by_obsidian_source(conn: &Connection, obsidian_source: &str) -> Result<Option<String>> {
+    let id = conn
+        .query_row(
+            "SELECT id FROM projects WHERE obsidian_source=?1",
+            params![obsidian_source],
+            |row| row.get(0),
+        )
+        .ok();
+    Ok(id)
+}
+
+/// Archive the project linked to a note that has left the active folder.
+/// Returns the project name when something changed.
+pub fn archive_obsidian_project(conn: &Connection, obsidian_source: &str) -> Result<Option<String>> {
+    let found: Option<(String, String)> = conn
+        .query_row(
+            "SELECT id, name FROM projects WHERE obsidian_source=?1 AND status != 'archived'",
+            params![obsidian_source],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok();
+    let Some((id, name)) = found else {
+        return Ok(None);
+    };
+    set_project_status(conn, &id, "archived")?;
+    Ok(Some(name))
+}
+
+/// Archiving or restoring also applies `relations`' archive rules.
+pub fn set_project_status(conn: &Connection, id: &str, status: &str) -> Result<()> {
+    let old: Option<String> = conn
+        .query_row("SELECT status FROM projects WHERE id=?1", params![id], |row| row.get(0))
+        .ok();
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut update = UpdateBuilder::new("projects");
+    update.set("status", status.to_string()).set("updated_at", now);
+    update.execute(conn, id)?;
+    if let Some(old) = old {
+        relations::status_changed(conn, relations::PROJECTS, id, &old, status)?;
+    }
+    Ok(())
+}
+
+/// Note mtime recorded at the last sync or write-back, used to detect vault-side edits.
+pub fn get_project_obsidian_mtime(conn: &Connection, id: &str) -> Result<Option<i64>> {
+    let mtime = conn
+        .query_row(
+            "SELECT obsidian_mtime FROM projects WHERE id=?1",
+            params![id],
+            |row| row.get::<_, Option<i64>>(0),
+        )
+        .ok()
+        .flatten();
+    Ok(mtime)
+}
+
+/// Point a project at a freshly written note, recording the note's values as
+/// the sync base so the next sync sees no change.
+pub fn link_project_to_note(
+    conn: &Connection,
+    id: &str,
+    obsidian_source: &str,
+    color: &str,
+    mtime: Option<i64>,
+) -> Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    conn.execute(
+        "UPDATE projects SET obsidian_source=?1, color=?2, obsidian_mtime=?3,
+                synced_name=name, synced_description=description, updated_at=?4
+         WHERE id=?5",
+        params![obsidian_source, color, mtime, now, id],
+    )?;
+    Ok(())
+}
+
+pub fn set_project_obsidian_mtime(conn: &Connection, id: &str, mtime: Option<i64>) -> Result<()> {
+    conn.execute(
+        "UPDATE projects SET obsidian_mtime=?1 WHERE id=?2",
+        params![mtime, id],
+    )?;
+    Ok(())
+}
+
+// Threads CRUD
+
+const THREAD_COLUMNS: &str =
+    "id, project_id, name, session_id, agent_id, created_at, updated_at, last_message_at, execution_target, model, muted";
+
+pub fn create_thread(conn: &Connection, thread: &Thread) -> Result<()> {
+    conn.execute(
+        "INSERT INTO threads (id, project_id, name, session_id, agent_id, created_at, updated_at, last_message_at, execution_target, model, muted)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            thread.id,
+            thread.project_id,
+            thread.name,
+            thread.session_id,
+            thread.agent_id,
+            thread.created_at,
+            thread.updated_at,
+            thread.last_message_at,
+            thread.execution_target,
+            thread.model,
+            thread.muted as i32,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn list_threads(conn: &Connection, project_id: Option<&str>) -> Result<Vec<Thread>> {
+    let (query, param): (String, Option<String>) = match project_id {
+        Some(pid) => (
+            format!(
+                "SELECT {} FROM threads WHERE project_id=?1 ORDER BY last_message_at DESC, updated_at DESC",
+                THREAD_COLUMNS
+            ),
             Some(pid.to_string()),
         ),
         None => (
-            "SELECT id, project_id, name, session_id, agent_id, created_at, updated_at, last_message_at
-             FROM threads WHERE project_id IS NULL ORDER BY last_message_at DESC, updated_at DESC".to_string(),
+            format!(
+                "SELECT {} FROM threads WHERE project_id IS NULL ORDER BY last_message_at DESC, updated_at DESC",
+                THREAD_COLUMNS
+            ),
             None,
         ),
     };
@@ -292,52 +1490,56 @@ fn row_to_thread(row: &rusqlite::Row) -> rusqlite::Result<Thread> {
         created_at: row.get(5)?,
         updated_at: row.get(6)?,
         last_message_at: row.get(7)?,
+        execution_target: row.get(8)?,
+        model: row.get(9)?,
+        muted: row.get::<_, i32>(10)? != 0,
     })
 }
 
 pub fn get_thread_by_session(conn: &Connection, session_id: &str) -> Result<Option<Thread>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project_id, name, session_id, agent_id, created_at, updated_at, last_message_at
-         FROM threads WHERE session_id=?1",
-    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM threads WHERE session_id=?1",
+        THREAD_COLUMNS
+    ))?;
     let mut rows = stmt.query_map(params![session_id], row_to_thread)?;
     Ok(rows.next().transpose()?)
 }
 
 pub fn touch_thread(conn: &Connection, thread_id: &str) -> Result<()> {
     let now = chrono::Utc::now().timestamp_millis();
-    conn.execute(
-        "UPDATE threads SET last_message_at=?1, updated_at=?1 WHERE id=?2",
-        params![now, thread_id],
-    )?;
+    let mut update = UpdateBuilder::new("threads");
+    update.set("last_message_at", now).set("updated_at", now);
+    update.execute(conn, thread_id)?;
     Ok(())
 }
 
 pub fn rename_thread(conn: &Connection, id: &str, name: &str) -> Result<()> {
     let now = chrono::Utc::now().timestamp_millis();
-    conn.execute(
-        "UPDATE threads SET name=?1, title_updated_at=?2, updated_at=?2 WHERE id=?3",
-        params![name, now, id],
-    )?;
+    let mut update = UpdateBuilder::new("threads");
+    update
+        .set("name", name.to_string())
+        .set("title_updated_at", now)
+        .set("updated_at", now);
+    update.execute(conn, id)?;
     Ok(())
 }
 
 pub fn get_thread(conn: &Connection, id: &str) -> Result<Option<Thread>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project_id, name, session_id, agent_id, created_at, updated_at, last_message_at
-         FROM threads WHERE id=?1",
-    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM threads WHERE id=?1",
+        THREAD_COLUMNS
+    ))?;
     let mut rows = stmt.query_map(params![id], row_to_thread)?;
     Ok(rows.next().transpose()?)
 }
 
 pub fn get_threads_needing_title_refresh(conn: &Connection) -> Result<Vec<Thread>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project_id, name, session_id, agent_id, created_at, updated_at, last_message_at
-         FROM threads
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM threads
          WHERE last_message_at IS NOT NULL
            AND (title_updated_at IS NULL OR last_message_at > title_updated_at)",
-    )?;
+        THREAD_COLUMNS
+    ))?;
     let rows = stmt.query_map([], row_to_thread)?;
     let mut threads = Vec::new();
     for t in rows {
@@ -346,17 +1548,87 @@ pub fn get_threads_needing_title_refresh(conn: &Connection) -> Result<Vec<Thread
     Ok(threads)
 }
 
-pub fn delete_thread(conn: &Connection, id: &str) -> Result<()> {
-    conn.execute("DELETE FROM threads WHERE id=?1", params![id])?;
+/// Threads with a message at or after `since` (epoch ms), newest first.
+pub fn list_threads_active_since(conn: &Connection, since: i64) -> Result<Vec<Thread>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM threads WHERE last_message_at >= ?1 ORDER BY last_message_at DESC",
+        THREAD_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![since], row_to_thread)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<Thread>>>()?)
+}
+
+pub fn set_thread_execution_target(conn: &Connection, id: &str, target: Option<&str>) -> Result<()> {
+    let mut update = UpdateBuilder::new("threads");
+    update
+        .set("execution_target", target.map(str::to_string))
+        .set("updated_at", chrono::Utc::now().timestamp_millis());
+    update.execute(conn, id)?;
+    Ok(())
+}
+
+/// Point a thread at another session file of its agent.
+pub fn set_thread_session(conn: &Connection, id: &str, session_id: &str) -> Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut update = UpdateBuilder::new("threads");
+    update
+        .set("session_id", session_id.to_string())
+        .set("last_message_at", now)
+        .set("updated_at", now);
+    update.execute(conn, id)?;
+    Ok(())
+}
+
+pub fn set_thread_model(conn: &Connection, id: &str, model: Option<&str>) -> Result<()> {
+    let mut update = UpdateBuilder::new("threads");
+    update
+        .set("model", model.map(str::to_string))
+        .set("updated_at", chrono::Utc::now().timestamp_millis());
+    update.execute(conn, id)?;
+    Ok(())
+}
+
+pub fn set_thread_muted(conn: &Connection, id: &str, muted: bool) -> Result<()> {
+    let mut update = UpdateBuilder::new("threads");
+    update
+        .set("muted", muted as i32)
+        .set("updated_at", chrono::Utc::now().timestamp_millis());
+    update.execute(conn, id)?;
+    Ok(())
+}
+
+/// Threads in active projects with no message since `before` (epoch ms) that
+/// haven't been resurfaced since, longest idle first.
+pub fn list_stale_threads(conn: &Connection, before: i64, limit: u32) -> Result<Vec<Thread>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM threads
+         WHERE last_message_at < ?1
+         AND (resurfaced_at IS NULL OR resurfaced_at < last_message_at)
+         AND project_id IN (SELECT id FROM projects WHERE status='active')
+         ORDER BY last_message_at LIMIT ?2",
+        THREAD_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![before, limit], row_to_thread)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<Thread>>>()?)
+}
+
+/// Leaves `updated_at` alone: resurfacing isn't a change the user made.
+pub fn set_thread_resurfaced(conn: &Connection, id: &str, at: i64) -> Result<()> {
+    conn.execute("UPDATE threads SET resurfaced_at=?1 WHERE id=?2", params![at, id])?;
     Ok(())
 }
 
 // Brain Dump CRUD
 
+const BRAIN_DUMP_COLUMNS: &str = "id, content, project_id, status, proactive, created_at, updated_at, followed_up_at, \
+     follow_up_interval_hours, max_follow_ups, follow_up_attempts, follow_up_thread_id";
+
 pub fn create_brain_dump(conn: &Connection, dump: &BrainDump) -> Result<()> {
     conn.execute(
-        "INSERT INTO brain_dumps (id, content, project_id, status, proactive, created_at, updated_at, followed_up_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        &format!(
+            "INSERT INTO brain_dumps ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            BRAIN_DUMP_COLUMNS
+        ),
         params![
             dump.id,
             dump.content,
@@ -366,57 +1638,49 @@ pub fn create_brain_dump(conn: &Connection, dump: &BrainDump) -> Result<()> {
             dump.created_at,
             dump.updated_at,
             dump.followed_up_at,
+            dump.follow_up_interval_hours,
+            dump.max_follow_ups,
+            dump.follow_up_attempts,
+            dump.follow_up_thread_id,
         ],
     )?;
     Ok(())
 }
 
+fn row_to_brain_dump(row: &rusqlite::Row) -> rusqlite::Result<BrainDump> {
+    Ok(BrainDump {
+        id: row.get(0)?,
+        content: row.get(1)?,
+        project_id: row.get(2)?,
+        status: row.get(3)?,
+        proactive: row.get::<_, i32>(4)? != 0,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        followed_up_at: row.get(7)?,
+        follow_up_interval_hours: row.get(8)?,
+        max_follow_ups: row.get(9)?,
+        follow_up_attempts: row.get(10)?,
+        follow_up_thread_id: row.get(11)?,
+    })
+}
+
 pub fn list_brain_dumps(conn: &Connection) -> Result<Vec<BrainDump>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, content, project_id, status, proactive, created_at, updated_at, followed_up_at
-         FROM brain_dumps ORDER BY created_at DESC",
-    )?;
-    let rows = stmt.query_map([], |row| {
-        Ok(BrainDump {
-            id: row.get(0)?,
-            content: row.get(1)?,
-            project_id: row.get(2)?,
-            status: row.get(3)?,
-            proactive: row.get::<_, i32>(4)? != 0,
-            created_at: row.get(5)?,
-            updated_at: row.get(6)?,
-            followed_up_at: row.get(7)?,
-        })
-    })?;
-    let mut dumps = Vec::new();
-    for d in rows {
-        dumps.push(d?);
-    }
-    Ok(dumps)
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM brain_dumps ORDER BY created_at DESC",
+        BRAIN_DUMP_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], row_to_brain_dump)?;
+    Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
 }
 
+/// Proactive dumps still being worked on, whether or not a follow-up is due.
 pub fn get_proactive_brain_dumps(conn: &Connection) -> Result<Vec<BrainDump>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, content, project_id, status, proactive, created_at, updated_at, followed_up_at
-         FROM brain_dumps WHERE proactive=1 AND status='open' ORDER BY created_at ASC",
-    )?;
-    let rows = stmt.query_map([], |row| {
-        Ok(BrainDump {
-            id: row.get(0)?,
-            content: row.get(1)?,
-            project_id: row.get(2)?,
-            status: row.get(3)?,
-            proactive: row.get::<_, i32>(4)? != 0,
-            created_at: row.get(5)?,
-            updated_at: row.get(6)?,
-            followed_up_at: row.get(7)?,
-        })
-    })?;
-    let mut dumps = Vec::new();
-    for d in rows {
-        dumps.push(d?);
-    }
-    Ok(dumps)
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM brain_dumps WHERE proactive=1 AND status IN ('open', 'in_progress') ORDER BY created_at ASC",
+        BRAIN_DUMP_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], row_to_brain_dump)?;
+    Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
 }
 
 pub fn update_brain_dump_status(conn: &Connection, id: &str, status: &str) -> Result<()> {
@@ -428,12 +1692,32 @@ pub fn update_brain_dump_status(conn: &Connection, id: &str, status: &str) -> Re
     Ok(())
 }
 
-pub fn set_brain_dump_followed_up(conn: &Connection, id: &str) -> Result<()> {
+/// Record a follow-up sent in `thread_id`, the `attempts`-th since the last reply.
+pub fn set_brain_dump_followed_up(conn: &Connection, id: &str, thread_id: &str, attempts: i32) -> Result<()> {
     let now = chrono::Utc::now().timestamp_millis();
-    conn.execute(
-        "UPDATE brain_dumps SET status='in_progress', followed_up_at=?1, updated_at=?1 WHERE id=?2",
-        params![now, id],
-    )?;
+    let mut update = UpdateBuilder::new("brain_dumps");
+    update
+        .set("status", "in_progress".to_string())
+        .set("followed_up_at", now)
+        .set("follow_up_thread_id", thread_id.to_string())
+        .set("follow_up_attempts", attempts)
+        .set("updated_at", now);
+    update.execute(conn, id)?;
+    Ok(())
+}
+
+pub fn set_brain_dump_cadence(
+    conn: &Connection,
+    id: &str,
+    interval_hours: Option<i64>,
+    max_follow_ups: Option<i32>,
+) -> Result<()> {
+    let mut update = UpdateBuilder::new("brain_dumps");
+    update
+        .set("follow_up_interval_hours", interval_hours)
+        .set("max_follow_ups", max_follow_ups)
+        .set("updated_at", chrono::Utc::now().timestamp_millis());
+    update.execute(conn, id)?;
     Ok(())
 }
 
@@ -446,8 +1730,51 @@ pub fn set_brain_dump_proactive(conn: &Connection, id: &str, proactive: bool) ->
     Ok(())
 }
 
-pub fn delete_brain_dump(conn: &Connection, id: &str) -> Result<()> {
-    conn.execute("DELETE FROM brain_dumps WHERE id=?1", params![id])?;
+// Title cache
+
+/// Cached titles older than this are pruned whenever a new one is stored.
+const TITLE_CACHE_TTL_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+pub fn get_cached_title(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let mut stmt = conn.prepare("SELECT title FROM title_cache WHERE key=?1")?;
+    let mut rows = stmt.query_map(params![key], |row| row.get::<_, String>(0))?;
+    Ok(rows.next().transpose()?)
+}
+
+pub fn cache_title(conn: &Connection, key: &str, title: &str) -> Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    conn.execute(
+        "INSERT INTO title_cache (key, title, created_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(key) DO UPDATE SET title=excluded.title, created_at=excluded.created_at",
+        params![key, title, now],
+    )?;
+    conn.execute(
+        "DELETE FROM title_cache WHERE created_at < ?1",
+        params![now - TITLE_CACHE_TTL_MS],
+    )?;
+    Ok(())
+}
+
+// Prompt templates
+
+/// The user's template for `purpose`, if they've replaced the default.
+pub fn get_prompt_template(conn: &Connection, purpose: &str) -> Result<Option<String>> {
+    let mut stmt = conn.prepare("SELECT template FROM prompt_templates WHERE purpose=?1")?;
+    let mut rows = stmt.query_map(params![purpose], |row| row.get::<_, String>(0))?;
+    Ok(rows.next().transpose()?)
+}
+
+pub fn set_prompt_template(conn: &Connection, purpose: &str, template: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO prompt_templates (purpose, template, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(purpose) DO UPDATE SET template=excluded.template, updated_at=excluded.updated_at",
+        params![purpose, template, chrono::Utc::now().timestamp_millis()],
+    )?;
+    Ok(())
+}
+
+pub fn delete_prompt_template(conn: &Connection, purpose: &str) -> Result<()> {
+    conn.execute("DELETE FROM prompt_templates WHERE purpose=?1", params![purpose])?;
     Ok(())
 }
 
@@ -468,56 +1795,537 @@ pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
     Ok(())
 }
 
-// Obsidian sync
-
-pub enum UpsertResult {
-    Created,
-    Updated,
-    Skipped,
+pub fn delete_setting(conn: &Connection, key: &str) -> Result<()> {
+    conn.execute("DELETE FROM settings WHERE key=?1", params![key])?;
+    Ok(())
 }
 
-pub fn upsert_obsidian_project(
-    conn: &Connection,
-    name: &str,
-    description: Option<&str>,
-    color: &str,
-    obsidian_source: &str,
-) -> Result<UpsertResult> {
+// Remote outbox
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OutboxMessage {
+    pub id: String,
+    pub thread_id: String,
+    pub agent_id: String,
+    pub session_id: String,
+    pub message: String,
+    pub created_at: i64,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub model: Option<String>,
+}
+
+pub fn enqueue_outbox(conn: &Connection, msg: &OutboxMessage) -> Result<()> {
+    conn.execute(
+        "INSERT INTO remote_outbox (id, thread_id, agent_id, session_id, message, created_at, model)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![msg.id, msg.thread_id, msg.agent_id, msg.session_id, msg.message, msg.created_at, msg.model],
+    )?;
+    Ok(())
+}
+
+/// Queued messages in the order they were sent, optionally for one thread.
+pub fn list_outbox(conn: &Connection, thread_id: Option<&str>) -> Result<Vec<OutboxMessage>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, thread_id, agent_id, session_id, message, created_at, attempts, last_error, model
+         FROM remote_outbox WHERE ?1 IS NULL OR thread_id=?1 ORDER BY created_at, rowid",
+    )?;
+    let rows = stmt.query_map(params![thread_id], |row| {
+        Ok(OutboxMessage {
+            id: row.get(0)?,
+            thread_id: row.get(1)?,
+            agent_id: row.get(2)?,
+            session_id: row.get(3)?,
+            message: row.get(4)?,
+            created_at: row.get(5)?,
+            attempts: row.get(6)?,
+            last_error: row.get(7)?,
+            model: row.get(8)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<OutboxMessage>>>()?)
+}
+
+pub fn delete_outbox(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute("DELETE FROM remote_outbox WHERE id=?1", params![id])?;
+    Ok(())
+}
+
+/// Count a failed delivery; returns the attempts so far.
+pub fn record_outbox_failure(conn: &Connection, id: &str, error: &str) -> Result<u32> {
+    conn.execute(
+        "UPDATE remote_outbox SET attempts=attempts+1, last_error=?1 WHERE id=?2",
+        params![error, id],
+    )?;
+    Ok(conn.query_row(
+        "SELECT attempts FROM remote_outbox WHERE id=?1",
+        params![id],
+        |row| row.get(0),
+    )?)
+}
+
+// Weekly review
+
+#[derive(Debug, Default, Serialize)]
+pub struct WeeklyStats {
+    pub threads_active: Vec<String>,
+    pub dumps_captured: u32,
+    pub dumps_closed: u32,
+    pub cards_created: u32,
+    pub cards_completed: Vec<String>,
+}
+
+/// Activity between `start` and `end` (epoch ms, end exclusive).
+pub fn weekly_stats(conn: &Connection, start: i64, end: i64) -> Result<WeeklyStats> {
+    let names = |sql: &str| -> Result<Vec<String>> {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params![start, end], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<Vec<String>>>()?)
+    };
+    let count = |sql: &str| -> Result<u32> { Ok(conn.query_row(sql, params![start, end], |row| row.get(0))?) };
+
+    Ok(WeeklyStats {
+        threads_active: names(
+            "SELECT name FROM threads WHERE last_message_at >= ?1 AND last_message_at < ?2
+             ORDER BY last_message_at DESC",
+        )?,
+        dumps_captured: count("SELECT COUNT(*) FROM brain_dumps WHERE created_at >= ?1 AND created_at < ?2")?,
+        dumps_closed: count(
+            "SELECT COUNT(*) FROM brain_dumps WHERE status='done' AND updated_at >= ?1 AND updated_at < ?2",
+        )?,
+        cards_created: count("SELECT COUNT(*) FROM kanban_items WHERE created_at >= ?1 AND created_at < ?2")?,
+        cards_completed: names(
+            "SELECT title FROM kanban_items WHERE completed_at >= ?1 AND completed_at < ?2
+             ORDER BY completed_at",
+        )?,
+    })
+}
+
+// Morning briefing
+
+#[derive(Debug, Default, Serialize)]
+pub struct BriefingInputs {
+    /// Dumps followed up on since the last briefing.
+    pub followed_up: Vec<String>,
+    pub cards_due: Vec<String>,
+    pub stale_threads: Vec<String>,
+    pub open_dumps: Vec<String>,
+}
+
+/// A thread counts as stale with no message for this long, unless it's been
+/// quiet for longer than `STALE_THREAD_MAX_MS` (then it's just old).
+const STALE_THREAD_MIN_MS: i64 = 3 * 24 * 60 * 60 * 1000;
+const STALE_THREAD_MAX_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+/// What goes into a briefing at `now`: follow-ups since `since`, cards due
+/// before `due_before` (all epoch ms) and what's been left hanging.
+pub fn briefing_inputs(conn: &Connection, since: i64, due_before: i64, now: i64) -> Result<BriefingInputs> {
+    fn names<P: rusqlite::Params>(conn: &Connection, sql: &str, params: P) -> Result<Vec<String>> {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params, |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<Vec<String>>>()?)
+    }
+
+    Ok(BriefingInputs {
+        followed_up: names(
+            conn,
+            "SELECT content FROM brain_dumps WHERE followed_up_at >= ?1 ORDER BY followed_up_at",
+            params![since],
+        )?,
+        cards_due: names(
+            conn,
+            "SELECT title FROM kanban_items WHERE due_at < ?1 AND status='active' AND completed_at IS NULL
+             ORDER BY due_at",
+            params![due_before],
+        )?,
+        stale_threads: names(
+            conn,
+            "SELECT name FROM threads WHERE last_message_at >= ?1 AND last_message_at < ?2
+             ORDER BY last_message_at",
+            params![now - STALE_THREAD_MAX_MS, now - STALE_THREAD_MIN_MS],
+        )?,
+        open_dumps: names(
+            conn,
+            "SELECT content FROM brain_dumps WHERE status IN ('open','in_progress') ORDER BY created_at DESC",
+            [],
+        )?,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Briefing {
+    pub id: String,
+    /// Local date it was written for, "YYYY-MM-DD".
+    pub date: String,
+    pub content: String,
+    pub created_at: i64,
+}
+
+pub fn create_briefing(conn: &Connection, briefing: &Briefing) -> Result<()> {
+    conn.execute(
+        "INSERT INTO briefings (id, date, content, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![briefing.id, briefing.date, briefing.content, briefing.created_at],
+    )?;
+    Ok(())
+}
+
+/// Most recent briefings first.
+pub fn list_briefings(conn: &Connection, limit: u32) -> Result<Vec<Briefing>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, date, content, created_at FROM briefings ORDER BY created_at DESC LIMIT ?1",
+    )?;
+    let rows = stmt.query_map(params![limit], |row| {
+        Ok(Briefing {
+            id: row.get(0)?,
+            date: row.get(1)?,
+            content: row.get(2)?,
+            created_at: row.get(3)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<Briefing>>>()?)
+}
+
+// Weekly retrospective
+
+/// One project's week; `project_id` is `None` for work outside any project.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectWeek {
+    pub project_id: Option<String>,
+    pub project: String,
+    /// Threads with a message during the week.
+    pub threads: Vec<String>,
+    /// Messages in those threads, filled in from their sessions.
+    pub messages: usize,
+    pub cards_completed: Vec<String>,
+    pub dumps_closed: Vec<String>,
+}
+
+/// Per-project activity between `start` and `end` (epoch ms, end
+/// exclusive), with the threads behind it so their messages can be counted.
+/// Projects with nothing to show are left out.
+pub fn retrospective_activity(conn: &Connection, start: i64, end: i64) -> Result<Vec<(ProjectWeek, Vec<Thread>)>> {
+    type Entry = (ProjectWeek, Vec<Thread>);
+    /// The entry for `project_id`, added on first use.
+    fn entry<'a>(conn: &Connection, weeks: &'a mut Vec<Entry>, project_id: Option<String>) -> Result<&'a mut Entry> {
+        let i = match weeks.iter().position(|(w, _)| w.project_id == project_id) {
+            Some(i) => i,
+            None => {
+                let project = match project_id.as_deref() {
+                    Some(id) => get_project(conn, id)?.map_or_else(|| id.to_string(), |p| p.name),
+                    None => "No project".to_string(),
+                };
+                weeks.push((ProjectWeek { project_id, project, ..Default::default() }, Vec::new()));
+                weeks.len() - 1
+            }
+        };
+        Ok(&mut weeks[i])
+    }
+
+    let mut weeks: Vec<Entry> = Vec::new();
+    for thread in list_threads_active_since(conn, start)? {
+        if thread.last_message_at.is_some_and(|at| at >= end) {
+            continue;
+        }
+        let (week, threads) = entry(conn, &mut weeks, thread.project_id.clone())?;
+        week.threads.push(thread.name.clone());
+        threads.push(thread);
+    }
+
+    let titled = |sql: &str| -> Result<Vec<(Option<String>, String)>> {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params![start, end], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    };
+    let cards = titled(
+        "SELECT project_id, title FROM kanban_items WHERE completed_at >= ?1 AND completed_at < ?2
+         ORDER BY completed_at",
+    )?;
+    let dumps = titled(
+        "SELECT project_id, content FROM brain_dumps WHERE status='done' AND updated_at >= ?1 AND updated_at < ?2
+         ORDER BY updated_at",
+    )?;
+    for (project_id, title) in cards {
+        entry(conn, &mut weeks, project_id)?.0.cards_completed.push(title);
+    }
+    for (project_id, content) in dumps {
+        entry(conn, &mut weeks, project_id)?.0.dumps_closed.push(content);
+    }
+
+    // Projects first, by name; unfiled work last
+    weeks.sort_by(|(a, _), (b, _)| {
+        a.project_id.is_none().cmp(&b.project_id.is_none()).then_with(|| a.project.cmp(&b.project))
+    });
+    Ok(weeks)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Retrospective {
+    pub id: String,
+    /// ISO week, "YYYY-WW".
+    pub week: String,
+    /// Local date of the Monday it covers, "YYYY-MM-DD".
+    pub week_start: String,
+    /// The agent's narrative; `None` when it couldn't be reached.
+    pub narrative: Option<String>,
+    pub projects: Vec<ProjectWeek>,
+    pub created_at: i64,
+}
+
+/// Store `retro`, replacing an earlier one for the same week.
+pub fn save_retrospective(conn: &Connection, retro: &Retrospective) -> Result<()> {
+    conn.execute(
+        "INSERT INTO retrospectives (id, week, week_start, narrative, projects, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(week) DO UPDATE SET
+            id=excluded.id, week_start=excluded.week_start, narrative=excluded.narrative,
+            projects=excluded.projects, created_at=excluded.created_at",
+        params![
+            retro.id,
+            retro.week,
+            retro.week_start,
+            retro.narrative,
+            serde_json::to_string(&retro.projects)?,
+            retro.created_at
+        ],
+    )?;
+    Ok(())
+}
+
+/// Most recent weeks first.
+pub fn list_retrospectives(conn: &Connection, limit: u32) -> Result<Vec<Retrospective>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, week, week_start, narrative, projects, created_at FROM retrospectives
+         ORDER BY week_start DESC LIMIT ?1",
+    )?;
+    let rows = stmt.query_map(params![limit], |row| {
+        let projects: String = row.get(4)?;
+        Ok(Retrospective {
+            id: row.get(0)?,
+            week: row.get(1)?,
+            week_start: row.get(2)?,
+            narrative: row.get(3)?,
+            projects: serde_json::from_str(&projects).unwrap_or_default(),
+            created_at: row.get(5)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<Retrospective>>>()?)
+}
+
+// Obsidian sync
+
+pub enum UpsertResult {
+    Created,
+    Updated,
+    /// Previously archived project whose note showed up in Active again.
+    Restored,
+    Skipped,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncConflict {
+    pub id: String,
+    pub project_id: String,
+    pub field: String, // 'name' | 'description'
+    pub local_value: Option<String>,
+    pub vault_value: Option<String>,
+    pub obsidian_source: Option<String>,
+    pub created_at: i64,
+    pub resolved_at: Option<i64>,
+}
+
+/// Pick the value for a field edited on both sides. The vault wins whenever
+/// the note changed since the last sync; if the app had also changed it, the
+/// local value is journaled in `sync_conflicts` first. `base` is `None` when
+/// there is no previous sync to compare against (plain overwrite).
+fn merge_synced_field<'a>(
+    conn: &Connection,
+    project: &Project,
+    field: &str,
+    local: Option<&'a str>,
+    base: Option<Option<&str>>,
+    vault: Option<&'a str>,
+) -> Result<Option<&'a str>> {
+    let Some(base) = base else {
+        return Ok(vault);
+    };
+    if vault == base {
+        return Ok(local);
+    }
+    if local != base && local != vault {
+        conn.execute(
+            "INSERT INTO sync_conflicts (id, project_id, field, local_value, vault_value, obsidian_source, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                uuid::Uuid::new_v4().to_string(),
+                project.id,
+                field,
+                local,
+                vault,
+                project.obsidian_source,
+                chrono::Utc::now().timestamp_millis()
+            ],
+        )?;
+    }
+    Ok(vault)
+}
+
+fn row_to_sync_conflict(row: &rusqlite::Row) -> rusqlite::Result<SyncConflict> {
+    Ok(SyncConflict {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        field: row.get(2)?,
+        local_value: row.get(3)?,
+        vault_value: row.get(4)?,
+        obsidian_source: row.get(5)?,
+        created_at: row.get(6)?,
+        resolved_at: row.get(7)?,
+    })
+}
+
+const SYNC_CONFLICT_COLUMNS: &str =
+    "id, project_id, field, local_value, vault_value, obsidian_source, created_at, resolved_at";
+
+pub fn list_sync_conflicts(conn: &Connection, include_resolved: bool) -> Result<Vec<SyncConflict>> {
+    let filter = if include_resolved { "" } else { "WHERE resolved_at IS NULL" };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM sync_conflicts {} ORDER BY created_at DESC",
+        SYNC_CONFLICT_COLUMNS, filter
+    ))?;
+    let rows = stmt.query_map([], row_to_sync_conflict)?;
+    let mut conflicts = Vec::new();
+    for c in rows {
+        conflicts.push(c?);
+    }
+    Ok(conflicts)
+}
+
+pub fn get_sync_conflict(conn: &Connection, id: &str) -> Result<Option<SyncConflict>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM sync_conflicts WHERE id=?1",
+        SYNC_CONFLICT_COLUMNS
+    ))?;
+    let mut rows = stmt.query_map(params![id], row_to_sync_conflict)?;
+    Ok(rows.next().transpose()?)
+}
+
+pub fn resolve_sync_conflict(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE sync_conflicts SET resolved_at=?1 WHERE id=?2",
+        params![chrono::Utc::now().timestamp_millis(), id],
+    )?;
+    Ok(())
+}
+
+/// Everything a vault note contributes to its project row.
+pub struct ProjectNote<'a> {
+    pub name: &'a str,
+    pub description: Option<&'a str>,
+    pub color: &'a str,
+    pub obsidian_source: &'a str,
+    pub mtime: Option<i64>,
+    /// Frontmatter status; `None` leaves the app-side status alone.
+    pub status: Option<&'a str>,
+    pub tags: &'a [String],
+    pub priority: i32,
+    pub due_at: Option<i64>,
+}
+
+pub fn upsert_obsidian_project(conn: &Connection, note: &ProjectNote) -> Result<UpsertResult> {
     let now = chrono::Utc::now().timestamp_millis();
+    let tags = tags_to_sql(note.tags);
 
     // Check if project with this obsidian_source already exists
-    let existing: Option<(String, String, Option<String>, Option<String>)> = conn
-        .prepare("SELECT id, name, description, color FROM projects WHERE obsidian_source=?1")?
-        .query_row(params![obsidian_source], |row| {
-            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
-        })
+    let existing: Option<Project> = conn
+        .prepare(&format!("SELECT {} FROM projects WHERE obsidian_source=?1", PROJECT_COLUMNS))?
+        .query_row(params![note.obsidian_source], row_to_project)
         .ok();
 
-    if let Some((id, old_name, old_desc, old_color)) = existing {
+    if let Some(old) = existing {
+        let status = match note.status {
+            Some(status) => status,
+            None if old.status == "archived" => "active",
+            None => old.status.as_str(),
+        };
+        let restored = old.status == "archived" && status != "archived";
+
+        // Three-way merge of the fields users also edit in the app, against
+        // the values the note had at the previous sync.
+        let (synced_name, synced_desc): (Option<String>, Option<String>) = conn.query_row(
+            "SELECT synced_name, synced_description FROM projects WHERE id=?1",
+            params![old.id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let base_known = synced_name.is_some();
+        let name = merge_synced_field(
+            conn,
+            &old,
+            "name",
+            Some(old.name.as_str()),
+            base_known.then_some(synced_name.as_deref()),
+            Some(note.name),
+        )?
+        .unwrap_or(note.name);
+        let description = merge_synced_field(
+            conn,
+            &old,
+            "description",
+            old.description.as_deref(),
+            base_known.then_some(synced_desc.as_deref()),
+            note.description,
+        )?;
+
         // Check if anything changed
-        if old_name == name
-            && old_desc.as_deref() == description
-            && old_color.as_deref() == Some(color)
+        if old.name == name
+            && old.description.as_deref() == description
+            && old.color.as_deref() == Some(note.color)
+            && old.status == status
+            && old.tags == note.tags
+            && old.priority == note.priority
+            && old.due_at == note.due_at
         {
+            conn.execute(
+                "UPDATE projects SET synced_name=?1, synced_description=?2, obsidian_mtime=?3 WHERE id=?4",
+                params![note.name, note.description, note.mtime, old.id],
+            )?;
             return Ok(UpsertResult::Skipped);
         }
         conn.execute(
-            "UPDATE projects SET name=?1, description=?2, color=?3, updated_at=?4 WHERE id=?5",
-            params![name, description, color, now, id],
+            "UPDATE projects SET name=?1, description=?2, color=?3, status=?4, tags=?5, priority=?6, due_at=?7,
+                    synced_name=?8, synced_description=?9, obsidian_mtime=?10, updated_at=?11
+             WHERE id=?12",
+            params![
+                name,
+                description,
+                note.color,
+                status,
+                tags,
+                note.priority,
+                note.due_at,
+                note.name,
+                note.description,
+                note.mtime,
+                now,
+                old.id
+            ],
         )?;
-        return Ok(UpsertResult::Updated);
+        relations::status_changed(conn, relations::PROJECTS, &old.id, &old.status, status)?;
+        return Ok(if restored { UpsertResult::Restored } else { UpsertResult::Updated });
     }
 
     // First sync: try to claim an existing project by name (no obsidian_source yet)
     let claimed: Option<String> = conn
         .prepare("SELECT id FROM projects WHERE name=?1 AND obsidian_source IS NULL")?
-        .query_row(params![name], |row| row.get(0))
+        .query_row(params![note.name], |row| row.get(0))
         .ok();
 
     if let Some(id) = claimed {
         conn.execute(
-            "UPDATE projects SET description=?1, color=?2, obsidian_source=?3, updated_at=?4 WHERE id=?5",
-            params![description, color, obsidian_source, now, id],
+            "UPDATE projects SET description=?1, color=?2, status=COALESCE(?3, status), tags=?4, priority=?5, due_at=?6,
+                    obsidian_source=?7, obsidian_mtime=?8, synced_name=?9, synced_description=?1, updated_at=?10
+             WHERE id=?11",
+            params![note.description, note.color, note.status, tags, note.priority, note.due_at, note.obsidian_source, note.mtime, note.name, now, id],
         )?;
         return Ok(UpsertResult::Updated);
     }
@@ -525,19 +2333,98 @@ pub fn upsert_obsidian_project(
     // Create new
     let id = uuid::Uuid::new_v4().to_string();
     conn.execute(
-        "INSERT INTO projects (id, name, description, color, agent_id, obsidian_source, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, 'main', ?5, ?6, ?6)",
-        params![id, name, description, color, obsidian_source, now],
+        "INSERT INTO projects (id, name, description, color, agent_id, status, tags, priority, due_at, obsidian_source, obsidian_mtime, synced_name, synced_description, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, 'main', ?5, ?6, ?7, ?8, ?9, ?10, ?2, ?3, ?11, ?11)",
+        params![
+            id,
+            note.name,
+            note.description,
+            note.color,
+            note.status.unwrap_or("active"),
+            tags,
+            note.priority,
+            note.due_at,
+            note.obsidian_source,
+            note.mtime,
+            now
+        ],
     )?;
     Ok(UpsertResult::Created)
 }
 
+/// Change marker for everything a board export shows: card count and latest
+/// update, plus project names and dependency edges, which change without
+/// touching a card.
+pub fn kanban_fingerprint(conn: &Connection) -> Result<String> {
+    use sha2::{Digest, Sha256};
+    let (count, updated): (i64, Option<i64>) =
+        conn.query_row("SELECT COUNT(*), MAX(updated_at) FROM kanban_items", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}:{:?}\n", count, updated));
+    for project in list_projects(conn, true)? {
+        hasher.update(format!("{}\t{}\t{}\n", project.id, project.name, project.status));
+    }
+    let mut edges = list_kanban_dependencies(conn)?;
+    edges.sort();
+    for (item_id, blocked_by_id) in edges {
+        hasher.update(format!("{}<{}\n", item_id, blocked_by_id));
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Entity links
+
+/// Replace every outgoing link of one kind from an entity with `target_ids`.
+pub fn replace_entity_links(
+    conn: &Connection,
+    source_type: &str,
+    source_id: &str,
+    target_type: &str,
+    target_ids: &[String],
+) -> Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    conn.execute(
+        "DELETE FROM entity_links WHERE source_type=?1 AND source_id=?2 AND target_type=?3",
+        params![source_type, source_id, target_type],
+    )?;
+    let mut stmt = conn.prepare(
+        "INSERT OR IGNORE INTO entity_links (source_type, source_id, target_type, target_id, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for target_id in target_ids {
+        stmt.execute(params![source_type, source_id, target_type, target_id, now])?;
+    }
+    Ok(())
+}
+
+/// Projects linked to or from the given project, in either direction.
+pub fn get_related_projects(conn: &Connection, project_id: &str) -> Result<Vec<Project>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM projects WHERE id != ?1 AND id IN (
+            SELECT target_id FROM entity_links
+             WHERE source_type='project' AND source_id=?1 AND target_type='project'
+            UNION
+            SELECT source_id FROM entity_links
+             WHERE target_type='project' AND target_id=?1 AND source_type='project'
+         ) ORDER BY name COLLATE NOCASE",
+        PROJECT_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![project_id], row_to_project)?;
+    let mut projects = Vec::new();
+    for p in rows {
+        projects.push(p?);
+    }
+    Ok(projects)
+}
+
 // Kanban items
 
 pub fn create_kanban_item(conn: &Connection, item: &KanbanItem) -> Result<()> {
     conn.execute(
-        "INSERT INTO kanban_items (id, project_id, source_type, source_id, title, description, column, position, status, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        "INSERT INTO kanban_items (id, project_id, source_type, source_id, title, description, \"column\", position, status, recurrence, completed_at, parent_id, due_at, agent_id, thread_id, priority, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
         params![
             item.id,
             item.project_id,
@@ -548,23 +2435,59 @@ pub fn create_kanban_item(conn: &Connection, item: &KanbanItem) -> Result<()> {
             item.column,
             item.position,
             item.status,
+            item.recurrence,
+            item.completed_at,
+            item.parent_id,
+            item.due_at,
+            item.agent_id,
+            item.thread_id,
+            item.priority,
             item.created_at,
             item.updated_at,
         ],
     )?;
+    log_kanban_move(conn, &item.id, None, &item.column, item.created_at)?;
     Ok(())
 }
 
-pub fn list_kanban_items(conn: &Connection, project_id: Option<&str>) -> Result<Vec<KanbanItem>> {
+const KANBAN_COLUMNS: &str = "id, project_id, source_type, source_id, title, description, \"column\", position, status, recurrence, completed_at, parent_id, due_at, agent_id, thread_id, priority, created_at, updated_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum KanbanSort {
+    #[default]
+    Manual,
+    Priority,
+    DueDate,
+    Age,
+}
+
+impl KanbanSort {
+    fn order_clause(self) -> &'static str {
+        match self {
+            KanbanSort::Manual => "\"column\", position",
+            KanbanSort::Priority => "\"column\", priority DESC, position",
+            KanbanSort::DueDate => "\"column\", due_at IS NULL, due_at, position",
+            KanbanSort::Age => "\"column\", created_at, position",
+        }
+    }
+}
+
+pub fn list_kanban_items(conn: &Connection, project_id: Option<&str>, sort: KanbanSort) -> Result<Vec<KanbanItem>> {
     let query = if let Some(_pid) = project_id {
-        "SELECT id, project_id, source_type, source_id, title, description, column, position, status, created_at, updated_at
-         FROM kanban_items WHERE project_id=?1 AND status='active' ORDER BY column, position"
+        format!(
+            "SELECT {} FROM kanban_items WHERE project_id=?1 AND status='active' ORDER BY {}",
+            KANBAN_COLUMNS,
+            sort.order_clause()
+        )
     } else {
-        "SELECT id, project_id, source_type, source_id, title, description, column, position, status, created_at, updated_at
-         FROM kanban_items WHERE status='active' ORDER BY column, position"
+        format!(
+            "SELECT {} FROM kanban_items WHERE status='active' ORDER BY {}",
+            KANBAN_COLUMNS,
+            sort.order_clause()
+        )
     };
 
-    let mut stmt = conn.prepare(query)?;
+    let mut stmt = conn.prepare(&query)?;
     let rows = if let Some(pid) = project_id {
         stmt.query_map(params![pid], row_to_kanban_item)?
     } else {
@@ -575,6 +2498,15 @@ pub fn list_kanban_items(conn: &Connection, project_id: Option<&str>) -> Result<
     for row in rows {
         items.push(row?);
     }
+    drop(stmt);
+
+    // Attach open blockers so the board can show blocked status
+    let blockers = list_open_blockers(conn)?;
+    for item in &mut items {
+        if let Some(ids) = blockers.get(&item.id) {
+            item.blocked_by = ids.clone();
+        }
+    }
     Ok(items)
 }
 
@@ -589,69 +2521,1439 @@ fn row_to_kanban_item(row: &rusqlite::Row) -> rusqlite::Result<KanbanItem> {
         column: row.get(6)?,
         position: row.get(7)?,
         status: row.get(8)?,
-        created_at: row.get(9)?,
-        updated_at: row.get(10)?,
+        recurrence: row.get(9)?,
+        completed_at: row.get(10)?,
+        parent_id: row.get(11)?,
+        due_at: row.get(12)?,
+        agent_id: row.get(13)?,
+        thread_id: row.get(14)?,
+        priority: row.get(15)?,
+        created_at: row.get(16)?,
+        updated_at: row.get(17)?,
+        blocked_by: Vec::new(),
     })
 }
 
-pub fn update_kanban_item(
-    conn: &Connection,
-    id: &str,
-    title: Option<&str>,
-    description: Option<&str>,
-    column: Option<&str>,
-    position: Option<i32>,
-    status: Option<&str>,
-) -> Result<()> {
-    let now = chrono::Utc::now().timestamp_millis();
+pub fn get_kanban_item(conn: &Connection, id: &str) -> Result<Option<KanbanItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM kanban_items WHERE id=?1",
+        KANBAN_COLUMNS
+    ))?;
+    let mut item = match stmt.query_map(params![id], row_to_kanban_item)?.next() {
+        Some(row) => row?,
+        None => return Ok(None),
+    };
+    item.blocked_by = list_open_blockers(conn)?.remove(id).unwrap_or_default();
+    Ok(Some(item))
+}
 
-    // Build dynamic UPDATE query
-    let mut updates = vec!["updated_at=?1".to_string()];
-    let mut param_count = 2;
+/// Cards mirrored from task lines in the given note (source_id is `<note>:<line>`).
+pub fn list_obsidian_task_items(conn: &Connection, note: &str) -> Result<Vec<KanbanItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM kanban_items
+         WHERE source_type='obsidian' AND substr(source_id, 1, length(?1) + 1) = ?1 || ':'
+         ORDER BY created_at",
+        KANBAN_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![note], row_to_kanban_item)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+}
 
-    let mut final_params: Vec<String> = vec![now.to_string()];
+pub fn dismiss_obsidian_task(conn: &Connection, note: &str, title: &str) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO dismissed_obsidian_tasks (note, title, dismissed_at) VALUES (?1, ?2, ?3)",
+        params![note, title, chrono::Utc::now().timestamp_millis()],
+    )?;
+    Ok(())
+}
 
-    if let Some(t) = title {
-        updates.push(format!("title=?{}", param_count));
-        final_params.push(t.to_string());
-        param_count += 1;
-    }
-    if let Some(d) = description {
-        updates.push(format!("description=?{}", param_count));
-        final_params.push(d.to_string());
-        param_count += 1;
-    }
-    if let Some(c) = column {
-        updates.push(format!("column=?{}", param_count));
-        final_params.push(c.to_string());
-        param_count += 1;
-    }
-    if let Some(p) = position {
-        updates.push(format!("position=?{}", param_count));
-        final_params.push(p.to_string());
-        param_count += 1;
-    }
-    if let Some(s) = status {
-        updates.push(format!("status=?{}", param_count));
-        final_params.push(s.to_string());
-        param_count += 1;
+/// Titles of the tasks in `note` whose cards were deleted.
+pub fn list_dismissed_obsidian_tasks(conn: &Connection, note: &str) -> Result<std::collections::HashSet<String>> {
+    let mut stmt = conn.prepare("SELECT title FROM dismissed_obsidian_tasks WHERE note = ?1")?;
+    let titles = stmt.query_map(params![note], |row| row.get::<_, String>(0))?;
+    Ok(titles.collect::<rusqlite::Result<_>>()?)
+}
+
+pub fn set_kanban_source_id(conn: &Connection, id: &str, source_id: &str) -> Result<()> {
+    let mut update = UpdateBuilder::new("kanban_items");
+    update.set("source_id", source_id.to_string());
+    update.execute(conn, id)?;
+    Ok(())
+}
+
+/// Done cards carrying a recurrence rule that haven't been re-spawned yet.
+pub fn list_recurring_done_items(conn: &Connection) -> Result<Vec<KanbanItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM kanban_items
+         WHERE \"column\"='done' AND status='active'
+           AND recurrence IS NOT NULL AND completed_at IS NOT NULL",
+        KANBAN_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], row_to_kanban_item)?;
+    let mut items = Vec::new();
+    for row in rows {
+        items.push(row?);
     }
+    Ok(items)
+}
+
+pub fn assign_kanban_item(conn: &Connection, id: &str, agent_id: &str, thread_id: &str) -> Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    conn.execute(
+        "UPDATE kanban_items SET agent_id=?1, thread_id=?2, updated_at=?3 WHERE id=?4",
+        params![agent_id, thread_id, now, id],
+    )?;
+    Ok(())
+}
 
-    let query = format!(
-        "UPDATE kanban_items SET {} WHERE id=?{}",
-        updates.join(", "),
-        param_count
-    );
-    final_params.push(id.to_string());
+pub fn set_kanban_priority(conn: &Connection, id: &str, priority: i32) -> Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    conn.execute(
+        "UPDATE kanban_items SET priority=?1, updated_at=?2 WHERE id=?3",
+        params![priority, now, id],
+    )?;
+    Ok(())
+}
 
-    let mut stmt = conn.prepare(&query)?;
-    let params_refs: Vec<&dyn rusqlite::ToSql> = final_params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
-    stmt.execute(params_refs.as_slice())?;
+/// A new due date also gets a new deadline nudge and overdue notice.
+pub fn set_kanban_due_at(conn: &Connection, id: &str, due_at: Option<i64>) -> Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    conn.execute(
+        "UPDATE kanban_items SET due_at=?1, nudged_at=NULL, overdue_notified_at=NULL, updated_at=?2 WHERE id=?3",
+        params![due_at, now, id],
+    )?;
+    Ok(())
+}
+
+/// Open cards due between `now` and `before` (epoch ms) that haven't been
+/// nudged about it yet, soonest first.
+pub fn list_kanban_items_to_nudge(conn: &Connection, now: i64, before: i64) -> Result<Vec<KanbanItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM kanban_items
+         WHERE due_at >= ?1 AND due_at < ?2 AND nudged_at IS NULL
+           AND status='active' AND \"column\" != 'done' AND completed_at IS NULL
+         ORDER BY due_at",
+        KANBAN_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![now, before], row_to_kanban_item)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<KanbanItem>>>()?)
+}
+
+/// Active cards with a due date, done ones included, soonest first.
+pub fn list_due_kanban_items(conn: &Connection) -> Result<Vec<KanbanItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM kanban_items WHERE due_at IS NOT NULL AND status='active' ORDER BY due_at",
+        KANBAN_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], row_to_kanban_item)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<KanbanItem>>>()?)
+}
+
+pub fn set_kanban_nudged(conn: &Connection, id: &str, at: i64) -> Result<()> {
+    conn.execute("UPDATE kanban_items SET nudged_at=?1 WHERE id=?2", params![at, id])?;
+    Ok(())
+}
 
+/// Open cards past their due date that haven't been notified about it yet,
+/// longest overdue first.
+pub fn list_overdue_kanban_items(conn: &Connection, now: i64) -> Result<Vec<KanbanItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM kanban_items
+         WHERE due_at < ?1 AND overdue_notified_at IS NULL
+           AND status='active' AND \"column\" != 'done' AND completed_at IS NULL
+         ORDER BY due_at",
+        KANBAN_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![now], row_to_kanban_item)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<KanbanItem>>>()?)
+}
+
+pub fn set_kanban_overdue_notified(conn: &Connection, id: &str, at: i64) -> Result<()> {
+    conn.execute("UPDATE kanban_items SET overdue_notified_at=?1 WHERE id=?2", params![at, id])?;
     Ok(())
 }
 
-pub fn delete_kanban_item(conn: &Connection, id: &str) -> Result<()> {
-    conn.execute("DELETE FROM kanban_items WHERE id=?1", params![id])?;
+pub fn set_kanban_recurrence(conn: &Connection, id: &str, recurrence: Option<&str>) -> Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    conn.execute(
+        "UPDATE kanban_items SET recurrence=?1, updated_at=?2 WHERE id=?3",
+        params![recurrence, now, id],
+    )?;
     Ok(())
 }
+
+/// Partial update for a kanban card; `None` leaves a field untouched.
+/// `project_id: Some(None)` explicitly unassigns the project.
+#[derive(Debug, Default)]
+pub struct KanbanItemUpdate<'a> {
+    pub title: Option<&'a str>,
+    pub description: Option<&'a str>,
+    pub column: Option<&'a str>,
+    pub position: Option<i32>,
+    pub status: Option<&'a str>,
+    pub project_id: Option<Option<&'a str>>,
+}
+
+/// A card changing column, as sent in the "kanban:moved" webhook.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnChange {
+    pub item_id: String,
+    pub from: String,
+    pub to: String,
+    pub moved_at: i64,
+}
+
+/// Returns the move when the card changed column, for the caller to publish
+/// once the write is committed.
+pub fn update_kanban_item(conn: &Connection, id: &str, changes: &KanbanItemUpdate) -> Result<Option<ColumnChange>> {
+    let now = chrono::Utc::now().timestamp_millis();
+
+    // Capture the previous column so real moves land in the activity log
+    let moved_from: Option<String> = match changes.column {
+        Some(c) => conn
+            .query_row(
+                "SELECT \"column\" FROM kanban_items WHERE id=?1",
+                params![id],
+                |row| row.get::<_, String>(0),
+            )
+            .ok()
+            .filter(|old| old != c),
+        None => None,
+    };
+
+    let mut update = UpdateBuilder::new("kanban_items");
+    update
+        .set("updated_at", now)
+        .set_opt("title", changes.title.map(str::to_string))
+        .set_opt("description", changes.description.map(str::to_string))
+        .set_opt("column", changes.column.map(str::to_string))
+        .set_opt("position", changes.position)
+        .set_opt("status", changes.status.map(str::to_string))
+        .set_opt("project_id", changes.project_id.map(|p| p.map(str::to_string)));
+    // Stamp completion on the first move into done; clear it when the card leaves
+    match changes.column {
+        Some("done") => {
+            update.set_if_null("completed_at", now);
+        }
+        Some(_) => {
+            update.set("completed_at", Value::Null);
+        }
+        None => {}
+    }
+    update.execute(conn, id)?;
+
+    let (Some(from), Some(to)) = (moved_from, changes.column) else {
+        return Ok(None);
+    };
+    log_kanban_move(conn, id, Some(&from), to, now)?;
+    Ok(Some(ColumnChange {
+        item_id: id.to_string(),
+        from,
+        to: to.to_string(),
+        moved_at: now,
+    }))
+}
+
+// SSH event log
+
+/// Events older than this are pruned as new ones are written.
+const SSH_EVENT_RETENTION_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SshEvent {
+    pub id: i64,
+    /// "connect", "disconnect", "reconnecting", "error", "command" or "forward".
+    pub kind: String,
+    pub host: String,
+    pub detail: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub ok: bool,
+    pub at: i64,
+}
+
+pub fn log_ssh_event(
+    conn: &Connection,
+    kind: &str,
+    host: &str,
+    detail: Option<&str>,
+    duration_ms: Option<i64>,
+    ok: bool,
+) -> Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    conn.execute(
+        "INSERT INTO ssh_events (kind, host, detail, duration_ms, ok, at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![kind, host, detail, duration_ms, ok, now],
+    )?;
+    conn.execute(
+        "DELETE FROM ssh_events WHERE at < ?1",
+        params![now - SSH_EVENT_RETENTION_MS],
+    )?;
+    Ok(())
+}
+
+/// Most recent events first, optionally only those at or after `since`.
+pub fn list_ssh_events(conn: &Connection, since: Option<i64>, limit: u32) -> Result<Vec<SshEvent>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, kind, host, detail, duration_ms, ok, at FROM ssh_events
+         WHERE at >= ?1 ORDER BY at DESC, id DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![since.unwrap_or(0), limit], |row| {
+        Ok(SshEvent {
+            id: row.get(0)?,
+            kind: row.get(1)?,
+            host: row.get(2)?,
+            detail: row.get(3)?,
+            duration_ms: row.get(4)?,
+            ok: row.get(5)?,
+            at: row.get(6)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<SshEvent>>>()?)
+}
+
+// Agent overrides
+
+/// Extra settings for one agent's local openclaw runs.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AgentOverride {
+    pub agent_id: String,
+    /// Appended to `openclaw agent ...`, one argument per entry.
+    #[serde(default)]
+    pub flags: Vec<String>,
+    /// Set on top of the app's environment.
+    #[serde(default)]
+    pub env: std::collections::BTreeMap<String, String>,
+    pub working_dir: Option<String>,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+const AGENT_OVERRIDE_COLUMNS: &str = "agent_id, flags, env, working_dir, updated_at";
+
+/// Flags and env are stored as JSON; NULL when empty.
+fn row_to_agent_override(row: &rusqlite::Row) -> rusqlite::Result<AgentOverride> {
+    let flags: Option<String> = row.get(1)?;
+    let env: Option<String> = row.get(2)?;
+    Ok(AgentOverride {
+        agent_id: row.get(0)?,
+        flags: flags
+            .and_then(|f| serde_json::from_str(&f).ok())
+            .unwrap_or_default(),
+        env: env
+            .and_then(|e| serde_json::from_str(&e).ok())
+            .unwrap_or_default(),
+        working_dir: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+pub fn get_agent_override(conn: &Connection, agent_id: &str) -> Result<Option<AgentOverride>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM agent_overrides WHERE agent_id=?1",
+        AGENT_OVERRIDE_COLUMNS
+    ))?;
+    let mut rows = stmt.query_map(params![agent_id], row_to_agent_override)?;
+    Ok(rows.next().transpose()?)
+}
+
+pub fn list_agent_overrides(conn: &Connection) -> Result<Vec<AgentOverride>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM agent_overrides ORDER BY agent_id",
+        AGENT_OVERRIDE_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], row_to_agent_override)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<AgentOverride>>>()?)
+}
+
+pub fn set_agent_override(conn: &Connection, ov: &AgentOverride) -> Result<()> {
+    let flags = (!ov.flags.is_empty()).then(|| serde_json::to_string(&ov.flags)).transpose()?;
+    let env = (!ov.env.is_empty()).then(|| serde_json::to_string(&ov.env)).transpose()?;
+    conn.execute(
+        "INSERT INTO agent_overrides (agent_id, flags, env, working_dir, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(agent_id) DO UPDATE SET
+            flags=excluded.flags, env=excluded.env,
+            working_dir=excluded.working_dir, updated_at=excluded.updated_at",
+        params![
+            ov.agent_id,
+            flags,
+            env,
+            ov.working_dir,
+            chrono::Utc::now().timestamp_millis()
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn delete_agent_override(conn: &Connection, agent_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM agent_overrides WHERE agent_id=?1", params![agent_id])?;
+    Ok(())
+}
+
+// Agent invocation log
+
+/// Invocations older than this are pruned as new ones are written.
+const AGENT_INVOCATION_RETENTION_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentInvocation {
+    pub id: i64,
+    pub agent_id: String,
+    pub session_id: Option<String>,
+    pub model: Option<String>,
+    pub remote: bool,
+    /// The openclaw command line, with the message redacted unless logging is "full".
+    pub command: String,
+    pub duration_ms: i64,
+    /// `None` for remote runs and runs killed by the timeout.
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub ok: bool,
+    /// Truncated reply; `None` when redacted or the run failed.
+    pub response: Option<String>,
+    pub error: Option<String>,
+    pub at: i64,
+}
+
+/// Insert `inv` (its `id` is ignored) and prune old entries.
+pub fn log_agent_invocation(conn: &Connection, inv: &AgentInvocation) -> Result<()> {
+    conn.execute(
+        "INSERT INTO agent_invocations
+            (agent_id, session_id, model, remote, command, duration_ms, exit_code, timed_out, ok, response, error, at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            inv.agent_id,
+            inv.session_id,
+            inv.model,
+            inv.remote,
+            inv.command,
+            inv.duration_ms,
+            inv.exit_code,
+            inv.timed_out,
+            inv.ok,
+            inv.response,
+            inv.error,
+            inv.at
+        ],
+    )?;
+    conn.execute(
+        "DELETE FROM agent_invocations WHERE at < ?1",
+        params![inv.at - AGENT_INVOCATION_RETENTION_MS],
+    )?;
+    Ok(())
+}
+
+/// Most recent invocations first, optionally for one agent.
+pub fn list_agent_invocations(conn: &Connection, agent_id: Option<&str>, limit: u32) -> Result<Vec<AgentInvocation>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, agent_id, session_id, model, remote, command, duration_ms, exit_code, timed_out, ok, response, error, at
+         FROM agent_invocations
+         WHERE ?1 IS NULL OR agent_id = ?1
+         ORDER BY at DESC, id DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![agent_id, limit], |row| {
+        Ok(AgentInvocation {
+            id: row.get(0)?,
+            agent_id: row.get(1)?,
+            session_id: row.get(2)?,
+            model: row.get(3)?,
+            remote: row.get(4)?,
+            command: row.get(5)?,
+            duration_ms: row.get(6)?,
+            exit_code: row.get(7)?,
+            timed_out: row.get(8)?,
+            ok: row.get(9)?,
+            response: row.get(10)?,
+            error: row.get(11)?,
+            at: row.get(12)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<AgentInvocation>>>()?)
+}
+
+pub fn clear_agent_invocations(conn: &Connection) -> Result<()> {
+    conn.execute("DELETE FROM agent_invocations", [])?;
+    Ok(())
+}
+
+// Proactive follow-up passes
+
+const PROACTIVE_RUN_RETENTION_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProactiveFailure {
+    pub brain_dump_id: String,
+    pub error: String,
+}
+
+/// What one follow-up pass did.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProactiveRun {
+    pub id: i64,
+    pub started_at: i64,
+    pub finished_at: i64,
+    /// Run from "Run now" rather than the schedule.
+    pub manual: bool,
+    pub digest: bool,
+    /// Dumps that were due.
+    pub processed: u32,
+    pub sent: u32,
+    /// Dumps that ran out of unanswered follow-ups and were switched off.
+    pub stopped: u32,
+    pub failures: Vec<ProactiveFailure>,
+    /// Why the pass as a whole failed, if it did.
+    pub error: Option<String>,
+}
+
+/// Insert `run` (its `id` is ignored) and prune old entries.
+pub fn record_proactive_run(conn: &Connection, run: &ProactiveRun) -> Result<()> {
+    conn.execute(
+        "INSERT INTO proactive_runs
+            (started_at, finished_at, manual, digest, processed, sent, stopped, failed, failures, error)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            run.started_at,
+            run.finished_at,
+            run.manual,
+            run.digest,
+            run.processed,
+            run.sent,
+            run.stopped,
+            run.failures.len() as u32,
+            serde_json::to_string(&run.failures)?,
+            run.error
+        ],
+    )?;
+    conn.execute(
+        "DELETE FROM proactive_runs WHERE started_at < ?1",
+        params![run.started_at - PROACTIVE_RUN_RETENTION_MS],
+    )?;
+    Ok(())
+}
+
+/// Most recent passes first.
+pub fn list_proactive_runs(conn: &Connection, limit: u32) -> Result<Vec<ProactiveRun>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, started_at, finished_at, manual, digest, processed, sent, stopped, failures, error
+         FROM proactive_runs ORDER BY started_at DESC, id DESC LIMIT ?1",
+    )?;
+    let rows = stmt.query_map(params![limit], |row| {
+        let failures: Option<String> = row.get(8)?;
+        Ok(ProactiveRun {
+            id: row.get(0)?,
+            started_at: row.get(1)?,
+            finished_at: row.get(2)?,
+            manual: row.get(3)?,
+            digest: row.get(4)?,
+            processed: row.get(5)?,
+            sent: row.get(6)?,
+            stopped: row.get(7)?,
+            failures: failures.and_then(|f| serde_json::from_str(&f).ok()).unwrap_or_default(),
+            error: row.get(9)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<ProactiveRun>>>()?)
+}
+
+// Follow-up outcomes
+
+/// One follow-up sent for one dump. `start_index..end_index` are the
+/// positions in the thread's session the follow-up and the agent's answer
+/// took up; a user message after them (and before the next follow-up in the
+/// thread) is a reply.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowUpOutcome {
+    pub id: i64,
+    pub brain_dump_id: String,
+    pub thread_id: String,
+    /// 1 for the first follow-up on the dump.
+    pub attempt: i32,
+    pub digest: bool,
+    pub sent_at: i64,
+    pub start_index: usize,
+    pub end_index: usize,
+    /// "pending", "replied" or "ignored".
+    pub outcome: String,
+    pub replied_at: Option<i64>,
+}
+
+pub fn create_follow_up_outcome(conn: &Connection, outcome: &FollowUpOutcome) -> Result<()> {
+    conn.execute(
+        "INSERT INTO follow_up_outcomes
+            (brain_dump_id, thread_id, attempt, digest, sent_at, start_index, end_index, outcome, replied_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            outcome.brain_dump_id,
+            outcome.thread_id,
+            outcome.attempt,
+            outcome.digest,
+            outcome.sent_at,
+            outcome.start_index as i64,
+            outcome.end_index as i64,
+            outcome.outcome,
+            outcome.replied_at
+        ],
+    )?;
+    Ok(())
+}
+
+const OUTCOME_COLUMNS: &str =
+    "id, brain_dump_id, thread_id, attempt, digest, sent_at, start_index, end_index, outcome, replied_at";
+
+fn row_to_outcome(row: &rusqlite::Row) -> rusqlite::Result<FollowUpOutcome> {
+    Ok(FollowUpOutcome {
+        id: row.get(0)?,
+        brain_dump_id: row.get(1)?,
+        thread_id: row.get(2)?,
+        attempt: row.get(3)?,
+        digest: row.get(4)?,
+        sent_at: row.get(5)?,
+        start_index: row.get::<_, i64>(6)? as usize,
+        end_index: row.get::<_, i64>(7)? as usize,
+        outcome: row.get(8)?,
+        replied_at: row.get(9)?,
+    })
+}
+
+/// Follow-ups still waiting on a reply, oldest first.
+pub fn list_pending_follow_up_outcomes(conn: &Connection) -> Result<Vec<FollowUpOutcome>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM follow_up_outcomes WHERE outcome='pending' ORDER BY sent_at",
+        OUTCOME_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], row_to_outcome)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<FollowUpOutcome>>>()?)
+}
+
+/// When the next follow-up after `sent_at` went out in the thread, with
+/// where it starts in the session.
+pub fn next_follow_up_in_thread(conn: &Connection, thread_id: &str, sent_at: i64) -> Result<Option<(i64, usize)>> {
+    let mut stmt = conn.prepare(
+        "SELECT sent_at, start_index FROM follow_up_outcomes WHERE thread_id=?1 AND sent_at > ?2
+         ORDER BY sent_at LIMIT 1",
+    )?;
+    let mut rows = stmt.query_map(params![thread_id, sent_at], |row| {
+        Ok((row.get(0)?, row.get::<_, i64>(1)? as usize))
+    })?;
+    Ok(rows.next().transpose()?)
+}
+
+pub fn resolve_follow_up_outcome(conn: &Connection, id: i64, outcome: &str, replied_at: Option<i64>) -> Result<()> {
+    conn.execute(
+        "UPDATE follow_up_outcomes SET outcome=?1, replied_at=?2 WHERE id=?3",
+        params![outcome, replied_at, id],
+    )?;
+    Ok(())
+}
+
+/// Every follow-up sent for one dump, oldest first.
+pub fn list_follow_up_outcomes(conn: &Connection, brain_dump_id: &str) -> Result<Vec<FollowUpOutcome>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM follow_up_outcomes WHERE brain_dump_id=?1 ORDER BY sent_at",
+        OUTCOME_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![brain_dump_id], row_to_outcome)?;
+    Ok(rows.collect::<rusqlite::Result<Vec<FollowUpOutcome>>>()?)
+}
+
+/// Replied, ignored and pending counts for one slice of the follow-ups.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutcomeCounts {
+    pub sent: u32,
+    pub replied: u32,
+    pub ignored: u32,
+    pub pending: u32,
+    /// Replied out of those settled; `None` until one is.
+    pub reply_rate: Option<f64>,
+    /// Median hours from follow-up to reply.
+    pub median_reply_hours: Option<f64>,
+}
+
+impl OutcomeCounts {
+    fn from_rows(rows: &[&(i32, bool, String, Option<i64>)]) -> Self {
+        let mut counts = OutcomeCounts { sent: rows.len() as u32, ..Default::default() };
+        let mut delays = Vec::new();
+        for (_, _, outcome, delay) in rows {
+            match outcome.as_str() {
+                "replied" => {
+                    counts.replied += 1;
+                    delays.extend(*delay);
+                }
+                "ignored" => counts.ignored += 1,
+                _ => counts.pending += 1,
+            }
+        }
+        let settled = counts.replied + counts.ignored;
+        counts.reply_rate = (settled > 0).then(|| counts.replied as f64 / settled as f64);
+        delays.sort_unstable();
+        counts.median_reply_hours = delays.get(delays.len() / 2).map(|ms| *ms as f64 / 3_600_000.0);
+        counts
+    }
+}
+
+/// How proactive follow-ups have landed since `since` (epoch ms): overall,
+/// first versus later attempts, and per-dump versus digest follow-ups.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProactiveEffectiveness {
+    pub overall: OutcomeCounts,
+    pub first_attempt: OutcomeCounts,
+    pub later_attempts: OutcomeCounts,
+    pub per_dump: OutcomeCounts,
+    pub digest: OutcomeCounts,
+}
+
+pub fn proactive_effectiveness(conn: &Connection, since: i64) -> Result<ProactiveEffectiveness> {
+    let mut stmt = conn.prepare(
+        "SELECT attempt, digest, outcome, replied_at - sent_at FROM follow_up_outcomes WHERE sent_at >= ?1",
+    )?;
+    let rows = stmt
+        .query_map(params![since], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+        .collect::<rusqlite::Result<Vec<(i32, bool, String, Option<i64>)>>>()?;
+    let slice = |keep: &dyn Fn(i32, bool) -> bool| {
+        OutcomeCounts::from_rows(&rows.iter().filter(|r| keep(r.0, r.1)).collect::<Vec<_>>())
+    };
+    Ok(ProactiveEffectiveness {
+        overall: slice(&|_, _| true),
+        first_attempt: slice(&|attempt, _| attempt <= 1),
+        later_attempts: slice(&|attempt, _| attempt > 1),
+        per_dump: slice(&|_, digest| !digest),
+        digest: slice(&|_, digest| digest),
+    })
+}
+
+// Events held during do-not-disturb
+
+pub const DEFERRED_EVENT: &str = "event";
+pub const DEFERRED_NOTIFICATION: &str = "notification";
+
+#[derive(Debug, Clone)]
+pub struct DeferredEvent {
+    pub id: i64,
+    /// `DEFERRED_EVENT` (emitted to the frontend as `name`) or `DEFERRED_NOTIFICATION`.
+    pub kind: String,
+    pub name: String,
+    pub payload: serde_json::Value,
+    pub created_at: i64,
+}
+
+/// Hold an event until do-not-disturb ends, and with `deliver_at` (epoch ms)
+/// also until then.
+pub fn defer_event(
+    conn: &Connection,
+    kind: &str,
+    name: &str,
+    payload: &serde_json::Value,
+    deliver_at: Option<i64>,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO deferred_events (kind, name, payload, created_at, deliver_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![kind, name, payload.to_string(), chrono::Utc::now().timestamp_millis(), deliver_at],
+    )?;
+    Ok(())
+}
+
+/// Held events due by `now`, oldest first.
+pub fn list_deferred_events(conn: &Connection, now: i64) -> Result<Vec<DeferredEvent>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, kind, name, payload, created_at FROM deferred_events
+         WHERE deliver_at IS NULL OR deliver_at <= ?1 ORDER BY id",
+    )?;
+    let rows = stmt.query_map(params![now], |row| {
+        let payload: String = row.get(3)?;
+        Ok(DeferredEvent {
+            id: row.get(0)?,
+            kind: row.get(1)?,
+            name: row.get(2)?,
+            payload: serde_json::from_str(&payload).unwrap_or_default(),
+            created_at: row.get(4)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<DeferredEvent>>>()?)
+}
+
+/// Held events due by `now`.
+pub fn count_deferred_events(conn: &Connection, now: i64) -> Result<u32> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*) FROM deferred_events WHERE deliver_at IS NULL OR deliver_at <= ?1",
+        params![now],
+        |row| row.get(0),
+    )?)
+}
+
+/// When the next held event waiting on a time after `now` comes due.
+pub fn next_deferred_delivery(conn: &Connection, now: i64) -> Result<Option<i64>> {
+    Ok(conn.query_row(
+        "SELECT MIN(deliver_at) FROM deferred_events WHERE deliver_at > ?1",
+        params![now],
+        |row| row.get(0),
+    )?)
+}
+
+pub fn delete_deferred_event(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("DELETE FROM deferred_events WHERE id=?1", params![id])?;
+    Ok(())
+}
+
+// Scheduled jobs
+
+/// Runs older than this are pruned whenever a new one starts.
+const JOB_RUN_RETENTION_MS: i64 = 30 * 24 * 60 * 60 * 1000;
+
+/// What's stored for a scheduled job; jobs never seen yet have no row.
+#[derive(Debug, Clone)]
+pub struct JobRecord {
+    pub enabled: bool,
+    /// Cron expression replacing the job's default.
+    pub schedule: Option<String>,
+    pub last_run_at: Option<i64>,
+}
+
+pub fn get_job(conn: &Connection, id: &str) -> Result<Option<JobRecord>> {
+    let mut stmt = conn.prepare("SELECT enabled, schedule, last_run_at FROM jobs WHERE id=?1")?;
+    let mut rows = stmt.query_map(params![id], |row| {
+        Ok(JobRecord {
+            enabled: row.get(0)?,
+            schedule: row.get(1)?,
+            last_run_at: row.get(2)?,
+        })
+    })?;
+    Ok(rows.next().transpose()?)
+}
+
+pub fn set_job_enabled(conn: &Connection, id: &str, enabled: bool) -> Result<()> {
+    conn.execute(
+        "INSERT INTO jobs (id, enabled) VALUES (?1, ?2)
+         ON CONFLICT(id) DO UPDATE SET enabled=excluded.enabled",
+        params![id, enabled],
+    )?;
+    Ok(())
+}
+
+pub fn set_job_schedule(conn: &Connection, id: &str, schedule: Option<&str>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO jobs (id, schedule) VALUES (?1, ?2)
+         ON CONFLICT(id) DO UPDATE SET schedule=excluded.schedule",
+        params![id, schedule],
+    )?;
+    Ok(())
+}
+
+/// Record that `id` started at `at`, returning the new run's id.
+pub fn start_job_run(conn: &Connection, id: &str, at: i64, manual: bool) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO jobs (id, last_run_at) VALUES (?1, ?2)
+         ON CONFLICT(id) DO UPDATE SET last_run_at=excluded.last_run_at",
+        params![id, at],
+    )?;
+    conn.execute(
+        "INSERT INTO job_runs (job_id, manual, started_at) VALUES (?1, ?2, ?3)",
+        params![id, manual, at],
+    )?;
+    let run_id = conn.last_insert_rowid();
+    conn.execute(
+        "DELETE FROM job_runs WHERE started_at < ?1",
+        params![at - JOB_RUN_RETENTION_MS],
+    )?;
+    Ok(run_id)
+}
+
+pub fn finish_job_run(conn: &Connection, run_id: i64, at: i64, detail: Option<&str>, error: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE job_runs SET finished_at=?1, ok=?2, detail=?3, error=?4 WHERE id=?5",
+        params![at, error.is_none(), detail, error, run_id],
+    )?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobRun {
+    pub id: i64,
+    pub job_id: String,
+    /// Started by the user rather than the schedule.
+    pub manual: bool,
+    pub started_at: i64,
+    /// `None` while still running, or if the app quit mid-run.
+    pub finished_at: Option<i64>,
+    pub ok: Option<bool>,
+    pub detail: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Most recent runs first, optionally for one job.
+pub fn list_job_runs(conn: &Connection, job_id: Option<&str>, limit: u32) -> Result<Vec<JobRun>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, job_id, manual, started_at, finished_at, ok, detail, error
+         FROM job_runs
+         WHERE ?1 IS NULL OR job_id = ?1
+         ORDER BY started_at DESC, id DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![job_id, limit], |row| {
+        Ok(JobRun {
+            id: row.get(0)?,
+            job_id: row.get(1)?,
+            manual: row.get(2)?,
+            started_at: row.get(3)?,
+            finished_at: row.get(4)?,
+            ok: row.get(5)?,
+            detail: row.get(6)?,
+            error: row.get(7)?,
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<Vec<JobRun>>>()?)
+}
+
+// Kanban activity log
+
+#[derive(Debug, Clone)]
+pub struct KanbanMove {
+    pub item_id: String,
+    pub from_column: Option<String>,
+    pub to_column: String,
+    pub at: i64,
+}
+
+pub fn log_kanban_move(conn: &Connection, item_id: &str, from: Option<&str>, to: &str, at: i64) -> Result<()> {
+    conn.execute(
+        "INSERT INTO kanban_activity (item_id, from_column, to_column, at) VALUES (?1, ?2, ?3, ?4)",
+        params![item_id, from, to, at],
+    )?;
+    Ok(())
+}
+
+/// All column transitions, ordered per item, optionally scoped to one project.
+pub fn list_kanban_activity(conn: &Connection, project_id: Option<&str>) -> Result<Vec<KanbanMove>> {
+    let map = |row: &rusqlite::Row| {
+        Ok(KanbanMove {
+            item_id: row.get(0)?,
+            from_column: row.get(1)?,
+            to_column: row.get(2)?,
+            at: row.get(3)?,
+        })
+    };
+    let rows = if let Some(pid) = project_id {
+        let mut stmt = conn.prepare(
+            "SELECT a.item_id, a.from_column, a.to_column, a.at
+             FROM kanban_activity a JOIN kanban_items k ON k.id = a.item_id
+             WHERE k.project_id=?1 ORDER BY a.item_id, a.at, a.id",
+        )?;
+        let rows = stmt.query_map(params![pid], map)?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()?
+    } else {
+        let mut stmt = conn.prepare(
+            "SELECT item_id, from_column, to_column, at
+             FROM kanban_activity ORDER BY item_id, at, id",
+        )?;
+        let rows = stmt.query_map([], map)?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()?
+    };
+    Ok(rows)
+}
+
+// Kanban dependencies
+
+pub fn add_kanban_dependency(conn: &Connection, item_id: &str, blocked_by_id: &str) -> Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    conn.execute(
+        "INSERT OR IGNORE INTO kanban_dependencies (item_id, blocked_by_id, created_at) VALUES (?1, ?2, ?3)",
+        params![item_id, blocked_by_id, now],
+    )?;
+    Ok(())
+}
+
+pub fn remove_kanban_dependency(conn: &Connection, item_id: &str, blocked_by_id: &str) -> Result<()> {
+    conn.execute(
+        "DELETE FROM kanban_dependencies WHERE item_id=?1 AND blocked_by_id=?2",
+        params![item_id, blocked_by_id],
+    )?;
+    Ok(())
+}
+
+/// Every blocked-by edge as (item_id, blocked_by_id), regardless of status.
+pub fn list_kanban_dependencies(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare("SELECT item_id, blocked_by_id FROM kanban_dependencies")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+}
+
+/// Map of item id → blockers that are still active and not done.
+pub fn list_open_blockers(conn: &Connection) -> Result<std::collections::HashMap<String, Vec<String>>> {
+    let mut stmt = conn.prepare(
+        "SELECT d.item_id, d.blocked_by_id
+         FROM kanban_dependencies d JOIN kanban_items b ON b.id = d.blocked_by_id
+         WHERE b.\"column\" != 'done' AND b.status = 'active'",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    let mut map: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
+    for row in rows {
+        let (item, blocker) = row?;
+        map.entry(item).or_default().push(blocker);
+    }
+    Ok(map)
+}
+
+/// Cards that list `blocked_by_id` as a blocker.
+pub fn list_kanban_dependents(conn: &Connection, blocked_by_id: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT item_id FROM kanban_dependencies WHERE blocked_by_id=?1")?;
+    let rows = stmt.query_map(params![blocked_by_id], |row| row.get(0))?;
+    Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+}
+
+// ── Search ───────────────────────────────────────────────────────────────────
+
+/// What `search` can find.
+pub const SEARCH_KINDS: &[&str] = &["thread", "brain_dump", "kanban"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    /// "thread", "brain_dump" or "kanban".
+    pub kind: String,
+    pub id: String,
+    pub project_id: Option<String>,
+    /// Thread name or card title; a dump's first line.
+    pub title: String,
+    /// The matching text with hits wrapped in `[` `]`.
+    pub snippet: String,
+    /// bm25 score; lower is a better match.
+    pub rank: f64,
+}
+
+/// Turn what the user typed into an FTS5 query: each word must appear, the
+/// last one as a prefix so results show up while typing. Quoting every word
+/// keeps FTS syntax characters from erroring.
+fn fts_query(query: &str) -> Option<String> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .map(|w| w.replace('"', ""))
+        .filter(|w| !w.is_empty())
+        .map(|w| format!("\"{}\"", w))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", words.join(" ")))
+}
+
+/// Best matches for `query` across threads, brain dumps and cards, titles
+/// weighted above body text. `kinds` narrows it to some of `SEARCH_KINDS`.
+pub fn search(conn: &Connection, query: &str, kinds: &[String], limit: u32) -> Result<Vec<SearchHit>> {
+    let Some(fts) = fts_query(query) else {
+        return Ok(Vec::new());
+    };
+    let mut values = vec![Value::Text(fts)];
+    let mut sql = "SELECT kind, ref_id, project_id, title, body,
+                snippet(search_index, -1, '[', ']', '…', 12),
+                bm25(search_index, 0, 0, 0, 10.0, 1.0) AS rank
+         FROM search_index WHERE search_index MATCH ?1"
+        .to_string();
+    if !kinds.is_empty() {
+        let placeholders: Vec<String> = kinds
+            .iter()
+            .map(|kind| {
+                values.push(Value::Text(kind.clone()));
+                format!("?{}", values.len())
+            })
+            .collect();
+        sql.push_str(&format!(" AND kind IN ({})", placeholders.join(", ")));
+    }
+    values.push(Value::Integer(limit as i64));
+    sql.push_str(&format!(" ORDER BY rank LIMIT ?{}", values.len()));
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(values.iter()), |row| {
+        let title: String = row.get(3)?;
+        let body: String = row.get(4)?;
+        Ok(SearchHit {
+            kind: row.get(0)?,
+            id: row.get(1)?,
+            project_id: row.get(2)?,
+            title: if title.is_empty() {
+                body.lines().next().unwrap_or_default().chars().take(80).collect()
+            } else {
+                title
+            },
+            snippet: row.get(5)?,
+            rank: row.get(6)?,
+        })
+    })?;
+    Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+}
+
+// ── Audit log ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub id: i64,
+    /// The table changed: "projects", "threads", "brain_dumps", "kanban_items" or "settings".
+    pub entity_type: String,
+    pub entity_id: String,
+    /// "create", "update" or "delete".
+    pub op: String,
+    /// The row before the change; `None` for a create.
+    pub before: Option<serde_json::Value>,
+    /// The row after the change; `None` for a delete.
+    pub after: Option<serde_json::Value>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditFilter {
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub op: Option<String>,
+    /// Epoch ms, inclusive.
+    pub since: Option<i64>,
+    /// Epoch ms, exclusive.
+    pub until: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+fn audit_entry(row: &rusqlite::Row) -> rusqlite::Result<AuditEntry> {
+    let json = |i: usize| -> rusqlite::Result<Option<serde_json::Value>> {
+        Ok(row
+            .get::<_, Option<String>>(i)?
+            .and_then(|s| serde_json::from_str(&s).ok()))
+    };
+    Ok(AuditEntry {
+        id: row.get(0)?,
+        entity_type: row.get(1)?,
+        entity_id: row.get(2)?,
+        op: row.get(3)?,
+        before: json(4)?,
+        after: json(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+/// Logged changes, newest first.
+pub fn list_audit_log(conn: &Connection, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, entity_type, entity_id, op, before, after, created_at FROM audit_log
+         WHERE (?1 IS NULL OR entity_type = ?1)
+           AND (?2 IS NULL OR entity_id = ?2)
+           AND (?3 IS NULL OR op = ?3)
+           AND (?4 IS NULL OR created_at >= ?4)
+           AND (?5 IS NULL OR created_at < ?5)
+         ORDER BY id DESC LIMIT ?6",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            filter.entity_type,
+            filter.entity_id,
+            filter.op,
+            filter.since,
+            filter.until,
+            filter.limit.unwrap_or(100)
+        ],
+        audit_entry,
+    )?;
+    Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+}
+
+fn json_to_sql(value: &serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
+
+/// Put an entity back the way it was before entry `id`: a create is deleted,
+/// an update's old values written back, a delete re-inserted. The undo is
+/// itself logged. Returns the entry undone.
+pub fn revert_audit_entry(conn: &Connection, id: i64) -> Result<AuditEntry> {
+    let mut stmt = conn.prepare(
+        "SELECT id, entity_type, entity_id, op, before, after, created_at FROM audit_log WHERE id = ?1",
+    )?;
+    let entry = stmt
+        .query_map(params![id], audit_entry)?
+        .next()
+        .transpose()?
+        .ok_or_else(|| anyhow!("Audit entry {} not found", id))?;
+    let (table, key, _) = AUDITED
+        .iter()
+        .find(|(table, _, _)| *table == entry.entity_type)
+        .ok_or_else(|| anyhow!("Can't undo changes to {}", entry.entity_type))?;
+    // Only columns the table still has
+    let columns = table_columns(conn, table)?;
+    let before = entry.before.as_ref().and_then(|b| b.as_object());
+    let fields: Vec<(&String, Value)> = before
+        .map(|b| {
+            b.iter()
+                .filter(|(c, _)| columns.contains(c))
+                .map(|(c, v)| (c, json_to_sql(v)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let entity_table = *table;
+    let table = quote_ident(table);
+    let key = quote_ident(key);
+    let tx = conn.unchecked_transaction()?;
+    match (entry.op.as_str(), before) {
+        // Settings aren't entities, so nothing hangs off them
+        ("create", _) if entity_table == "settings" => {
+            tx.execute(&format!("DELETE FROM {} WHERE {} = ?1", table, key), params![entry.entity_id])?;
+        }
+        // Same delete rules as deleting it from the UI
+        ("create", _) => relations::delete_row(&tx, entity_table, &entry.entity_id)?,
+        ("update", Some(_)) => {
+            let assignments: Vec<String> =
+                fields.iter().enumerate().map(|(i, (c, _))| format!("{} = ?{}", quote_ident(c), i + 2)).collect();
+            let mut values = vec![Value::Text(entry.entity_id.clone())];
+            values.extend(fields.iter().map(|(_, v)| v.clone()));
+            let changed = tx.execute(
+                &format!("UPDATE {} SET {} WHERE {} = ?1", table, assignments.join(", "), key),
+                rusqlite::params_from_iter(values.iter()),
+            )?;
+            if changed == 0 {
+                return Err(anyhow!("It has been deleted since; undo the delete first"));
+            }
+        }
+        ("delete", Some(_)) => {
+            let names: Vec<String> = fields.iter().map(|(c, _)| quote_ident(c)).collect();
+            let placeholders: Vec<String> = (1..=fields.len()).map(|i| format!("?{}", i)).collect();
+            tx.execute(
+                &format!("INSERT INTO {} ({}) VALUES ({})", table, names.join(", "), placeholders.join(", ")),
+                rusqlite::params_from_iter(fields.iter().map(|(_, v)| v)),
+            )
+            .map_err(|e| anyhow!("Can't restore it: {}", e))?;
+        }
+        _ => return Err(anyhow!("Audit entry {} has nothing to restore", id)),
+    }
+    tx.commit()?;
+    Ok(entry)
+}
+
+/// Drop audit entries older than `before` (epoch ms). Returns how many went.
+pub fn prune_audit_log(conn: &Connection, before: i64) -> Result<u32> {
+    Ok(conn.execute("DELETE FROM audit_log WHERE created_at < ?1", params![before])? as u32)
+}
+
+// ── Maintenance ──────────────────────────────────────────────────────────────
+
+/// Maintenance reports kept for the stats screen.
+const MAINTENANCE_RUNS_KEPT: u32 = 30;
+
+/// What one maintenance pass did.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceReport {
+    pub ran_at: i64,
+    pub duration_ms: i64,
+    /// `integrity_check` findings; empty when the database is sound.
+    pub integrity: Vec<String>,
+    /// WAL frames copied back into the database.
+    pub checkpointed: i64,
+    /// The checkpoint couldn't finish because a reader was active.
+    pub checkpoint_busy: bool,
+    /// Local threads deleted because their session file was gone on two
+    /// runs in a row.
+    pub orphan_threads: Vec<String>,
+    /// Cards unlinked from brain dumps that no longer exist.
+    pub orphan_cards: u32,
+    pub audit_pruned: u32,
+    /// Attachment files no longer used by anything; absent from older reports.
+    #[serde(default)]
+    pub blobs_removed: u32,
+}
+
+/// `PRAGMA optimize`: refresh query planner statistics where they're stale.
+pub fn optimize(conn: &Connection) -> Result<()> {
+    conn.execute_batch("PRAGMA optimize;")?;
+    Ok(())
+}
+
+/// Copy the WAL back into the database and truncate it. Returns (busy,
+/// frames checkpointed).
+pub fn checkpoint(conn: &Connection) -> Result<(bool, i64)> {
+    Ok(conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+        Ok((row.get::<_, i64>(0)? != 0, row.get::<_, i64>(2)?))
+    })?)
+}
+
+/// Cards made from a brain dump that has since been deleted become plain
+/// cards. Returns how many changed.
+pub fn detach_cards_from_missing_dumps(conn: &Connection) -> Result<u32> {
+    Ok(conn.execute(
+        "UPDATE kanban_items SET source_type = 'manual', source_id = NULL
+         WHERE source_type = 'brain_dump' AND source_id IS NOT NULL
+           AND source_id NOT IN (SELECT id FROM brain_dumps)",
+        [],
+    )? as u32)
+}
+
+/// Threads found without a session file on the last maintenance run.
+pub fn list_missing_sessions(conn: &Connection) -> Result<std::collections::HashSet<String>> {
+    let mut stmt = conn.prepare("SELECT thread_id FROM missing_sessions")?;
+    let ids = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    Ok(ids.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Replace the missing-session list with this run's findings, keeping when
+/// each was first noticed.
+pub fn set_missing_sessions(conn: &Connection, thread_ids: &[String], now: i64) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    let keep: Vec<Value> = thread_ids.iter().map(|id| Value::Text(id.clone())).collect();
+    tx.execute(
+        &format!(
+            "DELETE FROM missing_sessions WHERE thread_id NOT IN ({})",
+            vec!["?"; keep.len()].join(", ")
+        ),
+        rusqlite::params_from_iter(keep.iter()),
+    )?;
+    for id in thread_ids {
+        tx.execute(
+            "INSERT OR IGNORE INTO missing_sessions (thread_id, missing_since) VALUES (?1, ?2)",
+            params![id, now],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn record_maintenance_run(conn: &Connection, report: &MaintenanceReport) -> Result<()> {
+    conn.execute(
+        "INSERT INTO maintenance_runs (ran_at, report) VALUES (?1, ?2)",
+        params![report.ran_at, serde_json::to_string(report)?],
+    )?;
+    conn.execute(
+        "DELETE FROM maintenance_runs WHERE id NOT IN
+            (SELECT id FROM maintenance_runs ORDER BY id DESC LIMIT ?1)",
+        params![MAINTENANCE_RUNS_KEPT],
+    )?;
+    Ok(())
+}
+
+pub fn last_maintenance_run(conn: &Connection) -> Result<Option<MaintenanceReport>> {
+    let mut stmt = conn.prepare("SELECT report FROM maintenance_runs ORDER BY id DESC LIMIT 1")?;
+    let mut rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    match rows.next().transpose()? {
+        Some(report) => Ok(serde_json::from_str(&report).ok()),
+        None => Ok(None),
+    }
+}
+
+/// Size and shape of the app database.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbStats {
+    pub path: String,
+    pub db_bytes: u64,
+    pub wal_bytes: u64,
+    /// Pages free for reuse; a large share means VACUUM would shrink the file.
+    pub free_pages: i64,
+    pub page_count: i64,
+    pub schema_version: u32,
+    /// Rows per table, largest first.
+    pub tables: Vec<(String, i64)>,
+    pub last_maintenance: Option<MaintenanceReport>,
+}
+
+pub fn db_stats(conn: &Connection) -> Result<DbStats> {
+    let path = db_path();
+    let size = |p: &Path| std::fs::metadata(p).map(|m| m.len()).unwrap_or(0);
+    let mut wal = path.clone().into_os_string();
+    wal.push("-wal");
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+           AND sql NOT LIKE 'CREATE VIRTUAL TABLE%' AND name NOT LIKE 'search_index_%'",
+    )?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    let mut tables = Vec::new();
+    for name in names {
+        let count: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM {}", quote_ident(&name)), [], |row| row.get(0))?;
+        tables.push((name, count));
+    }
+    tables.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    Ok(DbStats {
+        db_bytes: size(&path),
+        wal_bytes: size(Path::new(&wal)),
+        path: path.to_string_lossy().to_string(),
+        free_pages: conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?,
+        page_count: conn.query_row("PRAGMA page_count", [], |row| row.get(0))?,
+        schema_version: schema_version(conn)?,
+        tables,
+        last_maintenance: last_maintenance_run(conn)?,
+    })
+}
+
+// ── Workspace statistics ─────────────────────────────────────────────────────
+
+/// What happened between two instants (epoch ms, end exclusive).
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityCounts {
+    pub threads_created: u32,
+    /// Threads with a message in the range.
+    pub threads_active: u32,
+    pub dumps_captured: u32,
+    pub dumps_resolved: u32,
+    pub cards_created: u32,
+    pub cards_completed: u32,
+}
+
+pub fn activity_counts(conn: &Connection, start: i64, end: i64) -> Result<ActivityCounts> {
+    let count = |sql: &str| -> Result<u32> { Ok(conn.query_row(sql, params![start, end], |row| row.get(0))?) };
+    Ok(ActivityCounts {
+        threads_created: count("SELECT COUNT(*) FROM threads WHERE created_at >= ?1 AND created_at < ?2")?,
+        threads_active: count("SELECT COUNT(*) FROM threads WHERE last_message_at >= ?1 AND last_message_at < ?2")?,
+        dumps_captured: count("SELECT COUNT(*) FROM brain_dumps WHERE created_at >= ?1 AND created_at < ?2")?,
+        dumps_resolved: count(
+            "SELECT COUNT(*) FROM brain_dumps WHERE status='done' AND updated_at >= ?1 AND updated_at < ?2",
+        )?,
+        cards_created: count("SELECT COUNT(*) FROM kanban_items WHERE created_at >= ?1 AND created_at < ?2")?,
+        cards_completed: count("SELECT COUNT(*) FROM kanban_items WHERE completed_at >= ?1 AND completed_at < ?2")?,
+    })
+}
+
+/// How much there is right now.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceTotals {
+    pub projects: u32,
+    pub archived_projects: u32,
+    pub threads: u32,
+    pub open_dumps: u32,
+    pub resolved_dumps: u32,
+    pub open_cards: u32,
+    pub completed_cards: u32,
+    /// Open cards per column.
+    pub cards_by_column: Vec<(String, u32)>,
+}
+
+pub fn workspace_totals(conn: &Connection) -> Result<WorkspaceTotals> {
+    let count = |sql: &str| -> Result<u32> { Ok(conn.query_row(sql, [], |row| row.get(0))?) };
+    let mut stmt = conn.prepare(
+        "SELECT \"column\", COUNT(*) FROM kanban_items
+         WHERE status='active' AND completed_at IS NULL
+         GROUP BY \"column\" ORDER BY COUNT(*) DESC",
+    )?;
+    let cards_by_column = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(WorkspaceTotals {
+        projects: count("SELECT COUNT(*) FROM projects WHERE status != 'archived'")?,
+        archived_projects: count("SELECT COUNT(*) FROM projects WHERE status = 'archived'")?,
+        threads: count("SELECT COUNT(*) FROM threads")?,
+        open_dumps: count("SELECT COUNT(*) FROM brain_dumps WHERE status IN ('open', 'in_progress')")?,
+        resolved_dumps: count("SELECT COUNT(*) FROM brain_dumps WHERE status='done'")?,
+        open_cards: count("SELECT COUNT(*) FROM kanban_items WHERE status='active' AND completed_at IS NULL")?,
+        completed_cards: count("SELECT COUNT(*) FROM kanban_items WHERE completed_at IS NOT NULL")?,
+        cards_by_column,
+    })
+}
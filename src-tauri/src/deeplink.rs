@@ -0,0 +1,146 @@
+use crate::{db, AppState};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+/// Registered in tauri.conf.json under `plugins.deep-link`.
+pub const SCHEME: &str = "openclaw-chat";
+
+/// Where a link sends the UI, sent as "deeplink:open".
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Navigation {
+    #[serde(rename_all = "camelCase")]
+    Thread { thread_id: String },
+    #[serde(rename_all = "camelCase")]
+    Project { project_id: String },
+    /// Text from `dump/new` to pre-fill the capture box with. Nothing is
+    /// saved until the user confirms it: any page or email can open a link.
+    #[serde(rename_all = "camelCase")]
+    Dump {
+        content: String,
+        project_id: Option<String>,
+    },
+}
+
+/// The link the app was launched with, kept until the UI is up to ask for
+/// it: "deeplink:open" goes out before anything listens.
+static PENDING: Mutex<Option<Navigation>> = Mutex::new(None);
+
+/// Handle links opened while the app runs, and the one it was launched
+/// with, if any.
+pub fn init(app: &tauri::App) {
+    let deep_link = app.deep_link();
+    // macOS registers the scheme from the bundle; elsewhere it's done at runtime
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = deep_link.register_all() {
+        eprintln!("[deeplink] Couldn't register {}://: {}", SCHEME, e);
+    }
+    let handle = app.handle().clone();
+    deep_link.on_open_url(move |event| {
+        for url in event.urls() {
+            let app = handle.clone();
+            tauri::async_runtime::spawn(async move { open(&app, &url, false).await });
+        }
+    });
+    if let Ok(Some(urls)) = deep_link.get_current() {
+        let handle = app.handle().clone();
+        tauri::async_runtime::spawn(async move {
+            for url in urls {
+                open(&handle, &url, true).await;
+            }
+        });
+    }
+}
+
+/// Navigation from the link the app was launched with, once.
+pub fn take_pending() -> Option<Navigation> {
+    PENDING.lock().unwrap().take()
+}
+
+/// Carry out a link and bring the window forward. Failures are sent as
+/// "deeplink:error" since there's no caller to return them to.
+async fn open(app: &AppHandle, url: &Url, at_launch: bool) {
+    focus_window(app);
+    match navigate(app, url).await {
+        Ok(navigation) => {
+            // At launch the UI asks for it once it's up; sending it as well
+            // would have it handled twice
+            if at_launch {
+                *PENDING.lock().unwrap() = Some(navigation);
+            } else {
+                show(app, &navigation);
+            }
+        }
+        Err(e) => {
+            eprintln!("[deeplink] {}: {}", url, e);
+            let _ = app.emit(
+                "deeplink:error",
+                serde_json::json!({ "url": url.as_str(), "error": e.to_string() }),
+            );
+        }
+    }
+}
+
+/// Routes:
+/// - `openclaw-chat://thread/<id>`
+/// - `openclaw-chat://project/<id>`
+/// - `openclaw-chat://dump/new?content=...[&project=<id>]`, pre-filling the
+///   capture box
+async fn navigate(app: &AppHandle, url: &Url) -> Result<Navigation> {
+    if url.scheme() != SCHEME {
+        return Err(anyhow!("Not an {}:// link", SCHEME));
+    }
+    let segments: Vec<String> = url
+        .path_segments()
+        .map(|s| s.filter(|s| !s.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default();
+    let query = |key: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+            .filter(|v| !v.trim().is_empty())
+    };
+    let db = app.state::<AppState>().db.clone();
+    match (url.host_str().unwrap_or_default(), segments.as_slice()) {
+        ("thread", [id]) => {
+            let id = id.clone();
+            let thread = db.call(move |conn| db::get_thread(conn, &id)).await?;
+            let thread = thread.ok_or_else(|| anyhow!("That thread no longer exists"))?;
+            Ok(Navigation::Thread { thread_id: thread.id })
+        }
+        ("project", [id]) => {
+            let id = id.clone();
+            let project = db.call(move |conn| db::get_project(conn, &id)).await?;
+            let project = project.ok_or_else(|| anyhow!("That project no longer exists"))?;
+            Ok(Navigation::Project { project_id: project.id })
+        }
+        ("dump", [action]) if action == "new" => {
+            let content = query("content").ok_or_else(|| anyhow!("Nothing to capture: the link has no content"))?;
+            // A project that's gone just isn't pre-selected
+            let project_id = match query("project") {
+                Some(id) => db.call(move |conn| db::get_project(conn, &id)).await?.map(|p| p.id),
+                None => None,
+            };
+            Ok(Navigation::Dump { content, project_id })
+        }
+        _ => Err(anyhow!("Unknown link")),
+    }
+}
+
+/// Bring the window forward and send the UI to `navigation`. The tray's
+/// shortcuts go through here too.
+pub fn show(app: &AppHandle, navigation: &Navigation) {
+    focus_window(app);
+    let _ = app.emit("deeplink:open", navigation);
+}
+
+pub fn focus_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
@@ -0,0 +1,194 @@
+use crate::notifications::{self, Notice};
+use crate::{db, scheduler, settings, webhooks};
+use anyhow::Result;
+use chrono::{DateTime, Local, TimeZone};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+/// Setting: "true" while do-not-disturb is switched on.
+pub const DND_SETTING: &str = "dnd";
+/// Setting: epoch ms do-not-disturb switches itself off at; unset for never.
+pub const DND_UNTIL_SETTING: &str = "dnd_until";
+/// Setting: "true" to also hold events while a macOS Focus is on.
+pub const FOLLOW_FOCUS_SETTING: &str = "dnd_follow_focus";
+
+/// How often held events are retried while only a Focus is keeping them back.
+const FOCUS_POLL_MINS: i64 = 5;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DndStatus {
+    pub enabled: bool,
+    pub until: Option<i64>,
+    pub follow_focus: bool,
+    pub focus_active: bool,
+    /// Whether events are being held right now.
+    pub active: bool,
+    /// Events waiting for do-not-disturb to end.
+    pub queued: u32,
+}
+
+pub fn status(conn: &rusqlite::Connection) -> Result<DndStatus> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let enabled = db::get_setting(conn, DND_SETTING)?.as_deref() == Some("true");
+    let until = db::get_setting(conn, DND_UNTIL_SETTING)?.and_then(|v| v.parse::<i64>().ok());
+    let follow_focus = db::get_setting(conn, FOLLOW_FOCUS_SETTING)?.as_deref() == Some("true");
+    let focus_active = follow_focus && focus_active();
+    let manual = enabled && until.is_none_or(|until| now < until);
+    Ok(DndStatus {
+        enabled: manual,
+        until: until.filter(|_| manual),
+        follow_focus,
+        focus_active,
+        active: manual || focus_active,
+        queued: db::count_deferred_events(conn, now)?,
+    })
+}
+
+/// Switch do-not-disturb on (optionally until `until`, epoch ms) or off.
+/// Anything held is delivered by the drain job once it's over.
+pub fn set(conn: &rusqlite::Connection, enabled: bool, until: Option<i64>, follow_focus: bool) -> Result<()> {
+    settings::set(conn, DND_SETTING, &enabled.into())?;
+    settings::set(conn, DND_UNTIL_SETTING, &until.into())?;
+    settings::set(conn, FOLLOW_FOCUS_SETTING, &follow_focus.into())?;
+    scheduler::reload();
+    Ok(())
+}
+
+fn is_active() -> bool {
+    db::background()
+        .and_then(|conn| status(&conn))
+        .map(|s| s.active)
+        .unwrap_or(false)
+}
+
+/// Emit a user-facing event now, or hold it until do-not-disturb ends.
+pub fn emit<S: Serialize + Clone>(app: &AppHandle, event: &str, payload: S) {
+    webhooks::publish(event, &payload);
+    if is_active() {
+        let held = serde_json::to_value(payload)
+            .map_err(anyhow::Error::from)
+            .and_then(|payload| defer(db::DEFERRED_EVENT, event, &payload));
+        if let Err(e) = held {
+            eprintln!("[dnd] Failed to hold {}: {}", event, e);
+        }
+        return;
+    }
+    let _ = app.emit(event, payload);
+}
+
+/// Post an OS notification now, or hold it until do-not-disturb ends.
+pub fn notify(app: &AppHandle, title: &str, body: &str) {
+    notify_notice(app, &Notice::new(title, body));
+}
+
+/// `notify` for a notice with buttons.
+pub fn notify_notice(app: &AppHandle, notice: &Notice) {
+    if is_active() {
+        let held = serde_json::to_value(notice)
+            .map_err(anyhow::Error::from)
+            .and_then(|payload| defer(db::DEFERRED_NOTIFICATION, "notification", &payload));
+        if let Err(e) = held {
+            eprintln!("[dnd] Failed to hold notification: {}", e);
+        }
+        return;
+    }
+    notifications::show_notice(app, notice);
+}
+
+fn defer(kind: &str, name: &str, payload: &serde_json::Value) -> Result<()> {
+    db::defer_event(&*db::background()?, kind, name, payload, None)?;
+    // The drain job has something to wait for now
+    scheduler::reload();
+    Ok(())
+}
+
+/// Hold a notice until `until` (epoch ms), then deliver it with the rest of
+/// what's held. Kept in the database, so it still comes after a restart.
+pub async fn snooze(notice: &Notice, until: i64) -> Result<()> {
+    let payload = serde_json::to_value(notice)?;
+    db::call(move |conn| db::defer_event(conn, db::DEFERRED_NOTIFICATION, "notification", &payload, Some(until)))
+        .await?;
+    scheduler::reload();
+    Ok(())
+}
+
+/// Scheduler trigger for the drain job: straight away once do-not-disturb
+/// is over, at its `until` while it's on, and every `FOCUS_POLL_MINS` while
+/// a Focus holds things back; and when the next snoozed notice comes due.
+/// Nothing while the queue is empty.
+pub fn next_drain_run(after: DateTime<Local>) -> Option<DateTime<Local>> {
+    let conn = db::background().ok()?;
+    let status = status(&conn).ok()?;
+    let snoozed = db::next_deferred_delivery(&conn, after.timestamp_millis())
+        .ok()
+        .flatten()
+        .and_then(|at| Local.timestamp_millis_opt(at).single());
+    let held = if status.queued == 0 {
+        None
+    } else if status.enabled {
+        // Turning it off by hand reloads the scheduler
+        status.until.and_then(|until| Local.timestamp_millis_opt(until).single())
+    } else if status.focus_active {
+        Some(after + chrono::Duration::minutes(FOCUS_POLL_MINS))
+    } else {
+        Some(after)
+    };
+    match (held, snoozed) {
+        (Some(held), Some(snoozed)) => Some(held.min(snoozed)),
+        (held, snoozed) => held.or(snoozed),
+    }
+}
+
+/// Deliver everything held, oldest first, unless do-not-disturb is still on.
+pub async fn drain_job(app: AppHandle) -> Result<String> {
+    let events = db::call(|conn| match status(conn)?.active {
+        true => Ok(None),
+        false => db::list_deferred_events(conn, chrono::Utc::now().timestamp_millis()).map(Some),
+    })
+    .await?;
+    let Some(events) = events else {
+        return Ok("Skipped: do-not-disturb is on".to_string());
+    };
+    for event in &events {
+        match event.kind.as_str() {
+            db::DEFERRED_NOTIFICATION => match serde_json::from_value::<Notice>(event.payload.clone()) {
+                Ok(notice) => notifications::show_notice(&app, &notice),
+                Err(e) => eprintln!("[dnd] Dropping a held notification: {}", e),
+            },
+            _ => {
+                let _ = app.emit(&event.name, &event.payload);
+            }
+        }
+        let id = event.id;
+        db::call(move |conn| db::delete_deferred_event(conn, id)).await?;
+    }
+    Ok(format!("{} held events delivered", events.len()))
+}
+
+/// Whether a macOS Focus (or the older Do Not Disturb) is on, read from the
+/// assertions file Focus keeps. Reading it needs Full Disk Access; without
+/// that, or elsewhere, Focus never counts.
+#[cfg(target_os = "macos")]
+fn focus_active() -> bool {
+    let Some(home) = dirs::home_dir() else {
+        return false;
+    };
+    let Ok(content) = std::fs::read_to_string(home.join("Library/DoNotDisturb/DB/Assertions.json")) else {
+        return false;
+    };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return false;
+    };
+    json.get("data")
+        .and_then(|d| d.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|d| d.get("storeAssertionRecords").and_then(|r| r.as_array()))
+        .any(|records| !records.is_empty())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn focus_active() -> bool {
+    false
+}
@@ -0,0 +1,136 @@
+use crate::openclaw::{AgentTimeout, UnrecognizedOutput};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use tauri::{AppHandle, Emitter};
+
+/// Error returned by every command and emitted for background failures.
+/// Serializes as `{ kind, message, retryable, hint }` so the frontend can
+/// tell a missing binary from a flaky connection.
+#[derive(Debug, Clone)]
+pub enum AppError {
+    NotFound(String),
+    InvalidInput(String),
+    /// No usable openclaw install.
+    OpenClawMissing(String),
+    AgentTimeout { secs: u64 },
+    /// openclaw printed output this version of the app can't read.
+    UnrecognizedOutput(String),
+    /// SSH session down or the remote host unreachable.
+    Remote(String),
+    Database(String),
+    Io(String),
+    Internal(String),
+}
+
+impl AppError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "not_found",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::OpenClawMissing(_) => "openclaw_missing",
+            AppError::AgentTimeout { .. } => "agent_timeout",
+            AppError::UnrecognizedOutput(_) => "unrecognized_output",
+            AppError::Remote(_) => "remote",
+            AppError::Database(_) => "database",
+            AppError::Io(_) => "io",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    /// Whether trying the same thing again later may work.
+    pub fn retryable(&self) -> bool {
+        matches!(
+            self,
+            AppError::AgentTimeout { .. } | AppError::Remote(_) | AppError::Database(_) | AppError::Io(_)
+        )
+    }
+
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            AppError::OpenClawMissing(_) => {
+                Some("Install openclaw or set its path under Settings → OpenClaw, then use Detect.")
+            }
+            AppError::AgentTimeout { .. } => {
+                Some("The agent took too long. Try again, or raise the timeout in Settings.")
+            }
+            AppError::UnrecognizedOutput(_) => {
+                Some("This openclaw release prints a format the app doesn't know yet; check for an app update.")
+            }
+            AppError::Remote(_) => Some("Check the SSH settings and that the remote host is reachable."),
+            AppError::Database(_) => Some("Another process may be holding the database; try again."),
+            _ => None,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            AppError::AgentTimeout { secs } => format!("OpenClaw timed out after {}s", secs),
+            AppError::NotFound(m)
+            | AppError::InvalidInput(m)
+            | AppError::OpenClawMissing(m)
+            | AppError::UnrecognizedOutput(m)
+            | AppError::Remote(m)
+            | AppError::Database(m)
+            | AppError::Io(m)
+            | AppError::Internal(m) => m.clone(),
+        }
+    }
+
+    /// Report a failure in a background job as `app:error`.
+    pub fn emit(&self, app: &AppHandle, source: &str) {
+        let _ = app.emit(
+            "app:error",
+            serde_json::json!({ "source": source, "error": self }),
+        );
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("AppError", 4)?;
+        s.serialize_field("kind", self.kind())?;
+        s.serialize_field("message", &self.message())?;
+        s.serialize_field("retryable", &self.retryable())?;
+        s.serialize_field("hint", &self.hint())?;
+        s.end()
+    }
+}
+
+impl<E: Into<anyhow::Error>> From<E> for AppError {
+    fn from(e: E) -> Self {
+        let e: anyhow::Error = e.into();
+        if let Some(timeout) = e.downcast_ref::<AgentTimeout>() {
+            return AppError::AgentTimeout { secs: timeout.secs };
+        }
+        if e.is::<UnrecognizedOutput>() {
+            return AppError::UnrecognizedOutput(e.to_string());
+        }
+        if e.is::<rusqlite::Error>() {
+            return AppError::Database(e.to_string());
+        }
+        if e.is::<openssh::Error>() || e.is::<openssh_sftp_client::Error>() {
+            return AppError::Remote(e.to_string());
+        }
+        if let Some(io) = e.downcast_ref::<std::io::Error>() {
+            return match io.kind() {
+                std::io::ErrorKind::NotFound => AppError::NotFound(e.to_string()),
+                _ => AppError::Io(e.to_string()),
+            };
+        }
+        // Errors built with anyhow! carry no type; go by the message
+        let message = e.to_string();
+        if message.contains("openclaw binary not found") {
+            AppError::OpenClawMissing(message)
+        } else if message.starts_with("SSH") || message.starts_with("SFTP") || message.contains("Not connected") {
+            AppError::Remote(message)
+        } else {
+            AppError::Internal(message)
+        }
+    }
+}
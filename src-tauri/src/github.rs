@@ -0,0 +1,278 @@
+use crate::{db, dnd, http, kanban, keychain, relations};
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+const KEYCHAIN_SERVICE: &str = "openclaw-chat-github";
+const KEYCHAIN_ACCOUNT: &str = "token";
+const API: &str = "https://api.github.com";
+
+/// What an issue can be made from, by table name.
+const OWNERS: &[&str] = &[relations::KANBAN_ITEMS, relations::BRAIN_DUMPS];
+
+/// Longest title taken from a brain dump's first line.
+const MAX_TITLE_CHARS: usize = 80;
+
+/// An issue made from a card or brain dump.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubIssue {
+    pub owner_type: String,
+    pub owner_id: String,
+    /// `owner/name`.
+    pub repo: String,
+    pub number: i64,
+    pub url: String,
+    /// "open" or "closed", as of `checked_at`.
+    pub state: String,
+    pub closed_at: Option<i64>,
+    pub created_at: i64,
+    pub checked_at: i64,
+}
+
+/// Save a personal access token in the keychain after checking it with
+/// GitHub, and return the account it belongs to. Empty removes the token.
+pub async fn set_token(token: &str) -> Result<Option<String>> {
+    let token = token.trim();
+    if token.is_empty() {
+        keychain::delete(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).await?;
+        return Ok(None);
+    }
+    let user = api(token, "GET", "/user", None).await?;
+    keychain::store(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT, "OpenClaw Chat GitHub", token).await?;
+    Ok(user["login"].as_str().map(str::to_string))
+}
+
+async fn token() -> Result<Option<String>> {
+    keychain::read(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).await
+}
+
+/// Set the repository a project's issues go to, or clear it with `None`.
+pub fn set_project_repo(conn: &Connection, project_id: &str, repo: Option<&str>) -> Result<()> {
+    match repo.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(repo) => {
+            check_repo(repo)?;
+            conn.execute(
+                "INSERT INTO github_repos (project_id, repo, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(project_id) DO UPDATE SET repo = excluded.repo, updated_at = excluded.updated_at",
+                params![project_id, repo, chrono::Utc::now().timestamp_millis()],
+            )?;
+        }
+        None => {
+            conn.execute("DELETE FROM github_repos WHERE project_id = ?1", params![project_id])?;
+        }
+    }
+    Ok(())
+}
+
+pub fn project_repo(conn: &Connection, project_id: &str) -> Result<Option<String>> {
+    Ok(conn
+        .query_row("SELECT repo FROM github_repos WHERE project_id = ?1", params![project_id], |row| row.get(0))
+        .optional()?)
+}
+
+fn check_repo(repo: &str) -> Result<()> {
+    let valid_part = |p: &str| {
+        !p.is_empty() && p != "." && p != ".." && p.bytes().all(|b| b.is_ascii_alphanumeric() || b"-_.".contains(&b))
+    };
+    match repo.split_once('/') {
+        Some((owner, name)) if valid_part(owner) && valid_part(name) => Ok(()),
+        _ => Err(anyhow!("Expected a repository as owner/name, got {:?}", repo)),
+    }
+}
+
+/// What `create_issue` will file: the repository of the owner's project and
+/// a title and body taken from the card or dump.
+pub struct IssueDraft {
+    pub repo: String,
+    pub title: String,
+    pub body: String,
+}
+
+pub fn draft(conn: &Connection, owner_type: &str, owner_id: &str) -> Result<IssueDraft> {
+    check_owner(owner_type)?;
+    if let Some(issue) = issue(conn, owner_type, owner_id)? {
+        return Err(anyhow!("Already filed as {}", issue.url));
+    }
+    let (project_id, title, body) = if owner_type == relations::KANBAN_ITEMS {
+        let item = db::get_kanban_item(conn, owner_id)?.ok_or_else(|| anyhow!("Card not found"))?;
+        (item.project_id, item.title, item.description.unwrap_or_default())
+    } else {
+        let (project_id, content): (Option<String>, String) = conn
+            .query_row(
+                "SELECT project_id, content FROM brain_dumps WHERE id = ?1",
+                params![owner_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("Brain dump not found"))?;
+        let first = content.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+        let title = match first.char_indices().nth(MAX_TITLE_CHARS) {
+            Some((cut, _)) => format!("{}…", first[..cut].trim_end()),
+            None => first.to_string(),
+        };
+        (project_id, title, content)
+    };
+    let project_id = project_id.ok_or_else(|| anyhow!("Move it into a project with a GitHub repository first"))?;
+    let repo = project_repo(conn, &project_id)?
+        .ok_or_else(|| anyhow!("Set a GitHub repository for this project first"))?;
+    Ok(IssueDraft { repo, title, body })
+}
+
+/// File the issue on GitHub. Returns its number and web URL.
+pub async fn create_issue(repo: &str, title: &str, body: &str, labels: &[String]) -> Result<(i64, String)> {
+    check_repo(repo)?;
+    if title.trim().is_empty() {
+        return Err(anyhow!("An issue needs a title"));
+    }
+    let token = token().await?.ok_or_else(|| anyhow!("Add a GitHub token in settings first"))?;
+    let created = api(
+        &token,
+        "POST",
+        &format!("/repos/{}/issues", repo),
+        Some(&json!({ "title": title.trim(), "body": body, "labels": labels })),
+    )
+    .await?;
+    let number = created["number"].as_i64().ok_or_else(|| anyhow!("GitHub didn't return an issue number"))?;
+    let url = created["html_url"].as_str().unwrap_or_default().to_string();
+    Ok((number, url))
+}
+
+/// Remember the issue filed for a card or dump.
+pub fn record_issue(
+    conn: &Connection,
+    owner_type: &str,
+    owner_id: &str,
+    repo: &str,
+    number: i64,
+    url: &str,
+) -> Result<GithubIssue> {
+    check_owner(owner_type)?;
+    let now = chrono::Utc::now().timestamp_millis();
+    conn.execute(
+        "INSERT OR REPLACE INTO github_issues
+            (owner_type, owner_id, repo, number, url, state, closed_at, created_at, checked_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, 'open', NULL, ?6, ?6)",
+        params![owner_type, owner_id, repo, number, url, now],
+    )?;
+    issue(conn, owner_type, owner_id)?.ok_or_else(|| anyhow!("Issue wasn't saved"))
+}
+
+const ISSUE_COLUMNS: &str = "owner_type, owner_id, repo, number, url, state, closed_at, created_at, checked_at";
+
+pub fn issue(conn: &Connection, owner_type: &str, owner_id: &str) -> Result<Option<GithubIssue>> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT {} FROM github_issues WHERE owner_type = ?1 AND owner_id = ?2", ISSUE_COLUMNS),
+            params![owner_type, owner_id],
+            row_to_issue,
+        )
+        .optional()?)
+}
+
+fn open_issues(conn: &Connection) -> Result<Vec<GithubIssue>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM github_issues WHERE state = 'open' ORDER BY checked_at",
+        ISSUE_COLUMNS
+    ))?;
+    let issues = stmt.query_map([], row_to_issue)?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(issues)
+}
+
+fn row_to_issue(row: &rusqlite::Row) -> rusqlite::Result<GithubIssue> {
+    Ok(GithubIssue {
+        owner_type: row.get(0)?,
+        owner_id: row.get(1)?,
+        repo: row.get(2)?,
+        number: row.get(3)?,
+        url: row.get(4)?,
+        state: row.get(5)?,
+        closed_at: row.get(6)?,
+        created_at: row.get(7)?,
+        checked_at: row.get(8)?,
+    })
+}
+
+fn check_owner(owner_type: &str) -> Result<()> {
+    if OWNERS.contains(&owner_type) {
+        Ok(())
+    } else {
+        Err(anyhow!("Issues can't be made from {:?}", owner_type))
+    }
+}
+
+/// Check open issues on GitHub. A closed issue moves its card to done and
+/// is sent as "github:issue_closed"; dumps only record the state.
+pub async fn issue_state_job(app: AppHandle) -> Result<String> {
+    let Some(token) = token().await? else {
+        return Ok("No GitHub token".to_string());
+    };
+    let issues = db::call(open_issues).await?;
+    let (mut closed, mut failed) = (0, 0);
+    for issue in &issues {
+        let fetched = match api(&token, "GET", &format!("/repos/{}/issues/{}", issue.repo, issue.number), None).await {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                eprintln!("[github] Couldn't check {}: {}", issue.url, e);
+                failed += 1;
+                continue;
+            }
+        };
+        let state = fetched["state"].as_str().unwrap_or("open");
+        let closed_at = fetched["closed_at"]
+            .as_str()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.timestamp_millis());
+        let is_closed = state == "closed";
+        let (state, owner_type, owner_id) = (state.to_string(), issue.owner_type.clone(), issue.owner_id.clone());
+        let completion = db::call(move |conn| {
+            conn.execute(
+                "UPDATE github_issues SET state = ?1, closed_at = ?2, checked_at = ?3 WHERE owner_type = ?4 AND owner_id = ?5",
+                params![state, closed_at, chrono::Utc::now().timestamp_millis(), owner_type, owner_id],
+            )?;
+            match is_closed && owner_type == relations::KANBAN_ITEMS {
+                true => kanban::complete_card(conn, &owner_id),
+                false => Ok(None),
+            }
+        })
+        .await?;
+        if !is_closed {
+            continue;
+        }
+        closed += 1;
+        if let Some(completion) = completion {
+            completion.announce(&app);
+        }
+        dnd::emit(&app, "github:issue_closed", issue);
+    }
+    if failed > 0 && failed == issues.len() {
+        return Err(anyhow!("Couldn't check any of {} open issues", failed));
+    }
+    Ok(format!("{} open issues checked, {} closed, {} failed", issues.len(), closed, failed))
+}
+
+async fn api(token: &str, method: &str, path: &str, body: Option<&Value>) -> Result<Value> {
+    let mut headers = vec![
+        format!("Authorization: Bearer {}", token),
+        "Accept: application/vnd.github+json".to_string(),
+        "X-GitHub-Api-Version: 2022-11-28".to_string(),
+    ];
+    if body.is_some() {
+        headers.push("Content-Type: application/json".to_string());
+    }
+    let body = body.map(Value::to_string);
+    let response = http::request(method, &format!("{}{}", API, path), &headers, body.as_deref(), 30).await?;
+    let value: Value = serde_json::from_str(&response.body).unwrap_or(Value::Null);
+    match response.status {
+        200..=299 => Ok(value),
+        401 => Err(anyhow!("GitHub rejected the token; add a new one in settings")),
+        404 => Err(anyhow!("Not found on GitHub, or the token can't see it: {}", path)),
+        status => Err(anyhow!(
+            "GitHub returned {}: {}",
+            status,
+            value["message"].as_str().unwrap_or("no details")
+        )),
+    }
+}
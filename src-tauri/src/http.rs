@@ -0,0 +1,69 @@
+use anyhow::{anyhow, Result};
+use tokio::io::AsyncWriteExt;
+
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Make an HTTP(S) request with curl. The request, headers included, goes
+/// to curl as a config on stdin, so tokens and signatures never show up in
+/// the process list. Any status is returned; only a failure to get one
+/// (DNS, TLS, timeout) is an error.
+pub async fn request(
+    method: &str,
+    url: &str,
+    headers: &[String],
+    body: Option<&str>,
+    timeout_secs: u32,
+) -> Result<Response> {
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(anyhow!("Only http and https URLs are supported: {}", url));
+    }
+    let mut config = vec![
+        format!("url = {}", quote(url)),
+        format!("request = {}", quote(method)),
+        "proto = \"=http,https\"".to_string(),
+        "user-agent = \"openclaw-chat\"".to_string(),
+        format!("max-time = {}", timeout_secs),
+        "silent".to_string(),
+        "show-error".to_string(),
+        "write-out = \"\\n%{http_code}\"".to_string(),
+    ];
+    config.extend(headers.iter().map(|h| format!("header = {}", quote(h))));
+    if let Some(body) = body {
+        config.push(format!("data-binary = {}", quote(body)));
+    }
+    let mut child = tokio::process::Command::new("curl")
+        .args(["--config", "-"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Couldn't run curl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.join("\n").as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(anyhow!("Request to {} failed: {}", url, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (body, status) = stdout.rsplit_once('\n').unwrap_or(("", &stdout));
+    Ok(Response {
+        status: status.trim().parse().unwrap_or(0),
+        body: body.to_string(),
+    })
+}
+
+/// Quoting for a curl config value.
+fn quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    )
+}
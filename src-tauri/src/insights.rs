@@ -0,0 +1,143 @@
+use crate::{db, mirror, openclaw, workspace};
+use anyhow::Result;
+use chrono::{Datelike, Local, TimeZone};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Longest trend `cmd_get_workspace_stats` will compute.
+pub const MAX_WEEKS: u32 = 104;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeekActivity {
+    /// Local midnight of the week's Monday, epoch ms.
+    pub start: i64,
+    #[serde(flatten)]
+    pub counts: db::ActivityCounts,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStats {
+    pub generated_at: i64,
+    #[serde(flatten)]
+    pub totals: db::WorkspaceTotals,
+    /// Chat messages across every thread's session.
+    pub messages: u64,
+    /// Oldest week first; the last one is the current, partial week.
+    pub weeks: Vec<WeekActivity>,
+    pub session_files: u32,
+    pub session_bytes: u64,
+    /// Local copies of remote sessions.
+    pub cache_files: u32,
+    pub cache_bytes: u64,
+}
+
+/// Counts, totals and the last `weeks` weeks of activity for the insights
+/// screen. Session files are read off the database pool.
+pub async fn workspace_stats(db: &db::Db, weeks: u32) -> Result<WorkspaceStats> {
+    let now = Local::now();
+    let starts = week_starts(now, weeks.clamp(1, MAX_WEEKS));
+    let end = now.timestamp_millis() + 1;
+    let (totals, weeks, threads) = db
+        .call(move |conn| {
+            let mut weeks = Vec::new();
+            for (i, &start) in starts.iter().enumerate() {
+                let until = starts.get(i + 1).copied().unwrap_or(end);
+                weeks.push(WeekActivity {
+                    start,
+                    counts: db::activity_counts(conn, start, until)?,
+                });
+            }
+            let threads = db::list_threads_active_since(conn, 0)?;
+            Ok::<_, anyhow::Error>((db::workspace_totals(conn)?, weeks, threads))
+        })
+        .await?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let messages = threads
+            .iter()
+            .filter_map(|t| session_file(&t.agent_id, &t.session_id))
+            .map(|path| message_count(&path))
+            .sum();
+        let (session_files, session_bytes) = usage(&openclaw::agents_dir(), "sessions");
+        let (cache_files, cache_bytes) = usage(&mirror::cache_dir(), "");
+        Ok(WorkspaceStats {
+            generated_at: now.timestamp_millis(),
+            totals,
+            messages,
+            weeks,
+            session_files,
+            session_bytes,
+            cache_files,
+            cache_bytes,
+        })
+    })
+    .await?
+}
+
+/// Mondays at local midnight, `weeks` of them ending with this week's.
+fn week_starts(now: chrono::DateTime<Local>, weeks: u32) -> Vec<i64> {
+    let monday = now.date_naive() - chrono::Duration::days(now.weekday().num_days_from_monday() as i64);
+    (0..weeks as i64)
+        .rev()
+        .map(|back| {
+            let date = monday - chrono::Duration::weeks(back);
+            Local
+                .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+                .earliest()
+                .map(|t| t.timestamp_millis())
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+/// The session file on this machine, or its mirrored copy.
+fn session_file(agent_id: &str, session_id: &str) -> Option<PathBuf> {
+    let local = openclaw::session_path(agent_id, session_id);
+    if local.exists() {
+        return Some(local);
+    }
+    mirror::cache_path(agent_id, session_id).ok().filter(|p| p.exists())
+}
+
+/// Message counts by path, with the size and mtime they were counted at, so
+/// only sessions that changed are parsed again.
+type Counted = HashMap<PathBuf, (u64, i64, u64)>;
+static COUNTS: Mutex<Option<Counted>> = Mutex::new(None);
+
+fn message_count(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::metadata(path) else {
+        return 0;
+    };
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as i64);
+    if let Some(&(len, mtime, count)) = COUNTS.lock().unwrap().get_or_insert_with(HashMap::new).get(path) {
+        if len == meta.len() && mtime == modified {
+            return count;
+        }
+    }
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return 0;
+    };
+    let count = openclaw::parse_session(&content).messages.len() as u64;
+    COUNTS
+        .lock()
+        .unwrap()
+        .get_or_insert_with(HashMap::new)
+        .insert(path.to_path_buf(), (meta.len(), modified, count));
+    count
+}
+
+/// Number and total size of the session files under `root`.
+fn usage(root: &Path, sub: &str) -> (u32, u64) {
+    workspace::session_files(root, sub)
+        .iter()
+        .filter_map(|(_, path)| std::fs::metadata(path).ok())
+        .fold((0, 0), |(files, bytes), meta| (files + 1, bytes + meta.len()))
+}
@@ -1,9 +1,66 @@
 use crate::db::{self, KanbanItem};
-use chrono::Utc;
+use crate::{obsidian, relations, webhooks};
+use anyhow::anyhow;
+use chrono::{DateTime, Datelike, Months, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tauri::{AppHandle, Emitter};
 use uuid::Uuid;
 
-pub fn list_kanban_items(conn: &rusqlite::Connection, project_id: Option<&str>) -> anyhow::Result<Vec<KanbanItem>> {
-    db::list_kanban_items(conn, project_id)
+pub const COLUMNS: [&str; 4] = ["backlog", "this_week", "in_progress", "done"];
+
+/// RRULE-lite recurrence for chores that come back after they're done.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Recurrence {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "daily" => Some(Self::Daily),
+            "weekly" => Some(Self::Weekly),
+            "monthly" => Some(Self::Monthly),
+            _ => None,
+        }
+    }
+
+    /// Timestamp (ms) of the next occurrence after `from_ms`.
+    pub fn next_after(self, from_ms: i64) -> i64 {
+        let Some(from) = DateTime::<Utc>::from_timestamp_millis(from_ms) else {
+            return from_ms;
+        };
+        let next = match self {
+            Self::Daily => from + chrono::Duration::days(1),
+            Self::Weekly => from + chrono::Duration::weeks(1),
+            Self::Monthly => from.checked_add_months(Months::new(1)).unwrap_or(from),
+        };
+        next.timestamp_millis()
+    }
+}
+
+pub fn list_kanban_items(
+    conn: &rusqlite::Connection,
+    project_id: Option<&str>,
+    sort: Option<&str>,
+) -> anyhow::Result<Vec<KanbanItem>> {
+    let sort = match sort.unwrap_or("manual") {
+        "manual" => db::KanbanSort::Manual,
+        "priority" => db::KanbanSort::Priority,
+        "due_date" => db::KanbanSort::DueDate,
+        "age" => db::KanbanSort::Age,
+        other => return Err(anyhow!("Unknown sort mode: {}", other)),
+    };
+    db::list_kanban_items(conn, project_id, sort)
+}
+
+pub fn set_priority(conn: &rusqlite::Connection, id: String, priority: i32) -> anyhow::Result<()> {
+    if !(0..=4).contains(&priority) {
+        return Err(anyhow!("Priority must be between 0 and 4"));
+    }
+    db::set_kanban_priority(conn, &id, priority)
 }
 
 pub fn create_kanban_item(
@@ -24,8 +81,50 @@ pub fn create_kanban_item(
         column: column.unwrap_or_else(|| "backlog".to_string()),
         position: 0,
         status: "active".to_string(),
+        recurrence: None,
+        completed_at: None,
+        parent_id: None,
+        due_at: None,
+        agent_id: None,
+        thread_id: None,
+        priority: 0,
+        created_at: now,
+        updated_at: now,
+        blocked_by: Vec::new(),
+    };
+    db::create_kanban_item(conn, &item)?;
+    Ok(item)
+}
+
+/// Backlog card that mirrors something living elsewhere (a vault task, an issue, …).
+pub fn create_sourced_item(
+    conn: &rusqlite::Connection,
+    title: String,
+    project_id: Option<String>,
+    source_type: &str,
+    source_id: String,
+) -> anyhow::Result<KanbanItem> {
+    let now = Utc::now().timestamp_millis();
+    let item = KanbanItem {
+        id: Uuid::new_v4().to_string(),
+        project_id,
+        source_type: source_type.to_string(),
+        source_id: Some(source_id),
+        title,
+        description: None,
+        column: "backlog".to_string(),
+        position: 0,
+        status: "active".to_string(),
+        recurrence: None,
+        completed_at: None,
+        parent_id: None,
+        due_at: None,
+        agent_id: None,
+        thread_id: None,
+        priority: 0,
         created_at: now,
         updated_at: now,
+        blocked_by: Vec::new(),
     };
     db::create_kanban_item(conn, &item)?;
     Ok(item)
@@ -40,39 +139,33 @@ pub fn update_kanban_item(
     position: Option<i32>,
     status: Option<String>,
     project_id: Option<String>,
-) -> anyhow::Result<()> {
-    // For now, we need to update project_id manually since db::update_kanban_item doesn't support it yet
-    // We'll need to enhance the db layer to support updating project_id
-    if let Some(proj_id) = project_id {
-        if proj_id.is_empty() {
-            // Empty string = explicit "unassign project"
-            conn.execute(
-                "UPDATE kanban_items SET project_id = NULL, updated_at = ?1 WHERE id = ?2",
-                rusqlite::params![Utc::now().timestamp_millis(), id],
-            )?;
-        } else {
-            conn.execute(
-                "UPDATE kanban_items SET project_id = ?1, updated_at = ?2 WHERE id = ?3",
-                rusqlite::params![proj_id, Utc::now().timestamp_millis(), id],
-            )?;
-        }
-    }
-    // None = no-op: project_id unchanged (e.g. column drag-and-drop)
-
-    // Update other fields if provided
+) -> anyhow::Result<Option<db::ColumnChange>> {
+    // Empty string = explicit "unassign project"; None = leave unchanged (e.g. column drag-and-drop)
+    let project_id = project_id.as_deref().map(|p| if p.is_empty() { None } else { Some(p) });
     db::update_kanban_item(
         conn,
         &id,
-        title.as_deref(),
-        description.as_deref(),
-        column.as_deref(),
-        position,
-        status.as_deref(),
+        &db::KanbanItemUpdate {
+            title: title.as_deref(),
+            description: description.as_deref(),
+            column: column.as_deref(),
+            position,
+            status: status.as_deref(),
+            project_id,
+        },
     )
 }
 
+/// Delete a card. A card mirrored from a vault task is remembered as
+/// dismissed so task sync doesn't recreate it.
 pub fn delete_kanban_item(conn: &rusqlite::Connection, id: String) -> anyhow::Result<()> {
-    db::delete_kanban_item(conn, &id)
+    if let Some(item) = db::get_kanban_item(conn, &id)? {
+        let note = item.source_id.as_deref().and_then(|s| s.rsplit_once(':')).map(|(note, _)| note);
+        if let (Some(note), "obsidian") = (note, item.source_type.as_str()) {
+            db::dismiss_obsidian_task(conn, note, &item.title)?;
+        }
+    }
+    relations::delete(conn, relations::KANBAN_ITEMS, &id)
 }
 
 pub fn promote_brain_dump(
@@ -93,11 +186,624 @@ pub fn promote_brain_dump(
         column: column.unwrap_or_else(|| "backlog".to_string()),
         position: 0,
         status: "active".to_string(),
+        recurrence: None,
+        completed_at: None,
+        parent_id: None,
+        due_at: None,
+        agent_id: None,
+        thread_id: None,
+        priority: 0,
         created_at: now,
         updated_at: now,
+        blocked_by: Vec::new(),
     };
     db::create_kanban_item(conn, &item)?;
     // Mark the brain dump as done
     db::update_brain_dump_status(conn, &dump_id, "done")?;
     Ok(item)
 }
+
+pub fn set_recurrence(
+    conn: &rusqlite::Connection,
+    id: String,
+    recurrence: Option<String>,
+) -> anyhow::Result<()> {
+    // Empty string clears the rule, same as None
+    let recurrence = recurrence.filter(|r| !r.is_empty());
+    if let Some(ref r) = recurrence {
+        if Recurrence::parse(r).is_none() {
+            return Err(anyhow!("Unknown recurrence rule: {}", r));
+        }
+    }
+    db::set_kanban_recurrence(conn, &id, recurrence.as_deref())
+}
+
+/// Clone recurring cards back into backlog once their next occurrence is due.
+/// The rule moves to the fresh card so the finished one is only spawned once.
+pub fn spawn_due_recurrences(conn: &rusqlite::Connection) -> anyhow::Result<Vec<KanbanItem>> {
+    let now = Utc::now().timestamp_millis();
+    let mut spawned = Vec::new();
+
+    for done in db::list_recurring_done_items(conn)? {
+        let (Some(rule), Some(completed_at)) = (done.recurrence.as_deref(), done.completed_at) else {
+            continue;
+        };
+        let Some(recurrence) = Recurrence::parse(rule) else {
+            continue;
+        };
+        if recurrence.next_after(completed_at) > now {
+            continue;
+        }
+
+        let item = KanbanItem {
+            id: Uuid::new_v4().to_string(),
+            project_id: done.project_id.clone(),
+            source_type: "recurring".to_string(),
+            source_id: Some(done.id.clone()),
+            title: done.title.clone(),
+            description: done.description.clone(),
+            column: "backlog".to_string(),
+            position: 0,
+            status: "active".to_string(),
+            recurrence: done.recurrence.clone(),
+            completed_at: None,
+            parent_id: done.parent_id.clone(),
+            due_at: done.due_at.map(|d| recurrence.next_after(d)),
+            agent_id: None,
+            thread_id: None,
+            priority: done.priority,
+            created_at: now,
+            updated_at: now,
+            blocked_by: Vec::new(),
+        };
+        db::create_kanban_item(conn, &item)?;
+        db::set_kanban_recurrence(conn, &done.id, None)?;
+        spawned.push(item);
+    }
+
+    Ok(spawned)
+}
+
+// ── Dependencies ─────────────────────────────────────────────────────────────
+
+/// Record that `item_id` is blocked by `blocked_by_id`, refusing self-links and
+/// anything that would close a cycle.
+pub fn add_dependency(conn: &rusqlite::Connection, item_id: &str, blocked_by_id: &str) -> anyhow::Result<()> {
+    if item_id == blocked_by_id {
+        return Err(anyhow!("A card can't block itself"));
+    }
+    for id in [item_id, blocked_by_id] {
+        if db::get_kanban_item(conn, id)?.is_none() {
+            return Err(anyhow!("Kanban item not found: {}", id));
+        }
+    }
+
+    // Walk the blocker's own blockers; reaching item_id means a cycle
+    let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+    for (item, blocker) in db::list_kanban_dependencies(conn)? {
+        edges.entry(item).or_default().push(blocker);
+    }
+    let mut seen = HashSet::new();
+    let mut stack = vec![blocked_by_id.to_string()];
+    while let Some(current) = stack.pop() {
+        if current == item_id {
+            return Err(anyhow!("Dependency would create a cycle"));
+        }
+        if seen.insert(current.clone()) {
+            if let Some(next) = edges.get(&current) {
+                stack.extend(next.iter().cloned());
+            }
+        }
+    }
+
+    db::add_kanban_dependency(conn, item_id, blocked_by_id)
+}
+
+pub fn remove_dependency(conn: &rusqlite::Connection, item_id: &str, blocked_by_id: &str) -> anyhow::Result<()> {
+    db::remove_kanban_dependency(conn, item_id, blocked_by_id)
+}
+
+/// Dependents of a just-completed card that no longer have any open blockers.
+pub fn newly_unblocked(conn: &rusqlite::Connection, completed_id: &str) -> anyhow::Result<Vec<String>> {
+    let open = db::list_open_blockers(conn)?;
+    Ok(db::list_kanban_dependents(conn, completed_id)?
+        .into_iter()
+        .filter(|id| !open.contains_key(id))
+        .collect())
+}
+
+// ── Agent dispatch ───────────────────────────────────────────────────────────
+
+/// A card `complete_card` moved to done, with what to send once the change
+/// is committed.
+#[derive(Debug)]
+pub struct Completion {
+    pub id: String,
+    pub moved: Option<db::ColumnChange>,
+    pub unblocked: Vec<String>,
+}
+
+impl Completion {
+    /// The "kanban:moved" webhook, and "kanban:unblocked" for cards that
+    /// were waiting on this one.
+    pub fn announce(&self, app: &AppHandle) {
+        if let Some(moved) = &self.moved {
+            webhooks::publish(webhooks::KANBAN_MOVED, moved);
+        }
+        if !self.unblocked.is_empty() {
+            let _ = app.emit(
+                "kanban:unblocked",
+                serde_json::json!({ "completedId": self.id, "unblockedIds": self.unblocked }),
+            );
+        }
+    }
+}
+
+/// Move a card to done however it got there (dragged, ticked in the vault,
+/// closed on GitHub...): tick its Obsidian task and work out which cards it
+/// unblocked. A card already done is left alone and gives `None`.
+pub fn complete_card(conn: &rusqlite::Connection, id: &str) -> anyhow::Result<Option<Completion>> {
+    let Some(item) = db::get_kanban_item(conn, id)? else {
+        return Ok(None);
+    };
+    if item.column == "done" {
+        return Ok(None);
+    }
+    let moved = update_kanban_item(conn, id.to_string(), None, None, Some("done".to_string()), None, None, None)?;
+    if let Err(e) = obsidian::complete_task_in_vault(conn, id) {
+        eprintln!("Failed to tick Obsidian task for card {}: {}", id, e);
+    }
+    Ok(Some(Completion {
+        id: id.to_string(),
+        moved,
+        unblocked: newly_unblocked(conn, id)?,
+    }))
+}
+
+/// Hand a card to an agent: create its work thread, link it to the card, and
+/// move the card to in_progress. Returns the card, the new thread and the
+/// column change to publish.
+pub fn dispatch_to_agent(
+    conn: &rusqlite::Connection,
+    id: &str,
+    agent_id: &str,
+) -> anyhow::Result<(KanbanItem, db::Thread, Option<db::ColumnChange>)> {
+    let item = db::get_kanban_item(conn, id)?.ok_or_else(|| anyhow!("Kanban item not found: {}", id))?;
+    if item.column == "done" {
+        return Err(anyhow!("Card is already done"));
+    }
+
+    let now = Utc::now().timestamp_millis();
+    let thread = db::Thread {
+        id: Uuid::new_v4().to_string(),
+        project_id: item.project_id.clone(),
+        name: item.title.clone(),
+        session_id: Uuid::new_v4().to_string(),
+        agent_id: agent_id.to_string(),
+        created_at: now,
+        updated_at: now,
+        last_message_at: None,
+        execution_target: None,
+        model: None,
+        muted: false,
+    };
+    db::create_thread(conn, &thread)?;
+    db::assign_kanban_item(conn, id, agent_id, &thread.id)?;
+    let moved = db::update_kanban_item(
+        conn,
+        id,
+        &db::KanbanItemUpdate {
+            column: Some("in_progress"),
+            ..Default::default()
+        },
+    )?;
+
+    let item = db::get_kanban_item(conn, id)?.unwrap_or(item);
+    Ok((item, thread, moved))
+}
+
+/// Work instruction sent to the agent for a dispatched card.
+pub fn dispatch_prompt(item: &KanbanItem, project: Option<&db::Project>) -> String {
+    let mut prompt = String::new();
+    if let Some(p) = project {
+        prompt.push_str(&format!(
+            "[System context: You are in project \"{}\" (id: {}). You can create kanban cards using the kanban-card command. Always use this project id when creating cards.]\n\n",
+            p.name, p.id
+        ));
+    }
+    prompt.push_str(&format!(
+        "You've been assigned the kanban card \"{}\" (id: {}). Work on it autonomously and report back what you did and anything left open.",
+        item.title, item.id
+    ));
+    if let Some(desc) = item.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        prompt.push_str(&format!("\n\nCard details:\n{}", desc));
+    }
+    prompt
+}
+
+// ── Markdown import ──────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownTask {
+    pub title: String,
+    pub done: bool,
+    pub indent: usize,
+    pub parent: Option<usize>, // index into the parsed task list
+    pub line: usize,           // 0-based line number in the source
+}
+
+/// Parse `- [ ]` / `- [x]` task lines; deeper-indented tasks become subtasks
+/// of the nearest shallower task above them.
+pub fn parse_markdown_tasks(markdown: &str) -> Vec<MarkdownTask> {
+    let mut tasks: Vec<MarkdownTask> = Vec::new();
+    let mut stack: Vec<usize> = Vec::new();
+
+    for (line_no, line) in markdown.lines().enumerate() {
+        let indent = line
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .map(|c| if c == '\t' { 4 } else { 1 })
+            .sum::<usize>();
+        let trimmed = line.trim_start();
+        let Some(rest) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+            .or_else(|| trimmed.strip_prefix("+ "))
+        else {
+            continue;
+        };
+        let (done, title) = if let Some(t) = rest.strip_prefix("[ ]") {
+            (false, t)
+        } else if let Some(t) = rest.strip_prefix("[x]").or_else(|| rest.strip_prefix("[X]")) {
+            (true, t)
+        } else {
+            continue;
+        };
+        let title = title.trim();
+        if title.is_empty() {
+            continue;
+        }
+
+        while let Some(&top) = stack.last() {
+            if tasks[top].indent >= indent {
+                stack.pop();
+            } else {
+                break;
+            }
+        }
+        tasks.push(MarkdownTask {
+            title: title.to_string(),
+            done,
+            indent,
+            parent: stack.last().copied(),
+            line: line_no,
+        });
+        stack.push(tasks.len() - 1);
+    }
+
+    tasks
+}
+
+/// Create kanban cards from a Markdown task list: open tasks land in backlog,
+/// checked ones in done, and nesting is kept through `parent_id`.
+pub fn import_markdown_tasks(
+    conn: &rusqlite::Connection,
+    markdown: &str,
+    project_id: Option<String>,
+    source_id: Option<String>,
+) -> anyhow::Result<Vec<KanbanItem>> {
+    let now = Utc::now().timestamp_millis();
+    let tasks = parse_markdown_tasks(markdown);
+    let mut created: Vec<KanbanItem> = Vec::with_capacity(tasks.len());
+
+    for (i, task) in tasks.iter().enumerate() {
+        let column = if task.done { "done" } else { "backlog" };
+        let item = KanbanItem {
+            id: Uuid::new_v4().to_string(),
+            project_id: project_id.clone(),
+            source_type: "markdown".to_string(),
+            source_id: source_id.clone(),
+            title: task.title.clone(),
+            description: None,
+            column: column.to_string(),
+            position: i as i32,
+            status: "active".to_string(),
+            recurrence: None,
+            completed_at: if task.done { Some(now) } else { None },
+            parent_id: task.parent.map(|p| created[p].id.clone()),
+            due_at: None,
+            agent_id: None,
+            thread_id: None,
+            priority: 0,
+            created_at: now,
+            updated_at: now,
+            blocked_by: Vec::new(),
+        };
+        db::create_kanban_item(conn, &item)?;
+        created.push(item);
+    }
+
+    Ok(created)
+}
+
+// ── Export ───────────────────────────────────────────────────────────────────
+
+fn column_label(column: &str) -> &str {
+    match column {
+        "backlog" => "Backlog",
+        "this_week" => "This Week",
+        "in_progress" => "In Progress",
+        "done" => "Done",
+        other => other,
+    }
+}
+
+fn format_date(ts: Option<i64>) -> String {
+    ts.and_then(DateTime::<Utc>::from_timestamp_millis)
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') || s.contains('\r') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Snapshot all active cards as a Markdown board (`"markdown"`) or CSV (`"csv"`).
+pub fn export_board(
+    conn: &rusqlite::Connection,
+    project_id: Option<&str>,
+    format: &str,
+) -> anyhow::Result<String> {
+    let items = db::list_kanban_items(conn, project_id, db::KanbanSort::Manual)?;
+    let project_name = match project_id {
+        Some(pid) => db::get_project(conn, pid)?.map(|p| p.name),
+        None => None,
+    };
+    // Every item's own project, since an unfiltered export spans them all
+    let projects: std::collections::HashMap<String, String> = db::list_projects(conn, true)?
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+
+    match format {
+        "markdown" | "md" => {
+            let mut out = format!(
+                "# {} board\n\n_Exported {}_\n",
+                project_name.as_deref().unwrap_or("Kanban"),
+                Utc::now().format("%Y-%m-%d %H:%M UTC")
+            );
+            for column in COLUMNS {
+                let cards: Vec<&KanbanItem> = items.iter().filter(|i| i.column == column).collect();
+                out.push_str(&format!("\n## {} ({})\n\n", column_label(column), cards.len()));
+                let in_column = |id: &str| cards.iter().any(|c| c.id == id);
+                // Top-level cards first, each followed by its subtasks in the same column
+                let mut written = HashSet::new();
+                for card in cards.iter().filter(|c| !c.parent_id.as_deref().is_some_and(in_column)) {
+                    push_markdown_tree(&mut out, &cards, card, 0, &mut written);
+                }
+            }
+            Ok(out)
+        }
+        "csv" => {
+            let mut out = String::from("id,title,column,project,due_date,created,completed,description\n");
+            for item in &items {
+                let row = [
+                    item.id.clone(),
+                    item.title.clone(),
+                    item.column.clone(),
+                    item
+                        .project_id
+                        .as_ref()
+                        .and_then(|pid| projects.get(pid))
+                        .cloned()
+                        .unwrap_or_default(),
+                    format_date(item.due_at),
+                    format_date(Some(item.created_at)),
+                    format_date(item.completed_at),
+                    item.description.clone().unwrap_or_default(),
+                ];
+                out.push_str(&row.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+                out.push('\n');
+            }
+            Ok(out)
+        }
+        // Open cards as a Markdown table with summary frontmatter, for Obsidian Dataview
+        "dataview" => {
+            let open: Vec<&KanbanItem> = items.iter().filter(|i| i.column != "done").collect();
+
+            let mut out = String::from("---\n");
+            out.push_str(&format!("updated: {}\n", Utc::now().format("%Y-%m-%dT%H:%M:%SZ")));
+            if let Some(name) = &project_name {
+                out.push_str(&format!("project: \"[[{}]]\"\n", name.replace('"', "'")));
+            }
+            out.push_str(&format!("open_cards: {}\n", open.len()));
+            for column in COLUMNS.iter().filter(|c| **c != "done") {
+                out.push_str(&format!("{}: {}\n", column, open.iter().filter(|i| i.column == *column).count()));
+            }
+            out.push_str("tags:\n  - kanban\n---\n\n");
+            out.push_str(&format!("# {} board\n\n", project_name.as_deref().unwrap_or("Kanban")));
+            out.push_str("| Card | Project | Column | Priority | Due | Blocked | Updated |\n");
+            out.push_str("| --- | --- | --- | --- | --- | --- | --- |\n");
+            for item in open {
+                let project = item
+                    .project_id
+                    .as_ref()
+                    .and_then(|pid| projects.get(pid))
+                    .map(|name| format!("[[{}]]", name))
+                    .unwrap_or_default();
+                let row = [
+                    item.title.clone(),
+                    project,
+                    column_label(&item.column).to_string(),
+                    item.priority.to_string(),
+                    format_date(item.due_at),
+                    if item.blocked_by.is_empty() { String::new() } else { "yes".to_string() },
+                    format_date(Some(item.updated_at)),
+                ];
+                let cells: Vec<String> = row.iter().map(|c| table_cell(c)).collect();
+                out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+            Ok(out)
+        }
+        other => Err(anyhow!("Unsupported export format: {}", other)),
+    }
+}
+
+fn table_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
+
+/// `card` and, indented under it, its subtasks at every depth. `written`
+/// keeps a parent loop from repeating forever.
+fn push_markdown_tree<'a>(
+    out: &mut String,
+    cards: &[&'a KanbanItem],
+    card: &'a KanbanItem,
+    depth: usize,
+    written: &mut HashSet<&'a str>,
+) {
+    if !written.insert(card.id.as_str()) {
+        return;
+    }
+    push_markdown_card(out, card, depth);
+    for sub in cards.iter().filter(|c| c.parent_id.as_deref() == Some(card.id.as_str())) {
+        push_markdown_tree(out, cards, sub, depth + 1, written);
+    }
+}
+
+fn push_markdown_card(out: &mut String, card: &KanbanItem, depth: usize) {
+    let check = if card.column == "done" { "x" } else { " " };
+    out.push_str(&format!("{}- [{}] {}", "  ".repeat(depth), check, card.title));
+    if card.due_at.is_some() {
+        out.push_str(&format!(" (due {})", format_date(card.due_at)));
+    }
+    out.push('\n');
+}
+
+// ── Flow metrics ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+pub struct KanbanMetrics {
+    pub range_start: Option<i64>,
+    pub completed: u32,
+    pub cycle_time_avg_ms: Option<i64>,
+    pub cycle_time_median_ms: Option<i64>,
+    pub throughput: Vec<WeeklyThroughput>,
+    pub column_time: Vec<ColumnTime>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WeeklyThroughput {
+    pub week_start: String, // ISO date of the Monday
+    pub completed: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnTime {
+    pub column: String,
+    pub avg_ms: Option<i64>,
+    pub samples: u32,
+}
+
+/// Parse a range like "7d" / "30d" / "all" into a start timestamp (ms).
+fn range_start(range: Option<&str>, now: i64) -> anyhow::Result<Option<i64>> {
+    let range = range.unwrap_or("30d");
+    if range == "all" {
+        return Ok(None);
+    }
+    let days: i64 = range
+        .strip_suffix('d')
+        .and_then(|d| d.parse().ok())
+        .filter(|d| *d > 0)
+        .ok_or_else(|| anyhow!("Invalid range: {} (expected e.g. 30d or all)", range))?;
+    Ok(Some(now - days * 24 * 60 * 60 * 1000))
+}
+
+/// Cycle time (first in_progress → done), weekly throughput, and average time
+/// spent per column, all derived from the kanban activity log.
+pub fn compute_metrics(
+    conn: &rusqlite::Connection,
+    project_id: Option<&str>,
+    range: Option<&str>,
+) -> anyhow::Result<KanbanMetrics> {
+    let now = Utc::now().timestamp_millis();
+    let start = range_start(range, now)?;
+    let in_range = |ts: i64| start.map(|s| ts >= s).unwrap_or(true);
+
+    let moves = db::list_kanban_activity(conn, project_id)?;
+    let mut by_item: BTreeMap<&str, Vec<&db::KanbanMove>> = BTreeMap::new();
+    for m in &moves {
+        by_item.entry(m.item_id.as_str()).or_default().push(m);
+    }
+
+    let mut cycle_times = Vec::new();
+    let mut weekly: BTreeMap<chrono::NaiveDate, u32> = BTreeMap::new();
+    let mut column_totals: BTreeMap<&str, (i64, u32)> = BTreeMap::new();
+
+    for history in by_item.values() {
+        let mut started_at: Option<i64> = None;
+        for (i, m) in history.iter().enumerate() {
+            // Time in column: from entering until the next move (or now if still there)
+            let left_at = history.get(i + 1).map(|n| n.at).unwrap_or(now);
+            if m.to_column != "done" && in_range(left_at) {
+                let entry = column_totals.entry(m.to_column.as_str()).or_insert((0, 0));
+                entry.0 += left_at - m.at;
+                entry.1 += 1;
+            }
+
+            if m.to_column == "in_progress" && started_at.is_none() {
+                started_at = Some(m.at);
+            }
+            if m.to_column == "done" && in_range(m.at) {
+                if let Some(s) = started_at {
+                    cycle_times.push(m.at - s);
+                }
+                if let Some(dt) = DateTime::<Utc>::from_timestamp_millis(m.at) {
+                    let date = dt.date_naive();
+                    let monday = date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+                    *weekly.entry(monday).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    cycle_times.sort_unstable();
+    let cycle_time_avg_ms = if cycle_times.is_empty() {
+        None
+    } else {
+        Some(cycle_times.iter().sum::<i64>() / cycle_times.len() as i64)
+    };
+    let cycle_time_median_ms = cycle_times.get(cycle_times.len() / 2).copied();
+
+    let column_time = COLUMNS
+        .iter()
+        .filter(|c| **c != "done")
+        .map(|c| {
+            let (total, samples) = column_totals.get(c).copied().unwrap_or((0, 0));
+            ColumnTime {
+                column: c.to_string(),
+                avg_ms: if samples > 0 { Some(total / samples as i64) } else { None },
+                samples,
+            }
+        })
+        .collect();
+
+    Ok(KanbanMetrics {
+        range_start: start,
+        completed: weekly.values().sum(),
+        cycle_time_avg_ms,
+        cycle_time_median_ms,
+        throughput: weekly
+            .into_iter()
+            .map(|(week, completed)| WeeklyThroughput {
+                week_start: week.to_string(),
+                completed,
+            })
+            .collect(),
+        column_time,
+    })
+}
@@ -0,0 +1,86 @@
+use anyhow::{anyhow, Result};
+use tokio::io::AsyncWriteExt;
+
+/// Save `secret` for `service`/`account` in the OS keychain (macOS Keychain
+/// or the Secret Service via `secret-tool`). The secret goes over stdin,
+/// never argv.
+pub async fn store(service: &str, account: &str, label: &str, secret: &str) -> Result<()> {
+    let (mut cmd, input) = if cfg!(target_os = "macos") {
+        let mut cmd = tokio::process::Command::new("/usr/bin/security");
+        cmd.arg("-i");
+        let line = format!(
+            "add-generic-password -U -s {} -a {} -l {} -w {}\n",
+            quote(service),
+            quote(account),
+            quote(label),
+            quote(secret)
+        );
+        (cmd, line)
+    } else {
+        let mut cmd = tokio::process::Command::new("secret-tool");
+        cmd.args(["store", "--label", label, "service", service, "account", account]);
+        (cmd, secret.to_string())
+    };
+    let mut child = cmd
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Keychain unavailable: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to store {}: {}",
+            label,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// The stored secret, or `None` when there isn't one.
+pub async fn read(service: &str, account: &str) -> Result<Option<String>> {
+    let output = if cfg!(target_os = "macos") {
+        tokio::process::Command::new("/usr/bin/security")
+            .args(["find-generic-password", "-s", service, "-a", account, "-w"])
+            .output()
+            .await
+    } else {
+        tokio::process::Command::new("secret-tool")
+            .args(["lookup", "service", service, "account", account])
+            .output()
+            .await
+    }
+    .map_err(|e| anyhow!("Keychain unavailable: {}", e))?;
+    // Both tools exit non-zero when nothing matches
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let secret = String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').to_string();
+    Ok(Some(secret).filter(|s| !s.is_empty()))
+}
+
+/// Remove the stored secret; nothing to remove is fine.
+pub async fn delete(service: &str, account: &str) -> Result<()> {
+    let output = if cfg!(target_os = "macos") {
+        tokio::process::Command::new("/usr/bin/security")
+            .args(["delete-generic-password", "-s", service, "-a", account])
+            .output()
+            .await
+    } else {
+        tokio::process::Command::new("secret-tool")
+            .args(["clear", "service", service, "account", account])
+            .output()
+            .await
+    };
+    output.map_err(|e| anyhow!("Keychain unavailable: {}", e))?;
+    Ok(())
+}
+
+/// Quoting for `security -i`, which reads its commands like a shell would.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
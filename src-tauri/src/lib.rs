@@ -1,15 +1,38 @@
 #![allow(dead_code, unused_imports)]
+mod blobs;
+mod calendar;
 mod db;
+mod deeplink;
+mod dnd;
+mod error;
+mod github;
+mod http;
+mod insights;
 mod kanban;
+mod keychain;
+mod maintenance;
+mod mcp;
+mod mirror;
+mod notifications;
 mod obsidian;
 mod openclaw;
 mod proactive;
+mod prompts;
+mod relations;
+mod scheduler;
+mod settings;
 mod ssh;
+mod sync;
+mod titles;
+mod tray;
 mod watcher;
+mod webhooks;
+mod workspace;
 
 use crate::db::*;
-use crate::openclaw::{load_session, ChatMessage};
-use crate::ssh::{new_shared_session, ConnectionStatus, SharedSshSession, SshConfig};
+use crate::error::AppError;
+use crate::openclaw::{load_session, AgentConfig, AgentInfo, ChatMessage, DEFAULT_AGENT_ID};
+use crate::ssh::{new_shared_session, SharedSshSession, SshConfig, SshHandle, SshManager};
 use crate::watcher::{watch_session, WatcherState};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
@@ -20,18 +43,26 @@ use uuid::Uuid;
 // ── Shared state ──────────────────────────────────────────────────────────────
 
 struct AppState {
-    db: Arc<Mutex<rusqlite::Connection>>,
+    db: db::Db,
     watcher_state: Arc<Mutex<WatcherState>>,
     ssh_session: SharedSshSession,
+    ssh_manager: SshManager,
     remote_mode: Arc<Mutex<bool>>,
+    /// The thread on screen while the window has focus, as the frontend reports it.
+    focused_thread: Arc<Mutex<Option<String>>>,
 }
 
 // ── Project commands ──────────────────────────────────────────────────────────
 
 #[tauri::command]
-async fn cmd_list_projects(state: State<'_, AppState>) -> Result<Vec<Project>, String> {
-    let conn = state.db.lock().unwrap();
-    list_projects(&conn).map_err(|e| e.to_string())
+async fn cmd_list_projects(
+    state: State<'_, AppState>,
+    include_archived: Option<bool>,
+) -> Result<Vec<Project>, AppError> {
+    state.db.call(move |conn| {
+        list_projects(conn, include_archived.unwrap_or(false)).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -40,39 +71,106 @@ async fn cmd_create_project(
     name: String,
     description: Option<String>,
     color: Option<String>,
-) -> Result<Project, String> {
+    agent_id: Option<String>,
+) -> Result<Project, AppError> {
     let now = Utc::now().timestamp_millis();
     let project = Project {
         id: Uuid::new_v4().to_string(),
         name,
         description,
         color,
-        agent_id: "main".to_string(),
+        agent_id: agent_id.unwrap_or_else(|| DEFAULT_AGENT_ID.to_string()),
+        status: "active".to_string(),
+        obsidian_source: None,
+        tags: Vec::new(),
+        priority: 0,
+        due_at: None,
         created_at: now,
         updated_at: now,
     };
-    let conn = state.db.lock().unwrap();
-    create_project(&conn, &project).map_err(|e| e.to_string())?;
-    Ok(project)
+    state.db.call(move |conn| {
+        create_project(conn, &project)?;
+        Ok(project)
+    })
+    .await
 }
 
 #[tauri::command]
 async fn cmd_update_project(
     state: State<'_, AppState>,
+    app: AppHandle,
     id: String,
     name: String,
     description: Option<String>,
     color: Option<String>,
-) -> Result<(), String> {
-    let conn = state.db.lock().unwrap();
-    update_project(&conn, &id, &name, description.as_deref(), color.as_deref())
-        .map_err(|e| e.to_string())
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        update_project(conn, &id, &name, description.as_deref(), color.as_deref())?;
+        write_back_to_vault(conn, &app, &id);
+        Ok(())
+    })
+    .await
+}
+
+/// Agent that new threads in the project run on.
+#[tauri::command]
+async fn cmd_set_project_agent(
+    state: State<'_, AppState>,
+    id: String,
+    agent_id: String,
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        set_project_agent(conn, &id, &agent_id).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_get_related_projects(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<Vec<Project>, AppError> {
+    state.db.call(move |conn| {
+        db::get_related_projects(conn, &project_id).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_set_project_status(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    id: String,
+    status: String,
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        set_project_status(conn, &id, &status)?;
+        write_back_to_vault(conn, &app, &id);
+        Ok(())
+    })
+    .await
+}
+
+/// Mirror app-side project edits into the Obsidian note; failures never block the edit.
+fn write_back_to_vault(conn: &rusqlite::Connection, app: &AppHandle, project_id: &str) {
+    match obsidian::write_back_project(conn, project_id) {
+        Ok(obsidian::WriteBack::Conflict(path)) => {
+            let _ = app.emit(
+                "obsidian:conflict",
+                serde_json::json!({ "projectId": project_id, "path": path.to_string_lossy() }),
+            );
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Obsidian write-back failed for {}: {}", project_id, e),
+    }
 }
 
 #[tauri::command]
-async fn cmd_delete_project(state: State<'_, AppState>, id: String) -> Result<(), String> {
-    let conn = state.db.lock().unwrap();
-    delete_project(&conn, &id).map_err(|e| e.to_string())
+async fn cmd_delete_project(state: State<'_, AppState>, id: String) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        relations::delete(conn, relations::PROJECTS, &id).map_err(AppError::from)
+    })
+    .await
 }
 
 // ── Thread commands ───────────────────────────────────────────────────────────
@@ -81,9 +179,11 @@ async fn cmd_delete_project(state: State<'_, AppState>, id: String) -> Result<()
 async fn cmd_list_threads(
     state: State<'_, AppState>,
     project_id: Option<String>,
-) -> Result<Vec<Thread>, String> {
-    let conn = state.db.lock().unwrap();
-    list_threads(&conn, project_id.as_deref()).map_err(|e| e.to_string())
+) -> Result<Vec<Thread>, AppError> {
+    state.db.call(move |conn| {
+        list_threads(conn, project_id.as_deref()).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -92,21 +192,95 @@ async fn cmd_create_thread(
     project_id: Option<String>,
     name: String,
     agent_id: Option<String>,
-) -> Result<Thread, String> {
+) -> Result<Thread, AppError> {
     let now = Utc::now().timestamp_millis();
-    let thread = Thread {
-        id: Uuid::new_v4().to_string(),
-        project_id,
-        name,
-        session_id: Uuid::new_v4().to_string(),
-        agent_id: agent_id.unwrap_or_else(|| "main".to_string()),
-        created_at: now,
-        updated_at: now,
-        last_message_at: None,
+    state.db.call(move |conn| {
+        let agent_id = match agent_id {
+            Some(agent_id) => agent_id,
+            None => project_agent(conn, project_id.as_deref())?,
+        };
+        let thread = Thread {
+            id: Uuid::new_v4().to_string(),
+            project_id,
+            name,
+            session_id: Uuid::new_v4().to_string(),
+            agent_id,
+            created_at: now,
+            updated_at: now,
+            last_message_at: None,
+            execution_target: None,
+            model: None,
+            muted: false,
+        };
+        create_thread(conn, &thread)?;
+        Ok(thread)
+    })
+    .await
+}
+
+/// The project's agent, or the default agent for threads outside a project.
+fn project_agent(conn: &rusqlite::Connection, project_id: Option<&str>) -> Result<String, AppError> {
+    let project = match project_id {
+        Some(id) => get_project(conn, id)?,
+        None => None,
     };
-    let conn = state.db.lock().unwrap();
-    create_thread(&conn, &thread).map_err(|e| e.to_string())?;
-    Ok(thread)
+    Ok(project.map_or_else(|| DEFAULT_AGENT_ID.to_string(), |p| p.agent_id))
+}
+
+/// Pin a thread to "local" or "remote", or pass `None` to follow the global mode.
+#[tauri::command]
+async fn cmd_set_thread_execution_target(
+    state: State<'_, AppState>,
+    id: String,
+    target: Option<String>,
+) -> Result<(), AppError> {
+    if let Some(t) = target.as_deref() {
+        if t != "local" && t != "remote" {
+            return Err(AppError::InvalidInput(format!("Unknown execution target: {}", t)));
+        }
+    }
+    state.db.call(move |conn| {
+        set_thread_execution_target(conn, &id, target.as_deref()).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Default model for a thread's messages; `None` goes back to the agent's.
+#[tauri::command]
+async fn cmd_set_thread_model(
+    state: State<'_, AppState>,
+    id: String,
+    model: Option<String>,
+) -> Result<(), AppError> {
+    let model = model.filter(|m| !m.trim().is_empty());
+    state.db.call(move |conn| {
+        set_thread_model(conn, &id, model.as_deref()).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Mute or unmute OS notifications for a thread's replies.
+#[tauri::command]
+async fn cmd_set_thread_muted(state: State<'_, AppState>, id: String, muted: bool) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        set_thread_muted(conn, &id, muted).map_err(AppError::from)
+    })
+    .await
+}
+
+/// The thread the user is looking at, or `None` when no thread is shown or
+/// the window is in the background. Replies there don't notify.
+#[tauri::command]
+async fn cmd_set_focused_thread(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    thread_id: Option<String>,
+) -> Result<(), AppError> {
+    if let Some(id) = thread_id.as_deref() {
+        tray::mark_read(&app, id);
+    }
+    *state.focused_thread.lock().unwrap() = thread_id;
+    Ok(())
 }
 
 #[tauri::command]
@@ -115,119 +289,434 @@ async fn cmd_rename_thread(
     app: AppHandle,
     id: String,
     name: String,
-) -> Result<(), String> {
-    let conn = state.db.lock().unwrap();
-    rename_thread(&conn, &id, &name).map_err(|e| e.to_string())?;
-    let _ = app.emit(
-        "thread:renamed",
-        serde_json::json!({ "threadId": id, "name": name }),
-    );
-    Ok(())
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        rename_thread(conn, &id, &name)?;
+        let _ = app.emit(
+            "thread:renamed",
+            serde_json::json!({ "threadId": id, "name": name }),
+        );
+        Ok(())
+    })
+    .await
 }
 
+/// Delete a thread, stop tailing its session and, once no other thread
+/// shares the session, keep, archive or delete the file per `session_retention`.
+/// Remote sessions stay on the host; only their local mirror goes.
 #[tauri::command]
-async fn cmd_delete_thread(state: State<'_, AppState>, id: String) -> Result<(), String> {
-    let conn = state.db.lock().unwrap();
-    delete_thread(&conn, &id).map_err(|e| e.to_string())
+async fn cmd_delete_thread(state: State<'_, AppState>, id: String) -> Result<(), AppError> {
+    let remote_default = *state.remote_mode.lock().unwrap();
+    let (thread, orphaned, retention) = state
+        .db
+        .call(move |conn| {
+            let thread = get_thread(conn, &id)?;
+            relations::delete(conn, relations::THREADS, &id)?;
+            let orphaned = match &thread {
+                Some(t) => get_thread_by_session(conn, &t.session_id)?.is_none(),
+                None => false,
+            };
+            let retention = db::get_setting(conn, openclaw::RETENTION_SETTING)?.unwrap_or_default();
+            Ok::<_, AppError>((thread, orphaned, retention))
+        })
+        .await?;
+    let Some(thread) = thread.filter(|_| orphaned) else {
+        return Ok(());
+    };
+    watcher::stop_watching(Arc::clone(&state.watcher_state), &thread.session_id);
+    let retention = retention.trim();
+    if thread.runs_remote(remote_default) {
+        if retention == "archive" || retention == "delete" {
+            let cache = mirror::cache_path(&thread.agent_id, &thread.session_id)?;
+            if cache.exists() {
+                std::fs::remove_file(cache)?;
+            }
+        }
+        return Ok(());
+    }
+    openclaw::retire_session(&thread.agent_id, &thread.session_id, retention)?;
+    Ok(())
 }
 
 // ── Chat commands ─────────────────────────────────────────────────────────────
 
+/// Where a thread's messages run: its own override, else the global remote mode.
+async fn runs_remote(state: &AppState, thread_id: Option<&str>) -> Result<bool, AppError> {
+    let remote_default = *state.remote_mode.lock().unwrap();
+    let Some(thread_id) = thread_id.map(str::to_string) else {
+        return Ok(remote_default);
+    };
+    state.db.call(move |conn| {
+        let thread = get_thread(conn, &thread_id)?;
+        Ok(thread.map_or(remote_default, |t| t.runs_remote(remote_default)))
+    })
+    .await
+}
+
+/// Messages waiting for the remote host, optionally for one thread.
+#[tauri::command]
+async fn cmd_list_outbox(
+    state: State<'_, AppState>,
+    thread_id: Option<String>,
+) -> Result<Vec<OutboxMessage>, AppError> {
+    state.db.call(move |conn| {
+        list_outbox(conn, thread_id.as_deref()).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_discard_outbox(state: State<'_, AppState>, id: String) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        delete_outbox(conn, &id).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Handle on the live SSH session, reconnecting first if it dropped. The
+/// session lock is released before the caller runs anything remote, so
+/// long-running commands don't block each other.
+async fn remote_handle(state: &AppState) -> anyhow::Result<SshHandle> {
+    ssh::ensure_connected(&state.ssh_session).await?;
+    state.ssh_session.lock().await.handle()
+}
+
 #[tauri::command]
 async fn cmd_load_session(
     state: State<'_, AppState>,
+    app: AppHandle,
     agent_id: String,
     session_id: String,
-) -> Result<Vec<ChatMessage>, String> {
-    let remote = *state.remote_mode.lock().unwrap();
-    if remote {
-        let ssh = state.ssh_session.lock().await;
-        let content = ssh
-            .read_session_file(&agent_id, &session_id)
-            .await
-            .map_err(|e| e.to_string())?;
-        let messages = content
-            .lines()
-            .filter_map(openclaw::parse_jsonl_line)
-            .collect();
-        Ok(messages)
+    thread_id: Option<String>,
+) -> Result<Vec<ChatMessage>, AppError> {
+    let remote = runs_remote(&state, thread_id.as_deref()).await?;
+    let parsed = if remote {
+        let fetched = match remote_handle(&state).await {
+            Ok(handle) => handle.read_session_file(&agent_id, &session_id).await,
+            Err(e) => Err(e),
+        };
+        match fetched {
+            Ok(content) => {
+                if !content.is_empty() {
+                    let _ = mirror::write_cache(&agent_id, &session_id, &content);
+                }
+                openclaw::parse_session(&content)
+            }
+            // Offline: serve the last mirrored copy if we have one
+            Err(e) => match mirror::load_cached(&agent_id, &session_id) {
+                Ok(Some(cached)) => cached,
+                _ => return Err(e.into()),
+            },
+        }
     } else {
-        load_session(&agent_id, &session_id).map_err(|e| e.to_string())
+        let parsed = load_session(&agent_id, &session_id)?;
+        match mirror::load_cached(&agent_id, &session_id) {
+            // Sessions that only ever ran remotely are readable from the mirror
+            Ok(Some(cached)) if parsed.messages.is_empty() => cached,
+            _ => parsed,
+        }
+    };
+    if !parsed.corrupt.is_empty() {
+        let _ = app.emit(
+            "session:corrupt_lines",
+            serde_json::json!({
+                "agentId": agent_id,
+                "sessionId": session_id,
+                "lines": parsed.corrupt,
+            }),
+        );
     }
+    Ok(parsed.messages)
 }
 
+/// Messages shown when a thread opens, and per "load earlier" page.
+const SESSION_PAGE_SIZE: u32 = 200;
+
+/// The newest `limit` messages before `before` (a cursor from the previous
+/// page; unset for the end of the session), so long sessions open without
+/// reading the whole file. Remote sessions are fetched into the mirror cache
+/// on the first page and paged from there.
 #[tauri::command]
-async fn cmd_send_message(
+async fn cmd_load_session_page(
     state: State<'_, AppState>,
-    app: AppHandle,
-    thread_id: String,
     agent_id: String,
     session_id: String,
-    message: String,
-) -> Result<(), String> {
-    // Touch the thread to update last_message_at
-    {
-        let conn = state.db.lock().unwrap();
-        touch_thread(&conn, &thread_id).map_err(|e| e.to_string())?;
+    thread_id: Option<String>,
+    limit: Option<u32>,
+    before: Option<u64>,
+) -> Result<openclaw::SessionPage, AppError> {
+    let remote = runs_remote(&state, thread_id.as_deref()).await?;
+    if remote && before.is_none() {
+        let fetched = match remote_handle(&state).await {
+            Ok(handle) => handle.read_session_file(&agent_id, &session_id).await,
+            Err(e) => Err(e),
+        };
+        match fetched {
+            Ok(content) if !content.is_empty() => {
+                mirror::write_cache(&agent_id, &session_id, &content)?;
+            }
+            Ok(_) => {}
+            // Offline: page through the last mirrored copy if we have one
+            Err(e) if !mirror::cache_path(&agent_id, &session_id)?.exists() => return Err(e.into()),
+            Err(_) => {}
+        }
+    }
+    let local = openclaw::session_path(&agent_id, &session_id);
+    // Sessions that only ever ran remotely are readable from the mirror
+    let path = if remote || !local.exists() {
+        mirror::cache_path(&agent_id, &session_id)?
+    } else {
+        local
+    };
+    let limit = limit.unwrap_or(SESSION_PAGE_SIZE).max(1) as usize;
+    Ok(openclaw::read_session_page(&path, before, limit)?)
+}
+
+/// Messages a compacted session keeps as they are.
+const COMPACT_KEEP_MESSAGES: usize = 40;
+/// Summarized messages reach the agent cut to this many characters each,
+/// and the transcript to `COMPACT_TRANSCRIPT_CHARS`, dropping the oldest.
+const COMPACT_EXCERPT_CHARS: usize = 2000;
+const COMPACT_TRANSCRIPT_CHARS: usize = 100_000;
+
+/// Replace all but the last `keep` messages of a local thread's session with
+/// a summary written by openclaw, so the thread opens quickly and its
+/// context stays small. The original file is archived; an open chat reloads
+/// through `chat:session_reset`.
+#[tauri::command]
+async fn cmd_compact_session(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    thread_id: String,
+    keep: Option<usize>,
+) -> Result<openclaw::CompactionResult, AppError> {
+    let id = thread_id.clone();
+    let thread = state
+        .db
+        .call(move |conn| get_thread(conn, &id).map_err(AppError::from))
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Thread {} not found", thread_id)))?;
+    if runs_remote(&state, Some(&thread_id)).await? {
+        return Err(AppError::InvalidInput("Only local sessions can be compacted".to_string()));
+    }
+    if watcher::awaiting_reply(&state.watcher_state, &thread.session_id) {
+        return Err(AppError::InvalidInput("The agent is still replying in this thread".to_string()));
+    }
+    let path = openclaw::session_path(&thread.agent_id, &thread.session_id);
+    let content = std::fs::read_to_string(&path)?;
+    let keep = keep.unwrap_or(COMPACT_KEEP_MESSAGES).max(1);
+    let Some(split) = openclaw::split_for_compaction(&content, keep) else {
+        return Err(AppError::InvalidInput(format!("Nothing to compact: the session has {} messages or fewer", keep)));
+    };
+
+    let mut transcript = Vec::new();
+    let mut size = 0;
+    for m in split.older.iter().rev() {
+        let line = format!("{}: {}", m.role, m.content.chars().take(COMPACT_EXCERPT_CHARS).collect::<String>());
+        size += line.chars().count() + 1;
+        if size > COMPACT_TRANSCRIPT_CHARS {
+            break;
+        }
+        transcript.push(line);
+    }
+    transcript.reverse();
+    let prompt = prompts::build(
+        prompts::COMPACTION,
+        &[
+            ("thread", &thread.name),
+            ("count", &split.older.len().to_string()),
+            ("transcript", &transcript.join("\n")),
+        ],
+    )
+    .await?;
+    // A session of its own, so the summary request stays out of the thread
+    let summary = openclaw::send_and_capture(openclaw::DEFAULT_AGENT_ID, None, thread.model.as_deref(), &prompt).await?;
+    if summary.content.trim().is_empty() {
+        return Err(AppError::Internal("openclaw returned an empty summary".to_string()));
+    }
+    let line = openclaw::compaction_line(&summary.content, split.older.len())?;
+
+    let (agent_id, session_id) = (thread.agent_id.clone(), thread.session_id.clone());
+    let (compacted, archived) = watcher::rewrite_session(&state.watcher_state, &session_id, || {
+        let (content, archived) = openclaw::write_compacted_session(&agent_id, &session_id, &content, &split, &line)?;
+        let len = content.len() as u64;
+        Ok((content, (len, archived)))
+    })?;
+    let _ = app.emit(
+        "chat:session_reset",
+        serde_json::json!({ "sessionId": thread.session_id }),
+    );
+    Ok(openclaw::CompactionResult {
+        summarized: split.older.len(),
+        kept: keep,
+        bytes_before: content.len() as u64,
+        bytes_after: compacted,
+        archived: archived.to_string_lossy().to_string(),
+    })
+}
+
+fn user_message(content: &str) -> ChatMessage {
+    ChatMessage {
+        role: "user".to_string(),
+        content: content.to_string(),
+        parts: Vec::new(),
+    }
+}
+
+/// Failures the UI handles specially get their own event on top of the
+/// command's error string.
+fn report_agent_error(app: &AppHandle, thread_id: &str, session_id: &str, e: &anyhow::Error) {
+    if let Some(timeout) = e.downcast_ref::<openclaw::AgentTimeout>() {
+        let _ = app.emit(
+            "chat:timeout",
+            serde_json::json!({
+                "threadId": thread_id,
+                "sessionId": session_id,
+                "timeoutSecs": timeout.secs,
+            }),
+        );
+    }
+    if let Some(format) = e.downcast_ref::<openclaw::UnrecognizedOutput>() {
+        let _ = app.emit(
+            "openclaw:format_error",
+            serde_json::json!({
+                "threadId": thread_id,
+                "version": format.version,
+                "sample": format.sample,
+            }),
+        );
     }
+}
+
+/// Bracket an agent run with `chat:processing_started` and
+/// `chat:processing_finished` so the UI shows a spinner exactly while the
+/// agent is working.
+async fn with_processing<T>(
+    app: &AppHandle,
+    thread_id: &str,
+    session_id: &str,
+    run: impl std::future::Future<Output = anyhow::Result<T>>,
+) -> anyhow::Result<T> {
+    let _ = app.emit(
+        "chat:processing_started",
+        serde_json::json!({ "threadId": thread_id, "sessionId": session_id }),
+    );
+    let result = run.await;
+    let _ = app.emit(
+        "chat:processing_finished",
+        serde_json::json!({ "threadId": thread_id, "sessionId": session_id, "ok": result.is_ok() }),
+    );
+    result
+}
 
-    let remote = *state.remote_mode.lock().unwrap();
+/// Send `message` to the thread's agent where the thread runs: over SSH
+/// (queued behind the outbox while the host is unreachable or earlier
+/// messages are held back) or through the local openclaw. Replies go out
+/// as "chat:message".
+async fn deliver_message(
+    app: &AppHandle,
+    state: &AppState,
+    remote: bool,
+    thread_id: &str,
+    agent_id: &str,
+    session_id: &str,
+    message: String,
+    model: Option<String>,
+) -> Result<(), AppError> {
     if remote {
-        let ssh = state.ssh_session.lock().await;
-        ssh.send_message_remote(&agent_id, &session_id, &message)
+        let handle = remote_handle(state).await.ok();
+        // Earlier queued messages go first so the remote session stays in order
+        if let Some(handle) = &handle {
+            mirror::flush_outbox(app, handle).await?;
+        }
+        let id = thread_id.to_string();
+        let still_queued = state
+            .db
+            .call(move |conn| list_outbox(conn, Some(&id)).map(|queued| !queued.is_empty()))
+            .await?;
+        let handle = match handle {
+            Some(handle) if !still_queued => handle,
+            // Host unreachable, or a failed delivery is holding this thread's
+            // queue back: queue it behind the rest and deliver from the mirror loop
+            _ => {
+                let queued = OutboxMessage {
+                    id: Uuid::new_v4().to_string(),
+                    thread_id: thread_id.to_string(),
+                    agent_id: agent_id.to_string(),
+                    session_id: session_id.to_string(),
+                    message,
+                    created_at: Utc::now().timestamp_millis(),
+                    attempts: 0,
+                    last_error: None,
+                    model: model.clone(),
+                };
+                state.db.call(move |conn| mirror::enqueue(conn, &queued)).await?;
+                let _ = app.emit(
+                    "mirror:queued",
+                    serde_json::json!({ "threadId": thread_id, "sessionId": session_id }),
+                );
+                return Ok(());
+            }
+        };
+        webhooks::publish_message(thread_id, session_id, &user_message(&message));
+        let run = handle.send_message_remote(agent_id, session_id, model.as_deref(), &message, |reply| {
+            webhooks::publish_message(thread_id, session_id, &reply);
+            let _ = app.emit(
+                "chat:message",
+                watcher::MessageEvent {
+                    session_id: session_id.to_string(),
+                    message: reply,
+                },
+            );
+        });
+        with_processing(app, thread_id, session_id, run)
             .await
-            .map_err(|e| e.to_string())?;
+            .inspect_err(|e| report_agent_error(app, thread_id, session_id, e))?;
+        let _ = app.emit("kanban:refresh", ());
         return Ok(());
     }
 
     // Build augmented message with project context if thread belongs to a project
-    let augmented_message = {
-        let conn = state.db.lock().unwrap();
-        let thread = get_thread(&conn, &thread_id).ok().flatten();
-        if let Some(ref t) = thread {
-            if let Some(ref pid) = t.project_id {
-                if let Some(project) = get_project(&conn, pid).ok().flatten() {
-                    format!(
-                        "[System context: You are in project \"{}\" (id: {}). You can create kanban cards using the kanban-card command. Always use this project id when creating cards.]\n\n{}",
-                        project.name, project.id, message
-                    )
-                } else {
-                    message.clone()
-                }
-            } else {
-                message.clone()
-            }
-        } else {
-            message.clone()
+    let id = thread_id.to_string();
+    let project = state
+        .db
+        .call(move |conn| {
+            let thread = get_thread(conn, &id)?;
+            let project_id = thread.and_then(|t| t.project_id);
+            Ok::<_, AppError>(project_id.and_then(|pid| get_project(conn, &pid).ok().flatten()))
+        })
+        .await
+        .ok()
+        .flatten();
+    let augmented_message = match project {
+        Some(project) => {
+            let context = format!(
+                "You are in project \"{}\" (id: {}). You can create kanban cards using the kanban-card command. Always use this project id when creating cards.",
+                project.name, project.id
+            );
+            openclaw::with_context(&context, &message)
         }
+        None => message.clone(),
     };
 
-    // Write user message to our JSONL file immediately (original, no context prefix)
-    let user_msg = openclaw::ChatMessage {
-        role: "user".to_string(),
-        content: message.clone(),
-    };
-    openclaw::append_message(&agent_id, &session_id, &user_msg)
-        .map_err(|e| format!("Failed to write user message: {}", e))?;
-
-    // Send augmented message to openclaw and capture stdout response
-    let response_text = openclaw::send_and_capture(&agent_id, &augmented_message)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // Write assistant response to our JSONL file
-    let assistant_msg = openclaw::ChatMessage {
-        role: "assistant".to_string(),
-        content: response_text.clone(),
+    // Send augmented message to openclaw and capture stdout response. Like on
+    // the remote host, openclaw writes both sides to the session file.
+    webhooks::publish_message(thread_id, session_id, &user_message(&message));
+    let run = openclaw::send_and_capture(agent_id, Some(session_id), model.as_deref(), &augmented_message);
+    let assistant_msg = match with_processing(app, thread_id, session_id, run).await {
+        Ok(reply) => reply,
+        Err(e) => {
+            report_agent_error(app, thread_id, session_id, &e);
+            return Err(e.into());
+        }
     };
-    openclaw::append_message(&agent_id, &session_id, &assistant_msg)
-        .map_err(|e| format!("Failed to write assistant message: {}", e))?;
 
     // Emit the assistant message to the frontend
+    webhooks::publish_message(thread_id, session_id, &assistant_msg);
     let _ = app.emit(
         "chat:message",
         watcher::MessageEvent {
-            session_id: session_id.clone(),
+            session_id: session_id.to_string(),
             message: assistant_msg,
         },
     );
@@ -235,26 +724,56 @@ async fn cmd_send_message(
     // Notify kanban board to refresh (agent may have created cards)
     let _ = app.emit("kanban:refresh", ());
 
+    Ok(())
+}
+
+#[tauri::command]
+async fn cmd_send_message(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    thread_id: String,
+    agent_id: String,
+    session_id: String,
+    message: String,
+    model: Option<String>,
+) -> Result<(), AppError> {
+    // Touch the thread to update last_message_at
+    let id = thread_id.clone();
+    let thread_model = state
+        .db
+        .call(move |conn| {
+            touch_thread(conn, &id)?;
+            Ok::<_, AppError>(get_thread(conn, &id).ok().flatten().and_then(|t| t.model))
+        })
+        .await?;
+    // A model picked for this message wins over the thread's default
+    let model = model.filter(|m| !m.trim().is_empty()).or(thread_model);
+
+    let remote = runs_remote(&state, Some(&thread_id)).await?;
+    deliver_message(&app, &state, remote, &thread_id, &agent_id, &session_id, message.clone(), model).await?;
+    if remote {
+        return Ok(());
+    }
+
     // Auto-title: if thread name is "New thread", generate a title from the user message
-    let should_title = {
-        let conn = state.db.lock().unwrap();
-        get_thread(&conn, &thread_id)
-            .ok()
-            .flatten()
-            .map(|t| t.name == "New thread")
-            .unwrap_or(false)
-    };
+    let id = thread_id.clone();
+    let should_title = state
+        .db
+        .call(move |conn| get_thread(conn, &id))
+        .await
+        .ok()
+        .flatten()
+        .map(|t| t.name == "New thread")
+        .unwrap_or(false);
     if should_title {
         let tid = thread_id.clone();
         let msg = message.clone();
         let app2 = app.clone();
-        let db = Arc::clone(&state.db);
+        let db = state.db.clone();
         tauri::async_runtime::spawn(async move {
-            if let Ok(title) = openclaw::generate_title(&msg).await {
-                {
-                    let conn = db.lock().unwrap();
-                    let _ = rename_thread(&conn, &tid, &title);
-                }
+            if let Ok(title) = titles::for_message(&msg).await {
+                let (id, name) = (tid.clone(), title.clone());
+                let _ = db.call(move |conn| rename_thread(conn, &id, &name)).await;
                 let _ = app2.emit(
                     "thread:renamed",
                     serde_json::json!({ "threadId": tid, "name": title }),
@@ -266,31 +785,127 @@ async fn cmd_send_message(
     Ok(())
 }
 
+/// Tail a session. `poll` checks the file on a timer instead of waiting for
+/// file events; unset follows the `watcher_mode` setting.
 #[tauri::command]
 async fn cmd_watch_session(
     state: State<'_, AppState>,
     app: AppHandle,
     agent_id: String,
     session_id: String,
-) -> Result<(), String> {
+    poll: Option<bool>,
+) -> Result<(), AppError> {
+    let poll = match poll {
+        Some(poll) => poll,
+        None => state.db.call(|conn| Ok::<_, AppError>(watcher_polls(conn))).await?,
+    };
     let watcher_state = Arc::clone(&state.watcher_state);
-    watch_session(app, watcher_state, agent_id, session_id)
+    watch_session(app, watcher_state, agent_id, session_id, poll)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
+}
+
+/// Whether `watcher_mode` asks for polling everywhere.
+fn watcher_polls(conn: &rusqlite::Connection) -> bool {
+    let mode = db::get_setting(conn, watcher::MODE_SETTING).ok().flatten();
+    mode.as_deref().map(str::trim) == Some("poll")
 }
 
 #[tauri::command]
-async fn cmd_stop_watching(state: State<'_, AppState>, session_id: String) -> Result<(), String> {
+async fn cmd_stop_watching(state: State<'_, AppState>, session_id: String) -> Result<(), AppError> {
     watcher::stop_watching(Arc::clone(&state.watcher_state), &session_id);
     Ok(())
 }
 
+/// Active watchers, per-session offsets and event counters.
+#[tauri::command]
+async fn cmd_get_watcher_status(state: State<'_, AppState>) -> Result<watcher::WatcherStatus, AppError> {
+    Ok(state.watcher_state.lock().unwrap().status())
+}
+
+/// The newest non-empty session of `agent_id` that no thread owns, typically
+/// one started with `openclaw` from a terminal. Local sessions only.
+#[tauri::command]
+async fn cmd_find_cli_session(
+    state: State<'_, AppState>,
+    agent_id: String,
+) -> Result<Option<openclaw::CliSession>, AppError> {
+    let sessions = openclaw::recent_sessions(&agent_id)?;
+    state.db.call(move |conn| {
+        for (session_id, last_active) in sessions {
+            if get_thread_by_session(conn, &session_id)?.is_some() {
+                continue;
+            }
+            let session = openclaw::describe_session(&agent_id, &session_id, last_active)?;
+            if session.message_count > 0 {
+                return Ok(Some(session));
+            }
+        }
+        Ok(None)
+    })
+    .await
+}
+
+/// Bring a CLI session into a thread. `link` switches the thread over to
+/// that session so the terminal and the app continue one conversation;
+/// `merge` copies its messages onto the end of the thread's own session.
+#[tauri::command]
+async fn cmd_reconcile_cli_session(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    thread_id: String,
+    session_id: String,
+    mode: String,
+) -> Result<Thread, AppError> {
+    if runs_remote(&state, Some(&thread_id)).await? {
+        return Err(AppError::InvalidInput(
+            "CLI sessions can only be brought into local threads".to_string(),
+        ));
+    }
+    state.db.call(move |conn| {
+        let thread = get_thread(conn, &thread_id)?
+            .ok_or_else(|| AppError::NotFound(format!("Thread not found: {}", thread_id)))?;
+        let exists = openclaw::recent_sessions(&thread.agent_id)?
+            .iter()
+            .any(|(id, _)| *id == session_id);
+        if !exists {
+            return Err(AppError::NotFound(format!(
+                "No session {} for agent {}",
+                session_id, thread.agent_id
+            )));
+        }
+        if let Some(owner) = get_thread_by_session(conn, &session_id)? {
+            return Err(AppError::InvalidInput(format!(
+                "Session {} already belongs to thread \"{}\"",
+                session_id, owner.name
+            )));
+        }
+        match mode.as_str() {
+            "link" => set_thread_session(conn, &thread.id, &session_id)?,
+            "merge" => {
+                openclaw::merge_session(&thread.agent_id, &session_id, &thread.session_id)?;
+                touch_thread(conn, &thread.id)?;
+            }
+            other => return Err(AppError::InvalidInput(format!("Unknown reconcile mode: {}", other))),
+        }
+        let _ = app.emit(
+            "thread:reconciled",
+            serde_json::json!({ "threadId": thread.id, "sessionId": session_id, "mode": mode }),
+        );
+        get_thread(conn, &thread_id)?
+            .ok_or_else(|| AppError::NotFound(format!("Thread not found: {}", thread_id)))
+    })
+    .await
+}
+
 // ── Brain Dump commands ───────────────────────────────────────────────────────
 
 #[tauri::command]
-async fn cmd_list_brain_dumps(state: State<'_, AppState>) -> Result<Vec<BrainDump>, String> {
-    let conn = state.db.lock().unwrap();
-    list_brain_dumps(&conn).map_err(|e| e.to_string())
+async fn cmd_list_brain_dumps(state: State<'_, AppState>) -> Result<Vec<BrainDump>, AppError> {
+    state.db.call(move |conn| {
+        list_brain_dumps(conn).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -298,7 +913,17 @@ async fn cmd_create_brain_dump(
     state: State<'_, AppState>,
     content: String,
     project_id: Option<String>,
-) -> Result<BrainDump, String> {
+) -> Result<BrainDump, AppError> {
+    let dump = state
+        .db
+        .call(move |conn| capture_brain_dump(conn, content, project_id).map_err(AppError::from))
+        .await?;
+    webhooks::publish(webhooks::BRAIN_DUMP_CREATED, &dump);
+    Ok(dump)
+}
+
+/// Save a new open dump and add it to today's daily note.
+fn capture_brain_dump(conn: &rusqlite::Connection, content: String, project_id: Option<String>) -> anyhow::Result<BrainDump> {
     let now = Utc::now().timestamp_millis();
     let dump = BrainDump {
         id: Uuid::new_v4().to_string(),
@@ -309,9 +934,16 @@ async fn cmd_create_brain_dump(
         created_at: now,
         updated_at: now,
         followed_up_at: None,
+        follow_up_interval_hours: None,
+        max_follow_ups: None,
+        follow_up_attempts: 0,
+        follow_up_thread_id: None,
     };
-    let conn = state.db.lock().unwrap();
-    create_brain_dump(&conn, &dump).map_err(|e| e.to_string())?;
+    create_brain_dump(conn, &dump)?;
+    // The capture is saved either way; a vault hiccup shouldn't fail it
+    if let Err(e) = obsidian::append_brain_dump_to_daily(conn, &dump) {
+        eprintln!("Daily note export failed: {}", e);
+    }
     Ok(dump)
 }
 
@@ -320,9 +952,11 @@ async fn cmd_update_brain_dump_status(
     state: State<'_, AppState>,
     id: String,
     status: String,
-) -> Result<(), String> {
-    let conn = state.db.lock().unwrap();
-    update_brain_dump_status(&conn, &id, &status).map_err(|e| e.to_string())
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        update_brain_dump_status(conn, &id, &status).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -330,15 +964,39 @@ async fn cmd_set_brain_dump_proactive(
     state: State<'_, AppState>,
     id: String,
     proactive: bool,
-) -> Result<(), String> {
-    let conn = state.db.lock().unwrap();
-    set_brain_dump_proactive(&conn, &id, proactive).map_err(|e| e.to_string())
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        set_brain_dump_proactive(conn, &id, proactive).map_err(AppError::from)
+    })
+    .await
+}
+
+/// How often a proactive dump is nudged (`None`: once) and after how many
+/// unanswered nudges it stops (`None`: the default limit).
+#[tauri::command]
+async fn cmd_set_brain_dump_cadence(
+    state: State<'_, AppState>,
+    id: String,
+    interval_hours: Option<i64>,
+    max_follow_ups: Option<i32>,
+) -> Result<(), AppError> {
+    if interval_hours.is_some_and(|h| h < 1) || max_follow_ups.is_some_and(|m| m < 1) {
+        return Err(AppError::InvalidInput(
+            "Interval and follow-up limit must be at least 1".to_string(),
+        ));
+    }
+    state.db.call(move |conn| {
+        set_brain_dump_cadence(conn, &id, interval_hours, max_follow_ups).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn cmd_delete_brain_dump(state: State<'_, AppState>, id: String) -> Result<(), String> {
-    let conn = state.db.lock().unwrap();
-    delete_brain_dump(&conn, &id).map_err(|e| e.to_string())
+async fn cmd_delete_brain_dump(state: State<'_, AppState>, id: String) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        relations::delete(conn, relations::BRAIN_DUMPS, &id).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -348,22 +1006,31 @@ async fn cmd_convert_dump_to_thread(
     project_id: Option<String>,
     name: String,
     agent_id: Option<String>,
-) -> Result<Thread, String> {
+) -> Result<Thread, AppError> {
     let now = Utc::now().timestamp_millis();
-    let thread = Thread {
-        id: Uuid::new_v4().to_string(),
-        project_id,
-        name,
-        session_id: Uuid::new_v4().to_string(),
-        agent_id: agent_id.unwrap_or_else(|| "main".to_string()),
-        created_at: now,
-        updated_at: now,
-        last_message_at: None,
-    };
-    let conn = state.db.lock().unwrap();
-    create_thread(&conn, &thread).map_err(|e| e.to_string())?;
-    update_brain_dump_status(&conn, &dump_id, "in_progress").map_err(|e| e.to_string())?;
-    Ok(thread)
+    state.db.call(move |conn| {
+        let agent_id = match agent_id {
+            Some(agent_id) => agent_id,
+            None => project_agent(conn, project_id.as_deref())?,
+        };
+        let thread = Thread {
+            id: Uuid::new_v4().to_string(),
+            project_id,
+            name,
+            session_id: Uuid::new_v4().to_string(),
+            agent_id,
+            created_at: now,
+            updated_at: now,
+            last_message_at: None,
+            execution_target: None,
+            model: None,
+            muted: false,
+        };
+        create_thread(conn, &thread)?;
+        update_brain_dump_status(conn, &dump_id, "in_progress")?;
+        Ok(thread)
+    })
+    .await
 }
 
 // ── Kanban commands ───────────────────────────────────────────────────────────
@@ -372,9 +1039,12 @@ async fn cmd_convert_dump_to_thread(
 async fn cmd_list_kanban_items(
     state: State<'_, AppState>,
     project_id: Option<String>,
-) -> Result<Vec<db::KanbanItem>, String> {
-    let conn = state.db.lock().unwrap();
-    kanban::list_kanban_items(&conn, project_id.as_deref()).map_err(|e| e.to_string())
+    sort: Option<String>,
+) -> Result<Vec<db::KanbanItem>, AppError> {
+    state.db.call(move |conn| {
+        kanban::list_kanban_items(conn, project_id.as_deref(), sort.as_deref()).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -384,15 +1054,18 @@ async fn cmd_create_kanban_item(
     project_id: Option<String>,
     description: Option<String>,
     column: Option<String>,
-) -> Result<db::KanbanItem, String> {
-    let conn = state.db.lock().unwrap();
-    kanban::create_kanban_item(&conn, title, project_id, description, column)
-        .map_err(|e| e.to_string())
+) -> Result<db::KanbanItem, AppError> {
+    state.db.call(move |conn| {
+        kanban::create_kanban_item(conn, title, project_id, description, column)
+            .map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
 async fn cmd_update_kanban_item(
     state: State<'_, AppState>,
+    app: AppHandle,
     id: String,
     title: Option<String>,
     description: Option<String>,
@@ -400,149 +1073,1490 @@ async fn cmd_update_kanban_item(
     position: Option<i32>,
     status: Option<String>,
     project_id: Option<String>,
-) -> Result<(), String> {
-    let conn = state.db.lock().unwrap();
-    kanban::update_kanban_item(&conn, id, title, description, column, position, status, project_id)
-        .map_err(|e| e.to_string())
-}
-
-#[tauri::command]
-async fn cmd_delete_kanban_item(state: State<'_, AppState>, id: String) -> Result<(), String> {
-    let conn = state.db.lock().unwrap();
-    kanban::delete_kanban_item(&conn, id).map_err(|e| e.to_string())
+) -> Result<(), AppError> {
+    let (moved, completion) = state.db.call(move |conn| {
+        // complete_card makes the move into done, and only when the card
+        // wasn't already there (reordering within done changes nothing else)
+        let completing = column.as_deref() == Some("done");
+        let column = column.filter(|c| c != "done");
+        let moved = kanban::update_kanban_item(conn, id.clone(), title, description, column, position, status, project_id)?;
+        let completion = if completing { kanban::complete_card(conn, &id)? } else { None };
+        Ok::<_, AppError>((moved, completion))
+    })
+    .await?;
+    if let Some(moved) = moved {
+        webhooks::publish(webhooks::KANBAN_MOVED, &moved);
+    }
+    if let Some(completion) = completion {
+        completion.announce(&app);
+    }
+    Ok(())
 }
 
 #[tauri::command]
-async fn cmd_promote_brain_dump(
+async fn cmd_add_kanban_dependency(
     state: State<'_, AppState>,
-    dump_id: String,
-    title: String,
-    project_id: Option<String>,
-    column: Option<String>,
-) -> Result<db::KanbanItem, String> {
-    let conn = state.db.lock().unwrap();
-    kanban::promote_brain_dump(&conn, dump_id, title, project_id, column)
-        .map_err(|e| e.to_string())
+    item_id: String,
+    blocked_by_id: String,
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        kanban::add_dependency(conn, &item_id, &blocked_by_id).map_err(AppError::from)
+    })
+    .await
 }
 
-// ── SSH commands ──────────────────────────────────────────────────────────────
-
 #[tauri::command]
-async fn cmd_configure_ssh(
+async fn cmd_remove_kanban_dependency(
     state: State<'_, AppState>,
-    config: SshConfig,
-) -> Result<(), String> {
-    let mut ssh = state.ssh_session.lock().await;
-    ssh.config = config;
-    Ok(())
+    item_id: String,
+    blocked_by_id: String,
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        kanban::remove_dependency(conn, &item_id, &blocked_by_id).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn cmd_get_ssh_config(state: State<'_, AppState>) -> Result<SshConfig, String> {
-    let ssh = state.ssh_session.lock().await;
-    Ok(ssh.config.clone())
+async fn cmd_delete_kanban_item(state: State<'_, AppState>, id: String) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        kanban::delete_kanban_item(conn, id).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn cmd_test_ssh(state: State<'_, AppState>) -> Result<String, String> {
-    let mut ssh = state.ssh_session.lock().await;
-    ssh.test_connection().await.map_err(|e| e.to_string())
+async fn cmd_set_kanban_recurrence(
+    state: State<'_, AppState>,
+    id: String,
+    recurrence: Option<String>,
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        kanban::set_recurrence(conn, id, recurrence).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn cmd_ssh_status(state: State<'_, AppState>) -> Result<String, String> {
-    let ssh = state.ssh_session.lock().await;
-    let status = match &ssh.status {
-        ConnectionStatus::Disconnected => "disconnected",
-        ConnectionStatus::Connecting => "connecting",
-        ConnectionStatus::Connected => "connected",
-        ConnectionStatus::Error(_) => "error",
-    };
-    Ok(status.to_string())
+async fn cmd_get_kanban_metrics(
+    state: State<'_, AppState>,
+    project_id: Option<String>,
+    range: Option<String>,
+) -> Result<kanban::KanbanMetrics, AppError> {
+    state.db.call(move |conn| {
+        kanban::compute_metrics(conn, project_id.as_deref(), range.as_deref())
+            .map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn cmd_set_remote_mode(
+async fn cmd_import_kanban_from_markdown(
     state: State<'_, AppState>,
-    enabled: bool,
-) -> Result<(), String> {
-    let mut mode = state.remote_mode.lock().unwrap();
-    *mode = enabled;
-    Ok(())
+    project_id: Option<String>,
+    markdown: Option<String>,
+    note_path: Option<String>,
+) -> Result<Vec<db::KanbanItem>, AppError> {
+    state.db.call(move |conn| {
+        let (markdown, source_id) = match (markdown, note_path) {
+            (Some(md), _) => (md, None),
+            (None, Some(note)) => {
+                // Relative note paths resolve against the configured Obsidian vault
+                let mut path = std::path::PathBuf::from(&note);
+                if path.is_relative() {
+                    let vault = db::get_setting(conn, "obsidian_vault_path")?
+                        .ok_or_else(|| AppError::InvalidInput("No vault path configured".to_string()))?;
+                    path = std::path::PathBuf::from(vault).join(path);
+                }
+                let content = std::fs::read_to_string(&path)
+                    .map_err(|e| AppError::Io(format!("Failed to read {}: {}", path.display(), e)))?;
+                (content, Some(note))
+            }
+            (None, None) => return Err(AppError::InvalidInput("Provide markdown or a note path".to_string())),
+        };
+        kanban::import_markdown_tasks(conn, &markdown, project_id, source_id)
+            .map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn cmd_get_remote_mode(state: State<'_, AppState>) -> Result<bool, String> {
-    Ok(*state.remote_mode.lock().unwrap())
-}
-
-// ── Settings & Obsidian commands ─────────────────────────────────────────────
-
-#[derive(Serialize)]
-struct SyncResult {
-    created: u32,
-    updated: u32,
-    skipped: u32,
-    errors: Vec<String>,
+async fn cmd_set_kanban_priority(
+    state: State<'_, AppState>,
+    id: String,
+    priority: i32,
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        kanban::set_priority(conn, id, priority).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn cmd_get_setting(state: State<'_, AppState>, key: String) -> Result<Option<String>, String> {
-    let conn = state.db.lock().unwrap();
-    db::get_setting(&conn, &key).map_err(|e| e.to_string())
+async fn cmd_set_kanban_due_date(
+    state: State<'_, AppState>,
+    id: String,
+    due_at: Option<i64>,
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        db::set_kanban_due_at(conn, &id, due_at).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn cmd_set_setting(state: State<'_, AppState>, key: String, value: String) -> Result<(), String> {
-    let conn = state.db.lock().unwrap();
-    db::set_setting(&conn, &key, &value).map_err(|e| e.to_string())
+async fn cmd_export_kanban(
+    state: State<'_, AppState>,
+    project_id: Option<String>,
+    format: String,
+) -> Result<String, AppError> {
+    state.db.call(move |conn| {
+        kanban::export_board(conn, project_id.as_deref(), &format).map_err(AppError::from)
+    })
+    .await
 }
 
 #[tauri::command]
-async fn cmd_sync_obsidian_vault(state: State<'_, AppState>) -> Result<SyncResult, String> {
-    let vault_path = {
-        let conn = state.db.lock().unwrap();
-        db::get_setting(&conn, "obsidian_vault_path").map_err(|e| e.to_string())?
-    };
+async fn cmd_dispatch_kanban_item(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    id: String,
+    agent_id: Option<String>,
+) -> Result<Thread, AppError> {
+    let item_id = id.clone();
+    let (prompt, thread, moved) = state
+        .db
+        .call(move |conn| {
+            let agent_id = agent_id.unwrap_or_else(|| DEFAULT_AGENT_ID.to_string());
+            let (item, thread, moved) = kanban::dispatch_to_agent(conn, &item_id, &agent_id)?;
+            let project = match item.project_id.as_deref() {
+                Some(pid) => get_project(conn, pid).ok().flatten(),
+                None => None,
+            };
+            Ok::<_, AppError>((kanban::dispatch_prompt(&item, project.as_ref()), thread, moved))
+        })
+        .await?;
+    if let Some(moved) = moved {
+        webhooks::publish(webhooks::KANBAN_MOVED, &moved);
+    }
+    let _ = app.emit("kanban:refresh", ());
+
+    // Run the agent in the background, where the thread runs like any
+    // message sent in it; progress lands in the linked thread
+    let t = thread.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = app.emit(
+            "chat:message",
+            watcher::MessageEvent {
+                session_id: t.session_id.clone(),
+                message: user_message(&prompt),
+            },
+        );
+
+        let state = app.state::<AppState>();
+        let sent = match runs_remote(&state, Some(&t.id)).await {
+            Ok(remote) => {
+                deliver_message(&app, &state, remote, &t.id, &t.agent_id, &t.session_id, prompt, t.model.clone()).await
+            }
+            Err(e) => Err(e),
+        };
+        match sent {
+            Ok(()) => {
+                let tid = t.id.clone();
+                let _ = state.db.call(move |conn| touch_thread(conn, &tid)).await;
+            }
+            Err(e) => {
+                let _ = app.emit(
+                    "kanban:dispatch_failed",
+                    serde_json::json!({
+                        "itemId": id,
+                        "threadId": t.id,
+                        "error": e.to_string(),
+                        "timedOut": matches!(e, AppError::AgentTimeout { .. }),
+                    }),
+                );
+                e.emit(&app, "kanban_dispatch");
+            }
+        }
+        let _ = app.emit("kanban:refresh", ());
+    });
+
+    Ok(thread)
+}
+
+#[tauri::command]
+async fn cmd_promote_brain_dump(
+    state: State<'_, AppState>,
+    dump_id: String,
+    title: String,
+    project_id: Option<String>,
+    column: Option<String>,
+) -> Result<db::KanbanItem, AppError> {
+    state.db.call(move |conn| {
+        kanban::promote_brain_dump(conn, dump_id, title, project_id, column)
+            .map_err(AppError::from)
+    })
+    .await
+}
+
+// ── SSH commands ──────────────────────────────────────────────────────────────
+
+#[tauri::command]
+async fn cmd_configure_ssh(
+    state: State<'_, AppState>,
+    config: SshConfig,
+) -> Result<(), AppError> {
+    config.validate()?;
+    let mut ssh = state.ssh_session.lock().await;
+    ssh.config = config;
+    Ok(())
+}
+
+#[tauri::command]
+async fn cmd_get_ssh_config(state: State<'_, AppState>) -> Result<SshConfig, AppError> {
+    let ssh = state.ssh_session.lock().await;
+    Ok(ssh.config.clone())
+}
+
+#[tauri::command]
+async fn cmd_test_ssh(state: State<'_, AppState>) -> Result<String, AppError> {
+    let mut ssh = state.ssh_session.lock().await;
+    ssh.test_connection().await.map_err(AppError::from)
+}
+
+/// Tunnel a local port to `remote_host:remote_port` (default: the remote's
+/// loopback) so the app can reach a remote HTTP API directly.
+#[tauri::command]
+async fn cmd_ssh_open_forward(
+    state: State<'_, AppState>,
+    remote_port: u16,
+    remote_host: Option<String>,
+    local_port: Option<u16>,
+) -> Result<ssh::PortForward, AppError> {
+    ssh::ensure_connected(&state.ssh_session).await?;
+    let mut ssh = state.ssh_session.lock().await;
+    let remote_host = remote_host.unwrap_or_else(|| "127.0.0.1".to_string());
+    ssh.open_forward(&remote_host, remote_port, local_port)
+        .await
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn cmd_ssh_close_forward(state: State<'_, AppState>, local_port: u16) -> Result<(), AppError> {
+    let mut ssh = state.ssh_session.lock().await;
+    ssh.close_forward(local_port).await.map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn cmd_ssh_list_forwards(state: State<'_, AppState>) -> Result<Vec<ssh::PortForward>, AppError> {
+    let ssh = state.ssh_session.lock().await;
+    Ok(ssh.forwards().to_vec())
+}
+
+/// Recent SSH connection events and command latencies, newest first.
+#[tauri::command]
+async fn cmd_get_ssh_log(
+    state: State<'_, AppState>,
+    since: Option<i64>,
+    limit: Option<u32>,
+) -> Result<Vec<SshEvent>, AppError> {
+    state.db.call(move |conn| {
+        list_ssh_events(conn, since, limit.unwrap_or(200)).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Logged openclaw runs, newest first. Empty unless the `agent_log` setting
+/// is "redacted" or "full".
+#[tauri::command]
+async fn cmd_get_agent_log(
+    state: State<'_, AppState>,
+    agent_id: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<AgentInvocation>, AppError> {
+    state.db.call(move |conn| {
+        list_agent_invocations(conn, agent_id.as_deref(), limit.unwrap_or(200)).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_clear_agent_log(state: State<'_, AppState>) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        clear_agent_invocations(conn).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Installed agents, locally or on the remote host. `remote` defaults to
+/// the global mode.
+#[tauri::command]
+async fn cmd_list_agents(
+    state: State<'_, AppState>,
+    remote: Option<bool>,
+) -> Result<Vec<AgentInfo>, AppError> {
+    let remote = remote.unwrap_or(*state.remote_mode.lock().unwrap());
+    if remote {
+        let handle = remote_handle(&state).await?;
+        handle.list_agents().await.map_err(AppError::from)
+    } else {
+        openclaw::list_agents().map_err(AppError::from)
+    }
+}
+
+/// Models openclaw can run, locally or on the remote host. `remote`
+/// defaults to the global mode.
+#[tauri::command]
+async fn cmd_list_models(
+    state: State<'_, AppState>,
+    remote: Option<bool>,
+) -> Result<Vec<openclaw::ModelInfo>, AppError> {
+    let remote = remote.unwrap_or(*state.remote_mode.lock().unwrap());
+    if remote {
+        let handle = remote_handle(&state).await?;
+        let stdout = handle
+            .exec_openclaw(&["models", "list", "--json"])
+            .await?;
+        openclaw::parse_models(&stdout).map_err(AppError::from)
+    } else {
+        openclaw::list_models().await.map_err(AppError::from)
+    }
+}
+
+#[tauri::command]
+async fn cmd_get_agent(
+    state: State<'_, AppState>,
+    agent_id: String,
+    remote: Option<bool>,
+) -> Result<AgentInfo, AppError> {
+    cmd_list_agents(state, remote)
+        .await?
+        .into_iter()
+        .find(|a| a.id == agent_id)
+        .ok_or_else(|| AppError::NotFound(format!("Agent not found: {}", agent_id)))
+}
+
+/// Scaffold a new local agent with an optional name, model and instructions.
+#[tauri::command]
+async fn cmd_create_agent(agent_id: String, config: Option<AgentConfig>) -> Result<AgentInfo, AppError> {
+    openclaw::create_agent(&agent_id, &config.unwrap_or_default()).map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn cmd_update_agent_config(agent_id: String, config: AgentConfig) -> Result<(), AppError> {
+    openclaw::update_agent_config(&agent_id, &config).map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn cmd_get_agent_instructions(agent_id: String) -> Result<Option<String>, AppError> {
+    openclaw::agent_instructions(&agent_id).map_err(AppError::from)
+}
+
+/// Structured diagnostics for remote mode (see `SshSession::health`).
+#[tauri::command]
+async fn cmd_remote_health(state: State<'_, AppState>) -> Result<ssh::RemoteHealth, AppError> {
+    let ssh = state.ssh_session.lock().await;
+    Ok(ssh.health().await)
+}
+
+/// Copy a local file to the remote box over SFTP.
+#[tauri::command]
+async fn cmd_ssh_upload(
+    state: State<'_, AppState>,
+    local_path: String,
+    remote_path: String,
+) -> Result<(), AppError> {
+    let handle = remote_handle(&state).await?;
+    handle
+        .upload_file(std::path::Path::new(&local_path), &remote_path)
+        .await
+        .map_err(AppError::from)
+}
+
+/// Copy a remote file to a local path over SFTP.
+#[tauri::command]
+async fn cmd_ssh_download(
+    state: State<'_, AppState>,
+    remote_path: String,
+    local_path: String,
+) -> Result<(), AppError> {
+    let handle = remote_handle(&state).await?;
+    let data = handle
+        .read_file(&remote_path)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Remote file not found: {}", remote_path)))?;
+    tokio::fs::write(&local_path, data).await.map_err(AppError::from)
+}
+
+/// List a directory in the remote file browser (root when `path` is empty).
+#[tauri::command]
+async fn cmd_remote_list_files(
+    state: State<'_, AppState>,
+    path: Option<String>,
+) -> Result<Vec<ssh::RemoteEntry>, AppError> {
+    let handle = remote_handle(&state).await?;
+    handle
+        .list_files(path.as_deref().unwrap_or(""))
+        .await
+        .map_err(AppError::from)
+}
+
+/// Pull a file from the remote files directory. Saves to `local_path`, or
+/// the Downloads folder by default; returns where it was written.
+#[tauri::command]
+async fn cmd_remote_download_file(
+    state: State<'_, AppState>,
+    path: String,
+    local_path: Option<String>,
+) -> Result<String, AppError> {
+    let handle = remote_handle(&state).await?;
+    let remote_path = handle.files_path(&path)?;
+    let data = handle
+        .read_file(&remote_path)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Remote file not found: {}", path)))?;
+    let local_path = match local_path {
+        Some(p) => std::path::PathBuf::from(p),
+        None => {
+            let name = std::path::Path::new(&path)
+                .file_name()
+                .ok_or_else(|| AppError::InvalidInput(format!("Not a file: {}", path)))?;
+            dirs::download_dir()
+                .or_else(dirs::home_dir)
+                .unwrap_or_default()
+                .join(name)
+        }
+    };
+    tokio::fs::write(&local_path, data).await?;
+    Ok(local_path.to_string_lossy().into_owned())
+}
+
+/// Push a local file into a directory under the remote files root. Returns
+/// the new file's browser path.
+#[tauri::command]
+async fn cmd_remote_upload_file(
+    state: State<'_, AppState>,
+    local_path: String,
+    remote_dir: Option<String>,
+) -> Result<String, AppError> {
+    let name = std::path::Path::new(&local_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| AppError::InvalidInput(format!("Not a file: {}", local_path)))?;
+    let rel = match remote_dir.as_deref().map(|d| d.trim_matches('/')) {
+        Some(dir) if !dir.is_empty() => format!("{}/{}", dir, name),
+        _ => name,
+    };
+    let handle = remote_handle(&state).await?;
+    let remote_path = handle.files_path(&rel)?;
+    handle.upload_file(std::path::Path::new(&local_path), &remote_path).await?;
+    Ok(rel)
+}
+
+/// Store the password for the configured host in the OS keychain.
+#[tauri::command]
+async fn cmd_set_ssh_password(state: State<'_, AppState>, password: String) -> Result<(), AppError> {
+    let account = state.ssh_session.lock().await.config.keychain_account();
+    ssh::store_password(&account, &password).await.map_err(AppError::from)
+}
+
+/// Trust the configured host's key after the user checked its fingerprint.
+#[tauri::command]
+async fn cmd_ssh_trust_host_key(state: State<'_, AppState>, fingerprint: String) -> Result<(), AppError> {
+    let config = state.ssh_session.lock().await.config.clone();
+    ssh::trust_host_key(&config.host, config.port, &fingerprint)
+        .await
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn cmd_ssh_forget_host_key(state: State<'_, AppState>) -> Result<(), AppError> {
+    let config = state.ssh_session.lock().await.config.clone();
+    ssh::forget_host_key(&config.host, config.port)
+        .await
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn cmd_ssh_connect(state: State<'_, AppState>) -> Result<(), AppError> {
+    state.ssh_manager.connect().await.map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn cmd_ssh_disconnect(state: State<'_, AppState>) -> Result<(), AppError> {
+    state.ssh_manager.disconnect().await.map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn cmd_ssh_status(state: State<'_, AppState>) -> Result<String, AppError> {
+    let ssh = state.ssh_session.lock().await;
+    Ok(ssh.status.label().to_string())
+}
+
+#[tauri::command]
+async fn cmd_set_remote_mode(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), AppError> {
+    set_remote_mode(&app, &state, enabled).await
+}
+
+/// Switch remote mode and tell the UI and tray with "remote_mode:changed".
+async fn set_remote_mode(app: &AppHandle, state: &AppState, enabled: bool) -> Result<(), AppError> {
+    let was_remote = {
+        let mut mode = state.remote_mode.lock().unwrap();
+        std::mem::replace(&mut *mode, enabled)
+    };
+    if was_remote && !enabled {
+        // Best-effort final pull so remote history stays readable offline
+        let handle = {
+            let ssh = state.ssh_session.lock().await;
+            if ssh.is_alive().await {
+                ssh.handle().ok()
+            } else {
+                None
+            }
+        };
+        if let Some(handle) = handle {
+            if let Err(e) = mirror::sync(app, &handle, 0, true).await {
+                eprintln!("[mirror] Final pull failed: {}", e);
+            }
+        }
+    }
+    if was_remote != enabled {
+        let _ = app.emit("remote_mode:changed", serde_json::json!({ "enabled": enabled }));
+        tray::refresh(app);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn cmd_get_remote_mode(state: State<'_, AppState>) -> Result<bool, AppError> {
+    Ok(*state.remote_mode.lock().unwrap())
+}
+
+// ── Settings & Obsidian commands ─────────────────────────────────────────────
+
+#[tauri::command]
+async fn cmd_get_setting(state: State<'_, AppState>, key: String) -> Result<Option<String>, AppError> {
+    state.db.call(move |conn| {
+        db::get_setting(conn, &key).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Save a setting, checked against its kind. Strings work for every kind;
+/// null resets it to the default. Loops that depend on it hear about it
+/// through `settings::subscribe`.
+#[tauri::command]
+async fn cmd_set_setting(state: State<'_, AppState>, key: String, value: serde_json::Value) -> Result<(), AppError> {
+    state.db.call(move |conn| settings::set(conn, &key, &value)).await?;
+    Ok(())
+}
+
+/// Every known setting with its kind, default and current value.
+#[tauri::command]
+async fn cmd_list_settings(state: State<'_, AppState>) -> Result<Vec<settings::SettingValue>, AppError> {
+    state.db.call(move |conn| {
+        settings::list(conn).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Where openclaw was found (honouring the binary/PATH settings) and its version.
+#[tauri::command]
+async fn cmd_detect_openclaw() -> Result<openclaw::OpenClawDetection, AppError> {
+    Ok(openclaw::detect_openclaw().await)
+}
+
+/// Per-agent flags, env and working directory for local runs.
+#[tauri::command]
+async fn cmd_list_agent_overrides(state: State<'_, AppState>) -> Result<Vec<AgentOverride>, AppError> {
+    state.db.call(move |conn| {
+        list_agent_overrides(conn).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_set_agent_override(
+    state: State<'_, AppState>,
+    mut agent_override: AgentOverride,
+) -> Result<(), AppError> {
+    if agent_override.agent_id.trim().is_empty() {
+        return Err(AppError::InvalidInput("Agent id is required".to_string()));
+    }
+    if let Some(key) = agent_override.env.keys().find(|k| k.is_empty() || k.contains('=')) {
+        return Err(AppError::InvalidInput(format!("Invalid environment variable name: {:?}", key)));
+    }
+    agent_override.working_dir = agent_override.working_dir.filter(|d| !d.trim().is_empty());
+    if let Some(dir) = &agent_override.working_dir {
+        if !openclaw::expand_home(dir).is_dir() {
+            return Err(AppError::InvalidInput(format!("Not a directory: {}", dir)));
+        }
+    }
+    state.db.call(move |conn| {
+        set_agent_override(conn, &agent_override).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_delete_agent_override(state: State<'_, AppState>, agent_id: String) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        delete_agent_override(conn, &agent_id).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Diagnostics for the settings panel. `ping` (default true) also runs a
+/// short real turn with the agent and times it.
+#[tauri::command]
+async fn cmd_check_agent_health(
+    agent_id: Option<String>,
+    ping: Option<bool>,
+) -> Result<openclaw::HealthReport, AppError> {
+    let agent_id = agent_id.unwrap_or_else(|| DEFAULT_AGENT_ID.to_string());
+    Ok(openclaw::check_health(&agent_id, ping.unwrap_or(true)).await)
+}
+
+#[tauri::command]
+async fn cmd_get_obsidian_layout(state: State<'_, AppState>) -> Result<obsidian::VaultLayout, AppError> {
+    state.db.call(move |conn| {
+        Ok(obsidian::VaultLayout::load(conn))
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_set_obsidian_layout(state: State<'_, AppState>, layout: obsidian::VaultLayout) -> Result<(), AppError> {
+    state.db.call(move |conn| layout.save(conn)).await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn cmd_get_proactive_settings(state: State<'_, AppState>) -> Result<proactive::ProactiveSchedule, AppError> {
+    state.db.call(move |conn| {
+        Ok(proactive::ProactiveSchedule::load(conn))
+    })
+    .await
+}
+
+/// Save the follow-up interval, active days and quiet hours; the running
+/// loop picks them up right away.
+#[tauri::command]
+async fn cmd_update_proactive_settings(
+    state: State<'_, AppState>,
+    schedule: proactive::ProactiveSchedule,
+) -> Result<(), AppError> {
+    state.db.call(move |conn| schedule.save(conn)).await?;
+    proactive::reload_schedule();
+    Ok(())
+}
+
+/// Follow up on proactive brain dumps right away, outside the schedule.
+#[tauri::command]
+async fn cmd_run_proactive_now(app: AppHandle) -> Result<proactive::ProactiveStatus, AppError> {
+    proactive::run_now(&app).await.map_err(AppError::from)
+}
+
+/// Suspend or resume the proactive loop; kept across restarts.
+#[tauri::command]
+async fn cmd_set_proactive_paused(paused: bool) -> Result<proactive::ProactiveStatus, AppError> {
+    proactive::set_paused(paused).await?;
+    Ok(proactive::status().await)
+}
+
+#[tauri::command]
+async fn cmd_get_proactive_status() -> Result<proactive::ProactiveStatus, AppError> {
+    Ok(proactive::status().await)
+}
+
+#[tauri::command]
+async fn cmd_get_dnd_status(state: State<'_, AppState>) -> Result<dnd::DndStatus, AppError> {
+    state.db.call(move |conn| {
+        dnd::status(conn).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Switch do-not-disturb on (until `until`, epoch ms, if given) or off.
+/// `follow_focus` also holds events while a macOS Focus is on.
+#[tauri::command]
+async fn cmd_set_dnd(
+    state: State<'_, AppState>,
+    enabled: bool,
+    until: Option<i64>,
+    follow_focus: bool,
+) -> Result<dnd::DndStatus, AppError> {
+    state.db.call(move |conn| {
+        dnd::set(conn, enabled, until, follow_focus)?;
+        dnd::status(conn).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Carry out a notification button from the UI, for platforms where the OS
+/// notification can't show it.
+#[tauri::command]
+async fn cmd_notification_action(
+    app: AppHandle,
+    action: notifications::Action,
+    notice: notifications::Notice,
+) -> Result<(), AppError> {
+    Ok(notifications::perform(&app, action, notice).await?)
+}
+
+/// Reply rates for proactive follow-ups over the last `days` (default 30).
+#[tauri::command]
+async fn cmd_get_proactive_effectiveness(
+    state: State<'_, AppState>,
+    days: Option<u32>,
+) -> Result<ProactiveEffectiveness, AppError> {
+    let since = chrono::Utc::now().timestamp_millis() - days.unwrap_or(30) as i64 * 24 * 60 * 60 * 1000;
+    state.db.call(move |conn| {
+        proactive_effectiveness(conn, since).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Every follow-up sent for a brain dump and whether it got a reply.
+#[tauri::command]
+async fn cmd_list_follow_up_outcomes(
+    state: State<'_, AppState>,
+    brain_dump_id: String,
+) -> Result<Vec<FollowUpOutcome>, AppError> {
+    state.db.call(move |conn| {
+        list_follow_up_outcomes(conn, &brain_dump_id).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Recent follow-up passes with per-dump failures, newest first.
+#[tauri::command]
+async fn cmd_list_proactive_runs(state: State<'_, AppState>, limit: Option<u32>) -> Result<Vec<ProactiveRun>, AppError> {
+    state.db.call(move |conn| {
+        list_proactive_runs(conn, limit.unwrap_or(20)).map_err(AppError::from)
+    })
+    .await
+}
+
+/// (Re)start the live watcher on the vault's active projects directory.
+async fn start_vault_watcher(app: &AppHandle, state: &AppState, vault_path: &str) {
+    let layout = match state.db.call(|conn| Ok::<_, anyhow::Error>(obsidian::VaultLayout::load(conn))).await {
+        Ok(layout) => layout,
+        Err(e) => {
+            eprintln!("Failed to watch Obsidian vault: {}", e);
+            return;
+        }
+    };
+    let active_path = layout.active_dir(vault_path);
+    if !active_path.is_dir() {
+        watcher::stop_vault_watch(Arc::clone(&state.watcher_state));
+        return;
+    }
+    if let Err(e) = watcher::watch_vault(
+        app.clone(),
+        Arc::clone(&state.watcher_state),
+        state.db.clone(),
+        active_path,
+        layout,
+    ) {
+        eprintln!("Failed to watch Obsidian vault: {}", e);
+    }
+}
+
+/// (Re)start session discovery as `session_discovery` says.
+async fn start_session_discovery(app: &AppHandle, state: &AppState) {
+    let settings = state
+        .db
+        .call(|conn| {
+            Ok::<_, anyhow::Error>((
+                db::get_setting(conn, watcher::DISCOVERY_SETTING).ok().flatten(),
+                watcher_polls(conn),
+            ))
+        })
+        .await;
+    let (mode, poll) = match settings {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Failed to watch for new sessions: {}", e);
+            return;
+        }
+    };
+    let create_threads = match mode.as_deref().map(str::trim) {
+        Some("off") => {
+            watcher::stop_session_discovery(Arc::clone(&state.watcher_state));
+            return;
+        }
+        Some("notify") => false,
+        _ => true,
+    };
+    if let Err(e) = watcher::watch_new_sessions(
+        app.clone(),
+        Arc::clone(&state.watcher_state),
+        state.db.clone(),
+        create_threads,
+        poll,
+    ) {
+        eprintln!("Failed to watch for new sessions: {}", e);
+    }
+}
+
+/// Pass every saved setting on as "settings:changed" and restart what
+/// depends on it, so changes apply without restarting the app.
+async fn follow_setting_changes(app: AppHandle) {
+    let mut changes = settings::subscribe();
+    loop {
+        let change = match changes.recv().await {
+            Ok(change) => change,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+        };
+        let _ = app.emit("settings:changed", &change);
+        // Job triggers read their settings when the scheduler wakes
+        scheduler::reload();
+        let state = app.state::<AppState>();
+        match change.key.as_str() {
+            settings::VAULT_PATH_SETTING | obsidian::LAYOUT_SETTING => {
+                let vault_path = state
+                    .db
+                    .call(|conn| settings::get_text(conn, settings::VAULT_PATH_SETTING))
+                    .await
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default();
+                // An empty path stops the watcher
+                start_vault_watcher(&app, &state, &vault_path).await;
+            }
+            watcher::DISCOVERY_SETTING | watcher::MODE_SETTING => start_session_discovery(&app, &state).await,
+            // Export right away rather than at the next scheduled run
+            calendar::EXPORT_SETTING => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let _ = scheduler::run_job(&app, scheduler::CALENDAR_EXPORT).await;
+                });
+            }
+            mcp::ENABLED_SETTING | mcp::PORT_SETTING | mcp::READ_ONLY_SETTING | mcp::TOKEN_SETTING => {
+                mcp::restart(&app).await;
+                let _ = app.emit("mcp:status", mcp::status());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Let the frontend know which projects a sync archived or brought back.
+fn emit_archive_changes(app: &AppHandle, result: &obsidian::SyncResult) {
+    if !result.archived.is_empty() || !result.restored.is_empty() {
+        let _ = app.emit(
+            "obsidian:archive_changes",
+            serde_json::json!({ "archived": result.archived, "restored": result.restored }),
+        );
+    }
+}
+
+/// Full vault sync outside the file watcher, run at startup and as the
+/// hourly `obsidian_sync` job. Quietly does nothing without a vault.
+fn sync_vault_now(app: &AppHandle) -> anyhow::Result<String> {
+    let state = app.state::<AppState>();
+    let (vault_path, layout) = {
+        let conn = state.db.get()?;
+        (db::get_setting(&conn, "obsidian_vault_path")?, obsidian::VaultLayout::load(&conn))
+    };
+    let Some(vault_path) = vault_path else {
+        return Ok("Skipped: no vault configured".to_string());
+    };
+    let active_path = layout.active_dir(&vault_path);
+    if !active_path.is_dir() {
+        return Ok(format!("Skipped: {} not found", active_path.display()));
+    }
+    let projects = obsidian::parse_vault(&active_path, &layout);
+    let result = obsidian::sync_vault(&*state.db.get()?, &active_path, &projects);
+    for e in &result.errors {
+        eprintln!("Obsidian sync error: {}", e);
+    }
+    for completion in &result.completions {
+        completion.announce(app);
+    }
+    emit_archive_changes(app, &result);
+    Ok(format!("{} projects processed, {} errors", projects.len(), result.errors.len()))
+}
+
+#[tauri::command]
+async fn cmd_sync_obsidian_vault(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    preview: Option<bool>,
+) -> Result<obsidian::SyncResult, AppError> {
+    let (vault_path, layout) = state
+        .db
+        .call(|conn| {
+            let vault_path = db::get_setting(conn, "obsidian_vault_path")?;
+            Ok::<_, AppError>((vault_path, obsidian::VaultLayout::load(conn)))
+        })
+        .await?;
 
     let Some(vault_path) = vault_path else {
-        return Err("No vault path configured".to_string());
+        return Err(AppError::InvalidInput("No vault path configured".to_string()));
     };
 
-    let active_path = std::path::PathBuf::from(&vault_path)
-        .join("10 Projects")
-        .join("Active");
+    let active_path = layout.active_dir(&vault_path);
     if !active_path.is_dir() {
-        return Err(format!(
+        return Err(AppError::NotFound(format!(
             "Active projects directory not found: {}",
             active_path.display()
-        ));
+        )));
     }
 
-    let projects = obsidian::parse_vault(&active_path);
+    let projects = obsidian::parse_vault(&active_path, &layout);
 
-    let conn = state.db.lock().unwrap();
-    let mut result = SyncResult {
-        created: 0,
-        updated: 0,
-        skipped: 0,
-        errors: Vec::new(),
-    };
-    for p in &projects {
-        match db::upsert_obsidian_project(
-            &conn,
-            &p.name,
-            p.description.as_deref(),
-            &p.color,
-            &p.obsidian_source,
-        ) {
-            Ok(db::UpsertResult::Created) => result.created += 1,
-            Ok(db::UpsertResult::Updated) => result.updated += 1,
-            Ok(db::UpsertResult::Skipped) => result.skipped += 1,
-            Err(e) => result.errors.push(format!("{}: {}", p.name, e)),
+    state.db.call(move |conn| {
+        if preview.unwrap_or(false) {
+            return obsidian::preview_sync(conn, &active_path, &projects).map_err(AppError::from);
+        }
+        let result = obsidian::sync_vault(conn, &active_path, &projects);
+        for completion in &result.completions {
+            completion.announce(&app);
         }
+        emit_archive_changes(&app, &result);
+        Ok(result)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_create_obsidian_note_for_project(
+    state: State<'_, AppState>,
+    project_id: String,
+    category: Option<String>,
+) -> Result<String, AppError> {
+    state.db.call(move |conn| {
+        obsidian::create_project_note(conn, &project_id, category.as_deref())
+            .map(|path| path.to_string_lossy().to_string())
+            .map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_generate_weekly_review(app: AppHandle) -> Result<String, AppError> {
+    proactive::write_weekly_review(&app)
+        .await
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(AppError::from)
+}
+
+/// Write this week's retrospective now; also exported to the vault when enabled.
+#[tauri::command]
+async fn cmd_generate_retrospective(app: AppHandle) -> Result<Retrospective, AppError> {
+    let (retro, _) = proactive::write_retrospective(&app).await?;
+    Ok(retro)
+}
+
+#[tauri::command]
+async fn cmd_list_retrospectives(state: State<'_, AppState>, limit: Option<u32>) -> Result<Vec<Retrospective>, AppError> {
+    state.db.call(move |conn| {
+        list_retrospectives(conn, limit.unwrap_or(12)).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_list_prompt_templates(state: State<'_, AppState>) -> Result<Vec<prompts::PromptTemplate>, AppError> {
+    state.db.call(move |conn| {
+        prompts::list(conn).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_set_prompt_template(
+    state: State<'_, AppState>,
+    purpose: String,
+    template: String,
+) -> Result<(), AppError> {
+    prompts::validate(&purpose, &template).map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    state.db.call(move |conn| {
+        prompts::save(conn, &purpose, &template).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_reset_prompt_template(state: State<'_, AppState>, purpose: String) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        prompts::reset(conn, &purpose).map_err(AppError::from)
+    })
+    .await
+}
+
+// ── Scheduled jobs ───────────────────────────────────────────────────────────
+
+#[tauri::command]
+async fn cmd_list_jobs(state: State<'_, AppState>) -> Result<Vec<scheduler::JobStatus>, AppError> {
+    state.db.call(move |conn| {
+        scheduler::list(conn).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_set_job_enabled(state: State<'_, AppState>, id: String, enabled: bool) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        scheduler::set_enabled(conn, &id, enabled).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Replace a cron job's schedule; `None` or blank restores the default.
+#[tauri::command]
+async fn cmd_set_job_schedule(
+    state: State<'_, AppState>,
+    id: String,
+    schedule: Option<String>,
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        scheduler::set_schedule(conn, &id, schedule.as_deref()).map_err(|e| AppError::InvalidInput(e.to_string()))
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_run_job_now(app: AppHandle, id: String) -> Result<String, AppError> {
+    scheduler::run_job(&app, &id).await
+}
+
+#[tauri::command]
+async fn cmd_list_job_runs(
+    state: State<'_, AppState>,
+    job_id: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<JobRun>, AppError> {
+    state.db.call(move |conn| {
+        list_job_runs(conn, job_id.as_deref(), limit.unwrap_or(50)).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Look for stale threads now instead of waiting for the next pass.
+#[tauri::command]
+async fn cmd_resurface_stale_threads(app: AppHandle) -> Result<Vec<proactive::ResurfacedThread>, AppError> {
+    proactive::resurface_stale_threads(&app).await.map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn cmd_generate_briefing(app: AppHandle) -> Result<Briefing, AppError> {
+    proactive::write_briefing(&app).await.map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn cmd_list_briefings(state: State<'_, AppState>, limit: Option<u32>) -> Result<Vec<Briefing>, AppError> {
+    state.db.call(move |conn| {
+        list_briefings(conn, limit.unwrap_or(14)).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_list_sync_conflicts(
+    state: State<'_, AppState>,
+    include_resolved: Option<bool>,
+) -> Result<Vec<db::SyncConflict>, AppError> {
+    state.db.call(move |conn| {
+        db::list_sync_conflicts(conn, include_resolved.unwrap_or(false)).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Close a sync conflict; with `restore`, put the overwritten app-side value back.
+#[tauri::command]
+async fn cmd_resolve_sync_conflict(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    id: String,
+    restore: bool,
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        let conflict = db::get_sync_conflict(conn, &id)?
+            .ok_or_else(|| AppError::NotFound(format!("Sync conflict not found: {}", id)))?;
+        if restore {
+            let project = get_project(conn, &conflict.project_id)?
+                .ok_or_else(|| AppError::NotFound(format!("Project not found: {}", conflict.project_id)))?;
+            let (name, description) = match conflict.field.as_str() {
+                "name" => (conflict.local_value.clone().unwrap_or(project.name), project.description),
+                "description" => (project.name, conflict.local_value.clone()),
+                other => return Err(AppError::InvalidInput(format!("Unknown conflict field: {}", other))),
+            };
+            update_project(conn, &project.id, &name, description.as_deref(), project.color.as_deref())?;
+            write_back_to_vault(conn, &app, &project.id);
+        }
+        db::resolve_sync_conflict(conn, &id).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_export_thread_to_obsidian(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    thread_id: String,
+) -> Result<String, AppError> {
+    let (thread, project, vault_path, folder) = state
+        .db
+        .call(move |conn| {
+            let thread = get_thread(conn, &thread_id)?
+                .ok_or_else(|| AppError::NotFound(format!("Thread not found: {}", thread_id)))?;
+            let project = match thread.project_id.as_deref() {
+                Some(pid) => get_project(conn, pid)?,
+                None => None,
+            };
+            let vault_path = db::get_setting(conn, "obsidian_vault_path")?
+                .ok_or_else(|| AppError::InvalidInput("No vault path configured".to_string()))?;
+            let folder = db::get_setting(conn, "obsidian_chat_export_folder")?
+                .unwrap_or_else(|| obsidian::DEFAULT_CHAT_EXPORT_FOLDER.to_string());
+            Ok::<_, AppError>((thread, project, vault_path, folder))
+        })
+        .await?;
+
+    let messages = cmd_load_session(
+        state,
+        app,
+        thread.agent_id.clone(),
+        thread.session_id.clone(),
+        Some(thread.id.clone()),
+    )
+    .await?;
+
+    let dir = std::path::PathBuf::from(&vault_path)
+        .join(&folder)
+        .join(obsidian::sanitize_filename(
+            project.as_ref().map(|p| p.name.as_str()).unwrap_or("Inbox"),
+        ));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.md", obsidian::sanitize_filename(&thread.name)));
+    let content = obsidian::render_transcript(&thread, project.as_ref(), &messages);
+    std::fs::write(&path, content)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// ── Search ───────────────────────────────────────────────────────────────────
+
+/// Ranked matches across threads, brain dumps and kanban cards. `kinds`
+/// limits it to some of "thread", "brain_dump" and "kanban".
+#[tauri::command]
+async fn cmd_search(
+    state: State<'_, AppState>,
+    query: String,
+    kinds: Option<Vec<String>>,
+    limit: Option<u32>,
+) -> Result<Vec<db::SearchHit>, AppError> {
+    let kinds = kinds.unwrap_or_default();
+    if let Some(kind) = kinds.iter().find(|k| !db::SEARCH_KINDS.contains(&k.as_str())) {
+        return Err(AppError::InvalidInput(format!("Unknown search kind: {}", kind)));
+    }
+    state
+        .db
+        .call(move |conn| db::search(conn, &query, &kinds, limit.unwrap_or(50)).map_err(AppError::from))
+        .await
+}
+
+// ── MCP server ───────────────────────────────────────────────────────────────
+
+#[tauri::command]
+async fn cmd_get_mcp_status() -> Result<mcp::McpStatus, AppError> {
+    Ok(mcp::status())
+}
+
+/// Give the MCP server a new access token, locking out clients with the old one.
+#[tauri::command]
+async fn cmd_reset_mcp_token(state: State<'_, AppState>) -> Result<String, AppError> {
+    state.db.call(|conn| mcp::new_token(conn).map_err(AppError::from)).await
+}
+
+// ── Audit log ────────────────────────────────────────────────────────────────
+
+/// Logged creates, updates and deletes of projects, threads, brain dumps,
+/// cards and settings, newest first.
+#[tauri::command]
+async fn cmd_get_audit_log(
+    state: State<'_, AppState>,
+    filter: Option<db::AuditFilter>,
+) -> Result<Vec<db::AuditEntry>, AppError> {
+    let filter = filter.unwrap_or_default();
+    state
+        .db
+        .call(move |conn| db::list_audit_log(conn, &filter).map_err(AppError::from))
+        .await
+}
+
+/// Undo one logged change.
+#[tauri::command]
+async fn cmd_revert_audit_entry(state: State<'_, AppState>, app: AppHandle, id: i64) -> Result<(), AppError> {
+    let entry = state
+        .db
+        .call(move |conn| db::revert_audit_entry(conn, id).map_err(AppError::from))
+        .await?;
+    let _ = app.emit(
+        "audit:reverted",
+        serde_json::json!({ "entityType": entry.entity_type, "entityId": entry.entity_id }),
+    );
+    if entry.entity_type == "kanban_items" {
+        let _ = app.emit("kanban:refresh", ());
+    }
+    Ok(())
+}
+
+// ── Database ─────────────────────────────────────────────────────────────────
+
+/// Database size, row counts and the last maintenance report.
+#[tauri::command]
+async fn cmd_get_db_stats(state: State<'_, AppState>) -> Result<db::DbStats, AppError> {
+    state.db.call(|conn| db::db_stats(conn).map_err(AppError::from)).await
+}
+
+/// Counts and weekly trends for the insights screen; `weeks` defaults to 12.
+#[tauri::command]
+async fn cmd_get_workspace_stats(
+    state: State<'_, AppState>,
+    weeks: Option<u32>,
+) -> Result<insights::WorkspaceStats, AppError> {
+    Ok(insights::workspace_stats(&state.db, weeks.unwrap_or(12)).await?)
+}
+
+// ── Attachments ──────────────────────────────────────────────────────────────
+
+/// Attach the file at `path` to a thread, brain dump or kanban card
+/// (`owner_type` is the table name). Its content is stored once however
+/// often it's attached.
+#[tauri::command]
+async fn cmd_add_attachment(
+    state: State<'_, AppState>,
+    owner_type: String,
+    owner_id: String,
+    path: String,
+) -> Result<blobs::Attachment, AppError> {
+    state.db.call(move |conn| {
+        let source = openclaw::expand_home(&path);
+        let name = source
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| AppError::InvalidInput(format!("Not a file: {}", path)))?;
+        let hash = blobs::store_file(conn, &source)?;
+        blobs::add_ref(conn, &hash, &owner_type, &owner_id, &name)?;
+        blobs::list(conn, &owner_type, &owner_id)?
+            .into_iter()
+            .find(|a| a.hash == hash)
+            .ok_or_else(|| AppError::Internal("Attachment vanished after saving".to_string()))
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_list_attachments(
+    state: State<'_, AppState>,
+    owner_type: String,
+    owner_id: String,
+) -> Result<Vec<blobs::Attachment>, AppError> {
+    state.db.call(move |conn| {
+        blobs::list(conn, &owner_type, &owner_id).map_err(AppError::from)
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cmd_remove_attachment(
+    state: State<'_, AppState>,
+    owner_type: String,
+    owner_id: String,
+    hash: String,
+) -> Result<(), AppError> {
+    state.db.call(move |conn| {
+        blobs::remove_ref(conn, &hash, &owner_type, &owner_id).map_err(AppError::from)
+    })
+    .await
+}
+
+/// Delete attachment files nothing refers to any more. Also runs with the
+/// weekly maintenance job.
+#[tauri::command]
+async fn cmd_gc_blobs(state: State<'_, AppState>) -> Result<blobs::GcReport, AppError> {
+    state.db.call(|conn| blobs::gc(conn).map_err(AppError::from)).await
+}
+
+// ── Workspace bundle ─────────────────────────────────────────────────────────
+
+/// Write the database, settings and session files to one bundle file at `path`.
+#[tauri::command]
+async fn cmd_export_workspace(state: State<'_, AppState>, path: String) -> Result<workspace::ExportSummary, AppError> {
+    if path.trim().is_empty() {
+        return Err(AppError::InvalidInput("Choose where to save the bundle".to_string()));
     }
-    Ok(result)
+    state
+        .db
+        .call(move |conn| workspace::export(conn, &openclaw::expand_home(path.trim())).map_err(AppError::from))
+        .await
+}
+
+/// Restore a bundle from `path`, merging into what's here unless `replace`.
+#[tauri::command]
+async fn cmd_import_workspace(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    path: String,
+    replace: Option<bool>,
+) -> Result<workspace::ImportSummary, AppError> {
+    let src = openclaw::expand_home(path.trim());
+    if !src.is_file() {
+        return Err(AppError::NotFound(format!("No bundle at {}", src.display())));
+    }
+    let summary = state
+        .db
+        .call(move |conn| workspace::import(conn, &src, replace.unwrap_or(false)).map_err(AppError::from))
+        .await?;
+    // Imported settings may move schedules around
+    scheduler::reload();
+    proactive::reload_schedule();
+    let _ = app.emit("workspace:imported", &summary);
+    Ok(summary)
+}
+
+// ── Sync ─────────────────────────────────────────────────────────────────────
+
+/// Sync with the other devices now instead of waiting for the scheduled run.
+#[tauri::command]
+async fn cmd_sync_now(app: AppHandle) -> Result<sync::SyncReport, AppError> {
+    Ok(sync::sync_now(&app).await?)
+}
+
+// ── GitHub ───────────────────────────────────────────────────────────────────
+
+/// Check and save a GitHub token, returning its account; empty removes it.
+#[tauri::command]
+async fn cmd_set_github_token(token: String) -> Result<Option<String>, AppError> {
+    Ok(github::set_token(&token).await?)
+}
+
+#[tauri::command]
+async fn cmd_set_project_github_repo(
+    state: State<'_, AppState>,
+    project_id: String,
+    repo: Option<String>,
+) -> Result<(), AppError> {
+    state
+        .db
+        .call(move |conn| github::set_project_repo(conn, &project_id, repo.as_deref()).map_err(AppError::from))
+        .await
+}
+
+#[tauri::command]
+async fn cmd_get_project_github_repo(state: State<'_, AppState>, project_id: String) -> Result<Option<String>, AppError> {
+    state
+        .db
+        .call(move |conn| github::project_repo(conn, &project_id).map_err(AppError::from))
+        .await
+}
+
+/// File an issue from a kanban card or brain dump in its project's
+/// repository. Title and body default to the card's or dump's text.
+#[tauri::command]
+async fn cmd_create_github_issue(
+    state: State<'_, AppState>,
+    owner_type: String,
+    owner_id: String,
+    title: Option<String>,
+    body: Option<String>,
+    labels: Option<Vec<String>>,
+) -> Result<github::GithubIssue, AppError> {
+    let draft = state
+        .db
+        .call({
+            let (owner_type, owner_id) = (owner_type.clone(), owner_id.clone());
+            move |conn| github::draft(conn, &owner_type, &owner_id).map_err(AppError::from)
+        })
+        .await?;
+    let title = title.filter(|t| !t.trim().is_empty()).unwrap_or(draft.title);
+    let body = body.unwrap_or(draft.body);
+    let (number, url) = github::create_issue(&draft.repo, &title, &body, &labels.unwrap_or_default()).await?;
+    state
+        .db
+        .call(move |conn| {
+            github::record_issue(conn, &owner_type, &owner_id, &draft.repo, number, &url).map_err(AppError::from)
+        })
+        .await
+}
+
+#[tauri::command]
+async fn cmd_get_github_issue(
+    state: State<'_, AppState>,
+    owner_type: String,
+    owner_id: String,
+) -> Result<Option<github::GithubIssue>, AppError> {
+    state
+        .db
+        .call(move |conn| github::issue(conn, &owner_type, &owner_id).map_err(AppError::from))
+        .await
+}
+
+// ── Webhooks ─────────────────────────────────────────────────────────────────
+
+#[tauri::command]
+async fn cmd_list_webhooks(state: State<'_, AppState>) -> Result<Vec<webhooks::Webhook>, AppError> {
+    state.db.call(|conn| webhooks::list(conn).map_err(AppError::from)).await
+}
+
+/// Create a webhook (no `id`) or change one. `events` empty means all of
+/// `webhooks::EVENTS`; a new webhook without a `secret` gets a random one.
+#[tauri::command]
+async fn cmd_save_webhook(
+    state: State<'_, AppState>,
+    id: Option<String>,
+    url: String,
+    events: Vec<String>,
+    enabled: Option<bool>,
+    secret: Option<String>,
+) -> Result<webhooks::Webhook, AppError> {
+    state
+        .db
+        .call(move |conn| {
+            webhooks::save(
+                conn,
+                id.as_deref(),
+                &url,
+                &events,
+                enabled.unwrap_or(true),
+                secret.as_deref().filter(|s| !s.is_empty()),
+            )
+            .map_err(AppError::from)
+        })
+        .await
+}
+
+#[tauri::command]
+async fn cmd_delete_webhook(state: State<'_, AppState>, id: String) -> Result<(), AppError> {
+    state.db.call(move |conn| webhooks::delete(conn, &id).map_err(AppError::from)).await
+}
+
+/// Send a "webhook:test" event to one webhook and return the HTTP status.
+#[tauri::command]
+async fn cmd_test_webhook(state: State<'_, AppState>, id: String) -> Result<u16, AppError> {
+    let hook_id = id.clone();
+    let hook = state
+        .db
+        .call(move |conn| webhooks::get(conn, &id).map_err(AppError::from))
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Webhook not found: {}", hook_id)))?;
+    Ok(webhooks::test(&hook).await?)
+}
+
+// ── Deep links ───────────────────────────────────────────────────────────────
+
+/// Where the link the app was launched with points, once; the UI asks on
+/// startup because "deeplink:open" went out before it was listening.
+#[tauri::command]
+async fn cmd_take_deep_link() -> Result<Option<deeplink::Navigation>, AppError> {
+    Ok(deeplink::take_pending())
 }
 
 // ── App entry point ───────────────────────────────────────────────────────────
@@ -552,93 +2566,237 @@ pub fn run() {
     // Initialize database
     let conn = open_db().expect("Failed to open database");
     init_db(&conn).expect("Failed to initialize database");
+    drop(conn);
+    let db = db::open_pool().expect("Failed to open database pool");
 
+    let ssh_session = new_shared_session();
     let app_state = AppState {
-        db: Arc::new(Mutex::new(conn)),
+        db,
         watcher_state: Arc::new(Mutex::new(WatcherState::new())),
-        ssh_session: new_shared_session(),
+        ssh_manager: SshManager::spawn(ssh_session.clone()),
+        ssh_session,
         remote_mode: Arc::new(Mutex::new(false)),
+        focused_thread: Arc::new(Mutex::new(None)),
     };
 
+    // Every command counts as the user being at the computer.
+    let handler = tauri::generate_handler![
+        cmd_list_projects,
+        cmd_create_project,
+        cmd_update_project,
+        cmd_delete_project,
+        cmd_set_project_status,
+        cmd_get_related_projects,
+        cmd_create_obsidian_note_for_project,
+        cmd_generate_weekly_review,
+        cmd_generate_retrospective,
+        cmd_list_retrospectives,
+        cmd_generate_briefing,
+        cmd_list_briefings,
+        cmd_resurface_stale_threads,
+        cmd_list_prompt_templates,
+        cmd_set_prompt_template,
+        cmd_reset_prompt_template,
+        cmd_list_jobs,
+        cmd_set_job_enabled,
+        cmd_set_job_schedule,
+        cmd_run_job_now,
+        cmd_list_job_runs,
+        cmd_list_sync_conflicts,
+        cmd_resolve_sync_conflict,
+        cmd_list_threads,
+        cmd_create_thread,
+        cmd_rename_thread,
+        cmd_set_thread_execution_target,
+        cmd_set_thread_model,
+        cmd_set_thread_muted,
+        cmd_set_focused_thread,
+        cmd_set_project_agent,
+        cmd_list_outbox,
+        cmd_discard_outbox,
+        cmd_delete_thread,
+        cmd_load_session,
+        cmd_load_session_page,
+        cmd_compact_session,
+        cmd_send_message,
+        cmd_watch_session,
+        cmd_stop_watching,
+        cmd_get_watcher_status,
+        cmd_find_cli_session,
+        cmd_reconcile_cli_session,
+        cmd_list_brain_dumps,
+        cmd_create_brain_dump,
+        cmd_update_brain_dump_status,
+        cmd_set_brain_dump_proactive,
+        cmd_set_brain_dump_cadence,
+        cmd_delete_brain_dump,
+        cmd_convert_dump_to_thread,
+        cmd_list_kanban_items,
+        cmd_create_kanban_item,
+        cmd_update_kanban_item,
+        cmd_delete_kanban_item,
+        cmd_add_kanban_dependency,
+        cmd_remove_kanban_dependency,
+        cmd_set_kanban_recurrence,
+        cmd_get_kanban_metrics,
+        cmd_import_kanban_from_markdown,
+        cmd_set_kanban_priority,
+        cmd_set_kanban_due_date,
+        cmd_export_kanban,
+        cmd_dispatch_kanban_item,
+        cmd_promote_brain_dump,
+        cmd_configure_ssh,
+        cmd_get_ssh_config,
+        cmd_test_ssh,
+        cmd_remote_health,
+        cmd_list_agents,
+        cmd_get_agent,
+        cmd_list_models,
+        cmd_create_agent,
+        cmd_update_agent_config,
+        cmd_get_agent_instructions,
+        cmd_get_ssh_log,
+        cmd_get_agent_log,
+        cmd_clear_agent_log,
+        cmd_ssh_open_forward,
+        cmd_ssh_close_forward,
+        cmd_ssh_list_forwards,
+        cmd_ssh_status,
+        cmd_ssh_trust_host_key,
+        cmd_ssh_forget_host_key,
+        cmd_ssh_connect,
+        cmd_set_ssh_password,
+        cmd_ssh_upload,
+        cmd_ssh_download,
+        cmd_remote_list_files,
+        cmd_remote_download_file,
+        cmd_remote_upload_file,
+        cmd_ssh_disconnect,
+        cmd_set_remote_mode,
+        cmd_get_remote_mode,
+        cmd_get_setting,
+        cmd_detect_openclaw,
+        cmd_check_agent_health,
+        cmd_list_agent_overrides,
+        cmd_set_agent_override,
+        cmd_delete_agent_override,
+        cmd_set_setting,
+        cmd_list_settings,
+        cmd_sync_obsidian_vault,
+        cmd_get_obsidian_layout,
+        cmd_set_obsidian_layout,
+        cmd_get_proactive_settings,
+        cmd_update_proactive_settings,
+        cmd_run_proactive_now,
+        cmd_set_proactive_paused,
+        cmd_get_proactive_status,
+        cmd_list_proactive_runs,
+        cmd_get_proactive_effectiveness,
+        cmd_get_dnd_status,
+        cmd_set_dnd,
+        cmd_notification_action,
+        cmd_list_follow_up_outcomes,
+        cmd_export_thread_to_obsidian,
+        cmd_search,
+        cmd_get_db_stats,
+        cmd_get_workspace_stats,
+        cmd_add_attachment,
+        cmd_list_attachments,
+        cmd_remove_attachment,
+        cmd_gc_blobs,
+        cmd_get_audit_log,
+        cmd_revert_audit_entry,
+        cmd_export_workspace,
+        cmd_import_workspace,
+        cmd_sync_now,
+        cmd_set_github_token,
+        cmd_set_project_github_repo,
+        cmd_get_project_github_repo,
+        cmd_create_github_issue,
+        cmd_get_github_issue,
+        cmd_list_webhooks,
+        cmd_save_webhook,
+        cmd_delete_webhook,
+        cmd_test_webhook,
+        cmd_take_deep_link,
+        cmd_get_mcp_status,
+        cmd_reset_mcp_token,
+    ];
+
     tauri::Builder::default()
+        // Must come first: on Windows and Linux a link launches a second
+        // process, which hands its arguments over here and exits
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            deeplink::focus_window(app);
+        }))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_deep_link::init())
         .manage(app_state)
-        .invoke_handler(tauri::generate_handler![
-            cmd_list_projects,
-            cmd_create_project,
-            cmd_update_project,
-            cmd_delete_project,
-            cmd_list_threads,
-            cmd_create_thread,
-            cmd_rename_thread,
-            cmd_delete_thread,
-            cmd_load_session,
-            cmd_send_message,
-            cmd_watch_session,
-            cmd_stop_watching,
-            cmd_list_brain_dumps,
-            cmd_create_brain_dump,
-            cmd_update_brain_dump_status,
-            cmd_set_brain_dump_proactive,
-            cmd_delete_brain_dump,
-            cmd_convert_dump_to_thread,
-            cmd_list_kanban_items,
-            cmd_create_kanban_item,
-            cmd_update_kanban_item,
-            cmd_delete_kanban_item,
-            cmd_promote_brain_dump,
-            cmd_configure_ssh,
-            cmd_get_ssh_config,
-            cmd_test_ssh,
-            cmd_ssh_status,
-            cmd_set_remote_mode,
-            cmd_get_remote_mode,
-            cmd_get_setting,
-            cmd_set_setting,
-            cmd_sync_obsidian_vault,
-        ])
+        .invoke_handler(move |invoke| {
+            proactive::note_activity();
+            handler(invoke)
+        })
         .setup(|app| {
-            // Start proactive loop in background
+            // openclaw-chat:// links from other apps
+            deeplink::init(app);
+            if let Err(e) = tray::init(app) {
+                eprintln!("[tray] Couldn't add the tray icon: {}", e);
+            }
+            // Background jobs: follow-ups, title refresh, recurrences, exports, reviews
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                proactive::run_proactive_loop(app_handle, None).await;
+                scheduler::run_scheduler(app_handle).await;
+            });
+            // Route SSH state transitions to the UI as ssh:status events
+            let app_handle6 = app.handle().clone();
+            let ssh_session = app.state::<AppState>().ssh_session.clone();
+            tauri::async_runtime::spawn(async move {
+                ssh_session.lock().await.set_app_handle(app_handle6);
+            });
+            // Mirror recent remote sessions locally and drain the offline outbox
+            let app_handle7 = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                mirror::run_mirror_loop(app_handle7).await;
             });
-            // Start nightly title refresh loop
-            let app_handle2 = app.handle().clone();
+            // Probe the openclaw install so format errors can name its version
+            let app_handle8 = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                proactive::run_title_refresh_loop(app_handle2).await;
+                let detection = openclaw::detect_openclaw().await;
+                let _ = app_handle8.emit("openclaw:detected", &detection);
             });
-            // Background Obsidian vault sync (2s delay)
-            let db_clone = Arc::clone(&app.state::<AppState>().db);
+            // Pick up conversations started from the CLI
+            let app_handle9 = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                start_session_discovery(&app_handle9, &app_handle9.state::<AppState>()).await;
+            });
+            // React to settings saved anywhere, and tell the UI
+            let app_handle10 = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                follow_setting_changes(app_handle10).await;
+            });
+            // Send app events to outbound webhooks
+            tauri::async_runtime::spawn(webhooks::run_dispatcher());
+            // Workspace tools for agents over MCP, if switched on
+            let app_handle11 = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                mcp::restart(&app_handle11).await;
+            });
+            // Background Obsidian vault sync (2s delay), then keep watching for edits
+            let app_handle4 = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-                let vault_path = {
-                    let conn = db_clone.lock().unwrap();
-                    db::get_setting(&conn, "obsidian_vault_path")
-                        .ok()
-                        .flatten()
-                };
+                let _ = scheduler::run_job(&app_handle4, scheduler::OBSIDIAN_SYNC).await;
+                let state = app_handle4.state::<AppState>();
+                let vault_path = state
+                    .db
+                    .call(|conn| db::get_setting(conn, "obsidian_vault_path"))
+                    .await
+                    .ok()
+                    .flatten();
                 if let Some(vault_path) = vault_path {
-                    let active_path = std::path::PathBuf::from(&vault_path)
-                        .join("10 Projects")
-                        .join("Active");
-                    if active_path.is_dir() {
-                        let projects = obsidian::parse_vault(&active_path);
-                        let conn = db_clone.lock().unwrap();
-                        for p in &projects {
-                            if let Err(e) = db::upsert_obsidian_project(
-                                &conn,
-                                &p.name,
-                                p.description.as_deref(),
-                                &p.color,
-                                &p.obsidian_source,
-                            ) {
-                                eprintln!("Obsidian sync error for {}: {}", p.name, e);
-                            }
-                        }
-                        eprintln!("Obsidian startup sync: {} projects processed", projects.len());
-                    }
+                    start_vault_watcher(&app_handle4, &state, &vault_path).await;
                 }
             });
             Ok(())
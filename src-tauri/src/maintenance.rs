@@ -0,0 +1,77 @@
+use crate::{blobs, db, dnd, mirror, openclaw, relations};
+use anyhow::Result;
+use rusqlite::Connection;
+use tauri::AppHandle;
+
+/// How long audit entries are kept.
+const AUDIT_RETENTION_MS: i64 = 90 * 24 * 60 * 60 * 1000;
+
+/// Scheduled database upkeep: planner statistics, a WAL checkpoint, a full
+/// integrity check and cleanup of rows pointing at things that are gone,
+/// and of attachment files nothing uses.
+/// The report is kept for `cmd_get_db_stats` and sent as
+/// "maintenance:finished".
+pub async fn maintenance_job(app: AppHandle) -> Result<String> {
+    let report = db::pool()?.call(run).await?;
+    dnd::emit(&app, "maintenance:finished", &report);
+    let mut summary = format!(
+        "{} WAL frames checkpointed, {} orphaned threads, {} cards unlinked",
+        report.checkpointed,
+        report.orphan_threads.len(),
+        report.orphan_cards
+    );
+    if !report.integrity.is_empty() {
+        summary.push_str(&format!(", integrity check found {} problems", report.integrity.len()));
+    }
+    Ok(summary)
+}
+
+fn run(conn: &Connection) -> Result<db::MaintenanceReport> {
+    let started = chrono::Utc::now().timestamp_millis();
+    let mut report = db::MaintenanceReport {
+        ran_at: started,
+        integrity: db::integrity_problems(conn, true)?,
+        ..Default::default()
+    };
+    // Don't delete anything from a database that may be damaged
+    if report.integrity.is_empty() {
+        report.orphan_threads = delete_orphan_threads(conn, started)?;
+        report.orphan_cards = db::detach_cards_from_missing_dumps(conn)?;
+        report.audit_pruned = db::prune_audit_log(conn, started - AUDIT_RETENTION_MS)?;
+        report.blobs_removed = blobs::gc(conn)?.removed;
+    }
+    db::optimize(conn)?;
+    (report.checkpoint_busy, report.checkpointed) = db::checkpoint(conn)?;
+    report.duration_ms = chrono::Utc::now().timestamp_millis() - started;
+    db::record_maintenance_run(conn, &report)?;
+    Ok(report)
+}
+
+/// Threads set to run locally that have had messages but whose session file
+/// (and mirror copy) no longer exists. A thread is only deleted when its
+/// file was also missing on the previous run, so a briefly unavailable home
+/// dir doesn't cost anything; until then it's just noted. Threads that
+/// haven't been written to yet, or that follow the remote mode setting, are
+/// left alone. Returns the names deleted.
+fn delete_orphan_threads(conn: &Connection, now: i64) -> Result<Vec<String>> {
+    let noted = db::list_missing_sessions(conn)?;
+    let mut missing = Vec::new();
+    let mut deleted = Vec::new();
+    for thread in db::list_threads_active_since(conn, 0)? {
+        if thread.execution_target.as_deref() != Some("local") {
+            continue;
+        }
+        let cached = mirror::cache_path(&thread.agent_id, &thread.session_id).is_ok_and(|p| p.exists());
+        if cached || openclaw::session_path(&thread.agent_id, &thread.session_id).exists() {
+            continue;
+        }
+        if noted.contains(&thread.id) {
+            relations::delete(conn, relations::THREADS, &thread.id)?;
+            deleted.push(thread.name);
+        } else {
+            missing.push(thread.id);
+        }
+    }
+    db::set_missing_sessions(conn, &missing, now)?;
+    Ok(deleted)
+}
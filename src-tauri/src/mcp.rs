@@ -0,0 +1,671 @@
+use crate::db::{self, BrainDump};
+use crate::{kanban, mirror, obsidian, openclaw, settings, webhooks};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+pub const ENABLED_SETTING: &str = "mcp_server";
+pub const PORT_SETTING: &str = "mcp_port";
+pub const READ_ONLY_SETTING: &str = "mcp_read_only";
+pub const TOKEN_SETTING: &str = "mcp_token";
+pub const DEFAULT_PORT: &str = "4717";
+
+/// MCP revisions this server speaks, newest first.
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26"];
+const MAX_BODY: usize = 1 << 20;
+const MAX_HEADER_LINES: usize = 64;
+const MAX_LINE: usize = 8 * 1024;
+/// How long a client gets to send the request line and headers.
+const HEADER_TIMEOUT: Duration = Duration::from_secs(10);
+/// Messages `read_thread` returns unless asked for another number.
+const DEFAULT_MESSAGES: usize = 50;
+
+/// Whether the server is up, and what an agent's MCP config needs to reach
+/// it.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub read_only: bool,
+    /// Streamable HTTP endpoint, e.g. `http://127.0.0.1:4717/mcp`.
+    pub url: Option<String>,
+    /// Sent by clients as `Authorization: Bearer <token>`.
+    pub token: Option<String>,
+    /// Why the server isn't running although it's enabled.
+    pub error: Option<String>,
+}
+
+static STATUS: Mutex<Option<McpStatus>> = Mutex::new(None);
+/// The running server. Held across a restart so two can't race for the port.
+static TASK: tokio::sync::Mutex<Option<tokio::task::JoinHandle<()>>> = tokio::sync::Mutex::const_new(None);
+
+pub fn status() -> McpStatus {
+    STATUS.lock().unwrap().clone().unwrap_or_default()
+}
+
+/// Stop the server and start it again with the current settings, if it's
+/// enabled. Run at startup and whenever one of its settings changes.
+pub async fn restart(app: &AppHandle) {
+    let mut task = TASK.lock().await;
+    if let Some(running) = task.take() {
+        running.abort();
+        // Wait for the listener to be dropped before binding the port again
+        let _ = running.await;
+    }
+    let status = match start(app).await {
+        Ok((status, running)) => {
+            *task = running;
+            status
+        }
+        Err(e) => {
+            eprintln!("[mcp] Couldn't start the server: {}", e);
+            McpStatus {
+                enabled: true,
+                error: Some(e.to_string()),
+                ..Default::default()
+            }
+        }
+    };
+    *STATUS.lock().unwrap() = Some(status);
+}
+
+async fn start(app: &AppHandle) -> Result<(McpStatus, Option<tokio::task::JoinHandle<()>>)> {
+    let db = db::pool()?;
+    let (enabled, read_only, port, token) = db
+        .call(|conn| -> Result<_> {
+            let enabled = settings::get_bool(conn, ENABLED_SETTING)?;
+            let read_only = settings::get_bool(conn, READ_ONLY_SETTING)?;
+            let port = settings::get_int(conn, PORT_SETTING)?.unwrap_or_default();
+            let token = match settings::get_text(conn, TOKEN_SETTING)? {
+                Some(token) => token,
+                None if enabled => new_token(conn)?,
+                None => String::new(),
+            };
+            Ok((enabled, read_only, port, token))
+        })
+        .await?;
+    if !enabled {
+        return Ok((McpStatus::default(), None));
+    }
+    let port = u16::try_from(port).map_err(|_| anyhow!("Port {} is out of range", port))?;
+    // Loopback only: the token is all that stands between a caller and the workspace
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .map_err(|e| anyhow!("Couldn't listen on port {}: {}", port, e))?;
+    let server = Server {
+        app: app.clone(),
+        token: token.clone(),
+        read_only,
+    };
+    let status = McpStatus {
+        enabled,
+        running: true,
+        read_only,
+        url: Some(format!("http://127.0.0.1:{}/mcp", port)),
+        token: Some(token),
+        error: None,
+    };
+    Ok((status, Some(tauri::async_runtime::spawn(serve(listener, server)))))
+}
+
+/// Replace the access token; clients have to be given the new one.
+pub fn new_token(conn: &rusqlite::Connection) -> Result<String> {
+    let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    settings::set(conn, TOKEN_SETTING, &json!(token))?;
+    Ok(token)
+}
+
+#[derive(Clone)]
+struct Server {
+    app: AppHandle,
+    token: String,
+    read_only: bool,
+}
+
+async fn serve(listener: TcpListener, server: Server) {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                eprintln!("[mcp] Failed to accept a connection: {}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let server = server.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = handle_connection(&server, stream).await {
+                eprintln!("[mcp] {}", e);
+            }
+        });
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// One request per connection: the Streamable HTTP transport, answering
+/// every POST with a plain JSON body and never opening an SSE stream.
+async fn handle_connection(server: &Server, stream: TcpStream) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let request = match read_request(&mut reader).await {
+        Ok(request) => request,
+        Err(e) => return respond(reader.get_mut(), 400, &json!({ "error": e.to_string() })).await,
+    };
+    let stream = reader.get_mut();
+    if request.path.split('?').next() != Some("/mcp") {
+        return respond(stream, 404, &json!({ "error": "Not found" })).await;
+    }
+    // Browsers send Origin; keep web pages from talking to the server
+    if let Some(origin) = request.header("origin") {
+        if !is_local_origin(origin) {
+            return respond(stream, 403, &json!({ "error": "Origin not allowed" })).await;
+        }
+    }
+    let authorized = request
+        .header("authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|token| same(token.trim(), &server.token));
+    if !authorized {
+        return respond(stream, 401, &json!({ "error": "Missing or wrong bearer token" })).await;
+    }
+    if request.method != "POST" {
+        return respond(stream, 405, &json!({ "error": "Only POST is supported" })).await;
+    }
+    let message: Value = match serde_json::from_slice(&request.body) {
+        Ok(message) => message,
+        Err(e) => return respond(stream, 400, &error_response(Value::Null, -32700, &format!("Parse error: {}", e))).await,
+    };
+    let batch = message.is_array();
+    let replies: Vec<Value> = match message {
+        Value::Array(messages) => {
+            let mut replies = Vec::new();
+            for message in messages {
+                replies.extend(handle_message(server, message).await);
+            }
+            replies
+        }
+        message => handle_message(server, message).await.into_iter().collect(),
+    };
+    match replies.len() {
+        // Only notifications or responses came in
+        0 => respond_empty(stream, 202).await,
+        _ if batch => respond(stream, 200, &Value::Array(replies)).await,
+        _ => respond(stream, 200, &replies[0]).await,
+    }
+}
+
+async fn read_request(reader: &mut BufReader<TcpStream>) -> Result<Request> {
+    let mut request = tokio::time::timeout(HEADER_TIMEOUT, read_head(reader))
+        .await
+        .map_err(|_| anyhow!("Timed out waiting for the headers"))??;
+    let length: usize = match request.header("content-length") {
+        Some(length) => length.parse().map_err(|_| anyhow!("Bad Content-Length"))?,
+        None => 0,
+    };
+    if length > MAX_BODY {
+        return Err(anyhow!("Request body too large"));
+    }
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body).await?;
+    Ok(request)
+}
+
+/// Request line and headers, with an empty body.
+async fn read_head(reader: &mut BufReader<TcpStream>) -> Result<Request> {
+    let mut line = String::new();
+    read_line(reader, &mut line).await?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(anyhow!("Malformed request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if read_line(reader, &mut line).await? == 0 {
+            return Err(anyhow!("Connection closed in the headers"));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADER_LINES {
+            return Err(anyhow!("Too many headers"));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    })
+}
+
+/// `read_line` that gives up on a line longer than `MAX_LINE` rather than
+/// buffering whatever the client sends.
+async fn read_line(reader: &mut BufReader<TcpStream>, line: &mut String) -> Result<usize> {
+    let read = (&mut *reader).take(MAX_LINE as u64 + 1).read_line(line).await?;
+    if line.len() > MAX_LINE {
+        return Err(anyhow!("Header line too long"));
+    }
+    Ok(read)
+}
+
+async fn respond(stream: &mut TcpStream, status: u16, body: &Value) -> Result<()> {
+    let body = body.to_string();
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason(status),
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+async fn respond_empty(stream: &mut TcpStream, status: u16) -> Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        status,
+        reason(status)
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Error",
+    }
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let authority = origin.split_once("://").map_or(origin, |(_, rest)| rest);
+    let host = match authority.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    matches!(host, "127.0.0.1" | "localhost" | "::1")
+}
+
+/// Compare tokens without giving away how much of a guess was right.
+fn same(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ── JSON-RPC ─────────────────────────────────────────────────────────────────
+
+/// The reply to one JSON-RPC message; none for notifications and responses.
+async fn handle_message(server: &Server, message: Value) -> Option<Value> {
+    let id = message.get("id").cloned()?;
+    let Some(method) = message["method"].as_str() else {
+        // A response to something we never sent
+        return None;
+    };
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let result = match method {
+        "initialize" => Ok(initialize(&params)),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tool_list(server.read_only) })),
+        "tools/call" => call_tool(server, &params).await,
+        _ => Err((-32601, format!("Method not found: {}", method))),
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error_response(id, code, &message),
+    })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn initialize(params: &Value) -> Value {
+    let requested = params["protocolVersion"].as_str().unwrap_or_default();
+    let version = PROTOCOL_VERSIONS
+        .iter()
+        .find(|v| **v == requested)
+        .unwrap_or(&PROTOCOL_VERSIONS[0]);
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": { "listChanged": false } },
+        "serverInfo": { "name": "openclaw-chat", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": "The user's OpenClaw Chat workspace: projects, chat threads, brain dumps \
+            and a kanban board with the columns backlog, this_week, in_progress and done. \
+            Use search or the list tools to find ids before changing anything.",
+    })
+}
+
+struct Tool {
+    name: &'static str,
+    description: &'static str,
+    /// Left out while the server is read-only.
+    writes: bool,
+}
+
+const TOOLS: &[Tool] = &[
+    Tool {
+        name: "list_projects",
+        description: "List the user's projects.",
+        writes: false,
+    },
+    Tool {
+        name: "list_threads",
+        description: "List chat threads, newest first, optionally only a project's.",
+        writes: false,
+    },
+    Tool {
+        name: "read_thread",
+        description: "The latest messages of a chat thread.",
+        writes: false,
+    },
+    Tool {
+        name: "list_brain_dumps",
+        description: "List brain dumps (quick captured notes), optionally by status.",
+        writes: false,
+    },
+    Tool {
+        name: "list_kanban_cards",
+        description: "List kanban cards, optionally by project or column.",
+        writes: false,
+    },
+    Tool {
+        name: "search",
+        description: "Full-text search across threads, brain dumps and kanban cards.",
+        writes: false,
+    },
+    Tool {
+        name: "create_brain_dump",
+        description: "Capture a brain dump for the user to sort out later.",
+        writes: true,
+    },
+    Tool {
+        name: "create_kanban_card",
+        description: "Add a card to the kanban board, in the backlog unless another column is given.",
+        writes: true,
+    },
+    Tool {
+        name: "update_kanban_card",
+        description: "Change a kanban card: its title, description, column, priority or due date. \
+            Moving it to done completes it.",
+        writes: true,
+    },
+];
+
+fn tool_list(read_only: bool) -> Vec<Value> {
+    TOOLS
+        .iter()
+        .filter(|tool| !(read_only && tool.writes))
+        .map(|tool| {
+            json!({
+                "name": tool.name,
+                "description": tool.description,
+                "inputSchema": input_schema(tool.name),
+                "annotations": { "readOnlyHint": !tool.writes },
+            })
+        })
+        .collect()
+}
+
+fn input_schema(tool: &str) -> Value {
+    let project_id = json!({ "type": "string", "description": "Project id from list_projects" });
+    let column = json!({ "type": "string", "enum": kanban::COLUMNS });
+    let priority = json!({ "type": "integer", "minimum": 0, "maximum": 4, "description": "0 none … 4 urgent" });
+    let due = json!({
+        "type": "string",
+        "description": "YYYY-MM-DD, YYYY-MM-DD HH:MM (local time) or RFC 3339; empty clears it",
+    });
+    match tool {
+        "list_projects" => json!({
+            "type": "object",
+            "properties": { "include_archived": { "type": "boolean" } },
+        }),
+        "list_threads" => json!({
+            "type": "object",
+            "properties": { "project_id": project_id },
+        }),
+        "read_thread" => json!({
+            "type": "object",
+            "properties": {
+                "thread_id": { "type": "string" },
+                "limit": { "type": "integer", "minimum": 1, "description": "Default 50" },
+            },
+            "required": ["thread_id"],
+        }),
+        "list_brain_dumps" => json!({
+            "type": "object",
+            "properties": { "status": { "type": "string", "enum": ["open", "in_progress", "done"] } },
+        }),
+        "list_kanban_cards" => json!({
+            "type": "object",
+            "properties": { "project_id": project_id, "column": column },
+        }),
+        "search" => json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string" },
+                "kinds": { "type": "array", "items": { "type": "string", "enum": db::SEARCH_KINDS } },
+                "limit": { "type": "integer", "minimum": 1 },
+            },
+            "required": ["query"],
+        }),
+        "create_brain_dump" => json!({
+            "type": "object",
+            "properties": { "content": { "type": "string" }, "project_id": project_id },
+            "required": ["content"],
+        }),
+        "create_kanban_card" => json!({
+            "type": "object",
+            "properties": {
+                "title": { "type": "string" },
+                "description": { "type": "string" },
+                "project_id": project_id,
+                "column": column,
+                "priority": priority,
+                "due": due,
+            },
+            "required": ["title"],
+        }),
+        "update_kanban_card" => json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "string" },
+                "title": { "type": "string" },
+                "description": { "type": "string" },
+                "column": column,
+                "priority": priority,
+                "due": due,
+            },
+            "required": ["id"],
+        }),
+        _ => json!({ "type": "object" }),
+    }
+}
+
+/// Run a tool. Failures of the tool itself come back as an `isError`
+/// result the agent can read; only unknown tools are protocol errors.
+async fn call_tool(server: &Server, params: &Value) -> Result<Value, (i64, String)> {
+    let name = params["name"].as_str().unwrap_or_default();
+    let tool = TOOLS
+        .iter()
+        .find(|tool| tool.name == name && !(server.read_only && tool.writes))
+        .ok_or_else(|| (-32602, format!("Unknown tool: {}", name)))?;
+    let args = match params.get("arguments") {
+        Some(Value::Object(args)) => Value::Object(args.clone()),
+        _ => json!({}),
+    };
+    let result = match db::pool() {
+        Ok(db) => {
+            let app = server.app.clone();
+            let name = tool.name;
+            db.call(move |conn| run_tool(&app, conn, name, &args)).await
+        }
+        Err(e) => Err(e),
+    };
+    Ok(match result {
+        Ok(value) => json!({
+            "content": [{ "type": "text", "text": serde_json::to_string_pretty(&value).unwrap_or_default() }],
+            "structuredContent": { "result": value },
+            "isError": false,
+        }),
+        Err(e) => json!({
+            "content": [{ "type": "text", "text": e.to_string() }],
+            "isError": true,
+        }),
+    })
+}
+
+fn run_tool(app: &AppHandle, conn: &rusqlite::Connection, name: &str, args: &Value) -> Result<Value> {
+    let text = |key: &str| args[key].as_str().map(str::trim).filter(|s| !s.is_empty()).map(str::to_string);
+    let required = |key: &str| text(key).ok_or_else(|| anyhow!("`{}` is required", key));
+    let project_id = text("project_id");
+    if let Some(id) = project_id.as_deref() {
+        db::get_project(conn, id)?.ok_or_else(|| anyhow!("No project with id {}", id))?;
+    }
+    let column = text("column");
+    if let Some(column) = column.as_deref() {
+        if !kanban::COLUMNS.contains(&column) {
+            return Err(anyhow!("Unknown column {:?}; use one of {}", column, kanban::COLUMNS.join(", ")));
+        }
+    }
+    let value = match name {
+        "list_projects" => {
+            json!(db::list_projects(conn, args["include_archived"].as_bool().unwrap_or(false))?)
+        }
+        "list_threads" => json!(db::list_threads(conn, project_id.as_deref())?),
+        "read_thread" => {
+            let thread_id = required("thread_id")?;
+            let thread = db::get_thread(conn, &thread_id)?.ok_or_else(|| anyhow!("No thread with id {}", thread_id))?;
+            let limit = args["limit"].as_u64().map_or(DEFAULT_MESSAGES, |n| n.max(1) as usize);
+            let mut messages = openclaw::load_session(&thread.agent_id, &thread.session_id)?.messages;
+            // Sessions that only ever ran remotely are readable from the mirror
+            if messages.is_empty() {
+                if let Some(cached) = mirror::load_cached(&thread.agent_id, &thread.session_id)? {
+                    messages = cached.messages;
+                }
+            }
+            let skip = messages.len().saturating_sub(limit);
+            let messages: Vec<Value> = messages
+                .into_iter()
+                .skip(skip)
+                .map(|m| json!({ "role": m.role, "content": m.content }))
+                .collect();
+            json!({ "thread": thread, "messages": messages })
+        }
+        "list_brain_dumps" => {
+            let status = text("status");
+            let dumps: Vec<BrainDump> = db::list_brain_dumps(conn)?
+                .into_iter()
+                .filter(|d| status.as_deref().is_none_or(|s| d.status == s))
+                .collect();
+            json!(dumps)
+        }
+        "list_kanban_cards" => {
+            let cards: Vec<db::KanbanItem> = db::list_kanban_items(conn, project_id.as_deref(), db::KanbanSort::Manual)?
+                .into_iter()
+                .filter(|card| column.as_deref().is_none_or(|c| card.column == c))
+                .collect();
+            json!(cards)
+        }
+        "search" => {
+            let kinds: Vec<String> = args["kinds"]
+                .as_array()
+                .map(|kinds| kinds.iter().filter_map(|k| k.as_str().map(str::to_string)).collect())
+                .unwrap_or_default();
+            if let Some(kind) = kinds.iter().find(|k| !db::SEARCH_KINDS.contains(&k.as_str())) {
+                return Err(anyhow!("Unknown search kind: {}", kind));
+            }
+            let limit = args["limit"].as_u64().unwrap_or(20) as u32;
+            json!(db::search(conn, &required("query")?, &kinds, limit)?)
+        }
+        "create_brain_dump" => {
+            let dump = crate::capture_brain_dump(conn, required("content")?, project_id)?;
+            webhooks::publish(webhooks::BRAIN_DUMP_CREATED, &dump);
+            let _ = app.emit("braindump:created", &dump);
+            json!(dump)
+        }
+        "create_kanban_card" => {
+            let item = kanban::create_kanban_item(conn, required("title")?, project_id, text("description"), column)?;
+            set_priority_and_due(conn, &item.id, args)?;
+            let _ = app.emit("kanban:refresh", ());
+            json!(db::get_kanban_item(conn, &item.id)?)
+        }
+        "update_kanban_card" => {
+            let id = required("id")?;
+            let card = db::get_kanban_item(conn, &id)?.ok_or_else(|| anyhow!("No kanban card with id {}", id))?;
+            let done = column.as_deref() == Some("done");
+            let moved = kanban::update_kanban_item(
+                conn,
+                id.clone(),
+                text("title"),
+                args["description"].as_str().map(str::to_string),
+                column.filter(|c| c != "done" && *c != card.column),
+                None,
+                None,
+                None,
+            )?;
+            if let Some(moved) = moved {
+                webhooks::publish(webhooks::KANBAN_MOVED, &moved);
+            }
+            set_priority_and_due(conn, &id, args)?;
+            if done {
+                if let Some(completion) = kanban::complete_card(conn, &id)? {
+                    completion.announce(app);
+                }
+            }
+            let _ = app.emit("kanban:refresh", ());
+            json!(db::get_kanban_item(conn, &id)?)
+        }
+        _ => return Err(anyhow!("Unknown tool: {}", name)),
+    };
+    Ok(value)
+}
+
+/// The optional `priority` and `due` arguments of the card tools.
+fn set_priority_and_due(conn: &rusqlite::Connection, id: &str, args: &Value) -> Result<()> {
+    if let Some(priority) = args["priority"].as_i64() {
+        kanban::set_priority(conn, id.to_string(), i32::try_from(priority).unwrap_or(i32::MAX))?;
+    }
+    match args["due"].as_str().map(str::trim) {
+        None => {}
+        Some("") => db::set_kanban_due_at(conn, id, None)?,
+        Some(due) => {
+            let due_at = obsidian::parse_date(due).ok_or_else(|| anyhow!("Can't read the due date {:?}", due))?;
+            db::set_kanban_due_at(conn, id, Some(due_at))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,226 @@
+use crate::db::{self, OutboxMessage};
+use crate::error::AppError;
+use crate::openclaw::{self, ChatMessage, ParsedSession};
+use crate::ssh::{ConnectionStatus, SshHandle};
+use crate::AppState;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+const MIRROR_INTERVAL_SECS: u64 = 120;
+/// Only sessions with activity in this window are pulled by the background loop.
+const MIRROR_WINDOW_MS: i64 = 7 * 24 * 60 * 60 * 1000;
+
+// ── Local cache of remote sessions ───────────────────────────────────────────
+
+/// Where the last-seen copy of a remote session lives on this machine.
+pub fn cache_path(agent_id: &str, session_id: &str) -> Result<PathBuf> {
+    for id in [agent_id, session_id] {
+        if id.is_empty() || id.contains('/') || id.contains('\\') || id == "." || id == ".." {
+            return Err(anyhow!("Invalid agent or session id: {:?}", id));
+        }
+    }
+    Ok(cache_dir().join(agent_id).join(format!("{}.jsonl", session_id)))
+}
+
+/// `~/.openclaw/chat/remote-cache`, one directory per agent.
+pub fn cache_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".openclaw")
+        .join("chat")
+        .join("remote-cache")
+}
+
+/// The cached copy, or `None` if the session was never mirrored.
+pub fn load_cached(agent_id: &str, session_id: &str) -> Result<Option<ParsedSession>> {
+    let path = cache_path(agent_id, session_id)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&path)?;
+    Ok(Some(openclaw::parse_session(&content)))
+}
+
+/// Replace the cached copy with `content`. Returns false when it was already current.
+pub fn write_cache(agent_id: &str, session_id: &str, content: &str) -> Result<bool> {
+    let path = cache_path(agent_id, session_id)?;
+    if std::fs::read_to_string(&path).ok().as_deref() == Some(content) {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("jsonl.tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, &path)?;
+    Ok(true)
+}
+
+/// Record a message locally that hasn't reached the remote host yet.
+pub fn append_cached(agent_id: &str, session_id: &str, msg: &ChatMessage) -> Result<()> {
+    let path = cache_path(agent_id, session_id)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    openclaw::append_line(&path, &openclaw::jsonl_line(msg)?)
+}
+
+// ── Outbox & pull ────────────────────────────────────────────────────────────
+
+/// A queued message that fails this many times is dropped and reported.
+const MAX_OUTBOX_ATTEMPTS: u32 = 5;
+
+/// Outcome for one queued message, reported in `outbox:flushed`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxDelivery {
+    pub id: String,
+    pub thread_id: String,
+    pub session_id: String,
+    pub delivered: bool,
+    /// Gave up after `MAX_OUTBOX_ATTEMPTS` failures.
+    pub dropped: bool,
+    pub error: Option<String>,
+}
+
+/// Deliver queued messages oldest first. A failure holds back the rest of
+/// that thread's queue so its messages never arrive out of order; other
+/// threads carry on. Emits `outbox:flushed` with every attempted delivery
+/// and returns how many went through.
+pub async fn flush_outbox(app: &AppHandle, ssh: &SshHandle) -> Result<usize> {
+    let queued = db::call(|conn| db::list_outbox(conn, None)).await?;
+    let mut blocked = HashSet::new();
+    let mut results = Vec::new();
+    for msg in queued {
+        if blocked.contains(&msg.thread_id) {
+            continue;
+        }
+        // Replies land in the remote session file and come back with the next pull
+        let sent = ssh
+            .send_message_remote(&msg.agent_id, &msg.session_id, msg.model.as_deref(), &msg.message, |_| {})
+            .await;
+        let id = msg.id.clone();
+        let failed = sent.as_ref().err().map(|e| e.to_string());
+        let (delivered, dropped, error) = db::call(move |conn| match failed {
+            None => {
+                db::delete_outbox(conn, &id)?;
+                Ok((true, false, None))
+            }
+            Some(error) => {
+                let attempts = db::record_outbox_failure(conn, &id, &error)?;
+                let dropped = attempts >= MAX_OUTBOX_ATTEMPTS;
+                if dropped {
+                    db::delete_outbox(conn, &id)?;
+                }
+                Ok((false, dropped, Some(error)))
+            }
+        })
+        .await?;
+        if !delivered {
+            blocked.insert(msg.thread_id.clone());
+        }
+        results.push(OutboxDelivery {
+            id: msg.id,
+            thread_id: msg.thread_id,
+            session_id: msg.session_id,
+            delivered,
+            dropped,
+            error,
+        });
+    }
+    if !results.is_empty() {
+        let _ = app.emit("outbox:flushed", serde_json::json!({ "results": results }));
+    }
+    Ok(results.iter().filter(|r| r.delivered).count())
+}
+
+/// Queue a message for the next time the remote host is reachable.
+pub fn enqueue(conn: &rusqlite::Connection, msg: &OutboxMessage) -> Result<()> {
+    db::enqueue_outbox(conn, msg)?;
+    append_cached(
+        &msg.agent_id,
+        &msg.session_id,
+        &ChatMessage {
+            role: "user".to_string(),
+            content: msg.message.clone(),
+            parts: Vec::new(),
+        },
+    )
+}
+
+/// Refresh the cache for every remote thread active since `since` (epoch ms).
+/// `remote_default` is the global mode, for threads without an override.
+/// Returns how many cached sessions changed.
+pub async fn pull_sessions(ssh: &SshHandle, since: i64, remote_default: bool) -> Result<usize> {
+    let threads = db::call(move |conn| db::list_threads_active_since(conn, since)).await?;
+    let mut seen = HashSet::new();
+    let mut changed = 0;
+    for thread in threads {
+        if !thread.runs_remote(remote_default) {
+            continue;
+        }
+        if !seen.insert((thread.agent_id.clone(), thread.session_id.clone())) {
+            continue;
+        }
+        let content = ssh.read_session_file(&thread.agent_id, &thread.session_id).await?;
+        if content.is_empty() {
+            continue;
+        }
+        if write_cache(&thread.agent_id, &thread.session_id, &content)? {
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// Flush the outbox, then pull sessions active since `since`. Emits
+/// `mirror:synced` when anything moved.
+pub async fn sync(app: &AppHandle, ssh: &SshHandle, since: i64, remote_default: bool) -> Result<()> {
+    let flushed = flush_outbox(app, ssh).await?;
+    let pulled = pull_sessions(ssh, since, remote_default).await?;
+    if flushed > 0 || pulled > 0 {
+        let _ = app.emit(
+            "mirror:synced",
+            serde_json::json!({ "flushed": flushed, "pulled": pulled }),
+        );
+    }
+    Ok(())
+}
+
+/// Background mirror: while the session is up, keep the local cache of recent
+/// remote sessions fresh and drain the outbox. Runs on a timer and right
+/// away whenever the connection comes (back) up.
+pub async fn run_mirror_loop(app: AppHandle) {
+    let mut status = app.state::<AppState>().ssh_session.lock().await.subscribe();
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(MIRROR_INTERVAL_SECS)) => {}
+            Ok(()) = status.changed() => {
+                if *status.borrow_and_update() != ConnectionStatus::Connected {
+                    continue;
+                }
+            }
+        }
+        let state = app.state::<AppState>();
+        let remote_default = *state.remote_mode.lock().unwrap();
+        let handle = {
+            let ssh = state.ssh_session.lock().await;
+            if !ssh.is_alive().await {
+                continue;
+            }
+            ssh.handle()
+        };
+        let Ok(ssh) = handle else {
+            continue;
+        };
+        let since = chrono::Utc::now().timestamp_millis() - MIRROR_WINDOW_MS;
+        if let Err(e) = sync(&app, &ssh, since, remote_default).await {
+            eprintln!("[mirror] Error: {}", e);
+            AppError::from(e).emit(&app, "mirror");
+        }
+    }
+}
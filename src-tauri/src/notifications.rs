@@ -0,0 +1,314 @@
+use crate::db;
+use crate::deeplink::{self, Navigation};
+use crate::dnd;
+use crate::error::AppError;
+use crate::kanban;
+use crate::openclaw::ChatMessage;
+use crate::tray;
+use crate::AppState;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// How much of the reply goes into the notification body.
+const SNIPPET_CHARS: usize = 140;
+
+/// How long "Snooze" puts a notification off for.
+const SNOOZE: Duration = Duration::from_secs(60 * 60);
+
+/// How long a notice's buttons are listened for. Past this it's closed where
+/// the platform allows, and a late answer does nothing.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+const ANSWER_WAIT: Duration = Duration::from_secs(30 * 60);
+
+/// A button on a notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    OpenThread,
+    Snooze,
+    MarkDone,
+}
+
+impl Action {
+    fn id(self) -> &'static str {
+        match self {
+            Action::OpenThread => "open_thread",
+            Action::Snooze => "snooze",
+            Action::MarkDone => "mark_done",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Action::OpenThread => "Open Thread",
+            Action::Snooze => "Snooze 1 Hour",
+            Action::MarkDone => "Mark Done",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Action> {
+        [Action::OpenThread, Action::Snooze, Action::MarkDone]
+            .into_iter()
+            .find(|a| a.id() == id || a.title() == id)
+    }
+}
+
+/// An OS notification, with what it's about so its buttons know what to
+/// act on. This is also what do-not-disturb holds; older held notifications
+/// have only a title and body.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notice {
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub thread_id: Option<String>,
+    #[serde(default)]
+    pub card_id: Option<String>,
+    #[serde(default)]
+    pub dump_id: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+impl Notice {
+    pub fn new(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Notice {
+            title: title.into(),
+            body: body.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The buttons that make sense for what's set: "Open Thread" needs a
+    /// thread and "Mark Done" a card or dump.
+    pub fn with_actions(mut self, actions: &[Action]) -> Self {
+        self.actions = actions
+            .iter()
+            .copied()
+            .filter(|a| match a {
+                Action::OpenThread => self.thread_id.is_some(),
+                Action::MarkDone => self.card_id.is_some() || self.dump_id.is_some(),
+                Action::Snooze => true,
+            })
+            .collect();
+        self
+    }
+}
+
+/// Post an OS notification for a reply in `session_id`, unless its thread is
+/// the one in front of the user, is muted, or there is no thread for it.
+/// Held while do-not-disturb is on.
+pub fn reply_arrived(app: &AppHandle, session_id: &str, reply: &ChatMessage) {
+    let state = app.state::<AppState>();
+    let thread = state
+        .db
+        .get()
+        .and_then(|conn| db::get_thread_by_session(&conn, session_id));
+    let Ok(Some(thread)) = thread else {
+        return;
+    };
+    let focused = state.focused_thread.lock().unwrap().as_deref() == Some(thread.id.as_str());
+    if focused {
+        return;
+    }
+    tray::mark_unread(app, &thread.id, &thread.name);
+    if thread.muted {
+        return;
+    }
+    let body = match snippet(&reply.content) {
+        body if body.is_empty() => "New reply".to_string(),
+        body => body,
+    };
+    dnd::notify(app, &thread.name, &body);
+}
+
+/// Post an OS notification right away.
+pub fn show(app: &AppHandle, title: &str, body: &str) {
+    let shown = app.notification().builder().title(title).body(body).show();
+    if let Err(e) = shown {
+        eprintln!("[notifications] Failed to notify {:?}: {}", title, e);
+    }
+}
+
+/// Post a notice right away. Buttons are shown where the OS notification
+/// center reports which one was pressed (macOS and Linux); elsewhere it's
+/// a plain notification. A click on the notice itself opens its thread.
+pub fn show_notice(app: &AppHandle, notice: &Notice) {
+    if notice.actions.is_empty() {
+        show(app, &notice.title, &notice.body);
+        return;
+    }
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        let (app, notice) = (app.clone(), notice.clone());
+        tauri::async_runtime::spawn(async move {
+            let answer = match native::ask(&notice).await {
+                Ok(answer) => answer,
+                Err(e) => {
+                    eprintln!("[notifications] Failed to notify {:?}: {}", notice.title, e);
+                    return;
+                }
+            };
+            let action = match answer {
+                native::Answer::Action(id) => Action::from_id(&id),
+                native::Answer::Clicked if notice.thread_id.is_some() => Some(Action::OpenThread),
+                native::Answer::Clicked => {
+                    deeplink::focus_window(&app);
+                    None
+                }
+                native::Answer::Dismissed => None,
+            };
+            let Some(action) = action else {
+                return;
+            };
+            if let Err(e) = perform(&app, action, notice).await {
+                AppError::from(e).emit(&app, "notification_action");
+            }
+        });
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    show(app, &notice.title, &notice.body);
+}
+
+/// Carry out a notice's button, from the OS notification or the UI.
+pub async fn perform(app: &AppHandle, action: Action, notice: Notice) -> Result<()> {
+    match action {
+        Action::OpenThread => {
+            let thread_id = notice.thread_id.ok_or_else(|| anyhow!("This notification has no thread"))?;
+            tray::mark_read(app, &thread_id);
+            deeplink::show(app, &Navigation::Thread { thread_id });
+        }
+        Action::Snooze => {
+            let until = chrono::Utc::now().timestamp_millis() + SNOOZE.as_millis() as i64;
+            dnd::snooze(&notice, until).await?;
+        }
+        Action::MarkDone => {
+            if let Some(card_id) = notice.card_id {
+                if let Some(completion) = db::call(move |conn| kanban::complete_card(conn, &card_id)).await? {
+                    completion.announce(app);
+                }
+                let _ = app.emit("kanban:refresh", ());
+            } else if let Some(dump_id) = notice.dump_id {
+                let id = dump_id.clone();
+                db::call(move |conn| db::update_brain_dump_status(conn, &id, "done")).await?;
+                let _ = app.emit("braindump:status_changed", serde_json::json!({ "id": dump_id, "status": "done" }));
+            } else {
+                return Err(anyhow!("This notification has nothing to mark done"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `text` on one line, cut to `SNIPPET_CHARS`.
+pub fn snippet(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= SNIPPET_CHARS {
+        return text;
+    }
+    let cut: String = text.chars().take(SNIPPET_CHARS - 1).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Notifications with buttons, straight through the platform's notification
+/// center; the notification plugin only does buttons on mobile.
+#[cfg(target_os = "macos")]
+mod native {
+    use super::{Notice, ANSWER_WAIT};
+    use anyhow::{anyhow, Result};
+    use mac_notification_sys::{MainButton, Notification, NotificationResponse};
+    use std::sync::Once;
+
+    pub enum Answer {
+        Action(String),
+        Clicked,
+        Dismissed,
+    }
+
+    static APPLICATION: Once = Once::new();
+
+    /// The framework only answers synchronously, so the wait gets a thread of
+    /// its own rather than one of the runtime's blocking ones, and is given up
+    /// on after `ANSWER_WAIT`.
+    pub async fn ask(notice: &Notice) -> Result<Answer> {
+        let notice = notice.clone();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(show_and_wait(&notice));
+        });
+        match tokio::time::timeout(ANSWER_WAIT, rx).await {
+            Ok(answer) => answer.map_err(|_| anyhow!("The notification went unanswered"))?,
+            Err(_) => Ok(Answer::Dismissed),
+        }
+    }
+
+    fn show_and_wait(notice: &Notice) -> Result<Answer> {
+        APPLICATION.call_once(|| {
+            let _ = mac_notification_sys::set_application("com.openclaw.chat");
+        });
+        let titles: Vec<&str> = notice.actions.iter().map(|a| a.title()).collect();
+        let button = match titles.as_slice() {
+            [only] => MainButton::SingleAction(*only),
+            _ => MainButton::DropdownActions("Actions", &titles),
+        };
+        let response = mac_notification_sys::send_notification(
+            &notice.title,
+            None,
+            &notice.body,
+            Some(Notification::new().main_button(button).close_button("Dismiss")),
+        )
+        .map_err(|e| anyhow!("{}", e))?;
+        Ok(match response {
+            NotificationResponse::ActionButton(title) => Answer::Action(title),
+            NotificationResponse::Click => Answer::Clicked,
+            _ => Answer::Dismissed,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod native {
+    use super::{Notice, ANSWER_WAIT};
+    use anyhow::Result;
+    use notify_rust::NotificationResponse;
+
+    pub enum Answer {
+        Action(String),
+        Clicked,
+        Dismissed,
+    }
+
+    pub async fn ask(notice: &Notice) -> Result<Answer> {
+        let mut notification = notify_rust::Notification::new();
+        notification
+            .appname("OpenClaw Chat")
+            .summary(&notice.title)
+            .body(&notice.body)
+            // "default" is a click on the notification itself
+            .action("default", "Open");
+        for action in &notice.actions {
+            notification.action(action.id(), action.title());
+        }
+        let handle = notification.show_async().await?;
+        let mut answer = Answer::Dismissed;
+        let waited = tokio::time::timeout(
+            ANSWER_WAIT,
+            handle.wait_for_action_async(|response| {
+                answer = match response {
+                    NotificationResponse::Default => Answer::Clicked,
+                    NotificationResponse::Action(id) => Answer::Action(id.clone()),
+                    _ => Answer::Dismissed,
+                };
+            }),
+        )
+        .await;
+        if waited.is_err() {
+            handle.close_async().await;
+        }
+        Ok(answer)
+    }
+}
@@ -1,3 +1,7 @@
+use crate::db::{self, KanbanItem};
+use crate::settings;
+use anyhow::{anyhow, Context, Result};
+use rusqlite::Connection;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
@@ -6,39 +10,119 @@ pub struct ObsidianProject {
     pub description: Option<String>,
     pub color: String,
     pub obsidian_source: String, // relative path for dedup
+    pub mtime: Option<i64>,
+    pub status: Option<String>,
+    pub tags: Vec<String>,
+    pub priority: i32,
+    pub due_at: Option<i64>,
+    /// Raw `[[WikiLink]]` targets found in the note.
+    pub links: Vec<String>,
 }
 
-/// Scan the Obsidian vault's active projects directory.
-pub fn parse_vault(active_path: &Path) -> Vec<ObsidianProject> {
-    let mut projects = Vec::new();
+/// Where project notes live in the vault and how categories map to colors.
+/// Stored as JSON under the `obsidian_layout` setting; defaults to
+/// `10 Projects/Active` with Business/Work subfolders.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct VaultLayout {
+    pub projects_root: String,
+    pub root_color: String,
+    pub categories: Vec<CategoryFolder>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CategoryFolder {
+    pub folder: String,
+    pub color: String,
+}
+
+impl Default for VaultLayout {
+    fn default() -> Self {
+        Self {
+            projects_root: "10 Projects/Active".to_string(),
+            root_color: "#7c3aed".to_string(),
+            categories: vec![
+                CategoryFolder {
+                    folder: "Business".to_string(),
+                    color: "#059669".to_string(),
+                },
+                CategoryFolder {
+                    folder: "Work".to_string(),
+                    color: "#2563eb".to_string(),
+                },
+            ],
+        }
+    }
+}
+
+pub const LAYOUT_SETTING: &str = "obsidian_layout";
+
+impl VaultLayout {
+    /// Configured layout, falling back to the default when unset or malformed.
+    pub fn load(conn: &Connection) -> Self {
+        db::get_setting(conn, LAYOUT_SETTING)
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, conn: &Connection) -> Result<()> {
+        if self.projects_root.trim().is_empty() {
+            return Err(anyhow!("Projects root can't be empty"));
+        }
+        if self.categories.iter().any(|c| c.folder.contains('/') || c.folder.trim().is_empty()) {
+            return Err(anyhow!("Category folders must be single, non-empty folder names"));
+        }
+        settings::set(conn, LAYOUT_SETTING, &serde_json::to_value(self)?)
+    }
+
+    /// Absolute directory holding active project notes.
+    pub fn active_dir(&self, vault_path: &str) -> PathBuf {
+        let mut dir = PathBuf::from(vault_path);
+        for part in self.projects_root.split('/').filter(|p| !p.is_empty()) {
+            dir.push(part);
+        }
+        dir
+    }
 
-    // Business/ → green
-    let business = active_path.join("Business");
-    if business.is_dir() {
-        scan_dir(&business, "Business", "#059669", &mut projects);
+    fn category_color(&self, folder: &str) -> Option<&str> {
+        self.categories
+            .iter()
+            .find(|c| c.folder == folder)
+            .map(|c| c.color.as_str())
     }
+}
+
+/// File modification time in ms since epoch.
+pub fn file_mtime_ms(path: &Path) -> Option<i64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let dur = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(dur.as_millis() as i64)
+}
+
+/// Scan the Obsidian vault's active projects directory.
+pub fn parse_vault(active_path: &Path, layout: &VaultLayout) -> Vec<ObsidianProject> {
+    let mut projects = Vec::new();
 
-    // Work/ → blue
-    let work = active_path.join("Work");
-    if work.is_dir() {
-        scan_dir(&work, "Work", "#2563eb", &mut projects);
+    // Category subfolders → their configured color
+    for category in &layout.categories {
+        let dir = active_path.join(&category.folder);
+        if dir.is_dir() {
+            scan_dir(&dir, &category.folder, &category.color, &mut projects);
+        }
     }
 
-    // Top-level .md files (Personal) → purple
+    // Top-level .md files (Personal) → root color
     if let Ok(entries) = std::fs::read_dir(active_path) {
         for entry in entries.flatten() {
             let path = entry.path();
-            if path.is_file() && path.extension().map(|e| e == "md").unwrap_or(false) {
-                let fname = path.file_name().unwrap_or_default().to_string_lossy();
-                if fname == "README.md" || fname == "Projects.md" {
-                    continue;
-                }
+            if path.is_file() && is_project_note(&path) {
                 let rel = path
                     .strip_prefix(active_path)
                     .unwrap_or(&path)
                     .to_string_lossy()
                     .to_string();
-                if let Some(p) = parse_file(&path, "#7c3aed", &rel) {
+                if let Some(p) = parse_file(&path, &layout.root_color, &rel) {
                     projects.push(p);
                 }
             }
@@ -48,17 +132,232 @@ pub fn parse_vault(active_path: &Path) -> Vec<ObsidianProject> {
     projects
 }
 
+fn is_project_note(path: &Path) -> bool {
+    if path.extension().map(|e| e != "md").unwrap_or(true) {
+        return false;
+    }
+    let fname = path.file_name().unwrap_or_default().to_string_lossy();
+    fname != "README.md" && fname != "Projects.md"
+}
+
+/// Parse a single note under the active projects directory, deriving its
+/// category color and relative source path from where it sits.
+pub fn parse_note(active_path: &Path, path: &Path, layout: &VaultLayout) -> Option<ObsidianProject> {
+    if !path.is_file() || !is_project_note(path) {
+        return None;
+    }
+    let rel = path.strip_prefix(active_path).ok()?;
+    let parts: Vec<String> = rel
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    match parts.as_slice() {
+        [_file] => parse_file(path, &layout.root_color, &parts.join("/")),
+        [category, _file] => parse_file(path, layout.category_color(category)?, &parts.join("/")),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct SyncResult {
+    pub created: u32,
+    pub updated: u32,
+    pub skipped: u32,
+    pub archived: Vec<String>,
+    pub restored: Vec<String>,
+    pub tasks_created: u32,
+    pub tasks_completed: u32,
+    /// Per-note breakdown of everything the sync changed (or would change, in preview).
+    pub changes: Vec<SyncChange>,
+    pub errors: Vec<String>,
+    /// Cards task sync moved to done, announced by the caller once committed.
+    #[serde(skip)]
+    pub completions: Vec<crate::kanban::Completion>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct SyncChange {
+    pub action: &'static str, // 'create' | 'update' | 'restore' | 'archive'
+    pub name: String,
+    pub obsidian_source: String,
+}
+
+/// Full sync of parsed notes: projects, links, archive detection, then tasks.
+pub fn sync_vault(conn: &Connection, active_path: &Path, projects: &[ObsidianProject]) -> SyncResult {
+    let mut result = upsert_projects(conn, active_path, projects);
+    match sync_note_tasks(conn, active_path, projects) {
+        Ok(tasks) => {
+            result.tasks_created = tasks.created;
+            result.tasks_completed = tasks.completed;
+            result.completions = tasks.completions;
+        }
+        Err(e) => result.errors.push(format!("Task sync: {}", e)),
+    }
+    result
+}
+
+/// What `sync_vault` would do, without keeping any of it.
+///
+/// Runs the real sync inside a transaction that is always rolled back, so the
+/// preview can't drift from the actual sync logic.
+pub fn preview_sync(conn: &Connection, active_path: &Path, projects: &[ObsidianProject]) -> Result<SyncResult> {
+    let tx = conn.unchecked_transaction()?;
+    let result = sync_vault(&tx, active_path, projects);
+    tx.rollback()?;
+    Ok(result)
+}
+
+/// Upsert parsed notes into the projects table, then archive linked projects
+/// whose notes are no longer in the active folder.
+pub fn upsert_projects(conn: &Connection, active_path: &Path, projects: &[ObsidianProject]) -> SyncResult {
+    let mut result = SyncResult::default();
+    for p in projects {
+        let action = match upsert_project(conn, p) {
+            Ok(db::UpsertResult::Created) => {
+                result.created += 1;
+                "create"
+            }
+            Ok(db::UpsertResult::Updated) => {
+                result.updated += 1;
+                "update"
+            }
+            Ok(db::UpsertResult::Restored) => {
+                result.restored.push(p.name.clone());
+                "restore"
+            }
+            Ok(db::UpsertResult::Skipped) => {
+                result.skipped += 1;
+                continue;
+            }
+            Err(e) => {
+                result.errors.push(format!("{}: {}", p.name, e));
+                continue;
+            }
+        };
+        result.changes.push(SyncChange {
+            action,
+            name: p.name.clone(),
+            obsidian_source: p.obsidian_source.clone(),
+        });
+    }
+    // Second pass so links can point at notes created earlier in this sync
+    for p in projects {
+        if let Err(e) = sync_project_links(conn, p) {
+            result.errors.push(format!("{} links: {}", p.name, e));
+        }
+    }
+    match archive_missing(conn, active_path, projects) {
+        Ok(archived) => {
+            for (name, source) in archived {
+                result.changes.push(SyncChange {
+                    action: "archive",
+                    name: name.clone(),
+                    obsidian_source: source,
+                });
+                result.archived.push(name);
+            }
+        }
+        Err(e) => result.errors.push(format!("Archive detection: {}", e)),
+    }
+    result
+}
+
+/// Record which synced projects a note links to, resolving `[[Target]]` by note
+/// path, file name or project name. Unresolved links are ignored.
+pub fn sync_project_links(conn: &Connection, p: &ObsidianProject) -> Result<()> {
+    let Some(source_id) = db::get_project_id_by_obsidian_source(conn, &p.obsidian_source)? else {
+        return Ok(());
+    };
+    let projects = db::list_projects(conn, true)?;
+    let mut targets: Vec<String> = Vec::new();
+    for link in &p.links {
+        let link = link.to_lowercase();
+        let found = projects.iter().find(|project| {
+            let source = project.obsidian_source.as_deref().map(|s| s.to_lowercase());
+            let source = source.as_deref().map(|s| s.trim_end_matches(".md"));
+            let stem = source.map(|s| s.rsplit('/').next().unwrap_or(s));
+            source == Some(link.as_str())
+                || stem == Some(link.as_str())
+                || project.name.to_lowercase() == link
+        });
+        if let Some(target) = found {
+            if target.id != source_id && !targets.contains(&target.id) {
+                targets.push(target.id.clone());
+            }
+        }
+    }
+    db::replace_entity_links(conn, "project", &source_id, "project", &targets)
+}
+
+/// Mark projects archived when their previously-seen note is gone from the
+/// active folder. A note missing from `seen` that is still on disk only failed
+/// to parse, and is left alone. Returns `(name, obsidian_source)` for each
+/// project archived.
+pub fn archive_missing(conn: &Connection, active_path: &Path, seen: &[ObsidianProject]) -> Result<Vec<(String, String)>> {
+    // An unmounted or moved vault would look like every note was deleted
+    if !active_path.is_dir() {
+        return Ok(Vec::new());
+    }
+    let seen: std::collections::HashSet<&str> = seen.iter().map(|p| p.obsidian_source.as_str()).collect();
+    let mut archived = Vec::new();
+    for project in db::list_projects(conn, false)? {
+        let Some(source) = project.obsidian_source.as_deref() else {
+            continue;
+        };
+        if seen.contains(source) {
+            continue;
+        }
+        let gone = matches!(
+            std::fs::metadata(active_path.join(source)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound
+        );
+        if gone {
+            if let Some(name) = db::archive_obsidian_project(conn, source)? {
+                archived.push((name, source.to_string()));
+            }
+        }
+    }
+    Ok(archived)
+}
+
+/// Relative source path for a note under the active folder, even if it no longer exists.
+pub fn note_source(active_path: &Path, path: &Path) -> Option<String> {
+    if path.extension().map(|e| e != "md").unwrap_or(true) {
+        return None;
+    }
+    let rel = path.strip_prefix(active_path).ok()?;
+    Some(
+        rel.components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+pub fn upsert_project(conn: &Connection, p: &ObsidianProject) -> Result<db::UpsertResult> {
+    db::upsert_obsidian_project(
+        conn,
+        &db::ProjectNote {
+            name: &p.name,
+            description: p.description.as_deref(),
+            color: &p.color,
+            obsidian_source: &p.obsidian_source,
+            mtime: p.mtime,
+            status: p.status.as_deref(),
+            tags: &p.tags,
+            priority: p.priority,
+            due_at: p.due_at,
+        },
+    )
+}
+
 fn scan_dir(dir: &Path, _category: &str, color: &str, out: &mut Vec<ObsidianProject>) {
     let Ok(entries) = std::fs::read_dir(dir) else {
         return;
     };
     for entry in entries.flatten() {
         let path = entry.path();
-        if path.is_file() && path.extension().map(|e| e == "md").unwrap_or(false) {
-            let fname = path.file_name().unwrap_or_default().to_string_lossy();
-            if fname == "README.md" || fname == "Projects.md" {
-                continue;
-            }
+        if path.is_file() && is_project_note(&path) {
             // Relative path from active_path's parent (includes Business/ or Work/)
             let rel = format!(
                 "{}/{}",
@@ -79,11 +378,16 @@ fn parse_file(path: &Path, color: &str, rel: &str) -> Option<ObsidianProject> {
     // Parse frontmatter
     let (frontmatter, body_start) = parse_frontmatter(&lines);
 
+    let text = |key: &str| {
+        frontmatter
+            .iter()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, v)| v.as_text())
+    };
+
     // Name: frontmatter title → first # heading → filename stem
-    let name = frontmatter
-        .iter()
-        .find(|(k, _)| k == "title")
-        .map(|(_, v)| v.clone())
+    let name = text("title")
+        .map(str::to_string)
         .or_else(|| {
             lines[body_start..]
                 .iter()
@@ -97,31 +401,179 @@ fn parse_file(path: &Path, color: &str, rel: &str) -> Option<ObsidianProject> {
                 .to_string()
         });
 
-    // Description: ## Objective / ## 🎯 section → **Concept:** value → first paragraph
-    let description = extract_description(&lines[body_start..]);
+    // Description: frontmatter description → ## Objective / ## 🎯 section → **Concept:** value → first paragraph
+    let description = text("description")
+        .map(str::to_string)
+        .or_else(|| extract_description(&lines[body_start..]));
+
+    // Status: explicit `status:` wins, `archived: true` is shorthand
+    let archived = frontmatter
+        .iter()
+        .any(|(k, v)| k == "archived" && *v == FrontValue::Bool(true));
+    let status = text("status")
+        .map(|s| s.to_lowercase())
+        .or_else(|| archived.then(|| "archived".to_string()));
+
+    let tags = frontmatter
+        .iter()
+        .find(|(k, _)| k == "tags")
+        .map(|(_, v)| {
+            v.as_list()
+                .into_iter()
+                .map(|t| t.trim_start_matches('#').to_string())
+                .filter(|t| !t.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let priority = text("priority").and_then(parse_priority).unwrap_or(0);
+    let due_at = ["due", "due_date", "deadline"]
+        .iter()
+        .find_map(|key| text(key))
+        .and_then(parse_date);
 
     Some(ObsidianProject {
         name: strip_wiki_links(&name),
         description: description.map(|d| strip_wiki_links(&d)),
         color: color.to_string(),
         obsidian_source: rel.to_string(),
+        mtime: file_mtime_ms(path),
+        status,
+        tags,
+        priority,
+        due_at,
+        links: extract_wiki_links(&content),
     })
 }
 
-fn parse_frontmatter(lines: &[&str]) -> (Vec<(String, String)>, usize) {
-    let mut pairs = Vec::new();
+/// A frontmatter value: the subset of YAML project notes actually use.
+#[derive(Debug, Clone, PartialEq)]
+enum FrontValue {
+    Text(String),
+    Bool(bool),
+    List(Vec<String>),
+}
+
+impl FrontValue {
+    fn parse(raw: &str) -> Self {
+        let raw = raw.trim();
+        // `[[Note]]` is a wiki-link scalar, not a flow list
+        let inline_list = raw.strip_prefix('[').filter(|r| !r.starts_with('['));
+        if let Some(inner) = inline_list.and_then(|r| r.strip_suffix(']')) {
+            return FrontValue::List(
+                inner
+                    .split(',')
+                    .map(unquote)
+                    .filter(|v| !v.is_empty())
+                    .collect(),
+            );
+        }
+        match raw.to_lowercase().as_str() {
+            "true" => FrontValue::Bool(true),
+            "false" => FrontValue::Bool(false),
+            _ => FrontValue::Text(unquote(raw)),
+        }
+    }
+
+    fn as_text(&self) -> Option<&str> {
+        match self {
+            FrontValue::Text(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    /// Lists as-is; a plain string is read as comma/space separated (`tags: a, b`).
+    fn as_list(&self) -> Vec<String> {
+        match self {
+            FrontValue::List(items) => items.clone(),
+            FrontValue::Text(t) => t
+                .split([',', ' '])
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect(),
+            FrontValue::Bool(_) => Vec::new(),
+        }
+    }
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+        .to_string()
+}
+
+/// Numbers on the kanban 0–4 scale, or the usual words for them.
+fn parse_priority(value: &str) -> Option<i32> {
+    let value = value.trim().to_lowercase();
+    if let Ok(n) = value.parse::<i32>() {
+        return Some(n.clamp(0, 4));
+    }
+    match value.as_str() {
+        "none" => Some(0),
+        "low" => Some(1),
+        "medium" | "med" | "normal" => Some(2),
+        "high" => Some(3),
+        "urgent" | "critical" => Some(4),
+        _ => None,
+    }
+}
+
+/// `YYYY-MM-DD` (local midnight), `YYYY-MM-DD HH:MM` / `YYYY-MM-DDTHH:MM[:SS]`
+/// (local time), or RFC 3339. Returns epoch millis.
+pub fn parse_date(value: &str) -> Option<i64> {
+    use chrono::{NaiveDate, NaiveDateTime, TimeZone};
+    let value = value.trim();
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(dt.timestamp_millis());
+    }
+    let naive = ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })?;
+    chrono::Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.timestamp_millis())
+}
+
+fn parse_frontmatter(lines: &[&str]) -> (Vec<(String, FrontValue)>, usize) {
+    let mut pairs: Vec<(String, FrontValue)> = Vec::new();
     if lines.first().map(|l| l.trim()) != Some("---") {
         return (pairs, 0);
     }
+    // Key whose value is an indented `- item` block on the following lines
+    let mut list_key: Option<String> = None;
     for (i, line) in lines.iter().enumerate().skip(1) {
         if line.trim() == "---" {
             return (pairs, i + 1);
         }
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let (Some(key), Some(item)) = (&list_key, trimmed.strip_prefix('-')) {
+            let item = unquote(item);
+            match pairs.last_mut() {
+                Some((k, FrontValue::List(items))) if k == key => items.push(item),
+                _ => pairs.push((key.clone(), FrontValue::List(vec![item]))),
+            }
+            continue;
+        }
+        list_key = None;
         if let Some((key, val)) = line.split_once(':') {
             let key = key.trim().to_lowercase();
-            let val = val.trim().trim_matches('"').to_string();
-            if !val.is_empty() {
-                pairs.push((key, val));
+            if val.trim().is_empty() {
+                list_key = Some(key);
+            } else {
+                pairs.push((key, FrontValue::parse(val)));
             }
         }
     }
@@ -199,6 +651,25 @@ fn truncate(s: &str, max: usize) -> String {
     }
 }
 
+/// Targets of every `[[Target|alias]]` / `[[Target#Heading]]` link, minus embeds' `!`.
+fn extract_wiki_links(content: &str) -> Vec<String> {
+    let mut links = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("]]") else {
+            break;
+        };
+        let inner = &rest[..end];
+        rest = &rest[end + 2..];
+        let target = inner.split(['|', '#', '^']).next().unwrap_or("").trim();
+        if !target.is_empty() && !links.iter().any(|l| l == target) {
+            links.push(target.to_string());
+        }
+    }
+    links
+}
+
 fn strip_wiki_links(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
     let mut chars = s.chars().peekable();
@@ -225,3 +696,659 @@ fn strip_wiki_links(s: &str) -> String {
     }
     result
 }
+
+// ── Task line sync ───────────────────────────────────────────────────────────
+
+pub const TASK_SYNC_SETTING: &str = "obsidian_task_sync";
+
+fn task_sync_enabled(conn: &Connection) -> bool {
+    db::get_setting(conn, TASK_SYNC_SETTING).ok().flatten().as_deref() == Some("true")
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct TaskSyncResult {
+    pub created: u32,
+    pub completed: u32,
+    #[serde(skip)]
+    pub completions: Vec<crate::kanban::Completion>,
+}
+
+/// Mirror `- [ ]` tasks from synced project notes as kanban cards
+/// (source_type `obsidian`, source_id `<note>:<line>`). Tasks checked off in
+/// the vault move their card to done. No-op unless task sync is enabled.
+pub fn sync_note_tasks(
+    conn: &Connection,
+    active_path: &Path,
+    projects: &[ObsidianProject],
+) -> Result<TaskSyncResult> {
+    let mut result = TaskSyncResult::default();
+    if !task_sync_enabled(conn) {
+        return Ok(result);
+    }
+
+    for p in projects {
+        let Some(project_id) = db::get_project_id_by_obsidian_source(conn, &p.obsidian_source)? else {
+            continue;
+        };
+        let Ok(content) = std::fs::read_to_string(active_path.join(&p.obsidian_source)) else {
+            continue;
+        };
+
+        let tasks: Vec<_> = crate::kanban::parse_markdown_tasks(&content)
+            .into_iter()
+            .map(|task| {
+                let source_id = format!("{}:{}", p.obsidian_source, task.line + 1);
+                (strip_wiki_links(&task.title), source_id, task.done)
+            })
+            .collect();
+        // A task keeps the card recorded at its line; cards whose line no
+        // longer holds them follow their title, so same-titled tasks in one
+        // note stay separate cards
+        let mut cards = db::list_obsidian_task_items(conn, &p.obsidian_source)?;
+        let dismissed = db::list_dismissed_obsidian_tasks(conn, &p.obsidian_source)?;
+        let mut matched: Vec<Option<KanbanItem>> = tasks
+            .iter()
+            .map(|(title, source_id, _)| {
+                let i = cards
+                    .iter()
+                    .position(|c| c.source_id.as_deref() == Some(source_id.as_str()) && c.title == *title)?;
+                Some(cards.remove(i))
+            })
+            .collect();
+        for ((title, _, _), card) in tasks.iter().zip(matched.iter_mut()) {
+            if card.is_none() {
+                if let Some(i) = cards.iter().position(|c| c.title == *title) {
+                    *card = Some(cards.remove(i));
+                }
+            }
+        }
+
+        for ((title, source_id, done), card) in tasks.into_iter().zip(matched) {
+            match card {
+                Some(item) => {
+                    if item.source_id.as_deref() != Some(source_id.as_str()) {
+                        db::set_kanban_source_id(conn, &item.id, &source_id)?;
+                    }
+                    if done {
+                        if let Some(completion) = crate::kanban::complete_card(conn, &item.id)? {
+                            result.completions.push(completion);
+                            result.completed += 1;
+                        }
+                    }
+                }
+                // Only open tasks become new cards; already-checked ones are
+                // history, and ones whose card was deleted stay gone
+                None if !done && !dismissed.contains(&title) => {
+                    crate::kanban::create_sourced_item(
+                        conn,
+                        title,
+                        Some(project_id.clone()),
+                        "obsidian",
+                        source_id,
+                    )?;
+                    result.created += 1;
+                }
+                None => {}
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Tick the originating `- [ ]` line in the vault when an Obsidian-sourced card is done.
+pub fn complete_task_in_vault(conn: &Connection, item_id: &str) -> Result<bool> {
+    if !task_sync_enabled(conn) {
+        return Ok(false);
+    }
+    let Some(item) = db::get_kanban_item(conn, item_id)? else {
+        return Ok(false);
+    };
+    if item.source_type != "obsidian" {
+        return Ok(false);
+    }
+    let Some((note, line)) = item.source_id.as_deref().and_then(|s| s.rsplit_once(':')) else {
+        return Ok(false);
+    };
+    let Some(vault_path) = db::get_setting(conn, "obsidian_vault_path")? else {
+        return Ok(false);
+    };
+    let path = VaultLayout::load(conn).active_dir(&vault_path).join(note);
+    let content = std::fs::read_to_string(&path)?;
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+    // Only a line whose task text is exactly the card's title is ticked
+    let tasks: Vec<_> = crate::kanban::parse_markdown_tasks(&content)
+        .into_iter()
+        .filter(|task| strip_wiki_links(&task.title) == item.title)
+        .collect();
+    let hint = line.parse::<usize>().ok().and_then(|n| n.checked_sub(1));
+    // Already ticked, e.g. the card was completed by syncing that tick
+    if tasks.iter().any(|task| task.done && Some(task.line) == hint) {
+        return Ok(false);
+    }
+    let open: Vec<usize> = tasks.iter().filter(|task| !task.done).map(|task| task.line).collect();
+    // Prefer the recorded line; fall back to the only such task in case the
+    // note was edited, and leave the note alone when that's ambiguous
+    let target = match hint {
+        Some(i) if open.contains(&i) => Some(i),
+        _ if open.len() == 1 => Some(open[0]),
+        _ => None,
+    };
+    let Some(i) = target else {
+        return Ok(false);
+    };
+    let Some(line) = lines.get_mut(i) else {
+        return Ok(false);
+    };
+
+    *line = line.replacen("[ ]", "[x]", 1);
+    let mut out = lines.join("\n");
+    if content.ends_with('\n') {
+        out.push('\n');
+    }
+    std::fs::write(&path, out)?;
+    Ok(true)
+}
+
+// ── Thread export ────────────────────────────────────────────────────────────
+
+pub const DEFAULT_CHAT_EXPORT_FOLDER: &str = "30 Resources/AI Chats";
+
+/// Make a string safe to use as a vault file or folder name.
+pub fn sanitize_filename(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let trimmed = cleaned.trim().trim_matches('.').trim();
+    if trimmed.is_empty() {
+        "Untitled".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Markdown transcript of a thread, with frontmatter pointing back at the app.
+pub fn render_transcript(
+    thread: &db::Thread,
+    project: Option<&db::Project>,
+    messages: &[crate::openclaw::ChatMessage],
+) -> String {
+    let now = chrono::Local::now();
+    let mut out = String::from("---\n");
+    out.push_str(&format!("title: {}\n", yaml_scalar(&thread.name)));
+    if let Some(p) = project {
+        out.push_str(&format!("project: {}\n", yaml_scalar(&format!("[[{}]]", p.name))));
+        out.push_str(&format!("project_id: {}\n", p.id));
+    }
+    out.push_str(&format!("thread_id: {}\n", thread.id));
+    out.push_str(&format!("session_id: {}\n", thread.session_id));
+    out.push_str(&format!("agent: {}\n", thread.agent_id));
+    out.push_str(&format!("exported: {}\n", now.format("%Y-%m-%d %H:%M")));
+    out.push_str("tags:\n  - ai-chat\n---\n\n");
+    out.push_str(&format!("# {}\n", thread.name));
+
+    for msg in messages {
+        let speaker = if msg.role == "user" { "You" } else { "Assistant" };
+        out.push_str(&format!("\n## {}\n\n{}\n", speaker, msg.content.trim_end()));
+    }
+    out
+}
+
+// ── Daily notes ──────────────────────────────────────────────────────────────
+
+pub const DAILY_NOTES_SETTING: &str = "obsidian_daily_notes";
+pub const DEFAULT_DAILY_HEADING: &str = "## Brain Dumps";
+
+/// Insert `bullet` at the end of the section under `heading`, adding the
+/// heading at the bottom of the note if it isn't there yet.
+pub fn append_under_heading(content: &str, heading: &str, bullet: &str) -> String {
+    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+    let level = heading.chars().take_while(|c| *c == '#').count();
+    let start = lines.iter().position(|l| l.trim() == heading.trim());
+
+    match start {
+        Some(start) => {
+            // Section ends at the next heading of the same or higher level
+            let mut end = lines[start + 1..]
+                .iter()
+                .position(|l| {
+                    let hashes = l.chars().take_while(|c| *c == '#').count();
+                    hashes > 0 && hashes <= level.max(1) && l[hashes..].starts_with(' ')
+                })
+                .map(|i| start + 1 + i)
+                .unwrap_or(lines.len());
+            while end > start + 1 && lines[end - 1].trim().is_empty() {
+                end -= 1;
+            }
+            lines.insert(end, bullet.to_string());
+        }
+        None => {
+            while lines.last().map(|l| l.trim().is_empty()).unwrap_or(false) {
+                lines.pop();
+            }
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(heading.to_string());
+            lines.push(bullet.to_string());
+        }
+    }
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Append a brain dump as a bullet in today's daily note.
+///
+/// Guarded by the `obsidian_daily_notes` setting. The note lives at
+/// `<obsidian_daily_folder>/YYYY-MM-DD.md`; when missing it's created from
+/// `obsidian_daily_template` (vault-relative, `{{date}}`/`{{title}}` filled in).
+pub fn append_brain_dump_to_daily(conn: &Connection, dump: &db::BrainDump) -> Result<Option<PathBuf>> {
+    if db::get_setting(conn, DAILY_NOTES_SETTING)?.as_deref() != Some("true") {
+        return Ok(None);
+    }
+    let Some(vault_path) = db::get_setting(conn, "obsidian_vault_path")? else {
+        return Ok(None);
+    };
+    let vault = PathBuf::from(vault_path);
+    let folder = db::get_setting(conn, "obsidian_daily_folder")?.unwrap_or_default();
+    let heading = db::get_setting(conn, "obsidian_daily_heading")?
+        .filter(|h| !h.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_DAILY_HEADING.to_string());
+
+    let created = chrono::DateTime::from_timestamp_millis(dump.created_at)
+        .map(|t| t.with_timezone(&chrono::Local))
+        .unwrap_or_else(chrono::Local::now);
+    let date = created.format("%Y-%m-%d").to_string();
+
+    let dir = vault.join(folder.trim_matches('/'));
+    let path = dir.join(format!("{}.md", date));
+    let existing = if path.exists() {
+        std::fs::read_to_string(&path)?
+    } else {
+        match db::get_setting(conn, "obsidian_daily_template")? {
+            Some(template) if !template.trim().is_empty() => {
+                let mut template_path = vault.join(template.trim_matches('/'));
+                if template_path.extension().is_none() {
+                    template_path.set_extension("md");
+                }
+                std::fs::read_to_string(&template_path)
+                    .with_context(|| format!("Reading daily template {}", template_path.display()))?
+                    .replace("{{date}}", &date)
+                    .replace("{{title}}", &date)
+            }
+            _ => String::new(),
+        }
+    };
+
+    let text = dump.content.split_whitespace().collect::<Vec<_>>().join(" ");
+    let bullet = format!("- {} {}", created.format("%H:%M"), text);
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(&path, append_under_heading(&existing, &heading, &bullet))?;
+    Ok(Some(path))
+}
+
+// ── Kanban export ────────────────────────────────────────────────────────────
+
+/// `single` (one board file) or `per_project`; anything else disables the export.
+pub const KANBAN_EXPORT_SETTING: &str = "obsidian_kanban_export";
+pub const DEFAULT_KANBAN_EXPORT_FOLDER: &str = "30 Resources/Kanban";
+/// JSON list of the board files the last export wrote, so ones that no
+/// longer match a project can be removed without touching the user's own notes.
+const KANBAN_EXPORT_FILES_SETTING: &str = "obsidian_kanban_export_files";
+
+/// Regenerate the Dataview-friendly board file(s) in the vault. Files are only
+/// rewritten when their content changes, and boards from an earlier export
+/// whose project was renamed, archived or deleted are removed. Returns the
+/// paths written.
+pub fn export_kanban_to_vault(conn: &Connection) -> Result<Vec<PathBuf>> {
+    let mode = db::get_setting(conn, KANBAN_EXPORT_SETTING)?.unwrap_or_default();
+    if mode != "single" && mode != "per_project" {
+        return Ok(Vec::new());
+    }
+    let Some(vault_path) = db::get_setting(conn, "obsidian_vault_path")? else {
+        return Ok(Vec::new());
+    };
+    let folder = db::get_setting(conn, "obsidian_kanban_export_folder")?
+        .unwrap_or_else(|| DEFAULT_KANBAN_EXPORT_FOLDER.to_string());
+    let dir = PathBuf::from(vault_path).join(folder.trim_matches('/'));
+    std::fs::create_dir_all(&dir)?;
+
+    let boards: Vec<(String, Option<String>)> = if mode == "single" {
+        vec![("Kanban Board".to_string(), None)]
+    } else {
+        db::list_projects(conn, false)?
+            .into_iter()
+            .map(|p| (format!("{} Board", sanitize_filename(&p.name)), Some(p.id)))
+            .collect()
+    };
+
+    let mut written = Vec::new();
+    let mut files = Vec::new();
+    for (file, project_id) in boards {
+        let content = crate::kanban::export_board(conn, project_id.as_deref(), "dataview")?;
+        let file = format!("{}.md", file);
+        let path = dir.join(&file);
+        files.push(file);
+        // Ignore the timestamp line so an unchanged board doesn't churn the vault
+        let unchanged = std::fs::read_to_string(&path)
+            .map(|old| strip_updated_line(&old) == strip_updated_line(&content))
+            .unwrap_or(false);
+        if !unchanged {
+            std::fs::write(&path, content)?;
+            written.push(path);
+        }
+    }
+
+    let previous: Vec<String> = db::get_setting(conn, KANBAN_EXPORT_FILES_SETTING)?
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default();
+    for stale in previous.iter().filter(|f| !files.contains(f)) {
+        match std::fs::remove_file(dir.join(stale)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+    db::set_setting(conn, KANBAN_EXPORT_FILES_SETTING, &serde_json::to_string(&files)?)?;
+    Ok(written)
+}
+
+fn strip_updated_line(content: &str) -> String {
+    content
+        .lines()
+        .filter(|l| !l.starts_with("updated: "))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// ── Weekly review ────────────────────────────────────────────────────────────
+
+pub const WEEKLY_REVIEW_SETTING: &str = "obsidian_weekly_review";
+pub const DEFAULT_WEEKLY_REVIEW_FOLDER: &str = "30 Resources/Weekly Reviews";
+
+/// "Weekly Review YYYY-WW" note: narrative first, then the raw numbers.
+pub fn render_weekly_review(
+    week: &str,
+    start: chrono::NaiveDate,
+    stats: &db::WeeklyStats,
+    narrative: Option<&str>,
+) -> String {
+    let mut out = String::from("---\n");
+    out.push_str(&format!("title: Weekly Review {}\n", week));
+    out.push_str(&format!("week: {}\n", week));
+    out.push_str(&format!("week_start: {}\n", start.format("%Y-%m-%d")));
+    out.push_str(&format!("threads_active: {}\n", stats.threads_active.len()));
+    out.push_str(&format!("dumps_captured: {}\n", stats.dumps_captured));
+    out.push_str(&format!("dumps_closed: {}\n", stats.dumps_closed));
+    out.push_str(&format!("cards_created: {}\n", stats.cards_created));
+    out.push_str(&format!("cards_completed: {}\n", stats.cards_completed.len()));
+    out.push_str("tags:\n  - weekly-review\n---\n\n");
+    out.push_str(&format!("# Weekly Review {}\n", week));
+
+    if let Some(narrative) = narrative.map(str::trim).filter(|n| !n.is_empty()) {
+        out.push_str(&format!("\n## Summary\n\n{}\n", narrative));
+    }
+
+    out.push_str("\n## Numbers\n\n");
+    out.push_str(&format!("- Threads active: {}\n", stats.threads_active.len()));
+    out.push_str(&format!(
+        "- Brain dumps: {} captured, {} closed\n",
+        stats.dumps_captured, stats.dumps_closed
+    ));
+    out.push_str(&format!(
+        "- Kanban: {} cards added, {} completed\n",
+        stats.cards_created,
+        stats.cards_completed.len()
+    ));
+
+    if !stats.cards_completed.is_empty() {
+        out.push_str("\n## Completed\n\n");
+        for title in &stats.cards_completed {
+            out.push_str(&format!("- [x] {}\n", title));
+        }
+    }
+    if !stats.threads_active.is_empty() {
+        out.push_str("\n## Conversations\n\n");
+        for name in &stats.threads_active {
+            out.push_str(&format!("- {}\n", name));
+        }
+    }
+    out
+}
+
+// ── Retrospective ────────────────────────────────────────────────────────────
+
+pub const RETROSPECTIVE_SETTING: &str = "obsidian_retrospective";
+pub const DEFAULT_RETROSPECTIVE_FOLDER: &str = "30 Resources/Retrospectives";
+
+/// "Retrospective YYYY-WW" note: narrative first, then a section per project.
+pub fn render_retrospective(retro: &db::Retrospective) -> String {
+    let mut out = String::from("---\n");
+    out.push_str(&format!("title: Retrospective {}\n", retro.week));
+    out.push_str(&format!("week: {}\n", retro.week));
+    out.push_str(&format!("week_start: {}\n", retro.week_start));
+    out.push_str("tags:\n  - retrospective\n---\n\n");
+    out.push_str(&format!("# Retrospective {}\n", retro.week));
+
+    if let Some(narrative) = retro.narrative.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        out.push_str(&format!("\n{}\n", narrative));
+    }
+    if retro.projects.is_empty() {
+        out.push_str("\nNothing happened this week.\n");
+    }
+    for project in &retro.projects {
+        out.push_str(&format!("\n## {}\n\n", project.project));
+        out.push_str(&format!(
+            "- {} messages in {} threads\n",
+            project.messages,
+            project.threads.len()
+        ));
+        for title in &project.cards_completed {
+            out.push_str(&format!("- [x] {}\n", title));
+        }
+        for content in &project.dumps_closed {
+            out.push_str(&format!("- Closed: {}\n", content.split_whitespace().collect::<Vec<_>>().join(" ")));
+        }
+    }
+    out
+}
+
+// ── Note creation ────────────────────────────────────────────────────────────
+
+/// Starter note for a project created in the app.
+pub fn render_project_note(project: &db::Project) -> String {
+    let mut out = String::from("---\n");
+    out.push_str(&format!("title: {}\n", yaml_scalar(&project.name)));
+    if let Some(desc) = project.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        out.push_str(&format!("description: {}\n", yaml_scalar(desc)));
+    }
+    out.push_str(&format!("status: {}\n", project.status));
+    if !project.tags.is_empty() {
+        out.push_str("tags:\n");
+        for tag in &project.tags {
+            out.push_str(&format!("  - {}\n", yaml_scalar(tag)));
+        }
+    }
+    if project.priority > 0 {
+        out.push_str(&format!("priority: {}\n", project.priority));
+    }
+    if let Some(due) = project.due_at.and_then(chrono::DateTime::from_timestamp_millis) {
+        out.push_str(&format!("due: {}\n", due.with_timezone(&chrono::Local).format("%Y-%m-%d")));
+    }
+    out.push_str(&format!("created: {}\n", chrono::Local::now().format("%Y-%m-%d")));
+    out.push_str("---\n\n");
+    out.push_str(&format!("# {}\n\n## 🎯 Objective\n\n", project.name));
+    if let Some(desc) = project.description.as_deref() {
+        out.push_str(desc.trim());
+        out.push('\n');
+    }
+    out.push_str("\n## Tasks\n");
+    out
+}
+
+/// Write a note for an app-created project into the active folder (or one of
+/// its category subfolders) and link the project to it. Returns the note path.
+pub fn create_project_note(conn: &Connection, project_id: &str, category: Option<&str>) -> Result<PathBuf> {
+    let vault_path = db::get_setting(conn, "obsidian_vault_path")?
+        .ok_or_else(|| anyhow!("No vault path configured"))?;
+    let project = db::get_project(conn, project_id)?
+        .ok_or_else(|| anyhow!("Project not found: {}", project_id))?;
+    if let Some(source) = &project.obsidian_source {
+        return Err(anyhow!("Project is already linked to {}", source));
+    }
+
+    let layout = VaultLayout::load(conn);
+    let (folder, color) = match category.filter(|c| !c.is_empty()) {
+        Some(category) => {
+            let color = layout
+                .category_color(category)
+                .ok_or_else(|| anyhow!("Unknown category folder: {}", category))?;
+            (Some(category), color.to_string())
+        }
+        None => (None, layout.root_color.clone()),
+    };
+
+    let file_name = format!("{}.md", sanitize_filename(&project.name));
+    let rel = match folder {
+        Some(folder) => format!("{}/{}", folder, file_name),
+        None => file_name,
+    };
+    let path = layout.active_dir(&vault_path).join(&rel);
+    if path.exists() {
+        return Err(anyhow!("A note already exists at {}", path.display()));
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, render_project_note(&project))?;
+
+    db::link_project_to_note(conn, project_id, &rel, &color, file_mtime_ms(&path))?;
+    Ok(path)
+}
+
+// ── Write-back ───────────────────────────────────────────────────────────────
+
+pub enum WriteBack {
+    /// Note rewritten; carries the new mtime.
+    Written(i64),
+    /// Write-back disabled or the project isn't linked to a note.
+    NotApplicable,
+    /// The note changed in the vault since the last sync; left untouched.
+    Conflict(PathBuf),
+}
+
+fn yaml_scalar(value: &str) -> String {
+    let needs_quotes = value.is_empty()
+        || value.contains(':')
+        || value.contains('#')
+        || value.contains('"')
+        || value.contains('\n')
+        || value.starts_with(['-', '[', '{', '>', '|', '*', '&', '!', '%', '@', '`', '\''])
+        || value != value.trim();
+    if needs_quotes {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Set (or add) top-level frontmatter keys, leaving the rest of the note alone.
+pub fn set_frontmatter_fields(content: &str, fields: &[(&str, &str)]) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let (_, body_start) = parse_frontmatter(&lines);
+    let mut front: Vec<String> = if body_start > 0 {
+        lines[1..body_start - 1].iter().map(|l| l.to_string()).collect()
+    } else {
+        Vec::new()
+    };
+
+    for (key, value) in fields {
+        let line = format!("{}: {}", key, yaml_scalar(value));
+        let existing = front.iter().position(|l| {
+            !is_continuation(l)
+                && l.split_once(':')
+                    .map(|(k, _)| k.trim().eq_ignore_ascii_case(key))
+                    .unwrap_or(false)
+        });
+        match existing {
+            Some(i) => {
+                // A block list or `|` scalar under the old value goes with
+                // it, blank lines inside the scalar included
+                let mut end = i + 1;
+                for (n, l) in front.iter().enumerate().skip(i + 1) {
+                    if is_continuation(l) {
+                        end = n + 1;
+                    } else if !l.trim().is_empty() {
+                        break;
+                    }
+                }
+                front.splice(i..end, [line]);
+            }
+            None => front.push(line),
+        }
+    }
+
+    let mut out = String::from("---\n");
+    for l in &front {
+        out.push_str(l);
+        out.push('\n');
+    }
+    out.push_str("---\n");
+    for l in &lines[body_start..] {
+        out.push_str(l);
+        out.push('\n');
+    }
+    out
+}
+
+/// Whether a frontmatter line belongs to the key above it: indented, or a
+/// list item, which YAML allows at the key's own indentation.
+fn is_continuation(line: &str) -> bool {
+    line.starts_with([' ', '\t']) || line == "-" || line.starts_with("- ")
+}
+
+/// Push a project's description and status into its note's frontmatter.
+/// Guarded by the `obsidian_write_back` setting; refuses to overwrite a note
+/// whose mtime is newer than what the last sync saw.
+pub fn write_back_project(conn: &Connection, project_id: &str) -> Result<WriteBack> {
+    if db::get_setting(conn, "obsidian_write_back")?.as_deref() != Some("true") {
+        return Ok(WriteBack::NotApplicable);
+    }
+    let Some(vault_path) = db::get_setting(conn, "obsidian_vault_path")? else {
+        return Ok(WriteBack::NotApplicable);
+    };
+    let project = db::get_project(conn, project_id)?
+        .ok_or_else(|| anyhow!("Project not found: {}", project_id))?;
+    let Some(source) = project.obsidian_source.as_deref() else {
+        return Ok(WriteBack::NotApplicable);
+    };
+
+    let path = VaultLayout::load(conn).active_dir(&vault_path).join(source);
+    if !path.is_file() {
+        return Err(anyhow!("Obsidian note not found: {}", path.display()));
+    }
+    let known = db::get_project_obsidian_mtime(conn, project_id)?;
+    if let (Some(known), Some(current)) = (known, file_mtime_ms(&path)) {
+        if current > known {
+            return Ok(WriteBack::Conflict(path));
+        }
+    }
+
+    let content = std::fs::read_to_string(&path)?;
+    let description = project.description.as_deref().unwrap_or("");
+    let updated = set_frontmatter_fields(
+        &content,
+        &[("description", description), ("status", project.status.as_str())],
+    );
+    std::fs::write(&path, updated)?;
+
+    let mtime = file_mtime_ms(&path).unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    db::set_project_obsidian_mtime(conn, project_id, Some(mtime))?;
+    Ok(WriteBack::Written(mtime))
+}
@@ -1,14 +1,91 @@
-use anyhow::{anyhow, Result};
+use crate::db;
+use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::time::Duration;
 
-pub const OPENCLAW_PATH_ENV: &str = "/opt/homebrew/bin:/usr/local/bin:/usr/bin:/bin";
+/// Setting: explicit path to the openclaw binary, skipping auto-detection.
+pub const BINARY_SETTING: &str = "openclaw_binary_path";
+/// Setting: PATH to run openclaw with, replacing the detected one.
+pub const PATH_SETTING: &str = "openclaw_path_env";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChatMessage {
     pub role: String,
     pub content: String,
+    /// Structured content beside the markdown text, in reply order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<MessagePart>,
+}
+
+/// A non-text piece of a message: from a typed `--json` payload or a
+/// non-text content block in the session file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessagePart {
+    Code {
+        language: Option<String>,
+        code: String,
+    },
+    Diff {
+        path: Option<String>,
+        diff: String,
+    },
+    /// Either a URL or inline base64 `data`.
+    Image {
+        url: Option<String>,
+        media_type: Option<String>,
+        data: Option<String>,
+    },
+    /// A tool the agent ran, with a short description of the call.
+    Tool {
+        name: String,
+        summary: Option<String>,
+    },
+}
+
+/// Read one typed block. Text blocks and unknown types give `None`.
+fn message_part(block: &serde_json::Value) -> Option<MessagePart> {
+    let str_field = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| block.get(*k)?.as_str())
+            .map(str::to_string)
+    };
+    match block.get("type")?.as_str()? {
+        "code" => Some(MessagePart::Code {
+            language: str_field(&["language", "lang"]),
+            code: str_field(&["code", "text"])?,
+        }),
+        "diff" => Some(MessagePart::Diff {
+            path: str_field(&["path", "file"]),
+            diff: str_field(&["diff", "text"])?,
+        }),
+        "image" => {
+            let url = str_field(&["url", "mediaUrl"]);
+            let data = str_field(&["data"]);
+            if url.is_none() && data.is_none() {
+                return None;
+            }
+            Some(MessagePart::Image {
+                url,
+                media_type: str_field(&["mimeType", "mediaType", "media_type"]),
+                data,
+            })
+        }
+        "toolCall" | "tool_use" | "tool" => {
+            let summary = str_field(&["summary"]).or_else(|| {
+                let args = block.get("arguments").or_else(|| block.get("input"))?;
+                Some(sample(&args.to_string()))
+            });
+            Some(MessagePart::Tool {
+                name: str_field(&["name", "toolName"])?,
+                summary,
+            })
+        }
+        _ => None,
+    }
 }
 
 // ── JSONL file format (for reading persisted sessions) ───────────────────────
@@ -23,26 +100,146 @@ struct JsonlMessage {
 #[derive(Debug, Serialize, Deserialize)]
 struct JsonlInner {
     role: String,
-    content: Vec<JsonlContent>,
+    content: Vec<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct JsonlContent {
-    #[serde(rename = "type")]
-    content_type: String,
-    text: Option<String>,
+// ── JSON stdout format from `openclaw agent --json` ──────────────────────────
+
+/// Shapes `openclaw agent --json` has printed across releases. Documents are
+/// matched by shape, so a mixed fleet of local and remote installs works.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputSchema {
+    /// `{ "payloads": [{ "text": ... }], "meta": ... }`
+    Payloads,
+    /// `{ "status": "ok", "result": { "payloads": [...] } }`
+    Envelope,
 }
 
-// ── JSON stdout format from `openclaw agent --json` ──────────────────────────
+impl OutputSchema {
+    const ALL: [OutputSchema; 2] = [OutputSchema::Payloads, OutputSchema::Envelope];
+
+    fn payloads(self, doc: &serde_json::Value) -> Option<&Vec<serde_json::Value>> {
+        match self {
+            OutputSchema::Payloads => doc.get("payloads")?.as_array(),
+            OutputSchema::Envelope => doc.get("result")?.get("payloads")?.as_array(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParsedOutput {
+    pub schema: OutputSchema,
+    pub texts: Vec<String>,
+    pub parts: Vec<MessagePart>,
+}
+
+/// JSON from openclaw that matches none of the known schemas.
+#[derive(Debug)]
+pub struct UnrecognizedOutput {
+    pub version: Option<String>,
+    pub sample: String,
+}
+
+impl std::fmt::Display for UnrecognizedOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Unrecognized openclaw output format (openclaw {}): {}",
+            self.version.as_deref().unwrap_or("version unknown"),
+            self.sample
+        )
+    }
+}
+
+impl std::error::Error for UnrecognizedOutput {}
 
-#[derive(Debug, Deserialize)]
-pub struct OpenClawOutput {
-    pub payloads: Vec<Payload>,
+/// First 200 chars of `text`, for error messages.
+fn sample(text: &str) -> String {
+    text.chars().take(200).collect()
+}
+
+/// Reply texts and typed parts from one `--json` document. A payload is
+/// text unless it has a `type`; `mediaUrl`/`mediaUrls` become images.
+pub fn parse_output(doc: &serde_json::Value) -> std::result::Result<ParsedOutput, UnrecognizedOutput> {
+    for schema in OutputSchema::ALL {
+        if let Some(payloads) = schema.payloads(doc) {
+            let mut parsed = ParsedOutput {
+                schema,
+                texts: Vec::new(),
+                parts: Vec::new(),
+            };
+            for payload in payloads {
+                match payload.get("type").and_then(|t| t.as_str()) {
+                    None | Some("text") => {
+                        if let Some(text) = payload.get("text").and_then(|t| t.as_str()) {
+                            parsed.texts.push(text.to_string());
+                        }
+                    }
+                    Some(_) => parsed.parts.extend(message_part(payload)),
+                }
+                let media = payload.get("mediaUrl").into_iter().chain(
+                    payload
+                        .get("mediaUrls")
+                        .and_then(|m| m.as_array())
+                        .into_iter()
+                        .flatten(),
+                );
+                for url in media.filter_map(|u| u.as_str()) {
+                    parsed.parts.push(MessagePart::Image {
+                        url: Some(url.to_string()),
+                        media_type: None,
+                        data: None,
+                    });
+                }
+            }
+            return Ok(parsed);
+        }
+    }
+    Err(UnrecognizedOutput {
+        version: openclaw_version(),
+        sample: sample(&doc.to_string()),
+    })
 }
 
-#[derive(Debug, Deserialize)]
-pub struct Payload {
-    pub text: Option<String>,
+/// Take every complete JSON document off the front of `buf`. An incomplete
+/// trailing document stays in `buf` for the next read; lines that aren't
+/// JSON (log noise) are dropped.
+pub fn drain_outputs(buf: &mut Vec<u8>) -> Vec<serde_json::Value> {
+    let mut docs = Vec::new();
+    loop {
+        let mut stream = serde_json::Deserializer::from_slice(buf).into_iter::<serde_json::Value>();
+        let mut consumed = 0;
+        let mut bad = false;
+        loop {
+            match stream.next() {
+                Some(Ok(doc)) => {
+                    consumed = stream.byte_offset();
+                    docs.push(doc);
+                }
+                Some(Err(e)) if e.is_eof() => break,
+                Some(Err(_)) => {
+                    bad = true;
+                    break;
+                }
+                None => {
+                    consumed = stream.byte_offset();
+                    break;
+                }
+            }
+        }
+        buf.drain(..consumed);
+        if !bad {
+            return docs;
+        }
+        // Skip the offending line; if it isn't finished yet, wait for more data
+        match buf.iter().position(|&b| b == b'\n') {
+            Some(nl) => {
+                buf.drain(..=nl);
+            }
+            None => return docs,
+        }
+    }
 }
 
 // ── Paths ────────────────────────────────────────────────────────────────────
@@ -69,8 +266,34 @@ fn ensure_session_dir(agent_id: &str) -> Result<PathBuf> {
 
 // ── JSONL parsing ────────────────────────────────────────────────────────────
 
+/// What one line of a session file holds.
+pub enum SessionLine {
+    Message(ChatMessage),
+    /// Valid JSON that isn't a chat message (tool calls, metadata, ...).
+    Other,
+    /// Not JSON at all, e.g. a line torn by a crashed writer.
+    Corrupt,
+}
+
+pub fn classify_line(line: &str) -> SessionLine {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+        return SessionLine::Corrupt;
+    };
+    match message_from_value(value) {
+        Some(msg) => SessionLine::Message(msg),
+        None => SessionLine::Other,
+    }
+}
+
 pub fn parse_jsonl_line(line: &str) -> Option<ChatMessage> {
-    let parsed: JsonlMessage = serde_json::from_str(line).ok()?;
+    match classify_line(line) {
+        SessionLine::Message(msg) => Some(msg),
+        _ => None,
+    }
+}
+
+fn message_from_value(value: serde_json::Value) -> Option<ChatMessage> {
+    let parsed = JsonlMessage::deserialize(value).ok()?;
     if parsed.msg_type != "message" {
         return None;
     }
@@ -78,130 +301,1116 @@ pub fn parse_jsonl_line(line: &str) -> Option<ChatMessage> {
     if inner.role != "user" && inner.role != "assistant" {
         return None;
     }
-    let text = inner
-        .content
-        .into_iter()
-        .filter_map(|c| if c.content_type == "text" { c.text } else { None })
-        .collect::<Vec<_>>()
-        .join("");
-    if text.is_empty() {
+    let mut text = String::new();
+    let mut parts = Vec::new();
+    for block in &inner.content {
+        if block.get("type").and_then(|t| t.as_str()) == Some("text") {
+            text.push_str(block.get("text").and_then(|t| t.as_str()).unwrap_or(""));
+        } else {
+            parts.extend(message_part(block));
+        }
+    }
+    if text.is_empty() && parts.is_empty() {
         return None;
     }
+    let content = if inner.role == "user" {
+        strip_context(&text).to_string()
+    } else {
+        text
+    };
     Some(ChatMessage {
         role: inner.role,
-        content: text,
+        content,
+        parts,
     })
 }
 
-pub fn load_session(agent_id: &str, session_id: &str) -> Result<Vec<ChatMessage>> {
+/// Start of the project context put in front of a user message before it
+/// goes to the agent. The agent's transcript records the message as sent, so
+/// the context is stripped again on read.
+const CONTEXT_PREFIX: &str = "[System context: ";
+
+pub fn with_context(context: &str, message: &str) -> String {
+    format!("{}{}]\n\n{}", CONTEXT_PREFIX, context, message)
+}
+
+fn strip_context(text: &str) -> &str {
+    text.strip_prefix(CONTEXT_PREFIX)
+        .and_then(|rest| rest.split_once("]\n\n"))
+        .map_or(text, |(_, message)| message)
+}
+
+/// A session line that couldn't be parsed. `line` is 1-based.
+#[derive(Debug, Clone, Serialize)]
+pub struct CorruptLine {
+    pub line: usize,
+    pub sample: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ParsedSession {
+    pub messages: Vec<ChatMessage>,
+    pub corrupt: Vec<CorruptLine>,
+}
+
+/// Chat messages from a session file. Corrupt lines are skipped and listed
+/// rather than failing the whole session; an unterminated last line is
+/// assumed to still be being written and is left out quietly.
+pub fn parse_session(content: &str) -> ParsedSession {
+    let mut parsed = ParsedSession::default();
+    let complete = match content.rfind('\n') {
+        Some(nl) => &content[..=nl],
+        None => "",
+    };
+    let tail = &content[complete.len()..];
+    for (i, line) in complete.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match classify_line(line) {
+            SessionLine::Message(msg) => parsed.messages.push(msg),
+            SessionLine::Other => {}
+            SessionLine::Corrupt => parsed.corrupt.push(CorruptLine {
+                line: i + 1,
+                sample: sample(line),
+            }),
+        }
+    }
+    // A finished document without its newline is still worth showing
+    if let SessionLine::Message(msg) = classify_line(tail) {
+        parsed.messages.push(msg);
+    }
+    parsed
+}
+
+/// A slice of a session's messages, oldest first.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionPage {
+    pub messages: Vec<ChatMessage>,
+    /// Cursor for the page before this one: pass it back as `before`.
+    /// `None` once the start of the file is reached.
+    pub before: Option<u64>,
+}
+
+/// Read size when walking a session file backwards.
+const PAGE_CHUNK: u64 = 64 * 1024;
+
+/// Up to `limit` messages ending just before byte offset `before` (the end
+/// of the file when `None`), reading backwards so only the tail of a long
+/// session is touched. Lines are classified as in `parse_session`; corrupt
+/// ones are skipped without being reported.
+pub fn read_session_page(path: &Path, before: Option<u64>, limit: usize) -> Result<SessionPage> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(SessionPage::default()),
+        Err(e) => return Err(e.into()),
+    };
+    let len = file.metadata()?.len();
+    let mut pos = before.unwrap_or(len).min(len);
+    // Bytes from `pos` up to the oldest line handled so far
+    let mut buf: Vec<u8> = Vec::new();
+    let mut newest_first = Vec::new();
+    loop {
+        while let Some(nl) = buf.iter().rposition(|&b| b == b'\n') {
+            let line_start = pos + nl as u64 + 1;
+            if let SessionLine::Message(msg) = classify_line(&String::from_utf8_lossy(&buf[nl + 1..])) {
+                newest_first.push(msg);
+                if newest_first.len() >= limit {
+                    newest_first.reverse();
+                    return Ok(SessionPage {
+                        messages: newest_first,
+                        before: Some(line_start),
+                    });
+                }
+            }
+            buf.truncate(nl);
+        }
+        if pos == 0 {
+            // What's left is the file's first line
+            if let SessionLine::Message(msg) = classify_line(&String::from_utf8_lossy(&buf)) {
+                newest_first.push(msg);
+            }
+            break;
+        }
+        let read = PAGE_CHUNK.min(pos);
+        pos -= read;
+        let mut chunk = vec![0; read as usize];
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+    }
+    newest_first.reverse();
+    Ok(SessionPage {
+        messages: newest_first,
+        before: None,
+    })
+}
+
+pub fn load_session(agent_id: &str, session_id: &str) -> Result<ParsedSession> {
     let path = session_path(agent_id, session_id);
     if !path.exists() {
-        return Ok(Vec::new());
+        return Ok(ParsedSession::default());
     }
     let content = std::fs::read_to_string(&path)?;
-    let messages = content
-        .lines()
-        .filter_map(parse_jsonl_line)
-        .collect();
-    Ok(messages)
+    Ok(parse_session(&content))
 }
 
-// ── Write messages to our own JSONL ──────────────────────────────────────────
+/// A session file found on disk, e.g. one started with `openclaw` from a terminal.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliSession {
+    pub agent_id: String,
+    pub session_id: String,
+    /// File mtime, epoch ms.
+    pub last_active: i64,
+    pub message_count: usize,
+    /// Start of the first user message.
+    pub preview: Option<String>,
+}
 
-pub fn append_message(agent_id: &str, session_id: &str, msg: &ChatMessage) -> Result<()> {
-    use std::io::Write;
+/// An agent's session ids with their mtimes, newest first.
+pub fn recent_sessions(agent_id: &str) -> Result<Vec<(String, i64)>> {
+    check_agent_id(agent_id)?;
+    let dir = agents_dir().join(agent_id).join("sessions");
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut sessions = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let (Some(id), Ok(modified)) = (
+            path.file_stem().and_then(|s| s.to_str()),
+            entry.metadata().and_then(|m| m.modified()),
+        ) else {
+            continue;
+        };
+        let ms = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        sessions.push((id.to_string(), ms));
+    }
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.1));
+    Ok(sessions)
+}
+
+/// Sessions this run of the app has sent a turn to. Session discovery leaves
+/// them alone: they belong to a thread or to one of the app's own jobs.
+static APP_SESSIONS: std::sync::Mutex<BTreeSet<String>> = std::sync::Mutex::new(BTreeSet::new());
+
+pub fn is_app_session(session_id: &str) -> bool {
+    APP_SESSIONS.lock().unwrap().contains(session_id)
+}
+
+pub fn describe_session(agent_id: &str, session_id: &str, last_active: i64) -> Result<CliSession> {
+    let messages = load_session(agent_id, session_id)?.messages;
+    let preview = messages
+        .iter()
+        .find(|m| m.role == "user")
+        .map(|m| m.content.chars().take(120).collect());
+    Ok(CliSession {
+        agent_id: agent_id.to_string(),
+        session_id: session_id.to_string(),
+        last_active,
+        message_count: messages.len(),
+        preview,
+    })
+}
+
+/// Copy the chat messages of session `from` onto the end of session `into`,
+/// verbatim so tool output and metadata on those lines survive. Returns how
+/// many messages were copied.
+pub fn merge_session(agent_id: &str, from: &str, into: &str) -> Result<usize> {
+    let path = session_path(agent_id, from);
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
     ensure_session_dir(agent_id)?;
+    let target = session_path(agent_id, into);
+    let mut copied = 0;
+    for line in content.lines() {
+        if let SessionLine::Message(_) = classify_line(line) {
+            append_line(&target, line)?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// Setting: what happens to a session file once its thread is deleted:
+/// "keep" (the default), "archive" to move it to `~/.openclaw/chat/archive`,
+/// or "delete".
+pub const RETENTION_SETTING: &str = "session_retention";
+
+/// Archive or delete a session file no thread uses any more, as `retention`
+/// says. Anything else keeps it.
+pub fn retire_session(agent_id: &str, session_id: &str, retention: &str) -> Result<()> {
+    check_agent_id(agent_id)?;
+    if session_id.is_empty() || session_id.contains(['/', '\\']) || session_id.starts_with('.') {
+        return Err(anyhow!("Invalid session id: {:?}", session_id));
+    }
     let path = session_path(agent_id, session_id);
+    if !path.exists() {
+        return Ok(());
+    }
+    match retention {
+        "delete" => std::fs::remove_file(&path)?,
+        "archive" => {
+            let dir = openclaw_dir().join("chat").join("archive").join(agent_id);
+            std::fs::create_dir_all(&dir)?;
+            std::fs::rename(&path, dir.join(format!("{}.jsonl", session_id)))
+                .with_context(|| format!("Failed to archive {}", path.display()))?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// ── Compaction ───────────────────────────────────────────────────────────────
+
+/// A session file cut for compaction.
+pub struct CompactionSplit {
+    /// Lines before the first message, e.g. openclaw's session header.
+    pub header: String,
+    /// Messages that get summarized.
+    pub older: Vec<ChatMessage>,
+    /// Byte offset of the first message kept; everything from here on stays.
+    pub keep_from: usize,
+}
+
+/// Split `content` so the last `keep` messages, and every line after the
+/// first of them, stay as they are. `None` when there's nothing older.
+pub fn split_for_compaction(content: &str, keep: usize) -> Option<CompactionSplit> {
+    let keep = keep.max(1);
+    let mut messages = Vec::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n').filter(|l| l.ends_with('\n')) {
+        if let SessionLine::Message(msg) = classify_line(line.trim_end()) {
+            messages.push((offset, msg));
+        }
+        offset += line.len();
+    }
+    if messages.len() <= keep {
+        return None;
+    }
+    let keep_from = messages[messages.len() - keep].0;
+    let first = messages[0].0;
+    messages.truncate(messages.len() - keep);
+    Some(CompactionSplit {
+        header: content[..first].to_string(),
+        older: messages.into_iter().map(|(_, msg)| msg).collect(),
+        keep_from,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactionResult {
+    pub summarized: usize,
+    pub kept: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Where the original session file was archived.
+    pub archived: String,
+}
+
+/// The line that stands in for `count` summarized messages. It reads as an
+/// ordinary assistant message; `compaction` records what it replaced.
+pub fn compaction_line(summary: &str, count: usize) -> Result<String> {
+    let line = serde_json::json!({
+        "type": "message",
+        "compaction": { "messages": count, "at": chrono::Utc::now().timestamp_millis() },
+        "message": {
+            "role": "assistant",
+            "content": [{
+                "type": "text",
+                "text": format!("Summary of the earlier conversation ({} messages):\n\n{}", count, summary.trim()),
+            }]
+        }
+    });
+    Ok(serde_json::to_string(&line)?)
+}
+
+/// Replace the session's messages before `split.keep_from` with
+/// `summary_line`, keeping a copy of the original under
+/// `~/.openclaw/chat/archive`. `read` is the content the split was made
+/// from: if the file no longer starts with it, nothing is written. Lines
+/// appended since are kept. Returns the new content and the archive path.
+pub fn write_compacted_session(
+    agent_id: &str,
+    session_id: &str,
+    read: &str,
+    split: &CompactionSplit,
+    summary_line: &str,
+) -> Result<(Vec<u8>, PathBuf)> {
+    use std::io::{Read, Write};
+    check_agent_id(agent_id)?;
+    let path = session_path(agent_id, session_id);
+    let mut file = std::fs::OpenOptions::new().read(true).write(true).open(&path)?;
+    file.lock()?;
+    let result = (|| -> Result<(Vec<u8>, PathBuf)> {
+        let mut current = Vec::new();
+        file.read_to_end(&mut current)?;
+        if !current.starts_with(read.as_bytes()) {
+            return Err(anyhow!("The session changed while it was being compacted; try again"));
+        }
+        let dir = openclaw_dir().join("chat").join("archive").join(agent_id);
+        std::fs::create_dir_all(&dir)?;
+        let archived = dir.join(format!("{}.{}.jsonl", session_id, chrono::Utc::now().timestamp_millis()));
+        std::fs::copy(&path, &archived).with_context(|| format!("Failed to archive {}", path.display()))?;
+
+        let mut content = split.header.clone().into_bytes();
+        content.extend_from_slice(summary_line.as_bytes());
+        content.push(b'\n');
+        content.extend_from_slice(&current[split.keep_from..]);
+        let tmp = path.with_extension("jsonl.compact");
+        let mut out = std::fs::File::create(&tmp)?;
+        out.write_all(&content)?;
+        out.sync_all()?;
+        std::fs::rename(&tmp, &path)?;
+        Ok((content, archived))
+    })();
+    let _ = file.unlock();
+    result
+}
+
+// ── Write messages to our own JSONL ──────────────────────────────────────────
+
+/// Append one line to a JSONL file shared with other writers (the CLI, the
+/// background loops, a second app window). Holds an exclusive advisory lock
+/// for the write, starts on a fresh line if a crashed writer left a torn
+/// one, and syncs before returning.
+pub fn append_line(path: &std::path::Path, line: &str) -> Result<()> {
+    use std::io::{Read, Seek, SeekFrom, Write};
     let mut file = std::fs::OpenOptions::new()
         .create(true)
+        .read(true)
         .append(true)
-        .open(&path)?;
+        .open(path)?;
+    file.lock()?;
+    let result = (|| -> Result<()> {
+        let mut buf = Vec::with_capacity(line.len() + 2);
+        if file.metadata()?.len() > 0 {
+            let mut last = [0u8];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                buf.push(b'\n');
+            }
+        }
+        buf.extend_from_slice(line.as_bytes());
+        buf.push(b'\n');
+        file.write_all(&buf)?;
+        file.sync_data()?;
+        Ok(())
+    })();
+    let _ = file.unlock();
+    result
+}
+
+pub fn append_message(agent_id: &str, session_id: &str, msg: &ChatMessage) -> Result<()> {
+    ensure_session_dir(agent_id)?;
+    append_line(&session_path(agent_id, session_id), &jsonl_line(msg)?)
+}
+
+/// Serializes a message as a single session JSONL line (without newline).
+pub fn jsonl_line(msg: &ChatMessage) -> Result<String> {
+    let mut content = vec![serde_json::json!({"type": "text", "text": msg.content})];
+    for part in &msg.parts {
+        content.push(serde_json::to_value(part)?);
+    }
     let line = serde_json::json!({
         "type": "message",
         "message": {
             "role": msg.role,
-            "content": [{"type": "text", "text": msg.content}]
+            "content": content
         }
     });
-    writeln!(file, "{}", serde_json::to_string(&line)?)?;
+    Ok(serde_json::to_string(&line)?)
+}
+
+// ── Agents ───────────────────────────────────────────────────────────────────
+
+/// The agent openclaw uses when none is configured.
+pub const DEFAULT_AGENT_ID: &str = "main";
+
+/// An installed agent, from its directory under `agents/` and its entry in
+/// `openclaw.json` (either may be missing).
+#[derive(Debug, Serialize, Clone)]
+pub struct AgentInfo {
+    pub id: String,
+    pub name: Option<String>,
+    pub emoji: Option<String>,
+    pub model: Option<String>,
+    pub workspace: Option<String>,
+    pub session_count: usize,
+    pub last_active: Option<i64>,
+    pub remote: bool,
+}
+
+/// Session file stats for one agent directory.
+#[derive(Debug, Default)]
+pub struct AgentSessions {
+    pub count: usize,
+    pub last_active: Option<i64>,
+}
+
+/// Combine agent directories with the `agents.list` entries of an
+/// `openclaw.json`. Unparseable config is ignored; the default agent is
+/// always present.
+pub fn merge_agents(
+    dirs: Vec<(String, AgentSessions)>,
+    config: Option<&str>,
+    remote: bool,
+) -> Vec<AgentInfo> {
+    let config: serde_json::Value = config
+        .and_then(|c| serde_json::from_str(c).ok())
+        .unwrap_or_default();
+    let entries = config["agents"]["list"].as_array().cloned().unwrap_or_default();
+    let default_model = model_name(&config["agents"]["defaults"]["model"]);
+
+    let bare = |id: String, sessions: AgentSessions| AgentInfo {
+        id,
+        name: None,
+        emoji: None,
+        model: None,
+        workspace: None,
+        session_count: sessions.count,
+        last_active: sessions.last_active,
+        remote,
+    };
+    let mut agents: Vec<AgentInfo> = dirs.into_iter().map(|(id, s)| bare(id, s)).collect();
+    let configured = entries.iter().filter_map(|e| e["id"].as_str());
+    for id in configured.chain([DEFAULT_AGENT_ID]) {
+        if !agents.iter().any(|a| a.id == id) {
+            agents.push(bare(id.to_string(), AgentSessions::default()));
+        }
+    }
+
+    for agent in &mut agents {
+        let entry = entries.iter().find(|e| e["id"].as_str() == Some(agent.id.as_str()));
+        if let Some(entry) = entry {
+            agent.name = entry["name"]
+                .as_str()
+                .or_else(|| entry["identity"]["name"].as_str())
+                .map(str::to_string);
+            agent.emoji = entry["identity"]["emoji"].as_str().map(str::to_string);
+            agent.model = model_name(&entry["model"]);
+            agent.workspace = entry["workspace"].as_str().map(str::to_string);
+        }
+        if agent.model.is_none() {
+            agent.model = default_model.clone();
+        }
+    }
+    agents.sort_by(|a, b| {
+        (b.id == DEFAULT_AGENT_ID)
+            .cmp(&(a.id == DEFAULT_AGENT_ID))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    agents
+}
+
+/// `model` is either a plain id or `{ "primary": id, ... }`.
+fn model_name(value: &serde_json::Value) -> Option<String> {
+    value
+        .as_str()
+        .or_else(|| value["primary"].as_str())
+        .map(str::to_string)
+}
+
+fn agent_sessions(dir: &std::path::Path) -> AgentSessions {
+    let mut sessions = AgentSessions::default();
+    let Ok(entries) = std::fs::read_dir(dir.join("sessions")) else {
+        return sessions;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+        sessions.count += 1;
+        let modified = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as i64);
+        sessions.last_active = sessions.last_active.max(modified);
+    }
+    sessions
+}
+
+/// Agents installed under `~/.openclaw`.
+pub fn list_agents() -> Result<Vec<AgentInfo>> {
+    let root = openclaw_dir();
+    let mut dirs = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(root.join("agents")) {
+        for entry in entries.flatten() {
+            if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                continue;
+            }
+            let id = entry.file_name().to_string_lossy().into_owned();
+            if id.starts_with('.') {
+                continue;
+            }
+            dirs.push((id, agent_sessions(&entry.path())));
+        }
+    }
+    let config = std::fs::read_to_string(root.join("openclaw.json")).ok();
+    Ok(merge_agents(dirs, config.as_deref(), false))
+}
+
+/// Fields of an agent the app can edit. `None` leaves a field alone; an
+/// empty string clears it.
+#[derive(Debug, Deserialize, Default)]
+pub struct AgentConfig {
+    pub name: Option<String>,
+    pub model: Option<String>,
+    /// Standing instructions, kept in the workspace's `AGENTS.md`.
+    pub instructions: Option<String>,
+}
+
+fn openclaw_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_default().join(".openclaw")
+}
+
+/// `~/.openclaw/agents`, holding each agent's `sessions` directory.
+pub fn agents_dir() -> PathBuf {
+    openclaw_dir().join("agents")
+}
+
+fn check_agent_id(agent_id: &str) -> Result<()> {
+    let valid = !agent_id.is_empty()
+        && agent_id.len() <= 64
+        && !agent_id.starts_with(['-', '.'])
+        && agent_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid agent id: {:?} (use letters, digits, - and _)", agent_id))
+    }
+}
+
+/// `openclaw.json` as a JSON value; missing is an empty object. Configs that
+/// only parse as JSON5 are refused rather than rewritten without comments.
+fn read_openclaw_config() -> Result<serde_json::Value> {
+    let path = openclaw_dir().join("openclaw.json");
+    match std::fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|_| {
+            anyhow!("{} isn't plain JSON; edit it with `openclaw config` instead", path.display())
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(serde_json::json!({})),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_openclaw_config(config: &serde_json::Value) -> Result<()> {
+    let path = openclaw_dir().join("openclaw.json");
+    std::fs::create_dir_all(openclaw_dir())?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(config)?)?;
+    std::fs::rename(&tmp, &path)?;
     Ok(())
 }
 
+/// Where an agent's workspace lives: its configured `workspace`, else
+/// openclaw's default (`workspace` for main, `workspace-<id>` otherwise).
+fn agent_workspace(entry: &serde_json::Value, agent_id: &str) -> PathBuf {
+    match entry["workspace"].as_str() {
+        Some(ws) => match ws.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+            None => PathBuf::from(ws),
+        },
+        None if agent_id == DEFAULT_AGENT_ID => openclaw_dir().join("workspace"),
+        None => openclaw_dir().join(format!("workspace-{}", agent_id)),
+    }
+}
+
+/// The agent's `agents.list` entry, added if it isn't there yet.
+fn agent_entry<'a>(config: &'a mut serde_json::Value, agent_id: &str) -> Result<&'a mut serde_json::Value> {
+    if !config.is_object() {
+        return Err(anyhow!("openclaw.json must contain an object"));
+    }
+    let agents = &mut config["agents"];
+    if agents.is_null() {
+        *agents = serde_json::json!({});
+    }
+    let list = &mut agents["list"];
+    if list.is_null() {
+        *list = serde_json::json!([]);
+    }
+    let list = list
+        .as_array_mut()
+        .ok_or_else(|| anyhow!("agents.list in openclaw.json must be an array"))?;
+    let pos = match list.iter().position(|e| e["id"].as_str() == Some(agent_id)) {
+        Some(pos) => pos,
+        None => {
+            list.push(serde_json::json!({ "id": agent_id }));
+            list.len() - 1
+        }
+    };
+    Ok(&mut list[pos])
+}
+
+fn apply_agent_config(agent_id: &str, update: &AgentConfig) -> Result<()> {
+    let mut config = read_openclaw_config()?;
+    let entry = agent_entry(&mut config, agent_id)?;
+    for (key, value) in [("name", &update.name), ("model", &update.model)] {
+        match value.as_deref().map(str::trim) {
+            None => {}
+            Some("") => {
+                entry.as_object_mut().map(|e| e.remove(key));
+            }
+            // Keep fallbacks when the model is in `{ primary, fallbacks }` form
+            Some(v) if key == "model" && entry[key].is_object() => {
+                entry[key]["primary"] = serde_json::json!(v)
+            }
+            Some(v) => entry[key] = serde_json::json!(v),
+        }
+    }
+    let workspace = agent_workspace(entry, agent_id);
+    write_openclaw_config(&config)?;
+
+    if let Some(instructions) = &update.instructions {
+        std::fs::create_dir_all(&workspace)?;
+        std::fs::write(workspace.join("AGENTS.md"), instructions)?;
+    }
+    Ok(())
+}
+
+/// Scaffold a new agent: its directory under `agents/`, a workspace, and an
+/// entry in `openclaw.json`.
+pub fn create_agent(agent_id: &str, config: &AgentConfig) -> Result<AgentInfo> {
+    check_agent_id(agent_id)?;
+    let dir = openclaw_dir().join("agents").join(agent_id);
+    if dir.exists() {
+        return Err(anyhow!("Agent already exists: {}", agent_id));
+    }
+    std::fs::create_dir_all(dir.join("sessions"))?;
+    std::fs::create_dir_all(dir.join("agent"))?;
+    apply_agent_config(agent_id, config)?;
+    list_agents()?
+        .into_iter()
+        .find(|a| a.id == agent_id)
+        .ok_or_else(|| anyhow!("Agent {} missing after create", agent_id))
+}
+
+/// Change an existing agent's name, model or instructions.
+pub fn update_agent_config(agent_id: &str, config: &AgentConfig) -> Result<()> {
+    check_agent_id(agent_id)?;
+    if !list_agents()?.iter().any(|a| a.id == agent_id) {
+        return Err(anyhow!("Agent not found: {}", agent_id));
+    }
+    apply_agent_config(agent_id, config)
+}
+
+/// Contents of the agent's `AGENTS.md`, if it has one.
+pub fn agent_instructions(agent_id: &str) -> Result<Option<String>> {
+    check_agent_id(agent_id)?;
+    let config = read_openclaw_config().unwrap_or_default();
+    let entries = config["agents"]["list"].as_array().cloned().unwrap_or_default();
+    let entry = entries
+        .into_iter()
+        .find(|e| e["id"].as_str() == Some(agent_id))
+        .unwrap_or_default();
+    match std::fs::read_to_string(agent_workspace(&entry, agent_id).join("AGENTS.md")) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
 // ── Send message and capture response ────────────────────────────────────────
 
+/// Setting: seconds an agent run may take before it's killed (0 = no limit).
+pub const TIMEOUT_SETTING: &str = "openclaw_timeout_secs";
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+
+/// An agent run that was killed for exceeding the timeout. Callers can
+/// `downcast_ref` it out of the `anyhow::Error` to report it separately.
+#[derive(Debug)]
+pub struct AgentTimeout {
+    pub secs: u64,
+}
+
+impl std::fmt::Display for AgentTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OpenClaw timed out after {}s", self.secs)
+    }
+}
+
+impl std::error::Error for AgentTimeout {}
+
+fn agent_timeout() -> Option<Duration> {
+    let secs = setting(TIMEOUT_SETTING)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_TIMEOUT_SECS);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+#[cfg(unix)]
+fn kill_process_group(pid: u32) {
+    let _ = std::process::Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", pid)])
+        .stderr(Stdio::null())
+        .status();
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_pid: u32) {}
+
+/// Arguments for `openclaw agent`. Local runs skip the gateway.
+pub fn agent_args(
+    local: bool,
+    agent_id: &str,
+    session_id: Option<&str>,
+    model: Option<&str>,
+    message: &str,
+) -> Vec<String> {
+    let mut args = vec!["agent".to_string()];
+    if local {
+        args.push("--local".to_string());
+    }
+    args.extend(["--agent".to_string(), agent_id.to_string()]);
+    if let Some(session_id) = session_id {
+        args.extend(["--session-id".to_string(), session_id.to_string()]);
+    }
+    if let Some(model) = model {
+        args.extend(["--model".to_string(), model.to_string()]);
+    }
+    args.extend(["--message".to_string(), message.to_string(), "--json".to_string()]);
+    args
+}
+
+// ── Invocation log ───────────────────────────────────────────────────────────
+
+/// What goes into the `agent_invocations` log: "off" (the default),
+/// "redacted" (timing, status and errors; message and reply reduced to their
+/// length) or "full" (message and reply kept, truncated).
+pub const LOG_SETTING: &str = "agent_log";
+/// Messages and replies are cut to this many characters in the log.
+const LOG_TEXT_CHARS: usize = 2000;
+
+/// One finished agent run, for `log_invocation`.
+pub struct Invocation<'a> {
+    pub agent_id: &'a str,
+    pub session_id: Option<&'a str>,
+    pub model: Option<&'a str>,
+    pub message: &'a str,
+    pub remote: bool,
+    pub duration: Duration,
+    pub exit_code: Option<i32>,
+}
+
+/// Record a run in the invocation log, as far as the log setting allows.
+/// Logging never fails the run; problems only go to stderr.
+pub async fn log_invocation(inv: &Invocation<'_>, result: std::result::Result<&str, &anyhow::Error>) {
+    let level = db::call(|conn| db::get_setting(conn, LOG_SETTING)).await.ok().flatten();
+    let full = match level.as_deref().map(str::trim) {
+        Some("full") => true,
+        Some("redacted") => false,
+        _ => return,
+    };
+    let keep = |text: &str| {
+        if full {
+            text.chars().take(LOG_TEXT_CHARS).collect()
+        } else {
+            format!("<{} chars>", text.chars().count())
+        }
+    };
+    let args = agent_args(!inv.remote, inv.agent_id, inv.session_id, inv.model, &keep(inv.message));
+    let command = std::iter::once("openclaw".to_string())
+        .chain(args.into_iter().map(|a| {
+            if a.contains(char::is_whitespace) {
+                format!("{:?}", a)
+            } else {
+                a
+            }
+        }))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let entry = db::AgentInvocation {
+        id: 0,
+        agent_id: inv.agent_id.to_string(),
+        session_id: inv.session_id.map(str::to_string),
+        model: inv.model.map(str::to_string),
+        remote: inv.remote,
+        command,
+        duration_ms: inv.duration.as_millis() as i64,
+        exit_code: inv.exit_code,
+        timed_out: result.is_err_and(|e| e.is::<AgentTimeout>()),
+        ok: result.is_ok(),
+        response: result.ok().filter(|_| full).map(keep),
+        error: result.err().map(|e| e.to_string().chars().take(LOG_TEXT_CHARS).collect()),
+        at: chrono::Utc::now().timestamp_millis(),
+    };
+    if let Err(e) = db::call(move |conn| db::log_agent_invocation(conn, &entry)).await {
+        eprintln!("[openclaw] Failed to log invocation: {}", e);
+    }
+}
+
 /// Spawns openclaw, captures the JSON response from stdout, returns assistant text.
-pub async fn send_and_capture(agent_id: &str, message: &str) -> Result<String> {
+///
+/// With a `session_id` the turn runs in that session, like on the remote
+/// host, and openclaw records both sides in the session file itself: that
+/// file is the transcript, so callers must not append the turn again.
+/// Without one openclaw falls back to the agent's default session. `model`
+/// overrides the agent's configured model for this turn. Extra flags, env
+/// and working directory come from the agent's `agent_overrides` row.
+pub async fn send_and_capture(
+    agent_id: &str,
+    session_id: Option<&str>,
+    model: Option<&str>,
+    message: &str,
+) -> Result<ChatMessage> {
+    if let Some(session_id) = session_id {
+        APP_SESSIONS.lock().unwrap().insert(session_id.to_string());
+    }
+    let started = std::time::Instant::now();
+    let mut exit_code = None;
+    let result = run_local_agent(agent_id, session_id, model, message, &mut exit_code).await;
+    log_invocation(
+        &Invocation {
+            agent_id,
+            session_id,
+            model,
+            message,
+            remote: false,
+            duration: started.elapsed(),
+            exit_code,
+        },
+        result.as_ref().map(|reply| reply.content.as_str()),
+    )
+    .await;
+    result
+}
+
+async fn run_local_agent(
+    agent_id: &str,
+    session_id: Option<&str>,
+    model: Option<&str>,
+    message: &str,
+    exit_code: &mut Option<i32>,
+) -> Result<ChatMessage> {
     let openclaw_bin = find_openclaw_binary()?;
 
     let db_path = dirs::home_dir()
         .unwrap_or_default()
         .join(".openclaw/chat/openclaw-chat.db");
 
-    let output = tokio::process::Command::new(&openclaw_bin)
-        .args([
-            "agent", "--local", "--agent", agent_id,
-            "--message", message, "--json",
-        ])
-        .env("PATH", OPENCLAW_PATH_ENV)
+    let agent = agent_id.to_string();
+    let overrides = db::call(move |conn| db::get_agent_override(conn, &agent))
+        .await?
+        .unwrap_or_default();
+
+    let mut command = tokio::process::Command::new(&openclaw_bin);
+    command
+        .args(agent_args(true, agent_id, session_id, model, message))
+        .args(&overrides.flags)
+        .env("PATH", path_env())
+        .envs(&overrides.env)
         .env("OPENCLAW_CHAT_DB", db_path.to_string_lossy().as_ref())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .spawn()?
-        .wait_with_output()
-        .await?;
+        .kill_on_drop(true);
+    // Own process group, so a timeout also takes down anything openclaw spawned
+    #[cfg(unix)]
+    command.process_group(0);
+    if let Some(dir) = overrides.working_dir.as_deref() {
+        command.current_dir(expand_home(dir));
+    }
+    let child = command.spawn()?;
+    let pid = child.id();
+
+    let output = match agent_timeout() {
+        Some(limit) => match tokio::time::timeout(limit, child.wait_with_output()).await {
+            Ok(output) => output?,
+            Err(_) => {
+                // Dropping the wait future killed openclaw and tokio reaps it
+                if let Some(pid) = pid {
+                    kill_process_group(pid);
+                }
+                return Err(AgentTimeout { secs: limit.as_secs() }.into());
+            }
+        },
+        None => child.wait_with_output().await?,
+    };
+    *exit_code = output.status.code();
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(anyhow!("OpenClaw error: {}", stderr));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let parsed: OpenClawOutput = serde_json::from_str(&stdout)
-        .map_err(|e| anyhow!("Failed to parse openclaw output: {} — raw: {}", e, &stdout[..stdout.len().min(200)]))?;
-
-    let text = parsed
-        .payloads
-        .into_iter()
-        .filter_map(|p| p.text)
-        .collect::<Vec<_>>()
-        .join("\n");
+    let mut stdout = output.stdout.clone();
+    let docs = drain_outputs(&mut stdout);
+    let mut texts = Vec::new();
+    let mut parts = Vec::new();
+    let mut unrecognized = None;
+    for doc in &docs {
+        match parse_output(doc) {
+            Ok(parsed) => {
+                texts.extend(parsed.texts);
+                parts.extend(parsed.parts);
+            }
+            Err(e) => unrecognized = Some(e),
+        }
+    }
+    if texts.is_empty() && parts.is_empty() {
+        if let Some(e) = unrecognized {
+            return Err(e.into());
+        }
+        if docs.is_empty() {
+            let raw = String::from_utf8_lossy(&output.stdout);
+            return Err(anyhow!("Failed to parse openclaw output — raw: {}", sample(&raw)));
+        }
+    }
+    let text = texts.join("\n");
 
-    if text.is_empty() {
+    if text.is_empty() && parts.is_empty() {
         return Err(anyhow!("OpenClaw returned empty response"));
     }
 
-    Ok(text)
+    Ok(ChatMessage {
+        role: "assistant".to_string(),
+        content: text,
+        parts,
+    })
+}
+
+// ── Models ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ModelInfo {
+    /// What `--model` takes, e.g. `anthropic/claude-sonnet-4-5`.
+    pub id: String,
+    pub name: Option<String>,
+}
+
+/// Models from `openclaw models list --json`: either a bare array or
+/// `{ "models": [...] }`, of ids or of objects keyed by `key`/`id`.
+pub fn parse_models(stdout: &str) -> Result<Vec<ModelInfo>> {
+    let doc: serde_json::Value = serde_json::from_str(stdout.trim())
+        .map_err(|e| anyhow!("Failed to parse model list: {} — raw: {}", e, sample(stdout)))?;
+    let list = doc
+        .as_array()
+        .or_else(|| doc["models"].as_array())
+        .ok_or_else(|| anyhow!("Unrecognized model list: {}", sample(stdout)))?;
+    Ok(list
+        .iter()
+        .filter_map(|m| {
+            if let Some(id) = m.as_str() {
+                return Some(ModelInfo { id: id.to_string(), name: None });
+            }
+            let id = m["key"].as_str().or_else(|| m["id"].as_str())?;
+            Some(ModelInfo {
+                id: id.to_string(),
+                name: m["name"].as_str().map(str::to_string),
+            })
+        })
+        .collect())
+}
+
+/// Models the local openclaw can use.
+pub async fn list_models() -> Result<Vec<ModelInfo>> {
+    let output = tokio::time::timeout(
+        Duration::from_secs(30),
+        tokio::process::Command::new(find_openclaw_binary()?)
+            .args(["models", "list", "--json"])
+            .env("PATH", path_env())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| anyhow!("openclaw models list timed out"))??;
+    if !output.status.success() {
+        return Err(anyhow!("OpenClaw error: {}", String::from_utf8_lossy(&output.stderr)));
+    }
+    parse_models(&String::from_utf8_lossy(&output.stdout))
 }
 
 // ── Find binary ──────────────────────────────────────────────────────────────
 
-pub fn find_openclaw_binary() -> Result<PathBuf> {
-    let candidates = [
-        PathBuf::from("/usr/local/bin/openclaw"),
-        PathBuf::from("/opt/homebrew/bin/openclaw"),
-        dirs::home_dir()
-            .unwrap_or_default()
-            .join(".local/bin/openclaw"),
-        dirs::home_dir()
-            .unwrap_or_default()
-            .join(".bun/bin/openclaw"),
-    ];
+fn setting(key: &str) -> Option<String> {
+    let conn = db::background().ok()?;
+    let value = db::get_setting(&conn, key).ok()??;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
 
-    for path in &candidates {
-        if path.exists() {
-            return Ok(path.clone());
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Where openclaw usually gets installed, for when the app was launched
+/// with a minimal PATH (e.g. from the macOS Dock).
+fn install_dirs() -> Vec<PathBuf> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let mut dirs: Vec<PathBuf> = ["/opt/homebrew/bin", "/usr/local/bin", "/usr/bin", "/bin"]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+    dirs.extend([
+        home.join(".local/bin"),
+        home.join(".bun/bin"),
+        home.join(".npm-global/bin"),
+    ]);
+    if let Some(appdata) = std::env::var_os("APPDATA") {
+        dirs.push(PathBuf::from(appdata).join("npm"));
+    }
+    dirs
+}
+
+/// PATH openclaw runs with: the override setting, else the app's own PATH
+/// followed by the usual install dirs.
+pub fn path_env() -> String {
+    if let Some(path) = setting(PATH_SETTING) {
+        return path;
+    }
+    let mut dirs: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|p| std::env::split_paths(&p).collect())
+        .unwrap_or_default();
+    for dir in install_dirs() {
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    std::env::join_paths(dirs)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| std::env::var("PATH").unwrap_or_default())
+}
+
+/// The configured binary if set, otherwise the first openclaw on `path_env()`.
+pub fn find_openclaw_binary() -> Result<PathBuf> {
+    if let Some(bin) = setting(BINARY_SETTING) {
+        let path = expand_home(&bin);
+        if path.is_file() {
+            return Ok(path);
         }
+        return Err(anyhow!("openclaw binary not found at {} (check Settings)", path.display()));
     }
 
-    if let Ok(output) = std::process::Command::new("which")
-        .arg("openclaw")
-        .env("PATH", OPENCLAW_PATH_ENV)
-        .output()
-    {
-        if output.status.success() {
-            let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !path.is_empty() {
-                return Ok(PathBuf::from(path));
+    let names: &[&str] = if cfg!(windows) {
+        &["openclaw.exe", "openclaw.cmd", "openclaw"]
+    } else {
+        &["openclaw"]
+    };
+    for dir in std::env::split_paths(&path_env()) {
+        for name in names {
+            let path = dir.join(name);
+            if path.is_file() {
+                return Ok(path);
             }
         }
     }
@@ -209,51 +1418,170 @@ pub fn find_openclaw_binary() -> Result<PathBuf> {
     Err(anyhow!("openclaw binary not found"))
 }
 
-/// Ask OpenClaw to generate a short thread title from message text.
-pub async fn generate_title(text: &str) -> Result<String> {
-    let prompt = format!(
-        "Summarize this in 3-6 words as a chat thread title (reply with just the title, no quotes): {}",
-        &text[..text.len().min(500)]
-    );
-    let result = send_and_capture("main", &prompt).await?;
-    // Clean up: take first line, strip quotes
-    let title = result
-        .lines()
-        .next()
-        .unwrap_or(&result)
-        .trim()
-        .trim_matches('"')
-        .trim_matches('\'')
-        .to_string();
-    if title.is_empty() {
-        return Err(anyhow!("Empty title generated"));
-    }
-    Ok(title)
-}
-
-/// Generate a title from recent conversation messages.
-pub async fn generate_title_from_messages(messages: &[ChatMessage]) -> Result<String> {
-    let summary: String = messages
-        .iter()
-        .take(5)
-        .map(|m| format!("{}: {}", m.role, &m.content[..m.content.len().min(200)]))
-        .collect::<Vec<_>>()
-        .join("\n");
-    let prompt = format!(
-        "Based on this conversation, generate a concise 3-6 word thread title (reply with just the title): {}",
-        summary
-    );
-    let result = send_and_capture("main", &prompt).await?;
-    let title = result
-        .lines()
-        .next()
-        .unwrap_or(&result)
-        .trim()
-        .trim_matches('"')
-        .trim_matches('\'')
-        .to_string();
-    if title.is_empty() {
-        return Err(anyhow!("Empty title generated"));
-    }
-    Ok(title)
+/// Version reported by the last successful probe.
+static OPENCLAW_VERSION: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
+pub fn openclaw_version() -> Option<String> {
+    OPENCLAW_VERSION.lock().unwrap().clone()
+}
+
+/// Result of looking for openclaw, for the settings screen.
+#[derive(Debug, Serialize, Clone)]
+pub struct OpenClawDetection {
+    pub path: Option<String>,
+    /// "setting" when the binary path was configured, "auto" otherwise.
+    pub source: &'static str,
+    pub version: Option<String>,
+    pub path_env: String,
+    pub error: Option<String>,
+}
+
+/// Locate openclaw and ask it for its version, which is kept for error reports.
+pub async fn detect_openclaw() -> OpenClawDetection {
+    let source = if setting(BINARY_SETTING).is_some() { "setting" } else { "auto" };
+    let path_env = path_env();
+    let bin = match find_openclaw_binary() {
+        Ok(bin) => bin,
+        Err(e) => {
+            return OpenClawDetection {
+                path: None,
+                source,
+                version: None,
+                path_env,
+                error: Some(e.to_string()),
+            }
+        }
+    };
+    let output = tokio::time::timeout(
+        Duration::from_secs(10),
+        tokio::process::Command::new(&bin)
+            .arg("--version")
+            .env("PATH", &path_env)
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await;
+    let (version, error) = match output {
+        Ok(Ok(out)) if out.status.success() => {
+            let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+            *OPENCLAW_VERSION.lock().unwrap() = Some(version.clone());
+            (Some(version), None)
+        }
+        Ok(Ok(out)) => (None, Some(String::from_utf8_lossy(&out.stderr).trim().to_string())),
+        Ok(Err(e)) => (None, Some(format!("Failed to run {}: {}", bin.display(), e))),
+        Err(_) => (None, Some("openclaw --version timed out".to_string())),
+    };
+    OpenClawDetection {
+        path: Some(bin.to_string_lossy().into_owned()),
+        source,
+        version,
+        path_env,
+        error,
+    }
+}
+
+// ── Health check ─────────────────────────────────────────────────────────────
+
+/// Prompt for the round-trip check; any reply counts.
+const PING_PROMPT: &str = "Health check from OpenClaw Chat. Reply with just: pong";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthCheck {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+    pub duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    /// Every check passed.
+    pub ok: bool,
+    pub detection: OpenClawDetection,
+    pub checks: Vec<HealthCheck>,
+}
+
+/// Check the local stack: binary, `--version`, writable session directories
+/// and, with `ping`, a real turn with `agent_id` in a throwaway session.
+pub async fn check_health(agent_id: &str, ping: bool) -> HealthReport {
+    let detection = detect_openclaw().await;
+    let mut checks = vec![
+        HealthCheck {
+            name: "binary".to_string(),
+            ok: detection.path.is_some(),
+            detail: match (&detection.path, &detection.error) {
+                (Some(path), _) => format!("{} ({})", path, detection.source),
+                (None, error) => error.clone().unwrap_or_default(),
+            },
+            duration_ms: None,
+        },
+        HealthCheck {
+            name: "version".to_string(),
+            ok: detection.version.is_some(),
+            detail: detection
+                .version
+                .clone()
+                .or_else(|| detection.error.clone())
+                .unwrap_or_default(),
+            duration_ms: None,
+        },
+    ];
+
+    let agents = list_agents().map(|a| a.into_iter().map(|a| a.id).collect::<Vec<_>>());
+    match agents {
+        Ok(ids) => {
+            for id in ids {
+                let result = ensure_session_dir(&id).and_then(|dir| {
+                    let probe = dir.join(".openclaw-chat-health");
+                    std::fs::write(&probe, b"ok")?;
+                    std::fs::remove_file(&probe)?;
+                    Ok(dir)
+                });
+                checks.push(HealthCheck {
+                    name: format!("sessions:{}", id),
+                    ok: result.is_ok(),
+                    detail: match result {
+                        Ok(dir) => format!("{} is writable", dir.display()),
+                        Err(e) => e.to_string(),
+                    },
+                    duration_ms: None,
+                });
+            }
+        }
+        Err(e) => checks.push(HealthCheck {
+            name: "agents".to_string(),
+            ok: false,
+            detail: e.to_string(),
+            duration_ms: None,
+        }),
+    }
+
+    if ping && detection.path.is_some() {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let started = std::time::Instant::now();
+        let result = match check_agent_id(agent_id) {
+            Ok(()) => send_and_capture(agent_id, Some(&session_id), None, PING_PROMPT).await,
+            Err(e) => Err(e),
+        };
+        let elapsed = started.elapsed().as_millis() as i64;
+        // A failed ping's transcript is left for debugging
+        if result.is_ok() {
+            let _ = std::fs::remove_file(session_path(agent_id, &session_id));
+        }
+        checks.push(HealthCheck {
+            name: format!("ping:{}", agent_id),
+            ok: result.is_ok(),
+            detail: match result {
+                Ok(reply) => sample(&reply.content),
+                Err(e) => e.to_string(),
+            },
+            duration_ms: Some(elapsed),
+        });
+    }
+
+    HealthReport {
+        ok: checks.iter().all(|c| c.ok),
+        detection,
+        checks,
+    }
 }
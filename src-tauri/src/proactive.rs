@@ -1,113 +1,1200 @@
-use crate::db::{get_proactive_brain_dumps, get_threads_needing_title_refresh, open_db, rename_thread, set_brain_dump_followed_up};
-use crate::openclaw::{self, ChatMessage};
-use anyhow::Result;
-use chrono::{Local, Timelike};
+use crate::db::{get_proactive_brain_dumps, get_threads_needing_title_refresh, rename_thread, set_brain_dump_followed_up};
+use crate::notifications::{self, Action, Notice};
+use crate::{db, dnd, kanban, mirror, obsidian, prompts, relations, scheduler, settings, titles};
+use crate::error::AppError;
+use crate::openclaw;
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Local, TimeZone, Timelike};
+use std::future::Future;
+use std::sync::atomic::{AtomicI64, Ordering};
 use std::time::Duration;
 use tauri::{AppHandle, Emitter};
+use tokio::sync::Semaphore;
 use uuid::Uuid;
 
-/// Interval between proactive follow-up checks (configurable; default 4 hours)
-const DEFAULT_INTERVAL_SECS: u64 = 4 * 60 * 60;
+/// Setting holding the `ProactiveSchedule` as JSON.
+pub const SCHEDULE_SETTING: &str = "proactive_schedule";
 
-pub async fn run_proactive_loop(app: AppHandle, interval_secs: Option<u64>) {
-    let interval = interval_secs.unwrap_or(DEFAULT_INTERVAL_SECS);
-    loop {
-        tokio::time::sleep(Duration::from_secs(interval)).await;
-        if let Err(e) = process_proactive_items(&app).await {
-            eprintln!("[proactive] Error: {}", e);
+/// When proactive follow-ups may run. Stored as JSON under
+/// `proactive_schedule`; defaults to every 4 hours, every day, quiet from
+/// 22:00 to 08:00 local time.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ProactiveSchedule {
+    pub interval_mins: u64,
+    /// Days follow-ups may run, 0 = Monday through 6 = Sunday.
+    pub active_days: Vec<u32>,
+    /// Local "HH:MM" bounds of the quiet window, which may wrap past
+    /// midnight. No window when either is unset or they're equal.
+    pub quiet_start: Option<String>,
+    pub quiet_end: Option<String>,
+    /// Hold a due pass until the user is at the computer, running it shortly
+    /// after they come back (see `next_follow_up_run`).
+    pub wait_for_activity: bool,
+}
+
+impl Default for ProactiveSchedule {
+    fn default() -> Self {
+        Self {
+            interval_mins: 4 * 60,
+            active_days: (0..7).collect(),
+            quiet_start: Some("22:00".to_string()),
+            quiet_end: Some("08:00".to_string()),
+            wait_for_activity: true,
         }
     }
 }
 
-/// Nightly loop: checks every 60s, runs title refresh once at 23:55.
-pub async fn run_title_refresh_loop(app: AppHandle) {
-    let mut last_run_date: Option<chrono::NaiveDate> = None;
-    loop {
-        tokio::time::sleep(Duration::from_secs(60)).await;
-        let now = Local::now();
-        let today = now.date_naive();
-        let hour = now.hour();
-        let minute = now.minute();
+impl ProactiveSchedule {
+    /// Configured schedule, falling back to the default when unset or malformed.
+    pub fn load(conn: &rusqlite::Connection) -> Self {
+        db::get_setting(conn, SCHEDULE_SETTING)
+            .ok()
+            .flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, conn: &rusqlite::Connection) -> Result<()> {
+        if self.interval_mins == 0 {
+            return Err(anyhow!("Interval must be at least a minute"));
+        }
+        if self.active_days.iter().any(|d| *d > 6) {
+            return Err(anyhow!("Active days run from 0 (Monday) to 6 (Sunday)"));
+        }
+        for time in [&self.quiet_start, &self.quiet_end].into_iter().flatten() {
+            parse_hhmm(time).ok_or_else(|| anyhow!("Quiet hours must be HH:MM, got {:?}", time))?;
+        }
+        settings::set(conn, SCHEDULE_SETTING, &serde_json::to_value(self)?)
+    }
+
+    /// Whether a follow-up may run at `t`.
+    pub fn allows(&self, t: chrono::DateTime<Local>) -> bool {
+        if !self.active_days.contains(&t.weekday().num_days_from_monday()) {
+            return false;
+        }
+        let quiet = self
+            .quiet_start
+            .as_deref()
+            .and_then(parse_hhmm)
+            .zip(self.quiet_end.as_deref().and_then(parse_hhmm));
+        let Some((start, end)) = quiet else {
+            return true;
+        };
+        let now = t.hour() * 60 + t.minute();
+        match start.cmp(&end) {
+            std::cmp::Ordering::Less => !(start..end).contains(&now),
+            std::cmp::Ordering::Greater => (end..start).contains(&now),
+            std::cmp::Ordering::Equal => true,
+        }
+    }
 
-        // Run at 23:55, once per day
-        if hour == 23 && minute == 55 && last_run_date != Some(today) {
-            last_run_date = Some(today);
-            if let Err(e) = refresh_stale_titles(&app).await {
-                eprintln!("[title-refresh] Error: {}", e);
+    /// The first time at or after `t` a follow-up may run, to the minute;
+    /// `None` when no day is active.
+    fn next_allowed(&self, t: chrono::DateTime<Local>) -> Option<chrono::DateTime<Local>> {
+        let mut t = t;
+        for _ in 0..=7 * 24 * 60 {
+            if self.allows(t) {
+                return Some(t);
             }
+            t += chrono::Duration::minutes(1);
         }
+        None
     }
 }
 
-async fn refresh_stale_titles(app: &AppHandle) -> Result<()> {
-    let conn = open_db()?;
-    let threads = get_threads_needing_title_refresh(&conn)?;
+/// Minutes past midnight for "HH:MM".
+pub fn parse_hhmm(time: &str) -> Option<u32> {
+    let (h, m) = time.trim().split_once(':')?;
+    let (h, m) = (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
+}
+
+/// Reschedule the next follow-up pass with the saved schedule.
+pub fn reload_schedule() {
+    scheduler::reload();
+}
+
+/// Setting: "true" while the proactive loop is paused. Survives restarts.
+pub const PAUSED_SETTING: &str = "proactive_paused";
+
+/// Where the proactive loop stands, for `cmd_get_proactive_status`.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProactiveStatus {
+    pub paused: bool,
+    pub running: bool,
+    /// Epoch ms; `None` while paused or with no active days.
+    pub next_run_at: Option<i64>,
+    pub last_run_at: Option<i64>,
+    /// Follow-ups sent by the last pass.
+    pub last_sent: usize,
+    /// Dumps the last pass failed to follow up on.
+    pub last_failed: usize,
+    pub last_error: Option<String>,
+}
 
-    for thread in threads {
-        let messages = openclaw::load_session(&thread.agent_id, &thread.session_id)?;
-        if messages.is_empty() {
-            continue;
+static STATUS: std::sync::Mutex<ProactiveStatus> = std::sync::Mutex::new(ProactiveStatus {
+    paused: false,
+    running: false,
+    next_run_at: None,
+    last_run_at: None,
+    last_sent: 0,
+    last_failed: 0,
+    last_error: None,
+});
+/// Held for the length of a pass so a manual run never overlaps the loop's.
+static PASS: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+pub async fn status() -> ProactiveStatus {
+    let mut status = STATUS.lock().unwrap().clone();
+    status.paused = db::call(|conn| Ok(is_paused(conn))).await.unwrap_or(false);
+    status.next_run_at = if status.paused { None } else { scheduler::next_run_at(scheduler::PROACTIVE) };
+    status
+}
+
+fn is_paused(conn: &rusqlite::Connection) -> bool {
+    db::get_setting(conn, PAUSED_SETTING)
+        .map(|v| v.as_deref() == Some("true"))
+        .unwrap_or(false)
+}
+
+/// Pause or resume the loop. Resuming starts a fresh wait.
+pub async fn set_paused(paused: bool) -> Result<()> {
+    db::call(move |conn| settings::set(conn, PAUSED_SETTING, &paused.into())).await?;
+    reload_schedule();
+    Ok(())
+}
+
+/// No command for this long and the user counts as away.
+const IDLE_AFTER_MS: i64 = 10 * 60 * 1000;
+/// How long after the user comes back a held pass runs, so it doesn't land
+/// in the middle of them catching up.
+const SETTLE_MS: i64 = 2 * 60 * 1000;
+/// Longest a due pass waits for the user before running anyway.
+const MAX_HOLD_MINS: i64 = 12 * 60;
+
+/// Epoch ms of the last command the frontend invoked.
+static LAST_ACTIVE: AtomicI64 = AtomicI64::new(0);
+/// Epoch ms the user came back after being away.
+static ACTIVE_SINCE: AtomicI64 = AtomicI64::new(0);
+
+/// Record user activity; called for every command invocation. Coming back
+/// after being away reschedules so a held pass goes out soon after.
+pub fn note_activity() {
+    let now = chrono::Utc::now().timestamp_millis();
+    let last = LAST_ACTIVE.swap(now, Ordering::Relaxed);
+    if now - last > IDLE_AFTER_MS {
+        ACTIVE_SINCE.store(now, Ordering::Relaxed);
+        scheduler::reload();
+    }
+}
+
+/// Since when the user has been at the computer, or `None` while away.
+fn active_since(now: i64) -> Option<i64> {
+    (now - LAST_ACTIVE.load(Ordering::Relaxed) <= IDLE_AFTER_MS).then(|| ACTIVE_SINCE.load(Ordering::Relaxed))
+}
+
+/// Scheduler trigger: `interval_mins` after the last pass, pushed past quiet
+/// hours and inactive days. With `wait_for_activity`, a pass that comes due
+/// while the user is away waits (up to `MAX_HOLD_MINS`) and runs `SETTLE_MS`
+/// after they're back. Nothing is scheduled while paused.
+pub fn next_follow_up_run(after: chrono::DateTime<Local>) -> Option<chrono::DateTime<Local>> {
+    let conn = db::background().ok()?;
+    if is_paused(&conn) {
+        return None;
+    }
+    let schedule = ProactiveSchedule::load(&conn);
+    drop(conn);
+    let due = schedule.next_allowed(after + chrono::Duration::minutes(schedule.interval_mins.max(1) as i64))?;
+    if !schedule.wait_for_activity {
+        return Some(due);
+    }
+    match active_since(chrono::Utc::now().timestamp_millis()) {
+        Some(since) => {
+            let settled = Local.timestamp_millis_opt(since + SETTLE_MS).single()?;
+            schedule.next_allowed(due.max(settled))
         }
-        match openclaw::generate_title_from_messages(&messages).await {
-            Ok(title) => {
-                rename_thread(&conn, &thread.id, &title)?;
-                let _ = app.emit(
-                    "thread:renamed",
-                    serde_json::json!({ "threadId": thread.id, "name": title }),
-                );
-            }
+        None => schedule.next_allowed(due + chrono::Duration::minutes(MAX_HOLD_MINS)),
+    }
+}
+
+pub async fn follow_up_job(app: AppHandle) -> Result<String> {
+    let run = run_pass(&app, PASS.lock().await, false).await?;
+    Ok(summarize(&run))
+}
+
+/// Follow up on every proactive brain dump now, outside the schedule.
+/// Fails if a pass is already running.
+pub async fn run_now(app: &AppHandle) -> Result<ProactiveStatus> {
+    let Ok(pass) = PASS.try_lock() else {
+        return Err(anyhow!("A follow-up pass is already running"));
+    };
+    run_pass(app, pass, true).await?;
+    Ok(status().await)
+}
+
+/// One follow-up pass, run while holding `PASS` and recorded in the status
+/// and the `proactive_runs` history.
+async fn run_pass(app: &AppHandle, _pass: tokio::sync::MutexGuard<'_, ()>, manual: bool) -> Result<db::ProactiveRun> {
+    STATUS.lock().unwrap().running = true;
+    let mut run = db::ProactiveRun {
+        started_at: chrono::Utc::now().timestamp_millis(),
+        manual,
+        ..Default::default()
+    };
+    let result = process_proactive_items(app, &mut run).await;
+    run.finished_at = chrono::Utc::now().timestamp_millis();
+    if let Err(e) = &result {
+        eprintln!("[proactive] Error: {}", e);
+        run.error = Some(e.to_string());
+    }
+    let record = run.clone();
+    if let Err(e) = db::call(move |conn| db::record_proactive_run(conn, &record)).await {
+        eprintln!("[proactive] Failed to record pass: {}", e);
+    }
+
+    {
+        let mut status = STATUS.lock().unwrap();
+        status.running = false;
+        status.last_run_at = Some(run.finished_at);
+        status.last_sent = run.sent as usize;
+        status.last_failed = run.failures.len();
+        status.last_error = run.error.clone();
+    }
+    let _ = app.emit("proactive:status", self::status().await);
+    result.map(|_| run)
+}
+
+/// One line for the job history, e.g. "2 follow-ups sent, 1 failed".
+fn summarize(run: &db::ProactiveRun) -> String {
+    let mut parts = vec![format!("{} follow-ups sent", run.sent)];
+    if run.stopped > 0 {
+        parts.push(format!("{} stopped", run.stopped));
+    }
+    if !run.failures.is_empty() {
+        parts.push(format!("{} failed", run.failures.len()));
+    }
+    parts.join(", ")
+}
+
+/// Setting: days without a message before a thread in an active project is
+/// resurfaced; "0" turns it off. Defaults to `DEFAULT_RESURFACE_DAYS`.
+pub const RESURFACE_DAYS_SETTING: &str = "resurface_after_days";
+const DEFAULT_RESURFACE_DAYS: i64 = 7;
+/// Most threads one pass suggests, so a long absence isn't a flood.
+const RESURFACE_LIMIT: u32 = 5;
+/// How much of the end of a conversation the agent sees.
+const RESURFACE_MESSAGES: usize = 6;
+const RESURFACE_EXCERPT_CHARS: usize = 300;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// A quiet thread with a message the user can send to pick it back up.
+/// Emitted as `thread:resurfaced`.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResurfacedThread {
+    pub thread: db::Thread,
+    pub idle_days: i64,
+    pub suggestion: String,
+}
+
+/// Runs every few hours, but only outside quiet hours and while follow-ups
+/// aren't paused.
+pub async fn resurface_job(app: AppHandle) -> Result<String> {
+    let (schedule, paused) = db::call(|conn| Ok((ProactiveSchedule::load(conn), is_paused(conn)))).await?;
+    if paused {
+        return Ok("Skipped: follow-ups paused".to_string());
+    }
+    if !schedule.allows(Local::now()) {
+        return Ok("Skipped: quiet hours".to_string());
+    }
+    let found = resurface_stale_threads(&app).await?;
+    Ok(format!("{} threads resurfaced", found.len()))
+}
+
+/// Ask the agent for a "where we left off / next step" message for each
+/// stale thread and emit it. A thread isn't suggested again until it sees
+/// another message and goes quiet once more.
+pub async fn resurface_stale_threads(app: &AppHandle) -> Result<Vec<ResurfacedThread>> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let (days, threads) = db::call(move |conn| {
+        let days = db::get_setting(conn, RESURFACE_DAYS_SETTING)?
+            .and_then(|v| v.trim().parse::<i64>().ok())
+            .unwrap_or(DEFAULT_RESURFACE_DAYS);
+        if days <= 0 {
+            return Ok((days, Vec::new()));
+        }
+        Ok((days, db::list_stale_threads(conn, now - days * DAY_MS, RESURFACE_LIMIT)?))
+    })
+    .await?;
+    let found = std::sync::Mutex::new(Vec::new());
+    let found_ref = &found;
+    run_bounded(threads, |thread| async move {
+        let idle_days = thread.last_message_at.map_or(days, |at| (now - at) / DAY_MS);
+        let suggestion = match suggest_next_step(&thread, idle_days).await {
+            Ok(Some(suggestion)) => suggestion,
+            Ok(None) => return,
             Err(e) => {
-                eprintln!("[title-refresh] Failed for thread {}: {}", thread.id, e);
+                eprintln!("[resurface] Failed for thread {}: {}", thread.id, e);
+                return;
+            }
+        };
+        let id = thread.id.clone();
+        if let Err(e) = db::call(move |conn| db::set_thread_resurfaced(conn, &id, now)).await {
+            eprintln!("[resurface] Failed to mark thread {}: {}", thread.id, e);
+        }
+        let resurfaced = ResurfacedThread { thread, idle_days, suggestion };
+        dnd::emit(app, "thread:resurfaced", &resurfaced);
+        found_ref.lock().unwrap().push(resurfaced);
+    })
+    .await;
+    Ok(found.into_inner().unwrap())
+}
+
+/// The message to send to pick `thread` back up, or `None` when there's no
+/// conversation to go on.
+async fn suggest_next_step(thread: &db::Thread, idle_days: i64) -> Result<Option<String>> {
+    let messages = thread_messages(thread)?;
+    if messages.is_empty() {
+        return Ok(None);
+    }
+    let tail = &messages[messages.len().saturating_sub(RESURFACE_MESSAGES)..];
+    let transcript = tail
+        .iter()
+        .map(|m| format!("{}: {}", m.role, m.content.chars().take(RESURFACE_EXCERPT_CHARS).collect::<String>()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let prompt = prompts::build(
+        prompts::RESURFACE,
+        &[("thread", &thread.name), ("idle_days", &idle_days.to_string()), ("transcript", &transcript)],
+    )
+    .await?;
+    let reply = openclaw::send_and_capture(openclaw::DEFAULT_AGENT_ID, None, None, &prompt).await?;
+    let suggestion = reply.content.trim().trim_matches('"').trim().to_string();
+    if suggestion.is_empty() {
+        return Err(anyhow!("Empty suggestion generated"));
+    }
+    Ok(Some(suggestion))
+}
+
+/// The thread's conversation, from its session file or the mirror's copy.
+fn thread_messages(thread: &db::Thread) -> Result<Vec<openclaw::ChatMessage>> {
+    let messages = openclaw::load_session(&thread.agent_id, &thread.session_id)?.messages;
+    if !messages.is_empty() {
+        return Ok(messages);
+    }
+    // Sessions that only ever ran remotely are readable from the mirror
+    Ok(mirror::load_cached(&thread.agent_id, &thread.session_id)?.map(|c| c.messages).unwrap_or_default())
+}
+
+/// How far ahead of a card's due date it gets a nudge.
+const NUDGE_AHEAD_MS: i64 = 24 * 60 * 60 * 1000;
+
+pub async fn deadline_nudge_job(app: AppHandle) -> Result<String> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let cards = db::call(move |conn| db::list_kanban_items_to_nudge(conn, now, now + NUDGE_AHEAD_MS)).await?;
+    let nudged = std::sync::atomic::AtomicUsize::new(0);
+    let nudged_ref = &nudged;
+    let app_ref = &app;
+    run_bounded(cards, |card| async move {
+        match nudge_card(app_ref, &card).await {
+            Ok(()) => {
+                nudged_ref.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => eprintln!("[deadline] Failed to nudge card {}: {}", card.id, e),
+        }
+    })
+    .await;
+    let overdue = db::call(move |conn| db::list_overdue_kanban_items(conn, now)).await?;
+    for card in &overdue {
+        notify_overdue(&app, card).await?;
+    }
+    Ok(format!("{} cards nudged, {} overdue", nudged.into_inner(), overdue.len()))
+}
+
+/// A card's due date as "Monday 3 March at 14:00", local time.
+fn due_text(card: &db::KanbanItem) -> String {
+    card.due_at
+        .and_then(|ms| Local.timestamp_millis_opt(ms).single())
+        .map(|t| t.format("%A %-d %B at %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Tell the user once that a card's due date has passed.
+async fn notify_overdue(app: &AppHandle, card: &db::KanbanItem) -> Result<()> {
+    let id = card.id.clone();
+    db::call(move |conn| db::set_kanban_overdue_notified(conn, &id, chrono::Utc::now().timestamp_millis())).await?;
+    let due = due_text(card);
+    let mut notice = Notice::new(format!("Overdue: {}", card.title), format!("Was due {}", due));
+    notice.thread_id = card.thread_id.clone();
+    notice.card_id = Some(card.id.clone());
+    dnd::notify_notice(app, &notice.with_actions(&[Action::OpenThread, Action::Snooze, Action::MarkDone]));
+    Ok(())
+}
+
+/// Ask the agent for a plan or reminder in the card's linked thread, opening
+/// (and linking) one first if it has none, then emit `kanban:nudge`.
+async fn nudge_card(app: &AppHandle, card: &db::KanbanItem) -> Result<()> {
+    let linked = card.clone();
+    let (thread, created, project) = db::call(move |conn| {
+        let card = linked;
+        let project = match card.project_id.as_deref() {
+            Some(id) => db::get_project(conn, id)?,
+            None => None,
+        };
+        let linked = match card.thread_id.as_deref() {
+            Some(id) => db::get_thread(conn, id)?,
+            None => None,
+        };
+        Ok(match linked {
+            Some(thread) => (thread, false, project),
+            None => {
+                let thread = create_follow_up_thread(conn, project.clone(), card.agent_id, card.title)?;
+                (thread, true, project)
             }
+        })
+    })
+    .await?;
+
+    let due = due_text(card);
+    let prompt = prompts::build(
+        prompts::DEADLINE,
+        &[
+            ("title", &card.title),
+            ("description", card.description.as_deref().filter(|d| !d.trim().is_empty()).unwrap_or("none")),
+            ("due", &due),
+            ("project", project.as_ref().map_or("no project", |p| p.name.as_str())),
+        ],
+    )
+    .await?;
+    let sent = openclaw::send_and_capture(&thread.agent_id, Some(&thread.session_id), None, &prompt).await;
+
+    if let Err(e) = sent {
+        if created {
+            let id = thread.id.clone();
+            db::call(move |conn| relations::delete(conn, relations::THREADS, &id)).await?;
+        }
+        return Err(e);
+    }
+    let card_id = card.id.clone();
+    let thread = db::call(move |conn| {
+        if created {
+            db::assign_kanban_item(conn, &card_id, &thread.agent_id, &thread.id)?;
         }
+        db::touch_thread(conn, &thread.id)?;
+        db::set_kanban_nudged(conn, &card_id, chrono::Utc::now().timestamp_millis())?;
+        Ok(db::get_thread(conn, &thread.id)?.unwrap_or(thread))
+    })
+    .await?;
+    dnd::emit(
+        app,
+        "kanban:nudge",
+        serde_json::json!({
+            "cardId": card.id,
+            "title": card.title,
+            "dueAt": card.due_at,
+            "threadId": thread.id,
+            "thread": thread,
+        }),
+    );
+    let mut notice = Notice::new(format!("Due soon: {}", card.title), format!("Due {}", due));
+    notice.thread_id = Some(thread.id.clone());
+    notice.card_id = Some(card.id.clone());
+    dnd::notify_notice(app, &notice.with_actions(&[Action::OpenThread, Action::Snooze, Action::MarkDone]));
+    if created {
+        let _ = app.emit("kanban:refresh", ());
     }
     Ok(())
 }
 
-async fn process_proactive_items(app: &AppHandle) -> Result<()> {
-    let conn = open_db()?;
-    let items = get_proactive_brain_dumps(&conn)?;
+/// Re-spawns recurring kanban cards into backlog when their next occurrence comes due.
+pub async fn recurrence_job(app: AppHandle) -> Result<String> {
+    let spawned = db::call(kanban::spawn_due_recurrences).await?;
+    if !spawned.is_empty() {
+        let _ = app.emit("kanban:refresh", ());
+    }
+    Ok(format!("{} recurring cards spawned", spawned.len()))
+}
+
+/// Board fingerprint and export mode at the last kanban export.
+type ExportFingerprint = (String, Option<String>);
+static LAST_EXPORT: std::sync::Mutex<Option<ExportFingerprint>> = std::sync::Mutex::new(None);
 
-    for item in items {
-        let session_id = Uuid::new_v4().to_string();
-        let prompt = format!(
-            "I jotted this down earlier: '{}'. Do you have thoughts, or can you help me take a first step on it?",
-            item.content
+/// Keeps the Obsidian kanban export current: regenerates whenever the board
+/// fingerprint or the export mode changes. No-op unless enabled in settings.
+pub async fn kanban_export_job() -> Result<String> {
+    db::call(|conn| {
+        let fingerprint = (
+            db::kanban_fingerprint(conn)?,
+            db::get_setting(conn, obsidian::KANBAN_EXPORT_SETTING)?,
         );
+        let mut last = LAST_EXPORT.lock().unwrap();
+        if last.as_ref() == Some(&fingerprint) {
+            return Ok("Unchanged".to_string());
+        }
+        obsidian::export_kanban_to_vault(conn)?;
+        *last = Some(fingerprint);
+        Ok("Exported".to_string())
+    })
+    .await
+}
+
+pub async fn title_refresh_job(app: AppHandle) -> Result<String> {
+    let count = refresh_stale_titles(&app).await?;
+    Ok(format!("{} threads checked", count))
+}
+
+/// No-op unless the `obsidian_weekly_review` setting is "true".
+pub async fn weekly_review_job(app: AppHandle) -> Result<String> {
+    let enabled = db::call(|conn| db::get_setting(conn, obsidian::WEEKLY_REVIEW_SETTING)).await?.as_deref() == Some("true");
+    if !enabled {
+        return Ok("Skipped: weekly review off".to_string());
+    }
+    let path = write_weekly_review(&app).await?;
+    Ok(format!("Wrote {}", path.display()))
+}
+
+/// Compose this ISO week's review (db stats + OpenClaw narrative) into the vault.
+pub async fn write_weekly_review(app: &AppHandle) -> Result<std::path::PathBuf> {
+    let now = Local::now();
+    let (week, start_date, start) = this_week(now);
+
+    let end = now.timestamp_millis() + 1;
+    let (stats, dir) = db::call(move |conn| {
+        let vault_path = db::get_setting(conn, "obsidian_vault_path")?
+            .ok_or_else(|| anyhow::anyhow!("No vault path configured"))?;
+        let folder = db::get_setting(conn, "obsidian_weekly_review_folder")?
+            .unwrap_or_else(|| obsidian::DEFAULT_WEEKLY_REVIEW_FOLDER.to_string());
+        let stats = db::weekly_stats(conn, start, end)?;
+        Ok((stats, std::path::PathBuf::from(vault_path).join(folder.trim_matches('/'))))
+    })
+    .await?;
+
+    let prompt = prompts::build(
+        prompts::SUMMARY,
+        &[
+            ("threads_active", &stats.threads_active.len().to_string()),
+            ("thread_names", &stats.threads_active.join(", ")),
+            ("dumps_captured", &stats.dumps_captured.to_string()),
+            ("dumps_closed", &stats.dumps_closed.to_string()),
+            ("cards_created", &stats.cards_created.to_string()),
+            ("cards_completed", &stats.cards_completed.len().to_string()),
+            ("card_titles", &stats.cards_completed.join(", ")),
+        ],
+    )
+    .await?;
+    // The numbers are the useful part; don't lose the note over a failed narrative
+    let narrative = match openclaw::send_and_capture(openclaw::DEFAULT_AGENT_ID, None, None, &prompt).await {
+        Ok(reply) => Some(reply.content),
+        Err(e) => {
+            eprintln!("[weekly-review] Narrative failed: {}", e);
+            None
+        }
+    };
+
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("Weekly Review {}.md", week));
+    let content = obsidian::render_weekly_review(&week, start_date, &stats, narrative.as_deref());
+    std::fs::write(&path, content)?;
+    dnd::emit(
+        app,
+        "obsidian:weekly_review",
+        serde_json::json!({ "week": week, "path": path.to_string_lossy() }),
+    );
+    Ok(path)
+}
+
+/// The ISO week `now` falls in as "YYYY-WW", with its Monday and the start
+/// of that Monday in epoch ms.
+fn this_week(now: chrono::DateTime<Local>) -> (String, chrono::NaiveDate, i64) {
+    let iso = now.iso_week();
+    let start_date = now.date_naive() - chrono::Duration::days(now.weekday().num_days_from_monday() as i64);
+    let start = Local
+        .from_local_datetime(&start_date.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .map(|t| t.timestamp_millis())
+        .unwrap_or(0);
+    (format!("{}-{:02}", iso.year(), iso.week()), start_date, start)
+}
+
+pub async fn retrospective_job(app: AppHandle) -> Result<String> {
+    let (retro, path) = write_retrospective(&app).await?;
+    Ok(match path {
+        Some(path) => format!("{} projects, wrote {}", retro.projects.len(), path.display()),
+        None => format!("{} projects", retro.projects.len()),
+    })
+}
+
+/// Summarize this ISO week per project (messages, completed cards, closed
+/// dumps) with an OpenClaw narrative, store it, export it to the vault when
+/// the `obsidian_retrospective` setting is "true", and emit
+/// `retrospective:ready`. Returns the note's path when it was exported.
+pub async fn write_retrospective(app: &AppHandle) -> Result<(db::Retrospective, Option<std::path::PathBuf>)> {
+    let now = Local::now();
+    let (week, start_date, start) = this_week(now);
+    let end = now.timestamp_millis() + 1;
+    let activity = db::call(move |conn| db::retrospective_activity(conn, start, end)).await?;
+
+    let mut projects = Vec::new();
+    for (mut project, threads) in activity {
+        // Sessions have no per-message times, so this is every message in
+        // the threads that were active this week
+        project.messages = threads
+            .iter()
+            .map(|t| thread_messages(t).map_or(0, |m| m.len()))
+            .sum();
+        projects.push(project);
+    }
+
+    let summary = projects
+        .iter()
+        .map(|p| {
+            let list = |items: &[String]| if items.is_empty() { "none".to_string() } else { items.join("; ") };
+            format!(
+                "- {}: {} messages in {} threads ({}). Cards completed: {}. Brain dumps closed: {}.",
+                p.project,
+                p.messages,
+                p.threads.len(),
+                list(&p.threads),
+                list(&p.cards_completed),
+                list(&p.dumps_closed)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    // Keep the numbers even when the agent can't be reached
+    let narrative = if projects.is_empty() {
+        None
+    } else {
+        let prompt = prompts::build(prompts::RETROSPECTIVE, &[("week", &week), ("projects", &summary)]).await?;
+        match openclaw::send_and_capture(openclaw::DEFAULT_AGENT_ID, None, None, &prompt).await {
+            Ok(reply) => Some(reply.content),
+            Err(e) => {
+                eprintln!("[retrospective] Narrative failed: {}", e);
+                None
+            }
+        }
+    };
+
+    let retro = db::Retrospective {
+        id: Uuid::new_v4().to_string(),
+        week,
+        week_start: start_date.format("%Y-%m-%d").to_string(),
+        narrative,
+        projects,
+        created_at: chrono::Utc::now().timestamp_millis(),
+    };
+    let saved = retro.clone();
+    let dir = db::call(move |conn| {
+        db::save_retrospective(conn, &saved)?;
+        let export = db::get_setting(conn, obsidian::RETROSPECTIVE_SETTING)?.as_deref() == Some("true");
+        Ok(match db::get_setting(conn, "obsidian_vault_path")? {
+            Some(vault_path) if export => {
+                let folder = db::get_setting(conn, "obsidian_retrospective_folder")?
+                    .unwrap_or_else(|| obsidian::DEFAULT_RETROSPECTIVE_FOLDER.to_string());
+                Some(std::path::PathBuf::from(vault_path).join(folder.trim_matches('/')))
+            }
+            _ => None,
+        })
+    })
+    .await?;
+    let path = match dir {
+        Some(dir) => {
+            std::fs::create_dir_all(&dir)?;
+            let path = dir.join(format!("Retrospective {}.md", retro.week));
+            std::fs::write(&path, obsidian::render_retrospective(&retro))?;
+            Some(path)
+        }
+        None => None,
+    };
+    dnd::emit(
+        app,
+        "retrospective:ready",
+        serde_json::json!({ "retrospective": retro, "path": path.as_ref().map(|p| p.to_string_lossy()) }),
+    );
+    Ok((retro, path))
+}
 
-        // Write user message
-        let user_msg = ChatMessage {
-            role: "user".to_string(),
-            content: prompt.clone(),
+/// Local "HH:MM" the morning briefing is written; unset or empty turns it off.
+pub const BRIEFING_TIME_SETTING: &str = "briefing_time";
+
+/// Scheduler trigger: the next time of day matching `briefing_time`.
+pub fn next_briefing_run(after: chrono::DateTime<Local>) -> Option<chrono::DateTime<Local>> {
+    let time = db::get_setting(&*db::background().ok()?, BRIEFING_TIME_SETTING).ok()??;
+    let mins = parse_hhmm(&time)?;
+    (0..=1)
+        .filter_map(|day| {
+            let date = after.date_naive() + chrono::Duration::days(day);
+            Local.from_local_datetime(&date.and_hms_opt(mins / 60, mins % 60, 0)?).earliest()
+        })
+        .find(|at| *at > after)
+}
+
+pub async fn briefing_job(app: AppHandle) -> Result<String> {
+    let briefing = write_briefing(&app).await?;
+    Ok(format!("Briefing for {}", briefing.date))
+}
+
+/// Compose today's briefing (overnight follow-ups, cards due today, stale
+/// threads, open dumps) with OpenClaw, store it and emit `briefing:ready`.
+pub async fn write_briefing(app: &AppHandle) -> Result<db::Briefing> {
+    let now = Local::now();
+    let today = now.date_naive();
+    let end_of_day = Local
+        .from_local_datetime(&(today + chrono::Duration::days(1)).and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .map(|t| t.timestamp_millis())
+        .unwrap_or(i64::MAX);
+
+    let now_ms = now.timestamp_millis();
+    let inputs = db::call(move |conn| {
+        // Everything since the last briefing, or the last day on the first run
+        let since = db::list_briefings(conn, 1)?
+            .first()
+            .map(|b| b.created_at)
+            .unwrap_or(now_ms - 24 * 60 * 60 * 1000);
+        db::briefing_inputs(conn, since, end_of_day, now_ms)
+    })
+    .await?;
+
+    let list = |items: &[String]| if items.is_empty() { "none".to_string() } else { items.join("; ") };
+    let prompt = prompts::build(
+        prompts::BRIEFING,
+        &[
+            ("followed_up", &list(&inputs.followed_up)),
+            ("cards_due", &list(&inputs.cards_due)),
+            ("stale_threads", &list(&inputs.stale_threads)),
+            ("open_dumps", &list(&inputs.open_dumps)),
+        ],
+    )
+    .await?;
+    // Fall back to the bare lists rather than skip the day
+    let content = match openclaw::send_and_capture(openclaw::DEFAULT_AGENT_ID, None, None, &prompt).await {
+        Ok(reply) => reply.content,
+        Err(e) => {
+            eprintln!("[briefing] Narrative failed: {}", e);
+            plain_briefing(&inputs)
+        }
+    };
+
+    let briefing = db::Briefing {
+        id: Uuid::new_v4().to_string(),
+        date: today.format("%Y-%m-%d").to_string(),
+        content,
+        created_at: chrono::Utc::now().timestamp_millis(),
+    };
+    let saved = briefing.clone();
+    db::call(move |conn| db::create_briefing(conn, &saved)).await?;
+    dnd::emit(app, "briefing:ready", &briefing);
+    let first_line = briefing.content.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+    let notice = Notice::new("Morning briefing ready", notifications::snippet(first_line.trim_start_matches('#')));
+    dnd::notify_notice(app, &notice.with_actions(&[Action::Snooze]));
+    Ok(briefing)
+}
+
+/// The briefing's inputs as Markdown lists, for when the agent can't be reached.
+fn plain_briefing(inputs: &db::BriefingInputs) -> String {
+    let sections = [
+        ("Followed up overnight", &inputs.followed_up),
+        ("Due today", &inputs.cards_due),
+        ("Gone quiet", &inputs.stale_threads),
+        ("Open brain dumps", &inputs.open_dumps),
+    ];
+    sections
+        .iter()
+        .filter(|(_, items)| !items.is_empty())
+        .map(|(label, items)| {
+            let lines: Vec<_> = items.iter().map(|i| format!("- {}", i)).collect();
+            format!("**{}**\n{}", label, lines.join("\n"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Returns how many threads were up for a refresh.
+async fn refresh_stale_titles(app: &AppHandle) -> Result<usize> {
+    let threads = db::call(get_threads_needing_title_refresh).await?;
+    let count = threads.len();
+    run_bounded(threads, |thread| async move {
+        if let Err(e) = refresh_title(app, &thread).await {
+            eprintln!("[title-refresh] Failed for thread {}: {}", thread.id, e);
+        }
+    })
+    .await;
+    Ok(count)
+}
+
+async fn refresh_title(app: &AppHandle, thread: &db::Thread) -> Result<()> {
+    let messages = openclaw::load_session(&thread.agent_id, &thread.session_id)?.messages;
+    if messages.is_empty() {
+        return Ok(());
+    }
+    let title = titles::for_conversation(&messages).await?;
+    let (id, name) = (thread.id.clone(), title.clone());
+    db::call(move |conn| rename_thread(conn, &id, &name)).await?;
+    let _ = app.emit(
+        "thread:renamed",
+        serde_json::json!({ "threadId": thread.id, "name": title }),
+    );
+    Ok(())
+}
+
+/// A follow-up counts as answered with a reply within this long.
+const REPLY_WINDOW_MS: i64 = 48 * 60 * 60 * 1000;
+
+/// Settle pending follow-up outcomes from the threads' session files: a user
+/// message after the follow-up (and before the next one in the thread)
+/// within `REPLY_WINDOW_MS` is a reply; none by then and it was ignored.
+pub async fn outcome_job(_app: AppHandle) -> Result<String> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let pending = db::call(db::list_pending_follow_up_outcomes).await?;
+    let (mut replied, mut ignored) = (0, 0);
+    for outcome in pending {
+        let deadline = outcome.sent_at + REPLY_WINDOW_MS;
+        let (thread_id, sent_at) = (outcome.thread_id.clone(), outcome.sent_at);
+        let (thread, next) = db::call(move |conn| {
+            Ok((
+                db::get_thread(conn, &thread_id)?,
+                db::next_follow_up_in_thread(conn, &thread_id, sent_at)?,
+            ))
+        })
+        .await?;
+        let reply_at = match &thread {
+            Some(thread) => {
+                let messages = thread_messages(thread)?;
+                let end = next.map_or(messages.len(), |(_, start)| start).min(messages.len());
+                let start = outcome.end_index.min(end);
+                messages[start..end].iter().any(|m| m.role == "user").then(|| match next {
+                    // Sometime before the next follow-up went out
+                    Some((next_sent, _)) => next_sent.min(now),
+                    // Replies sent from the app touch the thread
+                    None => thread.last_message_at.filter(|at| *at > outcome.sent_at).unwrap_or(now).min(now),
+                })
+            }
+            None => None,
         };
-        openclaw::append_message("main", &session_id, &user_msg)?;
-
-        match openclaw::send_and_capture("main", &prompt).await {
-            Ok(response) => {
-                // Write assistant response
-                let assistant_msg = ChatMessage {
-                    role: "assistant".to_string(),
-                    content: response,
-                };
-                openclaw::append_message("main", &session_id, &assistant_msg)?;
-
-                set_brain_dump_followed_up(&conn, &item.id)?;
-
-                let _ = app.emit(
-                    "braindump:followed_up",
-                    serde_json::json!({
-                        "brain_dump_id": item.id,
-                        "session_id": session_id,
-                        "content": item.content,
-                        "project_id": item.project_id,
-                    }),
-                );
+        let resolved = match reply_at {
+            Some(at) if at <= deadline => Some(("replied", Some(at))),
+            Some(at) => Some(("ignored", Some(at))),
+            None if now > deadline => Some(("ignored", None)),
+            None => None,
+        };
+        if let Some((state, at)) = resolved {
+            let id = outcome.id;
+            db::call(move |conn| db::resolve_follow_up_outcome(conn, id, state, at)).await?;
+            if state == "replied" {
+                replied += 1;
+            } else {
+                ignored += 1;
             }
+        }
+    }
+    Ok(format!("{} replied, {} ignored", replied, ignored))
+}
+
+/// Setting: "per_dump" (default) opens a thread per dump; "digest" sends
+/// every due dump in one message in a shared thread.
+pub const MODE_SETTING: &str = "proactive_mode";
+/// Setting: id of the thread digests go to, created on the first digest.
+pub const DIGEST_THREAD_SETTING: &str = "proactive_digest_thread";
+const DIGEST_THREAD_NAME: &str = "Brain dump follow-ups";
+
+/// Follow up on every proactive dump that's due, tallying the outcome of each
+/// in `run`.
+async fn process_proactive_items(app: &AppHandle, run: &mut db::ProactiveRun) -> Result<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let (items, digest): (Vec<_>, bool) = db::call(|conn| {
+        let digest = db::get_setting(conn, MODE_SETTING)?.as_deref() == Some("digest");
+        Ok((get_proactive_brain_dumps(conn)?, digest))
+    })
+    .await?;
+    let items: Vec<_> = items.into_iter().filter(|item| is_due(item, now)).collect();
+    run.digest = digest;
+    run.processed = items.len() as u32;
+    if items.is_empty() {
+        return Ok(());
+    }
+    if digest {
+        let _slot = AGENT_SLOTS.acquire().await.expect("AGENT_SLOTS is never closed");
+        return follow_up_digest(app, items, run).await;
+    }
+    let tally = std::sync::Mutex::new(&mut *run);
+    let tally_ref = &tally;
+    run_bounded(items, |item| async move {
+        let result = follow_up(app, &item).await;
+        let mut run = tally_ref.lock().unwrap();
+        match result {
+            Ok(true) => run.sent += 1,
+            Ok(false) => run.stopped += 1,
             Err(e) => {
                 eprintln!("[proactive] Failed to send for item {}: {}", item.id, e);
+                run.failures.push(db::ProactiveFailure { brain_dump_id: item.id.clone(), error: e.to_string() });
             }
         }
+    })
+    .await;
+    Ok(())
+}
+
+/// Never followed up, or its interval has passed since the last follow-up.
+fn is_due(item: &db::BrainDump, now: i64) -> bool {
+    match (item.followed_up_at, item.follow_up_interval_hours) {
+        (None, _) => true,
+        (Some(last), Some(hours)) => now - last >= hours * 60 * 60 * 1000,
+        (Some(_), None) => false,
+    }
+}
+
+/// The thread the dump was last followed up in, if it still exists.
+async fn follow_up_thread(item: &db::BrainDump) -> Result<Option<db::Thread>> {
+    match item.follow_up_thread_id.clone() {
+        Some(id) => db::call(move |conn| db::get_thread(conn, &id)).await,
+        None => Ok(None),
+    }
+}
+
+/// Unanswered follow-ups so far, where a message in `thread` since the last
+/// one counts as an answer. Once `max_follow_ups` have gone unanswered the
+/// dump stops being proactive and this returns `None`.
+async fn unanswered_attempts(
+    app: &AppHandle,
+    item: &db::BrainDump,
+    thread: Option<&db::Thread>,
+) -> Result<Option<i32>> {
+    let replied = thread.is_some_and(|t| t.last_message_at > item.followed_up_at);
+    let attempts = if replied { 0 } else { item.follow_up_attempts };
+    let max = item.max_follow_ups.unwrap_or(db::DEFAULT_MAX_FOLLOW_UPS);
+    if attempts < max {
+        return Ok(Some(attempts));
+    }
+    let id = item.id.clone();
+    db::call(move |conn| db::set_brain_dump_proactive(conn, &id, false)).await?;
+    dnd::emit(
+        app,
+        "braindump:follow_ups_stopped",
+        serde_json::json!({ "brain_dump_id": item.id, "attempts": attempts }),
+    );
+    Ok(None)
+}
+
+/// A new local thread for follow-ups and nudges, with `agent_id` or else the project's
+/// agent when there is one.
+fn create_follow_up_thread(
+    conn: &rusqlite::Connection,
+    project: Option<db::Project>,
+    agent_id: Option<String>,
+    name: String,
+) -> Result<db::Thread> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let thread = db::Thread {
+        id: Uuid::new_v4().to_string(),
+        project_id: project.as_ref().map(|p| p.id.clone()),
+        name,
+        session_id: Uuid::new_v4().to_string(),
+        agent_id: agent_id
+            .or_else(|| project.map(|p| p.agent_id))
+            .unwrap_or_else(|| openclaw::DEFAULT_AGENT_ID.to_string()),
+        created_at: now,
+        updated_at: now,
+        last_message_at: None,
+        execution_target: Some("local".to_string()),
+        model: None,
+        muted: false,
+    };
+    db::create_thread(conn, &thread)?;
+    Ok(thread)
+}
+
+/// Send `prompt` in `thread`, deleting the thread if it was `created` for
+/// this and the send failed so no empty thread is left behind; the next pass
+/// tries again. On success, records the follow-up (and a pending outcome)
+/// on every dump in `items` and emits `braindump:followed_up` for each.
+async fn send_follow_up(
+    app: &AppHandle,
+    thread: db::Thread,
+    created: bool,
+    digest: bool,
+    prompt: &str,
+    items: &[(&db::BrainDump, i32)],
+) -> Result<()> {
+    let start_index = thread_messages(&thread).map_or(0, |m| m.len());
+    // openclaw records the exchange in the session file itself
+    let sent = openclaw::send_and_capture(&thread.agent_id, Some(&thread.session_id), None, prompt).await;
+    let end_index = thread_messages(&thread).map_or(start_index, |m| m.len());
+    if let Err(e) = sent {
+        if created {
+            let id = thread.id.clone();
+            db::call(move |conn| relations::delete(conn, relations::THREADS, &id)).await?;
+        }
+        return Err(e);
+    }
+    let followed: Vec<(String, i32)> = items.iter().map(|(item, attempts)| (item.id.clone(), *attempts)).collect();
+    let thread = db::call(move |conn| {
+        db::touch_thread(conn, &thread.id)?;
+        let sent_at = chrono::Utc::now().timestamp_millis();
+        for (item_id, attempts) in followed {
+            set_brain_dump_followed_up(conn, &item_id, &thread.id, attempts + 1)?;
+            db::create_follow_up_outcome(
+                conn,
+                &db::FollowUpOutcome {
+                    id: 0,
+                    brain_dump_id: item_id,
+                    thread_id: thread.id.clone(),
+                    attempt: attempts + 1,
+                    digest,
+                    sent_at,
+                    start_index,
+                    end_index,
+                    outcome: "pending".to_string(),
+                    replied_at: None,
+                },
+            )?;
+        }
+        Ok(db::get_thread(conn, &thread.id)?.unwrap_or(thread))
+    })
+    .await?;
+
+    for (item, _) in items {
+        dnd::emit(
+            app,
+            "braindump:followed_up",
+            serde_json::json!({
+                "brain_dump_id": item.id,
+                "thread_id": thread.id,
+                "session_id": thread.session_id,
+                "content": item.content,
+                "project_id": thread.project_id,
+                "thread": thread,
+            }),
+        );
+    }
+    let mut notice = match items {
+        [(item, _)] => {
+            let mut notice = Notice::new("Brain dump follow-up", notifications::snippet(&item.content));
+            notice.dump_id = Some(item.id.clone());
+            notice
+        }
+        _ => Notice::new(format!("Follow-up on {} brain dumps", items.len()), thread.name.clone()),
+    };
+    notice.thread_id = Some(thread.id.clone());
+    dnd::notify_notice(app, &notice.with_actions(&[Action::OpenThread, Action::Snooze, Action::MarkDone]));
+    Ok(())
+}
+
+/// Send the next follow-up for a dump in its follow-up thread, opening one
+/// (in the dump's project, with that project's agent) the first time.
+/// Returns whether a follow-up went out.
+async fn follow_up(app: &AppHandle, item: &db::BrainDump) -> Result<bool> {
+    let existing = follow_up_thread(item).await?;
+    let Some(attempts) = unanswered_attempts(app, item, existing.as_ref()).await? else {
+        return Ok(false);
+    };
+
+    let created = existing.is_none();
+    let prompt = if created {
+        prompts::build(prompts::FOLLOW_UP, &[("content", &item.content)]).await?
+    } else {
+        prompts::build(
+            prompts::FOLLOW_UP_AGAIN,
+            &[("content", &item.content), ("attempt", &(attempts + 1).to_string())],
+        )
+        .await?
+    };
+    let thread = match existing {
+        Some(thread) => thread,
+        None => {
+            let (project_id, name) = (item.project_id.clone(), thread_name(&item.content));
+            db::call(move |conn| {
+                let project = match project_id.as_deref() {
+                    Some(id) => db::get_project(conn, id)?,
+                    None => None,
+                };
+                create_follow_up_thread(conn, project, None, name)
+            })
+            .await?
+        }
+    };
+    send_follow_up(app, thread, created, false, &prompt, &[(item, attempts)]).await?;
+    Ok(true)
+}
+
+/// Follow up on all of `items` at once, as a numbered list in the shared
+/// digest thread. A failed send counts against every dump in the batch.
+async fn follow_up_digest(app: &AppHandle, items: Vec<db::BrainDump>, run: &mut db::ProactiveRun) -> Result<()> {
+    let mut batch = Vec::new();
+    for item in &items {
+        let thread = follow_up_thread(item).await?;
+        match unanswered_attempts(app, item, thread.as_ref()).await? {
+            Some(attempts) => batch.push((item, attempts)),
+            None => run.stopped += 1,
+        }
+    }
+    if batch.is_empty() {
+        return Ok(());
     }
+    let list = batch
+        .iter()
+        .enumerate()
+        .map(|(i, (item, _))| format!("{}. {}", i + 1, item.content.trim()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let prompt = prompts::build(prompts::DIGEST, &[("count", &batch.len().to_string()), ("items", &list)]).await?;
 
+    let existing = db::call(|conn| match db::get_setting(conn, DIGEST_THREAD_SETTING)? {
+        Some(id) => db::get_thread(conn, &id),
+        None => Ok(None),
+    })
+    .await?;
+    let created = existing.is_none();
+    let thread = match existing {
+        Some(thread) => thread,
+        None => {
+            db::call(|conn| {
+                let thread = create_follow_up_thread(conn, None, None, DIGEST_THREAD_NAME.to_string())?;
+                db::set_setting(conn, DIGEST_THREAD_SETTING, &thread.id)?;
+                Ok(thread)
+            })
+            .await?
+        }
+    };
+    if let Err(e) = send_follow_up(app, thread, created, true, &prompt, &batch).await {
+        for (item, _) in &batch {
+            run.failures.push(db::ProactiveFailure { brain_dump_id: item.id.clone(), error: e.to_string() });
+        }
+        return Err(e);
+    }
+    run.sent = batch.len() as u32;
     Ok(())
 }
+
+/// The dump's text on one line, cut to a title's length.
+fn thread_name(content: &str) -> String {
+    let text = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= titles::MAX_TITLE_CHARS {
+        return text;
+    }
+    let cut: String = text.chars().take(titles::MAX_TITLE_CHARS).collect();
+    format!("{}\u{2026}", cut.trim_end())
+}
+
+/// Most openclaw calls background jobs make at once, shared by all of them.
+const BACKGROUND_CONCURRENCY: usize = 3;
+static AGENT_SLOTS: Semaphore = Semaphore::const_new(BACKGROUND_CONCURRENCY);
+
+/// Run `job` on every item, at most `BACKGROUND_CONCURRENCY` at a time across
+/// all background jobs. Returns once every item is done.
+async fn run_bounded<T, F, Fut>(items: Vec<T>, job: F)
+where
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let runs = items.into_iter().map(|item| {
+        let run = job(item);
+        async move {
+            let _slot = AGENT_SLOTS.acquire().await.expect("AGENT_SLOTS is never closed");
+            run.await
+        }
+    });
+    futures::future::join_all(runs).await;
+}
@@ -0,0 +1,224 @@
+use crate::db;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+
+/// A prompt the app sends to the agent on the user's behalf. The template
+/// may be replaced per purpose; `{name}` placeholders are filled in when
+/// it's sent.
+pub struct Purpose {
+    pub key: &'static str,
+    pub description: &'static str,
+    pub variables: &'static [&'static str],
+    pub default: &'static str,
+}
+
+pub const FOLLOW_UP: &str = "follow_up";
+pub const FOLLOW_UP_AGAIN: &str = "follow_up_again";
+pub const DIGEST: &str = "digest";
+pub const TITLE: &str = "title";
+pub const TITLE_CONVERSATION: &str = "title_conversation";
+pub const SUMMARY: &str = "summary";
+pub const BRIEFING: &str = "briefing";
+pub const RESURFACE: &str = "resurface";
+pub const RETROSPECTIVE: &str = "retrospective";
+pub const DEADLINE: &str = "deadline";
+pub const COMPACTION: &str = "compaction";
+
+pub const PURPOSES: &[Purpose] = &[
+    Purpose {
+        key: FOLLOW_UP,
+        description: "First proactive follow-up on a brain dump",
+        variables: &["content"],
+        default: "I jotted this down earlier: '{content}'. Do you have thoughts, or can you help me take a first step on it?",
+    },
+    Purpose {
+        key: FOLLOW_UP_AGAIN,
+        description: "Later follow-ups on the same brain dump",
+        variables: &["content", "attempt"],
+        default: "Checking back in on '{content}'. Any progress, or is there a next step you can help me with?",
+    },
+    Purpose {
+        key: DIGEST,
+        description: "Follow-up on several brain dumps at once, in digest mode",
+        variables: &["count", "items"],
+        default: "Here are {count} things I noted recently:\n{items}\n\n\
+                  For each, give me a quick thought or a first step, and tell me which one you'd tackle first.",
+    },
+    Purpose {
+        key: TITLE,
+        description: "Thread title from its first message",
+        variables: &["content"],
+        default: "Summarize this in 3-6 words as a chat thread title (reply with just the title, no quotes): {content}",
+    },
+    Purpose {
+        key: TITLE_CONVERSATION,
+        description: "Nightly thread title refresh from the conversation",
+        variables: &["content"],
+        default: "Based on this conversation, generate a concise 3-6 word thread title (reply with just the title): {content}",
+    },
+    Purpose {
+        key: SUMMARY,
+        description: "Narrative for the weekly review note",
+        variables: &[
+            "threads_active",
+            "thread_names",
+            "dumps_captured",
+            "dumps_closed",
+            "cards_created",
+            "cards_completed",
+            "card_titles",
+        ],
+        default: "Write a short, friendly weekly review (one or two paragraphs, plain Markdown, no headings) \
+                  of my week based on these numbers. Threads active: {threads_active} ({thread_names}). \
+                  Brain dumps captured: {dumps_captured}, closed: {dumps_closed}. \
+                  Kanban cards added: {cards_created}, completed: {cards_completed} ({card_titles}).",
+    },
+    Purpose {
+        key: BRIEFING,
+        description: "Morning briefing",
+        variables: &["followed_up", "cards_due", "stale_threads", "open_dumps"],
+        default: "Write my morning briefing: a short, friendly digest in plain Markdown (a few bullet points, \
+                  no headings) of what needs my attention today. Overnight follow-ups on: {followed_up}. \
+                  Cards due today: {cards_due}. Threads gone quiet: {stale_threads}. Open brain dumps: {open_dumps}.",
+    },
+    Purpose {
+        key: RESURFACE,
+        description: "Suggested message to pick a quiet thread back up",
+        variables: &["thread", "idle_days", "transcript"],
+        default: "I left this conversation, \"{thread}\", {idle_days} days ago. This is how it ended:\n{transcript}\n\n\
+                  Write the message I should send to pick it back up: one or two sentences saying where we left off \
+                  and asking for the next step. Reply with just the message, no quotes.",
+    },
+    Purpose {
+        key: RETROSPECTIVE,
+        description: "Narrative for the weekly per-project retrospective",
+        variables: &["week", "projects"],
+        default: "Write a short retrospective of my week ({week}) in plain Markdown, no headings: a sentence or two \
+                  per project on what moved forward and what seems stuck, then one line on what to focus on next \
+                  week. Here's what happened, by project:\n{projects}",
+    },
+    Purpose {
+        key: DEADLINE,
+        description: "Nudge in a kanban card's thread the day before it's due",
+        variables: &["title", "description", "due", "project"],
+        default: "\"{title}\" ({project}) is due {due}. Notes: {description}\n\n\
+                  Draft a short plan to get it done in time, or if it's nearly there, a reminder of what's left.",
+    },
+    Purpose {
+        key: COMPACTION,
+        description: "Summary that replaces the older part of a long conversation",
+        variables: &["thread", "count", "transcript"],
+        default: "This is the start of a long conversation, \"{thread}\" ({count} messages):\n{transcript}\n\n\
+                  Summarize it so the summary can stand in for these messages: the goal, decisions made, facts and \
+                  names worth keeping, anything promised, and what was still open. Plain Markdown, as short as it \
+                  can be without losing any of that. Reply with just the summary.",
+    },
+];
+
+pub fn purpose(key: &str) -> Result<&'static Purpose> {
+    PURPOSES
+        .iter()
+        .find(|p| p.key == key)
+        .ok_or_else(|| anyhow!("Unknown prompt purpose: {:?}", key))
+}
+
+/// A purpose with the template currently in use, for the settings screen.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplate {
+    pub purpose: &'static str,
+    pub description: &'static str,
+    pub variables: &'static [&'static str],
+    pub template: String,
+    pub default_template: &'static str,
+    pub customized: bool,
+}
+
+pub fn list(conn: &rusqlite::Connection) -> Result<Vec<PromptTemplate>> {
+    PURPOSES
+        .iter()
+        .map(|p| {
+            let custom = db::get_prompt_template(conn, p.key)?;
+            Ok(PromptTemplate {
+                purpose: p.key,
+                description: p.description,
+                variables: p.variables,
+                customized: custom.is_some(),
+                template: custom.unwrap_or_else(|| p.default.to_string()),
+                default_template: p.default,
+            })
+        })
+        .collect()
+}
+
+/// Replace the template for `key`.
+pub fn save(conn: &rusqlite::Connection, key: &str, template: &str) -> Result<()> {
+    validate(key, template)?;
+    db::set_prompt_template(conn, key, template)
+}
+
+/// Whether `template` can stand in for `key`'s: not empty, and no
+/// placeholders the purpose doesn't fill.
+pub fn validate(key: &str, template: &str) -> Result<()> {
+    let purpose = purpose(key)?;
+    if template.trim().is_empty() {
+        return Err(anyhow!("Template can't be empty"));
+    }
+    if let Some(unknown) = placeholders(template).find(|name| !purpose.variables.contains(name)) {
+        return Err(anyhow!(
+            "Unknown variable {{{}}} for {}; available: {}",
+            unknown,
+            key,
+            purpose.variables.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Go back to the built-in template for `key`.
+pub fn reset(conn: &rusqlite::Connection, key: &str) -> Result<()> {
+    purpose(key)?;
+    db::delete_prompt_template(conn, key)
+}
+
+/// The prompt for `key` with `vars` filled in, using the user's template if set.
+pub async fn build(key: &str, vars: &[(&str, &str)]) -> Result<String> {
+    let purpose = purpose(key)?;
+    let key = key.to_string();
+    let custom = db::call(move |conn| db::get_prompt_template(conn, &key)).await?;
+    Ok(render(custom.as_deref().unwrap_or(purpose.default), vars))
+}
+
+/// Fill `{name}` placeholders in one pass, so braces inside the values are
+/// left alone. Unknown placeholders stay as written.
+pub fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after
+            .find('}')
+            .and_then(|close| vars.iter().find(|(name, _)| *name == &after[..close]).map(|(_, v)| (close, v)));
+        match value {
+            Some((close, value)) => {
+                out.push_str(value);
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Names between braces that look like placeholders.
+fn placeholders(template: &str) -> impl Iterator<Item = &str> {
+    template.split('{').skip(1).filter_map(|part| {
+        let name = &part[..part.find('}')?];
+        (!name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')).then_some(name)
+    })
+}
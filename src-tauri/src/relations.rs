@@ -0,0 +1,351 @@
+use crate::db;
+use anyhow::Result;
+use rusqlite::{params, Connection};
+
+pub const PROJECTS: &str = "projects";
+pub const THREADS: &str = "threads";
+pub const BRAIN_DUMPS: &str = "brain_dumps";
+pub const KANBAN_ITEMS: &str = "kanban_items";
+
+/// What a rule does to the rows pointing at its parent.
+pub enum Action {
+    /// Delete them, applying their own rules first.
+    Delete,
+    /// Run this SET clause on them; they stay.
+    Set(&'static str),
+    /// Leave them as they are, e.g. history that outlives its subject.
+    Keep,
+}
+
+pub enum Event {
+    Delete,
+    /// Reversed, for the rows it touched, when the parent is restored.
+    Archive { undo: &'static str },
+}
+
+/// Rows of `child` whose `column` holds the parent's id (and that match
+/// `filter`, if any) get `action` when the parent goes through `event`.
+/// Rules run in order, so a narrower rule can come before a broader one.
+pub struct Rule {
+    pub parent: &'static str,
+    pub event: Event,
+    pub child: &'static str,
+    pub column: &'static str,
+    pub filter: Option<&'static str>,
+    pub action: Action,
+}
+
+/// Every cascade in the app. SQLite doesn't enforce the schema's foreign
+/// keys here, so these rules are what actually happens. Archived kanban
+/// cards are the trash: cards already in it go with their parent, cards
+/// still in use are kept and detached. Attachment references go with their
+/// owner; the files themselves wait for `blobs::gc`. Filed GitHub issues
+/// stay open on GitHub when their card or dump is deleted.
+pub const RULES: &[Rule] = &[
+    // Deleting a project keeps its conversations, dumps and cards, in the inbox
+    Rule {
+        parent: PROJECTS,
+        event: Event::Delete,
+        child: THREADS,
+        column: "project_id",
+        filter: None,
+        action: Action::Set("project_id = NULL"),
+    },
+    Rule {
+        parent: PROJECTS,
+        event: Event::Delete,
+        child: BRAIN_DUMPS,
+        column: "project_id",
+        filter: None,
+        action: Action::Set("project_id = NULL"),
+    },
+    Rule {
+        parent: PROJECTS,
+        event: Event::Delete,
+        child: KANBAN_ITEMS,
+        column: "project_id",
+        filter: Some("status = 'archived'"),
+        action: Action::Delete,
+    },
+    Rule {
+        parent: PROJECTS,
+        event: Event::Delete,
+        child: KANBAN_ITEMS,
+        column: "project_id",
+        filter: None,
+        action: Action::Set("project_id = NULL"),
+    },
+    Rule {
+        parent: PROJECTS,
+        event: Event::Delete,
+        child: "entity_links",
+        column: "source_id",
+        filter: Some("source_type = 'project'"),
+        action: Action::Delete,
+    },
+    Rule {
+        parent: PROJECTS,
+        event: Event::Delete,
+        child: "entity_links",
+        column: "target_id",
+        filter: Some("target_type = 'project'"),
+        action: Action::Delete,
+    },
+    Rule {
+        parent: PROJECTS,
+        event: Event::Delete,
+        child: "sync_conflicts",
+        column: "project_id",
+        filter: None,
+        action: Action::Delete,
+    },
+    Rule {
+        parent: PROJECTS,
+        event: Event::Delete,
+        child: "github_repos",
+        column: "project_id",
+        filter: None,
+        action: Action::Delete,
+    },
+    // An archived project's threads stay with it but stop notifying
+    Rule {
+        parent: PROJECTS,
+        event: Event::Archive { undo: "muted = 0" },
+        child: THREADS,
+        column: "project_id",
+        filter: Some("muted = 0"),
+        action: Action::Set("muted = 1"),
+    },
+    Rule {
+        parent: THREADS,
+        event: Event::Delete,
+        child: "remote_outbox",
+        column: "thread_id",
+        filter: None,
+        action: Action::Delete,
+    },
+    Rule {
+        parent: THREADS,
+        event: Event::Delete,
+        child: KANBAN_ITEMS,
+        column: "thread_id",
+        filter: None,
+        action: Action::Set("thread_id = NULL"),
+    },
+    Rule {
+        parent: THREADS,
+        event: Event::Delete,
+        child: BRAIN_DUMPS,
+        column: "follow_up_thread_id",
+        filter: None,
+        action: Action::Set("follow_up_thread_id = NULL"),
+    },
+    Rule {
+        parent: THREADS,
+        event: Event::Delete,
+        child: "blob_refs",
+        column: "owner_id",
+        filter: Some("owner_type = 'threads'"),
+        action: Action::Delete,
+    },
+    Rule {
+        parent: THREADS,
+        event: Event::Delete,
+        child: "follow_up_outcomes",
+        column: "thread_id",
+        filter: None,
+        action: Action::Keep,
+    },
+    // Cards made from a dump outlive it as ordinary cards
+    Rule {
+        parent: BRAIN_DUMPS,
+        event: Event::Delete,
+        child: KANBAN_ITEMS,
+        column: "source_id",
+        filter: Some("source_type = 'brain_dump' AND status = 'archived'"),
+        action: Action::Delete,
+    },
+    Rule {
+        parent: BRAIN_DUMPS,
+        event: Event::Delete,
+        child: KANBAN_ITEMS,
+        column: "source_id",
+        filter: Some("source_type = 'brain_dump'"),
+        action: Action::Set("source_type = 'manual', source_id = NULL"),
+    },
+    Rule {
+        parent: BRAIN_DUMPS,
+        event: Event::Delete,
+        child: "blob_refs",
+        column: "owner_id",
+        filter: Some("owner_type = 'brain_dumps'"),
+        action: Action::Delete,
+    },
+    Rule {
+        parent: BRAIN_DUMPS,
+        event: Event::Delete,
+        child: "github_issues",
+        column: "owner_id",
+        filter: Some("owner_type = 'brain_dumps'"),
+        action: Action::Delete,
+    },
+    Rule {
+        parent: BRAIN_DUMPS,
+        event: Event::Delete,
+        child: "follow_up_outcomes",
+        column: "brain_dump_id",
+        filter: None,
+        action: Action::Keep,
+    },
+    Rule {
+        parent: KANBAN_ITEMS,
+        event: Event::Delete,
+        child: "kanban_activity",
+        column: "item_id",
+        filter: None,
+        action: Action::Delete,
+    },
+    Rule {
+        parent: KANBAN_ITEMS,
+        event: Event::Delete,
+        child: "kanban_dependencies",
+        column: "item_id",
+        filter: None,
+        action: Action::Delete,
+    },
+    Rule {
+        parent: KANBAN_ITEMS,
+        event: Event::Delete,
+        child: "kanban_dependencies",
+        column: "blocked_by_id",
+        filter: None,
+        action: Action::Delete,
+    },
+    Rule {
+        parent: KANBAN_ITEMS,
+        event: Event::Delete,
+        child: "blob_refs",
+        column: "owner_id",
+        filter: Some("owner_type = 'kanban_items'"),
+        action: Action::Delete,
+    },
+    Rule {
+        parent: KANBAN_ITEMS,
+        event: Event::Delete,
+        child: "github_issues",
+        column: "owner_id",
+        filter: Some("owner_type = 'kanban_items'"),
+        action: Action::Delete,
+    },
+    Rule {
+        parent: KANBAN_ITEMS,
+        event: Event::Delete,
+        child: KANBAN_ITEMS,
+        column: "parent_id",
+        filter: None,
+        action: Action::Set("parent_id = NULL"),
+    },
+];
+
+/// Delete a project, thread, brain dump or kanban card (by table name) and
+/// apply the delete rules to everything pointing at it, in one transaction.
+pub fn delete(conn: &Connection, table: &str, id: &str) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    delete_row(&tx, table, id)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// `delete` for callers already inside a transaction.
+pub fn delete_row(conn: &Connection, table: &str, id: &str) -> Result<()> {
+    for rule in RULES.iter().filter(|r| r.parent == table && matches!(r.event, Event::Delete)) {
+        let (child, matching) = (db::quote_ident(rule.child), where_clause(rule));
+        match rule.action {
+            Action::Delete if is_parent(rule.child) => {
+                let mut stmt = conn.prepare(&format!("SELECT id FROM {} WHERE {}", child, matching))?;
+                let ids = stmt
+                    .query_map(params![id], |row| row.get::<_, String>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                for child_id in ids {
+                    delete_row(conn, rule.child, &child_id)?;
+                }
+            }
+            Action::Delete => {
+                conn.execute(&format!("DELETE FROM {} WHERE {}", child, matching), params![id])?;
+            }
+            Action::Set(set) => {
+                conn.execute(&format!("UPDATE {} SET {} WHERE {}", child, set, matching), params![id])?;
+            }
+            Action::Keep => {}
+        }
+    }
+    conn.execute("DELETE FROM cascade_marks WHERE parent_type = ?1 AND parent_id = ?2", params![table, id])?;
+    conn.execute("DELETE FROM cascade_marks WHERE child_type = ?1 AND child_id = ?2", params![table, id])?;
+    conn.execute(&format!("DELETE FROM {} WHERE id = ?1", db::quote_ident(table)), params![id])?;
+    Ok(())
+}
+
+/// Apply the archive rules after `id` was archived. The rows changed are
+/// remembered so `restored` can put back exactly those.
+pub fn archived(conn: &Connection, table: &str, id: &str) -> Result<()> {
+    for rule in RULES.iter().filter(|r| r.parent == table && matches!(r.event, Event::Archive { .. })) {
+        let Action::Set(set) = rule.action else {
+            continue;
+        };
+        let (child, matching) = (db::quote_ident(rule.child), where_clause(rule));
+        let mut stmt = conn.prepare(&format!("SELECT id FROM {} WHERE {}", child, matching))?;
+        let ids = stmt
+            .query_map(params![id], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for child_id in ids {
+            conn.execute(&format!("UPDATE {} SET {} WHERE id = ?1", child, set), params![child_id])?;
+            conn.execute(
+                "INSERT OR IGNORE INTO cascade_marks (parent_type, parent_id, child_type, child_id, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![table, id, rule.child, child_id, chrono::Utc::now().timestamp_millis()],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Undo what `archived` did to rows still belonging to `id`.
+pub fn restored(conn: &Connection, table: &str, id: &str) -> Result<()> {
+    for rule in RULES.iter().filter(|r| r.parent == table) {
+        let Event::Archive { undo } = rule.event else {
+            continue;
+        };
+        conn.execute(
+            &format!(
+                "UPDATE {child} SET {undo} WHERE {column} = ?2 AND id IN
+                   (SELECT child_id FROM cascade_marks WHERE parent_type = ?1 AND parent_id = ?2 AND child_type = ?3)",
+                child = db::quote_ident(rule.child),
+                column = db::quote_ident(rule.column),
+            ),
+            params![table, id, rule.child],
+        )?;
+    }
+    conn.execute("DELETE FROM cascade_marks WHERE parent_type = ?1 AND parent_id = ?2", params![table, id])?;
+    Ok(())
+}
+
+/// Run `archived` or `restored` for a status change from `old` to `new`.
+pub fn status_changed(conn: &Connection, table: &str, id: &str, old: &str, new: &str) -> Result<()> {
+    match (old == "archived", new == "archived") {
+        (false, true) => archived(conn, table, id),
+        (true, false) => restored(conn, table, id),
+        _ => Ok(()),
+    }
+}
+
+fn is_parent(table: &str) -> bool {
+    RULES.iter().any(|r| r.parent == table)
+}
+
+fn where_clause(rule: &Rule) -> String {
+    let column = format!("{} = ?1", db::quote_ident(rule.column));
+    match rule.filter {
+        Some(filter) => format!("{} AND ({})", column, filter),
+        None => column,
+    }
+}
@@ -0,0 +1,515 @@
+use crate::calendar;
+use crate::db;
+use crate::dnd;
+use crate::maintenance;
+use crate::error::AppError;
+use crate::github;
+use crate::proactive;
+use crate::sync;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone, Timelike};
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+
+/// When a job comes due.
+pub enum Trigger {
+    /// Five-field cron expression in local time; the user may replace it.
+    Cron(&'static str),
+    /// A fixed interval since the last run.
+    Every(Duration),
+    /// Worked out from the job's own settings: the next run after the given
+    /// time, or `None` while there's nothing to schedule.
+    Custom(fn(DateTime<Local>) -> Option<DateTime<Local>>),
+}
+
+/// A background job. Returns a one-line summary for the run history.
+pub struct Job {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub trigger: Trigger,
+    /// Run an occurrence missed while the app was closed as soon as it starts.
+    pub catch_up: bool,
+    pub run: fn(AppHandle) -> BoxFuture<'static, Result<String>>,
+}
+
+pub const PROACTIVE: &str = "proactive";
+pub const FOLLOW_UP_OUTCOMES: &str = "follow_up_outcomes";
+pub const TITLE_REFRESH: &str = "title_refresh";
+pub const RECURRENCE: &str = "recurrence";
+pub const DEADLINE_NUDGES: &str = "deadline_nudges";
+pub const KANBAN_EXPORT: &str = "kanban_export";
+pub const WEEKLY_REVIEW: &str = "weekly_review";
+pub const RETROSPECTIVE: &str = "retrospective";
+pub const BRIEFING: &str = "briefing";
+pub const RESURFACE: &str = "resurface";
+pub const OBSIDIAN_SYNC: &str = "obsidian_sync";
+pub const DND_DRAIN: &str = "dnd_drain";
+pub const MAINTENANCE: &str = "maintenance";
+pub const SYNC: &str = "sync";
+pub const GITHUB_ISSUES: &str = "github_issues";
+pub const CALENDAR_EXPORT: &str = "calendar_export";
+
+pub static JOBS: &[Job] = &[
+    Job {
+        id: PROACTIVE,
+        description: "Brain dump follow-ups",
+        trigger: Trigger::Custom(proactive::next_follow_up_run),
+        catch_up: false,
+        run: |app| Box::pin(proactive::follow_up_job(app)),
+    },
+    Job {
+        id: FOLLOW_UP_OUTCOMES,
+        description: "Check follow-ups for replies",
+        trigger: Trigger::Every(Duration::from_secs(15 * 60)),
+        catch_up: false,
+        run: |app| Box::pin(proactive::outcome_job(app)),
+    },
+    Job {
+        id: TITLE_REFRESH,
+        description: "Refresh stale thread titles",
+        trigger: Trigger::Cron("55 23 * * *"),
+        // A night the app was closed is made up for the next time it starts
+        catch_up: true,
+        run: |app| Box::pin(proactive::title_refresh_job(app)),
+    },
+    Job {
+        id: RECURRENCE,
+        description: "Respawn recurring kanban cards",
+        trigger: Trigger::Every(Duration::from_secs(15 * 60)),
+        catch_up: false,
+        run: |app| Box::pin(proactive::recurrence_job(app)),
+    },
+    Job {
+        id: DEADLINE_NUDGES,
+        description: "Nudge about cards due within a day, and notify overdue ones",
+        trigger: Trigger::Every(Duration::from_secs(15 * 60)),
+        catch_up: false,
+        run: |app| Box::pin(proactive::deadline_nudge_job(app)),
+    },
+    Job {
+        id: KANBAN_EXPORT,
+        description: "Export the kanban board to the vault",
+        trigger: Trigger::Every(Duration::from_secs(30)),
+        catch_up: false,
+        run: |_| Box::pin(proactive::kanban_export_job()),
+    },
+    Job {
+        id: WEEKLY_REVIEW,
+        description: "Weekly review note",
+        trigger: Trigger::Cron("0 18 * * 0"),
+        catch_up: false,
+        run: |app| Box::pin(proactive::weekly_review_job(app)),
+    },
+    Job {
+        id: RETROSPECTIVE,
+        description: "Weekly per-project retrospective",
+        trigger: Trigger::Cron("30 18 * * 0"),
+        catch_up: false,
+        run: |app| Box::pin(proactive::retrospective_job(app)),
+    },
+    Job {
+        id: BRIEFING,
+        description: "Morning briefing",
+        trigger: Trigger::Custom(proactive::next_briefing_run),
+        catch_up: false,
+        run: |app| Box::pin(proactive::briefing_job(app)),
+    },
+    Job {
+        id: RESURFACE,
+        description: "Resurface quiet threads",
+        trigger: Trigger::Every(Duration::from_secs(6 * 60 * 60)),
+        catch_up: false,
+        run: |app| Box::pin(proactive::resurface_job(app)),
+    },
+    Job {
+        id: OBSIDIAN_SYNC,
+        description: "Full Obsidian vault sync",
+        trigger: Trigger::Cron("0 * * * *"),
+        catch_up: false,
+        run: |app| Box::pin(async move { tauri::async_runtime::spawn_blocking(move || crate::sync_vault_now(&app)).await? }),
+    },
+    Job {
+        id: DND_DRAIN,
+        description: "Deliver what was held during do-not-disturb, and snoozed notifications",
+        trigger: Trigger::Custom(dnd::next_drain_run),
+        catch_up: false,
+        run: |app| Box::pin(dnd::drain_job(app)),
+    },
+    Job {
+        id: MAINTENANCE,
+        description: "Database upkeep and orphan cleanup",
+        trigger: Trigger::Cron("0 4 * * 0"),
+        catch_up: true,
+        run: |app| Box::pin(maintenance::maintenance_job(app)),
+    },
+    Job {
+        id: SYNC,
+        description: "Sync with other devices",
+        trigger: Trigger::Every(Duration::from_secs(10 * 60)),
+        catch_up: false,
+        run: |app| Box::pin(sync::sync_job(app)),
+    },
+    Job {
+        id: GITHUB_ISSUES,
+        description: "Check filed GitHub issues for closes",
+        trigger: Trigger::Every(Duration::from_secs(30 * 60)),
+        catch_up: false,
+        run: |app| Box::pin(github::issue_state_job(app)),
+    },
+    Job {
+        id: CALENDAR_EXPORT,
+        description: "Mirror due cards into Reminders or a calendar file",
+        trigger: Trigger::Every(Duration::from_secs(5 * 60)),
+        catch_up: false,
+        run: |_| Box::pin(calendar::export_job()),
+    },
+];
+
+pub fn job(id: &str) -> Result<&'static Job> {
+    JOBS.iter().find(|j| j.id == id).ok_or_else(|| anyhow!("Unknown job: {:?}", id))
+}
+
+/// The loop rechecks at least this often, so changed settings are seen
+/// without an explicit reload.
+const MAX_WAIT: Duration = Duration::from_secs(60);
+
+/// Wakes the scheduler to recompute due times.
+static CHANGED: Notify = Notify::const_new();
+/// Due times as last computed by the loop, epoch ms.
+static NEXT_RUNS: Mutex<BTreeMap<&str, i64>> = Mutex::new(BTreeMap::new());
+static RUNNING: Mutex<BTreeSet<&str>> = Mutex::new(BTreeSet::new());
+/// Each job's last error, so a job failing the same way every run reports it once.
+static LAST_ERRORS: Mutex<BTreeMap<&str, String>> = Mutex::new(BTreeMap::new());
+
+/// Recompute due times now, after a schedule or setting changed.
+pub fn reload() {
+    CHANGED.notify_one();
+}
+
+/// When the loop expects to run `id` next, epoch ms.
+pub fn next_run_at(id: &str) -> Option<i64> {
+    NEXT_RUNS.lock().unwrap().get(id).copied()
+}
+
+/// Run every enabled job as it comes due, each in its own task so a slow
+/// job doesn't hold up the rest. A job never overlaps itself.
+pub async fn run_scheduler(app: AppHandle) {
+    let started = Local::now();
+    loop {
+        let nexts = match next_runs(started).await {
+            Ok(nexts) => nexts,
+            Err(e) => {
+                eprintln!("[scheduler] Error: {}", e);
+                tokio::time::sleep(MAX_WAIT).await;
+                continue;
+            }
+        };
+
+        let now = Local::now();
+        let mut wake = now + chrono::Duration::from_std(MAX_WAIT).unwrap_or_default();
+        let mut next_runs = BTreeMap::new();
+        for (job, next) in JOBS.iter().zip(nexts) {
+            let Some(next) = next else {
+                continue;
+            };
+            next_runs.insert(job.id, next.timestamp_millis());
+            if next > now {
+                wake = wake.min(next);
+            } else if claim(job.id) {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let _ = run_claimed(&app, job, false).await;
+                });
+            }
+        }
+        *NEXT_RUNS.lock().unwrap() = next_runs;
+
+        let wait = (wake - Local::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = CHANGED.notified() => {}
+        }
+    }
+}
+
+/// `next_run` for every job, in `JOBS` order. Custom triggers read their
+/// settings from the database too, so the whole lot runs off the runtime.
+async fn next_runs(started: DateTime<Local>) -> Result<Vec<Option<DateTime<Local>>>> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let records = db::background().and_then(|conn| {
+            JOBS.iter()
+                .map(|job| db::get_job(&conn, job.id))
+                .collect::<Result<Vec<_>>>()
+        })?;
+        Ok(JOBS
+            .iter()
+            .zip(&records)
+            .map(|(job, record)| next_run(job, record.as_ref(), started))
+            .collect())
+    })
+    .await?
+}
+
+/// When `job` is next due, or `None` while it's disabled or has nothing
+/// scheduled. Counts from its last run, but never from before `started`
+/// unless the job catches up on missed runs.
+fn next_run(job: &Job, record: Option<&db::JobRecord>, started: DateTime<Local>) -> Option<DateTime<Local>> {
+    if !record.is_none_or(|r| r.enabled) {
+        return None;
+    }
+    let last = record
+        .and_then(|r| r.last_run_at)
+        .and_then(|ms| Local.timestamp_millis_opt(ms).single());
+    let after = match last {
+        Some(last) if job.catch_up || last > started => last,
+        _ => started,
+    };
+    match &job.trigger {
+        Trigger::Cron(default) => {
+            let expr = record.and_then(|r| r.schedule.as_deref()).unwrap_or(default);
+            Cron::parse(expr).ok()?.next_after(after)
+        }
+        Trigger::Every(interval) => Some(after + chrono::Duration::from_std(*interval).ok()?),
+        Trigger::Custom(next) => next(after),
+    }
+}
+
+fn claim(id: &'static str) -> bool {
+    RUNNING.lock().unwrap().insert(id)
+}
+
+/// Run the job `id` now, outside its schedule. Fails if it's already running.
+pub async fn run_job(app: &AppHandle, id: &str) -> Result<String, AppError> {
+    let job = job(id).map_err(|e| AppError::NotFound(e.to_string()))?;
+    if !claim(job.id) {
+        return Err(AppError::InvalidInput(format!("{} is already running", job.description)));
+    }
+    run_claimed(app, job, true).await
+}
+
+/// Run a job already claimed in `RUNNING`, record it in the history and emit
+/// `job:finished`. New failures of scheduled runs also go out as `app:error`.
+async fn run_claimed(app: &AppHandle, job: &'static Job, manual: bool) -> Result<String, AppError> {
+    let started_at = chrono::Utc::now().timestamp_millis();
+    let run_id = db::call(move |conn| db::start_job_run(conn, job.id, started_at, manual)).await;
+    let result = (job.run)(app.clone()).await.map_err(AppError::from);
+    RUNNING.lock().unwrap().remove(job.id);
+    reload();
+
+    let finished_at = chrono::Utc::now().timestamp_millis();
+    let (detail, error) = match &result {
+        Ok(detail) => (Some(detail.clone()), None),
+        Err(e) => (None, Some(e.message())),
+    };
+    let recorded = match run_id {
+        Ok(run_id) => {
+            let (detail, error) = (detail.clone(), error.clone());
+            db::call(move |conn| db::finish_job_run(conn, run_id, finished_at, detail.as_deref(), error.as_deref()))
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = recorded {
+        eprintln!("[scheduler] Failed to record {} run: {}", job.id, e);
+    }
+    match &result {
+        Ok(_) => {
+            LAST_ERRORS.lock().unwrap().remove(job.id);
+        }
+        Err(e) => {
+            eprintln!("[{}] Error: {}", job.id, e);
+            let previous = LAST_ERRORS.lock().unwrap().insert(job.id, e.message());
+            if !manual && previous != error {
+                e.emit(app, job.id);
+            }
+        }
+    }
+    let _ = app.emit(
+        "job:finished",
+        serde_json::json!({
+            "jobId": job.id,
+            "manual": manual,
+            "ok": result.is_ok(),
+            "detail": detail,
+            "error": error,
+        }),
+    );
+    result
+}
+
+/// A job as the settings screen shows it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStatus {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub enabled: bool,
+    /// Cron expression in effect; `None` for jobs timed another way.
+    pub schedule: Option<String>,
+    pub default_schedule: Option<&'static str>,
+    pub running: bool,
+    pub last_run_at: Option<i64>,
+    pub next_run_at: Option<i64>,
+    pub last_run: Option<db::JobRun>,
+}
+
+pub fn list(conn: &rusqlite::Connection) -> Result<Vec<JobStatus>> {
+    JOBS.iter()
+        .map(|job| {
+            let record = db::get_job(conn, job.id)?;
+            let default_schedule = match job.trigger {
+                Trigger::Cron(expr) => Some(expr),
+                _ => None,
+            };
+            Ok(JobStatus {
+                id: job.id,
+                description: job.description,
+                enabled: record.as_ref().is_none_or(|r| r.enabled),
+                schedule: default_schedule
+                    .map(|d| record.as_ref().and_then(|r| r.schedule.clone()).unwrap_or_else(|| d.to_string())),
+                default_schedule,
+                running: RUNNING.lock().unwrap().contains(job.id),
+                last_run_at: record.and_then(|r| r.last_run_at),
+                next_run_at: next_run_at(job.id),
+                last_run: db::list_job_runs(conn, Some(job.id), 1)?.into_iter().next(),
+            })
+        })
+        .collect()
+}
+
+pub fn set_enabled(conn: &rusqlite::Connection, id: &str, enabled: bool) -> Result<()> {
+    db::set_job_enabled(conn, job(id)?.id, enabled)?;
+    reload();
+    Ok(())
+}
+
+/// Replace a cron job's schedule; `None` goes back to its default.
+pub fn set_schedule(conn: &rusqlite::Connection, id: &str, schedule: Option<&str>) -> Result<()> {
+    let job = job(id)?;
+    if !matches!(job.trigger, Trigger::Cron(_)) {
+        return Err(anyhow!("{} has no cron schedule to change", job.description));
+    }
+    let schedule = schedule.map(str::trim).filter(|s| !s.is_empty());
+    if let Some(expr) = schedule {
+        Cron::parse(expr)?;
+    }
+    db::set_job_schedule(conn, job.id, schedule)?;
+    reload();
+    Ok(())
+}
+
+// ── Cron expressions ─────────────────────────────────────────────────────────
+
+/// "minute hour day-of-month month day-of-week", each a `*`, number, range
+/// or comma list with an optional `/step`. Weekdays run 0 (Sunday) to 6,
+/// with 7 also Sunday. As in cron, a day matches either day field when
+/// both are restricted.
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(anyhow!(
+                "Cron schedules have five fields (minute hour day month weekday), got {:?}",
+                expr
+            ));
+        };
+        let mut weekdays = cron_field(weekday, 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Self {
+            minutes: cron_field(minute, 0, 59)?,
+            hours: cron_field(hour, 0, 23)?,
+            days: cron_field(day, 1, 31)?,
+            months: cron_field(month, 1, 12)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+
+    /// The first matching minute after `t`, looking up to four years ahead
+    /// so a 29 February schedule is found. Minutes skipped by a DST change
+    /// don't match.
+    pub fn next_after(&self, t: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = t.naive_local().with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        let mut date = start.date();
+        for _ in 0..4 * 366 {
+            if self.matches_date(date) {
+                let first_hour = if date == start.date() { start.hour() } else { 0 };
+                for hour in (first_hour..24).filter(|h| self.hours & (1 << h) != 0) {
+                    let first_minute = if date == start.date() && hour == first_hour { start.minute() } else { 0 };
+                    for minute in (first_minute..60).filter(|m| self.minutes & (1 << m) != 0) {
+                        let at = Local.from_local_datetime(&date.and_hms_opt(hour, minute, 0)?).earliest();
+                        if let Some(at) = at.filter(|at| *at > t) {
+                            return Some(at);
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+/// Bitmask of the values `spec` allows between `min` and `max`.
+fn cron_field(spec: &str, min: u32, max: u32) -> Result<u64> {
+    let invalid = || anyhow!("Invalid cron field {:?} (values {}-{})", spec, min, max);
+    let mut bits = 0u64;
+    for part in spec.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step.parse::<usize>().ok().filter(|s| *s > 0).ok_or_else(invalid)?;
+                (range, Some(step))
+            }
+            None => (part, None),
+        };
+        let num = |s: &str| s.parse::<u32>().map_err(|_| invalid());
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (num(lo)?, num(hi)?)
+        } else {
+            // "5/15" runs from 5 to the end in steps of 15 ("5/1" every value from 5)
+            let value = num(range)?;
+            (value, if step.is_some() { max } else { value })
+        };
+        if lo < min || hi > max || lo > hi {
+            return Err(invalid());
+        }
+        for value in (lo..=hi).step_by(step.unwrap_or(1)) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
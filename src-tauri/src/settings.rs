@@ -0,0 +1,450 @@
+use crate::{calendar, db, dnd, mcp, obsidian, openclaw, proactive, sync, titles, watcher};
+use anyhow::{anyhow, Result};
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::OnceLock;
+use tokio::sync::broadcast;
+
+pub const VAULT_PATH_SETTING: &str = "obsidian_vault_path";
+
+/// The values a setting accepts. Values are stored as text: strings as they
+/// are, everything else as JSON, so older rows read back unchanged.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Kind {
+    Bool,
+    Int { min: i64, max: i64 },
+    Text,
+    /// One of `options`; "" means the feature is off.
+    Choice { options: &'static [&'static str] },
+    /// Local "HH:MM".
+    Time,
+    /// An object or array the owning module parses and checks itself.
+    Json,
+}
+
+/// A setting the app knows about. `default` is in stored form and applies
+/// while the setting is unset.
+pub struct Setting {
+    pub key: &'static str,
+    pub description: &'static str,
+    pub kind: Kind,
+    pub default: Option<&'static str>,
+}
+
+pub const SETTINGS: &[Setting] = &[
+    Setting {
+        key: dnd::DND_SETTING,
+        description: "Hold notifications and background events",
+        kind: Kind::Bool,
+        default: Some("false"),
+    },
+    Setting {
+        key: dnd::DND_UNTIL_SETTING,
+        description: "When do-not-disturb switches itself off, epoch ms",
+        kind: Kind::Int { min: 0, max: i64::MAX },
+        default: None,
+    },
+    Setting {
+        key: dnd::FOLLOW_FOCUS_SETTING,
+        description: "Also hold events while a macOS Focus is on",
+        kind: Kind::Bool,
+        default: Some("false"),
+    },
+    Setting {
+        key: VAULT_PATH_SETTING,
+        description: "Obsidian vault to sync with",
+        kind: Kind::Text,
+        default: None,
+    },
+    Setting {
+        key: obsidian::LAYOUT_SETTING,
+        description: "Where project notes live in the vault",
+        kind: Kind::Json,
+        default: None,
+    },
+    Setting {
+        key: obsidian::TASK_SYNC_SETTING,
+        description: "Sync kanban cards with tasks in project notes",
+        kind: Kind::Bool,
+        default: Some("false"),
+    },
+    Setting {
+        key: obsidian::DAILY_NOTES_SETTING,
+        description: "Append brain dumps to the daily note",
+        kind: Kind::Bool,
+        default: Some("false"),
+    },
+    Setting {
+        key: "obsidian_daily_folder",
+        description: "Vault folder holding daily notes",
+        kind: Kind::Text,
+        default: None,
+    },
+    Setting {
+        key: "obsidian_daily_heading",
+        description: "Heading brain dumps go under in the daily note",
+        kind: Kind::Text,
+        default: Some(obsidian::DEFAULT_DAILY_HEADING),
+    },
+    Setting {
+        key: "obsidian_daily_template",
+        description: "Template for new daily notes, relative to the vault",
+        kind: Kind::Text,
+        default: None,
+    },
+    Setting {
+        key: obsidian::KANBAN_EXPORT_SETTING,
+        description: "Export the kanban board to the vault",
+        kind: Kind::Choice { options: &["", "single", "per_project"] },
+        default: Some(""),
+    },
+    Setting {
+        key: "obsidian_kanban_export_folder",
+        description: "Vault folder for kanban exports",
+        kind: Kind::Text,
+        default: Some(obsidian::DEFAULT_KANBAN_EXPORT_FOLDER),
+    },
+    Setting {
+        key: obsidian::WEEKLY_REVIEW_SETTING,
+        description: "Write a weekly review note",
+        kind: Kind::Bool,
+        default: Some("false"),
+    },
+    Setting {
+        key: "obsidian_weekly_review_folder",
+        description: "Vault folder for weekly reviews",
+        kind: Kind::Text,
+        default: Some(obsidian::DEFAULT_WEEKLY_REVIEW_FOLDER),
+    },
+    Setting {
+        key: obsidian::RETROSPECTIVE_SETTING,
+        description: "Write a weekly retrospective per project",
+        kind: Kind::Bool,
+        default: Some("false"),
+    },
+    Setting {
+        key: "obsidian_retrospective_folder",
+        description: "Vault folder for retrospectives",
+        kind: Kind::Text,
+        default: Some(obsidian::DEFAULT_RETROSPECTIVE_FOLDER),
+    },
+    Setting {
+        key: "obsidian_chat_export_folder",
+        description: "Vault folder chats are exported to",
+        kind: Kind::Text,
+        default: Some(obsidian::DEFAULT_CHAT_EXPORT_FOLDER),
+    },
+    Setting {
+        key: "obsidian_write_back",
+        description: "Let the agent update notes in the vault",
+        kind: Kind::Bool,
+        default: Some("false"),
+    },
+    Setting {
+        key: openclaw::BINARY_SETTING,
+        description: "Path to the openclaw binary",
+        kind: Kind::Text,
+        default: None,
+    },
+    Setting {
+        key: openclaw::PATH_SETTING,
+        description: "PATH openclaw runs with",
+        kind: Kind::Text,
+        default: None,
+    },
+    Setting {
+        key: openclaw::RETENTION_SETTING,
+        description: "What happens to a session file once its thread is deleted",
+        kind: Kind::Choice { options: &["keep", "archive", "delete"] },
+        default: Some("keep"),
+    },
+    Setting {
+        key: openclaw::TIMEOUT_SETTING,
+        description: "Seconds an agent run may take",
+        kind: Kind::Int { min: 1, max: 24 * 60 * 60 },
+        default: Some("600"),
+    },
+    Setting {
+        key: openclaw::LOG_SETTING,
+        description: "What the agent invocation log keeps",
+        kind: Kind::Choice { options: &["off", "redacted", "full"] },
+        default: Some("off"),
+    },
+    Setting {
+        key: proactive::SCHEDULE_SETTING,
+        description: "Follow-up interval, active days and quiet hours",
+        kind: Kind::Json,
+        default: None,
+    },
+    Setting {
+        key: proactive::PAUSED_SETTING,
+        description: "Pause proactive follow-ups",
+        kind: Kind::Bool,
+        default: Some("false"),
+    },
+    Setting {
+        key: proactive::MODE_SETTING,
+        description: "A thread per brain dump, or one digest message",
+        kind: Kind::Choice { options: &["per_dump", "digest"] },
+        default: Some("per_dump"),
+    },
+    Setting {
+        key: proactive::DIGEST_THREAD_SETTING,
+        description: "Thread digests are sent to; set by the app",
+        kind: Kind::Text,
+        default: None,
+    },
+    Setting {
+        key: proactive::RESURFACE_DAYS_SETTING,
+        description: "Days before a quiet thread is resurfaced; 0 turns it off",
+        kind: Kind::Int { min: 0, max: 365 },
+        default: Some("7"),
+    },
+    Setting {
+        key: proactive::BRIEFING_TIME_SETTING,
+        description: "When the morning briefing is written; empty turns it off",
+        kind: Kind::Time,
+        default: None,
+    },
+    Setting {
+        key: titles::MODEL_SETTING,
+        description: "Model used for thread titles",
+        kind: Kind::Text,
+        default: None,
+    },
+    Setting {
+        key: watcher::MODE_SETTING,
+        description: "Watch files with OS events, or always poll",
+        kind: Kind::Choice { options: &["auto", "poll"] },
+        default: Some("auto"),
+    },
+    Setting {
+        key: watcher::DISCOVERY_SETTING,
+        description: "What to do with sessions started outside the app",
+        kind: Kind::Choice { options: &["off", "notify", "create"] },
+        default: Some("create"),
+    },
+    Setting {
+        key: sync::TARGET_SETTING,
+        description: "Sync with other devices through a shared folder or the SSH host",
+        kind: Kind::Choice { options: &["", "folder", "remote"] },
+        default: Some(""),
+    },
+    Setting {
+        key: sync::FOLDER_SETTING,
+        description: "Shared folder to sync through, e.g. in iCloud Drive or Syncthing",
+        kind: Kind::Text,
+        default: None,
+    },
+    Setting {
+        key: calendar::EXPORT_SETTING,
+        description: "Mirror cards with due dates into Apple Reminders or an .ics calendar file",
+        kind: Kind::Choice { options: &["", "reminders", "ics"] },
+        default: Some(""),
+    },
+    Setting {
+        key: calendar::ICS_PATH_SETTING,
+        description: "Where the .ics file goes (default ~/.openclaw/chat/due-cards.ics)",
+        kind: Kind::Text,
+        default: None,
+    },
+    Setting {
+        key: calendar::REMINDERS_LIST_SETTING,
+        description: "Reminders list for due cards",
+        kind: Kind::Text,
+        default: Some(calendar::DEFAULT_REMINDERS_LIST),
+    },
+    Setting {
+        key: mcp::ENABLED_SETTING,
+        description: "Let agents read and change the workspace over MCP",
+        kind: Kind::Bool,
+        default: Some("false"),
+    },
+    Setting {
+        key: mcp::PORT_SETTING,
+        description: "Local port the MCP server listens on",
+        kind: Kind::Int { min: 1024, max: 65535 },
+        default: Some(mcp::DEFAULT_PORT),
+    },
+    Setting {
+        key: mcp::READ_ONLY_SETTING,
+        description: "Only offer MCP tools that read the workspace",
+        kind: Kind::Bool,
+        default: Some("false"),
+    },
+    Setting {
+        key: mcp::TOKEN_SETTING,
+        description: "Bearer token MCP clients connect with; set by the app",
+        kind: Kind::Text,
+        default: None,
+    },
+];
+
+pub fn setting(key: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|s| s.key == key)
+}
+
+/// Sent to `subscribe` receivers after every `set`; the app passes it on
+/// to the UI as "settings:changed".
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingChanged {
+    pub key: String,
+    pub value: Value,
+}
+
+static CHANGES: OnceLock<broadcast::Sender<SettingChanged>> = OnceLock::new();
+
+fn changes() -> &'static broadcast::Sender<SettingChanged> {
+    CHANGES.get_or_init(|| broadcast::channel(64).0)
+}
+
+/// Every change saved through `set` from now on.
+pub fn subscribe() -> broadcast::Receiver<SettingChanged> {
+    changes().subscribe()
+}
+
+/// A setting's current value, with its default applied. Unset settings
+/// without a default, and unknown keys that were never saved, are null.
+pub fn get(conn: &Connection, key: &str) -> Result<Value> {
+    let stored = db::get_setting(conn, key)?;
+    let Some(setting) = setting(key) else {
+        return Ok(stored.map_or(Value::Null, Value::String));
+    };
+    Ok(stored
+        .as_deref()
+        .or(setting.default)
+        .map_or(Value::Null, |raw| decode(&setting.kind, raw)))
+}
+
+pub fn get_bool(conn: &Connection, key: &str) -> Result<bool> {
+    Ok(get(conn, key)?.as_bool().unwrap_or(false))
+}
+
+pub fn get_int(conn: &Connection, key: &str) -> Result<Option<i64>> {
+    Ok(get(conn, key)?.as_i64())
+}
+
+/// Text, choice and time settings; empty counts as unset.
+pub fn get_text(conn: &Connection, key: &str) -> Result<Option<String>> {
+    Ok(get(conn, key)?.as_str().filter(|s| !s.is_empty()).map(str::to_string))
+}
+
+/// Check `value` against the setting's kind, save it and announce the change.
+/// Null clears the setting back to its default. Strings are accepted for
+/// every kind ("true", "42", JSON text) so older callers keep working.
+/// Unknown keys are stored as text.
+pub fn set(conn: &Connection, key: &str, value: &Value) -> Result<()> {
+    let stored = match setting(key) {
+        Some(setting) => encode(setting, value)?,
+        None => match value {
+            Value::Null => None,
+            Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        },
+    };
+    match &stored {
+        Some(raw) => db::set_setting(conn, key, raw)?,
+        None => db::delete_setting(conn, key)?,
+    }
+    // Nobody listening yet is fine
+    let _ = changes().send(SettingChanged {
+        key: key.to_string(),
+        value: get(conn, key)?,
+    });
+    Ok(())
+}
+
+/// Every known setting with its current value, for the settings screen.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingValue {
+    pub key: &'static str,
+    pub description: &'static str,
+    pub kind: &'static Kind,
+    pub value: Value,
+    pub default: Value,
+    pub customized: bool,
+}
+
+pub fn list(conn: &Connection) -> Result<Vec<SettingValue>> {
+    SETTINGS
+        .iter()
+        .map(|s| {
+            Ok(SettingValue {
+                key: s.key,
+                description: s.description,
+                kind: &s.kind,
+                value: get(conn, s.key)?,
+                default: s.default.map_or(Value::Null, |raw| decode(&s.kind, raw)),
+                customized: db::get_setting(conn, s.key)?.is_some(),
+            })
+        })
+        .collect()
+}
+
+fn decode(kind: &Kind, raw: &str) -> Value {
+    match kind {
+        Kind::Bool => Value::Bool(raw == "true"),
+        Kind::Int { .. } => raw.trim().parse::<i64>().map_or(Value::Null, Value::from),
+        Kind::Json => serde_json::from_str(raw).unwrap_or(Value::Null),
+        Kind::Text | Kind::Choice { .. } | Kind::Time => Value::String(raw.to_string()),
+    }
+}
+
+/// The stored form of `value`, or None to clear the setting.
+fn encode(setting: &Setting, value: &Value) -> Result<Option<String>> {
+    let invalid = || anyhow!("Invalid value for {}: {}", setting.key, value);
+    if value.is_null() {
+        return Ok(None);
+    }
+    let text = value.as_str();
+    let stored = match &setting.kind {
+        Kind::Bool => match (value.as_bool(), text) {
+            (Some(b), _) => b.to_string(),
+            (_, Some(s @ ("true" | "false"))) => s.to_string(),
+            _ => return Err(invalid()),
+        },
+        Kind::Int { min, max } => {
+            if text.is_some_and(|s| s.trim().is_empty()) {
+                return Ok(None);
+            }
+            let n = value
+                .as_i64()
+                .or_else(|| text.and_then(|s| s.trim().parse().ok()))
+                .ok_or_else(invalid)?;
+            if n < *min || n > *max {
+                return Err(anyhow!("{} must be between {} and {}", setting.key, min, max));
+            }
+            n.to_string()
+        }
+        Kind::Text => text.ok_or_else(invalid)?.to_string(),
+        Kind::Choice { options } => {
+            let s = text.ok_or_else(invalid)?;
+            if !options.contains(&s) {
+                return Err(anyhow!("{} must be one of {}", setting.key, options.join(", ")));
+            }
+            s.to_string()
+        }
+        Kind::Time => {
+            let s = text.ok_or_else(invalid)?;
+            if !s.is_empty() && proactive::parse_hhmm(s).is_none() {
+                return Err(anyhow!("{} must be HH:MM, got {:?}", setting.key, s));
+            }
+            s.to_string()
+        }
+        Kind::Json => {
+            let parsed = match text {
+                Some(s) => serde_json::from_str::<Value>(s).map_err(|_| invalid())?,
+                None => value.clone(),
+            };
+            if !(parsed.is_object() || parsed.is_array()) {
+                return Err(invalid());
+            }
+            parsed.to_string()
+        }
+    };
+    Ok(Some(stored))
+}
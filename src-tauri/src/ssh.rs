@@ -1,9 +1,44 @@
+use crate::{db, keychain, openclaw};
 use anyhow::{anyhow, Result};
+use futures::StreamExt;
+use openssh_sftp_client::error::SftpErrorKind;
+use openssh_sftp_client::file::TokioCompatFile;
+use openssh_sftp_client::{Sftp, SftpOptions};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::io::AsyncBufReadExt;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt};
+use tokio::sync::{mpsc, oneshot, watch, Mutex};
+
+/// How often the master connection pings the server, and how often the
+/// keepalive task checks the session is still usable.
+const KEEPALIVE_INTERVAL_SECS: u64 = 30;
+const CONNECT_TIMEOUT_SECS: u64 = 15;
+/// Reconnect attempts before giving up (backoff doubles from 2s).
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+/// SFTP transfers move (and report progress) in chunks of this size.
+const SFTP_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Host keys the user confirmed, kept apart from ~/.ssh/known_hosts.
+const KNOWN_HOSTS_FILE: &str = "known_hosts";
+
+/// Service name for SSH passwords in the OS keychain.
+const KEYCHAIN_SERVICE: &str = "openclaw-chat-ssh";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SshAuthMethod {
+    /// Only the key at `key_path`.
+    #[default]
+    Key,
+    /// Whatever identities the running ssh-agent offers.
+    Agent,
+    /// Password from the OS keychain (see `store_password`).
+    Password,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SshConfig {
@@ -11,6 +46,21 @@ pub struct SshConfig {
     pub port: u16,
     pub user: String,
     pub key_path: String,
+    #[serde(default)]
+    pub auth_method: SshAuthMethod,
+    /// Remote openclaw binary; `openclaw` on the remote PATH when unset.
+    #[serde(default)]
+    pub openclaw_bin: Option<String>,
+    /// Remote OpenClaw state dir, relative to $HOME unless absolute
+    /// (`.openclaw` when unset).
+    #[serde(default)]
+    pub state_dir: Option<String>,
+    /// Extra environment for every remote openclaw invocation.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Root of the remote file browser; the state dir when unset.
+    #[serde(default)]
+    pub files_dir: Option<String>,
 }
 
 impl Default for SshConfig {
@@ -20,7 +70,58 @@ impl Default for SshConfig {
             port: 22,
             user: "clawdbot1".to_string(),
             key_path: "~/.ssh/id_ed25519".to_string(),
+            auth_method: SshAuthMethod::Key,
+            openclaw_bin: None,
+            state_dir: None,
+            env: BTreeMap::new(),
+            files_dir: None,
+        }
+    }
+}
+
+impl SshConfig {
+    /// Keychain account the password for this host is stored under.
+    pub fn keychain_account(&self) -> String {
+        format!("{}@{}:{}", self.user, self.host, self.port)
+    }
+
+    pub fn openclaw_bin(&self) -> &str {
+        match self.openclaw_bin.as_deref().map(str::trim) {
+            Some(bin) if !bin.is_empty() => bin,
+            _ => "openclaw",
+        }
+    }
+
+    /// State dir as a path SFTP and escaped arguments understand: `~/` is
+    /// dropped since both already resolve relative paths from $HOME.
+    pub fn state_dir(&self) -> &str {
+        match self.state_dir.as_deref().map(str::trim) {
+            Some(dir) if !dir.is_empty() => home_relative(dir),
+            _ => ".openclaw",
+        }
+    }
+
+    pub fn files_dir(&self) -> &str {
+        match self.files_dir.as_deref().map(str::trim) {
+            Some(dir) if !dir.is_empty() => home_relative(dir),
+            _ => self.state_dir(),
+        }
+    }
+
+    pub fn agents_dir(&self) -> String {
+        format!("{}/agents", self.state_dir())
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for key in self.env.keys() {
+            let valid = !key.is_empty()
+                && !key.starts_with(|c: char| c.is_ascii_digit())
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid {
+                return Err(anyhow!("Invalid environment variable name: {:?}", key));
+            }
         }
+        Ok(())
     }
 }
 
@@ -29,13 +130,100 @@ pub enum ConnectionStatus {
     Disconnected,
     Connecting,
     Connected,
+    /// Lost the connection; carries the current attempt number.
+    Reconnecting(u32),
     Error(String),
 }
 
+impl ConnectionStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConnectionStatus::Disconnected => "disconnected",
+            ConnectionStatus::Connecting => "connecting",
+            ConnectionStatus::Connected => "connected",
+            ConnectionStatus::Reconnecting(_) => "reconnecting",
+            ConnectionStatus::Error(_) => "error",
+        }
+    }
+}
+
+/// A host key offered by the server, as shown in the trust prompt.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostKey {
+    pub host: String,
+    pub port: u16,
+    pub key_type: String,
+    pub fingerprint: String,
+    /// The `ssh-keyscan` line, written verbatim to known_hosts on accept.
+    #[serde(skip)]
+    line: String,
+}
+
+/// Clock differences below this are reported as fine.
+const MAX_CLOCK_SKEW_SECS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Ok,
+    Warn,
+    Fail,
+    /// Not run because an earlier check failed.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthCheck {
+    pub name: &'static str,
+    pub status: HealthStatus,
+    pub detail: String,
+}
+
+/// Result of `SshSession::health`, one entry per check in a fixed order.
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteHealth {
+    pub ok: bool,
+    pub latency_ms: Option<u64>,
+    pub openclaw_version: Option<String>,
+    pub clock_skew_secs: Option<i64>,
+    pub checks: Vec<HealthCheck>,
+}
+
+/// A local TCP port tunnelled to a port reachable from the remote host.
+#[derive(Debug, Clone, Serialize)]
+pub struct PortForward {
+    pub local_port: u16,
+    pub remote_host: String,
+    pub remote_port: u16,
+    pub created_at: i64,
+}
+
+/// A file or directory in the remote file browser. `path` is relative to
+/// the configured files root.
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    pub size: Option<u64>,
+    pub modified: Option<i64>,
+}
+
 pub struct SshSession {
     pub config: SshConfig,
     pub status: ConnectionStatus,
-    session: Option<openssh::Session>,
+    session: Option<Arc<openssh::Session>>,
+    /// Control socket dir of a master we launched ourselves (password auth).
+    mux_dir: Option<PathBuf>,
+    /// Set once the app is running so status changes reach the UI.
+    app: Option<AppHandle>,
+    /// Open forwards, re-requested after a reconnect.
+    forwards: Vec<PortForward>,
+    /// Status changes for in-process listeners (see `subscribe`).
+    status_tx: watch::Sender<ConnectionStatus>,
+    /// Set while `reconnect` retries without holding the lock.
+    reconnecting: bool,
 }
 
 impl SshSession {
@@ -44,9 +232,73 @@ impl SshSession {
             config: SshConfig::default(),
             status: ConnectionStatus::Disconnected,
             session: None,
+            mux_dir: None,
+            app: None,
+            forwards: Vec::new(),
+            status_tx: watch::channel(ConnectionStatus::Disconnected).0,
+            reconnecting: false,
         }
     }
 
+    /// Follow status transitions without polling the lock, e.g. to act as
+    /// soon as a reconnect succeeds.
+    pub fn subscribe(&self) -> watch::Receiver<ConnectionStatus> {
+        self.status_tx.subscribe()
+    }
+
+    pub fn set_app_handle(&mut self, app: AppHandle) {
+        self.app = Some(app);
+    }
+
+    /// Record a state transition and emit `ssh:status` when it actually changed.
+    fn set_status(&mut self, status: ConnectionStatus) {
+        if self.status == status {
+            return;
+        }
+        self.status = status;
+        self.status_tx.send_replace(self.status.clone());
+        let logged = match &self.status {
+            ConnectionStatus::Connected => Some(("connect", None, true)),
+            ConnectionStatus::Disconnected => Some(("disconnect", None, true)),
+            ConnectionStatus::Reconnecting(n) => Some(("reconnecting", Some(format!("attempt {}", n)), false)),
+            ConnectionStatus::Error(e) => Some(("error", Some(e.clone()), false)),
+            ConnectionStatus::Connecting => None,
+        };
+        if let Some((kind, detail, ok)) = logged {
+            self.log_event(kind, detail.as_deref(), None, ok);
+        }
+        if let Some(app) = &self.app {
+            let (attempt, error) = match &self.status {
+                ConnectionStatus::Reconnecting(n) => (Some(*n), None),
+                ConnectionStatus::Error(e) => (None, Some(e.clone())),
+                _ => (None, None),
+            };
+            let _ = app.emit(
+                "ssh:status",
+                serde_json::json!({ "status": self.status.label(), "attempt": attempt, "error": error }),
+            );
+        }
+    }
+
+    fn host_label(&self) -> String {
+        format!("{}@{}:{}", self.config.user, self.config.host, self.config.port)
+    }
+
+    fn log_event(&self, kind: &str, detail: Option<&str>, duration_ms: Option<i64>, ok: bool) {
+        record_event(&self.host_label(), kind, detail, duration_ms, ok);
+    }
+
+    /// Handle for running commands on the live session.
+    pub fn handle(&self) -> Result<SshHandle> {
+        let session = self.session.clone().ok_or_else(|| anyhow!("Not connected"))?;
+        Ok(SshHandle {
+            session,
+            config: self.config.clone(),
+            host: self.host_label(),
+            app: self.app.clone(),
+        })
+    }
+
     pub fn expand_path(path: &str) -> String {
         if path.starts_with("~/") {
             let home = dirs::home_dir().unwrap_or_default();
@@ -57,69 +309,482 @@ impl SshSession {
     }
 
     pub async fn connect(&mut self) -> Result<()> {
-        self.status = ConnectionStatus::Connecting;
+        self.set_status(ConnectionStatus::Connecting);
+        match self.open_session().await {
+            Ok(session) => {
+                self.session = Some(Arc::new(session));
+                self.set_status(ConnectionStatus::Connected);
+                Ok(())
+            }
+            Err(e) => {
+                self.set_status(ConnectionStatus::Error(e.to_string()));
+                Err(e)
+            }
+        }
+    }
 
-        let key_path = Self::expand_path(&self.config.key_path);
-        let dest = format!(
-            "ssh://{}@{}:{}",
-            self.config.user, self.config.host, self.config.port
-        );
+    async fn open_session(&mut self) -> Result<openssh::Session> {
+        if self.config.auth_method == SshAuthMethod::Password {
+            self.remove_mux_dir();
+        }
+        let (session, mux_dir) = self.opener().open().await?;
+        if mux_dir.is_some() {
+            self.mux_dir = mux_dir;
+        }
+        Ok(session)
+    }
 
-        let session = openssh::Session::connect_mux(
-            &dest,
-            openssh::KnownHosts::Accept,
-        )
-        .await
-        .map_err(|e| anyhow!("SSH connect failed: {}", e))?;
+    /// What opening a session needs, apart from the lock.
+    fn opener(&self) -> Opener {
+        Opener {
+            config: self.config.clone(),
+            app: self.app.clone(),
+        }
+    }
 
-        self.session = Some(session);
-        self.status = ConnectionStatus::Connected;
-        Ok(())
+    fn remove_mux_dir(&mut self) {
+        if let Some(dir) = self.mux_dir.take() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
     }
 
     pub async fn disconnect(&mut self) {
         if let Some(session) = self.session.take() {
-            let _ = session.close().await;
+            close_session(session).await;
+        }
+        self.remove_mux_dir();
+        self.forwards.clear();
+        self.set_status(ConnectionStatus::Disconnected);
+    }
+
+    /// Whether the master connection still answers.
+    pub async fn is_alive(&self) -> bool {
+        match &self.session {
+            Some(session) => session.check().await.is_ok(),
+            None => false,
         }
-        self.status = ConnectionStatus::Disconnected;
     }
 
+    /// Check the current config works. Uses the live session when there is
+    /// one, otherwise a throwaway probe that leaves the managed state alone.
     pub async fn test_connection(&mut self) -> Result<String> {
-        self.connect().await?;
-        let output = self.exec("echo connected && hostname").await?;
-        Ok(output)
+        const PROBE: &str = "echo connected && hostname";
+        if self.is_alive().await {
+            return self.handle()?.exec(PROBE).await;
+        }
+        let session = self.open_session().await?;
+        let output = exec_on(&session, PROBE).await;
+        let _ = session.close().await;
+        self.remove_mux_dir();
+        output
     }
 
-    pub async fn exec(&self, cmd: &str) -> Result<String> {
+    // ── Port forwarding ───────────────────────────────────────────────────────
+
+    pub fn forwards(&self) -> &[PortForward] {
+        &self.forwards
+    }
+
+    /// Forward `127.0.0.1:local_port` to `remote_host:remote_port` as seen
+    /// from the remote box (e.g. an openclaw gateway bound to its loopback).
+    /// Without a local port a free one is picked.
+    pub async fn open_forward(
+        &mut self,
+        remote_host: &str,
+        remote_port: u16,
+        local_port: Option<u16>,
+    ) -> Result<PortForward> {
         let session = self.session.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
-        let output = session
-            .command("sh")
-            .arg("-c")
-            .arg(cmd)
-            .output()
+        let local_port = match local_port {
+            Some(port) => port,
+            None => free_local_port()?,
+        };
+        if self.forwards.iter().any(|f| f.local_port == local_port) {
+            return Err(anyhow!("Local port {} is already forwarded", local_port));
+        }
+        session
+            .request_port_forward(
+                openssh::ForwardType::Local,
+                openssh::Socket::new("127.0.0.1", local_port),
+                openssh::Socket::new(remote_host, remote_port),
+            )
             .await
-            .map_err(|e| anyhow!("SSH exec failed: {}", e))?;
+            .map_err(|e| anyhow!("Port forward failed: {}", e))?;
+        let forward = PortForward {
+            local_port,
+            remote_host: remote_host.to_string(),
+            remote_port,
+            created_at: chrono::Utc::now().timestamp_millis(),
+        };
+        self.log_event(
+            "forward",
+            Some(&format!("{} -> {}:{}", local_port, remote_host, remote_port)),
+            None,
+            true,
+        );
+        self.forwards.push(forward.clone());
+        Ok(forward)
+    }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Remote command failed: {}", stderr));
+    /// The mux client in openssh can open forwards but not cancel them, so
+    /// ask the master directly with `ssh -O cancel`.
+    pub async fn close_forward(&mut self, local_port: u16) -> Result<()> {
+        let index = self
+            .forwards
+            .iter()
+            .position(|f| f.local_port == local_port)
+            .ok_or_else(|| anyhow!("No forward on local port {}", local_port))?;
+        let forward = &self.forwards[index];
+        if let Some(session) = &self.session {
+            let output = tokio::process::Command::new("ssh")
+                .arg("-S")
+                .arg(session.control_socket())
+                .args(["-O", "cancel", "-L"])
+                .arg(format!("127.0.0.1:{}:{}:{}", forward.local_port, forward.remote_host, forward.remote_port))
+                .arg(&self.config.host)
+                .output()
+                .await
+                .map_err(|e| anyhow!("Failed to run ssh: {}", e))?;
+            if !output.status.success() {
+                return Err(anyhow!(
+                    "Failed to close forward: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
         }
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        self.forwards.remove(index);
+        self.log_event("forward", Some(&format!("closed {}", local_port)), None, true);
+        Ok(())
     }
 
-    pub async fn send_message_remote(
+    /// A new master has none of the old forwards; ask for them again.
+    async fn restore_forwards(&mut self) {
+        let Some(session) = &self.session else {
+            return;
+        };
+        let mut kept = Vec::new();
+        for forward in std::mem::take(&mut self.forwards) {
+            let result = session
+                .request_port_forward(
+                    openssh::ForwardType::Local,
+                    openssh::Socket::new("127.0.0.1", forward.local_port),
+                    openssh::Socket::new(forward.remote_host.as_str(), forward.remote_port),
+                )
+                .await;
+            match result {
+                Ok(()) => kept.push(forward),
+                Err(e) => self.log_event(
+                    "forward",
+                    Some(&format!("lost {} after reconnect: {}", forward.local_port, e)),
+                    None,
+                    false,
+                ),
+            }
+        }
+        self.forwards = kept;
+    }
+
+    /// Diagnose remote mode: reachability, the openclaw binary, the agents
+    /// directory and clock skew. Never fails; problems land in the report.
+    pub async fn health(&self) -> RemoteHealth {
+        let check = |name, status, detail: String| HealthCheck { name, status, detail };
+        let mut report = RemoteHealth {
+            ok: false,
+            latency_ms: None,
+            openclaw_version: None,
+            clock_skew_secs: None,
+            checks: Vec::new(),
+        };
+
+        let handle = match self.handle() {
+            Ok(handle) if self.is_alive().await => Some(handle),
+            _ => None,
+        };
+        let started = std::time::Instant::now();
+        let reachable = match &handle {
+            Some(handle) => handle.exec("true").await.is_ok(),
+            None => false,
+        };
+        let Some(handle) = handle.filter(|_| reachable) else {
+            let detail = match &self.status {
+                ConnectionStatus::Error(e) => e.clone(),
+                other => format!("Session is {}", other.label()),
+            };
+            report.checks.push(check("ssh", HealthStatus::Fail, detail));
+            for name in ["openclaw", "agents_dir", "clock"] {
+                report
+                    .checks
+                    .push(check(name, HealthStatus::Skipped, "SSH is not reachable".to_string()));
+            }
+            return report;
+        };
+        let latency = started.elapsed().as_millis() as u64;
+        report.latency_ms = Some(latency);
+        report.checks.push(check(
+            "ssh",
+            HealthStatus::Ok,
+            format!("{}@{} ({} ms)", self.config.user, self.config.host, latency),
+        ));
+
+        report.checks.push(match handle.exec_openclaw(&["--version"]).await {
+            Ok(version) => {
+                report.openclaw_version = Some(version.clone());
+                check("openclaw", HealthStatus::Ok, version)
+            }
+            Err(e) => check(
+                "openclaw",
+                HealthStatus::Fail,
+                format!("openclaw not runnable over SSH (is it on the non-interactive PATH?): {}", e),
+            ),
+        });
+
+        // The directory comes from config, so it goes in as $1, never into the script
+        const AGENTS_DIR_PROBE: &str = "d=\"$1\"; \
+            if [ ! -d \"$d\" ]; then echo missing; exit 0; fi; \
+            p=''; [ -r \"$d\" ] && p=\"${p}r\"; [ -w \"$d\" ] && p=\"${p}w\"; [ -x \"$d\" ] && p=\"${p}x\"; \
+            echo \"$p\"";
+        let agents_dir = self.config.agents_dir();
+        let probe = handle.exec_args("sh", &["-c", AGENTS_DIR_PROBE, "sh", &agents_dir]).await;
+        report.checks.push(match probe.as_deref() {
+            Ok("missing") => check(
+                "agents_dir",
+                HealthStatus::Fail,
+                format!("{} does not exist on the remote", agents_dir),
+            ),
+            Ok("rwx") => check("agents_dir", HealthStatus::Ok, format!("{} is readable and writable", agents_dir)),
+            Ok(perms) => check(
+                "agents_dir",
+                HealthStatus::Fail,
+                format!("{} has insufficient permissions (have '{}', need rwx)", agents_dir, perms),
+            ),
+            Err(e) => check("agents_dir", HealthStatus::Fail, e.to_string()),
+        });
+
+        let before = chrono::Utc::now().timestamp_millis();
+        let remote_now = handle.exec("date +%s").await;
+        let after = chrono::Utc::now().timestamp_millis();
+        report.checks.push(match remote_now.map(|out| out.parse::<i64>()) {
+            Ok(Ok(remote)) => {
+                let skew = remote - (before + after) / 2000;
+                report.clock_skew_secs = Some(skew);
+                let status = if skew.abs() > MAX_CLOCK_SKEW_SECS { HealthStatus::Warn } else { HealthStatus::Ok };
+                check("clock", status, format!("Remote clock is {:+}s from local", skew))
+            }
+            Ok(Err(_)) => check("clock", HealthStatus::Warn, "Could not parse remote time".to_string()),
+            Err(e) => check("clock", HealthStatus::Warn, e.to_string()),
+        });
+
+        report.ok = report.checks.iter().all(|c| c.status != HealthStatus::Fail);
+        report
+    }
+}
+
+/// A cheap, cloneable view of the live connection for running remote work.
+/// Each operation opens its own channel on the multiplexed master, so callers
+/// take a handle under the `SshSession` lock and release the lock before
+/// awaiting — a long `tail -f` or agent run no longer blocks other calls.
+#[derive(Clone)]
+pub struct SshHandle {
+    session: Arc<openssh::Session>,
+    config: SshConfig,
+    /// `user@host:port`, for the event log.
+    host: String,
+    app: Option<AppHandle>,
+}
+
+impl SshHandle {
+    fn log_event(&self, kind: &str, detail: Option<&str>, duration_ms: Option<i64>, ok: bool) {
+        record_event(&self.host, kind, detail, duration_ms, ok);
+    }
+
+    /// Await a remote operation and record its latency (and error, if any).
+    async fn timed<T>(&self, label: &str, op: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+        let started = std::time::Instant::now();
+        let result = op.await;
+        let elapsed = started.elapsed().as_millis() as i64;
+        match &result {
+            Ok(_) => self.log_event("command", Some(label), Some(elapsed), true),
+            Err(e) => self.log_event("command", Some(&format!("{}: {}", label, e)), Some(elapsed), false),
+        }
+        result
+    }
+
+    /// Run a fixed shell snippet remotely. Never interpolate user input into
+    /// `cmd` — use `exec_args` for anything that carries data.
+    pub async fn exec(&self, cmd: &str) -> Result<String> {
+        let session = &self.session;
+        self.timed(cmd, exec_on(session, cmd)).await
+    }
+
+    /// Run `program` with an argument vector. openssh shell-escapes the
+    /// program and every argument, so backticks, `$()`, quotes and newlines in
+    /// user text arrive as literal characters.
+    pub async fn exec_args(&self, program: &str, args: &[&str]) -> Result<String> {
+        let session = &self.session;
+        // Only the subcommand is logged; later arguments may carry user text
+        let label = match args.first() {
+            Some(first) => format!("{} {}", program, first),
+            None => program.to_string(),
+        };
+        self.timed(&label, async {
+            let output = session
+                .command(program)
+                .args(args)
+                .output()
+                .await
+                .map_err(|e| anyhow!("SSH exec failed: {}", e))?;
+            command_stdout(output)
+        })
+        .await
+    }
+
+    /// `openclaw <args>` using the configured binary, wrapped in `env` when
+    /// extra variables are set (sshd drops client-side env by default).
+    fn openclaw_command<'s>(&'s self, args: &[&str]) -> openssh::OwningCommand<&'s openssh::Session> {
+        let mut cmd = if self.config.env.is_empty() {
+            self.session.command(self.config.openclaw_bin().to_string())
+        } else {
+            let mut cmd = self.session.command("env");
+            for (key, value) in &self.config.env {
+                cmd.arg(format!("{}={}", key, value));
+            }
+            cmd.arg(self.config.openclaw_bin());
+            cmd
+        };
+        cmd.args(args);
+        cmd
+    }
+
+    /// Run openclaw with `args` and return its stdout.
+    pub async fn exec_openclaw(&self, args: &[&str]) -> Result<String> {
+        let label = format!("openclaw {}", args.first().copied().unwrap_or_default());
+        self.timed(&label, async {
+            let output = self
+                .openclaw_command(args)
+                .output()
+                .await
+                .map_err(|e| anyhow!("SSH exec failed: {}", e))?;
+            command_stdout(output)
+        })
+        .await
+    }
+
+    /// Run the agent remotely with `--json`, reading its stdout over the SSH
+    /// channel and handing each reply to `on_reply` as soon as its JSON is
+    /// complete. The remote openclaw still records the session file itself.
+    pub async fn send_message_remote<F>(
         &self,
         agent_id: &str,
         session_id: &str,
+        model: Option<&str>,
         message: &str,
-    ) -> Result<()> {
-        // Escape the message for shell
-        let escaped = message.replace('\'', "'\\''");
-        let cmd = format!(
-            "openclaw agent --agent '{}' --session-id '{}' --message '{}'",
-            agent_id, session_id, escaped
-        );
-        self.exec(&cmd).await?;
+        mut on_reply: F,
+    ) -> Result<()>
+    where
+        F: FnMut(openclaw::ChatMessage) + Send,
+    {
+        let started = std::time::Instant::now();
+        let mut replies = Vec::new();
+        let result = self
+            .timed(
+                "openclaw agent",
+                self.run_agent(agent_id, session_id, model, message, |reply| {
+                    replies.push(reply.content.clone());
+                    on_reply(reply);
+                }),
+            )
+            .await;
+        let reply = replies.join("\n");
+        openclaw::log_invocation(
+            &openclaw::Invocation {
+                agent_id,
+                session_id: Some(session_id),
+                model,
+                message,
+                remote: true,
+                duration: started.elapsed(),
+                exit_code: None,
+            },
+            result.as_ref().map(|_| reply.as_str()),
+        )
+        .await;
+        result
+    }
+
+    async fn run_agent<F>(
+        &self,
+        agent_id: &str,
+        session_id: &str,
+        model: Option<&str>,
+        message: &str,
+        mut on_reply: F,
+    ) -> Result<()>
+    where
+        F: FnMut(openclaw::ChatMessage) + Send,
+    {
+        let args = openclaw::agent_args(false, agent_id, Some(session_id), model, message);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let mut child = self
+            .openclaw_command(&args)
+            .stdout(openssh::Stdio::piped())
+            .stderr(openssh::Stdio::piped())
+            .spawn()
+            .await
+            .map_err(|e| anyhow!("SSH exec failed: {}", e))?;
+        let mut stdout = child.stdout().take().ok_or_else(|| anyhow!("openclaw stdout unavailable"))?;
+        let mut stderr = child.stderr().take().ok_or_else(|| anyhow!("openclaw stderr unavailable"))?;
+
+        let mut replies = 0;
+        let mut unrecognized = None;
+        let read_stdout = async {
+            let mut buf = Vec::new();
+            let mut chunk = vec![0u8; 8192];
+            loop {
+                let n = stdout.read(&mut chunk).await?;
+                if n == 0 {
+                    break;
+                }
+                buf.extend_from_slice(&chunk[..n]);
+                for doc in openclaw::drain_outputs(&mut buf) {
+                    match openclaw::parse_output(&doc) {
+                        Ok(parsed) => {
+                            if !parsed.texts.is_empty() || !parsed.parts.is_empty() {
+                                replies += 1;
+                                on_reply(openclaw::ChatMessage {
+                                    role: "assistant".to_string(),
+                                    content: parsed.texts.join("\n"),
+                                    parts: parsed.parts,
+                                });
+                            }
+                        }
+                        Err(mut e) => {
+                            // The local probe says nothing about the remote install
+                            e.version = None;
+                            unrecognized = Some(e);
+                        }
+                    }
+                }
+            }
+            Ok::<_, std::io::Error>(())
+        };
+        let read_stderr = async {
+            let mut err = String::new();
+            let _ = stderr.read_to_string(&mut err).await;
+            err
+        };
+        let (read, err) = tokio::join!(read_stdout, read_stderr);
+        let status = child.wait().await.map_err(|e| anyhow!("SSH exec failed: {}", e))?;
+        read.map_err(|e| anyhow!("Failed to read openclaw output: {}", e))?;
+
+        if !status.success() {
+            return Err(anyhow!("OpenClaw error: {}", err.trim()));
+        }
+        if replies == 0 {
+            return Err(match unrecognized {
+                Some(e) => e.into(),
+                None => anyhow!("OpenClaw returned empty response"),
+            });
+        }
         Ok(())
     }
 
@@ -132,17 +797,13 @@ impl SshSession {
     where
         F: Fn(String) + Send + 'static,
     {
-        let session = self.session.as_ref().ok_or_else(|| anyhow!("Not connected"))?;
-        let path = format!(
-            "~/.openclaw/agents/{}/sessions/{}.jsonl",
-            agent_id, session_id
-        );
-        let cmd = format!("tail -f '{}'", path);
+        let path = remote_session_path(&self.config, agent_id, session_id)?;
 
-        let mut child = session
-            .command("sh")
-            .arg("-c")
-            .arg(&cmd)
+        let mut child = self
+            .session
+            .command("tail")
+            .arg("-f")
+            .arg(&path)
             .stdout(openssh::Stdio::piped())
             .spawn()
             .await
@@ -162,12 +823,642 @@ impl SshSession {
         Ok(())
     }
 
+    /// Session transcript over SFTP; a missing file reads as empty.
     pub async fn read_session_file(&self, agent_id: &str, session_id: &str) -> Result<String> {
-        let path = format!(
-            "~/.openclaw/agents/{}/sessions/{}.jsonl",
-            agent_id, session_id
+        let path = remote_session_path(&self.config, agent_id, session_id)?;
+        match self.read_file(&path).await? {
+            Some(bytes) => Ok(String::from_utf8_lossy(&bytes).into_owned()),
+            None => Ok(String::new()),
+        }
+    }
+
+    // ── SFTP ──────────────────────────────────────────────────────────────────
+
+    /// Start an SFTP subsystem channel on the live session.
+    async fn open_sftp(&self) -> Result<(Sftp, openssh::Child<&openssh::Session>)> {
+        let session = &self.session;
+        let mut child = session
+            .subsystem("sftp")
+            .stdin(openssh::Stdio::piped())
+            .stdout(openssh::Stdio::piped())
+            .stderr(openssh::Stdio::null())
+            .spawn()
+            .await
+            .map_err(|e| anyhow!("Failed to start sftp: {}", e))?;
+        let stdin = child.stdin().take().ok_or_else(|| anyhow!("sftp stdin unavailable"))?;
+        let stdout = child.stdout().take().ok_or_else(|| anyhow!("sftp stdout unavailable"))?;
+        let sftp = Sftp::new(stdin, stdout, SftpOptions::default())
+            .await
+            .map_err(|e| anyhow!("SFTP handshake failed: {}", e))?;
+        Ok((sftp, child))
+    }
+
+    fn emit_transfer(&self, path: &str, direction: &str, transferred: u64, total: Option<u64>) {
+        if let Some(app) = &self.app {
+            let _ = app.emit(
+                "ssh:transfer",
+                serde_json::json!({
+                    "path": path,
+                    "direction": direction,
+                    "transferred": transferred,
+                    "total": total,
+                }),
+            );
+        }
+    }
+
+    /// Download a remote file in chunks, emitting `ssh:transfer` progress.
+    /// Returns `None` when the file doesn't exist.
+    pub async fn read_file(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        self.timed(&format!("sftp read {}", path), self.sftp_read(path)).await
+    }
+
+    async fn sftp_read(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let remote = sftp_path(path);
+        let (sftp, child) = self.open_sftp().await?;
+        let result = async {
+            let metadata = match sftp.fs().metadata(&remote).await {
+                Ok(metadata) => metadata,
+                Err(openssh_sftp_client::Error::SftpError(SftpErrorKind::NoSuchFile, _)) => {
+                    return Ok(None)
+                }
+                Err(e) => return Err(anyhow!("SFTP stat {} failed: {}", path, e)),
+            };
+            let total = metadata.len();
+            let file = sftp
+                .open(&remote)
+                .await
+                .map_err(|e| anyhow!("SFTP open {} failed: {}", path, e))?;
+            let mut file = Box::pin(TokioCompatFile::new(file));
+            let mut data = Vec::with_capacity(total.unwrap_or(0) as usize);
+            let mut chunk = vec![0u8; SFTP_CHUNK_SIZE];
+            loop {
+                let n = file.read(&mut chunk).await?;
+                if n == 0 {
+                    break;
+                }
+                data.extend_from_slice(&chunk[..n]);
+                self.emit_transfer(path, "download", data.len() as u64, total);
+            }
+            Ok(Some(data))
+        }
+        .await;
+        let _ = sftp.close().await;
+        let _ = child.wait().await;
+        result
+    }
+
+    /// Upload `data` to a remote file (created/truncated) in chunks, emitting
+    /// `ssh:transfer` progress.
+    pub async fn write_file(&self, path: &str, data: &[u8]) -> Result<()> {
+        let total = data.len() as u64;
+        self.timed(&format!("sftp write {}", path), self.sftp_write(path, data, total))
+            .await
+    }
+
+    /// Upload a local file, read `SFTP_CHUNK_SIZE` at a time rather than
+    /// loaded whole.
+    pub async fn upload_file(&self, local_path: &Path, path: &str) -> Result<()> {
+        let file = tokio::fs::File::open(local_path)
+            .await
+            .map_err(|e| anyhow!("Couldn't open {}: {}", local_path.display(), e))?;
+        let total = file.metadata().await?.len();
+        self.timed(&format!("sftp write {}", path), self.sftp_write(path, file, total))
+            .await
+    }
+
+    async fn sftp_write(
+        &self,
+        path: &str,
+        mut source: impl AsyncRead + Unpin,
+        total: u64,
+    ) -> Result<()> {
+        let remote = sftp_path(path);
+        let (sftp, child) = self.open_sftp().await?;
+        let result = async {
+            let mut file = sftp
+                .options()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&remote)
+                .await
+                .map_err(|e| anyhow!("SFTP open {} failed: {}", path, e))?;
+            let mut written = 0u64;
+            let mut chunk = vec![0u8; SFTP_CHUNK_SIZE];
+            loop {
+                let n = source.read(&mut chunk).await?;
+                if n == 0 {
+                    break;
+                }
+                file.write_all(&chunk[..n])
+                    .await
+                    .map_err(|e| anyhow!("SFTP write {} failed: {}", path, e))?;
+                written += n as u64;
+                self.emit_transfer(path, "upload", written, Some(total));
+            }
+            file.close().await.map_err(|e| anyhow!("SFTP close {} failed: {}", path, e))
+        }
+        .await;
+        let _ = sftp.close().await;
+        let _ = child.wait().await;
+        result
+    }
+
+    /// Home-relative remote path for `rel` inside the openclaw state dir.
+    pub fn state_path(&self, rel: &str) -> Result<String> {
+        Ok(format!("{}/{}", self.config.state_dir(), checked_relative(rel)?))
+    }
+
+    // ── File browser ──────────────────────────────────────────────────────────
+
+    /// Absolute-or-home-relative remote path for `rel` inside the files root.
+    pub fn files_path(&self, rel: &str) -> Result<String> {
+        let rel = checked_relative(rel)?;
+        let root = self.config.files_dir();
+        if rel.is_empty() {
+            Ok(root.to_string())
+        } else {
+            Ok(format!("{}/{}", root, rel))
+        }
+    }
+
+    /// Entries of a directory under the files root, directories first.
+    pub async fn list_files(&self, rel: &str) -> Result<Vec<RemoteEntry>> {
+        let dir_path = self.files_path(rel)?;
+        let base = checked_relative(rel)?;
+        let mut entries = self.list_dir(&dir_path).await?;
+        if !base.is_empty() {
+            for entry in &mut entries {
+                entry.path = format!("{}/{}", base, entry.name);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Entries of a remote directory, directories first. `path` of each
+    /// entry is just its name.
+    pub async fn list_dir(&self, dir_path: &str) -> Result<Vec<RemoteEntry>> {
+        self.timed(&format!("sftp list {}", dir_path), async {
+            let (sftp, child) = self.open_sftp().await?;
+            let result = async {
+                let dir = sftp
+                    .fs()
+                    .open_dir(sftp_path(dir_path))
+                    .await
+                    .map_err(|e| anyhow!("SFTP list {} failed: {}", dir_path, e))?;
+                let mut entries = Vec::new();
+                let mut stream = Box::pin(dir.read_dir());
+                while let Some(entry) = stream.next().await {
+                    let entry = entry.map_err(|e| anyhow!("SFTP list {} failed: {}", dir_path, e))?;
+                    let name = entry.filename().to_string_lossy().into_owned();
+                    if name == "." || name == ".." {
+                        continue;
+                    }
+                    let metadata = entry.metadata();
+                    entries.push(RemoteEntry {
+                        path: name.clone(),
+                        name,
+                        is_dir: entry.file_type().is_some_and(|t| t.is_dir()),
+                        size: metadata.len(),
+                        modified: metadata.modified().map(|t| t.as_duration().as_millis() as i64),
+                    });
+                }
+                entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+                Ok(entries)
+            }
+            .await;
+            let _ = sftp.close().await;
+            let _ = child.wait().await;
+            result
+        })
+        .await
+    }
+
+    // ── Agents ────────────────────────────────────────────────────────────────
+
+    /// Agents installed under the remote state dir.
+    pub async fn list_agents(&self) -> Result<Vec<openclaw::AgentInfo>> {
+        let agents_dir = self.config.agents_dir();
+        // No agents dir yet just means nothing has run there
+        let entries = self.list_dir(&agents_dir).await.unwrap_or_default();
+        let mut dirs = Vec::new();
+        for entry in entries {
+            if !entry.is_dir || entry.name.starts_with('.') {
+                continue;
+            }
+            let sessions_dir = format!("{}/{}/sessions", agents_dir, entry.name);
+            let mut sessions = openclaw::AgentSessions::default();
+            for file in self.list_dir(&sessions_dir).await.unwrap_or_default() {
+                if file.is_dir || !file.name.ends_with(".jsonl") {
+                    continue;
+                }
+                sessions.count += 1;
+                sessions.last_active = sessions.last_active.max(file.modified);
+            }
+            dirs.push((entry.name, sessions));
+        }
+        let config_path = format!("{}/openclaw.json", self.config.state_dir());
+        let config = self
+            .read_file(&config_path)
+            .await?
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+        Ok(openclaw::merge_agents(dirs, config.as_deref(), true))
+    }
+}
+
+/// `sh -c cmd` on a specific session, for probes outside the managed one.
+async fn exec_on(session: &openssh::Session, cmd: &str) -> Result<String> {
+    let output = session
+        .command("sh")
+        .arg("-c")
+        .arg(cmd)
+        .output()
+        .await
+        .map_err(|e| anyhow!("SSH exec failed: {}", e))?;
+    command_stdout(output)
+}
+
+/// Append to the `ssh_events` log in the background. Logging must never
+/// break or hold up a remote call, so failures only go to stderr.
+fn record_event(host: &str, kind: &str, detail: Option<&str>, duration_ms: Option<i64>, ok: bool) {
+    let (host, kind, detail) = (host.to_string(), kind.to_string(), detail.map(str::to_string));
+    tauri::async_runtime::spawn(async move {
+        let result =
+            db::call(move |conn| db::log_ssh_event(conn, &kind, &host, detail.as_deref(), duration_ms, ok)).await;
+        if let Err(e) = result {
+            eprintln!("[ssh] Failed to log event: {}", e);
+        }
+    });
+}
+
+/// Close the master if nothing else holds it. A handle still in use keeps
+/// the session alive until it finishes and drops.
+async fn close_session(session: Arc<openssh::Session>) {
+    if let Ok(session) = Arc::try_unwrap(session) {
+        let _ = session.close().await;
+    }
+}
+
+fn command_stdout(output: std::process::Output) -> Result<String> {
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("Remote command failed: {}", stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Remote transcript path under the configured state dir, relative to the
+/// remote $HOME unless absolute (escaped arguments don't get `~` expansion).
+/// Ids are checked so they can't walk out of the sessions directory.
+fn remote_session_path(config: &SshConfig, agent_id: &str, session_id: &str) -> Result<String> {
+    for id in [agent_id, session_id] {
+        if id.is_empty() || id.contains('/') || id.contains('\0') || id == "." || id == ".." {
+            return Err(anyhow!("Invalid agent or session id: {:?}", id));
+        }
+    }
+    Ok(format!("{}/{}/sessions/{}.jsonl", config.agents_dir(), agent_id, session_id))
+}
+
+// ── Known hosts ──────────────────────────────────────────────────────────────
+
+pub fn known_hosts_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".openclaw")
+        .join("chat")
+        .join(KNOWN_HOSTS_FILE)
+}
+
+/// How a host is written in known_hosts: bare on 22, `[host]:port` otherwise.
+fn host_pattern(host: &str, port: u16) -> String {
+    if port == 22 {
+        host.to_string()
+    } else {
+        format!("[{}]:{}", host, port)
+    }
+}
+
+async fn host_is_known(host: &str, port: u16) -> Result<bool> {
+    let path = known_hosts_path();
+    if !path.exists() {
+        return Ok(false);
+    }
+    let status = tokio::process::Command::new("ssh-keygen")
+        .arg("-F")
+        .arg(host_pattern(host, port))
+        .arg("-f")
+        .arg(&path)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await
+        .map_err(|e| anyhow!("Failed to run ssh-keygen: {}", e))?;
+    Ok(status.success())
+}
+
+/// Ask the server for its host keys and fingerprint them locally.
+pub async fn scan_host_keys(host: &str, port: u16) -> Result<Vec<HostKey>> {
+    let output = tokio::process::Command::new("ssh-keyscan")
+        .arg("-T")
+        .arg(CONNECT_TIMEOUT_SECS.to_string())
+        .arg("-p")
+        .arg(port.to_string())
+        .arg("--")
+        .arg(host)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run ssh-keyscan: {}", e))?;
+    let lines: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+    if lines.is_empty() {
+        return Err(anyhow!("Could not read host keys from {}", host_pattern(host, port)));
+    }
+
+    let mut keys = Vec::new();
+    for line in lines {
+        let Some(key_type) = line.split_whitespace().nth(1).map(str::to_string) else {
+            continue;
+        };
+        let fingerprint = fingerprint_line(&line).await?;
+        keys.push(HostKey {
+            host: host.to_string(),
+            port,
+            key_type,
+            fingerprint,
+            line,
+        });
+    }
+    Ok(keys)
+}
+
+/// SHA256 fingerprint of a single known_hosts line, via `ssh-keygen -l`.
+async fn fingerprint_line(line: &str) -> Result<String> {
+    let tmp = std::env::temp_dir().join(format!("openclaw-hostkey-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::write(&tmp, format!("{}\n", line))?;
+    let output = tokio::process::Command::new("ssh-keygen")
+        .arg("-l")
+        .arg("-f")
+        .arg(&tmp)
+        .output()
+        .await;
+    let _ = std::fs::remove_file(&tmp);
+    let output = output.map_err(|e| anyhow!("Failed to run ssh-keygen: {}", e))?;
+    // "256 SHA256:abc... host (ED25519)"
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .nth(1)
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("Could not fingerprint host key"))
+}
+
+/// Persist the host key the user confirmed. The server is scanned again and
+/// only a key matching `fingerprint` is written, so a key that changed since
+/// the prompt is never trusted by accident.
+pub async fn trust_host_key(host: &str, port: u16, fingerprint: &str) -> Result<()> {
+    use std::io::Write;
+    let keys = scan_host_keys(host, port).await?;
+    let key = keys
+        .iter()
+        .find(|k| k.fingerprint == fingerprint)
+        .ok_or_else(|| anyhow!("Host key for {} no longer matches {}", host_pattern(host, port), fingerprint))?;
+    let path = known_hosts_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", key.line)?;
+    Ok(())
+}
+
+/// Drop every trusted key for a host, e.g. after it was reinstalled.
+pub async fn forget_host_key(host: &str, port: u16) -> Result<()> {
+    let path = known_hosts_path();
+    if !path.exists() {
+        return Ok(());
+    }
+    let output = tokio::process::Command::new("ssh-keygen")
+        .arg("-R")
+        .arg(host_pattern(host, port))
+        .arg("-f")
+        .arg(&path)
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run ssh-keygen: {}", e))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to remove host key: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let _ = std::fs::remove_file(path.with_extension("old"));
+    Ok(())
+}
+
+/// Drop a leading `~/` (SFTP and escaped arguments resolve relative paths
+/// from $HOME already) and any trailing slash.
+fn home_relative(dir: &str) -> &str {
+    let dir = dir.strip_prefix("~/").unwrap_or(dir).trim_end_matches('/');
+    if dir.is_empty() || dir == "~" {
+        "."
+    } else {
+        dir
+    }
+}
+
+/// Normalize a browser path and refuse anything that could leave the files
+/// root: absolute paths and `..` components.
+fn checked_relative(rel: &str) -> Result<String> {
+    let mut parts = Vec::new();
+    for part in rel.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => return Err(anyhow!("Path escapes the remote files directory: {}", rel)),
+            p if p.contains('\0') => return Err(anyhow!("Invalid path: {:?}", rel)),
+            p => parts.push(p),
+        }
+    }
+    if rel.starts_with('/') {
+        return Err(anyhow!("Path must be relative to the remote files directory: {}", rel));
+    }
+    Ok(parts.join("/"))
+}
+
+/// Let the OS pick an unused loopback port.
+fn free_local_port() -> Result<u16> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    Ok(listener.local_addr()?.port())
+}
+
+/// SFTP paths don't expand `~`; relative paths already resolve from $HOME.
+fn sftp_path(path: &str) -> PathBuf {
+    PathBuf::from(path.strip_prefix("~/").unwrap_or(path))
+}
+
+/// Helper for `SSH_ASKPASS`: prints the stored password for `account`.
+fn write_askpass_script(dir: &std::path::Path, account: &str) -> Result<PathBuf> {
+    let account = account.replace('\'', "'\\''");
+    let lookup = if cfg!(target_os = "macos") {
+        format!(
+            "/usr/bin/security find-generic-password -s '{}' -a '{}' -w",
+            KEYCHAIN_SERVICE, account
+        )
+    } else {
+        format!("secret-tool lookup service '{}' account '{}'", KEYCHAIN_SERVICE, account)
+    };
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join("ssh-askpass.sh");
+    std::fs::write(&path, format!("#!/bin/sh\nexec {}\n", lookup))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(path)
+}
+
+/// Save the SSH password for `account` in the OS keychain.
+pub async fn store_password(account: &str, password: &str) -> Result<()> {
+    keychain::store(KEYCHAIN_SERVICE, account, "OpenClaw Chat SSH", password).await
+}
+
+/// Opens sessions from a copy of the config, so `reconnect` can retry
+/// without holding the session lock.
+struct Opener {
+    config: SshConfig,
+    app: Option<AppHandle>,
+}
+
+impl Opener {
+    /// The session, and the control socket dir of a master launched for
+    /// password auth.
+    async fn open(&self) -> Result<(openssh::Session, Option<PathBuf>)> {
+        self.verify_host_key().await?;
+        match self.config.auth_method {
+            SshAuthMethod::Password => {
+                let (session, dir) = self.open_password_session().await?;
+                Ok((session, Some(dir)))
+            }
+            SshAuthMethod::Key => {
+                let mut builder = self.builder();
+                builder.keyfile(SshSession::expand_path(&self.config.key_path));
+                Ok((self.connect(&builder).await?, None))
+            }
+            SshAuthMethod::Agent => {
+                let sock = std::env::var_os("SSH_AUTH_SOCK")
+                    .ok_or_else(|| anyhow!("ssh-agent not available (SSH_AUTH_SOCK is unset)"))?;
+                let mut builder = self.builder();
+                builder.ssh_auth_sock(PathBuf::from(sock));
+                Ok((self.connect(&builder).await?, None))
+            }
+        }
+    }
+
+    /// Options shared by key and agent auth. ServerAliveInterval keeps
+    /// NAT/firewall state warm and lets the master notice a dead peer instead
+    /// of hanging after sleep.
+    fn builder(&self) -> openssh::SessionBuilder {
+        let mut builder = openssh::SessionBuilder::default();
+        builder
+            .known_hosts_check(openssh::KnownHosts::Strict)
+            .user_known_hosts_file(known_hosts_path())
+            .server_alive_interval(Duration::from_secs(KEEPALIVE_INTERVAL_SECS))
+            .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS));
+        builder
+    }
+
+    async fn connect(&self, builder: &openssh::SessionBuilder) -> Result<openssh::Session> {
+        let dest = format!(
+            "ssh://{}@{}:{}",
+            self.config.user, self.config.host, self.config.port
         );
-        self.exec(&format!("cat '{}' 2>/dev/null || echo ''", path)).await
+        builder
+            .connect_mux(&dest)
+            .await
+            .map_err(|e| anyhow!("SSH connect failed: {}", e))
+    }
+
+    /// openssh always runs the master with BatchMode=yes, which rules out
+    /// password prompts, so launch the master ourselves with an askpass helper
+    /// that reads the keychain, then attach to its control socket.
+    async fn open_password_session(&self) -> Result<(openssh::Session, PathBuf)> {
+        let base = dirs::home_dir().unwrap_or_default().join(".openclaw").join("chat");
+        let askpass = write_askpass_script(&base, &self.config.keychain_account())?;
+        let dir = base.join(format!("ssh-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]));
+        std::fs::create_dir_all(&dir)?;
+        let ctl = dir.join("master");
+
+        let output = tokio::process::Command::new("ssh")
+            .arg("-M")
+            .arg("-f")
+            .arg("-N")
+            .arg("-S")
+            .arg(&ctl)
+            .args(["-o", "ControlPersist=yes"])
+            .args(["-o", "StrictHostKeyChecking=yes"])
+            .arg("-o")
+            .arg(format!("UserKnownHostsFile={}", known_hosts_path().display()))
+            .args(["-o", "PreferredAuthentications=password,keyboard-interactive"])
+            .args(["-o", "NumberOfPasswordPrompts=1"])
+            .arg("-o")
+            .arg(format!("ServerAliveInterval={}", KEEPALIVE_INTERVAL_SECS))
+            .arg("-o")
+            .arg(format!("ConnectTimeout={}", CONNECT_TIMEOUT_SECS))
+            .arg("-p")
+            .arg(self.config.port.to_string())
+            .arg("-l")
+            .arg(&self.config.user)
+            .arg(&self.config.host)
+            .env("SSH_ASKPASS", &askpass)
+            .env("SSH_ASKPASS_REQUIRE", "force")
+            .env("DISPLAY", std::env::var("DISPLAY").unwrap_or_else(|_| ":0".to_string()))
+            .stdin(std::process::Stdio::null())
+            .output()
+            .await
+            .map_err(|e| anyhow!("Failed to launch ssh: {}", e))?;
+        if !output.status.success() {
+            let _ = std::fs::remove_dir_all(&dir);
+            return Err(anyhow!(
+                "SSH connect failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok((openssh::Session::resume_mux(ctl.into_boxed_path(), None), dir))
+    }
+
+    /// Refuse to connect to a host whose key the user hasn't confirmed. On
+    /// first contact the offered keys go out as `ssh:host_key` so the UI can
+    /// show the fingerprint; `trust_host_key` records the user's answer.
+    async fn verify_host_key(&self) -> Result<()> {
+        if host_is_known(&self.config.host, self.config.port).await? {
+            return Ok(());
+        }
+        let keys = scan_host_keys(&self.config.host, self.config.port).await?;
+        if let Some(app) = &self.app {
+            let _ = app.emit(
+                "ssh:host_key",
+                serde_json::json!({
+                    "host": self.config.host,
+                    "port": self.config.port,
+                    "keys": keys,
+                }),
+            );
+        }
+        let shown = keys
+            .iter()
+            .map(|k| format!("{} {}", k.key_type, k.fingerprint))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Err(anyhow!(
+            "Host key for {} is not trusted yet ({}); confirm the fingerprint to connect",
+            host_pattern(&self.config.host, self.config.port),
+            shown
+        ))
     }
 }
 
@@ -176,3 +1467,171 @@ pub type SharedSshSession = Arc<Mutex<SshSession>>;
 pub fn new_shared_session() -> SharedSshSession {
     Arc::new(Mutex::new(SshSession::new()))
 }
+
+/// Drop the dead session and retry with exponential backoff. The lock is
+/// only taken between attempts, so remote calls fail fast while it retries
+/// instead of waiting out the backoff. A connect or disconnect in the
+/// meantime wins over a late attempt.
+pub async fn reconnect(shared: &SharedSshSession) -> Result<()> {
+    let opener = {
+        let mut ssh = shared.lock().await;
+        if ssh.reconnecting {
+            return Err(anyhow!("SSH is already reconnecting"));
+        }
+        if ssh.is_alive().await {
+            return Ok(());
+        }
+        ssh.reconnecting = true;
+        if let Some(session) = ssh.session.take() {
+            close_session(session).await;
+        }
+        ssh.remove_mux_dir();
+        ssh.opener()
+    };
+    let mut last_err = anyhow!("SSH reconnect failed");
+    for attempt in 1..=MAX_RECONNECT_ATTEMPTS {
+        {
+            let mut ssh = shared.lock().await;
+            if attempt > 1 && ssh.status != ConnectionStatus::Reconnecting(attempt - 1) {
+                return superseded(&mut ssh);
+            }
+            ssh.set_status(ConnectionStatus::Reconnecting(attempt));
+        }
+        let opened = opener.open().await;
+        let mut ssh = shared.lock().await;
+        if ssh.status != ConnectionStatus::Reconnecting(attempt) {
+            if let Ok((session, mux_dir)) = opened {
+                let _ = session.close().await;
+                if let Some(dir) = mux_dir {
+                    let _ = std::fs::remove_dir_all(dir);
+                }
+            }
+            return superseded(&mut ssh);
+        }
+        match opened {
+            Ok((session, mux_dir)) => {
+                ssh.session = Some(Arc::new(session));
+                ssh.mux_dir = mux_dir;
+                ssh.reconnecting = false;
+                ssh.restore_forwards().await;
+                ssh.set_status(ConnectionStatus::Connected);
+                return Ok(());
+            }
+            Err(e) => last_err = e,
+        }
+        drop(ssh);
+        if attempt < MAX_RECONNECT_ATTEMPTS {
+            tokio::time::sleep(Duration::from_secs(2u64 << (attempt - 1))).await;
+        }
+    }
+    let mut ssh = shared.lock().await;
+    ssh.reconnecting = false;
+    if ssh.status == ConnectionStatus::Reconnecting(MAX_RECONNECT_ATTEMPTS) {
+        ssh.set_status(ConnectionStatus::Error(last_err.to_string()));
+    }
+    Err(last_err)
+}
+
+/// The user connected or disconnected while `reconnect` was between attempts.
+fn superseded(ssh: &mut SshSession) -> Result<()> {
+    ssh.reconnecting = false;
+    match ssh.status {
+        ConnectionStatus::Connected => Ok(()),
+        _ => Err(anyhow!("SSH is not connected")),
+    }
+}
+
+/// Make sure there's a live session before a remote call, reconnecting
+/// if the previous one died (e.g. across laptop sleep). Never opens a
+/// session by itself — that's the connection manager's job.
+pub async fn ensure_connected(shared: &SharedSshSession) -> Result<()> {
+    {
+        let ssh = shared.lock().await;
+        if ssh.is_alive().await {
+            return Ok(());
+        }
+        if ssh.reconnecting {
+            return Err(anyhow!("SSH is reconnecting"));
+        }
+        if ssh.session.is_none() {
+            return Err(anyhow!("SSH is not connected"));
+        }
+    }
+    reconnect(shared).await
+}
+
+enum ManagerCommand {
+    Connect(oneshot::Sender<Result<()>>),
+    Disconnect(oneshot::Sender<()>),
+}
+
+/// Handle to the background task that owns the SSH session lifecycle:
+/// explicit connect/disconnect, keepalive checks and the reconnect policy.
+#[derive(Clone)]
+pub struct SshManager {
+    tx: mpsc::Sender<ManagerCommand>,
+}
+
+impl SshManager {
+    pub fn spawn(shared: SharedSshSession) -> Self {
+        let (tx, rx) = mpsc::channel(8);
+        tauri::async_runtime::spawn(run_manager(shared, rx));
+        Self { tx }
+    }
+
+    pub async fn connect(&self) -> Result<()> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(ManagerCommand::Connect(reply))
+            .await
+            .map_err(|_| anyhow!("SSH manager stopped"))?;
+        rx.await.map_err(|_| anyhow!("SSH manager stopped"))?
+    }
+
+    pub async fn disconnect(&self) -> Result<()> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(ManagerCommand::Disconnect(reply))
+            .await
+            .map_err(|_| anyhow!("SSH manager stopped"))?;
+        rx.await.map_err(|_| anyhow!("SSH manager stopped"))
+    }
+}
+
+/// While the user wants a connection, check it every keepalive interval and
+/// reconnect (with bounded retries) when it has died. After a disconnect the
+/// session is left alone.
+async fn run_manager(shared: SharedSshSession, mut rx: mpsc::Receiver<ManagerCommand>) {
+    let mut wanted = false;
+    let mut ticker = tokio::time::interval(Duration::from_secs(KEEPALIVE_INTERVAL_SECS));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            cmd = rx.recv() => match cmd {
+                Some(ManagerCommand::Connect(reply)) => {
+                    wanted = true;
+                    let mut ssh = shared.lock().await;
+                    let result = if ssh.is_alive().await { Ok(()) } else { ssh.connect().await };
+                    let _ = reply.send(result);
+                }
+                Some(ManagerCommand::Disconnect(reply)) => {
+                    wanted = false;
+                    shared.lock().await.disconnect().await;
+                    let _ = reply.send(());
+                }
+                None => break,
+            },
+            _ = ticker.tick() => {
+                if !wanted {
+                    continue;
+                }
+                if shared.lock().await.is_alive().await {
+                    continue;
+                }
+                if let Err(e) = reconnect(&shared).await {
+                    eprintln!("[ssh] Reconnect failed: {}", e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,671 @@
+use crate::ssh::SshHandle;
+use crate::{db, dnd, mirror, openclaw, relations, settings, watcher, workspace, AppState};
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager};
+
+pub const TARGET_SETTING: &str = "sync_target";
+pub const FOLDER_SETTING: &str = "sync_folder";
+
+/// Bumped when the snapshot layout (not the app schema) changes.
+const SNAPSHOT_FORMAT: u32 = 1;
+
+/// Tables replicated between devices. Every row has an `id` and an
+/// `updated_at`; the copy with the newer `updated_at` wins.
+const SYNCED: &[&str] = &[relations::PROJECTS, relations::THREADS, relations::BRAIN_DUMPS, relations::KANBAN_ITEMS];
+
+/// Layout of the shared folder: `devices/<device id>.db` is each device's
+/// latest snapshot, `sessions/<agent>/<session>.jsonl` the session files.
+const DEVICES: &str = "devices";
+const SESSIONS: &str = "sessions";
+/// The shared folder when syncing through the SSH host, under its state dir.
+const REMOTE_DIR: &str = "chat-sync";
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    pub device_id: String,
+    pub target: String,
+    /// Other devices whose snapshot had something new.
+    pub peers_merged: u32,
+    pub rows_written: u32,
+    pub rows_deleted: u32,
+    pub conflicts: Vec<SyncConflict>,
+    pub files_pulled: u32,
+    pub files_pushed: u32,
+    /// Newer session files not pulled because a reply is being written to
+    /// this machine's copy; the next sync tries again.
+    pub files_busy: u32,
+    /// Snapshots that couldn't be merged, and why.
+    pub skipped: Vec<String>,
+    pub finished_at: i64,
+}
+
+/// A row both devices changed since they last synced with each other.
+/// Resolved like any other row, by `updated_at`; listed so the user can
+/// check the result.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConflict {
+    pub table: String,
+    pub id: String,
+    pub device_id: String,
+    /// The other device's copy replaced this one's.
+    pub theirs_kept: bool,
+}
+
+/// Where snapshots and session files are exchanged.
+enum Target {
+    /// A folder another tool (iCloud Drive, Syncthing, Dropbox) keeps the
+    /// same on every machine.
+    Folder(PathBuf),
+    /// `REMOTE_DIR` on the SSH host every device connects to.
+    Remote(Box<SshHandle>, String),
+}
+
+impl Target {
+    fn describe(&self) -> String {
+        match self {
+            Target::Folder(dir) => dir.display().to_string(),
+            Target::Remote(_, dir) => format!("{} on the SSH host", dir),
+        }
+    }
+
+    /// File names in `rel`; nothing if it doesn't exist yet.
+    async fn list(&self, rel: &str) -> Result<Vec<String>> {
+        match self {
+            Target::Folder(dir) => {
+                let Ok(entries) = std::fs::read_dir(dir.join(rel)) else {
+                    return Ok(Vec::new());
+                };
+                Ok(entries
+                    .flatten()
+                    .filter(|e| e.path().is_file())
+                    .filter_map(|e| e.file_name().to_str().map(str::to_string))
+                    .collect())
+            }
+            Target::Remote(ssh, dir) => {
+                let path = format!("{}/{}", dir, rel);
+                ssh.exec_args("mkdir", &["-p", &path]).await?;
+                Ok(ssh
+                    .list_dir(&path)
+                    .await?
+                    .into_iter()
+                    .filter(|e| !e.is_dir)
+                    .map(|e| e.name)
+                    .collect())
+            }
+        }
+    }
+
+    async fn read(&self, rel: &str) -> Result<Option<Vec<u8>>> {
+        match self {
+            Target::Folder(dir) => match std::fs::read(dir.join(rel)) {
+                Ok(data) => Ok(Some(data)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e).with_context(|| format!("Failed to read {}", dir.join(rel).display())),
+            },
+            Target::Remote(ssh, dir) => ssh.read_file(&format!("{}/{}", dir, rel)).await,
+        }
+    }
+
+    /// Write through a temporary name, so other devices never pick up half
+    /// a file.
+    async fn write(&self, rel: &str, data: &[u8]) -> Result<()> {
+        let tmp = format!("{}.{}.tmp", rel, uuid::Uuid::new_v4().simple());
+        match self {
+            Target::Folder(dir) => {
+                let path = dir.join(rel);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(dir.join(&tmp), data)?;
+                std::fs::rename(dir.join(&tmp), &path)?;
+            }
+            Target::Remote(ssh, dir) => {
+                let (path, tmp) = (format!("{}/{}", dir, rel), format!("{}/{}", dir, tmp));
+                if let Some((parent, _)) = path.rsplit_once('/') {
+                    ssh.exec_args("mkdir", &["-p", parent]).await?;
+                }
+                ssh.write_file(&tmp, data).await?;
+                ssh.exec_args("mv", &["-f", &tmp, &path]).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A session file as one device has it, by `<agent>/<session>.jsonl`.
+#[derive(Debug, Clone)]
+struct FileEntry {
+    path: String,
+    size: u64,
+    modified_at: i64,
+    hash: String,
+}
+
+/// This machine's id, made on first use. It lives in a file rather than the
+/// database so a workspace bundle restored on another machine doesn't bring
+/// it along.
+pub fn device_id() -> Result<String> {
+    let path = dirs::home_dir()
+        .unwrap_or_default()
+        .join(".openclaw")
+        .join("chat")
+        .join("device-id");
+    if let Ok(id) = std::fs::read_to_string(&path) {
+        if valid_id(id.trim()) {
+            return Ok(id.trim().to_string());
+        }
+    }
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, &id).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(id)
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 64 && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+/// Scheduled sync; does nothing while sync is off.
+pub async fn sync_job(app: AppHandle) -> Result<String> {
+    let target = db::call(|conn| settings::get_text(conn, TARGET_SETTING)).await?;
+    if target.is_none() {
+        return Ok("Sync is off".to_string());
+    }
+    let report = sync_now(&app).await?;
+    Ok(format!(
+        "{} rows in, {} deleted, {} conflicts, {} files pulled, {} pushed",
+        report.rows_written,
+        report.rows_deleted,
+        report.conflicts.len(),
+        report.files_pulled,
+        report.files_pushed
+    ))
+}
+
+/// Merge every other device's snapshot into this database, exchange session
+/// files, then publish this device's own snapshot. The result is also sent
+/// as "sync:finished".
+pub async fn sync_now(app: &AppHandle) -> Result<SyncReport> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err(anyhow!("A sync is already running"));
+    }
+    let result = run(app).await;
+    RUNNING.store(false, Ordering::SeqCst);
+    let report = result?;
+    dnd::emit(app, "sync:finished", &report);
+    Ok(report)
+}
+
+async fn target(app: &AppHandle) -> Result<Target> {
+    let (kind, folder) = db::call(|conn| {
+        Ok((settings::get_text(conn, TARGET_SETTING)?, settings::get_text(conn, FOLDER_SETTING)?))
+    })
+    .await?;
+    match kind.as_deref() {
+        Some("folder") => {
+            let folder = folder.ok_or_else(|| anyhow!("Choose a folder to sync through"))?;
+            let dir = openclaw::expand_home(folder.trim());
+            if !dir.is_dir() {
+                return Err(anyhow!("Sync folder {} doesn't exist", dir.display()));
+            }
+            Ok(Target::Folder(dir))
+        }
+        Some("remote") => {
+            let ssh = crate::remote_handle(&app.state::<AppState>()).await?;
+            let dir = ssh.state_path(REMOTE_DIR)?;
+            Ok(Target::Remote(Box::new(ssh), dir))
+        }
+        _ => Err(anyhow!("Sync is off; choose a folder or the SSH host in settings")),
+    }
+}
+
+async fn run(app: &AppHandle) -> Result<SyncReport> {
+    let db = db::pool()?;
+    let target = target(app).await?;
+    let device = device_id()?;
+    let mut report = SyncReport {
+        device_id: device.clone(),
+        target: target.describe(),
+        ..Default::default()
+    };
+
+    // The newest copy of each session file any other device has
+    let mut theirs: HashMap<String, FileEntry> = HashMap::new();
+    for name in target.list(DEVICES).await? {
+        let Some(peer) = name.strip_suffix(".db").filter(|id| valid_id(id) && *id != device) else {
+            continue;
+        };
+        let Some(data) = target.read(&format!("{}/{}", DEVICES, name)).await? else {
+            continue;
+        };
+        let (device, peer) = (device.clone(), peer.to_string());
+        let merged = db
+            .call({
+                let peer = peer.clone();
+                move |conn| merge_snapshot(conn, &device, &peer, &data)
+            })
+            .await;
+        let merged = match merged {
+            Ok(merged) => merged,
+            Err(e) => {
+                report.skipped.push(format!("{}: {}", peer, e));
+                continue;
+            }
+        };
+        if let Some(rows) = merged.rows {
+            report.peers_merged += 1;
+            report.rows_written += rows.written;
+            report.rows_deleted += rows.deleted;
+            report.conflicts.extend(rows.conflicts);
+        }
+        for file in merged.files {
+            if theirs.get(&file.path).is_none_or(|f| f.modified_at < file.modified_at) {
+                theirs.insert(file.path.clone(), file);
+            }
+        }
+    }
+
+    let ours = db.call(local_files).await?;
+    let watcher_state = app.state::<AppState>().watcher_state.clone();
+    let mut in_folder = Vec::new();
+
+    for file in theirs.values() {
+        let mine = ours.get(&file.path);
+        if mine.is_some_and(|m| m.entry.hash == file.hash || m.entry.modified_at >= file.modified_at) {
+            continue;
+        }
+        let Some((agent_id, session_id)) = split_session(&file.path) else {
+            continue;
+        };
+        if watcher::awaiting_reply(&watcher_state, session_id) {
+            report.files_busy += 1;
+            continue;
+        }
+        let Some(content) = target.read(&format!("{}/{}", SESSIONS, file.path)).await? else {
+            continue;
+        };
+        // Another device's newer upload, or one still arriving; next time
+        if format!("{:x}", Sha256::digest(&content)) != file.hash {
+            continue;
+        }
+        let local = openclaw::session_path(agent_id, session_id);
+        if let Some(parent) = local.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = local.with_extension("jsonl.sync");
+        std::fs::write(&tmp, &content)?;
+        std::fs::rename(&tmp, &local)?;
+        in_folder.push(FileEntry {
+            modified_at: workspace::modified_ms(&local),
+            size: content.len() as u64,
+            ..file.clone()
+        });
+        report.files_pulled += 1;
+    }
+
+    for (path, file) in &ours {
+        if file.pushed_hash.as_deref() == Some(&file.entry.hash) {
+            continue;
+        }
+        if theirs
+            .get(path)
+            .is_some_and(|f| f.hash == file.entry.hash || f.modified_at > file.entry.modified_at)
+        {
+            continue;
+        }
+        let content = std::fs::read(&file.local).with_context(|| format!("Failed to read {}", file.local.display()))?;
+        // Appended to since it was hashed; pushed next time
+        if format!("{:x}", Sha256::digest(&content)) != file.entry.hash {
+            continue;
+        }
+        target.write(&format!("{}/{}", SESSIONS, path), &content).await?;
+        in_folder.push(file.entry.clone());
+        report.files_pushed += 1;
+    }
+
+    let snapshot = std::env::temp_dir().join(format!("openclaw-sync-{}.db", uuid::Uuid::new_v4().simple()));
+    let data = db
+        .call({
+            let (device, snapshot) = (device.clone(), snapshot.clone());
+            move |conn| {
+                mark_pushed(conn, &in_folder)?;
+                let files = local_files(conn)?.into_values().map(|f| f.entry).collect::<Vec<_>>();
+                write_snapshot(conn, &device, &files, &snapshot)?;
+                Ok::<_, anyhow::Error>(std::fs::read(&snapshot)?)
+            }
+        })
+        .await;
+    let _ = std::fs::remove_file(&snapshot);
+    target.write(&format!("{}/{}.db", DEVICES, device), &data?).await?;
+
+    report.finished_at = chrono::Utc::now().timestamp_millis();
+    Ok(report)
+}
+
+/// `<agent>/<session>.jsonl` as (agent, session), refusing anything that
+/// would land outside the agents directory.
+fn split_session(path: &str) -> Option<(&str, &str)> {
+    let (agent_id, file) = path.split_once('/')?;
+    let session_id = file.strip_suffix(".jsonl")?;
+    // cache_path refuses ids that would land outside its directory
+    mirror::cache_path(agent_id, session_id).ok()?;
+    Some((agent_id, session_id))
+}
+
+struct LocalFile {
+    local: PathBuf,
+    entry: FileEntry,
+    pushed_hash: Option<String>,
+}
+
+/// This machine's session files, hashed. Hashes are cached in `sync_files`
+/// by size and modification time, so unchanged files aren't read again.
+fn local_files(conn: &Connection) -> Result<HashMap<String, LocalFile>> {
+    let mut files = HashMap::new();
+    for (agent_id, local) in workspace::session_files(&openclaw::agents_dir(), "sessions") {
+        let Some(name) = local.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let path = format!("{}/{}", agent_id, name);
+        let Ok(meta) = std::fs::metadata(&local) else {
+            continue;
+        };
+        let (size, modified_at) = (meta.len(), workspace::modified_ms(&local));
+        let cached = conn
+            .query_row(
+                "SELECT size, modified_at, hash, pushed_hash FROM sync_files WHERE path = ?1",
+                params![path],
+                |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, i64>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let (hash, pushed_hash) = match cached {
+            Some((s, m, hash, pushed)) if s as u64 == size && m == modified_at => (hash, pushed),
+            cached => {
+                let Ok(content) = std::fs::read(&local) else {
+                    continue;
+                };
+                let hash = format!("{:x}", Sha256::digest(&content));
+                let pushed = cached.and_then(|(_, _, _, pushed)| pushed);
+                conn.execute(
+                    "INSERT INTO sync_files (path, size, modified_at, hash, pushed_hash) VALUES (?1, ?2, ?3, ?4, ?5)
+                     ON CONFLICT(path) DO UPDATE SET size = excluded.size, modified_at = excluded.modified_at,
+                        hash = excluded.hash",
+                    params![path, size as i64, modified_at, hash, pushed],
+                )?;
+                (hash, pushed)
+            }
+        };
+        files.insert(
+            path.clone(),
+            LocalFile {
+                local,
+                entry: FileEntry {
+                    path,
+                    size,
+                    modified_at,
+                    hash,
+                },
+                pushed_hash,
+            },
+        );
+    }
+    Ok(files)
+}
+
+/// Remember that the shared folder has these versions, whether this device
+/// pushed them or pulled them from there.
+fn mark_pushed(conn: &Connection, files: &[FileEntry]) -> Result<()> {
+    for file in files {
+        conn.execute(
+            "INSERT INTO sync_files (path, size, modified_at, hash, pushed_hash) VALUES (?1, ?2, ?3, ?4, ?4)
+             ON CONFLICT(path) DO UPDATE SET size = excluded.size, modified_at = excluded.modified_at,
+                hash = excluded.hash, pushed_hash = excluded.hash",
+            params![file.path, file.size as i64, file.modified_at, file.hash],
+        )?;
+    }
+    Ok(())
+}
+
+struct Merged {
+    /// None when the snapshot had nothing this device hadn't merged already.
+    rows: Option<MergedRows>,
+    files: Vec<FileEntry>,
+}
+
+#[derive(Default)]
+struct MergedRows {
+    written: u32,
+    deleted: u32,
+    conflicts: Vec<SyncConflict>,
+}
+
+fn merge_snapshot(conn: &Connection, device: &str, peer: &str, data: &[u8]) -> Result<Merged> {
+    let copy = std::env::temp_dir().join(format!("openclaw-sync-{}.db", uuid::Uuid::new_v4().simple()));
+    std::fs::write(&copy, data)?;
+    let result = merge_copy(conn, device, peer, &copy);
+    let _ = std::fs::remove_file(&copy);
+    result
+}
+
+fn merge_copy(conn: &Connection, device: &str, peer: &str, copy: &Path) -> Result<Merged> {
+    let snapshot = Connection::open(copy)?;
+    let info = |key: &str| {
+        snapshot
+            .query_row("SELECT value FROM sync_info WHERE key = ?1", [key], |row| row.get::<_, String>(0))
+            .optional()
+    };
+    let format: Option<u32> = info("format")?.and_then(|v| v.parse().ok());
+    match format {
+        None => return Err(anyhow!("Not a sync snapshot")),
+        Some(f) if f > SNAPSHOT_FORMAT => return Err(anyhow!("Written by a newer version of the app")),
+        Some(_) => {}
+    }
+    if info("device_id")?.as_deref() != Some(peer) {
+        return Err(anyhow!("Snapshot belongs to another device"));
+    }
+    let schema: u32 = info("schema_version")?.and_then(|v| v.parse().ok()).unwrap_or(0);
+    if schema > db::schema_version(conn)? {
+        return Err(anyhow!("Written by a newer version of the app; update the app"));
+    }
+    let exported_at: i64 = info("exported_at")?.and_then(|v| v.parse().ok()).unwrap_or(0);
+    let mut stmt = snapshot.prepare("SELECT path, modified_at, hash FROM sync_manifest")?;
+    let files = stmt
+        .query_map([], |row| {
+            Ok(FileEntry {
+                path: row.get(0)?,
+                size: 0,
+                modified_at: row.get(1)?,
+                hash: row.get(2)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    drop(stmt);
+    drop(snapshot);
+
+    let seen: Option<i64> = conn
+        .query_row("SELECT exported_at FROM sync_peers WHERE device_id = ?1", [peer], |row| row.get(0))
+        .optional()?;
+    if seen.is_some_and(|seen| seen >= exported_at) {
+        return Ok(Merged { rows: None, files });
+    }
+
+    let copy_str = copy.to_str().ok_or_else(|| anyhow!("Unsupported path: {}", copy.display()))?;
+    conn.execute("ATTACH DATABASE ?1 AS peer", [copy_str])?;
+    let rows = merge_rows(conn, device, peer, exported_at);
+    conn.execute_batch("DETACH DATABASE peer")?;
+    Ok(Merged { rows: Some(rows?), files })
+}
+
+/// Merge the attached `peer` snapshot's rows in one transaction: its
+/// deletions first, then rows this device doesn't have, then rows where its
+/// copy is newer. Ties go to the larger device id, so both sides agree.
+///
+/// A conflict is a row changed here since this device last merged `peer`
+/// and there since `peer` last merged this device, each judged by its own
+/// device's clock.
+fn merge_rows(conn: &Connection, device: &str, peer: &str, exported_at: i64) -> Result<MergedRows> {
+    let merged_at: i64 = conn
+        .query_row("SELECT merged_at FROM sync_peers WHERE device_id = ?1", [peer], |row| row.get(0))
+        .optional()?
+        .unwrap_or(0);
+    let their_merged_at: i64 = conn
+        .query_row("SELECT merged_at FROM peer.sync_clock WHERE device_id = ?1", [device], |row| row.get(0))
+        .optional()?
+        .unwrap_or(0);
+    let tx = conn.unchecked_transaction()?;
+    let mut merged = MergedRows::default();
+
+    let mut stmt = tx.prepare("SELECT table_name, row_id, deleted_at FROM peer.sync_tombstones")?;
+    let tombstones = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    drop(stmt);
+    for (table, id, deleted_at) in tombstones {
+        let Some(table) = SYNCED.iter().find(|t| **t == table) else {
+            continue;
+        };
+        let updated_at: Option<i64> = tx
+            .query_row(
+                &format!("SELECT updated_at FROM main.{} WHERE id = ?1", db::quote_ident(table)),
+                [&id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match updated_at {
+            // Changed here after it was deleted there: it comes back
+            Some(updated_at) if updated_at > deleted_at => continue,
+            Some(_) => {
+                relations::delete_row(&tx, table, &id)?;
+                merged.deleted += 1;
+            }
+            None => {}
+        }
+        // Keep the original time so a third device judges it the same way
+        tx.execute(
+            "INSERT INTO main.sync_tombstones (table_name, row_id, deleted_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(table_name, row_id) DO UPDATE SET deleted_at = excluded.deleted_at",
+            params![table, id, deleted_at],
+        )?;
+    }
+
+    for &table in SYNCED {
+        let ours = workspace::column_names(&tx, "main", table)?;
+        let theirs = workspace::column_names(&tx, "peer", table)?;
+        let shared: Vec<&String> = ours.iter().filter(|c| theirs.contains(c)).collect();
+        if !shared.iter().any(|c| *c == "id") || !shared.iter().any(|c| *c == "updated_at") {
+            continue;
+        }
+        let columns = shared.iter().map(|c| db::quote_ident(c)).collect::<Vec<_>>().join(", ");
+        let differs = shared
+            .iter()
+            .map(|c| format!("m.{0} IS NOT p.{0}", db::quote_ident(c)))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let quoted = db::quote_ident(table);
+
+        merged.written += tx.execute(
+            &format!(
+                "INSERT INTO main.{quoted} ({columns})
+                 SELECT {columns} FROM peer.{quoted} p
+                 WHERE p.id NOT IN (SELECT id FROM main.{quoted})
+                   AND NOT EXISTS (SELECT 1 FROM main.sync_tombstones s
+                                   WHERE s.table_name = ?1 AND s.row_id = p.id AND s.deleted_at >= p.updated_at)"
+            ),
+            [table],
+        )? as u32;
+
+        let mut stmt = tx.prepare(&format!(
+            "SELECT p.id, p.updated_at, m.updated_at FROM peer.{quoted} p JOIN main.{quoted} m ON m.id = p.id
+             WHERE {differs}"
+        ))?;
+        let changed = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        drop(stmt);
+        for (id, their_updated, our_updated) in changed {
+            let theirs_kept = their_updated > our_updated || (their_updated == our_updated && peer > device);
+            if our_updated > merged_at && their_updated > their_merged_at {
+                merged.conflicts.push(SyncConflict {
+                    table: table.to_string(),
+                    id: id.clone(),
+                    device_id: peer.to_string(),
+                    theirs_kept,
+                });
+            }
+            if theirs_kept {
+                tx.execute(
+                    &format!(
+                        "UPDATE main.{quoted} SET ({columns}) = (SELECT {columns} FROM peer.{quoted} WHERE id = ?1)
+                         WHERE id = ?1"
+                    ),
+                    [&id],
+                )?;
+                merged.written += 1;
+            }
+        }
+    }
+
+    tx.execute(
+        "INSERT INTO sync_peers (device_id, exported_at, merged_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(device_id) DO UPDATE SET exported_at = excluded.exported_at, merged_at = excluded.merged_at",
+        params![peer, exported_at, chrono::Utc::now().timestamp_millis()],
+    )?;
+    tx.commit()?;
+    Ok(merged)
+}
+
+/// This device's snapshot: the synced tables, its tombstones, which
+/// snapshot of every other device it has merged, and its session files.
+fn write_snapshot(conn: &Connection, device: &str, files: &[FileEntry], dest: &Path) -> Result<()> {
+    let dest_str = dest.to_str().ok_or_else(|| anyhow!("Unsupported path: {}", dest.display()))?;
+    conn.execute("ATTACH DATABASE ?1 AS snapshot", [dest_str])?;
+    let result = (|| {
+        let tx = conn.unchecked_transaction()?;
+        for table in SYNCED.iter().copied().chain(["sync_tombstones"]) {
+            let table = db::quote_ident(table);
+            tx.execute(&format!("CREATE TABLE snapshot.{table} AS SELECT * FROM main.{table}"), [])?;
+        }
+        tx.execute_batch(
+            "CREATE TABLE snapshot.sync_info (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+             CREATE TABLE snapshot.sync_clock AS SELECT device_id, exported_at, merged_at FROM main.sync_peers;
+             CREATE TABLE snapshot.sync_manifest (path TEXT PRIMARY KEY, modified_at INTEGER NOT NULL, hash TEXT NOT NULL);",
+        )?;
+        for (key, value) in [
+            ("format", SNAPSHOT_FORMAT.to_string()),
+            ("device_id", device.to_string()),
+            ("schema_version", db::schema_version(&tx)?.to_string()),
+            ("exported_at", chrono::Utc::now().timestamp_millis().to_string()),
+        ] {
+            tx.execute("INSERT INTO snapshot.sync_info (key, value) VALUES (?1, ?2)", params![key, value])?;
+        }
+        for file in files {
+            tx.execute(
+                "INSERT INTO snapshot.sync_manifest (path, modified_at, hash) VALUES (?1, ?2, ?3)",
+                params![file.path, file.modified_at, file.hash],
+            )?;
+        }
+        tx.commit()?;
+        Ok::<_, anyhow::Error>(())
+    })();
+    conn.execute_batch("DETACH DATABASE snapshot")?;
+    result
+}
@@ -0,0 +1,114 @@
+use crate::openclaw::{self, ChatMessage};
+use crate::{db, prompts};
+use anyhow::{anyhow, Result};
+
+/// Model used for title generation; unset or empty falls back to the agent's default.
+pub const MODEL_SETTING: &str = "title_model";
+/// Titles longer than this are cut at the last word boundary that fits.
+pub const MAX_TITLE_CHARS: usize = 60;
+
+/// How much of a first message goes into the prompt.
+const MESSAGE_EXCERPT_CHARS: usize = 500;
+/// How many messages, and how much of each, summarize a conversation.
+const CONVERSATION_MESSAGES: usize = 5;
+const CONVERSATION_EXCERPT_CHARS: usize = 200;
+
+/// Title for a new thread from its first user message.
+pub async fn for_message(text: &str) -> Result<String> {
+    generate(prompts::TITLE, &excerpt(text, MESSAGE_EXCERPT_CHARS)).await
+}
+
+/// Title for an existing thread from the start of its conversation.
+pub async fn for_conversation(messages: &[ChatMessage]) -> Result<String> {
+    let summary = messages
+        .iter()
+        .take(CONVERSATION_MESSAGES)
+        .map(|m| format!("{}: {}", m.role, excerpt(&m.content, CONVERSATION_EXCERPT_CHARS)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    generate(prompts::TITLE_CONVERSATION, &summary).await
+}
+
+/// Fill the `purpose` prompt with `content` and ask the agent for a title.
+/// Identical prompts for the same model reuse the cached title instead of
+/// calling out.
+async fn generate(purpose: &str, content: &str) -> Result<String> {
+    let prompt = prompts::build(purpose, &[("content", content)]).await?;
+    let key_prompt = prompt.clone();
+    let (model, key, cached) = db::call(move |conn| {
+        let model = db::get_setting(conn, MODEL_SETTING)?.filter(|m| !m.trim().is_empty());
+        let key = cache_key(model.as_deref(), &key_prompt);
+        let cached = db::get_cached_title(conn, &key)?;
+        Ok((model, key, cached))
+    })
+    .await?;
+    if let Some(title) = cached {
+        return Ok(title);
+    }
+    let reply = openclaw::send_and_capture(openclaw::DEFAULT_AGENT_ID, None, model.as_deref(), &prompt).await?;
+    let title = clean_title(&reply.content);
+    if title.is_empty() {
+        return Err(anyhow!("Empty title generated"));
+    }
+    let cached = title.clone();
+    db::call(move |conn| db::cache_title(conn, &key, &cached)).await?;
+    Ok(title)
+}
+
+/// First `max` characters of `text`, never splitting a character.
+fn excerpt(text: &str, max: usize) -> String {
+    text.chars().take(max).collect()
+}
+
+/// FNV-1a over model and prompt. Stable across builds, unlike `DefaultHasher`.
+fn cache_key(model: Option<&str>, prompt: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in model.unwrap_or("").bytes().chain([0]).chain(prompt.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Reduce a model reply to a bare title: first non-empty line, no quotes,
+/// markdown emphasis, "Title:" label or emoji, at most `MAX_TITLE_CHARS`.
+fn clean_title(reply: &str) -> String {
+    let line = reply.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("");
+    let line = line
+        .strip_prefix("Title:")
+        .or_else(|| line.strip_prefix("title:"))
+        .unwrap_or(line);
+    let stripped: String = line.chars().filter(|c| !is_emoji(*c)).collect();
+    let words = stripped.split_whitespace().collect::<Vec<_>>().join(" ");
+    let wrapper = |c: char| matches!(c, '"' | '\'' | '`' | '*' | '_' | '#') || c.is_whitespace();
+    let title = words
+        .trim_matches(wrapper)
+        .trim_end_matches(|c: char| c == '.' || wrapper(c));
+    truncate_words(title, MAX_TITLE_CHARS)
+}
+
+fn truncate_words(title: &str, max: usize) -> String {
+    if title.chars().count() <= max {
+        return title.to_string();
+    }
+    let cut: String = title.chars().take(max).collect();
+    match cut.rfind(' ') {
+        Some(i) if i > 0 => cut[..i].trim_end_matches([',', ';', ':', '-']).to_string(),
+        _ => cut,
+    }
+}
+
+/// Pictographs, dingbats, flags, and the joiners and selectors that glue them together.
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF
+            | 0x2600..=0x27BF
+            | 0x2B00..=0x2BFF
+            | 0x2300..=0x23FF
+            | 0x200D
+            | 0x20E3
+            | 0xFE00..=0xFE0F
+            | 0xE0020..=0xE007F
+    )
+}
@@ -0,0 +1,137 @@
+use crate::deeplink::{self, Navigation};
+use crate::{scheduler, AppState};
+use std::sync::Mutex;
+use tauri::menu::{CheckMenuItemBuilder, Menu, MenuBuilder, MenuEvent, SubmenuBuilder};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Emitter, Manager};
+
+const TRAY_ID: &str = "main";
+
+const QUICK_DUMP: &str = "quick_dump";
+const REMOTE_MODE: &str = "remote_mode";
+const RUN_PROACTIVE: &str = "run_proactive";
+const SHOW: &str = "show";
+/// Prefix of the unread thread items; the rest of the id is the thread's.
+const THREAD_PREFIX: &str = "thread:";
+
+/// Most unread threads listed in the menu.
+const MAX_UNREAD: usize = 8;
+
+/// Threads with a reply the user hasn't looked at yet, newest first, as
+/// (id, name). Kept in memory only, so it starts empty each launch.
+static UNREAD: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+pub fn init(app: &tauri::App) -> tauri::Result<()> {
+    let menu = build_menu(app.handle())?;
+    let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("OpenClaw Chat")
+        .menu(&menu)
+        .show_menu_on_left_click(true)
+        .on_menu_event(on_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+    Ok(())
+}
+
+/// A reply came in for a thread that isn't on screen.
+pub fn mark_unread(app: &AppHandle, thread_id: &str, name: &str) {
+    {
+        let mut unread = UNREAD.lock().unwrap();
+        unread.retain(|(id, _)| id != thread_id);
+        unread.insert(0, (thread_id.to_string(), name.to_string()));
+    }
+    refresh(app);
+}
+
+pub fn mark_read(app: &AppHandle, thread_id: &str) {
+    let removed = {
+        let mut unread = UNREAD.lock().unwrap();
+        let before = unread.len();
+        unread.retain(|(id, _)| id != thread_id);
+        unread.len() != before
+    };
+    if removed {
+        refresh(app);
+    }
+}
+
+/// Rebuild the menu after remote mode or the unread threads change.
+pub fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_menu(app) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => eprintln!("[tray] Couldn't rebuild the menu: {}", e),
+    }
+}
+
+fn build_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let remote = *app.state::<AppState>().remote_mode.lock().unwrap();
+    let remote_item = CheckMenuItemBuilder::with_id(REMOTE_MODE, "Remote Mode")
+        .checked(remote)
+        .build(app)?;
+    let unread = UNREAD.lock().unwrap().clone();
+    let mut threads = SubmenuBuilder::new(app, format!("Unread Threads ({})", unread.len())).enabled(!unread.is_empty());
+    for (id, name) in unread.iter().take(MAX_UNREAD) {
+        threads = threads.text(format!("{}{}", THREAD_PREFIX, id), name);
+    }
+    let threads = threads.build()?;
+    MenuBuilder::new(app)
+        .text(QUICK_DUMP, "Quick Brain Dump…")
+        .item(&remote_item)
+        .text(RUN_PROACTIVE, "Run Follow-ups Now")
+        .separator()
+        .item(&threads)
+        .separator()
+        .text(SHOW, "Show OpenClaw Chat")
+        .quit()
+        .build()
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    let id = event.id().as_ref();
+    match id {
+        QUICK_DUMP => {
+            // The capture box lives in the UI
+            deeplink::focus_window(app);
+            let _ = app.emit("tray:quick_dump", ());
+        }
+        REMOTE_MODE => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let state = app.state::<AppState>();
+                let enabled = !*state.remote_mode.lock().unwrap();
+                if let Err(e) = crate::set_remote_mode(&app, &state, enabled).await {
+                    e.emit(&app, "tray_remote_mode");
+                }
+                // The check mark flips on click; put it back if nothing changed
+                refresh(&app);
+            });
+        }
+        RUN_PROACTIVE => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = scheduler::run_job(&app, scheduler::PROACTIVE).await {
+                    e.emit(&app, "tray_run_proactive");
+                }
+            });
+        }
+        SHOW => deeplink::focus_window(app),
+        _ => {
+            if let Some(thread_id) = id.strip_prefix(THREAD_PREFIX) {
+                mark_read(app, thread_id);
+                deeplink::show(
+                    app,
+                    &Navigation::Thread {
+                        thread_id: thread_id.to_string(),
+                    },
+                );
+            }
+        }
+    }
+}
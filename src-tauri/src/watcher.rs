@@ -1,8 +1,17 @@
-use crate::openclaw::{parse_jsonl_line, session_path, ChatMessage};
+use crate::db::{self, Thread, UpsertResult};
+use crate::notifications;
+use crate::obsidian;
+use crate::openclaw::{self, classify_line, session_path, ChatMessage, SessionLine};
+use crate::titles;
 use anyhow::Result;
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 use tauri::{AppHandle, Emitter};
 use tokio::sync::mpsc;
 
@@ -14,124 +23,779 @@ pub struct MessageEvent {
 
 pub struct WatcherState {
     watchers: HashMap<String, RecommendedWatcher>,
-    file_offsets: Arc<Mutex<HashMap<String, u64>>>,
+    /// One watcher per agent sessions directory, shared by every watched
+    /// session of that agent.
+    session_dirs: HashMap<String, JsonlWatch>,
+    /// Sessions watched by polling their file instead.
+    polled_sessions: HashMap<String, JsonlWatch>,
+    discovery: Option<JsonlWatch>,
+    file_offsets: Arc<Mutex<HashMap<String, FileOffset>>>,
+}
+
+/// How many bytes before the offset are remembered to notice a rewrite.
+const TAIL_CHECK_BYTES: usize = 64;
+/// Change events for a sessions directory arriving within this window are
+/// coalesced, so a burst of writes is read once per file.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+/// Setting: "auto" (the default) watches with OS file events and falls back
+/// to polling when they fail; "poll" always polls, for network filesystems
+/// and remote mounts where events go missing.
+pub const MODE_SETTING: &str = "watcher_mode";
+/// How often a polled file or directory is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How far a watched session file has been read.
+struct FileOffset {
+    agent_id: String,
+    offset: u64,
+    /// The last bytes before `offset`. If they change, the file was
+    /// rewritten (edit, compaction, recreated by the CLI) rather than appended to.
+    tail: Vec<u8>,
+    /// The last message read was the user's: the agent is still working on it.
+    awaiting_reply: bool,
+    /// Epoch ms the offset last moved.
+    last_read_at: Option<i64>,
+}
+
+impl FileOffset {
+    fn new(agent_id: &str) -> Self {
+        Self {
+            agent_id: agent_id.to_string(),
+            offset: 0,
+            tail: Vec::new(),
+            awaiting_reply: false,
+            last_read_at: None,
+        }
+    }
+}
+
+/// What `cmd_get_watcher_status` reports, to diagnose messages that stop
+/// appearing without restarting the app.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatcherStatus {
+    pub watches: Vec<WatchStatus>,
+    pub sessions: Vec<SessionStatus>,
+    pub vault_watched: bool,
+}
+
+/// One directory or file watch. `key` is "agent:<id>", "session:<id>" or "discovery".
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchStatus {
+    pub key: String,
+    pub path: String,
+    pub polling: bool,
+    pub events: u64,
+    pub coalesced: u64,
+    pub dropped: u64,
+    pub last_event_at: Option<i64>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStatus {
+    pub session_id: String,
+    pub agent_id: String,
+    pub offset: u64,
+    pub awaiting_reply: bool,
+    pub last_read_at: Option<i64>,
 }
 
 impl WatcherState {
+    pub fn status(&self) -> WatcherStatus {
+        let mut watches = Vec::new();
+        for (agent_id, w) in &self.session_dirs {
+            watches.push(w.stats.status(&format!("agent:{}", agent_id), &w.path));
+        }
+        for (session_id, w) in &self.polled_sessions {
+            watches.push(w.stats.status(&format!("session:{}", session_id), &w.path));
+        }
+        if let Some(w) = &self.discovery {
+            watches.push(w.stats.status("discovery", &w.path));
+        }
+        watches.sort_by(|a, b| a.key.cmp(&b.key));
+        let mut sessions: Vec<SessionStatus> = self
+            .file_offsets
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(session_id, pos)| SessionStatus {
+                session_id: session_id.clone(),
+                agent_id: pos.agent_id.clone(),
+                offset: pos.offset,
+                awaiting_reply: pos.awaiting_reply,
+                last_read_at: pos.last_read_at,
+            })
+            .collect();
+        sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        WatcherStatus {
+            watches,
+            sessions,
+            vault_watched: self.watchers.contains_key(VAULT_WATCH_KEY),
+        }
+    }
+
     pub fn new() -> Self {
         Self {
             watchers: HashMap::new(),
+            session_dirs: HashMap::new(),
+            polled_sessions: HashMap::new(),
+            discovery: None,
             file_offsets: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
 
+/// Emit a session's history, then tail it. With `poll` the file is checked
+/// every `POLL_INTERVAL` instead of relying on the agent's directory watcher.
 pub async fn watch_session(
     app: AppHandle,
     state: Arc<Mutex<WatcherState>>,
     agent_id: String,
     session_id: String,
+    poll: bool,
 ) -> Result<()> {
     let path = session_path(&agent_id, &session_id);
+    let dir = path.parent().unwrap_or(&path).to_path_buf();
+    std::fs::create_dir_all(&dir)?;
+
+    let file_offsets = {
+        let guard = state.lock().unwrap();
+        Arc::clone(&guard.file_offsets)
+    };
+
+    // Start from the top: existing history is emitted first
+    file_offsets
+        .lock()
+        .unwrap()
+        .insert(session_id.clone(), FileOffset::new(&agent_id));
+    tail_session(&app, &file_offsets, &agent_id, &session_id, &path)?;
+
+    let mut guard = state.lock().unwrap();
+    if poll {
+        let watch = watch_sessions(app, file_offsets, agent_id, &path, true)?;
+        guard.polled_sessions.insert(session_id, watch);
+    } else if let Entry::Vacant(slot) = guard.session_dirs.entry(agent_id.clone()) {
+        slot.insert(watch_sessions(app, file_offsets, agent_id, &dir, false)?);
+    }
+    Ok(())
+}
+
+/// Watch an agent's sessions directory, or one session file, and tail
+/// whichever watched session a change names.
+fn watch_sessions(
+    app: AppHandle,
+    file_offsets: Arc<Mutex<HashMap<String, FileOffset>>>,
+    agent_id: String,
+    path: &Path,
+    poll: bool,
+) -> Result<JsonlWatch> {
+    let watcher = watch_jsonl(path, RecursiveMode::NonRecursive, poll, move |paths| {
+        for path in paths {
+            let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if let Err(e) = tail_session(&app, &file_offsets, &agent_id, session_id, &path) {
+                eprintln!("[watcher] Failed to read session {}: {}", session_id, e);
+            }
+        }
+    })?;
+    Ok(watcher)
+}
+
+/// A file or directory watched for `.jsonl` changes. Dropping it stops the watch.
+struct JsonlWatch {
+    path: PathBuf,
+    stats: Arc<WatchStats>,
+    _native: Option<RecommendedWatcher>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+/// Counters for one `JsonlWatch`, for `cmd_get_watcher_status`.
+#[derive(Default)]
+struct WatchStats {
+    polling: AtomicBool,
+    /// File events received, or changed files found when polling.
+    events: AtomicU64,
+    /// Wake-ups merged into one already pending.
+    coalesced: AtomicU64,
+    /// Errors from the OS watcher; the events behind them are lost.
+    dropped: AtomicU64,
+    /// Epoch ms of the last event; 0 for none yet.
+    last_event_at: AtomicI64,
+}
+
+impl WatchStats {
+    fn record(&self, events: usize) {
+        self.events.fetch_add(events as u64, Ordering::Relaxed);
+        self.last_event_at
+            .store(chrono::Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
 
-    // Make sure parent directory exists
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
+    fn status(&self, key: &str, path: &Path) -> WatchStatus {
+        let last = self.last_event_at.load(Ordering::Relaxed);
+        WatchStatus {
+            key: key.to_string(),
+            path: path.display().to_string(),
+            polling: self.polling.load(Ordering::Relaxed),
+            events: self.events.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            last_event_at: (last > 0).then_some(last),
+        }
+    }
+}
+
+impl Drop for JsonlWatch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Watch `path` for changes to `.jsonl` files and pass each burst to
+/// `on_change` once things settle for `DEBOUNCE`, every file named once.
+/// With `poll`, or once OS file events fail, the files are stat'ed every
+/// `POLL_INTERVAL` instead and the ones whose size or mtime moved are passed.
+fn watch_jsonl(
+    path: &Path,
+    mode: RecursiveMode,
+    poll: bool,
+    mut on_change: impl FnMut(HashSet<PathBuf>) + Send + 'static,
+) -> Result<JsonlWatch> {
+    // Changed files collect here; the channel only carries a wake-up, and a
+    // full channel means one is already pending, so the notify thread never blocks.
+    let pending = Arc::new(Mutex::new(HashSet::<PathBuf>::new()));
+    let failed = Arc::new(AtomicBool::new(false));
+    let stats = Arc::new(WatchStats::default());
+    let (tx, mut rx) = mpsc::channel::<()>(1);
+
+    let native = if poll {
+        None
+    } else {
+        let queued = Arc::clone(&pending);
+        let failure = Arc::clone(&failed);
+        let counters = Arc::clone(&stats);
+        let watcher = RecommendedWatcher::new(
+            move |res: Result<Event, notify::Error>| {
+                match res {
+                    Ok(event) => {
+                        counters.record(1);
+                        let paths = event.paths.into_iter().filter(|p| is_jsonl(p));
+                        queued.lock().unwrap().extend(paths);
+                    }
+                    Err(e) => {
+                        eprintln!("[watcher] File events failed, polling instead: {}", e);
+                        counters.dropped.fetch_add(1, Ordering::Relaxed);
+                        failure.store(true, Ordering::Relaxed);
+                    }
+                }
+                if tx.try_send(()).is_err() {
+                    counters.coalesced.fetch_add(1, Ordering::Relaxed);
+                }
+            },
+            Config::default(),
+        )
+        .and_then(|mut w| w.watch(path, mode).map(|()| w));
+        match watcher {
+            Ok(w) => Some(w),
+            Err(e) => {
+                eprintln!("[watcher] Can't watch {}, polling instead: {}", path.display(), e);
+                None
+            }
+        }
+    };
+
+    let root = path.to_path_buf();
+    let counters = Arc::clone(&stats);
+    counters.polling.store(native.is_none(), Ordering::Relaxed);
+    let task = tokio::spawn(async move {
+        let mut seen = HashMap::new();
+        loop {
+            if counters.polling.load(Ordering::Relaxed) {
+                tokio::time::sleep(POLL_INTERVAL).await;
+                let changed = poll_changes(&root, mode, &mut seen);
+                if !changed.is_empty() {
+                    counters.record(changed.len());
+                    on_change(changed);
+                }
+                continue;
+            }
+            if rx.recv().await.is_none() {
+                return;
+            }
+            tokio::time::sleep(DEBOUNCE).await;
+            if failed.load(Ordering::Relaxed) {
+                counters.polling.store(true, Ordering::Relaxed);
+                // Catch up on whatever the failed watcher missed
+                poll_changes(&root, mode, &mut seen);
+                on_change(seen.keys().cloned().collect());
+                continue;
+            }
+            let paths = std::mem::take(&mut *pending.lock().unwrap());
+            if !paths.is_empty() {
+                on_change(paths);
+            }
+        }
+    });
+
+    Ok(JsonlWatch {
+        path: path.to_path_buf(),
+        stats,
+        _native: native,
+        task,
+    })
+}
+
+fn is_jsonl(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("jsonl")
+}
+
+/// Stat the `.jsonl` files at or under `root` and return the ones whose size
+/// or mtime differs from `seen`, which is updated. The first call reports
+/// every file.
+fn poll_changes(
+    root: &Path,
+    mode: RecursiveMode,
+    seen: &mut HashMap<PathBuf, (u64, Option<SystemTime>)>,
+) -> HashSet<PathBuf> {
+    let mut files = Vec::new();
+    collect_jsonl(root, mode == RecursiveMode::Recursive, &mut files);
+    let mut changed = HashSet::new();
+    let mut current = HashMap::new();
+    for path in files {
+        let Ok(meta) = std::fs::metadata(&path) else {
+            continue;
+        };
+        let stamp = (meta.len(), meta.modified().ok());
+        if seen.get(&path) != Some(&stamp) {
+            changed.insert(path.clone());
+        }
+        current.insert(path, stamp);
+    }
+    *seen = current;
+    changed
+}
+
+fn collect_jsonl(path: &Path, recursive: bool, files: &mut Vec<PathBuf>) {
+    if path.is_file() {
+        if is_jsonl(path) {
+            files.push(path.to_path_buf());
+        }
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if recursive {
+                collect_jsonl(&path, true, files);
+            }
+        } else if is_jsonl(&path) {
+            files.push(path);
+        }
+    }
+}
+
+/// Emit whatever complete lines were appended to `path` since the stored
+/// offset and advance it. A file that shrank or changed before the offset
+/// was rewritten: `chat:session_reset` is emitted and it is read again from
+/// the top. The offset map is only ever updated here; a session no longer in
+/// it (or watched under another agent) is left alone.
+fn tail_session(
+    app: &AppHandle,
+    offsets: &Mutex<HashMap<String, FileOffset>>,
+    agent_id: &str,
+    session_id: &str,
+    path: &Path,
+) -> Result<()> {
+    let mut offsets = offsets.lock().unwrap();
+    let Some(pos) = offsets.get_mut(session_id).filter(|p| p.agent_id == agent_id) else {
+        return Ok(());
+    };
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    let len = file.metadata()?.len();
+    let rewritten = len < pos.offset || {
+        let mut before = vec![0u8; pos.tail.len()];
+        file.seek(SeekFrom::Start(pos.offset - pos.tail.len() as u64))?;
+        file.read_exact(&mut before)?;
+        before != pos.tail
+    };
+    if rewritten {
+        pos.offset = 0;
+        pos.tail.clear();
+        let _ = app.emit(
+            "chat:session_reset",
+            serde_json::json!({ "sessionId": session_id }),
+        );
+    }
+    if len <= pos.offset {
+        return Ok(());
+    }
+    file.seek(SeekFrom::Start(pos.offset))?;
+    let mut appended = Vec::new();
+    file.read_to_end(&mut appended)?;
+    let (consumed, last) = emit_complete_lines(app, session_id, &appended);
+    let consumed = consumed as usize;
+    // Also catches turns started outside the app, e.g. from a terminal
+    match last.as_ref().map(|m| m.role.as_str()) {
+        Some("user") if !pos.awaiting_reply => {
+            pos.awaiting_reply = true;
+            let _ = app.emit(
+                "chat:processing_started",
+                serde_json::json!({ "sessionId": session_id }),
+            );
+        }
+        Some("assistant") if pos.awaiting_reply => {
+            pos.awaiting_reply = false;
+            let _ = app.emit(
+                "chat:processing_finished",
+                serde_json::json!({ "sessionId": session_id, "ok": true }),
+            );
+            if let Some(reply) = &last {
+                notifications::reply_arrived(app, session_id, reply);
+            }
+        }
+        _ => {}
     }
+    if consumed > 0 {
+        pos.tail
+            .extend_from_slice(&appended[consumed.saturating_sub(TAIL_CHECK_BYTES)..consumed]);
+        let excess = pos.tail.len().saturating_sub(TAIL_CHECK_BYTES);
+        pos.tail.drain(..excess);
+        pos.offset += consumed as u64;
+        pos.last_read_at = Some(chrono::Utc::now().timestamp_millis());
+    }
+    Ok(())
+}
 
-    // Read any existing content first
-    let initial_offset = if path.exists() {
-        let content = std::fs::read_to_string(&path)?;
-        let mut offset = 0u64;
-        for line in content.lines() {
-            offset += line.len() as u64 + 1;
-            if let Some(msg) = parse_jsonl_line(line) {
+/// Emit the messages in the newline-terminated lines of `bytes` and return
+/// how many bytes that covered, with the last message. A
+/// trailing partial line is left for the next change so a write caught
+/// halfway isn't lost.
+fn emit_complete_lines(app: &AppHandle, session_id: &str, bytes: &[u8]) -> (u64, Option<ChatMessage>) {
+    let Some(end) = bytes.iter().rposition(|&b| b == b'\n').map(|nl| nl + 1) else {
+        return (0, None);
+    };
+    let mut last = None;
+    for line in bytes[..end].split(|&b| b == b'\n') {
+        let line = String::from_utf8_lossy(line);
+        match classify_line(&line) {
+            SessionLine::Message(msg) => {
                 let _ = app.emit(
                     "chat:message",
                     MessageEvent {
-                        session_id: session_id.clone(),
-                        message: msg,
+                        session_id: session_id.to_string(),
+                        message: msg.clone(),
                     },
                 );
+                last = Some(msg);
+            }
+            SessionLine::Corrupt if !line.trim().is_empty() => {
+                eprintln!("[watcher] Skipping corrupt line in session {}", session_id);
             }
+            _ => {}
         }
-        offset
-    } else {
-        0
+    }
+    (end as u64, last)
+}
+
+/// Stop tailing a session; the agent's directory watcher goes once its last
+/// session is unwatched.
+pub fn stop_watching(state: Arc<Mutex<WatcherState>>, session_id: &str) {
+    let mut guard = state.lock().unwrap();
+    let file_offsets = Arc::clone(&guard.file_offsets);
+    let mut offsets = file_offsets.lock().unwrap();
+    guard.polled_sessions.remove(session_id);
+    let Some(pos) = offsets.remove(session_id) else {
+        return;
     };
+    if !offsets.values().any(|p| p.agent_id == pos.agent_id) {
+        guard.session_dirs.remove(&pos.agent_id);
+    }
+}
 
-    {
-        let mut offsets = state.lock().unwrap().file_offsets.lock().unwrap().clone();
-        offsets.insert(session_id.clone(), initial_offset);
+/// Whether the agent is still working on the last message in a watched session.
+pub fn awaiting_reply(state: &Arc<Mutex<WatcherState>>, session_id: &str) -> bool {
+    let file_offsets = Arc::clone(&state.lock().unwrap().file_offsets);
+    let offsets = file_offsets.lock().unwrap();
+    offsets.get(session_id).is_some_and(|p| p.awaiting_reply)
+}
+
+/// Run `rewrite`, which replaces a session file and returns its new content,
+/// while the session's offset is held. A watched session then reads on from
+/// the end of the new content instead of seeing the rewrite as a reset.
+pub fn rewrite_session<T>(
+    state: &Arc<Mutex<WatcherState>>,
+    session_id: &str,
+    rewrite: impl FnOnce() -> Result<(Vec<u8>, T)>,
+) -> Result<T> {
+    let file_offsets = Arc::clone(&state.lock().unwrap().file_offsets);
+    let mut offsets = file_offsets.lock().unwrap();
+    let (content, result) = rewrite()?;
+    if let Some(pos) = offsets.get_mut(session_id) {
+        pos.offset = content.len() as u64;
+        pos.tail = content[content.len().saturating_sub(TAIL_CHECK_BYTES)..].to_vec();
+        pos.last_read_at = Some(chrono::Utc::now().timestamp_millis());
     }
+    Ok(result)
+}
 
-    let file_offsets = {
-        let guard = state.lock().unwrap();
-        Arc::clone(&guard.file_offsets)
-    };
-    {
-        let mut offsets = file_offsets.lock().unwrap();
-        offsets.insert(session_id.clone(), initial_offset);
+// ── Session discovery ────────────────────────────────────────────────────────
+
+/// What to do with sessions started outside the app: "off", "notify", or
+/// "create" (the default) to give each one a thread.
+pub const DISCOVERY_SETTING: &str = "session_discovery";
+
+/// Sent as `thread:discovered` when a session file shows up that no thread
+/// owns, e.g. after `openclaw agent` ran in a terminal.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredSession {
+    pub agent_id: String,
+    pub session_id: String,
+    pub preview: Option<String>,
+    /// The thread created for it; `None` in "notify" mode.
+    pub thread: Option<Thread>,
+}
+
+/// Watch every agent's sessions directory for new sessions. Files already
+/// there are left alone; a new one is picked up once it holds a message.
+/// Replaces any previous discovery watcher.
+pub fn watch_new_sessions(
+    app: AppHandle,
+    state: Arc<Mutex<WatcherState>>,
+    db: db::Db,
+    create_threads: bool,
+    poll: bool,
+) -> Result<()> {
+    let root = openclaw::agents_dir();
+    std::fs::create_dir_all(&root)?;
+
+    let mut known = HashSet::new();
+    for agent in std::fs::read_dir(&root)?.flatten() {
+        let Ok(sessions) = std::fs::read_dir(agent.path().join("sessions")) else {
+            continue;
+        };
+        for session in sessions.flatten() {
+            if let Some(id) = session.path().file_stem().and_then(|s| s.to_str()) {
+                known.insert(id.to_string());
+            }
+        }
     }
 
-    let (tx, mut rx) = mpsc::channel(32);
-    let path_clone = path.clone();
-    let session_id_clone = session_id.clone();
+    let agents = root.clone();
+    let watcher = watch_jsonl(&root, RecursiveMode::Recursive, poll, move |paths| {
+        for path in paths {
+            let Some((agent_id, session_id)) = session_of(&agents, &path) else {
+                continue;
+            };
+            if known.contains(&session_id) || openclaw::is_app_session(&session_id) {
+                continue;
+            }
+            match discover(&db, &agent_id, &session_id, create_threads) {
+                Ok(Some(found)) => {
+                    known.insert(session_id);
+                    let _ = app.emit("thread:discovered", found);
+                }
+                // Still empty, or a thread already has it
+                Ok(None) => {}
+                Err(e) => {
+                    known.insert(session_id.clone());
+                    eprintln!("[watcher] Failed to pick up session {}: {}", session_id, e);
+                }
+            }
+        }
+    })?;
+
+    let mut guard = state.lock().unwrap();
+    guard.discovery = Some(watcher);
+    Ok(())
+}
+
+pub fn stop_session_discovery(state: Arc<Mutex<WatcherState>>) {
+    let mut guard = state.lock().unwrap();
+    guard.discovery = None;
+}
+
+/// Agent and session id for `<root>/<agent>/sessions/<session>.jsonl`.
+fn session_of(root: &Path, path: &Path) -> Option<(String, String)> {
+    let rel = path.strip_prefix(root).ok()?;
+    let parts: Vec<&str> = rel.iter().filter_map(|p| p.to_str()).collect();
+    match parts.as_slice() {
+        [agent, "sessions", file] => Some((agent.to_string(), file.strip_suffix(".jsonl")?.to_string())),
+        _ => None,
+    }
+}
+
+/// Describe a new session and, with `create_thread`, give it a thread named
+/// after its first message. `None` while the file has no messages yet or
+/// when a thread already owns it.
+fn discover(
+    db: &db::Db,
+    agent_id: &str,
+    session_id: &str,
+    create_thread: bool,
+) -> Result<Option<DiscoveredSession>> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let session = openclaw::describe_session(agent_id, session_id, now)?;
+    if session.message_count == 0 {
+        return Ok(None);
+    }
+    let conn = db.get()?;
+    if db::get_thread_by_session(&conn, session_id)?.is_some() {
+        return Ok(None);
+    }
+    let thread = if create_thread {
+        let name = session
+            .preview
+            .as_deref()
+            .map(|p| p.chars().take(titles::MAX_TITLE_CHARS).collect::<String>())
+            .filter(|p| !p.trim().is_empty())
+            .unwrap_or_else(|| "CLI session".to_string());
+        let thread = Thread {
+            id: uuid::Uuid::new_v4().to_string(),
+            project_id: None,
+            name,
+            session_id: session_id.to_string(),
+            agent_id: agent_id.to_string(),
+            created_at: now,
+            updated_at: now,
+            last_message_at: Some(session.last_active),
+            execution_target: Some("local".to_string()),
+            model: None,
+            muted: false,
+        };
+        db::create_thread(&conn, &thread)?;
+        Some(thread)
+    } else {
+        None
+    };
+    Ok(Some(DiscoveredSession {
+        agent_id: session.agent_id,
+        session_id: session.session_id,
+        preview: session.preview,
+        thread,
+    }))
+}
+
+// ── Obsidian vault ───────────────────────────────────────────────────────────
+
+const VAULT_WATCH_KEY: &str = "obsidian:vault";
+
+#[derive(Clone, serde::Serialize)]
+pub struct ProjectSyncedEvent {
+    pub name: String,
+    pub obsidian_source: String,
+    pub result: &'static str, // 'created' | 'updated'
+}
+
+/// Watch the vault's active projects directory and upsert notes as they change.
+/// Replaces any previous vault watcher.
+pub fn watch_vault(
+    app: AppHandle,
+    state: Arc<Mutex<WatcherState>>,
+    db: db::Db,
+    active_path: PathBuf,
+    layout: obsidian::VaultLayout,
+) -> Result<()> {
+    let (tx, mut rx) = mpsc::channel::<Vec<PathBuf>>(64);
 
     let mut watcher = RecommendedWatcher::new(
         move |res: Result<Event, _>| {
-            if res.is_ok() {
-                let _ = tx.blocking_send(());
+            if let Ok(event) = res {
+                let _ = tx.blocking_send(event.paths);
             }
         },
         Config::default(),
     )?;
-
-    let watch_path = path.parent().unwrap_or(&path);
-    watcher.watch(watch_path, RecursiveMode::NonRecursive)?;
+    watcher.watch(&active_path, RecursiveMode::Recursive)?;
 
     {
         let mut guard = state.lock().unwrap();
-        guard.watchers.insert(session_id.clone(), watcher);
+        guard.watchers.insert(VAULT_WATCH_KEY.to_string(), watcher);
     }
 
-    let app_clone = app.clone();
-    let offsets_clone = Arc::clone(&file_offsets);
-
     tokio::spawn(async move {
-        while rx.recv().await.is_some() {
-            if !path_clone.exists() {
-                continue;
-            }
-
-            let current_offset = {
-                let offsets = offsets_clone.lock().unwrap();
-                *offsets.get(&session_id_clone).unwrap_or(&0)
-            };
-
-            if let Ok(content) = std::fs::read_to_string(&path_clone) {
-                let bytes = content.as_bytes();
-                if bytes.len() as u64 <= current_offset {
+        while let Some(paths) = rx.recv().await {
+            for path in paths {
+                // Note moved out of Active (or deleted): archive its project
+                if !path.exists() {
+                    let Some(source) = obsidian::note_source(&active_path, &path) else {
+                        continue;
+                    };
+                    let archived = db
+                        .call(move |conn| crate::db::archive_obsidian_project(conn, &source))
+                        .await;
+                    if let Ok(Some(name)) = archived {
+                        let _ = app.emit(
+                            "obsidian:archive_changes",
+                            serde_json::json!({ "archived": [name], "restored": [] }),
+                        );
+                    }
                     continue;
                 }
-                let new_content = &content[current_offset as usize..];
-                let mut new_offset = current_offset;
-
-                for line in new_content.lines() {
-                    new_offset += line.len() as u64 + 1;
-                    if let Some(msg) = parse_jsonl_line(line) {
-                        let _ = app_clone.emit(
-                            "chat:message",
-                            MessageEvent {
-                                session_id: session_id_clone.clone(),
-                                message: msg,
-                            },
-                        );
+                let Some(project) = obsidian::parse_note(&active_path, &path, &layout) else {
+                    continue;
+                };
+                let (note, dir) = (project.clone(), active_path.clone());
+                let synced = db
+                    .call(move |conn| {
+                        let result = obsidian::upsert_project(conn, &note);
+                        if let Err(e) = obsidian::sync_project_links(conn, &note) {
+                            eprintln!("Obsidian link sync error for {}: {}", note.name, e);
+                        }
+                        let tasks = obsidian::sync_note_tasks(conn, &dir, std::slice::from_ref(&note));
+                        Ok::<_, anyhow::Error>((result, tasks))
+                    })
+                    .await;
+                let (result, tasks) = match synced {
+                    Ok(synced) => synced,
+                    Err(e) => {
+                        eprintln!("Obsidian sync error for {}: {}", project.name, e);
+                        continue;
+                    }
+                };
+                match tasks {
+                    Ok(t) if t.created + t.completed > 0 => {
+                        for completion in &t.completions {
+                            completion.announce(&app);
+                        }
+                        let _ = app.emit("kanban:refresh", ());
                     }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Obsidian task sync error for {}: {}", project.name, e),
                 }
-
-                let mut offsets = offsets_clone.lock().unwrap();
-                offsets.insert(session_id_clone.clone(), new_offset);
+                let label = match result {
+                    Ok(UpsertResult::Created) => "created",
+                    Ok(UpsertResult::Updated) => "updated",
+                    Ok(UpsertResult::Restored) => {
+                        let _ = app.emit(
+                            "obsidian:archive_changes",
+                            serde_json::json!({ "archived": [], "restored": [project.name.clone()] }),
+                        );
+                        "updated"
+                    }
+                    Ok(UpsertResult::Skipped) => continue,
+                    Err(e) => {
+                        eprintln!("Obsidian sync error for {}: {}", project.name, e);
+                        continue;
+                    }
+                };
+                let _ = app.emit(
+                    "obsidian:project_synced",
+                    ProjectSyncedEvent {
+                        name: project.name,
+                        obsidian_source: project.obsidian_source,
+                        result: label,
+                    },
+                );
             }
         }
     });
@@ -139,7 +803,7 @@ pub async fn watch_session(
     Ok(())
 }
 
-pub fn stop_watching(state: Arc<Mutex<WatcherState>>, session_id: &str) {
+pub fn stop_vault_watch(state: Arc<Mutex<WatcherState>>) {
     let mut guard = state.lock().unwrap();
-    guard.watchers.remove(session_id);
+    guard.watchers.remove(VAULT_WATCH_KEY);
 }
@@ -0,0 +1,302 @@
+use crate::openclaw::ChatMessage;
+use crate::{db, http};
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+pub const BRAIN_DUMP_CREATED: &str = "braindump:created";
+pub const KANBAN_MOVED: &str = "kanban:moved";
+pub const CHAT_MESSAGE: &str = "chat:message";
+/// Sent only by `cmd_test_webhook`.
+const TEST: &str = "webhook:test";
+
+/// Events a webhook can subscribe to. Besides the three above, these are
+/// the user-facing events sent through `dnd::emit`; webhooks get them even
+/// while do-not-disturb holds them back from the UI.
+pub const EVENTS: &[&str] = &[
+    BRAIN_DUMP_CREATED,
+    KANBAN_MOVED,
+    CHAT_MESSAGE,
+    "briefing:ready",
+    "thread:resurfaced",
+    "github:issue_closed",
+    "maintenance:finished",
+    "sync:finished",
+];
+
+/// Delays before the second and third delivery attempts.
+const RETRY_DELAYS: &[Duration] = &[Duration::from_secs(5), Duration::from_secs(60)];
+const TIMEOUT_SECS: u32 = 15;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    /// Key for the `X-OpenClaw-Signature` HMAC; shown so it can be pasted
+    /// into the receiving end.
+    pub secret: String,
+    /// Events to send; empty means all of them.
+    pub events: Vec<String>,
+    pub enabled: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub last_sent_at: Option<i64>,
+    pub last_status: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// An event waiting to go out, already in the JSON body it'll be sent as.
+struct Pending {
+    event: &'static str,
+    body: String,
+    id: String,
+}
+
+struct Queue {
+    tx: mpsc::UnboundedSender<Pending>,
+    rx: Mutex<Option<mpsc::UnboundedReceiver<Pending>>>,
+}
+
+static QUEUE: OnceLock<Queue> = OnceLock::new();
+
+fn queue() -> &'static Queue {
+    QUEUE.get_or_init(|| {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Queue {
+            tx,
+            rx: Mutex::new(Some(rx)),
+        }
+    })
+}
+
+/// Queue `payload` for every enabled webhook subscribed to `event`. Never
+/// blocks or fails; events outside `EVENTS` are ignored.
+pub fn publish<S: Serialize>(event: &str, payload: &S) {
+    let Some(event) = EVENTS.iter().copied().find(|e| *e == event) else {
+        return;
+    };
+    let Ok(data) = serde_json::to_value(payload) else {
+        return;
+    };
+    let (id, body) = envelope(event, data);
+    let _ = queue().tx.send(Pending { event, body, id });
+}
+
+/// Publish a message sent or received in a thread as "chat:message". Only
+/// messages that go through the app are sent, not ones the watcher picks up
+/// from session files, so each one goes out once.
+pub fn publish_message(thread_id: &str, session_id: &str, message: &ChatMessage) {
+    publish(
+        CHAT_MESSAGE,
+        &json!({ "threadId": thread_id, "sessionId": session_id, "message": message }),
+    );
+}
+
+/// `{ id, event, sentAt, data }`; `id` doubles as `X-OpenClaw-Delivery` so
+/// receivers can drop retried duplicates.
+fn envelope(event: &str, data: Value) -> (String, String) {
+    let id = uuid::Uuid::new_v4().to_string();
+    let body = json!({
+        "id": id,
+        "event": event,
+        "sentAt": chrono::Utc::now().timestamp_millis(),
+        "data": data,
+    });
+    (id, body.to_string())
+}
+
+/// Deliver queued events for as long as the app runs. Each delivery runs on
+/// its own, so a slow endpoint doesn't hold up the others.
+pub async fn run_dispatcher() {
+    let Some(mut rx) = queue().rx.lock().unwrap().take() else {
+        return;
+    };
+    while let Some(pending) = rx.recv().await {
+        let event = pending.event;
+        let hooks = match db::pool() {
+            Ok(db) => db.call(move |conn| subscribed(conn, event)).await,
+            Err(e) => Err(e),
+        };
+        let hooks = match hooks {
+            Ok(hooks) => hooks,
+            Err(e) => {
+                eprintln!("[webhooks] Couldn't load webhooks for {}: {}", event, e);
+                continue;
+            }
+        };
+        for hook in hooks {
+            let (id, body) = (pending.id.clone(), pending.body.clone());
+            tauri::async_runtime::spawn(async move {
+                let mut result = deliver(&hook, event, &id, &body).await;
+                for delay in RETRY_DELAYS {
+                    if result.is_ok() {
+                        break;
+                    }
+                    tokio::time::sleep(*delay).await;
+                    result = deliver(&hook, event, &id, &body).await;
+                }
+                if let Err(e) = record_result(&hook.id, &result).await {
+                    eprintln!("[webhooks] Couldn't record delivery to {}: {}", hook.url, e);
+                }
+            });
+        }
+    }
+}
+
+/// Send one event to `hook` and return the HTTP status. Anything but 2xx
+/// is an error.
+async fn deliver(hook: &Webhook, event: &str, id: &str, body: &str) -> Result<u16> {
+    let mut headers = vec![
+        "Content-Type: application/json".to_string(),
+        format!("X-OpenClaw-Event: {}", event),
+        format!("X-OpenClaw-Delivery: {}", id),
+    ];
+    if !hook.secret.is_empty() {
+        headers.push(format!("X-OpenClaw-Signature: sha256={}", sign(&hook.secret, body)?));
+    }
+    let response = http::request("POST", &hook.url, &headers, Some(body), TIMEOUT_SECS).await?;
+    match response.status {
+        200..=299 => Ok(response.status),
+        status => Err(anyhow!("{} answered {}", hook.url, status)),
+    }
+}
+
+/// Hex HMAC-SHA256 of `body`, the same scheme GitHub uses for its webhooks.
+fn sign(secret: &str, body: &str) -> Result<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|e| anyhow!("Invalid secret: {}", e))?;
+    mac.update(body.as_bytes());
+    Ok(mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+async fn record_result(id: &str, result: &Result<u16>) -> Result<()> {
+    let (id, status, error) = match result {
+        Ok(status) => (id.to_string(), Some(*status as i64), None),
+        Err(e) => (id.to_string(), None, Some(e.to_string())),
+    };
+    db::pool()?
+        .call(move |conn| {
+            conn.execute(
+                "UPDATE webhooks SET last_sent_at = ?1, last_status = ?2, last_error = ?3 WHERE id = ?4",
+                params![chrono::Utc::now().timestamp_millis(), status, error, id],
+            )?;
+            Ok::<_, anyhow::Error>(())
+        })
+        .await
+}
+
+/// Send a `webhook:test` event to one webhook now, enabled or not, and
+/// return the status it answered with.
+pub async fn test(hook: &Webhook) -> Result<u16> {
+    let (id, body) = envelope(TEST, json!({ "webhookId": hook.id }));
+    let result = deliver(hook, TEST, &id, &body).await;
+    record_result(&hook.id, &result).await?;
+    result
+}
+
+const WEBHOOK_COLUMNS: &str =
+    "id, url, secret, events, enabled, created_at, updated_at, last_sent_at, last_status, last_error";
+
+pub fn list(conn: &Connection) -> Result<Vec<Webhook>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM webhooks ORDER BY created_at", WEBHOOK_COLUMNS))?;
+    let hooks = stmt.query_map([], row_to_webhook)?.collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(hooks)
+}
+
+pub fn get(conn: &Connection, id: &str) -> Result<Option<Webhook>> {
+    Ok(conn
+        .query_row(
+            &format!("SELECT {} FROM webhooks WHERE id = ?1", WEBHOOK_COLUMNS),
+            params![id],
+            row_to_webhook,
+        )
+        .optional()?)
+}
+
+fn subscribed(conn: &Connection, event: &str) -> Result<Vec<Webhook>> {
+    Ok(list(conn)?
+        .into_iter()
+        .filter(|h| h.enabled && (h.events.is_empty() || h.events.iter().any(|e| e == event)))
+        .collect())
+}
+
+fn row_to_webhook(row: &rusqlite::Row) -> rusqlite::Result<Webhook> {
+    let events: String = row.get(3)?;
+    Ok(Webhook {
+        id: row.get(0)?,
+        url: row.get(1)?,
+        secret: row.get(2)?,
+        events: serde_json::from_str(&events).unwrap_or_default(),
+        enabled: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        last_sent_at: row.get(7)?,
+        last_status: row.get(8)?,
+        last_error: row.get(9)?,
+    })
+}
+
+/// Create a webhook (no `id`) or change one. A new webhook without a
+/// `secret` gets a random one; an existing one keeps its secret unless a
+/// new one is given.
+pub fn save(
+    conn: &Connection,
+    id: Option<&str>,
+    url: &str,
+    events: &[String],
+    enabled: bool,
+    secret: Option<&str>,
+) -> Result<Webhook> {
+    let url = url.trim();
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(anyhow!("Webhook URLs must start with http:// or https://"));
+    }
+    if let Some(unknown) = events.iter().find(|e| !EVENTS.contains(&e.as_str())) {
+        return Err(anyhow!("Unknown event {:?}; expected one of {}", unknown, EVENTS.join(", ")));
+    }
+    let events = serde_json::to_string(events)?;
+    let now = chrono::Utc::now().timestamp_millis();
+    let id = match id {
+        Some(id) => {
+            let changed = conn.execute(
+                "UPDATE webhooks SET url = ?1, events = ?2, enabled = ?3, secret = COALESCE(?4, secret), updated_at = ?5
+                 WHERE id = ?6",
+                params![url, events, enabled, secret, now, id],
+            )?;
+            if changed == 0 {
+                return Err(anyhow!("Webhook not found"));
+            }
+            id.to_string()
+        }
+        None => {
+            let id = uuid::Uuid::new_v4().to_string();
+            let secret = match secret {
+                Some(secret) => secret.to_string(),
+                None => uuid::Uuid::new_v4().simple().to_string(),
+            };
+            conn.execute(
+                "INSERT INTO webhooks (id, url, secret, events, enabled, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+                params![id, url, secret, events, enabled, now],
+            )?;
+            id
+        }
+    };
+    get(conn, &id)?.ok_or_else(|| anyhow!("Webhook wasn't saved"))
+}
+
+pub fn delete(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute("DELETE FROM webhooks WHERE id = ?1", params![id])?;
+    Ok(())
+}
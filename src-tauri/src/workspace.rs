@@ -0,0 +1,268 @@
+use crate::{db, mirror, openclaw};
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Bumped when the bundle layout (not the app schema) changes.
+const BUNDLE_FORMAT: u32 = 1;
+
+/// Session files stored in a bundle, by where they live on disk.
+const SESSION: &str = "session";
+const REMOTE_CACHE: &str = "remote-cache";
+
+/// Tables a bundle never overwrites. The other machine's audit log and
+/// sync state stay behind; the import itself is logged here like any other
+/// change.
+const SKIP_TABLES: &[&str] = &[
+    "schema_version",
+    "audit_log",
+    "workspace_info",
+    "workspace_files",
+    "sync_peers",
+    "sync_files",
+    "reminder_links",
+    "missing_sessions",
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSummary {
+    pub path: String,
+    pub files: u32,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub rows: u32,
+    pub files_written: u32,
+    /// Session files left alone because this machine's copy is as new.
+    pub files_skipped: u32,
+}
+
+/// Write a workspace bundle to `dest`: a SQLite file holding a snapshot of
+/// the app database (settings included) plus every local session file and
+/// the cached copies of remote ones. Replaces `dest` if it exists.
+pub fn export(conn: &Connection, dest: &Path) -> Result<ExportSummary> {
+    if dest.exists() {
+        std::fs::remove_file(dest).with_context(|| format!("Can't replace {}", dest.display()))?;
+    }
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let dest_str = dest.to_str().ok_or_else(|| anyhow!("Unsupported path: {}", dest.display()))?;
+    conn.execute("VACUUM INTO ?1", [dest_str])?;
+
+    let bundle = Connection::open(dest)?;
+    bundle.execute_batch(
+        "CREATE TABLE workspace_info (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+         CREATE TABLE workspace_files (
+            kind TEXT NOT NULL,
+            path TEXT NOT NULL,
+            content BLOB NOT NULL,
+            modified_at INTEGER NOT NULL,
+            PRIMARY KEY (kind, path)
+         );",
+    )?;
+    let tx = bundle.unchecked_transaction()?;
+    for (key, value) in [
+        ("format", BUNDLE_FORMAT.to_string()),
+        ("schema_version", db::schema_version(conn)?.to_string()),
+        ("exported_at", chrono::Utc::now().timestamp_millis().to_string()),
+    ] {
+        tx.execute("INSERT INTO workspace_info (key, value) VALUES (?1, ?2)", params![key, value])?;
+    }
+    let mut files = 0;
+    let mut bytes = 0;
+    for (kind, root, sub) in [(SESSION, openclaw::agents_dir(), "sessions"), (REMOTE_CACHE, mirror::cache_dir(), "")] {
+        for (agent_id, path) in session_files(&root, sub) {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let content = std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+            tx.execute(
+                "INSERT INTO workspace_files (kind, path, content, modified_at) VALUES (?1, ?2, ?3, ?4)",
+                params![kind, format!("{}/{}", agent_id, name), content, modified_ms(&path)],
+            )?;
+            files += 1;
+            bytes += content.len() as u64;
+        }
+    }
+    tx.commit()?;
+    Ok(ExportSummary {
+        path: dest.to_string_lossy().to_string(),
+        files,
+        bytes,
+    })
+}
+
+/// `<root>/<agent>/<sub>/*.jsonl` with the agent each belongs to.
+pub fn session_files(root: &Path, sub: &str) -> Vec<(String, PathBuf)> {
+    let Ok(agents) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut files = Vec::new();
+    for agent in agents.flatten() {
+        let Some(agent_id) = agent.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        let Ok(entries) = std::fs::read_dir(agent.path().join(sub)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|e| e == "jsonl") && path.is_file() {
+                files.push((agent_id.clone(), path));
+            }
+        }
+    }
+    files
+}
+
+pub fn modified_ms(path: &Path) -> i64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+/// Load a bundle written by `export`. By default rows are merged: anything
+/// already here (same id, same setting) wins, and a session file is only
+/// overwritten by a newer copy. With `replace`, the bundle's rows replace
+/// this machine's and every session file in it is written.
+pub fn import(conn: &Connection, src: &Path, replace: bool) -> Result<ImportSummary> {
+    // Work on a copy so migrating an older bundle never touches the original
+    let copy = std::env::temp_dir().join(format!("openclaw-import-{}.db", uuid::Uuid::new_v4().simple()));
+    std::fs::copy(src, &copy).with_context(|| format!("Failed to read {}", src.display()))?;
+    let result = import_copy(conn, &copy, replace);
+    let _ = std::fs::remove_file(&copy);
+    result
+}
+
+fn import_copy(conn: &Connection, copy: &Path, replace: bool) -> Result<ImportSummary> {
+    let bundle = Connection::open(copy)?;
+    let format: Option<u32> = bundle
+        .query_row("SELECT value FROM workspace_info WHERE key = 'format'", [], |row| row.get::<_, String>(0))
+        .ok()
+        .and_then(|v| v.parse().ok());
+    match format {
+        None => return Err(anyhow!("Not a workspace bundle")),
+        Some(f) if f > BUNDLE_FORMAT => {
+            return Err(anyhow!("This bundle was written by a newer version of the app; update the app"))
+        }
+        Some(_) => {}
+    }
+    db::migrate(&bundle)?;
+    let (files_written, files_skipped) = import_files(&bundle, replace)?;
+    drop(bundle);
+
+    let copy_str = copy.to_str().ok_or_else(|| anyhow!("Unsupported path: {}", copy.display()))?;
+    conn.execute("ATTACH DATABASE ?1 AS bundle", [copy_str])?;
+    let rows = import_rows(conn, replace);
+    conn.execute_batch("DETACH DATABASE bundle")?;
+    Ok(ImportSummary {
+        rows: rows?,
+        files_written,
+        files_skipped,
+    })
+}
+
+/// Copy every app table's rows from the attached bundle, matching columns by
+/// name so tables that grew columns in a different order still line up.
+fn import_rows(conn: &Connection, replace: bool) -> Result<u32> {
+    let tables = table_names(conn, "main")?;
+    let bundled = table_names(conn, "bundle")?;
+    let tx = conn.unchecked_transaction()?;
+    let mut rows = 0;
+    for table in tables.iter().filter(|t| bundled.contains(t) && !SKIP_TABLES.contains(&t.as_str())) {
+        let ours = column_names(&tx, "main", table)?;
+        let theirs = column_names(&tx, "bundle", table)?;
+        let columns = ours
+            .iter()
+            .filter(|c| theirs.contains(c))
+            .map(|c| db::quote_ident(c))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if replace {
+            tx.execute(&format!("DELETE FROM main.{}", db::quote_ident(table)), [])?;
+        }
+        let table = db::quote_ident(table);
+        rows += tx.execute(
+            &format!("INSERT OR IGNORE INTO main.{table} ({columns}) SELECT {columns} FROM bundle.{table}"),
+            [],
+        )? as u32;
+    }
+    tx.commit()?;
+    Ok(rows)
+}
+
+/// Ordinary tables in `schema`. Full-text indexes and their shadow tables
+/// are left out; triggers rebuild them as rows arrive.
+pub fn table_names(conn: &Connection, schema: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT name, sql LIKE 'CREATE VIRTUAL TABLE%' FROM {}.sqlite_master
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        schema
+    ))?;
+    let tables = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?)))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let virtual_tables: Vec<String> = tables.iter().filter(|(_, v)| *v).map(|(name, _)| format!("{}_", name)).collect();
+    Ok(tables
+        .into_iter()
+        .filter(|(name, is_virtual)| !is_virtual && !virtual_tables.iter().any(|vt| name.starts_with(vt)))
+        .map(|(name, _)| name)
+        .collect())
+}
+
+pub fn column_names(conn: &Connection, schema: &str, table: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA {}.table_info({})", schema, db::quote_ident(table)))?;
+    let names = stmt
+        .query_map([], |row| row.get(1))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(names)
+}
+
+/// Write the bundle's session files into place. Returns (written, skipped).
+fn import_files(bundle: &Connection, replace: bool) -> Result<(u32, u32)> {
+    let mut stmt = bundle.prepare("SELECT kind, path, content, modified_at FROM workspace_files")?;
+    let files = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Vec<u8>>(2)?,
+            row.get::<_, i64>(3)?,
+        ))
+    })?;
+    let (mut written, mut skipped) = (0, 0);
+    for file in files {
+        let (kind, path, content, modified_at) = file?;
+        let Some((agent_id, session_id)) = path.split_once('/').and_then(|(a, f)| Some((a, f.strip_suffix(".jsonl")?)))
+        else {
+            eprintln!("[workspace] Skipping unexpected bundle entry {}", path);
+            continue;
+        };
+        // cache_path refuses ids that would land outside its directory
+        let cached = mirror::cache_path(agent_id, session_id)?;
+        let target = match kind.as_str() {
+            SESSION => openclaw::session_path(agent_id, session_id),
+            REMOTE_CACHE => cached,
+            _ => continue,
+        };
+        if !replace && target.exists() && modified_ms(&target) >= modified_at {
+            skipped += 1;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = target.with_extension("jsonl.import");
+        std::fs::write(&tmp, &content)?;
+        std::fs::rename(&tmp, &target)?;
+        written += 1;
+    }
+    Ok((written, skipped))
+}
@@ -10,8 +10,8 @@ import NewProjectModal from "./components/NewProjectModal";
 import SettingsPanel from "./components/SettingsPanel";
 import { useTheme } from "./hooks/useTheme";
 import { useProjects } from "./hooks/useProjects";
-import { onBrainDumpFollowedUp, onThreadRenamed, syncObsidianVault } from "./lib/tauri";
-import type { Thread, Project } from "./lib/tauri";
+import { onBrainDumpFollowedUp, onKanbanNudge, onThreadRenamed, onThreadResurfaced, setFocusedThread, syncObsidianVault } from "./lib/tauri";
+import type { Thread, Project, ResurfacedThread } from "./lib/tauri";
 
 export default function App() {
   const { mode, setMode, cycle } = useTheme();
@@ -37,6 +37,8 @@ export default function App() {
   const [showSettings, setShowSettings] = useState(false);
   const [showBrainDump, setShowBrainDump] = useState(false);
   const [brainDumpOpenCount, setBrainDumpOpenCount] = useState(0);
+  const [resurfaced, setResurfaced] = useState<ResurfacedThread[]>([]);
+  const [autoSend, setAutoSend] = useState<string | null>(null);
 
   // Resizable sidebar
   const [sidebarWidth, setSidebarWidth] = useState(240);
@@ -73,15 +75,24 @@ export default function App() {
     return () => { cleanup?.(); };
   }, []);
 
-  // Handle proactive brain dump follow-ups creating new threads
+  // Tell the backend which thread is in front of the user so its replies don't notify
+  useEffect(() => {
+    const report = () =>
+      setFocusedThread(document.hasFocus() ? activeThread?.id ?? null : null).catch(() => {});
+    report();
+    window.addEventListener("focus", report);
+    window.addEventListener("blur", report);
+    return () => {
+      window.removeEventListener("focus", report);
+      window.removeEventListener("blur", report);
+    };
+  }, [activeThread?.id]);
+
+  // Proactive brain dump follow-ups arrive in a thread of their own
   useEffect(() => {
     let cleanup: (() => void) | null = null;
-    onBrainDumpFollowedUp(async (event) => {
-      const thread = await addThread(
-        event.content.slice(0, 60) + (event.content.length > 60 ? "\u2026" : ""),
-        event.project_id,
-        "main"
-      );
+    onBrainDumpFollowedUp(({ thread }) => {
+      touchThread(thread);
       setActiveThread(thread);
     }).then((fn) => {
       cleanup = fn;
@@ -89,7 +100,45 @@ export default function App() {
     return () => {
       cleanup?.();
     };
-  }, [addThread]);
+  }, [touchThread]);
+
+  // Deadline nudges land in the card's thread; surface it in the sidebar
+  useEffect(() => {
+    let cleanup: (() => void) | null = null;
+    onKanbanNudge(({ thread }) => {
+      touchThread(thread);
+    }).then((fn) => {
+      cleanup = fn;
+    });
+    return () => {
+      cleanup?.();
+    };
+  }, [touchThread]);
+
+  // Quiet threads come back with a suggested message to pick them up again
+  useEffect(() => {
+    let cleanup: (() => void) | null = null;
+    onThreadResurfaced((item) => {
+      setResurfaced((prev) => [...prev.filter((r) => r.thread.id !== item.thread.id), item]);
+    }).then((fn) => {
+      cleanup = fn;
+    });
+    return () => {
+      cleanup?.();
+    };
+  }, []);
+
+  const dismissResurfaced = useCallback((threadId: string) => {
+    setResurfaced((prev) => prev.filter((r) => r.thread.id !== threadId));
+  }, []);
+
+  const handleSendResurfaced = useCallback((item: ResurfacedThread) => {
+    dismissResurfaced(item.thread.id);
+    setActiveView("chat");
+    setActiveProject(null);
+    setActiveThread(item.thread);
+    setAutoSend(item.suggestion);
+  }, [dismissResurfaced]);
 
   // Startup: sync Obsidian vault then refresh project list
   useEffect(() => {
@@ -221,6 +270,7 @@ export default function App() {
           flexDirection: "column",
           overflow: "hidden",
           minWidth: 0,
+          position: "relative",
         }}
       >
         {/* Header bar */}
@@ -326,8 +376,40 @@ export default function App() {
             thread={activeThread}
             isDark={isDark}
             onSent={touchThread}
+            autoSend={autoSend}
+            onAutoSent={() => setAutoSend(null)}
           />
         )}
+
+        {resurfaced.length > 0 && (
+          <div style={{ position: "absolute", right: 16, bottom: 16, width: 340, display: "flex", flexDirection: "column", gap: 8, zIndex: 20 }}>
+            {resurfaced.map((item) => (
+              <div
+                key={item.thread.id}
+                style={{ padding: 12, borderRadius: 10, border: "1px solid var(--color-border)", background: "var(--color-surface)", boxShadow: "0 4px 16px rgba(0,0,0,0.12)" }}
+              >
+                <div style={{ fontSize: 12, color: "var(--color-text-2)", marginBottom: 4 }}>
+                  {item.thread.name} · quiet for {item.idleDays} days
+                </div>
+                <div style={{ fontSize: 13, color: "var(--color-text)", marginBottom: 8 }}>{item.suggestion}</div>
+                <div style={{ display: "flex", gap: 6, justifyContent: "flex-end" }}>
+                  <button
+                    onClick={() => dismissResurfaced(item.thread.id)}
+                    style={{ padding: "4px 10px", borderRadius: 6, border: "1px solid var(--color-border)", background: "none", color: "var(--color-text-2)", fontSize: 12, cursor: "pointer" }}
+                  >
+                    Dismiss
+                  </button>
+                  <button
+                    onClick={() => handleSendResurfaced(item)}
+                    style={{ padding: "4px 10px", borderRadius: 6, border: "none", background: "var(--color-accent)", color: "#fff", fontSize: 12, cursor: "pointer" }}
+                  >
+                    Send
+                  </button>
+                </div>
+              </div>
+            ))}
+          </div>
+        )}
       </div>
 
       {/* Brain Dump right panel */}
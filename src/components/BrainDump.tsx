@@ -12,6 +12,7 @@ import {
   deleteBrainDump,
   listBrainDumps,
   setBrainDumpProactive,
+  setBrainDumpCadence,
   updateBrainDumpStatus,
   promoteBrainDump,
   type BrainDump as BrainDumpItem,
@@ -89,6 +90,17 @@ export default function BrainDump({ projects, onCreateThread, onSelectThread, on
     } catch {}
   }, []);
 
+  // Once → daily → weekly
+  const handleCycleCadence = useCallback(async (item: BrainDumpItem) => {
+    const next = !item.follow_up_interval_hours ? 24 : item.follow_up_interval_hours === 24 ? 168 : null;
+    try {
+      await setBrainDumpCadence(item.id, next, item.max_follow_ups ?? null);
+      setItems((prev) =>
+        prev.map((d) => (d.id === item.id ? { ...d, follow_up_interval_hours: next } : d))
+      );
+    } catch {}
+  }, []);
+
   const handleDone = useCallback(async (id: string) => {
     try {
       await updateBrainDumpStatus(id, "done");
@@ -367,6 +379,17 @@ export default function BrainDump({ projects, onCreateThread, onSelectThread, on
                     <IconSparkles size={11} />
                     {item.proactive ? "Auto on" : "Auto"}
                   </ActionButton>
+                  {item.proactive && (
+                    <ActionButton title="How often to follow up" onClick={() => handleCycleCadence(item)}>
+                      {item.follow_up_interval_hours === 24
+                        ? "Daily"
+                        : item.follow_up_interval_hours === 168
+                        ? "Weekly"
+                        : item.follow_up_interval_hours
+                        ? `${item.follow_up_interval_hours}h`
+                        : "Once"}
+                    </ActionButton>
+                  )}
                   <ActionButton title="Mark done" onClick={() => handleDone(item.id)}>
                     <IconCheck size={11} />
                   </ActionButton>
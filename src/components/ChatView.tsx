@@ -1,29 +1,62 @@
-import { useEffect, useRef } from "react";
+import { useEffect, useRef, useState } from "react";
 import { IconMessage } from "@tabler/icons-react";
 import MessageBubble from "./MessageBubble";
 import InputBar from "./InputBar";
 import { useChat } from "../hooks/useChat";
-import type { Thread } from "../lib/tauri";
+import { compactSession, errorMessage, type Thread } from "../lib/tauri";
 
 interface Props {
   thread: Thread | null;
   isDark: boolean;
   onSent?: (thread: Thread) => void;
+  /** Sent as soon as the thread has loaded, e.g. a resurfaced suggestion. */
+  autoSend?: string | null;
+  onAutoSent?: () => void;
 }
 
-export default function ChatView({ thread, isDark, onSent }: Props) {
-  const { messages, sending, loading, error, send } = useChat(thread);
+export default function ChatView({ thread, isDark, onSent, autoSend, onAutoSent }: Props) {
+  const { messages, sending, loading, processing, error, send, hasEarlier, loadingEarlier, loadEarlier } =
+    useChat(thread);
   const bottomRef = useRef<HTMLDivElement>(null);
+  const [compacting, setCompacting] = useState(false);
+  const [compactNote, setCompactNote] = useState<string | null>(null);
 
+  useEffect(() => setCompactNote(null), [thread?.id]);
+
+  const handleCompact = async () => {
+    if (!thread) return;
+    const ok = window.confirm(
+      "Replace the older messages of this thread with a summary? The original session file is archived."
+    );
+    if (!ok) return;
+    setCompacting(true);
+    try {
+      const result = await compactSession(thread.id);
+      setCompactNote(`Summarized ${result.summarized} older messages`);
+    } catch (err) {
+      setCompactNote(errorMessage(err));
+    } finally {
+      setCompacting(false);
+    }
+  };
+
+  // Follow the newest message; paging in earlier ones leaves it alone
+  const newest = messages[messages.length - 1];
   useEffect(() => {
     bottomRef.current?.scrollIntoView({ behavior: "smooth" });
-  }, [messages.length]);
+  }, [newest]);
 
   const handleSend = async (text: string) => {
     await send(text);
     if (thread) onSent?.(thread);
   };
 
+  useEffect(() => {
+    if (!autoSend || !thread || loading || sending) return;
+    onAutoSent?.();
+    handleSend(autoSend);
+  }, [autoSend, thread, loading, sending]);
+
   if (!thread) {
     return (
       <div
@@ -98,6 +131,48 @@ export default function ChatView({ thread, isDark, onSent }: Props) {
           </div>
         )}
 
+        {!loading && hasEarlier && (
+          <div style={{ textAlign: "center", marginBottom: 12 }}>
+            <button
+              onClick={loadEarlier}
+              disabled={loadingEarlier}
+              style={{
+                padding: "6px 14px",
+                borderRadius: 8,
+                border: "1px solid var(--color-border)",
+                background: "var(--color-surface-2)",
+                color: "var(--color-text-2)",
+                fontSize: 13,
+                cursor: loadingEarlier ? "default" : "pointer",
+              }}
+            >
+              {loadingEarlier ? "Loading…" : "Load earlier messages"}
+            </button>
+            <button
+              onClick={handleCompact}
+              disabled={compacting || processing}
+              style={{
+                marginLeft: 8,
+                padding: "6px 14px",
+                borderRadius: 8,
+                border: "1px solid var(--color-border)",
+                background: "var(--color-surface-2)",
+                color: "var(--color-text-2)",
+                fontSize: 13,
+                cursor: compacting || processing ? "default" : "pointer",
+              }}
+            >
+              {compacting ? "Summarizing…" : "Compact older messages"}
+            </button>
+          </div>
+        )}
+
+        {compactNote && (
+          <div style={{ textAlign: "center", color: "var(--color-text-2)", fontSize: 12, marginBottom: 12 }}>
+            {compactNote}
+          </div>
+        )}
+
         {messages.map((msg, i) => (
           <div key={i}>
             <MessageBubble message={msg} isDark={isDark} />
@@ -121,7 +196,7 @@ export default function ChatView({ thread, isDark, onSent }: Props) {
           </div>
         )}
 
-        {sending && (
+        {(sending || processing) && (
           <div style={{ display: "flex", justifyContent: "flex-start", marginBottom: 8 }}>
             <div
               style={{
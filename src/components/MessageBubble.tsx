@@ -3,7 +3,7 @@ import { Prism as SyntaxHighlighter } from "react-syntax-highlighter";
 import { oneDark } from "react-syntax-highlighter/dist/esm/styles/prism";
 import { oneLight } from "react-syntax-highlighter/dist/esm/styles/prism";
 import { remarkPlugins } from "../lib/markdown";
-import type { ChatMessage } from "../lib/tauri";
+import type { ChatMessage, MessagePart } from "../lib/tauri";
 
 interface Props {
   message: ChatMessage;
@@ -60,7 +60,47 @@ export default function MessageBubble({ message, isDark }: Props) {
             {message.content}
           </ReactMarkdown>
         </div>
+        {message.parts?.map((part, i) => (
+          <Part key={i} part={part} isDark={isDark} />
+        ))}
       </div>
     </div>
   );
 }
+
+function Part({ part, isDark }: { part: MessagePart; isDark: boolean }) {
+  const codeStyle = { margin: "0.5rem 0", borderRadius: 8, fontSize: 13 };
+  switch (part.type) {
+    case "code":
+      return (
+        <SyntaxHighlighter
+          style={isDark ? oneDark : oneLight}
+          language={part.language ?? "text"}
+          PreTag="div"
+          customStyle={codeStyle}
+        >
+          {part.code.replace(/\n$/, "")}
+        </SyntaxHighlighter>
+      );
+    case "diff":
+      return (
+        <div>
+          {part.path && <div style={{ fontSize: 12, opacity: 0.7, marginTop: 6 }}>{part.path}</div>}
+          <SyntaxHighlighter style={isDark ? oneDark : oneLight} language="diff" PreTag="div" customStyle={codeStyle}>
+            {part.diff.replace(/\n$/, "")}
+          </SyntaxHighlighter>
+        </div>
+      );
+    case "image": {
+      const src = part.url ?? `data:${part.media_type ?? "image/png"};base64,${part.data}`;
+      return <img src={src} alt="" style={{ maxWidth: "100%", borderRadius: 8, margin: "0.5rem 0" }} />;
+    }
+    case "tool":
+      return (
+        <div style={{ fontSize: 12, opacity: 0.7, margin: "4px 0", fontFamily: "monospace", wordBreak: "break-all" }}>
+          ⚙ {part.name}
+          {part.summary && ` ${part.summary}`}
+        </div>
+      );
+  }
+}
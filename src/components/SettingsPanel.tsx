@@ -4,11 +4,54 @@ import {
   configureSsh,
   getSshConfig,
   getRemoteMode,
+  onSshHostKey,
   setRemoteMode,
+  sshConnect,
+  sshDisconnect,
   testSsh,
+  trustSshHostKey,
+  getProactiveSettings,
+  getProactiveStatus,
+  getProactiveEffectiveness,
+  getDndStatus,
+  setDnd,
+  listJobs,
+  listPromptTemplates,
+  onJobFinished,
+  runJobNow,
+  setJobEnabled,
+  setJobSchedule,
+  resetPromptTemplate,
+  setPromptTemplate,
+  onProactiveStatus,
+  runProactiveNow,
+  setProactivePaused,
   getSetting,
   setSetting,
+  updateProactiveSettings,
   syncObsidianVault,
+  exportWorkspace,
+  getAuditLog,
+  getDbStats,
+  type DbStats,
+  revertAuditEntry,
+  type AuditEntry,
+  importWorkspace,
+  detectOpenclaw,
+  checkAgentHealth,
+  errorMessage,
+  type HealthReport,
+  type OpenClawDetection,
+  type ProactiveMode,
+  type ProactiveSchedule,
+  type ProactiveStatus,
+  type ProactiveEffectiveness,
+  type DndStatus,
+  type JobStatus,
+  type PromptTemplate,
+  type SessionDiscovery,
+  type SessionRetention,
+  type WatcherMode,
   type SshConfig,
   type SyncResult,
 } from "../lib/tauri";
@@ -38,14 +81,119 @@ export default function SettingsPanel({ onClose, themeMode, onThemeChange, onPro
   const [syncing, setSyncing] = useState(false);
   const [syncResult, setSyncResult] = useState<SyncResult | null>(null);
 
+  // Local openclaw install
+  const [openclawBin, setOpenclawBin] = useState("");
+  const [openclawPath, setOpenclawPath] = useState("");
+  const [titleModel, setTitleModel] = useState("");
+  const [titleRefreshAt, setTitleRefreshAt] = useState("23:55");
+  const [discovery, setDiscovery] = useState<SessionDiscovery>("create");
+  const [retention, setRetention] = useState<SessionRetention>("keep");
+  const [watcherMode, setWatcherMode] = useState<WatcherMode>("auto");
+
+  // Proactive follow-up schedule
+  const [schedule, setSchedule] = useState<ProactiveSchedule>({
+    interval_mins: 240,
+    active_days: [0, 1, 2, 3, 4, 5, 6],
+    quiet_start: "22:00",
+    quiet_end: "08:00",
+    wait_for_activity: true,
+  });
+  const [proactive, setProactive] = useState<ProactiveStatus | null>(null);
+  const [effectiveness, setEffectiveness] = useState<ProactiveEffectiveness | null>(null);
+  const [runningNow, setRunningNow] = useState(false);
+  const [proactiveMode, setProactiveMode] = useState<ProactiveMode>("per_dump");
+  const [briefingTime, setBriefingTime] = useState("");
+  const [resurfaceDays, setResurfaceDays] = useState("7");
+  const [exportRetrospective, setExportRetrospective] = useState(false);
+
+  useEffect(() => {
+    getProactiveStatus().then(setProactive).catch(() => {});
+    getProactiveEffectiveness().then(setEffectiveness).catch(() => {});
+    const unlisten = onProactiveStatus(setProactive);
+    return () => { unlisten.then((fn) => fn()); };
+  }, []);
+
+  const handleRunNow = useCallback(async () => {
+    setRunningNow(true);
+    try {
+      setProactive(await runProactiveNow());
+    } catch (err) {
+      setProactive((p) => (p ? { ...p, lastError: errorMessage(err) } : p));
+    } finally {
+      setRunningNow(false);
+    }
+  }, []);
+
+  const handleTogglePaused = useCallback(async () => {
+    if (!proactive) return;
+    try {
+      setProactive(await setProactivePaused(!proactive.paused));
+    } catch (err) {
+      console.error(err);
+    }
+  }, [proactive]);
+  const [detecting, setDetecting] = useState(false);
+  const [detection, setDetection] = useState<OpenClawDetection | null>(null);
+  const [checking, setChecking] = useState(false);
+  const [health, setHealth] = useState<HealthReport | null>(null);
+  const [healthError, setHealthError] = useState<string | null>(null);
+
   useEffect(() => {
-    Promise.all([getSshConfig(), getRemoteMode(), getSetting("obsidian_vault_path")]).then(([cfg, rm, vp]) => {
+    Promise.all([
+      getSshConfig(),
+      getRemoteMode(),
+      getSetting("obsidian_vault_path"),
+      getSetting("openclaw_binary_path"),
+      getSetting("openclaw_path_env"),
+      getSetting("title_model"),
+      getSetting("session_discovery"),
+      getSetting("session_retention"),
+      getSetting("watcher_mode"),
+      getProactiveSettings(),
+      getSetting("briefing_time"),
+      getSetting("resurface_after_days"),
+      getSetting("proactive_mode"),
+      listJobs(),
+      getSetting("obsidian_retrospective"),
+    ]).then(([cfg, rm, vp, bin, pathEnv, tm, sd, sr, wm, ps, bt, rd, pm, jobs, er]) => {
       setConfig(cfg);
       setRemote(rm);
       if (vp) setVaultPath(vp);
+      if (bin) setOpenclawBin(bin);
+      if (pathEnv) setOpenclawPath(pathEnv);
+      if (tm) setTitleModel(tm);
+      if (sd === "off" || sd === "notify") setDiscovery(sd);
+      if (sr === "archive" || sr === "delete") setRetention(sr);
+      if (wm === "poll") setWatcherMode(wm);
+      setSchedule(ps);
+      if (bt) setBriefingTime(bt);
+      if (rd) setResurfaceDays(rd);
+      if (pm === "digest") setProactiveMode(pm);
+      setExportRetrospective(er === "true");
+      const refreshAt = cronToTime(jobs.find((j) => j.id === "title_refresh")?.schedule);
+      if (refreshAt) setTitleRefreshAt(refreshAt);
     }).catch(() => {});
   }, []);
 
+  // First contact with a host: show its fingerprint and only trust it on confirmation
+  useEffect(() => {
+    const unlisten = onSshHostKey(async ({ host, port, keys }) => {
+      const key = keys.find((k) => k.keyType === "ssh-ed25519") ?? keys[0];
+      if (!key) return;
+      const ok = window.confirm(
+        `The authenticity of ${host}:${port} can't be established.\n\n${key.keyType} key fingerprint:\n${key.fingerprint}\n\nTrust this host?`
+      );
+      if (!ok) return;
+      try {
+        await trustSshHostKey(key.fingerprint);
+        setTestResult({ ok: true, msg: "Host key trusted — test again to connect" });
+      } catch (err: any) {
+        setTestResult({ ok: false, msg: errorMessage(err) });
+      }
+    });
+    return () => { unlisten.then((fn) => fn()); };
+  }, []);
+
   const handleTest = useCallback(async () => {
     setTesting(true);
     setTestResult(null);
@@ -54,7 +202,7 @@ export default function SettingsPanel({ onClose, themeMode, onThemeChange, onPro
       const result = await testSsh();
       setTestResult({ ok: true, msg: `Connected: ${result}` });
     } catch (err: any) {
-      setTestResult({ ok: false, msg: String(err) });
+      setTestResult({ ok: false, msg: errorMessage(err) });
     } finally {
       setTesting(false);
     }
@@ -65,14 +213,59 @@ export default function SettingsPanel({ onClose, themeMode, onThemeChange, onPro
     try {
       await configureSsh(config);
       await setRemoteMode(remote);
+      // Remote calls no longer connect on demand; open/close the session explicitly
+      if (remote) await sshConnect();
+      else await sshDisconnect();
       if (vaultPath) await setSetting("obsidian_vault_path", vaultPath);
+      await setSetting("openclaw_binary_path", openclawBin);
+      await setSetting("openclaw_path_env", openclawPath);
+      await setSetting("title_model", titleModel);
+      const refreshCron = timeToCron(titleRefreshAt);
+      if (refreshCron) await setJobSchedule("title_refresh", refreshCron);
+      await setSetting("session_discovery", discovery);
+      await setSetting("session_retention", retention);
+      await setSetting("watcher_mode", watcherMode);
+      await updateProactiveSettings(schedule);
+      await setSetting("proactive_mode", proactiveMode);
+      await setSetting("briefing_time", briefingTime.trim());
+      await setSetting("resurface_after_days", resurfaceDays || "0");
+      await setSetting("obsidian_retrospective", exportRetrospective ? "true" : "false");
     } catch (err) {
       console.error(err);
     } finally {
       setSaving(false);
       onClose();
     }
-  }, [config, remote, onClose]);
+  }, [config, remote, onClose, vaultPath, openclawBin, openclawPath, titleModel, titleRefreshAt, discovery, retention, watcherMode, schedule, proactiveMode, briefingTime, resurfaceDays, exportRetrospective]);
+
+  const handleDetect = useCallback(async () => {
+    setDetecting(true);
+    setDetection(null);
+    try {
+      await setSetting("openclaw_binary_path", openclawBin);
+      await setSetting("openclaw_path_env", openclawPath);
+      setDetection(await detectOpenclaw());
+    } catch (err: any) {
+      setDetection({ path: null, source: "auto", version: null, path_env: "", error: errorMessage(err) });
+    } finally {
+      setDetecting(false);
+    }
+  }, [openclawBin, openclawPath]);
+
+  const handleHealthCheck = useCallback(async () => {
+    setChecking(true);
+    setHealth(null);
+    setHealthError(null);
+    try {
+      await setSetting("openclaw_binary_path", openclawBin);
+      await setSetting("openclaw_path_env", openclawPath);
+      setHealth(await checkAgentHealth());
+    } catch (err: any) {
+      setHealthError(errorMessage(err));
+    } finally {
+      setChecking(false);
+    }
+  }, [openclawBin, openclawPath]);
 
   return (
     <div
@@ -183,7 +376,17 @@ export default function SettingsPanel({ onClose, themeMode, onThemeChange, onPro
                   setSyncResult(result);
                   if (result.created > 0 || result.updated > 0) onProjectsChanged?.();
                 } catch (err: any) {
-                  setSyncResult({ created: 0, updated: 0, skipped: 0, errors: [String(err)] });
+                  setSyncResult({
+                    created: 0,
+                    updated: 0,
+                    skipped: 0,
+                    archived: [],
+                    restored: [],
+                    tasks_created: 0,
+                    tasks_completed: 0,
+                    changes: [],
+                    errors: [errorMessage(err)],
+                  });
                 } finally {
                   setSyncing(false);
                 }
@@ -209,6 +412,307 @@ export default function SettingsPanel({ onClose, themeMode, onThemeChange, onPro
           </div>
         </section>
 
+        {/* OpenClaw */}
+        <section style={{ marginBottom: 24 }}>
+          <div style={{ fontSize: 12, fontWeight: 600, color: "var(--color-text-2)", textTransform: "uppercase", letterSpacing: 1, marginBottom: 10 }}>
+            OpenClaw
+          </div>
+          <div style={{ display: "flex", flexDirection: "column", gap: 10 }}>
+            <Field label="Binary path (blank to auto-detect)" value={openclawBin} onChange={setOpenclawBin} placeholder="/usr/local/bin/openclaw" />
+            <Field label="PATH override (blank for default)" value={openclawPath} onChange={setOpenclawPath} placeholder="/usr/local/bin:/usr/bin:/bin" />
+            <Field label="Title model (blank for the agent's default)" value={titleModel} onChange={setTitleModel} placeholder="a small, cheap model" />
+            <Field label="Refresh stale titles nightly at" value={titleRefreshAt} onChange={setTitleRefreshAt} placeholder="23:55" />
+            <Choice
+              label="Sessions started from the CLI"
+              value={discovery}
+              onChange={setDiscovery}
+              options={[
+                ["create", "Add a thread for each"],
+                ["notify", "Notice, but don't add threads"],
+                ["off", "Ignore"],
+              ]}
+            />
+            <Choice
+              label="Session file when its thread is deleted"
+              value={retention}
+              onChange={setRetention}
+              options={[
+                ["keep", "Keep"],
+                ["archive", "Move to ~/.openclaw/chat/archive"],
+                ["delete", "Delete"],
+              ]}
+            />
+            <Choice
+              label="Watching session files"
+              value={watcherMode}
+              onChange={setWatcherMode}
+              options={[
+                ["auto", "File events, polling if they fail"],
+                ["poll", "Always poll (network drives, remote mounts)"],
+              ]}
+            />
+
+            {detection && (
+              <div
+                style={{
+                  padding: "8px 12px",
+                  borderRadius: 8,
+                  background: detection.error ? "#fee2e2" : "#d1fae5",
+                  color: detection.error ? "#991b1b" : "#065f46",
+                  fontSize: 13,
+                  wordBreak: "break-all",
+                }}
+              >
+                {detection.path && <div>{detection.path} ({detection.source})</div>}
+                {detection.version && <div>Version: {detection.version}</div>}
+                {detection.error && <div>{detection.error}</div>}
+              </div>
+            )}
+
+            {(health || healthError) && (
+              <div
+                style={{
+                  padding: "8px 12px",
+                  borderRadius: 8,
+                  background: health?.ok ? "#d1fae5" : "#fee2e2",
+                  color: health?.ok ? "#065f46" : "#991b1b",
+                  fontSize: 13,
+                  wordBreak: "break-all",
+                }}
+              >
+                {healthError && <div>{healthError}</div>}
+                {health?.checks.map((c) => (
+                  <div key={c.name}>
+                    {c.ok ? "✓" : "✗"} {c.name}: {c.detail}
+                    {c.duration_ms != null && ` (${(c.duration_ms / 1000).toFixed(1)}s)`}
+                  </div>
+                ))}
+              </div>
+            )}
+
+            <div style={{ display: "flex", gap: 8 }}>
+              <button
+                onClick={handleDetect}
+                disabled={detecting}
+                style={{
+                  padding: "8px 16px",
+                  borderRadius: 8,
+                  border: "1px solid var(--color-border)",
+                  background: "var(--color-surface-2)",
+                  color: "var(--color-text)",
+                  fontSize: 14,
+                  cursor: detecting ? "default" : "pointer",
+                  display: "flex",
+                  alignItems: "center",
+                  gap: 6,
+                }}
+              >
+                {detecting && <IconLoader2 size={14} className="animate-spin" />}
+                {detecting ? "Detecting…" : "Detect"}
+              </button>
+              <button
+                onClick={handleHealthCheck}
+                disabled={checking}
+                style={{
+                  padding: "8px 16px",
+                  borderRadius: 8,
+                  border: "1px solid var(--color-border)",
+                  background: "var(--color-surface-2)",
+                  color: "var(--color-text)",
+                  fontSize: 14,
+                  cursor: checking ? "default" : "pointer",
+                  display: "flex",
+                  alignItems: "center",
+                  gap: 6,
+                }}
+              >
+                {checking && <IconLoader2 size={14} className="animate-spin" />}
+                {checking ? "Checking…" : "Health check"}
+              </button>
+            </div>
+          </div>
+        </section>
+
+        {/* Do not disturb */}
+        <section style={{ marginBottom: 24 }}>
+          <div style={{ fontSize: 12, fontWeight: 600, color: "var(--color-text-2)", textTransform: "uppercase", letterSpacing: 1, marginBottom: 10 }}>
+            Do not disturb
+          </div>
+          <DoNotDisturb />
+        </section>
+
+        {/* Proactive follow-ups */}
+        <section style={{ marginBottom: 24 }}>
+          <div style={{ fontSize: 12, fontWeight: 600, color: "var(--color-text-2)", textTransform: "uppercase", letterSpacing: 1, marginBottom: 10 }}>
+            Follow-ups
+          </div>
+          <div style={{ display: "flex", flexDirection: "column", gap: 10 }}>
+            <Field
+              label="Every (minutes)"
+              value={String(schedule.interval_mins)}
+              onChange={(v) => setSchedule((s) => ({ ...s, interval_mins: Number(v.replace(/\D/g, "")) || 0 }))}
+            />
+            <div>
+              <div style={{ fontSize: 12, color: "var(--color-text-2)", marginBottom: 4 }}>On</div>
+              <div style={{ display: "flex", gap: 4 }}>
+                {DAYS.map((day, i) => {
+                  const on = schedule.active_days.includes(i);
+                  return (
+                    <button
+                      key={day}
+                      onClick={() =>
+                        setSchedule((s) => ({
+                          ...s,
+                          active_days: on ? s.active_days.filter((d) => d !== i) : [...s.active_days, i].sort(),
+                        }))
+                      }
+                      style={{
+                        flex: 1,
+                        padding: "6px 0",
+                        borderRadius: 6,
+                        border: "1px solid var(--color-border)",
+                        background: on ? "var(--color-accent)" : "var(--color-surface-2)",
+                        color: on ? "#fff" : "var(--color-text)",
+                        fontSize: 12,
+                        cursor: "pointer",
+                      }}
+                    >
+                      {day}
+                    </button>
+                  );
+                })}
+              </div>
+            </div>
+            <div style={{ display: "flex", gap: 10 }}>
+              <div style={{ flex: 1 }}>
+                <Field
+                  label="Quiet from"
+                  value={schedule.quiet_start ?? ""}
+                  onChange={(v) => setSchedule((s) => ({ ...s, quiet_start: v || null }))}
+                  placeholder="22:00"
+                />
+              </div>
+              <div style={{ flex: 1 }}>
+                <Field
+                  label="Until"
+                  value={schedule.quiet_end ?? ""}
+                  onChange={(v) => setSchedule((s) => ({ ...s, quiet_end: v || null }))}
+                  placeholder="08:00"
+                />
+              </div>
+            </div>
+            <label style={{ display: "flex", alignItems: "center", gap: 10, cursor: "pointer" }}>
+              <input
+                type="checkbox"
+                checked={schedule.wait_for_activity}
+                onChange={(e) => setSchedule((s) => ({ ...s, wait_for_activity: e.target.checked }))}
+                style={{ width: 16, height: 16, cursor: "pointer" }}
+              />
+              <span style={{ fontSize: 14 }}>Wait until I'm at the computer</span>
+            </label>
+            <Choice
+              label="Send follow-ups"
+              value={proactiveMode}
+              onChange={setProactiveMode}
+              options={[
+                ["per_dump", "In a thread per brain dump"],
+                ["digest", "As one digest of everything due"],
+              ]}
+            />
+            <Field
+              label="Morning briefing at (blank for none)"
+              value={briefingTime}
+              onChange={setBriefingTime}
+              placeholder="07:30"
+            />
+            <Field
+              label="Resurface threads quiet for (days, 0 for never)"
+              value={resurfaceDays}
+              onChange={(v) => setResurfaceDays(v.replace(/\D/g, ""))}
+            />
+            <label style={{ display: "flex", alignItems: "center", gap: 10, cursor: "pointer" }}>
+              <input
+                type="checkbox"
+                checked={exportRetrospective}
+                onChange={(e) => setExportRetrospective(e.target.checked)}
+                style={{ width: 16, height: 16, cursor: "pointer" }}
+              />
+              <span style={{ fontSize: 14 }}>Save the Sunday retrospective to Obsidian</span>
+            </label>
+
+            {proactive && (
+              <div style={{ fontSize: 13, color: proactive.lastError ? "#991b1b" : "var(--color-text-2)" }}>
+                {proactive.paused
+                  ? "Paused"
+                  : proactive.nextRunAt
+                  ? `Next run ${new Date(proactive.nextRunAt).toLocaleString()}`
+                  : "No run scheduled"}
+                {proactive.lastRunAt && ` · last run ${new Date(proactive.lastRunAt).toLocaleString()}, ${proactive.lastSent} sent`}
+                {proactive.lastRunAt && proactive.lastFailed > 0 && `, ${proactive.lastFailed} failed`}
+                {proactive.lastError && <div>{proactive.lastError}</div>}
+              </div>
+            )}
+            {effectiveness && effectiveness.overall.sent > 0 && (
+              <div style={{ fontSize: 13, color: "var(--color-text-2)" }}>
+                Last 30 days: {effectiveness.overall.replied} of {effectiveness.overall.sent} follow-ups answered within 48h
+                {effectiveness.overall.replyRate != null && ` (${Math.round(effectiveness.overall.replyRate * 100)}%)`}
+                {effectiveness.overall.pending > 0 && `, ${effectiveness.overall.pending} waiting`}
+              </div>
+            )}
+
+            <div style={{ display: "flex", gap: 8 }}>
+              <button
+                onClick={handleRunNow}
+                disabled={runningNow || proactive?.running}
+                style={{ padding: "7px 14px", borderRadius: 8, border: "1px solid var(--color-border)", background: "var(--color-surface-2)", color: "var(--color-text)", fontSize: 14, cursor: "pointer", display: "flex", alignItems: "center", gap: 6 }}
+              >
+                {(runningNow || proactive?.running) && <IconLoader2 size={14} className="animate-spin" />}
+                Run now
+              </button>
+              <button
+                onClick={handleTogglePaused}
+                disabled={!proactive}
+                style={{ padding: "7px 14px", borderRadius: 8, border: "1px solid var(--color-border)", background: "var(--color-surface-2)", color: "var(--color-text)", fontSize: 14, cursor: "pointer" }}
+              >
+                {proactive?.paused ? "Resume" : "Pause"}
+              </button>
+            </div>
+          </div>
+        </section>
+
+        {/* Agent prompts */}
+        <section style={{ marginBottom: 24 }}>
+          <div style={{ fontSize: 12, fontWeight: 600, color: "var(--color-text-2)", textTransform: "uppercase", letterSpacing: 1, marginBottom: 10 }}>
+            Prompts
+          </div>
+          <PromptTemplates />
+        </section>
+
+        {/* Background jobs */}
+        <section style={{ marginBottom: 24 }}>
+          <div style={{ fontSize: 12, fontWeight: 600, color: "var(--color-text-2)", textTransform: "uppercase", letterSpacing: 1, marginBottom: 10 }}>
+            Background jobs
+          </div>
+          <Jobs />
+          <DatabaseStats />
+        </section>
+
+        {/* Audit log */}
+        <section style={{ marginBottom: 24 }}>
+          <div style={{ fontSize: 12, fontWeight: 600, color: "var(--color-text-2)", textTransform: "uppercase", letterSpacing: 1, marginBottom: 10 }}>
+            Recent changes
+          </div>
+          <RecentChanges onReverted={onProjectsChanged} />
+        </section>
+
+        {/* Workspace bundle */}
+        <section style={{ marginBottom: 24 }}>
+          <div style={{ fontSize: 12, fontWeight: 600, color: "var(--color-text-2)", textTransform: "uppercase", letterSpacing: 1, marginBottom: 10 }}>
+            Export &amp; import
+          </div>
+          <Workspace onImported={onProjectsChanged} />
+        </section>
+
         {/* SSH */}
         <section>
           <div style={{ fontSize: 12, fontWeight: 600, color: "var(--color-text-2)", textTransform: "uppercase", letterSpacing: 1, marginBottom: 10 }}>
@@ -230,6 +734,8 @@ export default function SettingsPanel({ onClose, themeMode, onThemeChange, onPro
             <Field label="SSH Port" value={String(config.port)} onChange={(v) => setConfig({ ...config, port: parseInt(v) || 22 })} placeholder="22" />
             <Field label="SSH User" value={config.user} onChange={(v) => setConfig({ ...config, user: v })} placeholder="clawdbot1" />
             <Field label="SSH Key" value={config.key_path} onChange={(v) => setConfig({ ...config, key_path: v })} placeholder="~/.ssh/id_ed25519" />
+            <Field label="Remote openclaw binary" value={config.openclaw_bin ?? ""} onChange={(v) => setConfig({ ...config, openclaw_bin: v || null })} placeholder="openclaw" />
+            <Field label="Remote state dir" value={config.state_dir ?? ""} onChange={(v) => setConfig({ ...config, state_dir: v || null })} placeholder="~/.openclaw" />
 
             {testResult && (
               <div
@@ -290,6 +796,388 @@ export default function SettingsPanel({ onClose, themeMode, onThemeChange, onPro
   );
 }
 
+const DAYS = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/** "HH:MM" for a daily cron schedule ("M H * * *"), or undefined for anything else. */
+function cronToTime(cron?: string | null) {
+  const m = cron?.match(/^(\d{1,2}) (\d{1,2}) \* \* \*$/);
+  return m ? `${m[2].padStart(2, "0")}:${m[1].padStart(2, "0")}` : undefined;
+}
+
+/** Daily cron schedule for "HH:MM", or undefined if it isn't a valid time. */
+function timeToCron(time: string) {
+  const m = time.trim().match(/^(\d{1,2}):(\d{2})$/);
+  if (!m || Number(m[1]) > 23 || Number(m[2]) > 59) return undefined;
+  return `${Number(m[2])} ${Number(m[1])} * * *`;
+}
+
+/** Switch jobs on and off, change cron schedules and run jobs by hand; each change applies right away. */
+function Jobs() {
+  const [jobs, setJobs] = useState<JobStatus[]>([]);
+  const [schedules, setSchedules] = useState<Record<string, string>>({});
+  const [error, setError] = useState<string | null>(null);
+
+  const load = useCallback(
+    () =>
+      listJobs()
+        .then((list) => {
+          setJobs(list);
+          setSchedules(Object.fromEntries(list.map((j) => [j.id, j.schedule ?? ""])));
+        })
+        .catch(() => {}),
+    []
+  );
+  useEffect(() => {
+    load();
+    const unlisten = onJobFinished(() => load());
+    return () => { unlisten.then((fn) => fn()); };
+  }, [load]);
+
+  const act = async (fn: () => Promise<unknown>) => {
+    setError(null);
+    try {
+      await fn();
+    } catch (err) {
+      setError(errorMessage(err));
+    }
+    load();
+  };
+
+  const buttonStyle = { padding: "4px 10px", borderRadius: 6, border: "1px solid var(--color-border)", background: "var(--color-surface-2)", color: "var(--color-text)", fontSize: 12, cursor: "pointer" };
+
+  return (
+    <div style={{ display: "flex", flexDirection: "column", gap: 8 }}>
+      {jobs.map((job) => (
+        <div key={job.id} style={{ padding: 10, borderRadius: 8, border: "1px solid var(--color-border)" }}>
+          <div style={{ display: "flex", alignItems: "center", gap: 8 }}>
+            <input
+              type="checkbox"
+              checked={job.enabled}
+              onChange={(e) => act(() => setJobEnabled(job.id, e.target.checked))}
+              style={{ width: 14, height: 14, cursor: "pointer" }}
+            />
+            <span style={{ flex: 1, fontSize: 14 }}>{job.description}</span>
+            <button onClick={() => act(() => runJobNow(job.id))} disabled={job.running} style={buttonStyle}>
+              {job.running ? "Running…" : "Run now"}
+            </button>
+          </div>
+          {job.defaultSchedule && (
+            <div style={{ display: "flex", gap: 6, marginTop: 6 }}>
+              <input
+                value={schedules[job.id] ?? ""}
+                onChange={(e) => setSchedules((s) => ({ ...s, [job.id]: e.target.value }))}
+                onBlur={() => {
+                  const value = (schedules[job.id] ?? "").trim();
+                  if (value !== job.schedule) act(() => setJobSchedule(job.id, value || null));
+                }}
+                placeholder={job.defaultSchedule}
+                style={{ flex: 1, padding: "4px 8px", borderRadius: 6, border: "1px solid var(--color-border)", background: "var(--color-surface-2)", color: "var(--color-text)", fontSize: 12, fontFamily: "monospace", outline: "none" }}
+              />
+              {job.schedule !== job.defaultSchedule && (
+                <button onClick={() => act(() => setJobSchedule(job.id, null))} style={buttonStyle}>
+                  Default
+                </button>
+              )}
+            </div>
+          )}
+          <div style={{ fontSize: 12, color: job.lastRun?.ok === false ? "#991b1b" : "var(--color-text-2)", marginTop: 6 }}>
+            {job.nextRunAt ? `Next ${new Date(job.nextRunAt).toLocaleString()}` : "Not scheduled"}
+            {job.lastRun &&
+              ` · last ${new Date(job.lastRun.started_at).toLocaleString()}: ${job.lastRun.error ?? job.lastRun.detail ?? "running"}`}
+          </div>
+        </div>
+      ))}
+      {error && <div style={{ fontSize: 13, color: "#991b1b" }}>{error}</div>}
+    </div>
+  );
+}
+
+type DndDuration = "off" | "hour" | "morning" | "on";
+
+/** Do-not-disturb switch; saved on its own, not with the panel. */
+function DoNotDisturb() {
+  const [status, setStatus] = useState<DndStatus | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    getDndStatus().then(setStatus).catch(() => {});
+  }, []);
+
+  const apply = async (enabled: boolean, until: number | null, followFocus: boolean) => {
+    setError(null);
+    try {
+      setStatus(await setDnd(enabled, until, followFocus));
+    } catch (err) {
+      setError(errorMessage(err));
+    }
+  };
+
+  const choose = (duration: DndDuration) => {
+    let until: number | null = null;
+    if (duration === "hour") until = Date.now() + 60 * 60 * 1000;
+    if (duration === "morning") {
+      const morning = new Date();
+      morning.setDate(morning.getDate() + 1);
+      morning.setHours(8, 0, 0, 0);
+      until = morning.getTime();
+    }
+    apply(duration !== "off", until, status?.followFocus ?? false);
+  };
+
+  if (!status) return null;
+  return (
+    <div style={{ display: "flex", flexDirection: "column", gap: 10 }}>
+      <Choice<DndDuration>
+        label="Hold follow-ups, briefings and notifications"
+        value={status.enabled ? (status.until ? "hour" : "on") : "off"}
+        onChange={choose}
+        options={[
+          ["off", "Off"],
+          ["hour", status.enabled && status.until ? `Until ${new Date(status.until).toLocaleString()}` : "For an hour"],
+          ["morning", "Until tomorrow morning"],
+          ["on", "Until I turn it off"],
+        ]}
+      />
+      <label style={{ display: "flex", alignItems: "center", gap: 10, cursor: "pointer" }}>
+        <input
+          type="checkbox"
+          checked={status.followFocus}
+          onChange={(e) => apply(status.enabled, status.until ?? null, e.target.checked)}
+          style={{ width: 16, height: 16, cursor: "pointer" }}
+        />
+        <span style={{ fontSize: 14 }}>Also while a macOS Focus is on</span>
+      </label>
+      {status.active && (
+        <div style={{ fontSize: 13, color: "var(--color-text-2)" }}>
+          {status.focusActive && !status.enabled ? "Focus is on" : "On"} · {status.queued} held
+        </div>
+      )}
+      {error && <div style={{ fontSize: 13, color: "#991b1b" }}>{error}</div>}
+    </div>
+  );
+}
+
+/** Database size and what the last maintenance pass found. */
+function DatabaseStats() {
+  const [stats, setStats] = useState<DbStats | null>(null);
+
+  useEffect(() => {
+    getDbStats().then(setStats).catch(() => {});
+  }, []);
+
+  if (!stats) return null;
+  const last = stats.lastMaintenance;
+  const mb = (bytes: number) => (bytes / 1e6).toFixed(1);
+  return (
+    <div style={{ fontSize: 13, color: "var(--color-text-2)", marginTop: 10 }}>
+      Database {mb(stats.dbBytes)} MB (+{mb(stats.walBytes)} MB WAL), schema v{stats.schemaVersion}.{" "}
+      {last
+        ? `Last maintenance ${new Date(last.ranAt).toLocaleString()}: ${
+            last.integrity.length ? `${last.integrity.length} integrity problems` : "no integrity problems"
+          }, ${last.orphanThreads.length} orphaned threads removed, ${last.orphanCards} cards unlinked.`
+        : "Maintenance hasn't run yet."}
+    </div>
+  );
+}
+
+const ENTITY_LABELS: Record<AuditEntry["entityType"], string> = {
+  projects: "Project",
+  threads: "Thread",
+  brain_dumps: "Brain dump",
+  kanban_items: "Card",
+  settings: "Setting",
+};
+
+/** A name for the changed row, from whichever snapshot is there. */
+function auditLabel(entry: AuditEntry) {
+  const row = entry.after ?? entry.before ?? {};
+  const name = row.name ?? row.title ?? row.content ?? row.key ?? entry.entityId;
+  return String(name).split("\n")[0].slice(0, 60);
+}
+
+/** The last changes to projects, threads, dumps, cards and settings, each undoable. */
+function RecentChanges({ onReverted }: { onReverted?: () => void }) {
+  const [entries, setEntries] = useState<AuditEntry[]>([]);
+  const [error, setError] = useState<string | null>(null);
+
+  const load = useCallback(() => {
+    getAuditLog({ limit: 20 }).then(setEntries).catch(() => {});
+  }, []);
+
+  useEffect(load, [load]);
+
+  const undo = async (entry: AuditEntry) => {
+    setError(null);
+    try {
+      await revertAuditEntry(entry.id);
+      onReverted?.();
+      load();
+    } catch (err) {
+      setError(errorMessage(err));
+    }
+  };
+
+  if (entries.length === 0) {
+    return <div style={{ fontSize: 13, color: "var(--color-text-2)" }}>No changes yet.</div>;
+  }
+  return (
+    <div style={{ display: "flex", flexDirection: "column", gap: 6 }}>
+      {entries.map((entry) => (
+        <div key={entry.id} style={{ display: "flex", alignItems: "center", gap: 8, fontSize: 13 }}>
+          <span style={{ color: "var(--color-text-2)", minWidth: 120 }}>{new Date(entry.createdAt).toLocaleString()}</span>
+          <span style={{ flex: 1, overflow: "hidden", textOverflow: "ellipsis", whiteSpace: "nowrap" }}>
+            {ENTITY_LABELS[entry.entityType]} {entry.op}d: {auditLabel(entry)}
+          </span>
+          <button
+            onClick={() => undo(entry)}
+            style={{ padding: "2px 10px", borderRadius: 6, border: "1px solid var(--color-border)", background: "var(--color-surface-2)", color: "var(--color-text)", fontSize: 12, cursor: "pointer" }}
+          >
+            Undo
+          </button>
+        </div>
+      ))}
+      {error && <div style={{ fontSize: 13, color: "#991b1b" }}>{error}</div>}
+    </div>
+  );
+}
+
+/** Move everything to another machine as one bundle file. */
+function Workspace({ onImported }: { onImported?: () => void }) {
+  const [path, setPath] = useState("~/Desktop/openclaw-workspace.db");
+  const [replace, setReplace] = useState(false);
+  const [busy, setBusy] = useState(false);
+  const [result, setResult] = useState<{ ok: boolean; msg: string } | null>(null);
+
+  const run = async (action: "export" | "import") => {
+    setBusy(true);
+    setResult(null);
+    try {
+      if (action === "export") {
+        const done = await exportWorkspace(path);
+        setResult({ ok: true, msg: `Saved ${done.files} session files (${(done.bytes / 1e6).toFixed(1)} MB) to ${done.path}` });
+      } else {
+        if (replace && !window.confirm("Replace everything here with the bundle's contents?")) return;
+        const done = await importWorkspace(path, replace);
+        onImported?.();
+        setResult({ ok: true, msg: `Imported ${done.rows} records and ${done.filesWritten} session files (${done.filesSkipped} already up to date)` });
+      }
+    } catch (err) {
+      setResult({ ok: false, msg: errorMessage(err) });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const button = {
+    padding: "8px 16px",
+    borderRadius: 8,
+    border: "1px solid var(--color-border)",
+    background: "var(--color-surface-2)",
+    color: "var(--color-text)",
+    fontSize: 14,
+    cursor: busy ? "default" : "pointer",
+  } as const;
+
+  return (
+    <div style={{ display: "flex", flexDirection: "column", gap: 10 }}>
+      <Field label="Bundle file" value={path} onChange={setPath} placeholder="~/Desktop/openclaw-workspace.db" />
+      <label style={{ display: "flex", alignItems: "center", gap: 10, cursor: "pointer" }}>
+        <input
+          type="checkbox"
+          checked={replace}
+          onChange={(e) => setReplace(e.target.checked)}
+          style={{ width: 16, height: 16, cursor: "pointer" }}
+        />
+        <span style={{ fontSize: 14 }}>On import, replace instead of merging</span>
+      </label>
+      <div style={{ display: "flex", gap: 8 }}>
+        <button onClick={() => run("export")} disabled={busy} style={button}>
+          Export
+        </button>
+        <button onClick={() => run("import")} disabled={busy} style={button}>
+          Import
+        </button>
+      </div>
+      {result && (
+        <div style={{ fontSize: 13, color: result.ok ? "#065f46" : "#991b1b" }}>{result.msg}</div>
+      )}
+    </div>
+  );
+}
+
+/** Edit the prompts background jobs send the agent; saved on their own, not with the panel. */
+function PromptTemplates() {
+  const [templates, setTemplates] = useState<PromptTemplate[]>([]);
+  const [purpose, setPurpose] = useState("follow_up");
+  const [draft, setDraft] = useState("");
+  const [status, setStatus] = useState<{ ok: boolean; msg: string } | null>(null);
+
+  const load = useCallback(() => listPromptTemplates().then(setTemplates).catch(() => {}), []);
+  useEffect(() => {
+    load();
+  }, [load]);
+
+  const current = templates.find((t) => t.purpose === purpose);
+  useEffect(() => {
+    setDraft(current?.template ?? "");
+    setStatus(null);
+  }, [current?.purpose, current?.template]);
+
+  const handleSave = async () => {
+    try {
+      await setPromptTemplate(purpose, draft);
+      setStatus({ ok: true, msg: "Saved" });
+      load();
+    } catch (err) {
+      setStatus({ ok: false, msg: errorMessage(err) });
+    }
+  };
+
+  const handleReset = async () => {
+    try {
+      await resetPromptTemplate(purpose);
+      setStatus({ ok: true, msg: "Back to the default" });
+      load();
+    } catch (err) {
+      setStatus({ ok: false, msg: errorMessage(err) });
+    }
+  };
+
+  if (templates.length === 0) return null;
+  const buttonStyle = { padding: "7px 14px", borderRadius: 8, border: "1px solid var(--color-border)", background: "var(--color-surface-2)", color: "var(--color-text)", fontSize: 14, cursor: "pointer" };
+
+  return (
+    <div style={{ display: "flex", flexDirection: "column", gap: 10 }}>
+      <Choice
+        label="Prompt"
+        value={purpose}
+        onChange={setPurpose}
+        options={templates.map((t) => [t.purpose, `${t.description}${t.customized ? " (edited)" : ""}`])}
+      />
+      <textarea
+        value={draft}
+        onChange={(e) => setDraft(e.target.value)}
+        rows={5}
+        style={{ width: "100%", padding: "7px 12px", borderRadius: 8, border: "1px solid var(--color-border)", background: "var(--color-surface-2)", color: "var(--color-text)", fontSize: 13, outline: "none", boxSizing: "border-box", resize: "vertical", fontFamily: "inherit" }}
+      />
+      {current && (
+        <div style={{ fontSize: 12, color: "var(--color-text-2)" }}>
+          Variables: {current.variables.map((v) => `{${v}}`).join(" ")}
+        </div>
+      )}
+      {status && <div style={{ fontSize: 13, color: status.ok ? "#065f46" : "#991b1b" }}>{status.msg}</div>}
+      <div style={{ display: "flex", gap: 8 }}>
+        <button onClick={handleSave} disabled={draft === current?.template} style={buttonStyle}>
+          Save prompt
+        </button>
+        <button onClick={handleReset} disabled={!current?.customized} style={buttonStyle}>
+          Reset to default
+        </button>
+      </div>
+    </div>
+  );
+}
+
 function Field({
   label,
   value,
@@ -324,3 +1212,41 @@ function Field({
     </div>
   );
 }
+
+function Choice<T extends string>({
+  label,
+  value,
+  onChange,
+  options,
+}: {
+  label: string;
+  value: T;
+  onChange: (v: T) => void;
+  options: [T, string][];
+}) {
+  return (
+    <div>
+      <div style={{ fontSize: 12, color: "var(--color-text-2)", marginBottom: 4 }}>{label}</div>
+      <select
+        value={value}
+        onChange={(e) => onChange(e.target.value as T)}
+        style={{
+          width: "100%",
+          padding: "7px 12px",
+          borderRadius: 8,
+          border: "1px solid var(--color-border)",
+          background: "var(--color-surface-2)",
+          color: "var(--color-text)",
+          fontSize: 14,
+          outline: "none",
+        }}
+      >
+        {options.map(([v, text]) => (
+          <option key={v} value={v}>
+            {text}
+          </option>
+        ))}
+      </select>
+    </div>
+  );
+}
@@ -1,27 +1,58 @@
 import { useCallback, useEffect, useRef, useState } from "react";
 import {
-  loadSession,
+  loadSessionPage,
   onChatMessage,
+  onSessionReset,
+  onProcessingStarted,
+  onProcessingFinished,
   sendMessage,
   stopWatching,
   watchSession,
+  errorMessage,
   type ChatMessage,
   type Thread,
 } from "../lib/tauri";
 
+const PAGE_SIZE = 200;
+
 export function useChat(thread: Thread | null) {
   const [messages, setMessages] = useState<ChatMessage[]>([]);
+  // Cursor for the page above what's loaded; undefined at the start of the session
+  const [before, setBefore] = useState<number | undefined>(undefined);
+  const [loadingEarlier, setLoadingEarlier] = useState(false);
+  const loadedRef = useRef(0);
   const [sending, setSending] = useState(false);
   const [loading, setLoading] = useState(false);
+  // The agent is working on this session, whoever started the turn
+  const [processing, setProcessing] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const unlistenRef = useRef<(() => void) | null>(null);
+  const unlistenResetRef = useRef<(() => void) | null>(null);
+  const unlistenProcessingRef = useRef<(() => void)[]>([]);
   const activeSessionRef = useRef<string | null>(null);
   const sendingRef = useRef(false);
 
+  // Re-read the newest messages, as many as are loaded now plus room for
+  // what just arrived, so earlier pages the user opened stay open
+  const reload = async (thread: Thread) => {
+    const page = await loadSessionPage(
+      thread.agent_id ?? "main",
+      thread.session_id,
+      thread.id,
+      Math.max(PAGE_SIZE, loadedRef.current + 20)
+    );
+    // If it came back empty but we had an optimistic message, keep it
+    if (page.messages.length === 0) return;
+    loadedRef.current = page.messages.length;
+    setMessages(page.messages);
+    setBefore(page.before);
+  };
+
   // Load session and set up event listener when thread changes
   useEffect(() => {
     if (!thread) {
       setMessages([]);
+      setBefore(undefined);
       return;
     }
 
@@ -38,6 +69,13 @@ export function useChat(thread: Thread | null) {
         unlistenRef.current();
         unlistenRef.current = null;
       }
+      if (unlistenResetRef.current) {
+        unlistenResetRef.current();
+        unlistenResetRef.current = null;
+      }
+      unlistenProcessingRef.current.forEach((fn) => fn());
+      unlistenProcessingRef.current = [];
+      setProcessing(false);
 
       activeSessionRef.current = thread.session_id;
 
@@ -59,12 +97,39 @@ export function useChat(thread: Thread | null) {
       });
       if (!cancelled) unlistenRef.current = unlisten;
 
-      // Load existing messages from JSONL
+      // The file was rewritten (edit, compaction): reload instead of appending
+      const unlistenReset = await onSessionReset(async (event) => {
+        if (event.sessionId !== thread.session_id) return;
+        try {
+          await reload(thread);
+        } catch {
+          // keep what we have
+        }
+      });
+      if (!cancelled) unlistenResetRef.current = unlistenReset;
+
+      const unlistenStarted = await onProcessingStarted((event) => {
+        if (event.sessionId === thread.session_id) setProcessing(true);
+      });
+      const unlistenFinished = await onProcessingFinished((event) => {
+        if (event.sessionId === thread.session_id) setProcessing(false);
+      });
+      if (!cancelled) unlistenProcessingRef.current = [unlistenStarted, unlistenFinished];
+
+      // Load the newest messages from JSONL; older ones page in on request
+      loadedRef.current = 0;
       try {
-        const existing = await loadSession(thread.agent_id, thread.session_id);
-        if (!cancelled) setMessages(existing);
+        const page = await loadSessionPage(thread.agent_id, thread.session_id, thread.id, PAGE_SIZE);
+        if (!cancelled) {
+          loadedRef.current = page.messages.length;
+          setMessages(page.messages);
+          setBefore(page.before);
+        }
       } catch {
-        if (!cancelled) setMessages([]);
+        if (!cancelled) {
+          setMessages([]);
+          setBefore(undefined);
+        }
       }
 
       // Start background watcher
@@ -88,6 +153,8 @@ export function useChat(thread: Thread | null) {
   useEffect(() => {
     return () => {
       if (unlistenRef.current) unlistenRef.current();
+      if (unlistenResetRef.current) unlistenResetRef.current();
+      unlistenProcessingRef.current.forEach((fn) => fn());
       if (activeSessionRef.current) {
         stopWatching(activeSessionRef.current).catch(() => {});
       }
@@ -95,7 +162,7 @@ export function useChat(thread: Thread | null) {
   }, []);
 
   const send = useCallback(
-    async (text: string) => {
+    async (text: string, model?: string) => {
       if (!thread || !text.trim() || sending) return;
 
       setError(null);
@@ -112,26 +179,20 @@ export function useChat(thread: Thread | null) {
           thread.id,
           thread.agent_id ?? "main",
           thread.session_id,
-          text.trim()
+          text.trim(),
+          model
         );
       } catch (err: any) {
-        const msg = err?.message || err?.toString() || "Unknown error";
+        const msg = errorMessage(err) || "Unknown error";
         console.error("sendMessage failed:", msg);
         setError(msg);
       }
 
       // Always reload canonical state from JSONL after send completes (or fails)
       try {
-        const canonical = await loadSession(
-          thread.agent_id ?? "main",
-          thread.session_id
-        );
-        if (canonical.length > 0) {
-          setMessages(canonical);
-        }
-        // If canonical is empty but we had an optimistic message, keep it
+        await reload(thread);
       } catch {
-        // loadSession failed — keep whatever we have
+        // reload failed — keep whatever we have
       }
 
       sendingRef.current = false;
@@ -140,5 +201,30 @@ export function useChat(thread: Thread | null) {
     [thread, sending]
   );
 
-  return { messages, sending, loading, error, send };
+  const loadEarlier = useCallback(async () => {
+    if (!thread || before === undefined || loadingEarlier) return;
+    setLoadingEarlier(true);
+    try {
+      const page = await loadSessionPage(thread.agent_id, thread.session_id, thread.id, PAGE_SIZE, before);
+      loadedRef.current += page.messages.length;
+      setMessages((prev) => [...page.messages, ...prev]);
+      setBefore(page.before);
+    } catch (err) {
+      setError(errorMessage(err));
+    } finally {
+      setLoadingEarlier(false);
+    }
+  }, [thread, before, loadingEarlier]);
+
+  return {
+    messages,
+    sending,
+    loading,
+    processing,
+    error,
+    send,
+    hasEarlier: before !== undefined,
+    loadingEarlier,
+    loadEarlier,
+  };
 }
@@ -6,6 +6,7 @@ import {
   deleteThread,
   listProjects,
   listThreads,
+  onThreadDiscovered,
   onThreadRenamed,
   updateProject,
   type Project,
@@ -68,6 +69,19 @@ export function useProjects() {
     };
   }, []);
 
+  // Sessions started from the CLI show up as new standalone threads
+  useEffect(() => {
+    let cleanup: (() => void) | null = null;
+    onThreadDiscovered(({ thread }) => {
+      if (thread) setStandaloneThreads((prev) => [thread, ...prev]);
+    }).then((fn) => {
+      cleanup = fn;
+    });
+    return () => {
+      cleanup?.();
+    };
+  }, []);
+
   const addProject = useCallback(
     async (name: string, description?: string, color?: string) => {
       const project = await createProject(name, description, color);
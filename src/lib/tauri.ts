@@ -1,12 +1,46 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 
+/** What every command rejects with, and the payload of `app:error`. */
+export interface AppError {
+  kind:
+    | "not_found"
+    | "invalid_input"
+    | "openclaw_missing"
+    | "agent_timeout"
+    | "unrecognized_output"
+    | "remote"
+    | "database"
+    | "io"
+    | "internal";
+  message: string;
+  retryable: boolean;
+  hint: string | null;
+}
+
+export const errorMessage = (err: unknown): string => {
+  const e = err as Partial<AppError> | undefined;
+  if (e && typeof e === "object" && typeof e.message === "string") {
+    return e.hint ? `${e.message} — ${e.hint}` : e.message;
+  }
+  return String(err);
+};
+
+/** Failures in background jobs (mirror, proactive, title refresh, dispatch). */
+export const onAppError = (cb: (event: { source: string; error: AppError }) => void) =>
+  listen("app:error", (e: any) => cb(e.payload));
+
 export interface Project {
   id: string;
   name: string;
   description?: string;
   color?: string;
   agent_id: string;
+  status: string;
+  obsidian_source?: string;
+  tags: string[];
+  priority: number; // 0 none · 1 low · 2 medium · 3 high · 4 urgent
+  due_at?: number;
   created_at: number;
   updated_at: number;
 }
@@ -20,8 +54,15 @@ export interface Thread {
   created_at: number;
   updated_at: number;
   last_message_at?: number;
+  /** Per-thread override; null follows the global remote mode. */
+  execution_target?: ExecutionTarget | null;
+  model?: string | null;
+  /** No OS notifications for replies in this thread. */
+  muted?: boolean;
 }
 
+export type ExecutionTarget = "local" | "remote";
+
 export interface BrainDump {
   id: string;
   content: string;
@@ -31,32 +72,65 @@ export interface BrainDump {
   created_at: number;
   updated_at: number;
   followed_up_at?: number;
+  /** Hours between follow-ups; null follows up once. */
+  follow_up_interval_hours?: number | null;
+  /** Unanswered follow-ups before auto stops; null uses the default (3). */
+  max_follow_ups?: number | null;
+  follow_up_attempts: number;
+  follow_up_thread_id?: string | null;
 }
 
 export interface KanbanItem {
   id: string;
   project_id?: string;
-  source_type: "manual" | "brain_dump" | "research";
+  source_type: "manual" | "brain_dump" | "research" | "recurring" | "markdown" | "obsidian";
   source_id?: string;
   title: string;
   description?: string;
   column: "backlog" | "this_week" | "in_progress" | "done";
   position: number;
   status: "active" | "archived";
+  recurrence?: "daily" | "weekly" | "monthly";
+  completed_at?: number;
+  parent_id?: string;
+  due_at?: number;
+  agent_id?: string;
+  thread_id?: string;
+  priority: number; // 0 none · 1 low · 2 medium · 3 high · 4 urgent
   created_at: number;
   updated_at: number;
+  blocked_by: string[];
 }
 
 export interface ChatMessage {
   role: "user" | "assistant";
   content: string;
+  /** Structured content beside the markdown text; absent when there is none. */
+  parts?: MessagePart[];
 }
 
+export type MessagePart =
+  | { type: "code"; language: string | null; code: string }
+  | { type: "diff"; path: string | null; diff: string }
+  | { type: "image"; url: string | null; media_type: string | null; data: string | null }
+  | { type: "tool"; name: string; summary: string | null };
+
+export type SshAuthMethod = "key" | "agent" | "password";
+
 export interface SshConfig {
   host: string;
   port: number;
   user: string;
   key_path: string;
+  auth_method?: SshAuthMethod;
+  /** Remote openclaw binary; "openclaw" on the remote PATH when unset. */
+  openclaw_bin?: string | null;
+  /** Remote state dir, relative to $HOME unless absolute (".openclaw" when unset). */
+  state_dir?: string | null;
+  /** Extra environment for remote openclaw invocations. */
+  env?: Record<string, string>;
+  /** Root of the remote file browser; the state dir when unset. */
+  files_dir?: string | null;
 }
 
 export interface MessageEvent {
@@ -65,12 +139,19 @@ export interface MessageEvent {
 }
 
 // Projects
-export const listProjects = () => invoke<Project[]>("cmd_list_projects");
-export const createProject = (name: string, description?: string, color?: string) =>
-  invoke<Project>("cmd_create_project", { name, description, color });
+export const listProjects = (includeArchived?: boolean) =>
+  invoke<Project[]>("cmd_list_projects", { includeArchived });
+export const createProject = (name: string, description?: string, color?: string, agentId?: string) =>
+  invoke<Project>("cmd_create_project", { name, description, color, agentId });
 export const updateProject = (id: string, name: string, description?: string, color?: string) =>
   invoke<void>("cmd_update_project", { id, name, description, color });
 export const deleteProject = (id: string) => invoke<void>("cmd_delete_project", { id });
+export const setProjectStatus = (id: string, status: string) =>
+  invoke<void>("cmd_set_project_status", { id, status });
+export const setProjectAgent = (id: string, agentId: string) =>
+  invoke<void>("cmd_set_project_agent", { id, agentId });
+export const getRelatedProjects = (projectId: string) =>
+  invoke<Project[]>("cmd_get_related_projects", { projectId });
 
 // Threads — Tauri v2 converts snake_case Rust params to camelCase for JS
 export const listThreads = (projectId?: string) =>
@@ -80,21 +161,151 @@ export const createThread = (name: string, projectId?: string, agentId?: string)
 export const renameThread = (id: string, name: string) =>
   invoke<void>("cmd_rename_thread", { id, name });
 export const deleteThread = (id: string) => invoke<void>("cmd_delete_thread", { id });
+export const setThreadExecutionTarget = (id: string, target: ExecutionTarget | null) =>
+  invoke<void>("cmd_set_thread_execution_target", { id, target });
+export const setThreadModel = (id: string, model: string | null) =>
+  invoke<void>("cmd_set_thread_model", { id, model });
+export const setThreadMuted = (id: string, muted: boolean) =>
+  invoke<void>("cmd_set_thread_muted", { id, muted });
+/** The thread on screen, or null when none is or the window is in the background. */
+export const setFocusedThread = (threadId: string | null) =>
+  invoke<void>("cmd_set_focused_thread", { threadId });
+
+// Agents
+export interface AgentInfo {
+  id: string;
+  name: string | null;
+  emoji: string | null;
+  model: string | null;
+  workspace: string | null;
+  session_count: number;
+  last_active: number | null;
+  remote: boolean;
+}
+
+export const listAgents = (remote?: boolean) =>
+  invoke<AgentInfo[]>("cmd_list_agents", { remote });
+export const getAgent = (agentId: string, remote?: boolean) =>
+  invoke<AgentInfo>("cmd_get_agent", { agentId, remote });
+
+export interface ModelInfo {
+  id: string;
+  name: string | null;
+}
+
+export const listModels = (remote?: boolean) =>
+  invoke<ModelInfo[]>("cmd_list_models", { remote });
+
+/** Omitted fields are left alone; an empty string clears the field. */
+export interface AgentConfig {
+  name?: string;
+  model?: string;
+  instructions?: string;
+}
+
+export const createAgent = (agentId: string, config?: AgentConfig) =>
+  invoke<AgentInfo>("cmd_create_agent", { agentId, config });
+export const updateAgentConfig = (agentId: string, config: AgentConfig) =>
+  invoke<void>("cmd_update_agent_config", { agentId, config });
+export const getAgentInstructions = (agentId: string) =>
+  invoke<string | null>("cmd_get_agent_instructions", { agentId });
 
 // Chat
-export const loadSession = (agentId: string, sessionId: string) =>
-  invoke<ChatMessage[]>("cmd_load_session", { agentId, sessionId });
+export const loadSession = (agentId: string, sessionId: string, threadId?: string) =>
+  invoke<ChatMessage[]>("cmd_load_session", { agentId, sessionId, threadId });
+export interface SessionPage {
+  messages: ChatMessage[];
+  /** Pass back as `before` for the page above; absent at the start of the session. */
+  before?: number;
+}
+/** The newest `limit` messages (200 by default) before the `before` cursor. */
+export const loadSessionPage = (
+  agentId: string,
+  sessionId: string,
+  threadId?: string,
+  limit?: number,
+  before?: number
+) => invoke<SessionPage>("cmd_load_session_page", { agentId, sessionId, threadId, limit, before });
+/** `model` overrides the thread's default for this message only. */
 export const sendMessage = (
   threadId: string,
   agentId: string,
   sessionId: string,
-  message: string
-) => invoke<void>("cmd_send_message", { threadId, agentId, sessionId, message });
-export const watchSession = (agentId: string, sessionId: string) =>
-  invoke<void>("cmd_watch_session", { agentId, sessionId });
+  message: string,
+  model?: string
+) => invoke<void>("cmd_send_message", { threadId, agentId, sessionId, message, model });
+/** `poll` checks the file on a timer; omit it to follow the `watcher_mode` setting. */
+export const watchSession = (agentId: string, sessionId: string, poll?: boolean) =>
+  invoke<void>("cmd_watch_session", { agentId, sessionId, poll });
 export const stopWatching = (sessionId: string) =>
   invoke<void>("cmd_stop_watching", { sessionId });
 
+export interface WatchStatus {
+  /** "agent:<id>", "session:<id>" or "discovery". */
+  key: string;
+  path: string;
+  polling: boolean;
+  events: number;
+  /** Wake-ups merged into one already pending. */
+  coalesced: number;
+  /** OS watcher errors; the events behind them were lost. */
+  dropped: number;
+  lastEventAt?: number;
+}
+
+export interface SessionWatchStatus {
+  sessionId: string;
+  agentId: string;
+  offset: number;
+  awaitingReply: boolean;
+  lastReadAt?: number;
+}
+
+export interface WatcherStatus {
+  watches: WatchStatus[];
+  sessions: SessionWatchStatus[];
+  vaultWatched: boolean;
+}
+
+export const getWatcherStatus = () => invoke<WatcherStatus>("cmd_get_watcher_status");
+
+/** A session file with no thread, e.g. one started with `openclaw` in a terminal. */
+export interface CliSession {
+  agentId: string;
+  sessionId: string;
+  lastActive: number;
+  messageCount: number;
+  preview: string | null;
+}
+export const findCliSession = (agentId: string) =>
+  invoke<CliSession | null>("cmd_find_cli_session", { agentId });
+/** `link` moves the thread onto the CLI session; `merge` copies its messages into the thread. */
+export const reconcileCliSession = (threadId: string, sessionId: string, mode: "link" | "merge") =>
+  invoke<Thread>("cmd_reconcile_cli_session", { threadId, sessionId, mode });
+export const onThreadReconciled = (
+  cb: (event: { threadId: string; sessionId: string; mode: "link" | "merge" }) => void
+) => listen("thread:reconciled", (e: any) => cb(e.payload));
+
+/** The `session_discovery` setting: what happens to sessions started outside the app. */
+export type SessionDiscovery = "off" | "notify" | "create";
+/** The `session_retention` setting: what happens to a session file when its thread is deleted. */
+export type SessionRetention = "keep" | "archive" | "delete";
+/** The `watcher_mode` setting: file events with a polling fallback, or always poll. */
+export type WatcherMode = "auto" | "poll";
+/** The `proactive_mode` setting: a thread per brain dump, or one digest for all that are due. */
+export type ProactiveMode = "per_dump" | "digest";
+
+export interface DiscoveredSession {
+  agentId: string;
+  sessionId: string;
+  preview?: string;
+  /** Created for the session; absent in "notify" mode. */
+  thread?: Thread;
+}
+
+export const onThreadDiscovered = (cb: (event: DiscoveredSession) => void) =>
+  listen("thread:discovered", (e: any) => cb(e.payload));
+
 // Brain Dump
 export const listBrainDumps = () => invoke<BrainDump[]>("cmd_list_brain_dumps");
 export const createBrainDump = (content: string, projectId?: string) =>
@@ -103,7 +314,86 @@ export const updateBrainDumpStatus = (id: string, status: string) =>
   invoke<void>("cmd_update_brain_dump_status", { id, status });
 export const setBrainDumpProactive = (id: string, proactive: boolean) =>
   invoke<void>("cmd_set_brain_dump_proactive", { id, proactive });
+export const setBrainDumpCadence = (id: string, intervalHours: number | null, maxFollowUps: number | null) =>
+  invoke<void>("cmd_set_brain_dump_cadence", { id, intervalHours, maxFollowUps });
 export const deleteBrainDump = (id: string) => invoke<void>("cmd_delete_brain_dump", { id });
+
+/** When proactive follow-ups may run. Days are 0 (Monday) to 6 (Sunday); quiet hours are local "HH:MM". */
+export interface ProactiveSchedule {
+  interval_mins: number;
+  active_days: number[];
+  quiet_start?: string | null;
+  quiet_end?: string | null;
+  /** Hold a due pass until the user is back at the computer. */
+  wait_for_activity: boolean;
+}
+export const getProactiveSettings = () => invoke<ProactiveSchedule>("cmd_get_proactive_settings");
+export const updateProactiveSettings = (schedule: ProactiveSchedule) =>
+  invoke<void>("cmd_update_proactive_settings", { schedule });
+
+export interface ProactiveStatus {
+  paused: boolean;
+  running: boolean;
+  nextRunAt?: number;
+  lastRunAt?: number;
+  /** Follow-ups sent by the last pass. */
+  lastSent: number;
+  /** Dumps the last pass failed to follow up on. */
+  lastFailed: number;
+  lastError?: string;
+}
+export interface ProactiveRun {
+  id: number;
+  startedAt: number;
+  finishedAt: number;
+  manual: boolean;
+  digest: boolean;
+  processed: number;
+  sent: number;
+  stopped: number;
+  failures: { brainDumpId: string; error: string }[];
+  error?: string;
+}
+export const listProactiveRuns = (limit?: number) =>
+  invoke<ProactiveRun[]>("cmd_list_proactive_runs", { limit });
+
+export interface OutcomeCounts {
+  sent: number;
+  replied: number;
+  ignored: number;
+  pending: number;
+  /** Replied out of those settled (0-1). */
+  replyRate?: number;
+  medianReplyHours?: number;
+}
+/** How follow-ups have landed: a reply within 48h counts. */
+export interface ProactiveEffectiveness {
+  overall: OutcomeCounts;
+  firstAttempt: OutcomeCounts;
+  laterAttempts: OutcomeCounts;
+  perDump: OutcomeCounts;
+  digest: OutcomeCounts;
+}
+export interface FollowUpOutcome {
+  id: number;
+  brainDumpId: string;
+  threadId: string;
+  attempt: number;
+  digest: boolean;
+  sentAt: number;
+  outcome: "pending" | "replied" | "ignored";
+  repliedAt?: number;
+}
+export const getProactiveEffectiveness = (days?: number) =>
+  invoke<ProactiveEffectiveness>("cmd_get_proactive_effectiveness", { days });
+export const listFollowUpOutcomes = (brainDumpId: string) =>
+  invoke<FollowUpOutcome[]>("cmd_list_follow_up_outcomes", { brainDumpId });
+export const getProactiveStatus = () => invoke<ProactiveStatus>("cmd_get_proactive_status");
+export const runProactiveNow = () => invoke<ProactiveStatus>("cmd_run_proactive_now");
+export const setProactivePaused = (paused: boolean) =>
+  invoke<ProactiveStatus>("cmd_set_proactive_paused", { paused });
+export const onProactiveStatus = (cb: (status: ProactiveStatus) => void) =>
+  listen("proactive:status", (e: any) => cb(e.payload));
 export const convertDumpToThread = (
   dumpId: string,
   name: string,
@@ -117,28 +407,718 @@ export const configureSsh = (config: SshConfig) =>
 export const getSshConfig = () => invoke<SshConfig>("cmd_get_ssh_config");
 export const testSsh = () => invoke<string>("cmd_test_ssh");
 export const sshStatus = () => invoke<string>("cmd_ssh_status");
+export const setSshPassword = (password: string) =>
+  invoke<void>("cmd_set_ssh_password", { password });
+export const sshUpload = (localPath: string, remotePath: string) =>
+  invoke<void>("cmd_ssh_upload", { localPath, remotePath });
+export const sshDownload = (remotePath: string, localPath: string) =>
+  invoke<void>("cmd_ssh_download", { remotePath, localPath });
+export const onSshTransfer = (
+  cb: (event: { path: string; direction: "upload" | "download"; transferred: number; total?: number }) => void
+) => listen("ssh:transfer", (e: any) => cb(e.payload));
+export const sshConnect = () => invoke<void>("cmd_ssh_connect");
+export const sshDisconnect = () => invoke<void>("cmd_ssh_disconnect");
+
+export interface HostKey {
+  host: string;
+  port: number;
+  keyType: string;
+  fingerprint: string;
+}
+export const onSshHostKey = (
+  cb: (event: { host: string; port: number; keys: HostKey[] }) => void
+) => listen("ssh:host_key", (e: any) => cb(e.payload));
+export const trustSshHostKey = (fingerprint: string) =>
+  invoke<void>("cmd_ssh_trust_host_key", { fingerprint });
+export const forgetSshHostKey = () => invoke<void>("cmd_ssh_forget_host_key");
+
+export interface HealthCheck {
+  name: "ssh" | "openclaw" | "agents_dir" | "clock";
+  status: "ok" | "warn" | "fail" | "skipped";
+  detail: string;
+}
+export interface RemoteHealth {
+  ok: boolean;
+  latency_ms: number | null;
+  openclaw_version: string | null;
+  clock_skew_secs: number | null;
+  checks: HealthCheck[];
+}
+export const remoteHealth = () => invoke<RemoteHealth>("cmd_remote_health");
+
+export interface SshEvent {
+  id: number;
+  kind: "connect" | "disconnect" | "reconnecting" | "error" | "command" | "forward";
+  host: string;
+  detail: string | null;
+  duration_ms: number | null;
+  ok: boolean;
+  at: number;
+}
+export const getSshLog = (since?: number, limit?: number) =>
+  invoke<SshEvent[]>("cmd_get_ssh_log", { since, limit });
+
+/** An openclaw run from the invocation log (setting `agent_log`: "off" | "redacted" | "full"). */
+export interface AgentInvocation {
+  id: number;
+  agent_id: string;
+  session_id: string | null;
+  model: string | null;
+  remote: boolean;
+  command: string;
+  duration_ms: number;
+  exit_code: number | null;
+  timed_out: boolean;
+  ok: boolean;
+  response: string | null;
+  error: string | null;
+  at: number;
+}
+export const getAgentLog = (agentId?: string, limit?: number) =>
+  invoke<AgentInvocation[]>("cmd_get_agent_log", { agentId, limit });
+export const clearAgentLog = () => invoke<void>("cmd_clear_agent_log");
+
+export interface PortForward {
+  local_port: number;
+  remote_host: string;
+  remote_port: number;
+  created_at: number;
+}
+export const sshOpenForward = (remotePort: number, remoteHost?: string, localPort?: number) =>
+  invoke<PortForward>("cmd_ssh_open_forward", { remotePort, remoteHost, localPort });
+export const sshCloseForward = (localPort: number) =>
+  invoke<void>("cmd_ssh_close_forward", { localPort });
+export const sshListForwards = () => invoke<PortForward[]>("cmd_ssh_list_forwards");
+
+// Remote file browser (paths are relative to the configured files_dir)
+export interface RemoteEntry {
+  name: string;
+  path: string;
+  is_dir: boolean;
+  size: number | null;
+  modified: number | null;
+}
+export const remoteListFiles = (path?: string) =>
+  invoke<RemoteEntry[]>("cmd_remote_list_files", { path });
+export const remoteDownloadFile = (path: string, localPath?: string) =>
+  invoke<string>("cmd_remote_download_file", { path, localPath });
+export const remoteUploadFile = (localPath: string, remoteDir?: string) =>
+  invoke<string>("cmd_remote_upload_file", { localPath, remoteDir });
+
+export type SshStatus = "disconnected" | "connecting" | "connected" | "reconnecting" | "error";
+export const onSshStatus = (
+  cb: (event: { status: SshStatus; attempt?: number; error?: string }) => void
+) => listen("ssh:status", (e: any) => cb(e.payload));
 export const setRemoteMode = (enabled: boolean) =>
   invoke<void>("cmd_set_remote_mode", { enabled });
 export const getRemoteMode = () => invoke<boolean>("cmd_get_remote_mode");
+/** Remote mode was switched, from the UI or the tray. */
+export const onRemoteModeChanged = (cb: (event: { enabled: boolean }) => void) =>
+  listen("remote_mode:changed", (e: any) => cb(e.payload));
+
+// Remote session mirror
+export const onMirrorQueued = (
+  cb: (event: { threadId: string; sessionId: string }) => void
+) => listen("mirror:queued", (e: any) => cb(e.payload));
+export interface OutboxMessage {
+  id: string;
+  thread_id: string;
+  agent_id: string;
+  session_id: string;
+  message: string;
+  created_at: number;
+  attempts: number;
+  last_error: string | null;
+  model: string | null;
+}
+export interface OutboxDelivery {
+  id: string;
+  threadId: string;
+  sessionId: string;
+  delivered: boolean;
+  dropped: boolean;
+  error: string | null;
+}
+export const listOutbox = (threadId?: string) =>
+  invoke<OutboxMessage[]>("cmd_list_outbox", { threadId });
+export const discardOutbox = (id: string) => invoke<void>("cmd_discard_outbox", { id });
+export const onOutboxFlushed = (cb: (event: { results: OutboxDelivery[] }) => void) =>
+  listen("outbox:flushed", (e: any) => cb(e.payload));
+export const onMirrorSynced = (
+  cb: (event: { flushed: number; pulled: number }) => void
+) => listen("mirror:synced", (e: any) => cb(e.payload));
 
 // Settings
 export const getSetting = (key: string) =>
   invoke<string | null>("cmd_get_setting", { key });
-export const setSetting = (key: string, value: string) =>
+export const setSetting = (key: string, value: SettingJson) =>
   invoke<void>("cmd_set_setting", { key, value });
+export type SettingJson = string | number | boolean | null | SettingJson[] | { [key: string]: SettingJson };
+export type SettingKind =
+  | { type: "bool" }
+  | { type: "int"; min: number; max: number }
+  | { type: "text" }
+  | { type: "choice"; options: string[] }
+  | { type: "time" }
+  | { type: "json" };
+export interface SettingValue {
+  key: string;
+  description: string;
+  kind: SettingKind;
+  value: SettingJson;
+  default: SettingJson;
+  customized: boolean;
+}
+export const listSettings = () => invoke<SettingValue[]>("cmd_list_settings");
+export const onSettingsChanged = (cb: (change: { key: string; value: SettingJson }) => void) =>
+  listen("settings:changed", (e: any) => cb(e.payload));
+
+// OpenClaw binary
+export interface OpenClawDetection {
+  path: string | null;
+  source: "setting" | "auto";
+  version: string | null;
+  path_env: string;
+  error: string | null;
+}
+
+export const detectOpenclaw = () => invoke<OpenClawDetection>("cmd_detect_openclaw");
+/** Fired once at startup with the probe result. */
+export const onOpenclawDetected = (cb: (event: OpenClawDetection) => void) =>
+  listen<OpenClawDetection>("openclaw:detected", (e) => cb(e.payload));
+/** openclaw printed JSON this app can't read — usually a release with a new output format. */
+export const onOpenclawFormatError = (
+  cb: (event: { threadId: string; version: string | null; sample: string }) => void
+) => listen("openclaw:format_error", (e: any) => cb(e.payload));
+
+export interface HealthCheck {
+  /** "binary", "version", "sessions:<agent>", "ping:<agent>" or "agents". */
+  name: string;
+  ok: boolean;
+  detail: string;
+  duration_ms: number | null;
+}
+export interface HealthReport {
+  ok: boolean;
+  detection: OpenClawDetection;
+  checks: HealthCheck[];
+}
+/** `ping` (default true) runs a short real turn with the agent. */
+export const checkAgentHealth = (agentId?: string, ping?: boolean) =>
+  invoke<HealthReport>("cmd_check_agent_health", { agentId, ping });
+
+/** Extra settings applied to one agent's local openclaw runs. */
+export interface AgentOverride {
+  agent_id: string;
+  /** Appended to `openclaw agent ...`, one argument per entry. */
+  flags: string[];
+  env: Record<string, string>;
+  working_dir: string | null;
+  updated_at?: number;
+}
+export const listAgentOverrides = () => invoke<AgentOverride[]>("cmd_list_agent_overrides");
+export const setAgentOverride = (agentOverride: AgentOverride) =>
+  invoke<void>("cmd_set_agent_override", { agentOverride });
+export const deleteAgentOverride = (agentId: string) =>
+  invoke<void>("cmd_delete_agent_override", { agentId });
 
 // Obsidian sync
 export interface SyncResult {
   created: number;
   updated: number;
   skipped: number;
+  archived: string[];
+  restored: string[];
+  tasks_created: number;
+  tasks_completed: number;
+  changes: SyncChange[];
   errors: string[];
 }
-export const syncObsidianVault = () => invoke<SyncResult>("cmd_sync_obsidian_vault");
+
+export interface SyncChange {
+  action: "create" | "update" | "restore" | "archive";
+  name: string;
+  obsidian_source: string;
+}
+/** `category` is one of the layout's category folders; omit for the projects root. */
+export const createObsidianNoteForProject = (projectId: string, category?: string) =>
+  invoke<string>("cmd_create_obsidian_note_for_project", { projectId, category });
+
+export const generateWeeklyReview = () => invoke<string>("cmd_generate_weekly_review");
+export const onWeeklyReview = (cb: (event: { week: string; path: string }) => void) =>
+  listen("obsidian:weekly_review", (e: any) => cb(e.payload));
+
+export interface Briefing {
+  id: string;
+  /** Local date, "YYYY-MM-DD". */
+  date: string;
+  content: string;
+  created_at: number;
+}
+
+export const generateBriefing = () => invoke<Briefing>("cmd_generate_briefing");
+export const listBriefings = (limit?: number) => invoke<Briefing[]>("cmd_list_briefings", { limit });
+export const onBriefingReady = (cb: (briefing: Briefing) => void) =>
+  listen("briefing:ready", (e: any) => cb(e.payload));
+
+/** One project's week; `projectId` is absent for work outside any project. */
+export interface ProjectWeek {
+  projectId?: string;
+  project: string;
+  threads: string[];
+  /** Messages in the threads active that week. */
+  messages: number;
+  cardsCompleted: string[];
+  dumpsClosed: string[];
+}
+export interface Retrospective {
+  id: string;
+  /** ISO week, "YYYY-WW". */
+  week: string;
+  weekStart: string;
+  narrative?: string;
+  projects: ProjectWeek[];
+  createdAt: number;
+}
+export const generateRetrospective = () => invoke<Retrospective>("cmd_generate_retrospective");
+export const listRetrospectives = (limit?: number) =>
+  invoke<Retrospective[]>("cmd_list_retrospectives", { limit });
+export const onRetrospectiveReady = (cb: (event: { retrospective: Retrospective; path?: string }) => void) =>
+  listen("retrospective:ready", (e: any) => cb(e.payload));
+
+export interface ResurfacedThread {
+  thread: Thread;
+  idleDays: number;
+  /** Message to send to pick the thread back up. */
+  suggestion: string;
+}
+
+export interface PromptTemplate {
+  purpose: string;
+  description: string;
+  /** Placeholders the template may use, written as `{name}`. */
+  variables: string[];
+  template: string;
+  defaultTemplate: string;
+  customized: boolean;
+}
+
+export const listPromptTemplates = () => invoke<PromptTemplate[]>("cmd_list_prompt_templates");
+export const setPromptTemplate = (purpose: string, template: string) =>
+  invoke<void>("cmd_set_prompt_template", { purpose, template });
+export const resetPromptTemplate = (purpose: string) => invoke<void>("cmd_reset_prompt_template", { purpose });
+
+export interface JobRun {
+  id: number;
+  job_id: string;
+  manual: boolean;
+  started_at: number;
+  finished_at: number | null;
+  ok: boolean | null;
+  detail: string | null;
+  error: string | null;
+}
+
+export interface JobStatus {
+  id: string;
+  description: string;
+  enabled: boolean;
+  /** Cron expression in effect; null for jobs timed by an interval or their own settings. */
+  schedule: string | null;
+  defaultSchedule: string | null;
+  running: boolean;
+  lastRunAt: number | null;
+  nextRunAt: number | null;
+  lastRun: JobRun | null;
+}
+
+export const listJobs = () => invoke<JobStatus[]>("cmd_list_jobs");
+export const setJobEnabled = (id: string, enabled: boolean) => invoke<void>("cmd_set_job_enabled", { id, enabled });
+export const setJobSchedule = (id: string, schedule: string | null) =>
+  invoke<void>("cmd_set_job_schedule", { id, schedule });
+export const runJobNow = (id: string) => invoke<string>("cmd_run_job_now", { id });
+export const listJobRuns = (jobId?: string, limit?: number) =>
+  invoke<JobRun[]>("cmd_list_job_runs", { jobId, limit });
+/** Do-not-disturb: while `active`, background jobs hold their events and notifications. */
+export interface DndStatus {
+  enabled: boolean;
+  /** Epoch ms it switches itself off. */
+  until?: number;
+  followFocus: boolean;
+  focusActive: boolean;
+  active: boolean;
+  queued: number;
+}
+export const getDndStatus = () => invoke<DndStatus>("cmd_get_dnd_status");
+export const setDnd = (enabled: boolean, until: number | null, followFocus: boolean) =>
+  invoke<DndStatus>("cmd_set_dnd", { enabled, until, followFocus });
+
+export const onJobFinished = (
+  cb: (event: { jobId: string; manual: boolean; ok: boolean; detail: string | null; error: string | null }) => void
+) => listen("job:finished", (e: any) => cb(e.payload));
+
+export const resurfaceStaleThreads = () => invoke<ResurfacedThread[]>("cmd_resurface_stale_threads");
+export const onThreadResurfaced = (cb: (event: ResurfacedThread) => void) =>
+  listen("thread:resurfaced", (e: any) => cb(e.payload));
+
+export interface SyncConflict {
+  id: string;
+  project_id: string;
+  field: "name" | "description";
+  local_value?: string;
+  vault_value?: string;
+  obsidian_source?: string;
+  created_at: number;
+  resolved_at?: number;
+}
+
+export const listSyncConflicts = (includeResolved?: boolean) =>
+  invoke<SyncConflict[]>("cmd_list_sync_conflicts", { includeResolved });
+export const resolveSyncConflict = (id: string, restore: boolean) =>
+  invoke<void>("cmd_resolve_sync_conflict", { id, restore });
+export const syncObsidianVault = (preview?: boolean) =>
+  invoke<SyncResult>("cmd_sync_obsidian_vault", { preview });
+export interface VaultLayout {
+  projects_root: string;
+  root_color: string;
+  categories: { folder: string; color: string }[];
+}
+export const getObsidianLayout = () => invoke<VaultLayout>("cmd_get_obsidian_layout");
+export const setObsidianLayout = (layout: VaultLayout) =>
+  invoke<void>("cmd_set_obsidian_layout", { layout });
+export const exportThreadToObsidian = (threadId: string) =>
+  invoke<string>("cmd_export_thread_to_obsidian", { threadId });
+// Search
+export type SearchKind = "thread" | "brain_dump" | "kanban";
+export interface SearchHit {
+  kind: SearchKind;
+  id: string;
+  projectId?: string;
+  title: string;
+  /** Matching text with hits wrapped in [ ]. */
+  snippet: string;
+  /** bm25 score; lower is better. Hits come back best first. */
+  rank: number;
+}
+export const search = (query: string, kinds?: SearchKind[], limit?: number) =>
+  invoke<SearchHit[]>("cmd_search", { query, kinds, limit });
+
+// Database
+export interface MaintenanceReport {
+  ranAt: number;
+  durationMs: number;
+  /** Integrity check findings; empty when the database is sound. */
+  integrity: string[];
+  checkpointed: number;
+  checkpointBusy: boolean;
+  orphanThreads: string[];
+  orphanCards: number;
+  auditPruned: number;
+  blobsRemoved?: number;
+}
+export interface DbStats {
+  path: string;
+  dbBytes: number;
+  walBytes: number;
+  freePages: number;
+  pageCount: number;
+  schemaVersion: number;
+  /** [table, rows], largest first. */
+  tables: [string, number][];
+  lastMaintenance?: MaintenanceReport;
+}
+export const getDbStats = () => invoke<DbStats>("cmd_get_db_stats");
+export const onMaintenanceFinished = (cb: (report: MaintenanceReport) => void) =>
+  listen("maintenance:finished", (e: any) => cb(e.payload));
+
+// Attachments
+export type AttachmentOwner = "threads" | "brain_dumps" | "kanban_items";
+export interface Attachment {
+  hash: string;
+  name: string;
+  mime: string;
+  size: number;
+  /** Where the stored copy lives on disk. */
+  path: string;
+  createdAt: number;
+}
+export interface BlobGcReport {
+  removed: number;
+  bytesFreed: number;
+  missing: number;
+}
+export const addAttachment = (ownerType: AttachmentOwner, ownerId: string, path: string) =>
+  invoke<Attachment>("cmd_add_attachment", { ownerType, ownerId, path });
+export const listAttachments = (ownerType: AttachmentOwner, ownerId: string) =>
+  invoke<Attachment[]>("cmd_list_attachments", { ownerType, ownerId });
+export const removeAttachment = (ownerType: AttachmentOwner, ownerId: string, hash: string) =>
+  invoke<void>("cmd_remove_attachment", { ownerType, ownerId, hash });
+export const gcBlobs = () => invoke<BlobGcReport>("cmd_gc_blobs");
+
+// Workspace statistics
+export interface ActivityCounts {
+  threadsCreated: number;
+  threadsActive: number;
+  dumpsCaptured: number;
+  dumpsResolved: number;
+  cardsCreated: number;
+  cardsCompleted: number;
+}
+export interface WeekActivity extends ActivityCounts {
+  /** Local midnight of the week's Monday, epoch ms. */
+  start: number;
+}
+export interface WorkspaceStats {
+  generatedAt: number;
+  projects: number;
+  archivedProjects: number;
+  threads: number;
+  openDumps: number;
+  resolvedDumps: number;
+  openCards: number;
+  completedCards: number;
+  cardsByColumn: [string, number][];
+  messages: number;
+  /** Oldest first; the last is the current, partial week. */
+  weeks: WeekActivity[];
+  sessionFiles: number;
+  sessionBytes: number;
+  cacheFiles: number;
+  cacheBytes: number;
+}
+export const getWorkspaceStats = (weeks?: number) =>
+  invoke<WorkspaceStats>("cmd_get_workspace_stats", { weeks });
+
+// Audit log
+export type AuditEntity = "projects" | "threads" | "brain_dumps" | "kanban_items" | "settings";
+export interface AuditEntry {
+  id: number;
+  entityType: AuditEntity;
+  entityId: string;
+  op: "create" | "update" | "delete";
+  /** The row before the change; absent for a create. */
+  before?: Record<string, unknown>;
+  /** The row after the change; absent for a delete. */
+  after?: Record<string, unknown>;
+  createdAt: number;
+}
+export interface AuditFilter {
+  entityType?: AuditEntity;
+  entityId?: string;
+  op?: AuditEntry["op"];
+  since?: number;
+  until?: number;
+  limit?: number;
+}
+export const getAuditLog = (filter?: AuditFilter) => invoke<AuditEntry[]>("cmd_get_audit_log", { filter });
+/** Put the entity back as it was before this change. */
+export const revertAuditEntry = (id: number) => invoke<void>("cmd_revert_audit_entry", { id });
+
+/** A workspace bundle: one file with the database, settings and session files. */
+export interface WorkspaceExport {
+  path: string;
+  files: number;
+  bytes: number;
+}
+export interface WorkspaceImport {
+  rows: number;
+  filesWritten: number;
+  /** Session files kept because the copy here was as new. */
+  filesSkipped: number;
+}
+export const exportWorkspace = (path: string) => invoke<WorkspaceExport>("cmd_export_workspace", { path });
+/** Merge a bundle into this workspace, or with `replace` overwrite it. */
+export const importWorkspace = (path: string, replace?: boolean) =>
+  invoke<WorkspaceImport>("cmd_import_workspace", { path, replace });
+export const onObsidianProjectSynced = (
+  cb: (event: { name: string; obsidian_source: string; result: "created" | "updated" }) => void
+) => listen("obsidian:project_synced", (e: any) => cb(e.payload));
+export const onObsidianArchiveChanges = (
+  cb: (event: { archived: string[]; restored: string[] }) => void
+) => listen("obsidian:archive_changes", (e: any) => cb(e.payload));
+export const onObsidianConflict = (
+  cb: (event: { projectId: string; path: string }) => void
+) => listen("obsidian:conflict", (e: any) => cb(e.payload));
+
+// Device sync
+export interface SyncConflict {
+  table: string;
+  id: string;
+  deviceId: string;
+  /** The other device's copy replaced this one's. */
+  theirsKept: boolean;
+}
+export interface SyncReport {
+  deviceId: string;
+  target: string;
+  peersMerged: number;
+  rowsWritten: number;
+  rowsDeleted: number;
+  conflicts: SyncConflict[];
+  filesPulled: number;
+  filesPushed: number;
+  filesBusy: number;
+  skipped: string[];
+  finishedAt: number;
+}
+export const syncNow = () => invoke<SyncReport>("cmd_sync_now");
+export const onSyncFinished = (cb: (report: SyncReport) => void) =>
+  listen("sync:finished", (e: any) => cb(e.payload));
+
+// GitHub
+export type GithubIssueOwner = "kanban_items" | "brain_dumps";
+export interface GithubIssue {
+  ownerType: GithubIssueOwner;
+  ownerId: string;
+  /** owner/name */
+  repo: string;
+  number: number;
+  url: string;
+  state: "open" | "closed";
+  closedAt?: number;
+  createdAt: number;
+  checkedAt: number;
+}
+/** Check and save a token; resolves to the GitHub login. An empty token removes it. */
+export const setGithubToken = (token: string) => invoke<string | null>("cmd_set_github_token", { token });
+export const setProjectGithubRepo = (projectId: string, repo?: string) =>
+  invoke<void>("cmd_set_project_github_repo", { projectId, repo });
+export const getProjectGithubRepo = (projectId: string) =>
+  invoke<string | null>("cmd_get_project_github_repo", { projectId });
+/** Title and body default to the card's or dump's text. */
+export const createGithubIssue = (
+  ownerType: GithubIssueOwner,
+  ownerId: string,
+  options: { title?: string; body?: string; labels?: string[] } = {}
+) => invoke<GithubIssue>("cmd_create_github_issue", { ownerType, ownerId, ...options });
+export const getGithubIssue = (ownerType: GithubIssueOwner, ownerId: string) =>
+  invoke<GithubIssue | null>("cmd_get_github_issue", { ownerType, ownerId });
+export const onGithubIssueClosed = (cb: (issue: GithubIssue) => void) =>
+  listen("github:issue_closed", (e: any) => cb(e.payload));
+
+// Webhooks
+export type WebhookEvent =
+  | "braindump:created"
+  | "kanban:moved"
+  | "chat:message"
+  | "briefing:ready"
+  | "thread:resurfaced"
+  | "github:issue_closed"
+  | "maintenance:finished"
+  | "sync:finished";
+export interface Webhook {
+  id: string;
+  url: string;
+  /** Key for the X-OpenClaw-Signature header: sha256=<hex HMAC-SHA256 of the body>. */
+  secret: string;
+  /** Empty means every event. */
+  events: WebhookEvent[];
+  enabled: boolean;
+  createdAt: number;
+  updatedAt: number;
+  lastSentAt?: number;
+  lastStatus?: number;
+  lastError?: string;
+}
+export const listWebhooks = () => invoke<Webhook[]>("cmd_list_webhooks");
+/** Omit `id` to create one; a new webhook without a secret gets a random one. */
+export const saveWebhook = (webhook: {
+  id?: string;
+  url: string;
+  events: WebhookEvent[];
+  enabled?: boolean;
+  secret?: string;
+}) => invoke<Webhook>("cmd_save_webhook", webhook);
+export const deleteWebhook = (id: string) => invoke<void>("cmd_delete_webhook", { id });
+/** Sends a "webhook:test" event and resolves to the HTTP status. */
+export const testWebhook = (id: string) => invoke<number>("cmd_test_webhook", { id });
+
+// Deep links
+/** Where an openclaw-chat:// link (or a tray shortcut) points: thread/<id>, project/<id> or dump/new?content=... */
+export type DeepLinkNavigation =
+  | { kind: "thread"; threadId: string }
+  | { kind: "project"; projectId: string }
+  /** Pre-fill the capture box; save with createBrainDump only once the user confirms. */
+  | { kind: "dump"; content: string; projectId?: string };
+/** The link the app was launched with, if any; call once on startup. */
+export const takeDeepLink = () => invoke<DeepLinkNavigation | null>("cmd_take_deep_link");
+export const onDeepLinkOpen = (cb: (navigation: DeepLinkNavigation) => void) =>
+  listen<DeepLinkNavigation>("deeplink:open", (e) => cb(e.payload));
+export const onDeepLinkError = (cb: (event: { url: string; error: string }) => void) =>
+  listen("deeplink:error", (e: any) => cb(e.payload));
+
+// Tray
+/** "Quick Brain Dump…" was picked in the tray; the window is already in front. */
+export const onTrayQuickDump = (cb: () => void) => listen("tray:quick_dump", () => cb());
+
+// Notifications
+export type NotificationAction = "open_thread" | "snooze" | "mark_done";
+/** An OS notification and what it's about; its buttons act on the thread, card or dump set. */
+export interface Notice {
+  title: string;
+  body: string;
+  threadId?: string;
+  cardId?: string;
+  dumpId?: string;
+  actions: NotificationAction[];
+}
+/** Run a notification's button from the UI (for platforms without notification buttons). */
+export const notificationAction = (action: NotificationAction, notice: Notice) =>
+  invoke<void>("cmd_notification_action", { action, notice });
+/** A brain dump was marked done from a notification. */
+export const onBrainDumpStatusChanged = (cb: (event: { id: string; status: string }) => void) =>
+  listen("braindump:status_changed", (e: any) => cb(e.payload));
+
+// MCP server
+/** Workspace tools for agents at `url` (Streamable HTTP), switched on with the mcp_server setting. */
+export interface McpStatus {
+  enabled: boolean;
+  running: boolean;
+  readOnly: boolean;
+  url?: string;
+  /** Clients send it as `Authorization: Bearer <token>`. */
+  token?: string;
+  /** Why it isn't running although it's enabled, e.g. the port is taken. */
+  error?: string;
+}
+export const getMcpStatus = () => invoke<McpStatus>("cmd_get_mcp_status");
+/** Returns the new token; clients with the old one are locked out. */
+export const resetMcpToken = () => invoke<string>("cmd_reset_mcp_token");
+export const onMcpStatus = (cb: (status: McpStatus) => void) =>
+  listen<McpStatus>("mcp:status", (e) => cb(e.payload));
+/** An agent captured a brain dump through the MCP server. */
+export const onBrainDumpCreated = (cb: (dump: BrainDump) => void) =>
+  listen<BrainDump>("braindump:created", (e) => cb(e.payload));
 
 // Events
 export const onChatMessage = (cb: (event: MessageEvent) => void) =>
   listen<MessageEvent>("chat:message", (e) => cb(e.payload));
+/** The agent started working on a turn. `threadId` is absent when the watcher noticed it. */
+export const onProcessingStarted = (cb: (event: { sessionId: string; threadId?: string }) => void) =>
+  listen("chat:processing_started", (e: any) => cb(e.payload));
+export const onProcessingFinished = (
+  cb: (event: { sessionId: string; threadId?: string; ok: boolean }) => void
+) => listen("chat:processing_finished", (e: any) => cb(e.payload));
+/** A watched session file was rewritten; its history is being re-emitted from the top. */
+export interface CompactionResult {
+  summarized: number;
+  kept: number;
+  bytesBefore: number;
+  bytesAfter: number;
+  /** Where the original session file was archived. */
+  archived: string;
+}
+export const compactSession = (threadId: string, keep?: number) =>
+  invoke<CompactionResult>("cmd_compact_session", { threadId, keep });
+export const onSessionReset = (cb: (event: { sessionId: string }) => void) =>
+  listen("chat:session_reset", (e: any) => cb(e.payload));
+/** Lines of a session file that weren't valid JSON and were skipped on load. */
+export const onSessionCorruptLines = (
+  cb: (event: { agentId: string; sessionId: string; lines: { line: number; sample: string }[] }) => void
+) => listen("session:corrupt_lines", (e: any) => cb(e.payload));
+export const onChatTimeout = (
+  cb: (event: { threadId: string; sessionId: string; timeoutSecs: number }) => void
+) => listen("chat:timeout", (e: any) => cb(e.payload));
 
 export const onThreadRenamed = (
   cb: (event: { threadId: string; name: string }) => void
@@ -147,18 +1127,30 @@ export const onThreadRenamed = (
 export const onBrainDumpFollowedUp = (
   cb: (event: {
     brain_dump_id: string;
+    thread_id: string;
     session_id: string;
     content: string;
     project_id?: string;
+    /** The thread the follow-up was sent in. */
+    thread: Thread;
   }) => void
 ) => listen("braindump:followed_up", (e: any) => cb(e.payload));
 
+export const onBrainDumpFollowUpsStopped = (
+  cb: (event: { brain_dump_id: string; attempts: number }) => void
+) => listen("braindump:follow_ups_stopped", (e: any) => cb(e.payload));
+
 export const onKanbanRefresh = (cb: () => void) =>
   listen("kanban:refresh", () => cb());
+/** A card due within a day got a plan or reminder in its linked thread. */
+export const onKanbanNudge = (
+  cb: (event: { cardId: string; title: string; dueAt: number; threadId: string; thread: Thread }) => void
+) => listen("kanban:nudge", (e: any) => cb(e.payload));
 
 // Kanban
-export const listKanbanItems = (projectId?: string) =>
-  invoke<KanbanItem[]>("cmd_list_kanban_items", { projectId });
+export type KanbanSort = "manual" | "priority" | "due_date" | "age";
+export const listKanbanItems = (projectId?: string, sort?: KanbanSort) =>
+  invoke<KanbanItem[]>("cmd_list_kanban_items", { projectId, sort });
 export const createKanbanItem = (
   title: string,
   projectId?: string,
@@ -176,5 +1168,40 @@ export const updateKanbanItem = (
 ) => invoke<void>("cmd_update_kanban_item", { id, title, description, column, position, status, projectId });
 export const deleteKanbanItem = (id: string) =>
   invoke<void>("cmd_delete_kanban_item", { id });
+export const addKanbanDependency = (itemId: string, blockedById: string) =>
+  invoke<void>("cmd_add_kanban_dependency", { itemId, blockedById });
+export const removeKanbanDependency = (itemId: string, blockedById: string) =>
+  invoke<void>("cmd_remove_kanban_dependency", { itemId, blockedById });
+export const onKanbanUnblocked = (
+  cb: (event: { completedId: string; unblockedIds: string[] }) => void
+) => listen("kanban:unblocked", (e: any) => cb(e.payload));
+export const setKanbanRecurrence = (id: string, recurrence?: "daily" | "weekly" | "monthly") =>
+  invoke<void>("cmd_set_kanban_recurrence", { id, recurrence });
+export interface KanbanMetrics {
+  range_start?: number;
+  completed: number;
+  cycle_time_avg_ms?: number;
+  cycle_time_median_ms?: number;
+  throughput: { week_start: string; completed: number }[];
+  column_time: { column: string; avg_ms?: number; samples: number }[];
+}
+export const getKanbanMetrics = (projectId?: string, range?: string) =>
+  invoke<KanbanMetrics>("cmd_get_kanban_metrics", { projectId, range });
+export const importKanbanFromMarkdown = (
+  projectId?: string,
+  markdown?: string,
+  notePath?: string
+) => invoke<KanbanItem[]>("cmd_import_kanban_from_markdown", { projectId, markdown, notePath });
+export const setKanbanPriority = (id: string, priority: number) =>
+  invoke<void>("cmd_set_kanban_priority", { id, priority });
+export const setKanbanDueDate = (id: string, dueAt?: number) =>
+  invoke<void>("cmd_set_kanban_due_date", { id, dueAt });
+export const exportKanban = (format: "markdown" | "csv" | "dataview", projectId?: string) =>
+  invoke<string>("cmd_export_kanban", { projectId, format });
+export const dispatchKanbanItem = (id: string, agentId?: string) =>
+  invoke<Thread>("cmd_dispatch_kanban_item", { id, agentId });
+export const onKanbanDispatchFailed = (
+  cb: (event: { itemId: string; threadId: string; error: string; timedOut: boolean }) => void
+) => listen("kanban:dispatch_failed", (e: any) => cb(e.payload));
 export const promoteBrainDump = (dumpId: string, title: string, projectId?: string, column?: string) =>
   invoke<KanbanItem>("cmd_promote_brain_dump", { dumpId, title, projectId, column });